@@ -5,7 +5,9 @@ use std::io::{BufRead, BufReader};
 fn positions_from_file(file: File) -> Vec<Position> {
     BufReader::new(file)
         .lines()
-        .map(|l| Position::from_id(l.expect("Could not parse line")))
+        .map(|l| {
+            Position::try_from_id(&l.expect("Could not parse line")).expect("Illegal position ID")
+        })
         .collect()
 }
 pub fn contact_positions() -> Vec<Position> {
@@ -0,0 +1,133 @@
+use coach::match_analysis::{Decision, DecisionKind, MatchAnalysis, Severity, Thresholds};
+use logic::game_state::Player;
+
+/// Totals of both players followed by the `worst` blunders of the match.
+pub fn format_report(analysis: &MatchAnalysis, thresholds: &Thresholds, worst: usize) -> String {
+    let classified = analysis.classified(thresholds);
+    let count = |side: Player, severity: Severity| {
+        classified
+            .iter()
+            .filter(|(decision, s)| decision.side == side && *s >= severity)
+            .count()
+    };
+    let mut lines = vec![format!(
+        "{:<16}{:>10}{:>8}{:>10}{:>10}{:>7}{:>8}",
+        "Player", "Decisions", "Errors", "Blunders", "Eq. loss", "PR", "Luck"
+    )];
+    for (summary, side) in analysis.summaries.iter().zip([Player::One, Player::Two]) {
+        lines.push(format!(
+            "{:<16}{:>10}{:>8}{:>10}{:>10.3}{:>7.1}{:>+8.3}",
+            summary.name,
+            summary.checker_plays + summary.cube_decisions,
+            count(side, Severity::Error),
+            count(side, Severity::Blunder),
+            summary.total_equity_loss(),
+            summary.performance_rating(),
+            summary.total_luck,
+        ));
+    }
+
+    let mut blunders = analysis.blunders(thresholds);
+    blunders.sort_by(|a, b| b.equity_loss.total_cmp(&a.equity_loss));
+    blunders.truncate(worst);
+    if !blunders.is_empty() {
+        lines.push(String::new());
+        lines.push("Worst blunders:".to_string());
+        for decision in blunders {
+            lines.push(format_decision(decision));
+        }
+    }
+    lines.join("\n")
+}
+
+fn format_decision(decision: &Decision) -> String {
+    let kind = match decision.kind {
+        DecisionKind::CheckerPlay => "checker play",
+        DecisionKind::Double => "double",
+        DecisionKind::NoDouble => "no double",
+        DecisionKind::Take => "take",
+        DecisionKind::Drop => "drop",
+    };
+    let mwc = match decision.mwc_loss {
+        Some(loss) => format!(" ({:.2}% MWC)", -100.0 * loss),
+        None => String::new(),
+    };
+    format!(
+        "  Game {}, {}, {} in {}: {:.3}{}",
+        decision.game + 1,
+        decision.player,
+        kind,
+        decision.position_id,
+        -decision.equity_loss,
+        mwc
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyze::format_report;
+    use coach::match_analysis::{Decision, DecisionKind, MatchAnalysis, PlayerSummary, Thresholds};
+    use logic::game_state::Player;
+
+    fn decision(player: &str, kind: DecisionKind, equity_loss: f32) -> Decision {
+        let side = if player == "alice" {
+            Player::One
+        } else {
+            Player::Two
+        };
+        Decision {
+            game: 0,
+            player: player.to_string(),
+            side,
+            kind,
+            position_id: "4HPwATDgc/ABMA".to_string(),
+            equity_loss,
+            mwc_loss: None,
+        }
+    }
+
+    #[test]
+    fn report_with_worst_blunders() {
+        // Given
+        let analysis = MatchAnalysis {
+            decisions: vec![
+                decision("alice", DecisionKind::CheckerPlay, 0.1),
+                decision("alice", DecisionKind::CheckerPlay, 0.05),
+                decision("bob", DecisionKind::Drop, 0.25),
+                decision("bob", DecisionKind::CheckerPlay, 0.0),
+            ],
+            rolls: vec![],
+            summaries: [
+                PlayerSummary {
+                    name: "alice".to_string(),
+                    checker_plays: 2,
+                    checker_equity_loss: 0.15,
+                    rolls: 2,
+                    total_luck: 0.2,
+                    ..Default::default()
+                },
+                PlayerSummary {
+                    name: "bob".to_string(),
+                    checker_plays: 1,
+                    cube_decisions: 1,
+                    cube_equity_loss: 0.25,
+                    rolls: 1,
+                    total_luck: -0.2,
+                    ..Default::default()
+                },
+            ],
+        };
+        // When
+        let report = format_report(&analysis, &Thresholds::default(), 1);
+        // Then
+        assert_eq!(
+            report,
+            "Player           Decisions  Errors  Blunders  Eq. loss     PR    Luck
+alice                    2       2         1     0.150   37.5  +0.200
+bob                      2       1         1     0.250   62.5  -0.200
+
+Worst blunders:
+  Game 1, bob, drop in 4HPwATDgc/ABMA: -0.250"
+        );
+    }
+}
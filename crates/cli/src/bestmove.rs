@@ -0,0 +1,141 @@
+use crate::convert::{parse, Format};
+use engine::dice::Dice;
+use engine::position::{Position, X_BAR};
+use logic::bg_move::BgMove;
+use logic::wildbg_api::RankedMove;
+
+/// A GnuBG position ID or an XGID, which is recognized by its colons.
+pub fn parse_position(text: &str) -> Result<Position, String> {
+    if text.contains(':') {
+        parse(text, Format::Xgid)
+    } else {
+        parse(text, Format::GnubgId)
+    }
+}
+
+/// Dice like `31`, `3-1` or `3,1`.
+///
+/// If `text` is not given, the dice are taken from the XGID `position`.
+pub fn parse_dice(text: Option<&str>, position: &str) -> Result<Dice, String> {
+    let text = match text {
+        Some(text) => text,
+        // The fifth field of an XGID contains the dice, `00` if they have not been rolled yet.
+        None => match position.split(':').nth(4) {
+            Some(dice) if dice != "00" => dice,
+            _ => return Err("No dice given.".to_string()),
+        },
+    };
+    let digits: Vec<usize> = text
+        .chars()
+        .filter(|c| !matches!(c, '-' | ','))
+        .map(|c| c.to_digit(10).map(|digit| digit as usize))
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("'{text}' are not dice like 31 or 3-1."))?;
+    match digits.as_slice() {
+        [die1, die2] => Dice::try_from((*die1, *die2)).map_err(|error| error.to_string()),
+        _ => Err(format!("'{text}' are not dice like 31 or 3-1.")),
+    }
+}
+
+/// One line per move with equity, difference to the best move and the probabilities in percent.
+pub fn format_ranked_moves(moves: &[RankedMove]) -> String {
+    let mut lines = vec![format!(
+        "{:>3}  {:<24}{:>7}{:>8}{:>7}{:>7}{:>7}",
+        "#", "Move", "Equity", "Diff", "Win", "W(g)", "L(g)"
+    )];
+    for (index, ranked) in moves.iter().enumerate() {
+        let difference = if index == 0 {
+            String::new()
+        } else {
+            format!("{:+.3}", ranked.equity_difference)
+        };
+        let probabilities = &ranked.probabilities;
+        lines.push(format!(
+            "{:>3}  {:<24}{:>+7.3}{:>8}{:>7.1}{:>7.1}{:>7.1}",
+            index + 1,
+            format_move(&ranked.bg_move),
+            ranked.equity,
+            difference,
+            100.0 * probabilities.win(),
+            100.0 * probabilities.win_gammon,
+            100.0 * probabilities.lose_gammon,
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Like `8/5 6/5*`, hits are marked with `*`.
+pub(crate) fn format_move(bg_move: &BgMove) -> String {
+    let point = |pip: usize| match pip {
+        X_BAR => "bar".to_string(),
+        0 => "off".to_string(),
+        pip => pip.to_string(),
+    };
+    let details: Vec<String> = bg_move
+        .details()
+        .iter()
+        .map(|detail| {
+            let hit = if detail.hit() { "*" } else { "" };
+            format!("{}/{}{}", point(detail.from()), point(detail.to()), hit)
+        })
+        .collect();
+    if details.is_empty() {
+        "(no move)".to_string()
+    } else {
+        details.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bestmove::{format_ranked_moves, parse_dice, parse_position};
+    use engine::dice::Dice;
+    use engine::position::STARTING;
+    use engine::test_utils::EvaluatorFake;
+    use logic::wildbg_api::{WildbgApi, WildbgConfig};
+
+    #[test]
+    fn parse_positions() {
+        assert_eq!(parse_position("4HPwATDgc/ABMA"), Ok(STARTING));
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:0:0:10";
+        assert_eq!(parse_position(xgid), Ok(STARTING));
+        assert!(parse_position("4HPwATDgc").is_err());
+    }
+
+    #[test]
+    fn parse_dice_from_argument_or_xgid() {
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:0:0:10";
+        assert_eq!(parse_dice(Some("31"), xgid), Ok(Dice::new(3, 1)));
+        assert_eq!(parse_dice(Some("4-4"), xgid), Ok(Dice::new(4, 4)));
+        assert_eq!(parse_dice(None, xgid), Ok(Dice::new(5, 2)));
+        assert!(parse_dice(None, "4HPwATDgc/ABMA").is_err());
+        assert!(parse_dice(Some("3"), xgid).is_err());
+        assert!(parse_dice(Some("71"), xgid).is_err());
+    }
+
+    #[test]
+    fn ranked_moves_table() {
+        // Given
+        let api = WildbgApi::new(EvaluatorFake {});
+        let config = WildbgConfig {
+            away: None,
+            gammons_count: true,
+        };
+        // When
+        let moves = api.ranked_moves(&STARTING, &Dice::new(6, 2), &config, 2);
+        let table = format_ranked_moves(&moves);
+        // Then
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "  #  Move                     Equity    Diff    Win   W(g)   L(g)"
+        );
+        assert_eq!(
+            lines[1],
+            "  1  13/11 11/5               +0.300           65.0    0.0    0.0"
+        );
+        assert!(lines[2].starts_with("  2  "));
+        assert!(lines[2].contains("+0.000  -0.300   50.0"));
+    }
+}
@@ -0,0 +1,150 @@
+use clap::ValueEnum;
+use engine::position::Position;
+
+/// Position encodings supported by `wildbg convert`.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Format {
+    /// GnuBG position ID with 14 characters. `wildbg` uses the same format internally.
+    GnubgId,
+    /// XGID as used by eXtreme Gammon. Only the board and the player on roll are used.
+    Xgid,
+    /// 26 comma separated integers, index 0 is the bar of `o`, index 25 the bar of `x`.
+    Pips,
+    /// The 10 bytes of the GnuBG position key, encoded as 20 hexadecimal characters.
+    Binary,
+    /// FIBS board in "boardstyle 3". Only the board is used, seen by the player named first.
+    Fibs,
+}
+
+pub fn convert(line: &str, from: Format, to: Format) -> Result<String, String> {
+    let position = parse(line, from)?;
+    Ok(format(&position, to))
+}
+
+pub(crate) fn parse(line: &str, format: Format) -> Result<Position, String> {
+    match format {
+        Format::GnubgId => parse_gnubg_id(line),
+        Format::Xgid => Position::from_xgid(line).map_err(|error| error.to_string()),
+        Format::Pips => parse_pips(line),
+        Format::Binary => parse_binary(line),
+        Format::Fibs => Position::from_fibs_board(line).map_err(|error| error.to_string()),
+    }
+}
+
+fn format(position: &Position, format: Format) -> String {
+    match format {
+        Format::GnubgId => position.position_id(),
+        Format::Xgid => position.xgid(),
+        Format::Pips => {
+            let pips: [i8; 26] = (*position).into();
+            pips.map(|pip| pip.to_string()).join(",")
+        }
+        Format::Binary => position
+            .position_key()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+        Format::Fibs => position.fibs_board(),
+    }
+}
+
+fn parse_gnubg_id(line: &str) -> Result<Position, String> {
+    Position::try_from_id(line)
+        .map_err(|error| format!("'{line}' is not a GnuBG position ID: {error}"))
+}
+
+fn parse_pips(line: &str) -> Result<Position, String> {
+    let pips: Vec<i8> = line
+        .split(',')
+        .map(|pip| pip.trim().parse::<i8>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("'{line}' contains values which are not integers."))?;
+    let pips: [i8; 26] = pips
+        .try_into()
+        .map_err(|_| format!("'{line}' doesn't contain exactly 26 values."))?;
+    Position::try_from(pips).map_err(|error| error.to_string())
+}
+
+fn parse_binary(line: &str) -> Result<Position, String> {
+    if line.len() != 20 || !line.is_ascii() {
+        return Err(format!(
+            "'{line}' doesn't contain exactly 20 hexadecimal characters."
+        ));
+    }
+    let mut key = [0_u8; 10];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&line[2 * i..2 * i + 2], 16)
+            .map_err(|_| format!("'{line}' contains non hexadecimal characters."))?;
+    }
+    Position::from_position_key(key)
+        .map_err(|error| format!("'{line}' is not a GnuBG position key: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convert::{convert, Format};
+
+    const STARTING_PIPS: &str = "0,-2,0,0,0,0,5,0,3,0,0,0,-5,5,0,0,0,-3,0,-5,0,0,0,0,2,0";
+    const STARTING_FIBS: &str = "board:You:Opponent:9999:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0";
+
+    #[test]
+    fn gnubg_id_to_all_formats() {
+        let id = "4HPwATDgc/ABMA";
+        assert_eq!(convert(id, Format::GnubgId, Format::GnubgId).unwrap(), id);
+        assert_eq!(
+            convert(id, Format::GnubgId, Format::Xgid).unwrap(),
+            "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10"
+        );
+        assert_eq!(
+            convert(id, Format::GnubgId, Format::Pips).unwrap(),
+            STARTING_PIPS
+        );
+        assert_eq!(
+            convert(id, Format::GnubgId, Format::Binary).unwrap(),
+            "e073f00130e073f00130"
+        );
+        assert_eq!(
+            convert(id, Format::GnubgId, Format::Fibs).unwrap(),
+            STARTING_FIBS
+        );
+    }
+
+    #[test]
+    fn all_formats_to_gnubg_id() {
+        let id = "4HPwATDgc/ABMA";
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:0:0:10";
+        assert_eq!(convert(xgid, Format::Xgid, Format::GnubgId).unwrap(), id);
+        assert_eq!(
+            convert(STARTING_PIPS, Format::Pips, Format::GnubgId).unwrap(),
+            id
+        );
+        let binary = "e073f00130e073f00130";
+        assert_eq!(
+            convert(binary, Format::Binary, Format::GnubgId).unwrap(),
+            id
+        );
+        assert_eq!(
+            convert(STARTING_FIBS, Format::Fibs, Format::GnubgId).unwrap(),
+            id
+        );
+    }
+
+    #[test]
+    fn illegal_input() {
+        assert!(convert("4HPwATDgc/AB", Format::GnubgId, Format::Xgid).is_err());
+        assert!(convert("1,2,3", Format::Pips, Format::Xgid).is_err());
+        assert!(convert("e073f00130e073f0013x", Format::Binary, Format::Xgid).is_err());
+        // 80 bits set would make the decoder read beyond the key.
+        assert!(convert("ffffffffffffffffffff", Format::Binary, Format::Xgid).is_err());
+        // 16 checkers for x.
+        assert!(convert("000000feff0100000000", Format::Binary, Format::Xgid).is_err());
+        assert_eq!(
+            convert(
+                "0,-2,0,0,0,0,5,0,3,0,0,0,-5,5,0,0,0,-3,0,-5,0,0,0,0,3,0",
+                Format::Pips,
+                Format::Xgid
+            ),
+            Err("Player x has more than 15 checkers on the board.".to_string())
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use clap::{Parser, Subcommand};
+use coach::mat;
+use coach::match_analysis::{analyze_match, Thresholds};
+use coach::rollout::{RolloutEvaluator, RolloutSummary, GAMES_PER_REPETITION};
+use engine::composite::CompositeEvaluator;
+use engine::dice_gen::FastrandDice;
+use engine::evaluator::Evaluator;
+use logic::match_state::MatchState;
+use logic::money_rules::MoneyRules;
+use logic::wildbg_api::{WildbgApi, WildbgConfig};
+use std::fs;
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+mod analyze;
+mod bestmove;
+mod convert;
+mod play;
+mod rollout;
+
+/// Command line interface for wildbg.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Converts positions from one format into another, one position per line.
+    ///
+    /// Lines that can't be parsed are reported on stderr and skipped.
+    Convert {
+        /// Format of the input lines.
+        #[arg(long)]
+        from: convert::Format,
+        /// Format of the output lines.
+        #[arg(long)]
+        to: convert::Format,
+        /// File with one position per line. If not given, positions are read from stdin.
+        file: Option<PathBuf>,
+    },
+    /// Prints the best moves for a position and dice, ranked by cubeless money game equity.
+    ///
+    /// Probabilities are in percent, `W(g)` and `L(g)` include backgammons.
+    Bestmove {
+        /// GnuBG position ID or XGID, seen from the player on roll.
+        position: String,
+        /// Dice like `31` or `3-1`. Can be omitted if the XGID contains dice.
+        dice: Option<String>,
+        /// Maximum number of moves to print.
+        #[arg(long, default_value_t = 10)]
+        moves: usize,
+    },
+    /// Rolls out a position and compares the result with the evaluation of the neural net.
+    ///
+    /// Both players play the move with the best cubeless equity according to the neural net.
+    Rollout {
+        /// GnuBG position ID or XGID, seen from the player on roll.
+        position: String,
+        /// Number of games, rounded up to a multiple of 1296.
+        #[arg(long, default_value_t = GAMES_PER_REPETITION)]
+        trials: usize,
+        /// Number of threads. By default all cores are used.
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Seed for the dice. Rollouts with the same seed and nets have identical results.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Also print statistics of the games: length, hits and gammon rates by game phase.
+        #[arg(long)]
+        report: bool,
+    },
+    /// Analyzes all checker plays and cube decisions of a match in `.mat` format.
+    ///
+    /// Prints the totals of both players and the worst blunders.
+    Analyze {
+        /// The match file.
+        file: PathBuf,
+        /// Maximum number of blunders to print.
+        #[arg(long, default_value_t = 10)]
+        worst: usize,
+        /// Additionally writes the whole analysis as JSON into this file.
+        #[arg(long)]
+        json: Option<PathBuf>,
+    },
+    /// Plays a money game or a match against wildbg in the terminal.
+    ///
+    /// Moves are entered in standard notation like `8/5 6/5` or `bar/22* 13/10(2)`.
+    Play {
+        /// Length of the match. Without it, money games are played.
+        #[arg(long)]
+        match_length: Option<u32>,
+    },
+}
+
+fn main() {
+    let args = Args::parse();
+    let result = match args.command {
+        Command::Convert { from, to, file } => run_convert(from, to, file),
+        Command::Bestmove {
+            position,
+            dice,
+            moves,
+        } => run_bestmove(&position, dice.as_deref(), moves),
+        Command::Rollout {
+            position,
+            trials,
+            threads,
+            seed,
+            report,
+        } => run_rollout(&position, trials, threads, seed, report),
+        Command::Analyze { file, worst, json } => run_analyze(&file, worst, json),
+        Command::Play { match_length } => run_play(match_length),
+    };
+    if let Err(message) = result {
+        eprintln!("{message}");
+        std::process::exit(1);
+    }
+}
+
+fn run_bestmove(position: &str, dice: Option<&str>, moves: usize) -> Result<(), String> {
+    let dice = bestmove::parse_dice(dice, position)?;
+    let position = bestmove::parse_position(position)?;
+    let api = WildbgApi::try_default()?;
+    let config = WildbgConfig {
+        away: None,
+        gammons_count: true,
+    };
+    let ranked_moves = api.ranked_moves(&position, &dice, &config, moves);
+    println!("{}", bestmove::format_ranked_moves(&ranked_moves));
+    Ok(())
+}
+
+fn run_rollout(
+    position: &str,
+    trials: usize,
+    threads: Option<usize>,
+    seed: Option<u64>,
+    report: bool,
+) -> Result<(), String> {
+    let position = bestmove::parse_position(position)?;
+    if position.has_lost() || position.sides_switched().has_lost() {
+        return Err("The game is already over.".to_string());
+    }
+    if let Some(threads) = threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|error| error.to_string())?;
+    }
+    let seed = seed.unwrap_or_else(FastrandDice::random_seed);
+    let repetitions = trials.div_ceil(GAMES_PER_REPETITION).max(1);
+    let nets = CompositeEvaluator::try_default()?;
+    let net_probabilities = nets.eval(&position);
+    let evaluator = RolloutEvaluator::with_evaluator_and_seed(nets, seed);
+    let (results, report) = if report {
+        let report = evaluator.report_with_seed(&position, seed, repetitions);
+        (report.results(), Some(report))
+    } else {
+        let results = evaluator.results_with_seed(&position, seed, repetitions);
+        (results, None)
+    };
+    println!("Seed: {seed}");
+    println!(
+        "{}",
+        rollout::format_summary(&RolloutSummary::from(&results), &net_probabilities)
+    );
+    if let Some(report) = report {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+fn run_analyze(file: &Path, worst: usize, json: Option<PathBuf>) -> Result<(), String> {
+    let content =
+        fs::read_to_string(file).map_err(|_| format!("Could not open file {}", file.display()))?;
+    let mat_match = mat::parse(&content)?;
+    let evaluator = CompositeEvaluator::try_default()?;
+    let analysis = analyze_match(&evaluator, &mat_match)?;
+    println!(
+        "{}",
+        analyze::format_report(&analysis, &Thresholds::default(), worst)
+    );
+    if let Some(path) = json {
+        let json = serde_json::to_string_pretty(&analysis).map_err(|error| error.to_string())?;
+        fs::write(&path, json)
+            .map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+    }
+    Ok(())
+}
+
+fn run_play(match_length: Option<u32>) -> Result<(), String> {
+    let match_state = match match_length {
+        Some(length) => MatchState::new(length)?,
+        None => MatchState::money(MoneyRules::default()),
+    };
+    let api = WildbgApi::try_default()?;
+    play::play(
+        &api,
+        match_state,
+        &mut FastrandDice::new(),
+        stdin().lock(),
+        stdout().lock(),
+    )
+}
+
+fn run_convert(
+    from: convert::Format,
+    to: convert::Format,
+    file: Option<PathBuf>,
+) -> Result<(), String> {
+    let reader: Box<dyn BufRead> = match file {
+        None => Box::new(BufReader::new(stdin())),
+        Some(path) => match File::open(&path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(_) => return Err(format!("Could not open file {}", path.display())),
+        },
+    };
+    let mut stdout = stdout().lock();
+    let mut number_of_errors = 0_usize;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|error| error.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match convert::convert(line.trim(), from, to) {
+            Ok(converted) => writeln!(stdout, "{converted}").map_err(|error| error.to_string())?,
+            Err(message) => {
+                number_of_errors += 1;
+                eprintln!("Line {}: {message}", index + 1);
+            }
+        }
+    }
+    if number_of_errors > 0 {
+        Err(format!(
+            "{number_of_errors} line(s) could not be converted."
+        ))
+    } else {
+        Ok(())
+    }
+}
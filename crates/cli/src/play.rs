@@ -0,0 +1,371 @@
+use crate::bestmove::{format_move, format_ranked_moves};
+use coach::mat::parse_checker_moves;
+use engine::dice::Dice;
+use engine::dice_gen::DiceGen;
+use engine::evaluator::Evaluator;
+use logic::bg_move::{apply_checker_moves, BgMove};
+use logic::cube::{CubeAccess, Janowski, WindowPosition};
+use logic::game_state::{CubeOwner, GameState, Player};
+use logic::match_equity::MatchEquityTable;
+use logic::match_state::MatchState;
+use logic::wildbg_api::WildbgApi;
+use std::io::{BufRead, Write};
+
+/// The human always plays [Player::One] and moves from the 24 point to the 1 point.
+const HUMAN: Player = Player::One;
+
+/// Plays games against `api` until the match is over or the human quits.
+///
+/// Lines of the human are read from `input`, the board and all messages are written to `output`.
+pub fn play<T: Evaluator>(
+    api: &WildbgApi<T>,
+    mut match_state: MatchState,
+    dice_gen: &mut impl DiceGen,
+    input: impl BufRead,
+    output: impl Write,
+) -> Result<(), String> {
+    let mut terminal = Terminal { input, output };
+    loop {
+        let Some(game) = play_game(api, &match_state, dice_gen, &mut terminal)? else {
+            return terminal.say("Bye.");
+        };
+        let outcome = game.outcome().expect("The game is over.");
+        let winner = if outcome.winner == HUMAN {
+            "You win"
+        } else {
+            "wildbg wins"
+        };
+        terminal.say(&format!("{} {} point(s).", winner, outcome.points))?;
+        match_state.add_game(&game)?;
+        match match_state.winner() {
+            Some(winner) => {
+                terminal.say(&format_score(&match_state))?;
+                let winner = if winner == HUMAN {
+                    "You win"
+                } else {
+                    "wildbg wins"
+                };
+                return terminal.say(&format!("{winner} the match."));
+            }
+            None if match_state.length().is_none() => {
+                terminal.say(&format_score(&match_state))?;
+                match terminal.ask("Another game? (y/n)")?.as_deref() {
+                    Some("y" | "yes") => {}
+                    _ => return terminal.say("Bye."),
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Returns `None` if the human quits before the game is over.
+fn play_game<T: Evaluator>(
+    api: &WildbgApi<T>,
+    match_state: &MatchState,
+    dice_gen: &mut impl DiceGen,
+    terminal: &mut Terminal<impl BufRead, impl Write>,
+) -> Result<Option<GameState>, String> {
+    let Dice::Mixed(dice) = dice_gen.roll_mixed() else {
+        unreachable!("The opening roll is never a double.");
+    };
+    let (human_die, engine_die) = match dice_gen.choose_index(&[1.0, 1.0]) {
+        0 => (dice.big(), dice.small()),
+        _ => (dice.small(), dice.big()),
+    };
+    let mut game = match_state.new_game(human_die, engine_die)?;
+    terminal.say(&format!(
+        "You roll a {human_die}, wildbg rolls a {engine_die}."
+    ))?;
+
+    while game.outcome().is_none() {
+        let is_human = game.turn() == HUMAN;
+        if game.is_doubled() {
+            let value = 2 * game.cube().value();
+            if is_human {
+                // The position is seen from the human, who has doubled.
+                if engine_takes(api, &game, match_state) {
+                    terminal.say(&format!("wildbg takes, the cube is now at {value}."))?;
+                    game.take()?;
+                } else {
+                    terminal.say("wildbg passes.")?;
+                    game.pass()?;
+                }
+            } else {
+                let question = format!("wildbg doubles to {value}. Take or pass? (t/p)");
+                match terminal.ask(&question)?.as_deref() {
+                    Some("t" | "take") => game.take()?,
+                    Some("p" | "pass") => game.pass()?,
+                    Some("q" | "quit") | None => return Ok(None),
+                    Some(_) => terminal.say("Please enter 't' or 'p'.")?,
+                }
+            }
+            continue;
+        }
+
+        let Some(dice) = game.dice() else {
+            if is_human {
+                if game.may_double() {
+                    terminal.say(&render(&game, match_state))?;
+                    match terminal.ask("Roll or double? (r/d)")?.as_deref() {
+                        Some("r" | "roll") => {}
+                        Some("d" | "double") => {
+                            game.double()?;
+                            continue;
+                        }
+                        Some("q" | "quit") | None => return Ok(None),
+                        Some(_) => {
+                            terminal.say("Please enter 'r' or 'd'.")?;
+                            continue;
+                        }
+                    }
+                }
+            } else if game.may_double() && engine_doubles(api, &game, match_state) {
+                game.double()?;
+                continue;
+            }
+            game.roll(dice_gen.roll())?;
+            continue;
+        };
+
+        let legal_moves = BgMove::all_legal_moves(game.position(), &dice);
+        if !is_human {
+            let config = match_state.config(&game);
+            let best = api.best_move(game.position(), &dice, &config);
+            let legal_move = legal_moves
+                .into_iter()
+                .find(|legal_move| legal_move.bg_move == best)
+                .expect("The best move is a legal move.");
+            terminal.say(&format!(
+                "wildbg rolls {} and plays {}.",
+                format_dice(&dice),
+                format_move(&legal_move.bg_move)
+            ))?;
+            game.play(&legal_move.position)?;
+            continue;
+        }
+
+        terminal.say(&render(&game, match_state))?;
+        if let [legal_move] = legal_moves.as_slice() {
+            terminal.say(&format!(
+                "You roll {} and play {}.",
+                format_dice(&dice),
+                format_move(&legal_move.bg_move)
+            ))?;
+            game.play(&legal_move.position)?;
+            continue;
+        }
+        let question = format!(
+            "You roll {}. Your move? (like 8/5 6/5, 'hint' or 'quit')",
+            format_dice(&dice)
+        );
+        let Some(answer) = terminal.ask(&question)? else {
+            return Ok(None);
+        };
+        match answer.as_str() {
+            "q" | "quit" => return Ok(None),
+            "h" | "hint" => {
+                let config = match_state.config(&game);
+                let ranked_moves = api.ranked_moves(game.position(), &dice, &config, 5);
+                terminal.say(&format_ranked_moves(&ranked_moves))?;
+            }
+            notation => {
+                let played = parse_checker_moves(notation)
+                    .and_then(|checker_moves| apply_checker_moves(game.position(), &checker_moves))
+                    .and_then(|new| game.play(&new).map_err(|error| error.to_string()));
+                if let Err(message) = played {
+                    terminal.say(&message)?;
+                }
+            }
+        }
+    }
+    Ok(Some(game))
+}
+
+/// `true` if wildbg, who is on turn, should double.
+///
+/// Money games use [Janowski]'s model, matches the doubling window of the default
+/// [MatchEquityTable].
+fn engine_doubles<T: Evaluator>(
+    api: &WildbgApi<T>,
+    game: &GameState,
+    match_state: &MatchState,
+) -> bool {
+    let probabilities = api.probabilities(game.position());
+    match match_state.match_score(game.turn()) {
+        None => {
+            let access = match game.cube().owner() {
+                CubeOwner::Centered => CubeAccess::Centered,
+                CubeOwner::Owned(_) => CubeAccess::Owned,
+            };
+            Janowski::default().should_double(&probabilities, access)
+        }
+        Some(score) => {
+            let window =
+                MatchEquityTable::default().window(&probabilities, &score, game.cube().value());
+            matches!(
+                window.position(),
+                WindowPosition::DoubleTake | WindowPosition::DoublePass
+            )
+        }
+    }
+}
+
+/// `true` if wildbg should take the double of the human, who is on turn.
+fn engine_takes<T: Evaluator>(
+    api: &WildbgApi<T>,
+    game: &GameState,
+    match_state: &MatchState,
+) -> bool {
+    let probabilities = api.probabilities(game.position());
+    match match_state.match_score(game.turn()) {
+        None => Janowski::default().should_take(&probabilities),
+        Some(score) => {
+            let window =
+                MatchEquityTable::default().window(&probabilities, &score, game.cube().value());
+            probabilities.win() < window.cash_point
+        }
+    }
+}
+
+/// Score, cube and the board from the point of view of the human, who plays `X`.
+pub fn render(game: &GameState, match_state: &MatchState) -> String {
+    let position = if game.turn() == HUMAN {
+        *game.position()
+    } else {
+        game.position().sides_switched()
+    };
+    format!(
+        "{}\n{}\n{}",
+        format_score(match_state),
+        format_cube(game),
+        position.ascii_board()
+    )
+}
+
+fn format_score(match_state: &MatchState) -> String {
+    let score = format!(
+        "You {}, wildbg {}",
+        match_state.score(HUMAN),
+        match_state.score(HUMAN.opponent())
+    );
+    match match_state.length() {
+        Some(length) if match_state.is_crawford() => {
+            format!("Score: {score} ({length} point match, Crawford game)")
+        }
+        Some(length) => format!("Score: {score} ({length} point match)"),
+        None => format!("Money game: {score}"),
+    }
+}
+
+fn format_cube(game: &GameState) -> String {
+    let cube = game.cube();
+    let owner = match cube.owner() {
+        CubeOwner::Centered => "centered",
+        CubeOwner::Owned(HUMAN) => "owned by you",
+        CubeOwner::Owned(_) => "owned by wildbg",
+    };
+    format!("Cube: {} ({})", cube.value(), owner)
+}
+
+fn format_dice(dice: &Dice) -> String {
+    match dice {
+        Dice::Double(die) => format!("{die}{die}"),
+        Dice::Mixed(dice) => format!("{}{}", dice.big(), dice.small()),
+    }
+}
+
+struct Terminal<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Terminal<R, W> {
+    fn say(&mut self, text: &str) -> Result<(), String> {
+        writeln!(self.output, "{text}").map_err(|error| error.to_string())
+    }
+
+    /// Returns the trimmed answer in lowercase or `None` at the end of the input.
+    fn ask(&mut self, question: &str) -> Result<Option<String>, String> {
+        write!(self.output, "{question} ").map_err(|error| error.to_string())?;
+        self.output.flush().map_err(|error| error.to_string())?;
+        let mut answer = String::new();
+        match self.input.read_line(&mut answer) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(answer.trim().to_lowercase())),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::play::{engine_doubles, engine_takes, play, render};
+    use engine::dice::Dice;
+    use engine::dice_gen::DiceGenMock;
+    use engine::position::STARTING;
+    use engine::test_utils::EvaluatorFake;
+    use logic::match_state::MatchState;
+    use logic::money_rules::MoneyRules;
+    use logic::wildbg_api::WildbgApi;
+
+    #[test]
+    fn render_starting_position() {
+        // Given
+        let match_state = MatchState::new(5).unwrap();
+        let game = match_state.new_game(3, 1).unwrap();
+        // When
+        let board = render(&game, &match_state);
+        // Then
+        assert_eq!(
+            board,
+            format!(
+                "Score: You 0, wildbg 0 (5 point match)\nCube: 1 (centered)\n{}",
+                STARTING.ascii_board()
+            )
+        );
+    }
+
+    #[test]
+    fn scripted_session() {
+        // Given
+        let api = WildbgApi::new(EvaluatorFake {});
+        let mut dice_gen = DiceGenMock::new(&[Dice::new(3, 1), Dice::new(6, 4)]);
+        let input = "8/5 6/4\n8/5 6/5\nquit\n".as_bytes();
+        let mut output = Vec::new();
+        // When
+        let result = play(
+            &api,
+            MatchState::new(3).unwrap(),
+            &mut dice_gen,
+            input,
+            &mut output,
+        );
+        // Then
+        assert_eq!(result, Ok(()));
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("You roll a 3, wildbg rolls a 1.\n"));
+        assert!(output.contains("This is not a legal move for the given position and dice."));
+        assert!(output.contains("wildbg rolls 64 and plays 13/7 13/9."));
+        assert!(output.ends_with("Bye.\n"));
+        dice_gen.assert_all_dice_were_used();
+    }
+
+    #[test]
+    fn cube_decisions_in_money_game_and_match() {
+        // Given
+        let api = WildbgApi::new(EvaluatorFake {});
+        let money = MatchState::money(MoneyRules::default());
+        let double_match_point = MatchState::new(2).unwrap();
+        // When
+        let money_game = money.new_game(1, 2).unwrap();
+        let match_game = double_match_point.new_game(1, 2).unwrap();
+        // Then
+        // With even chances Janowski's model neither doubles nor passes.
+        assert!(!engine_doubles(&api, &money_game, &money));
+        assert!(engine_takes(&api, &money_game, &money));
+        // At 2-away 2-away the first game decides the match anyway, so doubling is right.
+        assert!(engine_doubles(&api, &match_game, &double_match_point));
+        assert!(engine_takes(&api, &match_game, &double_match_point));
+    }
+}
@@ -0,0 +1,78 @@
+use coach::rollout::RolloutSummary;
+use engine::probabilities::Probabilities;
+
+/// Table of rollout results, their standard errors and the evaluation of the neural net.
+pub fn format_summary(summary: &RolloutSummary, net: &Probabilities) -> String {
+    let rollout = &summary.probabilities;
+    let rows = [
+        (
+            "Win",
+            rollout.win(),
+            summary.probability_errors[0],
+            net.win(),
+        ),
+        (
+            "W(g)",
+            rollout.win_gammon,
+            summary.probability_errors[1],
+            net.win_gammon,
+        ),
+        (
+            "L(g)",
+            rollout.lose_gammon,
+            summary.probability_errors[2],
+            net.lose_gammon,
+        ),
+    ];
+    let mut lines = vec![
+        format!("Games: {}", summary.games),
+        format!(
+            "{:<8}{:>9}{:>10}{:>12}",
+            "", "Rollout", "Std err", "Neural net"
+        ),
+    ];
+    for (name, value, error, net_value) in rows {
+        lines.push(format!(
+            "{:<8}{:>8.2}%{:>9.2}%{:>11.2}%",
+            name,
+            100.0 * value,
+            100.0 * error,
+            100.0 * net_value
+        ));
+    }
+    lines.push(format!(
+        "{:<8}{:>+9.3}{:>10.3}{:>+12.3}",
+        "Equity",
+        rollout.equity(),
+        summary.equity_error,
+        net.equity()
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rollout::format_summary;
+    use coach::rollout::RolloutSummary;
+    use engine::probabilities::{Probabilities, ResultCounter};
+
+    #[test]
+    fn format() {
+        let summary = RolloutSummary::from(&ResultCounter::new(30, 20, 0, 40, 10, 0));
+        let net = Probabilities {
+            win_normal: 0.3,
+            win_gammon: 0.1,
+            lose_normal: 0.4,
+            lose_gammon: 0.2,
+        };
+        assert_eq!(
+            format_summary(&summary, &net),
+            "Games: 100
+          Rollout   Std err  Neural net
+Win        50.00%     5.00%      40.00%
+W(g)       20.00%     4.00%      10.00%
+L(g)       10.00%     3.00%      20.00%
+Equity     +0.100     0.137      -0.300"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use crate::data::PositionRecord;
+use engine::position::Position;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Positions leased to a worker by the coordinator of a distributed data generation.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Batch {
+    /// Needed to send back the results.
+    pub id: u64,
+    /// Empty if there is currently nothing to do, for example because the last positions are still
+    /// rolled out by other workers.
+    pub position_ids: Vec<String>,
+    /// `true` once all positions have been rolled out, the worker can stop then.
+    pub finished: bool,
+}
+
+/// Hands out positions for rollouts in batches and keeps track of which batches are done.
+///
+/// Workers might crash or lose their connection. So batches are only leased: If the results of a
+/// batch are not sent back within the lease duration, its positions are handed out again.
+pub struct BatchQueue {
+    pending: VecDeque<Position>,
+    leased: HashMap<u64, Lease>,
+    next_id: u64,
+    lease_duration: Duration,
+}
+
+struct Lease {
+    positions: Vec<Position>,
+    expires: Instant,
+}
+
+impl BatchQueue {
+    pub fn new(positions: Vec<Position>, lease_duration: Duration) -> Self {
+        Self {
+            pending: positions.into(),
+            leased: HashMap::new(),
+            next_id: 0,
+            lease_duration,
+        }
+    }
+
+    /// Leases up to `size` positions.
+    pub fn lease(&mut self, size: usize, now: Instant) -> Batch {
+        self.release_expired(now);
+        let count = size.min(self.pending.len());
+        if count == 0 {
+            return Batch {
+                id: 0,
+                position_ids: Vec::new(),
+                finished: self.is_finished(),
+            };
+        }
+        let positions: Vec<Position> = self.pending.drain(..count).collect();
+        self.next_id += 1;
+        let batch = Batch {
+            id: self.next_id,
+            position_ids: positions.iter().map(|p| p.position_id()).collect(),
+            finished: false,
+        };
+        let expires = now + self.lease_duration;
+        self.leased
+            .insert(self.next_id, Lease { positions, expires });
+        batch
+    }
+
+    /// Accepts the results of a batch if they contain exactly the leased positions.
+    pub fn complete(&mut self, id: u64, records: &[PositionRecord]) -> Result<(), String> {
+        let lease = self
+            .leased
+            .get(&id)
+            .ok_or_else(|| format!("Batch {id} is unknown, already completed or has expired."))?;
+        let matches = lease.positions.len() == records.len()
+            && lease
+                .positions
+                .iter()
+                .zip(records)
+                .all(|(position, record)| *position == record.position());
+        if !matches {
+            return Err(format!(
+                "The results don't match the positions of batch {id}."
+            ));
+        }
+        self.leased.remove(&id);
+        Ok(())
+    }
+
+    /// Number of positions not rolled out yet, including those which are leased.
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+            + self
+                .leased
+                .values()
+                .map(|lease| lease.positions.len())
+                .sum::<usize>()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty() && self.leased.is_empty()
+    }
+
+    fn release_expired(&mut self, now: Instant) {
+        let expired: Vec<u64> = self
+            .leased
+            .iter()
+            .filter(|(_, lease)| lease.expires <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            if let Some(lease) = self.leased.remove(&id) {
+                self.pending.extend(lease.positions);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::batch_queue::BatchQueue;
+    use crate::data::PositionRecord;
+    use engine::pos;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+    use std::time::{Duration, Instant};
+
+    fn records(position_ids: &[String]) -> Vec<PositionRecord> {
+        position_ids
+            .iter()
+            .map(|id| {
+                PositionRecord::new(
+                    &Position::try_from_id(id).unwrap(),
+                    &Probabilities::default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lease_and_complete() {
+        // Given
+        let positions = vec![
+            pos!(x 1:1; o 24:1),
+            pos!(x 2:1; o 24:1),
+            pos!(x 3:1; o 24:1),
+        ];
+        let mut queue = BatchQueue::new(positions, Duration::from_secs(60));
+        let now = Instant::now();
+        // When
+        let first = queue.lease(2, now);
+        let second = queue.lease(2, now);
+        let nothing_left = queue.lease(2, now);
+        // Then
+        assert_eq!(first.position_ids.len(), 2);
+        assert_eq!(second.position_ids.len(), 1);
+        assert!(nothing_left.position_ids.is_empty());
+        assert!(!nothing_left.finished);
+        assert_eq!(queue.remaining(), 3);
+
+        queue
+            .complete(first.id, &records(&first.position_ids))
+            .unwrap();
+        queue
+            .complete(second.id, &records(&second.position_ids))
+            .unwrap();
+        assert!(queue.lease(2, now).finished);
+        assert_eq!(
+            queue.complete(first.id, &records(&first.position_ids)),
+            Err("Batch 1 is unknown, already completed or has expired.".to_string())
+        );
+    }
+
+    #[test]
+    fn expired_lease_is_handed_out_again() {
+        // Given
+        let mut queue = BatchQueue::new(vec![pos!(x 1:1; o 24:1)], Duration::from_secs(60));
+        let now = Instant::now();
+        let lost = queue.lease(1, now);
+        // When
+        let again = queue.lease(1, now + Duration::from_secs(60));
+        // Then
+        assert_eq!(again.position_ids, lost.position_ids);
+        assert_ne!(again.id, lost.id);
+        assert!(queue
+            .complete(lost.id, &records(&lost.position_ids))
+            .is_err());
+        assert!(queue
+            .complete(again.id, &records(&again.position_ids))
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_wrong_positions() {
+        let mut queue = BatchQueue::new(vec![pos!(x 1:1; o 24:1)], Duration::from_secs(60));
+        let batch = queue.lease(1, Instant::now());
+        let wrong = records(&[pos!(x 2:1; o 24:1).position_id()]);
+        assert_eq!(
+            queue.complete(batch.id, &wrong),
+            Err("The results don't match the positions of batch 1.".to_string())
+        );
+        assert_eq!(queue.remaining(), 1);
+    }
+}
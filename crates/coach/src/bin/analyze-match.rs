@@ -0,0 +1,36 @@
+use coach::mat::parse;
+use coach::match_analysis::{analyze_match, Thresholds};
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+use std::fs;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// This binary analyzes all checker plays and cube decisions of a match in `.mat` format.
+///
+/// The equity loss of each decision, the luck of each roll and the totals of both players are
+/// printed as JSON. Alternatively only the blunders are printed for a quick review.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure which match you want to analyze and what to print.
+    let path = "training-data/match.mat";
+    let only_blunders = false;
+    let thresholds = Thresholds::default();
+
+    let evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
+    let content = fs::read_to_string(path)?;
+    let analysis = parse(&content)
+        .and_then(|mat_match| analyze_match(&evaluator, &mat_match))
+        .unwrap_or_else(|error| {
+            eprintln!("{}: {}", path, error);
+            std::process::exit(1)
+        });
+    let json = if only_blunders {
+        serde_json::to_string_pretty(&analysis.blunders(&thresholds))?
+    } else {
+        serde_json::to_string_pretty(&analysis)?
+    };
+    println!("{}", json);
+    Ok(())
+}
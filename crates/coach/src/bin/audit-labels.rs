@@ -0,0 +1,63 @@
+use coach::data::PositionRecord;
+use coach::label_audit::{average, sample, LabelAudit};
+use coach::progress::{Progress, ProgressMode};
+use coach::rollout::RolloutEvaluator;
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use engine::evaluator::Evaluator;
+use engine::position::OngoingPhase;
+use mimalloc::MiMalloc;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// This binary audits the quality of existing training data before training on it.
+///
+/// A random sample of the positions is rolled out again, several times with different seeds.
+/// The distribution of the differences between the existing labels and the new rollouts is printed.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure which data and how much of it you want to audit.
+    let phase = OngoingPhase::Race;
+    let fraction = 0.01;
+    let rollouts_per_position = 4;
+    let seed = 0;
+
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    println!("Read training data from {}", training_path);
+    let records: Vec<PositionRecord> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&training_path)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    let sample = sample(&records, fraction, seed);
+    println!(
+        "Roll out {} of {} positions {} times each",
+        sample.len(),
+        records.len(),
+        rollouts_per_position
+    );
+
+    let rollout_evaluators: Vec<_> = (0..rollouts_per_position)
+        .map(|i| {
+            CompositeEvaluator::try_default()
+                .map(|evaluator| RolloutEvaluator::with_evaluator_and_seed(evaluator, seed + i))
+                .unwrap_or_exit_with_message()
+        })
+        .collect();
+
+    let mut audit = LabelAudit::default();
+    let mut progress = Progress::new(sample.len(), "positions", ProgressMode::from_env());
+    for record in sample.iter() {
+        let position = record.position();
+        let rollouts: Vec<_> = rollout_evaluators
+            .iter()
+            .map(|rollout_evaluator| rollout_evaluator.eval(&position))
+            .collect();
+        audit.add(&record.probabilities(), &average(&rollouts));
+        progress.inc()?;
+    }
+
+    progress.finish();
+    println!("{}", audit);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use clap::Parser;
+use coach::coach_helpers::{quarantine_file_name, store_path};
+use coach::config::{CoachConfig, DEFAULT_CONFIG_PATH};
+use coach::data::{mirrored_positions, PositionRecord};
+use coach::labels::Labeler;
+use coach::progress::{Progress, ProgressMode};
+use coach::quarantine::QuarantineEvaluator;
+use coach::rollout::RolloutEvaluator;
+use coach::store::PositionStore;
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use engine::evaluator::Evaluator;
+use mimalloc::MiMalloc;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Adds the mirrored version of each position to training data, see `Position::mirror`.
+///
+/// The mirrored positions have the other player on roll, so they are labeled like in
+/// `generate-training-data`, with the labels, repetitions and seed of the table `[training-data]`
+/// of the configuration file. For example `training-data/contact.csv` becomes
+/// `training-data/contact-augmented.csv`, which contains all original records followed by the
+/// mirrored ones.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// TOML file with the parameters. If it doesn't exist, the defaults are used.
+    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
+    config: String,
+    /// CSV file with position IDs and probabilities.
+    #[arg(long, default_value = "training-data/contact.csv")]
+    input: PathBuf,
+    /// By default the input file name with `-augmented` appended.
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// How to show the progress. By default a bar in a terminal, otherwise a line every 30 seconds.
+    #[arg(long, value_enum)]
+    progress: Option<ProgressMode>,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let config = CoachConfig::load(&args.config).unwrap_or_exit_with_message();
+    config.init_thread_pool().unwrap_or_exit_with_message();
+    let training_data = &config.training_data;
+    let output = args.output.unwrap_or_else(|| {
+        let stem = args.input.file_stem().unwrap_or_default().to_string_lossy();
+        args.input.with_file_name(format!("{stem}-augmented.csv"))
+    });
+
+    let records = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&args.input)?
+        .deserialize()
+        .collect::<Result<Vec<PositionRecord>, _>>()?;
+    let mirrored = mirrored_positions(&records);
+
+    let evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
+    _ = std::fs::create_dir("training-data");
+    let evaluator = QuarantineEvaluator::new(evaluator, &quarantine_file_name())?;
+    let rollout_evaluator = match training_data.seed {
+        Some(seed) => RolloutEvaluator::with_evaluator_and_seed(evaluator, seed),
+        None => RolloutEvaluator::with_evaluator(evaluator),
+    }
+    .with_repetitions(training_data.repetitions);
+    let labeler = Labeler::new(rollout_evaluator, training_data.label_source);
+    let mut store = PositionStore::open(Path::new(&store_path()))?;
+
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(File::create(&output)?);
+    writer.write_record(PositionRecord::csv_header())?;
+    for record in records.iter() {
+        writer.serialize(record)?;
+    }
+    println!("Label {} mirrored positions", mirrored.len());
+    let mode = args.progress.unwrap_or_else(ProgressMode::from_env);
+    let mut progress = Progress::new(mirrored.len(), "positions", mode);
+    for position in mirrored.iter() {
+        let probabilities = labeler.eval(position);
+        store.insert(position, &probabilities)?;
+        writer.serialize(PositionRecord::new(position, &probabilities))?;
+        writer.flush()?;
+        progress.inc()?;
+    }
+    progress.finish();
+    println!(
+        "{} records and {} mirrored records written to {}",
+        records.len(),
+        mirrored.len(),
+        output.display()
+    );
+    Ok(())
+}
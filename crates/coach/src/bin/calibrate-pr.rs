@@ -0,0 +1,48 @@
+use coach::calibration::{
+    analyze_player, performance_rating, Benchmark, Calibration, CalibrationReport,
+};
+use coach::mat::parse;
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+use std::fs;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// This binary compares the PR wildbg computes for human matches with the PR of other tools.
+///
+/// The benchmark file is a CSV file with the columns `match_file`, `player` and `reference_pr`.
+/// Each line refers to a `.mat` file and one of the two players of that match.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure which benchmark and which nets you want to use.
+    let benchmark_path = "training-data/pr-benchmark.csv";
+    let contact_path = "neural-nets/contact.onnx";
+    let race_path = "neural-nets/race.onnx";
+
+    let benchmarks: Vec<Benchmark> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(benchmark_path)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    let evaluator = CompositeEvaluator::from_file_paths_optimized(contact_path, race_path)
+        .unwrap_or_exit_with_message();
+
+    let mut report = CalibrationReport::default();
+    for benchmark in benchmarks {
+        let content = fs::read_to_string(&benchmark.match_file)?;
+        let summary = parse(&content)
+            .and_then(|mat_match| analyze_player(&evaluator, &mat_match, &benchmark.player))
+            .unwrap_or_else(|error| {
+                eprintln!("{}: {}", benchmark.match_file, error);
+                std::process::exit(1)
+            });
+        report.calibrations.push(Calibration {
+            computed_pr: performance_rating(&summary),
+            move_count: summary.move_count,
+            benchmark,
+        });
+    }
+    println!("{}", report);
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use clap::Parser;
+use coach::data::PositionRecord;
+use coach::label_audit::sample;
+use coach::prediction_metrics::PredictionMetrics;
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use engine::evaluator::Evaluator;
+use mimalloc::MiMalloc;
+use rayon::prelude::*;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Compares the probabilities of neural nets with rollouts and prints Brier score, log loss and
+/// calibration curve for winning, winning a gammon and losing a gammon.
+///
+/// Use rollout data which the nets have not been trained on, otherwise the numbers look too good.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// CSV file with position IDs and rollout probabilities.
+    #[arg(long, default_value = "training-data/race.csv")]
+    input: String,
+    #[arg(long, default_value = "neural-nets/contact.onnx")]
+    contact: String,
+    #[arg(long, default_value = "neural-nets/race.onnx")]
+    race: String,
+    /// Randomly picked fraction of the positions in `input`.
+    #[arg(long, default_value_t = 1.0)]
+    fraction: f32,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let evaluator = CompositeEvaluator::from_file_paths_optimized(&args.contact, &args.race)
+        .unwrap_or_exit_with_message();
+
+    println!("Read rollouts from {}", args.input);
+    let records: Vec<PositionRecord> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&args.input)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    let sample = sample(&records, args.fraction, args.seed);
+
+    let predictions: Vec<_> = sample
+        .par_iter()
+        .map(|record| evaluator.eval(&record.position()))
+        .collect();
+    let mut metrics = PredictionMetrics::default();
+    for (record, predicted) in sample.iter().zip(&predictions) {
+        metrics.add(predicted, &record.probabilities());
+    }
+    println!("Compared {} positions\n\n{metrics}", sample.len());
+    Ok(())
+}
@@ -22,9 +22,9 @@ fn main() {
         "neural-nets/race.onnx",
     )
     .unwrap_or_exit_with_message();
-    // let evaluator_2 = engine::multiply::MultiPlyEvaluator {
-    //     evaluator: evaluator_2,
-    // };
+    // let evaluator_2 = engine::multiply::MultiPlyEvaluator::new(evaluator_2);
+    // To measure whether discounting deeper evaluations helps, compare with plain 2-ply instead:
+    // let evaluator_2 = engine::multiply::MultiPlyEvaluator::new(evaluator_2).with_discount(0.2);
 
     let duel = Duel::new(evaluator_1, evaluator_2);
 
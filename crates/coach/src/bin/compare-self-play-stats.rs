@@ -0,0 +1,33 @@
+use coach::coach_helpers::self_play_stats_file_name;
+use coach::self_play_stats::SelfPlayRecord;
+
+/// This binary prints the statistics of all self play runs, so that different net generations can be compared.
+///
+/// The statistics are written by `generate-positions.rs`.
+/// Big changes from one run to the next are marked with `!`, they might indicate problems in training.
+fn main() -> std::io::Result<()> {
+    // Change the next two lines to configure when a change between two runs is marked.
+    let gammon_rate_threshold = 0.02;
+    let average_length_threshold = 0.1;
+
+    let path = self_play_stats_file_name();
+    let records = SelfPlayRecord::read_all(&path)?;
+    println!(
+        "{:<30} {:>8} {:>8} {:>7} {:>8}",
+        "run", "games", "gammons", "length", "dances"
+    );
+    for (i, record) in records.iter().enumerate() {
+        let warning = match i.checked_sub(1).map(|previous| &records[previous]) {
+            Some(previous)
+                if (record.gammon_rate - previous.gammon_rate).abs() > gammon_rate_threshold
+                    || (record.average_length / previous.average_length - 1.0).abs()
+                        > average_length_threshold =>
+            {
+                " !"
+            }
+            _ => "",
+        };
+        println!("{}{}", record, warning);
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use coach::data::{InputsRecord, PositionRecord};
+use coach::data::stream_inputs;
 use engine::inputs;
 use engine::position::OngoingPhase;
 use std::fs::File;
@@ -15,20 +15,14 @@ fn main() -> std::io::Result<()> {
         training_path, inputs_path
     );
 
-    let mut csv_reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(&training_path)?;
-
     let mut inputs_writer = csv::WriterBuilder::new()
         .has_headers(false)
         .from_writer(File::create(&inputs_path)?);
 
-    for result in csv_reader.deserialize() {
-        let position_record: PositionRecord = result?;
-        let inputs_record = InputsRecord::new(&position_record, &inputs_gen);
-        inputs_writer.serialize(inputs_record)?;
-        inputs_writer.flush()?;
-    }
+    stream_inputs(File::open(&training_path)?, &inputs_gen, |inputs_record| {
+        Ok(inputs_writer.serialize(inputs_record)?)
+    })?;
+    inputs_writer.flush()?;
 
     println!("\nDone!");
     Ok(())
@@ -0,0 +1,34 @@
+use coach::data::stream_inputs;
+use coach::npz::NpzWriter;
+use engine::inputs;
+use engine::inputs::InputsGen;
+use engine::position::OngoingPhase;
+use std::fs::File;
+use std::io::BufWriter;
+
+/// Like `convert-to-inputs.rs`, but writes NumPy arrays instead of CSV, which PyTorch can use without parsing.
+fn main() -> std::io::Result<()> {
+    // Change the next two lines in case you want to create inputs for another game phase.
+    let phase = OngoingPhase::Race;
+    let inputs_gen = inputs::RaceInputsGen {};
+    convert(phase, inputs_gen)?;
+
+    println!("\nDone!");
+    Ok(())
+}
+
+fn convert<T: InputsGen + Sync>(phase: OngoingPhase, inputs_gen: T) -> std::io::Result<()> {
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    let inputs_path = format!("training-data/{:?}-inputs.npz", phase).to_lowercase();
+    println!(
+        "Read training data from {} and write inputs to {}",
+        training_path, inputs_path
+    );
+
+    let mut npz_writer = NpzWriter::new(T::NUM_INPUTS)?;
+    stream_inputs(File::open(&training_path)?, &inputs_gen, |inputs_record| {
+        npz_writer.write(inputs_record)
+    })?;
+    npz_writer.finish(BufWriter::new(File::create(&inputs_path)?))?;
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use axum::extract::{Path, Query, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use clap::Parser;
+use coach::batch_queue::{Batch, BatchQueue};
+use coach::coach_helpers::{positions_file_name, read_positions, store_path};
+use coach::data::{prepare_resume, PositionRecord};
+use coach::store::PositionStore;
+use engine::position::{OngoingPhase, Position};
+use engine::probabilities::Probabilities;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+
+/// Hands out the positions of `generate-positions` to workers on other machines and collects their rollouts.
+///
+/// Start the workers with `cargo run -r -p coach --features distributed --bin worker -- --coordinator http://<host>:<port>`.
+/// The training data is written to the same file as by `generate-training-data`. If the coordinator is
+/// restarted, positions which are already in there are not rolled out again.
+///
+/// Only workers with the same token are served. The coordinator listens on localhost by default,
+/// use `--address 0.0.0.0` to reach it from other machines.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    #[arg(long, default_value = "127.0.0.1")]
+    address: String,
+    #[arg(long, default_value_t = 8090)]
+    port: u16,
+    /// `contact` or `race`.
+    #[arg(long, default_value = "race")]
+    phase: String,
+    /// After this many seconds without results, the positions of a batch are handed out again.
+    #[arg(long, default_value_t = 3600)]
+    lease_secs: u64,
+    /// Shared secret of coordinator and workers.
+    #[arg(long, env = "WILDBG_COORDINATOR_TOKEN")]
+    token: String,
+}
+
+struct Coordinator {
+    queue: BatchQueue,
+    csv_writer: csv::Writer<File>,
+    store: PositionStore,
+}
+
+type SharedCoordinator = Arc<Mutex<Coordinator>>;
+
+#[derive(Deserialize)]
+struct LeaseParams {
+    size: usize,
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let phase = match args.phase.as_str() {
+        "contact" => OngoingPhase::Contact,
+        "race" => OngoingPhase::Race,
+        other => {
+            eprintln!("Unknown phase '{other}', use 'contact' or 'race'.");
+            std::process::exit(1);
+        }
+    };
+    let coordinator = open(&phase, Duration::from_secs(args.lease_secs))?;
+    println!(
+        "{} '{:?}' positions still need to be rolled out.",
+        coordinator.queue.remaining(),
+        phase
+    );
+
+    let app = Router::new()
+        .route("/batches", post(lease))
+        .route("/batches/:id/results", post(complete))
+        .with_state(Arc::new(Mutex::new(coordinator)))
+        .layer(middleware::from_fn_with_state(
+            Arc::new(format!("Bearer {}", args.token)),
+            check_token,
+        ));
+    let listener = tokio::net::TcpListener::bind((args.address.as_str(), args.port)).await?;
+    println!("Waiting for workers on {}", listener.local_addr()?);
+    axum::serve(listener, app).await
+}
+
+fn open(phase: &OngoingPhase, lease_duration: Duration) -> std::io::Result<Coordinator> {
+    let positions_path = positions_file_name(phase);
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+
+    let new_file = !std::path::Path::new(&training_path).exists();
+    let mut seen: HashSet<Position> = if new_file {
+        HashSet::new()
+    } else {
+        prepare_resume(std::path::Path::new(&training_path))?
+    };
+    let positions: Vec<Position> = read_positions(&positions_path)?
+        .into_iter()
+        .filter(|position| seen.insert(*position))
+        .collect();
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&training_path)?;
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(file);
+    if new_file {
+        csv_writer.write_record(PositionRecord::csv_header())?;
+        csv_writer.flush()?;
+    }
+    Ok(Coordinator {
+        queue: BatchQueue::new(positions, lease_duration),
+        csv_writer,
+        store: PositionStore::open(std::path::Path::new(&store_path()))?,
+    })
+}
+
+async fn check_token(
+    State(expected): State<Arc<String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorization = request
+        .headers()
+        .get(AUTHORIZATION)
+        .map(|value| value.as_bytes())
+        .unwrap_or_default();
+    // Compared in constant time, so that the response time doesn't reveal how much of the token is right.
+    if bool::from(authorization.ct_eq(expected.as_bytes())) {
+        next.run(request).await
+    } else {
+        (StatusCode::UNAUTHORIZED, "Missing or wrong token.").into_response()
+    }
+}
+
+async fn lease(
+    State(coordinator): State<SharedCoordinator>,
+    Query(params): Query<LeaseParams>,
+) -> Json<Batch> {
+    let mut coordinator = coordinator.lock().unwrap();
+    Json(coordinator.queue.lease(params.size, Instant::now()))
+}
+
+async fn complete(
+    State(coordinator): State<SharedCoordinator>,
+    Path(id): Path<u64>,
+    Json(records): Json<Vec<PositionRecord>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let records = records
+        .iter()
+        .map(|record| {
+            let p = record.probabilities();
+            Probabilities::try_new(p.win_normal, p.win_gammon, p.lose_normal, p.lose_gammon)
+                .map(|probabilities| PositionRecord::new(&record.position(), &probabilities))
+                .map_err(|error| {
+                    let position_id = record.position().position_id();
+                    (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("{position_id}: {error}"),
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // Writing to the store and the CSV file blocks, so it must not happen on the async runtime.
+    tokio::task::spawn_blocking(move || {
+        let mut coordinator = coordinator.lock().unwrap();
+        coordinator
+            .queue
+            .complete(id, &records)
+            .map_err(|message| (StatusCode::CONFLICT, message))?;
+        let internal_error =
+            |error: std::io::Error| (StatusCode::INTERNAL_SERVER_ERROR, error.to_string());
+        for record in &records {
+            coordinator
+                .store
+                .insert(&record.position(), &record.probabilities())
+                .map_err(internal_error)?;
+            coordinator
+                .csv_writer
+                .serialize(record)
+                .map_err(|error| internal_error(error.into()))?;
+        }
+        coordinator.csv_writer.flush().map_err(internal_error)?;
+        println!(
+            "Batch {id} done, {} positions remaining.",
+            coordinator.queue.remaining()
+        );
+        Ok(StatusCode::NO_CONTENT)
+    })
+    .await
+    .map_err(|error| (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))?
+}
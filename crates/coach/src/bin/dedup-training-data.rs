@@ -0,0 +1,41 @@
+use coach::data::PositionRecord;
+use coach::dedup::Deduplicator;
+use engine::position::OngoingPhase;
+use std::fs::File;
+
+/// Merges duplicate positions in the training data, for example after concatenating several runs.
+///
+/// The probabilities of duplicates are averaged and the file is overwritten.
+fn main() -> std::io::Result<()> {
+    // Change the next line in case you want to deduplicate another game phase.
+    let phase = OngoingPhase::Race;
+
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    println!("Read training data from {}", training_path);
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&training_path)?;
+    let mut dedup = Deduplicator::default();
+    for result in csv_reader.deserialize() {
+        let record: PositionRecord = result?;
+        dedup.add_record(&record);
+    }
+
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(File::create(&training_path)?);
+    csv_writer.write_record(PositionRecord::csv_header())?;
+    for record in dedup.records() {
+        csv_writer.serialize(record)?;
+    }
+    csv_writer.flush()?;
+
+    println!(
+        "Merged {} duplicates, {} positions written to {}",
+        dedup.duplicates(),
+        dedup.len(),
+        training_path
+    );
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use clap::Parser;
+use coach::duel::{points, Duel, PairedScore};
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+use std::io::{stdout, Write};
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Plays money games or matches between two pairs of neural nets and reports how much stronger
+/// the first one is, with a 95% confidence interval.
+///
+/// Games are played in pairs with the same dice, each side starting once. This cancels out most of
+/// the luck, so fewer games are needed than with independent dice.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    #[arg(long, default_value = "neural-nets/contact.onnx")]
+    contact1: String,
+    #[arg(long, default_value = "neural-nets/race.onnx")]
+    race1: String,
+    #[arg(long, default_value = "neural-nets/contact.onnx")]
+    contact2: String,
+    #[arg(long, default_value = "neural-nets/race.onnx")]
+    race2: String,
+    /// Number of games or matches, rounded up to an even number.
+    #[arg(long, default_value_t = 10_000)]
+    games: u64,
+    /// Play cubeless matches to this number of points instead of money games.
+    #[arg(long)]
+    match_length: Option<u32>,
+    /// The same seed plays the same dice, so that the results can be reproduced.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+/// Number of pairs played in parallel before the intermediate result is printed.
+const PAIRS_PER_ROUND: u64 = 50;
+
+fn main() {
+    let args = Args::parse();
+    let evaluator1 = CompositeEvaluator::from_file_paths_optimized(&args.contact1, &args.race1)
+        .unwrap_or_exit_with_message();
+    let evaluator2 = CompositeEvaluator::from_file_paths_optimized(&args.contact2, &args.race2)
+        .unwrap_or_exit_with_message();
+    let duel = Duel::new(evaluator1, evaluator2);
+
+    let pairs = args.games.div_ceil(2);
+    let mut score = PairedScore::default();
+    for round_start in (0..pairs).step_by(PAIRS_PER_ROUND as usize) {
+        let round_end = (round_start + PAIRS_PER_ROUND).min(pairs);
+        let round = duel.play_pairs(
+            round_start..round_end,
+            args.seed,
+            |duel, dice_gen| match args.match_length {
+                None => duel
+                    .duel_results(dice_gen)
+                    .map(|result| points(&result) as f64),
+                Some(length) => duel
+                    .match_pair(length, dice_gen)
+                    .map(|won| if won { 1.0 } else { 0.0 }),
+            },
+        );
+        score = score.combine(&round);
+        print!("\r{}", summary(&score, args.match_length));
+        stdout().flush().unwrap();
+    }
+    println!();
+}
+
+fn summary(score: &PairedScore, match_length: Option<u32>) -> String {
+    match match_length {
+        None => format!(
+            "After {} money games, nets 1 score {:+.4} ± {:.4} points per game.  ",
+            score.games(),
+            score.mean(),
+            score.confidence_95(),
+        ),
+        Some(length) => format!(
+            "After {} matches to {} points, nets 1 win {:.2}% ± {:.2}%.  ",
+            score.games(),
+            length,
+            100.0 * score.mean(),
+            100.0 * score.confidence_95(),
+        ),
+    }
+}
@@ -0,0 +1,39 @@
+use coach::mat::{self_play_game, MatMatch};
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use engine::dice_gen::FastrandDice;
+use logic::game_state::Player;
+use mimalloc::MiMalloc;
+use std::fs;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// This binary plays cubeless games against itself and writes them to a `.mat` file.
+///
+/// The file can be imported into GNUBG or XG to review the games.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure how many games you want and where they are stored.
+    let amount = 10;
+    let path = "training-data/self-play.mat";
+    let evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
+
+    let mut dice_gen = FastrandDice::new();
+    let mut session = MatMatch {
+        length: None,
+        players: ["wildbg".to_string(), "wildbg2".to_string()],
+        games: Vec::new(),
+    };
+    for index in 0..amount {
+        let first = if index % 2 == 0 {
+            Player::One
+        } else {
+            Player::Two
+        };
+        session.push(self_play_game(&evaluator, &mut dice_gen, first));
+    }
+    _ = fs::create_dir("training-data");
+    fs::write(path, session.to_string())?;
+    println!("Wrote {} self play games to {}.", amount, path);
+    Ok(())
+}
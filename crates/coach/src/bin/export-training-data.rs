@@ -0,0 +1,31 @@
+use coach::coach_helpers::export_file_name;
+use coach::export::{export, SCHEMA_VERSION};
+use engine::position::OngoingPhase;
+use std::fs::File;
+use std::io::BufReader;
+
+/// This binary prepares training data for publishing.
+///
+/// Columns which are not part of the training data are dropped and the rows are shuffled.
+/// The schema version is part of the name of the exported file.
+fn main() -> std::io::Result<()> {
+    // Change the next line in case you want to export data for another game phase.
+    let phase = OngoingPhase::Race;
+
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    let export_path = export_file_name(&phase, SCHEMA_VERSION);
+    println!(
+        "Read training data from {} and write anonymized data to {}",
+        training_path, export_path
+    );
+
+    // Not seeded on purpose, the order of the exported rows should not be reproducible.
+    let count = export(
+        BufReader::new(File::open(&training_path)?),
+        File::create(&export_path)?,
+        &mut fastrand::Rng::new(),
+    )?;
+
+    println!("Exported {} positions.", count);
+    Ok(())
+}
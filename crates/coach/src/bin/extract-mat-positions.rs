@@ -0,0 +1,54 @@
+use coach::coach_helpers::positions_file_name;
+use coach::mat::parse;
+use engine::position::{GamePhase, OngoingPhase};
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+
+/// This binary collects positions from human matches in `.mat` format.
+///
+/// All games are replayed and all positions of the given phase are written to the same file
+/// as `generate-positions.rs` does. Later `generate-training-data.rs` can be used to roll them out.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure where the matches are and which positions you want.
+    let matches_directory = "training-data/matches";
+    let phase = OngoingPhase::Contact;
+
+    let mut positions = HashSet::new();
+    let mut match_count = 0;
+    for entry in fs::read_dir(matches_directory)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "mat") {
+            continue;
+        }
+        let games = parse(&fs::read_to_string(&path)?).and_then(|mat_match| mat_match.replay());
+        match games {
+            Ok(games) => {
+                match_count += 1;
+                let states = games.iter().flatten();
+                positions.extend(states.map(|state| *state.position()).filter(
+                    |position| matches!(position.game_phase(), GamePhase::Ongoing(p) if p == phase),
+                ));
+            }
+            Err(error) => println!("Skipping {}: {}", path.display(), error),
+        }
+    }
+
+    let path = positions_file_name(&phase);
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(File::create(&path)?);
+    csv_writer.write_record(["position_id"])?;
+    for position in &positions {
+        csv_writer.write_record([position.position_id()])?;
+    }
+    csv_writer.flush()?;
+    println!(
+        "Wrote {} '{:?}' positions from {} matches to {}.",
+        positions.len(),
+        phase,
+        match_count,
+        path
+    );
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use clap::Parser;
+use coach::data::PositionRecord;
+use coach::position_class::PositionClass;
+use std::fs::File;
+use std::path::PathBuf;
+
+/// Splits training data into one file per position class, for training the nets of each class.
+///
+/// For example `training-data/contact.csv` is split into `training-data/contact-class.csv`,
+/// `training-data/crashed-class.csv` and `training-data/race-class.csv`.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// CSV file with position IDs and probabilities.
+    #[arg(long, default_value = "training-data/contact.csv")]
+    input: PathBuf,
+    /// Classes for which a file is written, comma separated. Positions of other classes are dropped.
+    #[arg(long, value_delimiter = ',', default_value = "contact,crashed,race")]
+    classes: Vec<PositionClass>,
+    /// Only keep positions in which one of the players has a prime of at least this length.
+    #[arg(long)]
+    min_prime: Option<usize>,
+    /// Directory for the files `<class>-class.csv`.
+    #[arg(long, default_value = "training-data")]
+    output_dir: PathBuf,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+
+    let mut writers = Vec::new();
+    for class in &args.classes {
+        let path = args.output_dir.join(format!("{class}-class.csv"));
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(File::create(&path)?);
+        writer.write_record(PositionRecord::csv_header())?;
+        writers.push((*class, path, writer, 0usize));
+    }
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&args.input)?;
+    for result in csv_reader.deserialize() {
+        let record: PositionRecord = result?;
+        let position = record.position();
+        if let Some(min_prime) = args.min_prime {
+            let prime = position
+                .longest_prime()
+                .max(position.sides_switched().longest_prime());
+            if prime < min_prime {
+                continue;
+            }
+        }
+        let class = PositionClass::of(&position);
+        if let Some((_, _, writer, count)) = writers
+            .iter_mut()
+            .find(|(wanted, _, _, _)| Some(*wanted) == class)
+        {
+            writer.serialize(record)?;
+            *count += 1;
+        }
+    }
+
+    for (class, path, mut writer, count) in writers {
+        writer.flush()?;
+        println!("{count} '{class}' positions written to {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use coach::data::PositionRecord;
+use coach::position_index::PositionIndex;
+use engine::inputs::RaceInputsGen;
+use engine::pos;
+use engine::position::OngoingPhase;
+
+/// This binary looks for the positions in the training data which are most similar to a given position.
+///
+/// Similarity is measured by the distance of the neural net inputs. Comparing the labels of similar
+/// positions helps to understand why the net evaluates a position the way it does.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure which position you are interested in.
+    let phase = OngoingPhase::Race;
+    let inputs_gen = RaceInputsGen {};
+    let position = pos!(x 6:4, 5:3, 4:3, 3:2; o 19:4, 20:3, 21:3, 22:2);
+    let count = 10;
+
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    println!("Read training data from {}", training_path);
+    let mut index = PositionIndex::new(inputs_gen, 0);
+    for record in csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&training_path)?
+        .deserialize()
+    {
+        let record: PositionRecord = record?;
+        index.insert(record.position(), record.probabilities());
+    }
+
+    println!(
+        "Positions most similar to {} out of {}:",
+        position.position_id(),
+        index.len()
+    );
+    for neighbor in index.nearest(&position, count) {
+        println!(
+            "{} distance {:.3} {:?}",
+            neighbor.position.position_id(),
+            neighbor.distance,
+            neighbor.probabilities
+        );
+    }
+    Ok(())
+}
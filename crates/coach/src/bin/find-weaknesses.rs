@@ -0,0 +1,85 @@
+use clap::Parser;
+use coach::data::PositionRecord;
+use coach::unwrap::UnwrapHelper;
+use coach::weakness::{successors, worst_positions};
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+use std::collections::HashSet;
+use std::fs::File;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Finds the positions in rollout data which the neural nets evaluate worst.
+///
+/// The positions are written in the format of `generate-positions`, so that they can be rolled out
+/// with `generate-training-data --input` for the next training batch. As the positions themselves
+/// have already been rolled out, `--successors` adds the positions after the best move for each roll.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// CSV file with position IDs and rollout probabilities.
+    #[arg(long, default_value = "training-data/race.csv")]
+    input: String,
+    #[arg(long, default_value = "neural-nets/contact.onnx")]
+    contact: String,
+    #[arg(long, default_value = "neural-nets/race.onnx")]
+    race: String,
+    /// Number of positions with the biggest equity errors.
+    #[arg(long, default_value_t = 1000)]
+    amount: usize,
+    /// Also write the positions after the best move for each of the 21 rolls.
+    #[arg(long)]
+    successors: bool,
+    #[arg(long, default_value = "training-data/weak-positions.csv")]
+    output: String,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let evaluator = CompositeEvaluator::from_file_paths_optimized(&args.contact, &args.race)
+        .unwrap_or_exit_with_message();
+
+    println!("Read rollouts from {}", args.input);
+    let records: Vec<PositionRecord> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&args.input)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    let worst = worst_positions(&evaluator, &records, args.amount);
+
+    println!("Biggest equity errors out of {} positions:", records.len());
+    for discrepancy in worst.iter().take(10) {
+        println!(
+            "{} net {:+.3} rollout {:+.3} error {:.3}",
+            discrepancy.position.position_id(),
+            discrepancy.net.equity(),
+            discrepancy.rollout.equity(),
+            discrepancy.error()
+        );
+    }
+
+    let mut positions = Vec::new();
+    let mut unique = HashSet::new();
+    for discrepancy in &worst {
+        let mut add = |position| {
+            if unique.insert(position) {
+                positions.push(position);
+            }
+        };
+        add(discrepancy.position);
+        if args.successors {
+            successors(&evaluator, &discrepancy.position)
+                .into_iter()
+                .for_each(add);
+        }
+    }
+    let mut csv_writer = csv::Writer::from_writer(File::create(&args.output)?);
+    csv_writer.write_record(["position_id"])?;
+    for position in &positions {
+        csv_writer.write_record([position.position_id()])?;
+    }
+    csv_writer.flush()?;
+    println!("{} positions written to {}", positions.len(), args.output);
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use clap::Parser;
+use coach::coach_helpers::{positions_file_name, read_positions};
+use coach::config::parse_phase;
+use coach::cube_rollout::{CubeOwner, CubeRecord, CubefulRollout};
+use coach::progress::{Progress, ProgressMode};
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use engine::dice_gen::FastrandDice;
+use engine::position::GameState::Ongoing;
+use engine::position::Position;
+use logic::match_equity::MatchScore;
+use mimalloc::MiMalloc;
+use std::fs::File;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Rolls out cube decisions of money games or at a match score, as training data for cube nets or
+/// calibration.
+///
+/// The positions are read from the output of `generate-positions`. For each position the equities
+/// of no double, double/take and double/pass are written to `training-data/<phase>-cube.csv`,
+/// together with the correct cube action. With `--owned` each position is rolled out a second
+/// time with a cube of 2 owned by the player on roll. With `--away` the file name contains the
+/// score, for example `training-data/race-cube-3-5-away.csv`.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// `contact` or `race`.
+    #[arg(long, default_value = "race")]
+    phase: String,
+    /// Number of games per position and cube action.
+    #[arg(long, default_value_t = 1296)]
+    games: usize,
+    /// Also roll out each position with the cube owned by the player on roll.
+    #[arg(long)]
+    owned: bool,
+    /// Points still needed by the player on roll and by the opponent, for example `3,5`.
+    /// Money play if missing.
+    #[arg(long, value_delimiter = ',', num_args = 2)]
+    away: Option<Vec<u32>>,
+    /// Seed of the rollouts, by default a random one.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// How to show the progress. By default a bar in a terminal, otherwise a line every 30 seconds.
+    #[arg(long, value_enum)]
+    progress: Option<ProgressMode>,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let phase = parse_phase(&args.phase).unwrap_or_exit_with_message();
+    let positions_path = positions_file_name(&phase);
+    let score = args.away.map(|away| match away[..] {
+        [x_away, o_away] if x_away > 0 && o_away > 0 => MatchScore {
+            x_away,
+            o_away,
+            crawford: false,
+        },
+        _ => {
+            eprintln!("Both players must still need at least one point.");
+            std::process::exit(1)
+        }
+    });
+    let cube_path = match &score {
+        None => format!("training-data/{:?}-cube.csv", phase),
+        Some(score) => format!(
+            "training-data/{:?}-cube-{}-{}-away.csv",
+            phase, score.x_away, score.o_away
+        ),
+    }
+    .to_lowercase();
+    println!("Read positions from {positions_path} and write cube decisions to {cube_path}");
+
+    let positions: Vec<Position> = read_positions(&positions_path)?
+        .into_iter()
+        .filter(|position| position.game_state() == Ongoing)
+        .collect();
+    let mut cube_states = vec![(1, CubeOwner::Centered)];
+    if args.owned {
+        cube_states.push((2, CubeOwner::OnRoll));
+    }
+
+    let evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
+    let seed = args.seed.unwrap_or_else(FastrandDice::random_seed);
+    let rollout = CubefulRollout::new(evaluator, args.games, seed);
+    let mut csv_writer = csv::Writer::from_writer(File::create(&cube_path)?);
+    let mode = args.progress.unwrap_or_else(ProgressMode::from_env);
+    let mut progress = Progress::new(positions.len() * cube_states.len(), "rollouts", mode);
+    for position in &positions {
+        for (cube_value, cube_owner) in &cube_states {
+            let equities = rollout.eval(position, score.as_ref(), *cube_value, *cube_owner);
+            csv_writer.serialize(CubeRecord::new(
+                position,
+                score.as_ref(),
+                *cube_value,
+                *cube_owner,
+                &equities,
+            ))?;
+            csv_writer.flush()?;
+            progress.inc()?;
+        }
+    }
+    progress.finish();
+    println!("Done!");
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use coach::coach_helpers::duration;
+use coach::hypergammon_solver::ExactSolver;
+use engine::position::HYPERGAMMON_STARTING;
+use mimalloc::MiMalloc;
+use std::fs::File;
+use std::time::Instant;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// This binary calculates exact probabilities for all hypergammon positions.
+///
+/// The result is written to a binary file which can be loaded with `HypergammonEvaluator::from_file_path`.
+fn main() -> std::io::Result<()> {
+    // Change the next two lines to configure the precision and the output file.
+    let max_change = 0.000_001;
+    let path = "neural-nets/hypergammon.db";
+
+    let start = Instant::now();
+    let mut solver = ExactSolver::new(HYPERGAMMON_STARTING);
+    println!(
+        "Found {} hypergammon positions in {}.",
+        solver.len(),
+        duration(start.elapsed().as_secs())
+    );
+
+    let mut iteration = 0;
+    loop {
+        iteration += 1;
+        let change = solver.iterate();
+        println!(
+            "Iteration {}: biggest change in equity {:.7}. Time elapsed: {}.",
+            iteration,
+            change,
+            duration(start.elapsed().as_secs())
+        );
+        if change < max_change {
+            break;
+        }
+    }
+
+    solver.into_evaluator().write(&mut File::create(path)?)?;
+    println!("Database written to {}.", path);
+    Ok(())
+}
@@ -1,9 +1,11 @@
-use coach::coach_helpers::{duration, positions_file_name};
+use clap::Parser;
+use coach::coach_helpers::{duration, quarantine_file_name, self_play_stats_file_name};
+use coach::config::{parse_phase, CoachConfig, PositionsConfig, DEFAULT_CONFIG_PATH};
 use coach::position_finder::PositionFinder;
+use coach::quarantine::QuarantineEvaluator;
 use coach::unwrap::UnwrapHelper;
 use engine::composite::CompositeEvaluator;
 use engine::evaluator::Evaluator;
-use engine::position::OngoingPhase;
 use mimalloc::MiMalloc;
 use std::fs::File;
 use std::time::Instant;
@@ -15,21 +17,61 @@ static GLOBAL: MiMalloc = MiMalloc;
 ///
 /// The data is persisted with position ID only.
 /// Later `generate-training-data.rs` can be used to roll out these positions.
+///
+/// The parameters are read from the table `[positions]` of the configuration file,
+/// the options below override them.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// TOML file with the parameters. If it doesn't exist, the defaults are used.
+    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
+    config: String,
+    /// `contact` or `race`.
+    #[arg(long)]
+    phase: Option<String>,
+    /// Number of positions to find.
+    #[arg(long)]
+    amount: Option<usize>,
+    /// Higher values let the self play games choose more sub-optimal moves, 0.0 always plays the best move.
+    #[arg(long)]
+    temperature: Option<f32>,
+    /// Name under which the statistics of the self play games are stored, typically the name of the nets.
+    #[arg(long)]
+    run: Option<String>,
+    /// File for the positions, by default `training-data/<phase>-positions.csv`.
+    #[arg(long)]
+    output: Option<String>,
+}
+
 fn main() -> std::io::Result<()> {
-    // Change the next couple of lines to configure what, how and how much you want to roll out.
-    let phase = OngoingPhase::Race;
-    let amount = 10;
+    let args = Args::parse();
+    let mut config = CoachConfig::load(&args.config).unwrap_or_exit_with_message();
+    let positions = &mut config.positions;
+    if let Some(phase) = args.phase {
+        positions.phase = parse_phase(&phase).unwrap_or_exit_with_message();
+    }
+    if let Some(amount) = args.amount {
+        positions.amount = amount;
+    }
+    if let Some(temperature) = args.temperature {
+        positions.temperature = temperature;
+    }
+    if let Some(run) = args.run {
+        positions.run = run;
+    }
+    if let Some(output) = args.output {
+        positions.set_output(output);
+    }
     let finder_evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
-    find_and_roll_out(finder_evaluator, amount, phase)?;
+    find_and_roll_out(finder_evaluator, &config.positions)?;
     Ok(())
 }
 
 fn find_and_roll_out<T: Evaluator>(
     finder_evaluator: T,
-    amount: usize,
-    phase: OngoingPhase,
+    config: &PositionsConfig,
 ) -> std::io::Result<()> {
-    let path = positions_file_name(&phase);
+    let path = config.output();
     _ = std::fs::create_dir("training-data");
     _ = std::fs::remove_file(&path);
 
@@ -40,17 +82,27 @@ fn find_and_roll_out<T: Evaluator>(
 
     println!(
         "Find {} '{:?}' positions and write them to {}.",
-        amount, phase, path
+        config.amount, config.phase, path
     );
 
     let find_start = Instant::now();
-    let mut finder = PositionFinder::with_random_dice(finder_evaluator);
-    let positions = finder.find_positions(amount, phase);
+    let finder_evaluator = QuarantineEvaluator::new(finder_evaluator, &quarantine_file_name())?;
+    let mut finder =
+        PositionFinder::with_random_dice(finder_evaluator).with_temperature(config.temperature);
+    let positions = finder.find_positions(config.amount, config.phase);
     for position in positions {
         csv_writer.write_record([position.position_id()])?;
     }
     csv_writer.flush()?;
 
+    let stats_path = self_play_stats_file_name();
+    let record = finder.stats().record(&config.run);
+    record.append_to(&stats_path)?;
+    println!(
+        "Self play statistics appended to {}:\n{}",
+        stats_path, record
+    );
+
     println!(
         "All positions found in {}.",
         duration(find_start.elapsed().as_secs())
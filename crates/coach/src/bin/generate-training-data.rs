@@ -1,13 +1,20 @@
-use coach::coach_helpers::{positions_file_name, print_progress};
-use coach::data::PositionRecord;
+use clap::Parser;
+use coach::coach_helpers::{quarantine_file_name, read_positions, store_path};
+use coach::config::{parse_phase, CoachConfig, TrainingDataConfig, DEFAULT_CONFIG_PATH};
+use coach::data::{prepare_resume, PositionRecord};
+use coach::labels::Labeler;
+use coach::progress::{Progress, ProgressMode};
+use coach::quarantine::QuarantineEvaluator;
 use coach::rollout::RolloutEvaluator;
+use coach::store::PositionStore;
 use coach::unwrap::UnwrapHelper;
 use engine::composite::CompositeEvaluator;
 use engine::evaluator::Evaluator;
-use engine::position::{OngoingPhase, Position};
+use engine::position::Position;
 use mimalloc::MiMalloc;
-use std::fs::File;
-use std::time::Instant;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -16,13 +23,79 @@ static GLOBAL: MiMalloc = MiMalloc;
 ///
 /// The data is persisted with position ID and the "classic" 5 values for the probabilities.
 /// The resulting file cannot be read by the Python scripts, they have to be converted first with `convert-to-inputs.rs`.
+///
+/// The parameters are read from the table `[training-data]` of the configuration file,
+/// the options below override them.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// TOML file with the parameters. If it doesn't exist, the defaults are used.
+    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
+    config: String,
+    /// `contact` or `race`.
+    #[arg(long)]
+    phase: Option<String>,
+    /// Rollouts play 1296 games per repetition and position.
+    #[arg(long)]
+    repetitions: Option<usize>,
+    /// Seed of the rollouts, by default a random one.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Number of threads for the rollouts, by default all cores.
+    #[arg(long)]
+    threads: Option<usize>,
+    /// File with the positions to roll out, by default `training-data/<phase>-positions.csv`.
+    #[arg(long)]
+    input: Option<String>,
+    /// File for the training data, by default `training-data/<phase>.csv`.
+    #[arg(long)]
+    output: Option<String>,
+    /// Continue an interrupted run: Keep the existing training data and only roll out the positions which are not in there yet.
+    #[arg(long)]
+    resume: bool,
+    /// How to show the progress. By default a bar in a terminal, otherwise a line every 30 seconds.
+    #[arg(long, value_enum)]
+    progress: Option<ProgressMode>,
+}
+
 fn main() -> std::io::Result<()> {
-    // Change the next couple of lines to configure what, how and how much you want to roll out.
-    let phase = OngoingPhase::Race;
-    let rollout_evaluator = CompositeEvaluator::try_default()
-        .map(RolloutEvaluator::with_evaluator)
-        .unwrap_or_exit_with_message();
-    find_and_roll_out(rollout_evaluator, phase)?;
+    let args = Args::parse();
+    let mut config = CoachConfig::load(&args.config).unwrap_or_exit_with_message();
+    if let Some(threads) = args.threads {
+        config.threads = Some(threads);
+    }
+    config.init_thread_pool().unwrap_or_exit_with_message();
+    let training_data = &mut config.training_data;
+    if let Some(phase) = args.phase {
+        training_data.phase = parse_phase(&phase).unwrap_or_exit_with_message();
+    }
+    if let Some(repetitions) = args.repetitions {
+        training_data.repetitions = repetitions;
+    }
+    if let Some(seed) = args.seed {
+        training_data.seed = Some(seed);
+    }
+    if let Some(input) = args.input {
+        training_data.set_input(input);
+    }
+    if let Some(output) = args.output {
+        training_data.set_output(output);
+    }
+
+    let evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
+    _ = std::fs::create_dir("training-data");
+    let evaluator = QuarantineEvaluator::new(evaluator, &quarantine_file_name())?;
+    let rollout_evaluator = match training_data.seed {
+        Some(seed) => RolloutEvaluator::with_evaluator_and_seed(evaluator, seed),
+        None => RolloutEvaluator::with_evaluator(evaluator),
+    }
+    .with_repetitions(training_data.repetitions);
+    find_and_roll_out(
+        Labeler::new(rollout_evaluator, training_data.label_source),
+        training_data,
+        args.resume,
+        args.progress.unwrap_or_else(ProgressMode::from_env),
+    )?;
 
     println!("\nDone!");
     Ok(())
@@ -30,40 +103,58 @@ fn main() -> std::io::Result<()> {
 
 fn find_and_roll_out<T: Evaluator>(
     rollout_evaluator: T,
-    phase: OngoingPhase,
+    config: &TrainingDataConfig,
+    resume: bool,
+    progress_mode: ProgressMode,
 ) -> std::io::Result<()> {
-    let positions_path = positions_file_name(&phase);
-    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    let phase = config.phase;
+    let positions_path = config.input();
+    let training_path = config.output();
 
     println!(
         "Read positions from {} and write training data to {}",
         positions_path, training_path
     );
 
-    let reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(&positions_path)?;
-    let positions: Vec<Position> = reader
-        .into_records()
-        .map(|record| Position::from_id(record.unwrap().as_slice().to_string()))
+    let positions = read_positions(&positions_path)?;
+    // Each record is flushed right after its rollout, so the training data itself tells us what's done.
+    let mut seen: HashSet<Position> = if resume {
+        prepare_resume(Path::new(&training_path))?
+    } else {
+        HashSet::new()
+    };
+    let done = seen.len();
+    // The positions file might contain duplicates if several runs were concatenated.
+    let positions: Vec<Position> = positions
+        .into_iter()
+        .filter(|position| seen.insert(*position))
         .collect();
 
     _ = std::fs::create_dir("training-data");
-    _ = std::fs::remove_file(&training_path);
-    let mut csv_writer = csv::WriterBuilder::new()
-        .has_headers(false)
-        .from_writer(File::create(&training_path)?);
-    csv_writer.write_record(PositionRecord::csv_header())?;
+    let mut builder = csv::WriterBuilder::new();
+    builder.has_headers(false);
+    let mut csv_writer = if resume {
+        println!("{} positions have already been rolled out.", done);
+        builder.from_writer(OpenOptions::new().append(true).open(&training_path)?)
+    } else {
+        let mut csv_writer = builder.from_writer(File::create(&training_path)?);
+        csv_writer.write_record(PositionRecord::csv_header())?;
+        csv_writer
+    };
+    // The store collects the training data of all runs, the CSV file only the one of this run.
+    let mut store = PositionStore::open(Path::new(&store_path()))?;
 
     println!("Roll out {} '{:?}' positions", positions.len(), phase);
 
-    let rollout_start = Instant::now();
-    for (i, position) in positions.iter().enumerate() {
+    let mut progress = Progress::new(positions.len(), "positions", progress_mode);
+    for position in positions.iter() {
         let probabilities = rollout_evaluator.eval(position);
+        store.insert(position, &probabilities)?;
         let record = PositionRecord::new(position, &probabilities);
         csv_writer.serialize(record)?;
         csv_writer.flush()?;
-        print_progress(i, positions.len(), rollout_start)?;
+        progress.inc()?;
     }
+    progress.finish();
     Ok(())
 }
@@ -0,0 +1,64 @@
+use clap::Parser;
+use coach::gnubg_benchmark::{read_entries, score};
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Measures the strength of neural nets with the benchmark databases of GNU Backgammon.
+///
+/// Download for example `contact.bm`, `crashed.bm` and `race.bm` from
+/// https://alpha.gnu.org/gnu/gnubg/nn-training/ and pass them as arguments.
+/// For each file the error rate is printed: the average equity lost per move in thousandths,
+/// compared to the rolled out best move. Lower is better.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// Benchmark databases.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    #[arg(long, default_value = "neural-nets/contact.onnx")]
+    contact: String,
+    #[arg(long, default_value = "neural-nets/race.onnx")]
+    race: String,
+    /// Only use the first entries of each file, for quicker results.
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let evaluator = CompositeEvaluator::from_file_paths_optimized(&args.contact, &args.race)
+        .unwrap_or_exit_with_message();
+
+    for path in &args.files {
+        let mut entries =
+            read_entries(BufReader::new(File::open(path)?)).unwrap_or_exit_with_message();
+        if let Some(limit) = args.limit {
+            entries.truncate(limit);
+        }
+        let score = score(&evaluator, &entries);
+        print!("{}: ", path.display());
+        if score.moves > 0 {
+            print!(
+                "{} moves with an error rate of {:.2}. ",
+                score.moves,
+                score.error_rate()
+            );
+        }
+        if score.rollouts > 0 {
+            print!(
+                "{} positions with an equity error of {:.2}.",
+                score.rollouts,
+                score.equity_error_rate()
+            );
+        }
+        println!();
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use clap::Parser;
+use coach::config::parse_phase;
+use coach::data::PositionRecord;
+use coach::importance::ImportanceData;
+use coach::label_audit::sample;
+use coach::unwrap::UnwrapHelper;
+use engine::inputs::{ContactInputsGen, InputsGen, RaceInputsGen};
+use engine::onnx::OnnxEvaluator;
+use engine::position::OngoingPhase;
+use mimalloc::MiMalloc;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Measures how much a neural net depends on each of its inputs and prints a ranking.
+///
+/// The values of one input at a time are shuffled between the rollout labeled positions. Inputs
+/// for which the equity error barely increases are candidates for removal from the encoding.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// `contact` or `race`.
+    #[arg(long, default_value = "race")]
+    phase: String,
+    /// CSV file with position IDs and rollout probabilities, by default `training-data/<phase>.csv`.
+    #[arg(long)]
+    input: Option<String>,
+    /// By default `neural-nets/<phase>.onnx`.
+    #[arg(long)]
+    model: Option<String>,
+    /// Randomly picked fraction of the positions in `input`.
+    #[arg(long, default_value_t = 1.0)]
+    fraction: f32,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Number of inputs to print, by default all.
+    #[arg(long)]
+    top: Option<usize>,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let phase = parse_phase(&args.phase).unwrap_or_exit_with_message();
+    match phase {
+        OngoingPhase::Contact => run(&args, ContactInputsGen {}),
+        OngoingPhase::Race => run(&args, RaceInputsGen {}),
+    }
+}
+
+fn run<T: InputsGen + Sync>(args: &Args, inputs_gen: T) -> std::io::Result<()> {
+    let input = args
+        .input
+        .clone()
+        .unwrap_or_else(|| format!("training-data/{}.csv", args.phase));
+    let model = args
+        .model
+        .clone()
+        .unwrap_or_else(|| format!("neural-nets/{}.onnx", args.phase));
+    println!("Read rollouts from {input}");
+    let records: Vec<PositionRecord> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&input)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    let sample = sample(&records, args.fraction, args.seed);
+    let positions: Vec<_> = sample.iter().map(|record| record.position()).collect();
+    let labels = sample.iter().map(|record| record.probabilities()).collect();
+    let inputs = inputs_gen.inputs_for_all(&positions);
+    let data = ImportanceData::new(T::NUM_INPUTS, inputs, labels);
+    let evaluator = OnnxEvaluator::from_file_path(&model, inputs_gen).unwrap_or_exit_with_message();
+
+    let predict = |inputs: Vec<f32>| evaluator.eval_inputs(inputs);
+    let baseline = data.baseline_error(&predict);
+    println!(
+        "Mean absolute equity error of {model} on {} positions: {baseline:.5}\n",
+        positions.len()
+    );
+    let importances = data.permutation_importance(&predict, args.seed);
+    println!("rank  input  error increase");
+    for (rank, importance) in importances
+        .iter()
+        .take(args.top.unwrap_or(T::NUM_INPUTS))
+        .enumerate()
+    {
+        println!(
+            "{:4}  {:5}  {:+.5}",
+            rank + 1,
+            importance.input,
+            importance.importance
+        );
+    }
+    let unused = importances
+        .iter()
+        .filter(|importance| importance.importance <= 0.0)
+        .count();
+    println!(
+        "\n{unused} of {} inputs don't increase the error",
+        T::NUM_INPUTS
+    );
+    Ok(())
+}
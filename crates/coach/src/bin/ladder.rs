@@ -0,0 +1,102 @@
+use clap::{Parser, Subcommand};
+use coach::coach_helpers::{ladder_file_name, ladder_results_file_name};
+use coach::duel::{points, Duel};
+use coach::ladder::Ladder;
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Keeps Elo ratings of all generations of neural nets in `training-data/ladder.csv` and the
+/// results they are based on in `training-data/ladder-results.csv`.
+///
+/// Each new generation plays money games against the best rated ones on the ladder. Ratings
+/// are based on the share of won games; gammons count as normal wins here.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rates new nets and adds them to the ladder.
+    Add {
+        /// Name of the generation, for example `gen-7`.
+        name: String,
+        #[arg(long)]
+        contact: String,
+        #[arg(long)]
+        race: String,
+        /// Number of games against each opponent.
+        #[arg(long, default_value_t = 10_000)]
+        games: u64,
+        /// Number of the best rated entries to play against.
+        #[arg(long, default_value_t = 3)]
+        opponents: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Prints all entries, the best rated first.
+    Show,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let (path, results_path) = (ladder_file_name(), ladder_results_file_name());
+    let mut ladder = Ladder::load(&path, &results_path)?;
+    match args.command {
+        Command::Add {
+            name,
+            contact,
+            race,
+            games,
+            opponents,
+            seed,
+        } => {
+            if ladder.contains(&name) {
+                eprintln!("'{name}' is already on the ladder.");
+                std::process::exit(1);
+            }
+            let mut results = Vec::new();
+            for opponent in ladder.opponents(opponents) {
+                let contender = CompositeEvaluator::from_file_paths_optimized(&contact, &race)
+                    .unwrap_or_exit_with_message();
+                let evaluator = CompositeEvaluator::from_file_paths_optimized(
+                    &opponent.contact,
+                    &opponent.race,
+                )
+                .unwrap_or_exit_with_message();
+                let duel = Duel::new(contender, evaluator);
+                let score = duel.play_pairs(0..games.div_ceil(2), seed, |duel, dice_gen| {
+                    duel.duel_results(dice_gen)
+                        .map(|result| if points(&result) > 0 { 1.0 } else { 0.0 })
+                });
+                println!(
+                    "'{name}' wins {:.2}% ± {:.2}% of {} games against '{}'.",
+                    100.0 * score.mean(),
+                    100.0 * score.confidence_95(),
+                    score.games(),
+                    opponent.name,
+                );
+                results.push((opponent.name.clone(), score.mean(), score.games()));
+            }
+            let entry = ladder
+                .add(&name, &contact, &race, &results)
+                .unwrap_or_exit_with_message();
+            println!("'{name}' has a rating of {:.0}.", entry.rating);
+            _ = std::fs::create_dir("training-data");
+            ladder.save(&path, &results_path)?;
+        }
+        Command::Show => {
+            println!("{:<20} {:>7} {:>8}   nets", "name", "rating", "games");
+            for entry in ladder.opponents(ladder.entries().len()) {
+                println!("{entry}");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use coach::coach_helpers::store_path;
+use coach::data::PositionRecord;
+use coach::store::PositionStore;
+use engine::position::OngoingPhase;
+use std::path::Path;
+
+/// Imports training data from CSV into the `PositionStore`, for example data of older runs.
+///
+/// Positions already in the store get the probabilities from the CSV file.
+fn main() -> std::io::Result<()> {
+    // Change the next line in case you want to import another game phase.
+    let phase = OngoingPhase::Race;
+
+    let training_path = format!("training-data/{:?}.csv", phase).to_lowercase();
+    let store_path = store_path();
+    println!(
+        "Read training data from {} and store it in {}",
+        training_path, store_path
+    );
+
+    let mut store = PositionStore::open(Path::new(&store_path))?;
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&training_path)?;
+    let mut replaced = 0;
+    for result in csv_reader.deserialize() {
+        let record: PositionRecord = result?;
+        if !store.insert(&record.position(), &record.probabilities())? {
+            replaced += 1;
+        }
+    }
+
+    println!(
+        "{} positions replaced, the store now contains {} '{:?}' positions.",
+        replaced,
+        store.len(&phase)?,
+        phase
+    );
+    Ok(())
+}
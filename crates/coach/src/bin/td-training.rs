@@ -0,0 +1,71 @@
+use clap::Parser;
+use coach::onnx_export::to_onnx;
+use coach::progress::{Progress, ProgressMode};
+use coach::td::TdTrainer;
+use coach::training::Mlp;
+use engine::dice_gen::FastrandDice;
+use engine::inputs::{ContactInputsGen, InputsGen};
+
+/// Trains a new neural net from scratch with TD(λ) during self play, without any training data.
+///
+/// One net with contact inputs is trained for all positions. Every `--save-every` games it's
+/// written to `training-data/td-<games>.onnx`. Such a net can then be used as contact net to
+/// generate the first rollout data for the usual supervised training.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    #[arg(long, default_value_t = 100_000)]
+    games: usize,
+    #[arg(long, default_value_t = 0.7)]
+    lambda: f32,
+    #[arg(long, default_value_t = 100e-6)]
+    learning_rate: f32,
+    /// Sizes of the hidden layers, comma separated.
+    #[arg(long, value_delimiter = ',', default_value = "300,250,200")]
+    hidden: Vec<usize>,
+    #[arg(long, default_value_t = 10_000)]
+    save_every: usize,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// How to show the progress. By default a bar in a terminal, otherwise a line every 30 seconds.
+    #[arg(long, value_enum)]
+    progress: Option<ProgressMode>,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    _ = std::fs::create_dir("training-data");
+    let mlp = Mlp::new(ContactInputsGen::NUM_INPUTS, &args.hidden, args.seed);
+    let mut trainer = TdTrainer::new(
+        mlp,
+        args.learning_rate,
+        args.lambda,
+        ContactInputsGen {},
+        FastrandDice::with_seed(args.seed),
+    );
+
+    let mode = args.progress.unwrap_or_else(ProgressMode::from_env);
+    let mut progress = Progress::new(args.games, "games", mode);
+    let mut loss_sum = 0.0;
+    for game in 1..=args.games {
+        loss_sum += trainer.train_one_game();
+        progress.inc()?;
+        if game % args.save_every == 0 || game == args.games {
+            let path = format!("training-data/td-{game:07}.onnx");
+            std::fs::write(
+                &path,
+                to_onnx(trainer.mlp(), ContactInputsGen::INPUTS_VERSION),
+            )?;
+            let stats = trainer.stats();
+            println!(
+                "\n{game} games: loss {:.5}, gammon rate {:.1}%, average length {:.1}. Saved {path}",
+                loss_sum / game as f32,
+                100.0 * stats.gammon_rate(),
+                stats.average_length(),
+            );
+        }
+    }
+
+    println!("\nDone!");
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use clap::{Parser, ValueEnum};
+use coach::data::stream_inputs;
+use coach::inputs_registry::{DynInputsGen, InputsRegistry};
+use coach::onnx_export::to_onnx;
+use coach::training::{Mlp, Sample, Trainer};
+use coach::unwrap::UnwrapHelper;
+use std::fs::File;
+use std::time::Instant;
+
+/// Trains a new neural net in Rust, as alternative to `training/src/train-on-rollout-data.py`.
+///
+/// Reads the training data from `training-data/<phase>.csv`, so no conversion to inputs is needed.
+/// After the first third of the epochs, the net of each epoch is written to
+/// `training-data/<phase>-rust-<epoch>.onnx`.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    #[arg(long, value_enum, default_value_t = Phase::Race)]
+    phase: Phase,
+    #[arg(long, default_value_t = 120)]
+    epochs: usize,
+    #[arg(long, default_value_t = 1000e-6)]
+    learning_rate: f32,
+    #[arg(long, default_value_t = 64)]
+    batch_size: usize,
+    /// Sizes of the hidden layers, comma separated.
+    #[arg(long, value_delimiter = ',', default_value = "300,250,200")]
+    hidden: Vec<usize>,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Name of the inputs generator, by default the one of the phase. See `InputsRegistry`.
+    #[arg(long)]
+    inputs: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Phase {
+    Contact,
+    Race,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let phase = match args.phase {
+        Phase::Contact => "contact",
+        Phase::Race => "race",
+    };
+    let registry = InputsRegistry::default();
+    let inputs_gen = registry
+        .get(args.inputs.as_deref().unwrap_or(phase))
+        .unwrap_or_exit_with_message();
+    train(&args, phase, inputs_gen)?;
+    println!("\nDone!");
+    Ok(())
+}
+
+fn train(args: &Args, phase: &str, inputs_gen: &dyn DynInputsGen) -> std::io::Result<()> {
+    let training_path = format!("training-data/{phase}.csv");
+    println!("Read training data from {}", training_path);
+    let mut samples = Vec::new();
+    stream_inputs(File::open(&training_path)?, inputs_gen, |inputs_record| {
+        samples.push(Sample::from(inputs_record));
+        Ok(())
+    })?;
+    println!("Train on {} positions", samples.len());
+
+    let mut trainer = Trainer::new(
+        Mlp::new(inputs_gen.num_inputs(), &args.hidden, args.seed),
+        args.learning_rate,
+    );
+    let mut rng = fastrand::Rng::with_seed(args.seed);
+    for epoch in 1..=args.epochs {
+        let start = Instant::now();
+        let loss = trainer.train_epoch(&mut samples, args.batch_size, &mut rng);
+        println!(
+            "[Epoch: {epoch}] loss: {loss:.5} ({:.1}s)",
+            start.elapsed().as_secs_f32()
+        );
+        if epoch as f32 > args.epochs as f32 * 0.33 {
+            let path = format!("training-data/{phase}-rust-{epoch:03}.onnx");
+            std::fs::write(&path, to_onnx(trainer.mlp(), inputs_gen.inputs_version()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use coach::rollout::RolloutEvaluator;
+use coach::stored_rollout::{model_hash, StoredRollout, Verification};
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use mimalloc::MiMalloc;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// This binary replays stored rollouts and checks that the results are identical, bit for bit.
+///
+/// Each rollout in the file is repeated with its seed. Rollouts done with other neural nets are skipped.
+/// Exits with an error if there is a mismatch or if no rollout could be verified at all.
+fn main() -> std::io::Result<()> {
+    // Change the next couple of lines to configure which rollouts and which nets you want to verify.
+    let rollouts_path = "training-data/stored-rollouts.csv";
+    let contact_path = "neural-nets/contact.onnx";
+    let race_path = "neural-nets/race.onnx";
+
+    let hash = model_hash(&[contact_path, race_path])?;
+    println!(
+        "Verify rollouts from {} with model hash {}",
+        rollouts_path, hash
+    );
+    let rollouts: Vec<StoredRollout> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(rollouts_path)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+
+    let (mut identical, mut skipped, mut mismatches) = (0, 0, 0);
+    let evaluator = RolloutEvaluator::with_evaluator(
+        CompositeEvaluator::from_file_paths_optimized(contact_path, race_path)
+            .unwrap_or_exit_with_message(),
+    );
+    for rollout in &rollouts {
+        match rollout.verify(&evaluator, &hash) {
+            Verification::Identical => identical += 1,
+            Verification::DifferentModel => skipped += 1,
+            Verification::Mismatch { stored, replayed } => {
+                mismatches += 1;
+                println!(
+                    "Mismatch for {}:\n  stored   {:?}\n  replayed {:?}",
+                    rollout.position().position_id(),
+                    stored,
+                    replayed
+                );
+            }
+        }
+    }
+
+    println!(
+        "\n{} identical, {} mismatches, {} skipped because of other nets.",
+        identical, mismatches, skipped
+    );
+    if mismatches > 0 {
+        std::process::exit(1);
+    }
+    if identical == 0 {
+        eprintln!("No rollout was verified.");
+        std::process::exit(1);
+    }
+    Ok(())
+}
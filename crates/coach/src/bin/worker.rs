@@ -0,0 +1,86 @@
+use clap::Parser;
+use coach::batch_queue::Batch;
+use coach::coach_helpers::quarantine_file_name;
+use coach::data::PositionRecord;
+use coach::quarantine::QuarantineEvaluator;
+use coach::rollout::RolloutEvaluator;
+use coach::unwrap::UnwrapHelper;
+use engine::composite::CompositeEvaluator;
+use engine::evaluator::Evaluator;
+use engine::position::{Position, PositionError};
+use mimalloc::MiMalloc;
+use std::time::Duration;
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+/// Rolls out positions for a `coordinator` on another machine until all positions are done.
+///
+/// The worker needs the same neural nets as the coordinator would use, so copy them first.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// URL of the coordinator, for example `http://192.168.1.10:8090`.
+    #[arg(long)]
+    coordinator: String,
+    /// Number of positions rolled out before the results are sent back.
+    #[arg(long, default_value_t = 10)]
+    batch_size: usize,
+    /// The token the coordinator was started with.
+    #[arg(long, env = "WILDBG_COORDINATOR_TOKEN")]
+    token: String,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let evaluator = CompositeEvaluator::try_default().unwrap_or_exit_with_message();
+    _ = std::fs::create_dir("training-data");
+    let evaluator = QuarantineEvaluator::new(evaluator, &quarantine_file_name())?;
+    let rollout_evaluator = RolloutEvaluator::with_evaluator(evaluator);
+    let client = reqwest::blocking::Client::builder().timeout(None).build()?;
+
+    loop {
+        let batch: Batch = client
+            .post(format!("{}/batches", args.coordinator))
+            .bearer_auth(&args.token)
+            .query(&[("size", args.batch_size)])
+            .send()?
+            .error_for_status()?
+            .json()?;
+        if batch.finished {
+            println!("All positions have been rolled out.");
+            return Ok(());
+        }
+        if batch.position_ids.is_empty() {
+            // The last positions are rolled out by other workers. If one of them fails, we take over.
+            std::thread::sleep(Duration::from_secs(60));
+            continue;
+        }
+
+        let records = batch
+            .position_ids
+            .iter()
+            .map(|id| {
+                let position = Position::try_from_id(id)?;
+                Ok(PositionRecord::new(
+                    &position,
+                    &rollout_evaluator.eval(&position),
+                ))
+            })
+            .collect::<Result<Vec<_>, PositionError>>()?;
+        let response = client
+            .post(format!("{}/batches/{}/results", args.coordinator, batch.id))
+            .bearer_auth(&args.token)
+            .json(&records)
+            .send()?;
+        match response.error_for_status_ref() {
+            Ok(_) => println!("Batch {} sent, {} positions.", batch.id, records.len()),
+            // For example because the lease expired; the positions are then rolled out again anyway.
+            Err(_) => eprintln!(
+                "Batch {} was rejected: {}",
+                batch.id,
+                response.text().unwrap_or_default()
+            ),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::mat::MatMatch;
+use engine::evaluator::Evaluator;
+use logic::analysis::{analyze_move, AnalysisSummary};
+use serde::Deserialize;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A human performance in a publicly available match, together with the PR another tool computed.
+///
+/// This is one line of the benchmark CSV file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Benchmark {
+    /// Path of the `.mat` file.
+    pub match_file: String,
+    /// Name of the player as written in the `.mat` file.
+    pub player: String,
+    /// Performance rating (PR) of the player according to the other tool, for example XG or GNUBG.
+    pub reference_pr: f32,
+}
+
+/// PR as used by other tools: average equity loss per non-forced decision, multiplied by 500.
+///
+/// Only checker play is analyzed, cube decisions are not part of the PR yet.
+pub fn performance_rating(summary: &AnalysisSummary) -> f32 {
+    summary.average_equity_loss() * 500.0
+}
+
+/// Analyzes all non-forced checker plays of `player` in `mat_match`.
+///
+/// Moves are compared by cubeless money equity, also for match play.
+pub fn analyze_player<T: Evaluator>(
+    evaluator: &T,
+    mat_match: &MatMatch,
+    player: &str,
+) -> Result<AnalysisSummary, String> {
+    let player = mat_match
+        .player(player)
+        .ok_or_else(|| format!("Player {} is not part of the match.", player))?;
+    let mut summary = AnalysisSummary::default();
+    for game in &mat_match.games {
+        for (mover, record) in game.move_records()? {
+            let forced = record
+                .position
+                .all_positions_after_moving(&record.dice)
+                .len()
+                == 1;
+            if mover != player || forced {
+                continue;
+            }
+            let analysis = analyze_move(evaluator, &record, |p| p.equity())?;
+            summary.add(&analysis);
+        }
+    }
+    Ok(summary)
+}
+
+/// Computed PR of one [Benchmark].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calibration {
+    pub benchmark: Benchmark,
+    pub computed_pr: f32,
+    pub move_count: usize,
+}
+
+impl Calibration {
+    /// Positive if wildbg sees more errors than the other tool.
+    pub fn difference(&self) -> f32 {
+        self.computed_pr - self.benchmark.reference_pr
+    }
+}
+
+/// Comparison of computed and reference PRs for all benchmarks.
+#[derive(Debug, Default)]
+pub struct CalibrationReport {
+    pub calibrations: Vec<Calibration>,
+}
+
+impl CalibrationReport {
+    /// Average signed difference. Positive if wildbg is stricter than the other tool.
+    pub fn bias(&self) -> f32 {
+        self.average(|c| c.difference())
+    }
+
+    pub fn mean_absolute_difference(&self) -> f32 {
+        self.average(|c| c.difference().abs())
+    }
+
+    fn average(&self, f: impl Fn(&Calibration) -> f32) -> f32 {
+        if self.calibrations.is_empty() {
+            return 0.0;
+        }
+        self.calibrations.iter().map(f).sum::<f32>() / self.calibrations.len() as f32
+    }
+}
+
+impl fmt::Display for CalibrationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<30} {:<20} {:>6} {:>9} {:>9} {:>7}",
+            "Match", "Player", "Moves", "Reference", "Computed", "Diff"
+        )?;
+        for c in &self.calibrations {
+            writeln!(
+                f,
+                "{:<30} {:<20} {:>6} {:>9.2} {:>9.2} {:>+7.2}",
+                c.benchmark.match_file,
+                c.benchmark.player,
+                c.move_count,
+                c.benchmark.reference_pr,
+                c.computed_pr,
+                c.difference()
+            )?;
+        }
+        write!(
+            f,
+            "Bias: {:+.2}, mean absolute difference: {:.2}",
+            self.bias(),
+            self.mean_absolute_difference()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calibration::{analyze_player, Benchmark, Calibration, CalibrationReport};
+    use crate::mat::parse;
+    use engine::evaluator::Evaluator;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+
+    /// Test double. The more checkers are on the bar, the worse for the player on roll.
+    struct EvaluatorFake {}
+    impl Evaluator for EvaluatorFake {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let on_bar = pos.pip(25) as f32;
+            Probabilities {
+                win_normal: 0.8 - on_bar * 0.1,
+                win_gammon: 0.0,
+                lose_normal: 0.2 + on_bar * 0.1,
+                lose_gammon: 0.0,
+            }
+        }
+    }
+
+    const MATCH: &str = " 1 point match
+
+ Game 1
+ Alice : 0                          Bob : 0
+  1) 31: 8/5 6/5                    64: 24/18 13/9
+  2) 62: 24/18 13/11                21: 6/5 13/11
+";
+
+    #[test]
+    fn missed_hit_is_an_error() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        // When
+        let alice = analyze_player(&EvaluatorFake {}, &mat_match, "Alice").unwrap();
+        // Then
+        assert_eq!(alice.move_count, 2);
+        // Not hitting the blot on the 7 point loses 0.2 equity.
+        assert!((alice.total_equity_loss - 0.2).abs() < 0.0001);
+    }
+
+    #[test]
+    fn unknown_player() {
+        let mat_match = parse(MATCH).unwrap();
+        let result = analyze_player(&EvaluatorFake {}, &mat_match, "Carol");
+        assert_eq!(
+            result,
+            Err("Player Carol is not part of the match.".to_string())
+        );
+    }
+
+    #[test]
+    fn report_bias_and_mean_absolute_difference() {
+        // Given
+        let calibration = |reference_pr, computed_pr| Calibration {
+            benchmark: Benchmark {
+                match_file: "match.mat".to_string(),
+                player: "Alice".to_string(),
+                reference_pr,
+            },
+            computed_pr,
+            move_count: 100,
+        };
+        // When
+        let report = CalibrationReport {
+            calibrations: vec![calibration(5.0, 6.0), calibration(8.0, 6.0)],
+        };
+        // Then
+        assert_eq!(report.bias(), -0.5);
+        assert_eq!(report.mean_absolute_difference(), 1.5);
+    }
+}
@@ -1,24 +1,53 @@
-use engine::position::OngoingPhase;
-use std::io::{stdout, Write};
-use std::time::Instant;
+use engine::position::{OngoingPhase, Position};
+use std::io::{Error, ErrorKind};
 
 pub fn positions_file_name(phase: &OngoingPhase) -> String {
     format!("training-data/{:?}-positions.csv", phase).to_lowercase()
 }
 
-pub fn print_progress(done: usize, total: usize, start: Instant) -> std::io::Result<()> {
-    let progress = (done + 1) as f32 / total as f32;
-    let left = 1.0 - progress;
-    let seconds_done = start.elapsed().as_secs();
-    let seconds_todo = (seconds_done as f32 * (left / progress)) as u64;
-    print!(
-        "\rProgress: {:2.2} %. Time elapsed: {}. Time left: {}.  ",
-        progress * 100.0,
-        duration(seconds_done),
-        duration(seconds_todo),
-    );
-    stdout().flush()?;
-    Ok(())
+/// Reads a CSV file with one position ID per line, see [positions_file_name].
+///
+/// Illegal position IDs result in an error of kind [ErrorKind::InvalidData].
+pub fn read_positions(path: &str) -> std::io::Result<Vec<Position>> {
+    csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(path)?
+        .into_records()
+        .map(|record| {
+            Position::try_from_id(record?.as_slice())
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error))
+        })
+        .collect()
+}
+
+/// CSV file with training data prepared for publishing, see `coach::export::export`.
+pub fn export_file_name(phase: &OngoingPhase, schema_version: u32) -> String {
+    format!("training-data/{:?}-export-v{}.csv", phase, schema_version).to_lowercase()
+}
+
+/// CSV file in which the statistics of all self play runs are collected.
+pub fn self_play_stats_file_name() -> String {
+    "training-data/self-play-stats.csv".to_string()
+}
+
+/// CSV file with the ratings of all generations of neural nets, see `coach::ladder::Ladder`.
+pub fn ladder_file_name() -> String {
+    "training-data/ladder.csv".to_string()
+}
+
+/// CSV file with the results of all games played for the ladder, see `coach::ladder::Ladder`.
+pub fn ladder_results_file_name() -> String {
+    "training-data/ladder-results.csv".to_string()
+}
+
+/// CSV file in which positions with inconsistent evaluations are collected, see `QuarantineEvaluator`.
+pub fn quarantine_file_name() -> String {
+    "training-data/quarantine.csv".to_string()
+}
+
+/// SQLite database of the `PositionStore` with the training data of all runs.
+pub fn store_path() -> String {
+    "training-data/store.sqlite".to_string()
 }
 
 pub fn duration(seconds: u64) -> String {
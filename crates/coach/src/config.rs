@@ -0,0 +1,351 @@
+use crate::coach_helpers::positions_file_name;
+use crate::labels::LabelSource;
+use crate::position_finder::DEFAULT_TEMPERATURE;
+use engine::position::OngoingPhase;
+use serde::Deserialize;
+
+// Parameters of `generate-positions` and `generate-training-data`, read from a TOML file:
+//
+// ```toml
+// # Number of threads for the rollouts, all cores if missing.
+// threads = 8
+//
+// [positions]
+// phase = "race"
+// amount = 10000
+// temperature = 0.3
+// run = "default-nets"
+//
+// [training-data]
+// phase = "race"
+// labels = "rollout"  # or "two-ply" or "blend" together with `two-ply-weight = 0.5`
+// repetitions = 1
+// seed = 42
+// output = "training-data/race.csv"
+// ```
+//
+// All keys are optional. Command line options of the binaries take precedence over the file.
+
+/// Path of the configuration file if no other one is given on the command line.
+pub const DEFAULT_CONFIG_PATH: &str = "coach.toml";
+
+#[derive(Debug, PartialEq)]
+pub struct CoachConfig {
+    /// Size of the global thread pool of rayon. `None` uses all cores.
+    pub threads: Option<usize>,
+    pub positions: PositionsConfig,
+    pub training_data: TrainingDataConfig,
+}
+
+/// Parameters of `generate-positions`.
+#[derive(Debug, PartialEq)]
+pub struct PositionsConfig {
+    pub phase: OngoingPhase,
+    pub amount: usize,
+    pub temperature: f32,
+    /// Name under which the statistics of the self play games are stored, typically the name of the nets.
+    pub run: String,
+    output: Option<String>,
+}
+
+/// Parameters of `generate-training-data`.
+#[derive(Debug, PartialEq)]
+pub struct TrainingDataConfig {
+    pub phase: OngoingPhase,
+    pub label_source: LabelSource,
+    /// Rollouts play `1296 * repetitions` games per position.
+    pub repetitions: usize,
+    /// Seed of the rollouts. `None` uses a different random seed for each run.
+    pub seed: Option<u64>,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+impl Default for CoachConfig {
+    fn default() -> Self {
+        Self {
+            threads: None,
+            positions: PositionsConfig {
+                phase: OngoingPhase::Race,
+                amount: 10,
+                temperature: DEFAULT_TEMPERATURE,
+                run: "default-nets".to_string(),
+                output: None,
+            },
+            training_data: TrainingDataConfig {
+                phase: OngoingPhase::Race,
+                label_source: LabelSource::Rollout,
+                repetitions: 1,
+                seed: None,
+                input: None,
+                output: None,
+            },
+        }
+    }
+}
+
+impl CoachConfig {
+    /// Reads the configuration from `path`. A missing file means the default configuration.
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content).map_err(|error| format!("{path}: {error}")),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(format!("{path}: {error}")),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        // Typos in keys would silently be ignored otherwise, so unknown keys are rejected.
+        let file: ConfigFile = toml::from_str(content).map_err(|error| error.to_string())?;
+        let mut config = Self {
+            threads: file.threads,
+            ..Self::default()
+        };
+
+        let positions = &mut config.positions;
+        if let Some(phase) = file.positions.phase {
+            positions.phase = parse_phase(&phase)?;
+        }
+        if let Some(amount) = file.positions.amount {
+            positions.amount = amount;
+        }
+        if let Some(temperature) = file.positions.temperature {
+            if temperature < 0.0 {
+                return Err("The temperature must not be negative.".to_string());
+            }
+            positions.temperature = temperature;
+        }
+        if let Some(run) = file.positions.run {
+            positions.run = run;
+        }
+        positions.output = file.positions.output;
+
+        let training_data = &mut config.training_data;
+        let file_data = file.training_data;
+        if let Some(phase) = file_data.phase {
+            training_data.phase = parse_phase(&phase)?;
+        }
+        if let Some(labels) = file_data.labels {
+            training_data.label_source = parse_labels(&labels, file_data.two_ply_weight)?;
+        }
+        if let Some(repetitions) = file_data.repetitions {
+            if repetitions == 0 {
+                return Err("At least one repetition is needed.".to_string());
+            }
+            training_data.repetitions = repetitions;
+        }
+        training_data.seed = file_data.seed;
+        training_data.input = file_data.input;
+        training_data.output = file_data.output;
+        Ok(config)
+    }
+
+    /// Sets the size of rayon's global thread pool, if configured. Must be called before any rollout.
+    pub fn init_thread_pool(&self) -> Result<(), String> {
+        match self.threads {
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+                .map_err(|error| error.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl PositionsConfig {
+    /// File to which the positions are written.
+    pub fn output(&self) -> String {
+        self.output
+            .clone()
+            .unwrap_or_else(|| positions_file_name(&self.phase))
+    }
+
+    pub fn set_output(&mut self, output: String) {
+        self.output = Some(output);
+    }
+}
+
+impl TrainingDataConfig {
+    /// File from which the positions are read, by default the output of `generate-positions`.
+    pub fn input(&self) -> String {
+        self.input
+            .clone()
+            .unwrap_or_else(|| positions_file_name(&self.phase))
+    }
+
+    pub fn set_input(&mut self, input: String) {
+        self.input = Some(input);
+    }
+
+    /// File to which the training data is written.
+    pub fn output(&self) -> String {
+        self.output
+            .clone()
+            .unwrap_or_else(|| format!("training-data/{:?}.csv", self.phase).to_lowercase())
+    }
+
+    pub fn set_output(&mut self, output: String) {
+        self.output = Some(output);
+    }
+}
+
+/// `rollout`, `two-ply` or `blend`. Blending without a weight mixes both halfway.
+fn parse_labels(value: &str, two_ply_weight: Option<f32>) -> Result<LabelSource, String> {
+    match value {
+        "rollout" => Ok(LabelSource::Rollout),
+        "two-ply" => Ok(LabelSource::TwoPly),
+        "blend" => {
+            let two_ply_weight = two_ply_weight.unwrap_or(0.5);
+            if (0.0..=1.0).contains(&two_ply_weight) {
+                Ok(LabelSource::Blend { two_ply_weight })
+            } else {
+                Err("The weight of 2-ply must be between 0.0 and 1.0.".to_string())
+            }
+        }
+        other => Err(format!(
+            "Unknown labels '{other}', use 'rollout', 'two-ply' or 'blend'."
+        )),
+    }
+}
+
+/// `contact` or `race`.
+pub fn parse_phase(value: &str) -> Result<OngoingPhase, String> {
+    match value {
+        "contact" => Ok(OngoingPhase::Contact),
+        "race" => Ok(OngoingPhase::Race),
+        other => Err(format!("Unknown phase '{other}', use 'contact' or 'race'.")),
+    }
+}
+
+/// Layout of the TOML file, all keys are optional.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    threads: Option<usize>,
+    positions: PositionsFile,
+    #[serde(rename = "training-data")]
+    training_data: TrainingDataFile,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PositionsFile {
+    phase: Option<String>,
+    amount: Option<usize>,
+    temperature: Option<f32>,
+    run: Option<String>,
+    output: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct TrainingDataFile {
+    phase: Option<String>,
+    labels: Option<String>,
+    two_ply_weight: Option<f32>,
+    repetitions: Option<usize>,
+    seed: Option<u64>,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::CoachConfig;
+    use crate::labels::LabelSource;
+    use engine::position::OngoingPhase;
+
+    #[test]
+    fn parse_all_keys() {
+        // Given
+        let content = r#"
+            # Rollouts on half of the cores
+            threads = 4
+
+            [positions]
+            phase = "contact"
+            amount = 20_000
+            temperature = 0.5
+            run = "gen-7"  # the current nets
+            output = "positions.csv"
+
+            [training-data]
+            phase = "contact"
+            two-ply-weight = 0.25
+            labels = "blend"
+            repetitions = 2
+            seed = 42
+            input = "positions.csv"
+            output = "data/contact#7.csv"
+        "#;
+        // When
+        let config = CoachConfig::parse(content).unwrap();
+        // Then
+        assert_eq!(config.threads, Some(4));
+        assert_eq!(config.positions.phase, OngoingPhase::Contact);
+        assert_eq!(config.positions.amount, 20_000);
+        assert_eq!(config.positions.temperature, 0.5);
+        assert_eq!(config.positions.run, "gen-7");
+        assert_eq!(config.positions.output(), "positions.csv");
+        let training_data = &config.training_data;
+        assert_eq!(
+            training_data.label_source,
+            LabelSource::Blend {
+                two_ply_weight: 0.25
+            }
+        );
+        assert_eq!(training_data.repetitions, 2);
+        assert_eq!(training_data.seed, Some(42));
+        assert_eq!(training_data.input(), "positions.csv");
+        assert_eq!(training_data.output(), "data/contact#7.csv");
+    }
+
+    #[test]
+    fn defaults() {
+        let config = CoachConfig::parse("[training-data]\nphase = \"contact\"").unwrap();
+
+        assert_eq!(config.positions, CoachConfig::default().positions);
+        assert_eq!(
+            config.positions.output(),
+            "training-data/race-positions.csv"
+        );
+        assert_eq!(
+            config.training_data.input(),
+            "training-data/contact-positions.csv"
+        );
+        assert_eq!(config.training_data.output(), "training-data/contact.csv");
+        assert_eq!(config.training_data.label_source, LabelSource::Rollout);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            CoachConfig::parse("[positions]\nphase = \"bearoff\""),
+            Err("Unknown phase 'bearoff', use 'contact' or 'race'.".to_string())
+        );
+        assert_eq!(
+            CoachConfig::parse("[positions]\ntemperature = -1"),
+            Err("The temperature must not be negative.".to_string())
+        );
+        assert_eq!(
+            CoachConfig::parse("[training-data]\nrepetitions = 0"),
+            Err("At least one repetition is needed.".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_toml() {
+        for (content, message) in [
+            (
+                "[positions]\namount = \"many\"",
+                "invalid type: string \"many\"",
+            ),
+            ("[positions]\namout = 3", "unknown field `amout`"),
+            ("threads = 2\nthreads = 3", "duplicate key `threads`"),
+            ("[positions\n", "invalid table header"),
+        ] {
+            let error = CoachConfig::parse(content).unwrap_err();
+            assert!(error.contains(message), "{error}");
+        }
+    }
+}
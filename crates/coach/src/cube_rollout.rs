@@ -0,0 +1,394 @@
+use crate::duel::points;
+use engine::dice_gen::{DiceGen, FastrandDice};
+use engine::evaluator::Evaluator;
+use engine::position::GameState::{GameOver, Ongoing};
+use engine::position::Position;
+use logic::cube::{CubeAccess, Janowski};
+use logic::match_equity::{MatchEquityTable, MatchScore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Cubeful rollouts for training data of cube decisions, for money play and at match scores.
+//
+// Each game is played twice from the same position and with the same dice: once without a double
+// and once after double/take. During money games both players double and take according to
+// Janowski's model, based on the cubeless probabilities of the evaluator. At a match
+// score they use the doubling window of the match equity table instead, and the results of the
+// games are converted into match winning chances.
+// Gammons always count, there is no Jacoby rule.
+
+/// Who may double, seen from the player on roll.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CubeOwner {
+    Centered,
+    OnRoll,
+    Opponent,
+}
+
+impl CubeOwner {
+    fn switched(self) -> Self {
+        match self {
+            CubeOwner::Centered => CubeOwner::Centered,
+            CubeOwner::OnRoll => CubeOwner::Opponent,
+            CubeOwner::Opponent => CubeOwner::OnRoll,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CubeAction {
+    NoDouble,
+    DoubleTake,
+    DoublePass,
+    /// The opponent would pass, but playing on for a gammon is worth more.
+    TooGood,
+}
+
+/// Cubeful equities of the player on roll for the three possible outcomes of the cube decision.
+///
+/// The equities are normalized to the current cube value, so double/pass is always `1.0`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubefulEquities {
+    pub no_double: f32,
+    pub double_take: f32,
+    pub double_pass: f32,
+}
+
+impl CubefulEquities {
+    /// The opponent chooses between take and pass, then the player on roll between that and no double.
+    pub fn action(&self) -> CubeAction {
+        if self.double_take >= self.double_pass {
+            if self.no_double > self.double_pass {
+                CubeAction::TooGood
+            } else {
+                CubeAction::DoublePass
+            }
+        } else if self.double_take > self.no_double {
+            CubeAction::DoubleTake
+        } else {
+            CubeAction::NoDouble
+        }
+    }
+}
+
+/// One line of the cube training data.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct CubeRecord {
+    pub position_id: String,
+    /// Points the player on roll still needs, empty for money play.
+    pub x_away: Option<u32>,
+    /// Points the opponent still needs, empty for money play.
+    pub o_away: Option<u32>,
+    pub crawford: bool,
+    pub cube_value: u32,
+    pub cube_owner: CubeOwner,
+    pub action: CubeAction,
+    pub no_double: f32,
+    pub double_take: f32,
+    pub double_pass: f32,
+}
+
+impl CubeRecord {
+    pub fn new(
+        position: &Position,
+        score: Option<&MatchScore>,
+        cube_value: u32,
+        cube_owner: CubeOwner,
+        equities: &CubefulEquities,
+    ) -> Self {
+        Self {
+            position_id: position.position_id(),
+            x_away: score.map(|score| score.x_away),
+            o_away: score.map(|score| score.o_away),
+            crawford: score.is_some_and(|score| score.crawford),
+            cube_value,
+            cube_owner,
+            action: equities.action(),
+            no_double: equities.no_double,
+            double_take: equities.double_take,
+            double_pass: equities.double_pass,
+        }
+    }
+}
+
+/// Two `CubefulRollout`s with the same evaluator, seed and number of games return identical equities.
+pub struct CubefulRollout<T: Evaluator> {
+    evaluator: T,
+    games: usize,
+    seed: u64,
+    table: MatchEquityTable,
+}
+
+impl<T: Evaluator + Sync> CubefulRollout<T> {
+    pub fn new(evaluator: T, games: usize, seed: u64) -> Self {
+        assert!(games > 0, "At least one game is needed.");
+        Self {
+            evaluator,
+            games,
+            seed,
+            table: MatchEquityTable::default(),
+        }
+    }
+
+    /// Match equity table for rollouts at a match score, by default [MatchEquityTable::default].
+    pub fn with_table(mut self, table: MatchEquityTable) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Rolls out the cube decision of the player on roll, who must have access to the cube.
+    ///
+    /// `score` is seen from the player on roll, `None` means money play. At a match score the
+    /// equities are normalized match equities, see [MatchEquityTable::normalized_equity].
+    pub fn eval(
+        &self,
+        position: &Position,
+        score: Option<&MatchScore>,
+        cube_value: u32,
+        cube_owner: CubeOwner,
+    ) -> CubefulEquities {
+        assert_ne!(
+            cube_owner,
+            CubeOwner::Opponent,
+            "The player on roll can't double."
+        );
+        assert!(
+            !score.is_some_and(|score| score.crawford),
+            "Nobody can double in the Crawford game."
+        );
+        debug_assert!(position.game_state() == Ongoing);
+
+        // Independent of the Rust version and the platform, so that the training data can be
+        // reproduced.
+        let mut dice_gen = FastrandDice::with_seed(split_mix(position.zobrist() ^ self.seed));
+        let seeds: Vec<u64> = (0..self.games).map(|_| dice_gen.seed()).collect();
+
+        let (no_double, double_take) = seeds
+            .par_iter()
+            .map(|seed| {
+                let no_double = self.play(position, score, cube_value, cube_owner, *seed);
+                let double_take =
+                    self.play(position, score, 2 * cube_value, CubeOwner::Opponent, *seed);
+                (no_double, double_take)
+            })
+            .reduce(|| (0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+        let normalized = |sum: f64| match score {
+            None => (sum / (self.games as f64 * cube_value as f64)) as f32,
+            Some(score) => {
+                let mwc = (sum / self.games as f64) as f32;
+                self.table.normalized_equity(mwc, score, cube_value)
+            }
+        };
+        CubefulEquities {
+            no_double: normalized(no_double),
+            double_take: normalized(double_take),
+            double_pass: 1.0,
+        }
+    }
+
+    /// Plays one game in which the player on roll rolls right away.
+    ///
+    /// Returns the points won by that player including the cube value, or at a match score the
+    /// match winning chances of that player after the game.
+    fn play(
+        &self,
+        position: &Position,
+        score: Option<&MatchScore>,
+        cube_value: u32,
+        cube_owner: CubeOwner,
+        seed: u64,
+    ) -> f64 {
+        let mut dice_gen = FastrandDice::with_seed(seed);
+        let mut pos = *position;
+        let mut value = cube_value as i32;
+        let mut owner = cube_owner;
+        // `1` if the player on roll is the one of `position`, `-1` otherwise.
+        let mut sign = 1;
+        let outcome = |points: i32| match score {
+            None => points as f64,
+            Some(score) => {
+                let won = points.max(0) as u32;
+                let lost = (-points).max(0) as u32;
+                self.table.mwc_at(&score.after_game(won, lost)) as f64
+            }
+        };
+        loop {
+            let next = self
+                .evaluator
+                .best_position_by_equity(&pos, &dice_gen.roll());
+            if let GameOver(result) = next.game_state() {
+                // `next` is seen from the opponent, who has just lost.
+                return outcome(sign * value * points(&result.reverse()));
+            }
+            pos = next;
+            owner = owner.switched();
+            sign = -sign;
+            if owner != CubeOwner::Opponent {
+                // The score of the player who is on roll now.
+                let score = score.map(|score| match sign {
+                    1 => *score,
+                    _ => score.sides_switched(),
+                });
+                match self.cube_decision(&pos, score.as_ref(), value as u32, owner) {
+                    InGameDecision::NoDouble => {}
+                    InGameDecision::DoubleTake => {
+                        value *= 2;
+                        owner = CubeOwner::Opponent;
+                    }
+                    InGameDecision::DoublePass => return outcome(sign * value),
+                }
+            }
+        }
+    }
+
+    /// Cube decision during a rollout for the player on roll of `position`.
+    fn cube_decision(
+        &self,
+        position: &Position,
+        score: Option<&MatchScore>,
+        cube_value: u32,
+        cube_owner: CubeOwner,
+    ) -> InGameDecision {
+        let probabilities = self.evaluator.eval(position);
+        match score {
+            None => {
+                let janowski = Janowski::default();
+                let access = match cube_owner {
+                    CubeOwner::OnRoll => CubeAccess::Owned,
+                    _ => CubeAccess::Centered,
+                };
+                if !janowski.should_double(&probabilities, access) {
+                    InGameDecision::NoDouble
+                } else if janowski.should_take(&probabilities) {
+                    InGameDecision::DoubleTake
+                } else {
+                    InGameDecision::DoublePass
+                }
+            }
+            Some(score) if score.crawford => InGameDecision::NoDouble,
+            Some(score) => {
+                let window = self.table.window(&probabilities, score, cube_value);
+                let win = probabilities.win();
+                if win >= window.too_good_point {
+                    InGameDecision::NoDouble
+                } else if win >= window.cash_point {
+                    InGameDecision::DoublePass
+                } else if win >= window.doubling_point {
+                    InGameDecision::DoubleTake
+                } else {
+                    InGameDecision::NoDouble
+                }
+            }
+        }
+    }
+}
+
+enum InGameDecision {
+    NoDouble,
+    DoubleTake,
+    DoublePass,
+}
+
+/// Finalizer of SplitMix64, see <https://prng.di.unimi.it/splitmix64.c>.
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube_rollout::{split_mix, CubeAction, CubeOwner, CubefulEquities, CubefulRollout};
+    use engine::evaluator::RandomEvaluator;
+    use engine::pos;
+    use engine::test_utils::EvaluatorFake;
+    use logic::match_equity::MatchScore;
+
+    fn equities(no_double: f32, double_take: f32) -> CubefulEquities {
+        CubefulEquities {
+            no_double,
+            double_take,
+            double_pass: 1.0,
+        }
+    }
+
+    #[test]
+    fn actions() {
+        assert_eq!(equities(0.3, 0.2).action(), CubeAction::NoDouble);
+        assert_eq!(equities(0.6, 0.8).action(), CubeAction::DoubleTake);
+        assert_eq!(equities(0.9, 1.2).action(), CubeAction::DoublePass);
+        assert_eq!(equities(1.3, 1.6).action(), CubeAction::TooGood);
+        // With equal equities for take and pass, the opponent passes.
+        assert_eq!(equities(0.8, 1.0).action(), CubeAction::DoublePass);
+    }
+
+    #[test]
+    fn last_roll_of_the_game() {
+        // Given
+        let rollout = CubefulRollout::new(RandomEvaluator {}, 10, 1);
+        // Every roll bears off the last checker, the opponent has already borne off one.
+        let position = pos!(x 1:1; o 24:14);
+        // When
+        let result = rollout.eval(&position, None, 2, CubeOwner::OnRoll);
+        // Then
+        assert_eq!(result, equities(1.0, 2.0));
+        assert_eq!(result.action(), CubeAction::DoublePass);
+    }
+
+    #[test]
+    fn last_roll_of_the_game_at_match_score() {
+        // Given
+        let rollout = CubefulRollout::new(RandomEvaluator {}, 10, 1);
+        let position = pos!(x 1:1; o 24:14);
+        let score = MatchScore {
+            x_away: 2,
+            o_away: 2,
+            crawford: false,
+        };
+        // When
+        let result = rollout.eval(&position, Some(&score), 1, CubeOwner::Centered);
+        // Then
+        assert_eq!(result.no_double, 1.0);
+        // The doubled cube wins the match, which is worth more than a single point.
+        assert!(result.double_take > 1.0);
+        assert_eq!(result.action(), CubeAction::DoublePass);
+    }
+
+    #[test]
+    fn same_seed_same_equities() {
+        // Given
+        let position = pos!(x 5:2, 3:2; o 20:2, 22:2);
+        let rollout = |seed: u64| CubefulRollout::new(EvaluatorFake {}, 20, seed);
+        // When
+        let first = rollout(7).eval(&position, None, 1, CubeOwner::Centered);
+        let second = rollout(7).eval(&position, None, 1, CubeOwner::Centered);
+        // Then
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn split_mix_is_reference_implementation() {
+        // First output of the reference implementation, seeded with zero.
+        assert_eq!(split_mix(0), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nobody can double in the Crawford game.")]
+    fn crawford_game() {
+        let rollout = CubefulRollout::new(RandomEvaluator {}, 10, 1);
+        let score = MatchScore {
+            x_away: 3,
+            o_away: 1,
+            crawford: true,
+        };
+        rollout.eval(&pos!(x 1:1; o 24:14), Some(&score), 1, CubeOwner::Centered);
+    }
+
+    #[test]
+    #[should_panic(expected = "The player on roll can't double.")]
+    fn opponent_owns_cube() {
+        let rollout = CubefulRollout::new(RandomEvaluator {}, 10, 1);
+        rollout.eval(&pos!(x 1:1; o 24:14), None, 2, CubeOwner::Opponent);
+    }
+}
@@ -1,7 +1,19 @@
-use engine::inputs::InputsGen;
+use crate::inputs_registry::DynInputsGen;
 use engine::position::Position;
 use engine::probabilities::Probabilities;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::path::Path;
+use std::sync::mpsc::sync_channel;
+
+/// Number of records which are read and converted together by [stream_inputs].
+const CHUNK_SIZE: usize = 1000;
+
+/// Number of converted chunks which may wait for the writer in [stream_inputs].
+const CHANNEL_BOUND: usize = 4;
 
 /// Position ID and 5 probabilities meant to be serialized to CSV to keep training data for a longer time.
 ///
@@ -10,7 +22,8 @@ use serde::{Deserialize, Serialize};
 /// `win_g` and `lose_g` include the chance to win or lose backgammon.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PositionRecord {
-    position_id: String,
+    #[serde(rename = "position_id")]
+    position: Position,
     win: f32,
     win_g: f32,
     lose_g: f32,
@@ -19,13 +32,27 @@ pub struct PositionRecord {
 impl PositionRecord {
     pub fn new(position: &Position, probabilities: &Probabilities) -> Self {
         PositionRecord {
-            position_id: position.position_id(),
+            position: *position,
             win: probabilities.win_normal + probabilities.win_gammon,
             win_g: probabilities.win_gammon,
             lose_g: probabilities.lose_gammon,
         }
     }
 
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Converts the 5 "classic" values back into the 4 fields of [Probabilities].
+    pub fn probabilities(&self) -> Probabilities {
+        Probabilities {
+            win_normal: self.win - self.win_g,
+            win_gammon: self.win_g,
+            lose_normal: 1.0 - self.win - self.lose_g,
+            lose_gammon: self.lose_g,
+        }
+    }
+
     pub fn csv_header() -> Vec<String> {
         vec![
             "position_id".to_owned(),
@@ -46,14 +73,216 @@ pub struct InputsRecord {
 }
 
 impl InputsRecord {
-    pub fn new<T: InputsGen>(record: &PositionRecord, inputs_gen: &T) -> Self {
-        let position = Position::from_id(record.position_id.clone());
+    pub fn new<T: DynInputsGen + ?Sized>(record: &PositionRecord, inputs_gen: &T) -> Self {
+        let probabilities = record.probabilities();
         InputsRecord {
-            win_normal: record.win - record.win_g,
-            win_gammon: record.win_g,
-            lose_normal: 1.0 - record.win - record.lose_g,
-            lose_gammon: record.lose_g,
-            inputs: inputs_gen.inputs_for_single(&position),
+            win_normal: probabilities.win_normal,
+            win_gammon: probabilities.win_gammon,
+            lose_normal: probabilities.lose_normal,
+            lose_gammon: probabilities.lose_gammon,
+            inputs: inputs_gen.inputs_for_single(&record.position()),
         }
     }
+
+    /// The 4 probabilities followed by the inputs, in the order of the CSV columns.
+    pub fn values(&self) -> Vec<f32> {
+        let probabilities = [
+            self.win_normal,
+            self.win_gammon,
+            self.lose_normal,
+            self.lose_gammon,
+        ];
+        probabilities
+            .into_iter()
+            .chain(self.inputs.iter().copied())
+            .collect()
+    }
+}
+
+/// Reads [PositionRecord]s as CSV and passes the corresponding [InputsRecord]s to `write`, in the same order.
+///
+/// The records are read and converted in parallel on other threads while `write` is called on this
+/// one. They are sent in chunks through a bounded channel, so that only a few thousand records are
+/// in memory at any time, independent of the size of the training data.
+///
+/// Returns the number of written records.
+pub fn stream_inputs<T: DynInputsGen + ?Sized>(
+    reader: impl Read + Send,
+    inputs_gen: &T,
+    mut write: impl FnMut(&InputsRecord) -> std::io::Result<()>,
+) -> std::io::Result<usize> {
+    let (sender, receiver) = sync_channel::<csv::Result<Vec<InputsRecord>>>(CHANNEL_BOUND);
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            let mut csv_reader = csv::ReaderBuilder::new()
+                .has_headers(true)
+                .from_reader(reader);
+            let mut records = csv_reader.deserialize::<PositionRecord>();
+            loop {
+                let chunk: csv::Result<Vec<PositionRecord>> =
+                    records.by_ref().take(CHUNK_SIZE).collect();
+                let chunk = chunk.map(|chunk| {
+                    chunk
+                        .par_iter()
+                        .map(|record| InputsRecord::new(record, inputs_gen))
+                        .collect::<Vec<_>>()
+                });
+                let done = chunk
+                    .as_ref()
+                    .map_or(true, |chunk| chunk.len() < CHUNK_SIZE);
+                // Sending fails if the writer has stopped because of an error.
+                if sender.send(chunk).is_err() || done {
+                    return;
+                }
+            }
+        });
+
+        let mut count = 0;
+        for chunk in receiver {
+            for record in chunk? {
+                write(&record)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    })
+}
+
+/// The positions of `records` seen from the opponent, see [Position::mirror].
+///
+/// The mirrored position has the other player on roll, so its probabilities are not just the
+/// switched ones of the original; each mirrored position needs its own label.
+/// Mirrored positions which are already part of `records` or identical to their original are
+/// skipped.
+pub fn mirrored_positions(records: &[PositionRecord]) -> Vec<Position> {
+    let mut seen: HashSet<Position> = records.iter().map(|record| record.position).collect();
+    records
+        .iter()
+        .map(|record| record.position.mirror())
+        .filter(|mirrored| seen.insert(*mirrored))
+        .collect()
+}
+
+/// Prepares a CSV file of [PositionRecord]s, to which an interrupted run appended records, for
+/// appending more records. Returns the positions which are already in the file.
+///
+/// If the run was interrupted while writing a record, that incomplete last line is removed.
+pub fn prepare_resume(path: &Path) -> std::io::Result<HashSet<Position>> {
+    let content = std::fs::read(path)?;
+    let complete = content
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+    if complete < content.len() {
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_len(complete as u64)?;
+    }
+    let mut done = HashSet::new();
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(&content[..complete]);
+    for result in csv_reader.deserialize() {
+        let record: PositionRecord = result?;
+        done.insert(record.position());
+    }
+    Ok(done)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{
+        mirrored_positions, prepare_resume, stream_inputs, PositionRecord, CHUNK_SIZE,
+    };
+    use engine::inputs::{InputsGen, RaceInputsGen};
+    use engine::pos;
+    use engine::probabilities::Probabilities;
+
+    #[test]
+    fn stream_keeps_order() {
+        // Given
+        let mut csv = String::from("position_id,win,win_g,lose_g\n");
+        let position_id = pos!(x 1:1; o 24:1).position_id();
+        let rows = 2 * CHUNK_SIZE + 1;
+        for i in 0..rows {
+            csv.push_str(&format!(
+                "{position_id},{},0.0,0.0\n",
+                i as f32 / rows as f32
+            ));
+        }
+        // When
+        let mut written = Vec::new();
+        let count = stream_inputs(csv.as_bytes(), &RaceInputsGen {}, |record| {
+            written.push(record.values());
+            Ok(())
+        })
+        .unwrap();
+        // Then
+        assert_eq!(count, rows);
+        assert_eq!(written.len(), rows);
+        for (i, values) in written.iter().enumerate() {
+            assert_eq!(values[0], i as f32 / rows as f32);
+            assert_eq!(values.len(), 4 + RaceInputsGen::NUM_INPUTS);
+        }
+    }
+
+    #[test]
+    fn stream_stops_at_illegal_record() {
+        let csv = "position_id,win,win_g,lose_g\nabc,not a number,0.0,0.0\n";
+        let result = stream_inputs(csv.as_bytes(), &RaceInputsGen {}, |_| Ok(()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn stream_stops_at_illegal_position_id() {
+        let csv = "position_id,win,win_g,lose_g\n//////////////,1.0,0.0,0.0\n";
+        let result = stream_inputs(csv.as_bytes(), &RaceInputsGen {}, |_| Ok(()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn resume_after_incomplete_record() {
+        // Given
+        let path = std::env::temp_dir().join(format!("wildbg-resume-{}.csv", std::process::id()));
+        let first = pos!(x 1:1; o 24:1);
+        let second = pos!(x 2:1; o 24:1);
+        let content = format!(
+            "position_id,win,win_g,lose_g\n{},1.0,0.0,0.0\n{},0.",
+            first.position_id(),
+            second.position_id()
+        );
+        std::fs::write(&path, &content).unwrap();
+        // When
+        let done = prepare_resume(&path).unwrap();
+        // Then
+        assert_eq!(done.len(), 1);
+        assert!(done.contains(&first));
+        let expected_length = content.rfind('\n').unwrap() + 1;
+        assert_eq!(std::fs::read(&path).unwrap().len(), expected_length);
+        _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn mirrored_positions_are_new() {
+        // Given
+        let probabilities = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.25,
+            lose_normal: 0.125,
+            lose_gammon: 0.125,
+        };
+        let records = [
+            PositionRecord::new(&pos!(x 5:2; o 22:1), &probabilities),
+            // These two are mirrored versions of each other.
+            PositionRecord::new(&pos!(x 6:1; o 24:1), &probabilities),
+            PositionRecord::new(&pos!(x 1:1; o 19:1), &probabilities),
+            // Symmetric, so identical to its mirrored record
+            PositionRecord::new(&pos!(x 2:1; o 23:1), &probabilities),
+        ];
+        // When
+        let mirrored = mirrored_positions(&records);
+        // Then
+        assert_eq!(mirrored, vec![pos!(x 3:1; o 20:2)]);
+    }
 }
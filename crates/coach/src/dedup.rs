@@ -0,0 +1,119 @@
+use crate::data::PositionRecord;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use std::collections::HashMap;
+
+/// Merges training data of positions which were rolled out more than once.
+///
+/// Early game positions are found again and again by the self play games, so concatenated training
+/// data contains many duplicates. They would give these positions more weight during training.
+/// The probabilities of all rollouts of the same position are averaged; all rollouts are assumed to
+/// consist of the same number of games.
+#[derive(Default)]
+pub struct Deduplicator {
+    /// Positions in the order they were first added, so that the output is deterministic.
+    order: Vec<Position>,
+    /// Sum of the probabilities and number of rollouts for each position.
+    merged: HashMap<Position, ([f32; 4], u32)>,
+}
+
+impl Deduplicator {
+    pub fn add(&mut self, position: &Position, probabilities: &Probabilities) {
+        let (sum, count) = self.merged.entry(*position).or_insert_with(|| {
+            self.order.push(*position);
+            ([0.0; 4], 0)
+        });
+        let values = [
+            probabilities.win_normal,
+            probabilities.win_gammon,
+            probabilities.lose_normal,
+            probabilities.lose_gammon,
+        ];
+        for (sum, value) in sum.iter_mut().zip(values) {
+            *sum += value;
+        }
+        *count += 1;
+    }
+
+    pub fn add_record(&mut self, record: &PositionRecord) {
+        self.add(&record.position(), &record.probabilities());
+    }
+
+    /// Number of different positions.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Number of added records which were merged into an earlier one.
+    pub fn duplicates(&self) -> u32 {
+        self.merged.values().map(|(_, count)| count - 1).sum()
+    }
+
+    /// One record per position with averaged probabilities, in the order of the first occurrence.
+    pub fn records(&self) -> Vec<PositionRecord> {
+        self.order
+            .iter()
+            .map(|position| {
+                let (sum, count) = self.merged[position];
+                let count = count as f32;
+                let probabilities = Probabilities {
+                    win_normal: sum[0] / count,
+                    win_gammon: sum[1] / count,
+                    lose_normal: sum[2] / count,
+                    lose_gammon: sum[3] / count,
+                };
+                PositionRecord::new(position, &probabilities)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dedup::Deduplicator;
+    use engine::pos;
+    use engine::probabilities::Probabilities;
+
+    #[test]
+    fn merge_duplicates_and_keep_order() {
+        // Given
+        let first = pos!(x 1:1; o 24:1);
+        let second = pos!(x 2:1; o 24:1);
+        let mut dedup = Deduplicator::default();
+        // When
+        dedup.add(
+            &first,
+            &Probabilities {
+                win_normal: 0.6,
+                win_gammon: 0.0,
+                lose_normal: 0.4,
+                lose_gammon: 0.0,
+            },
+        );
+        dedup.add(&second, &Probabilities::default());
+        dedup.add(
+            &first,
+            &Probabilities {
+                win_normal: 0.4,
+                win_gammon: 0.2,
+                lose_normal: 0.2,
+                lose_gammon: 0.2,
+            },
+        );
+        // Then
+        assert_eq!(dedup.len(), 2);
+        assert_eq!(dedup.duplicates(), 1);
+        let records = dedup.records();
+        assert_eq!(records[0].position(), first);
+        assert_eq!(records[1].position(), second);
+        let merged = records[0].probabilities();
+        assert!((merged.win_normal - 0.5).abs() < 0.0001);
+        assert!((merged.win_gammon - 0.1).abs() < 0.0001);
+        assert!((merged.lose_normal - 0.3).abs() < 0.0001);
+        assert!((merged.lose_gammon - 0.1).abs() < 0.0001);
+    }
+}
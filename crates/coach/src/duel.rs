@@ -1,8 +1,10 @@
-use engine::dice_gen::DiceGen;
+use engine::dice_gen::{DiceGen, FastrandDice};
 use engine::evaluator::Evaluator;
 use engine::position::GameState::{GameOver, Ongoing};
-use engine::position::STARTING;
-use engine::probabilities::ResultCounter;
+use engine::position::{GameResult, STARTING};
+use engine::probabilities::{Probabilities, ResultCounter};
+use rayon::prelude::*;
+use std::ops::Range;
 
 pub struct Duel<T: Evaluator, U: Evaluator> {
     evaluator1: T,
@@ -22,12 +24,22 @@ impl<T: Evaluator, U: Evaluator> Duel<T, U> {
     /// The two `Evaluator`s will play twice each against each other.
     /// Either `Evaluator` will start once and play with the same dice as vice versa.
     pub fn duel<V: DiceGen>(&self, dice_gen: &mut V) -> ResultCounter {
+        let mut counter = ResultCounter::default();
+        for result in self.duel_results(dice_gen) {
+            counter.add(result);
+        }
+        counter
+    }
+
+    /// Like [Duel::duel], but returns both results from the perspective of `evaluator1`:
+    /// first the one of the game started by `evaluator1`, then the one started by `evaluator2`.
+    pub fn duel_results<V: DiceGen>(&self, dice_gen: &mut V) -> [GameResult; 2] {
         let mut pos1 = STARTING;
         let mut pos2 = STARTING;
         let mut iteration = 0;
         let mut pos1_finished = false;
         let mut pos2_finished = false;
-        let mut counter = ResultCounter::default();
+        let mut results = [GameResult::WinNormal, GameResult::WinNormal];
         while !(pos1_finished && pos2_finished) {
             let dice = dice_gen.roll();
 
@@ -47,7 +59,7 @@ impl<T: Evaluator, U: Evaluator> Duel<T, U> {
                         } else {
                             result.reverse()
                         };
-                        counter.add(result);
+                        results[0] = result;
                     }
                 }
             }
@@ -67,13 +79,151 @@ impl<T: Evaluator, U: Evaluator> Duel<T, U> {
                         } else {
                             result
                         };
-                        counter.add(result);
+                        results[1] = result;
                     }
                 }
             }
             iteration += 1;
         }
-        debug_assert!(counter.sum() == 2, "Each duel should have two game results");
-        counter
+        results
+    }
+
+    /// Plays two cubeless matches to `length` points, one started by each `Evaluator`.
+    ///
+    /// Game `i` of both matches is played with the same dice, in one match `evaluator1` moves first,
+    /// in the other one `evaluator2`. The checker play doesn't take the score into account.
+    /// Returns for both matches whether `evaluator1` has won.
+    pub fn match_pair<V: DiceGen>(&self, length: u32, dice_gen: &mut V) -> [bool; 2] {
+        // Points of `evaluator1` and `evaluator2` in both matches.
+        let mut scores = [[0u32; 2]; 2];
+        let ongoing = |score: &[u32; 2]| score[0] < length && score[1] < length;
+        let mut game = 0;
+        while scores.iter().any(ongoing) {
+            let results = self.duel_results(dice_gen);
+            for (match_index, score) in scores.iter_mut().enumerate() {
+                if ongoing(score) {
+                    let points = points(&results[(game + match_index) % 2]);
+                    if points > 0 {
+                        score[0] += points as u32;
+                    } else {
+                        score[1] += -points as u32;
+                    }
+                }
+            }
+            game += 1;
+        }
+        [scores[0][0] >= length, scores[1][0] >= length]
+    }
+}
+
+impl<T: Evaluator + Sync, U: Evaluator + Sync> Duel<T, U> {
+    /// Plays the given `pairs` in parallel and sums up their scores.
+    ///
+    /// `score` plays one pair, for example with [Duel::duel_results] or [Duel::match_pair], and
+    /// returns the score of `evaluator1` in both games or matches. Each pair gets its own dice,
+    /// derived from `seed`, so that the results don't depend on the order in which pairs are played.
+    pub fn play_pairs<F>(&self, pairs: Range<u64>, seed: u64, score: F) -> PairedScore
+    where
+        F: Fn(&Self, &mut FastrandDice) -> [f64; 2] + Sync,
+    {
+        pairs
+            .into_par_iter()
+            .map(|pair| {
+                let mut dice_gen = FastrandDice::with_seed(seed.wrapping_add(pair));
+                let mut paired_score = PairedScore::default();
+                paired_score.add(score(self, &mut dice_gen));
+                paired_score
+            })
+            .reduce(PairedScore::default, |a, b| a.combine(&b))
+    }
+}
+
+/// Positive if the first player wins, negative if the second player wins.
+pub fn points(result: &GameResult) -> i32 {
+    Probabilities::from(result).equity() as i32
+}
+
+/// Average of a score that is measured in mirrored pairs, for example points per game, together
+/// with its confidence interval.
+///
+/// Both games of a pair are played with the same dice, so their results are strongly correlated.
+/// That's why the variance is measured over the pairs, not over single games.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PairedScore {
+    pairs: u64,
+    sum: f64,
+    sum_of_squares: f64,
+}
+
+impl PairedScore {
+    pub fn add(&mut self, pair: [f64; 2]) {
+        let value = (pair[0] + pair[1]) / 2.0;
+        self.pairs += 1;
+        self.sum += value;
+        self.sum_of_squares += value * value;
+    }
+
+    pub fn combine(self, other: &PairedScore) -> Self {
+        Self {
+            pairs: self.pairs + other.pairs,
+            sum: self.sum + other.sum,
+            sum_of_squares: self.sum_of_squares + other.sum_of_squares,
+        }
+    }
+
+    /// Number of single games or matches, twice the number of pairs.
+    pub fn games(&self) -> u64 {
+        2 * self.pairs
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum / self.pairs as f64
+    }
+
+    /// Half the width of the 95% confidence interval around [PairedScore::mean].
+    pub fn confidence_95(&self) -> f64 {
+        if self.pairs < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.pairs as f64;
+        let variance = (self.sum_of_squares - self.sum * self.sum / n) / (n - 1.0);
+        1.96 * (variance.max(0.0) / n).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::duel::{points, PairedScore};
+    use engine::position::GameResult::{LoseGammon, LoseNormal, WinGammon, WinNormal};
+
+    #[test]
+    fn points_of_results() {
+        assert_eq!(points(&WinNormal), 1);
+        assert_eq!(points(&WinGammon), 2);
+        assert_eq!(points(&LoseNormal), -1);
+        assert_eq!(points(&LoseGammon), -2);
+    }
+
+    #[test]
+    fn paired_score() {
+        // Given
+        let mut score = PairedScore::default();
+        // When
+        score.add([1.0, -1.0]);
+        score.add([2.0, 1.0]);
+        score.add([1.0, 1.0]);
+        score.add([-2.0, -1.0]);
+        // Then
+        assert_eq!(score.games(), 8);
+        // The pairs have the values 0.0, 1.5, 1.0 and -1.5 with a variance of 1.75
+        assert_eq!(score.mean(), 0.25);
+        assert!((score.confidence_95() - 1.96 * (1.75f64 / 4.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_pair_has_no_confidence() {
+        let mut score = PairedScore::default();
+        score.add([1.0, -1.0]);
+        assert_eq!(score.confidence_95(), f64::INFINITY);
     }
 }
@@ -0,0 +1,98 @@
+use crate::data::PositionRecord;
+use std::io::{Read, Write};
+
+/// Version of the CSV layout written by [export]. It's part of the file name of exported data.
+///
+/// Increase it whenever a column of [PositionRecord] is added, removed or changes its meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Prepares training data for publishing and returns the number of exported records.
+///
+/// Only the columns of [PositionRecord] are kept, everything else in the input is dropped.
+/// The rows are shuffled, so that the order in which the positions were found or rolled out can't be seen anymore.
+/// The labels themselves are not changed.
+pub fn export(
+    reader: impl Read,
+    writer: impl Write,
+    rng: &mut fastrand::Rng,
+) -> csv::Result<usize> {
+    let mut records: Vec<PositionRecord> = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(reader)
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    rng.shuffle(&mut records);
+
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    csv_writer.write_record(PositionRecord::csv_header())?;
+    for record in &records {
+        csv_writer.serialize(record)?;
+    }
+    csv_writer.flush()?;
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::export;
+
+    #[test]
+    fn drops_unknown_columns_and_keeps_labels() {
+        // Given
+        let input = "\
+position_id,win,win_g,lose_g,run,machine
+4HPwATDgc/ABMA,0.5,0.1,0.2,17,laptop
+jGfkASjg8wcBMA,0.25,0,0.5,17,laptop
+";
+        let mut output: Vec<u8> = Vec::new();
+        // When
+        let count = export(
+            input.as_bytes(),
+            &mut output,
+            &mut fastrand::Rng::with_seed(3),
+        )
+        .unwrap();
+        // Then
+        assert_eq!(count, 2);
+        let output = String::from_utf8(output).unwrap();
+        let mut lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.remove(0), "position_id,win,win_g,lose_g");
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec!["4HPwATDgc/ABMA,0.5,0.1,0.2", "jGfkASjg8wcBMA,0.25,0.0,0.5"]
+        );
+    }
+
+    #[test]
+    fn shuffles_rows() {
+        // Given
+        let ids = [
+            "4HPwATDgc/ABMA",
+            "jGfkASjg8wcBMA",
+            "4HPwATDgc/AAMA",
+            "4HPwATDgc/ACMA",
+        ];
+        let mut input = "position_id,win,win_g,lose_g\n".to_string();
+        for id in ids {
+            input.push_str(&format!("{id},0.5,0.0,0.0\n"));
+        }
+        // When
+        let orders: Vec<String> = (0..10)
+            .map(|seed| {
+                let mut output: Vec<u8> = Vec::new();
+                export(
+                    input.as_bytes(),
+                    &mut output,
+                    &mut fastrand::Rng::with_seed(seed),
+                )
+                .unwrap();
+                String::from_utf8(output).unwrap()
+            })
+            .collect();
+        // Then
+        assert!(orders.iter().any(|order| order != &orders[0]));
+    }
+}
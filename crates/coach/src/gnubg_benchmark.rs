@@ -0,0 +1,319 @@
+use engine::dice::Dice;
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use rayon::prelude::*;
+use std::io::BufRead;
+
+// The benchmark databases of GNU Backgammon, for example `contact.bm`, `crashed.bm` and `race.bm`
+// from https://alpha.gnu.org/gnu/gnubg/nn-training/ contain two kinds of lines:
+//
+// `m <position id> <die 1> <die 2> <best move> <move 2> <loss 2> <move 3> <loss 3> ...`
+// lists the best moves for a position and dice, together with the equity they lose
+// compared to the best move, according to rollouts.
+//
+// `o <position id> <win> <win gammon> <win backgammon> <lose gammon> <lose backgammon>`
+// contains rollout probabilities of a position.
+//
+// Moves are GnuBG position IDs of the position after moving. Other lines are ignored.
+
+/// One line of a GnuBG benchmark database.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BenchmarkEntry {
+    Move {
+        position: Position,
+        dice: Dice,
+        /// Positions after the listed moves with their equity loss, the best move first.
+        moves: Vec<(Position, f32)>,
+    },
+    Rollout {
+        position: Position,
+        probabilities: Probabilities,
+    },
+}
+
+impl BenchmarkEntry {
+    /// Returns `None` for lines which are neither move nor rollout entries.
+    pub fn parse(line: &str) -> Result<Option<Self>, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first() {
+            Some(&"m") => Self::parse_move(&tokens[1..]).map(Some),
+            Some(&"o") => Self::parse_rollout(&tokens[1..]).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_move(tokens: &[&str]) -> Result<Self, String> {
+        if tokens.len() < 4 || !tokens.len().is_multiple_of(2) {
+            return Err(format!("Invalid move entry: {}", tokens.join(" ")));
+        }
+        let position = parse_position(tokens[0])?;
+        let die = |token: &str| match token.parse::<usize>() {
+            Ok(die) if (1..=6).contains(&die) => Ok(die),
+            _ => Err(format!("'{token}' is not a die.")),
+        };
+        let dice = Dice::new(die(tokens[1])?, die(tokens[2])?);
+        let mut moves = vec![(parse_position(tokens[3])?, 0.0)];
+        for pair in tokens[4..].chunks_exact(2) {
+            let loss = pair[1]
+                .parse::<f32>()
+                .ok()
+                .filter(|loss| loss.is_finite() && *loss >= 0.0)
+                .ok_or_else(|| format!("'{}' is not an equity loss.", pair[1]))?;
+            moves.push((parse_position(pair[0])?, loss));
+        }
+        Ok(Self::Move {
+            position,
+            dice,
+            moves,
+        })
+    }
+
+    fn parse_rollout(tokens: &[&str]) -> Result<Self, String> {
+        if tokens.len() != 6 {
+            return Err(format!("Invalid rollout entry: {}", tokens.join(" ")));
+        }
+        let position = parse_position(tokens[0])?;
+        let values = tokens[1..]
+            .iter()
+            .map(|token| {
+                token
+                    .parse::<f32>()
+                    .map_err(|_| format!("'{token}' is not a probability."))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        let (win, win_gammon, lose_gammon) = (values[0], values[1], values[3]);
+        let probabilities = Probabilities::try_new(
+            win - win_gammon,
+            win_gammon,
+            1.0 - win - lose_gammon,
+            lose_gammon,
+        )
+        .map_err(|error| format!("Invalid rollout entry: {}. {error}", tokens.join(" ")))?;
+        Ok(Self::Rollout {
+            position,
+            probabilities,
+        })
+    }
+}
+
+fn parse_position(id: &str) -> Result<Position, String> {
+    Position::try_from_id(id).map_err(|error| format!("{id}: {error}"))
+}
+
+/// Reads all move and rollout entries of a benchmark database.
+pub fn read_entries(reader: impl BufRead) -> Result<Vec<BenchmarkEntry>, String> {
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|error| error.to_string())?;
+        if let Some(entry) = BenchmarkEntry::parse(&line)? {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// How far the choices and evaluations of an evaluator are away from the benchmark.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchmarkScore {
+    pub moves: usize,
+    /// Sum of the equity lost by all chosen moves.
+    pub move_loss: f64,
+    pub rollouts: usize,
+    /// Sum of the absolute differences between evaluated and rolled out equities.
+    pub equity_error: f64,
+}
+
+impl BenchmarkScore {
+    /// The usual benchmark number of GnuBG: average equity loss per move in thousandths.
+    ///
+    /// 0 if there are no move entries.
+    pub fn error_rate(&self) -> f64 {
+        1000.0 * self.move_loss / self.moves.max(1) as f64
+    }
+
+    /// Average absolute equity error of the evaluations in thousandths.
+    ///
+    /// 0 if there are no rollout entries.
+    pub fn equity_error_rate(&self) -> f64 {
+        1000.0 * self.equity_error / self.rollouts.max(1) as f64
+    }
+
+    fn combine(self, other: Self) -> Self {
+        Self {
+            moves: self.moves + other.moves,
+            move_loss: self.move_loss + other.move_loss,
+            rollouts: self.rollouts + other.rollouts,
+            equity_error: self.equity_error + other.equity_error,
+        }
+    }
+}
+
+/// Equity which an unlisted move loses in addition to the worst listed move.
+///
+/// The databases only list the best moves, so an unlisted move is worse than all of them.
+pub const UNLISTED_MOVE_MARGIN: f32 = 0.01;
+
+/// Lets `evaluator` choose a move for each move entry and evaluate each rollout entry.
+///
+/// If the chosen move is not listed, it loses [UNLISTED_MOVE_MARGIN] more than the worst listed move.
+pub fn score<T: Evaluator + Sync>(evaluator: &T, entries: &[BenchmarkEntry]) -> BenchmarkScore {
+    entries
+        .par_iter()
+        .map(|entry| match entry {
+            BenchmarkEntry::Move {
+                position,
+                dice,
+                moves,
+            } => {
+                let chosen = evaluator.best_position_by_equity(position, dice);
+                // Databases differ in whether positions after moving are seen from the player
+                // who has moved or the opponent, so we look for both.
+                let listed = chosen.sides_switched();
+                let loss = match moves
+                    .iter()
+                    .find(|(after_move, _)| *after_move == listed || *after_move == chosen)
+                {
+                    Some((_, loss)) => *loss,
+                    None => {
+                        let worst = moves.iter().map(|(_, loss)| *loss).fold(0.0, f32::max);
+                        worst + UNLISTED_MOVE_MARGIN
+                    }
+                };
+                BenchmarkScore {
+                    moves: 1,
+                    move_loss: loss as f64,
+                    ..Default::default()
+                }
+            }
+            BenchmarkEntry::Rollout {
+                position,
+                probabilities,
+            } => {
+                let error = evaluator.eval(position).equity() - probabilities.equity();
+                BenchmarkScore {
+                    rollouts: 1,
+                    equity_error: error.abs() as f64,
+                    ..Default::default()
+                }
+            }
+        })
+        .reduce(BenchmarkScore::default, BenchmarkScore::combine)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gnubg_benchmark::{read_entries, score, BenchmarkEntry, BenchmarkScore};
+    use engine::dice::Dice;
+    use engine::pos;
+    use engine::position::Position;
+    use engine::test_utils::EvaluatorFake;
+
+    fn position() -> Position {
+        pos!(x 8:1, 6:1; o 19:2)
+    }
+
+    /// The move 8/5 6/5 makes the 5 point, which the fake evaluator likes most.
+    fn best() -> Position {
+        pos!(x 5:2; o 19:2)
+    }
+
+    fn other() -> Position {
+        pos!(x 7:1, 3:1; o 19:2)
+    }
+
+    fn another() -> Position {
+        pos!(x 8:1, 2:1; o 19:2)
+    }
+
+    fn move_line(moves: &[(Position, f32)]) -> String {
+        let mut line = format!(
+            "m {} 3 1 {}",
+            position().position_id(),
+            moves[0].0.position_id()
+        );
+        for (position, loss) in &moves[1..] {
+            line.push_str(&format!(" {} {}", position.position_id(), loss));
+        }
+        line
+    }
+
+    #[test]
+    fn parse_move() {
+        // Given
+        let line = move_line(&[(best(), 0.0), (other(), 0.125)]);
+        // When
+        let entry = BenchmarkEntry::parse(&line).unwrap().unwrap();
+        // Then
+        assert_eq!(
+            entry,
+            BenchmarkEntry::Move {
+                position: position(),
+                dice: Dice::new(3, 1),
+                moves: vec![(best(), 0.0), (other(), 0.125)],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rollout() {
+        let line = format!("o {} 0.6 0.2 0.01 0.1 0.02", position().position_id());
+
+        let entry = BenchmarkEntry::parse(&line).unwrap().unwrap();
+
+        let BenchmarkEntry::Rollout {
+            position: parsed,
+            probabilities,
+        } = entry
+        else {
+            panic!("Expected a rollout entry.");
+        };
+        assert_eq!(parsed, position());
+        assert!((probabilities.win_normal - 0.4).abs() < 0.0001);
+        assert!((probabilities.win_gammon - 0.2).abs() < 0.0001);
+        assert!((probabilities.lose_normal - 0.3).abs() < 0.0001);
+        assert!((probabilities.lose_gammon - 0.1).abs() < 0.0001);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(BenchmarkEntry::parse("# comment"), Ok(None));
+        assert_eq!(
+            BenchmarkEntry::parse("m 4HPwATDgc/ABMA 3 7 4HPwATDgc/ABMA"),
+            Err("'7' is not a die.".to_string())
+        );
+        assert!(BenchmarkEntry::parse("o 4HPwATDgc/ABMA 0.5").is_err());
+        assert!(BenchmarkEntry::parse("o 4HPwATDgc/ABMA NaN 0.0 0.0 0.0 0.0").is_err());
+        assert!(BenchmarkEntry::parse("o 4HPwATDgc/ABMA 1.5 0.0 0.0 0.0 0.0").is_err());
+        assert_eq!(
+            BenchmarkEntry::parse("m 4HPwATDgc/ABMA 3 1 4HPwATDgc/ABMA 4HPwATDgc/ABMA NaN"),
+            Err("'NaN' is not an equity loss.".to_string())
+        );
+        assert_eq!(BenchmarkScore::default().error_rate(), 0.0);
+        assert_eq!(BenchmarkScore::default().equity_error_rate(), 0.0);
+    }
+
+    #[test]
+    fn score_moves_and_rollouts() {
+        // Given
+        let lines = [
+            // The best move is found.
+            move_line(&[(best(), 0.0), (other(), 0.1)]),
+            // The benchmark prefers another move, the chosen one loses 0.2.
+            move_line(&[(other(), 0.0), (best(), 0.2), (another(), 0.3)]),
+            // The chosen move is not listed, so it loses more than the worst listed one.
+            move_line(&[(other(), 0.0), (another(), 0.24)]),
+            // The fake evaluator has an equity of 0.0 for this position.
+            format!("o {} 0.55 0.0 0.0 0.0 0.0", position().position_id()),
+        ]
+        .join("\n");
+        let entries = read_entries(lines.as_bytes()).unwrap();
+        // When
+        let score = score(&EvaluatorFake {}, &entries);
+        // Then
+        assert_eq!(score.moves, 3);
+        assert!((score.error_rate() - 150.0).abs() < 0.001);
+        assert_eq!(score.rollouts, 1);
+        assert!((score.equity_error_rate() - 100.0).abs() < 0.001);
+    }
+}
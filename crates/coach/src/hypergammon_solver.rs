@@ -0,0 +1,168 @@
+use engine::dice::ALL_21;
+use engine::hypergammon::{game_state, HypergammonEvaluator};
+use engine::position::GameState::{GameOver, Ongoing};
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use rayon::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Calculates exact cubeless probabilities for all positions reachable from a starting position.
+///
+/// This is only feasible for small state spaces like hypergammon. The probabilities are found by
+/// value iteration: Each iteration evaluates every position one ply deep, based on the values of the
+/// previous iteration. Because checkers can be hit, the state space contains cycles, so several
+/// iterations are needed until the values converge.
+pub struct ExactSolver {
+    /// All ongoing positions, from the point of view of the player on roll.
+    positions: Vec<Position>,
+    indices: HashMap<Position, usize>,
+    values: Vec<Probabilities>,
+}
+
+impl ExactSolver {
+    /// Finds all ongoing positions reachable from `start` and initializes them with a 50:50 guess.
+    pub fn new(start: Position) -> Self {
+        let mut positions = vec![start];
+        let mut indices = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(position) = queue.pop_front() {
+            for (dice, _) in ALL_21 {
+                for next in position.all_positions_after_moving(&dice) {
+                    if game_state(&next) == Ongoing && !indices.contains_key(&next) {
+                        indices.insert(next, positions.len());
+                        positions.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        let values = vec![
+            Probabilities {
+                win_normal: 0.5,
+                lose_normal: 0.5,
+                ..Default::default()
+            };
+            positions.len()
+        ];
+        Self {
+            positions,
+            indices,
+            values,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Does one iteration over all positions and returns the biggest change in equity.
+    pub fn iterate(&mut self) -> f32 {
+        let new_values: Vec<Probabilities> = self
+            .positions
+            .par_iter()
+            .map(|position| self.one_ply(position))
+            .collect();
+        let max_change = new_values
+            .iter()
+            .zip(&self.values)
+            .map(|(new, old)| (new.equity() - old.equity()).abs())
+            .fold(0.0, f32::max);
+        self.values = new_values;
+        max_change
+    }
+
+    /// Iterates until the biggest change in equity is smaller than `max_change`.
+    pub fn solve(&mut self, max_change: f32) {
+        while self.iterate() >= max_change {}
+    }
+
+    pub fn into_evaluator(self) -> HypergammonEvaluator {
+        HypergammonEvaluator::new(self.positions.into_iter().zip(self.values).collect())
+    }
+
+    /// Averages over all 21 dice. For each roll the best move according to the current values is chosen.
+    fn one_ply(&self, position: &Position) -> Probabilities {
+        let best: Vec<(Probabilities, f32)> = ALL_21
+            .iter()
+            .map(|(dice, amount)| {
+                // Values of `next` are from the point of view of the opponent, so the best move has the lowest equity.
+                let best = position
+                    .all_positions_after_moving(dice)
+                    .iter()
+                    .map(|next| self.value(next))
+                    .min_by(|a, b| a.equity().total_cmp(&b.equity()))
+                    .expect("There is always at least one legal move.")
+                    .switch_sides();
+                (best, *amount as f32)
+            })
+            .collect();
+        Probabilities::weighted_average(best.iter().map(|(p, amount)| (p, *amount)))
+            .expect("The weights of all rolls sum up to 36.")
+    }
+
+    fn value(&self, position: &Position) -> Probabilities {
+        match game_state(position) {
+            GameOver(result) => Probabilities::from(&result),
+            Ongoing => self.values[self.indices[position]].clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hypergammon_solver::ExactSolver;
+    use engine::evaluator::Evaluator;
+    use engine::pos;
+
+    #[test]
+    fn certain_win() {
+        // Given
+        let mut solver = ExactSolver::new(pos!(x 2:1; o 23:1));
+        // When
+        solver.solve(0.0001);
+        // Then
+        let evaluator = solver.into_evaluator();
+        assert_eq!(evaluator.eval(&pos!(x 2:1; o 23:1)).win_normal, 1.0);
+    }
+
+    #[test]
+    fn short_race_converges() {
+        // Given
+        let start = pos!(x 6:1; o 19:1);
+        let mut solver = ExactSolver::new(start);
+        // When
+        solver.solve(0.00001);
+        // Then
+        let evaluator = solver.into_evaluator();
+        let probabilities = evaluator.eval(&start);
+        assert!(probabilities.is_consistent());
+        // All rolls apart from 11, 21, 31, 41 and 32 bear off at once.
+        assert!(probabilities.win_normal > 27.0 / 36.0);
+        assert_eq!(probabilities.win_gammon, 0.0);
+        // Values must be a fixed point of the solver
+        let mut check = ExactSolver::new(start);
+        check.values = check
+            .positions
+            .iter()
+            .map(|position| evaluator.eval(position))
+            .collect();
+        assert!(check.iterate() < 0.0001);
+    }
+
+    #[test]
+    fn contact_with_gammons() {
+        // Given
+        let start = pos!(x 3:1; o 1:1, 2:1, 4:1);
+        let mut solver = ExactSolver::new(start);
+        // When
+        solver.solve(0.0001);
+        // Then
+        let probabilities = solver.into_evaluator().eval(&start);
+        assert!(probabilities.is_consistent());
+        assert!(probabilities.win_gammon > 0.0);
+    }
+}
@@ -0,0 +1,151 @@
+use engine::probabilities::Probabilities;
+use rayon::prelude::*;
+
+/// Number of samples passed at once to the prediction function.
+const BATCH_SIZE: usize = 1000;
+
+/// How much a neural net depends on a single input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputImportance {
+    /// Index of the input, as in [engine::inputs::InputsGen::fill_inputs].
+    pub input: usize,
+    /// Increase of the mean absolute equity error when the values of this input are shuffled
+    /// between the samples. Values close to zero mean the net doesn't use this input.
+    pub importance: f32,
+}
+
+/// Rollout labeled inputs for measuring the importance of inputs.
+pub struct ImportanceData {
+    num_inputs: usize,
+    /// `num_inputs` values for each sample.
+    inputs: Vec<f32>,
+    labels: Vec<Probabilities>,
+}
+
+impl ImportanceData {
+    pub fn new(num_inputs: usize, inputs: Vec<f32>, labels: Vec<Probabilities>) -> Self {
+        assert_eq!(inputs.len(), num_inputs * labels.len());
+        Self {
+            num_inputs,
+            inputs,
+            labels,
+        }
+    }
+
+    /// Mean absolute equity error of `predict` on the unmodified inputs.
+    ///
+    /// `predict` takes the inputs of several samples and returns their probabilities, like
+    /// [engine::onnx::OnnxEvaluator::eval_inputs].
+    pub fn baseline_error<F>(&self, predict: &F) -> f32
+    where
+        F: Fn(Vec<f32>) -> Vec<Probabilities> + Sync,
+    {
+        self.error(predict, &self.inputs)
+    }
+
+    /// Permutation importance of all inputs, the most important first.
+    ///
+    /// For each input its values are shuffled between the samples, so that the input carries no
+    /// information anymore while its distribution stays the same. The more the error increases,
+    /// the more important the input is.
+    pub fn permutation_importance<F>(&self, predict: &F, seed: u64) -> Vec<InputImportance>
+    where
+        F: Fn(Vec<f32>) -> Vec<Probabilities> + Sync,
+    {
+        let baseline = self.baseline_error(predict);
+        let mut importances: Vec<InputImportance> = (0..self.num_inputs)
+            .into_par_iter()
+            .map(|input| {
+                let permuted = self.permuted(input, seed.wrapping_add(input as u64));
+                InputImportance {
+                    input,
+                    importance: self.error(predict, &permuted) - baseline,
+                }
+            })
+            .collect();
+        importances.sort_by(|a, b| b.importance.total_cmp(&a.importance));
+        importances
+    }
+
+    /// Copy of all inputs with the values of the given input shuffled between the samples.
+    fn permuted(&self, input: usize, seed: u64) -> Vec<f32> {
+        let mut column: Vec<f32> = self
+            .inputs
+            .iter()
+            .skip(input)
+            .step_by(self.num_inputs)
+            .copied()
+            .collect();
+        fastrand::Rng::with_seed(seed).shuffle(&mut column);
+        let mut permuted = self.inputs.clone();
+        permuted
+            .chunks_exact_mut(self.num_inputs)
+            .zip(column)
+            .for_each(|(sample, value)| sample[input] = value);
+        permuted
+    }
+
+    fn error<F>(&self, predict: &F, inputs: &[f32]) -> f32
+    where
+        F: Fn(Vec<f32>) -> Vec<Probabilities>,
+    {
+        if self.labels.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = inputs
+            .chunks(BATCH_SIZE * self.num_inputs)
+            .zip(self.labels.chunks(BATCH_SIZE))
+            .map(|(batch, labels)| {
+                predict(batch.to_vec())
+                    .iter()
+                    .zip(labels)
+                    .map(|(predicted, label)| (predicted.equity() - label.equity()).abs())
+                    .sum::<f32>()
+            })
+            .sum();
+        sum / self.labels.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::importance::ImportanceData;
+    use engine::probabilities::Probabilities;
+
+    fn probabilities(win: f32) -> Probabilities {
+        Probabilities {
+            win_normal: win,
+            lose_normal: 1.0 - win,
+            ..Default::default()
+        }
+    }
+
+    /// Only looks at the first input.
+    fn predict(inputs: Vec<f32>) -> Vec<Probabilities> {
+        inputs
+            .chunks_exact(3)
+            .map(|sample| probabilities(sample[0]))
+            .collect()
+    }
+
+    #[test]
+    fn unused_inputs_are_unimportant() {
+        // Given
+        // The first input varies and determines the label, the second varies and is ignored,
+        // the third is always the same.
+        let inputs: Vec<f32> = (0..100)
+            .flat_map(|i| [i as f32 / 100.0, (i % 7) as f32, 1.0])
+            .collect();
+        let labels: Vec<Probabilities> =
+            (0..100).map(|i| probabilities(i as f32 / 100.0)).collect();
+        let data = ImportanceData::new(3, inputs, labels);
+        // When
+        let importances = data.permutation_importance(&predict, 0);
+        // Then
+        assert_eq!(data.baseline_error(&predict), 0.0);
+        assert_eq!(importances[0].input, 0);
+        assert!(importances[0].importance > 0.3);
+        assert_eq!(importances[1].importance, 0.0);
+        assert_eq!(importances[2].importance, 0.0);
+    }
+}
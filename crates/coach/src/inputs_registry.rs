@@ -0,0 +1,156 @@
+use engine::inputs::{
+    ContactInputsGen, GnubgInputsGen, InputsGen, RaceInputsGen, TesauroInputsGen,
+};
+use engine::position::Position;
+use std::collections::BTreeMap;
+
+/// Object safe version of [InputsGen], so that generators can be chosen at runtime.
+///
+/// Implemented for all [InputsGen]s, so there is nothing to do besides implementing [InputsGen].
+pub trait DynInputsGen: Sync {
+    fn num_inputs(&self) -> usize;
+
+    fn inputs_version(&self) -> &'static str;
+
+    fn inputs_for_single(&self, pos: &Position) -> Vec<f32>;
+
+    fn inputs_for_all(&self, positions: &[Position]) -> Vec<f32>;
+}
+
+impl<T: InputsGen + Sync> DynInputsGen for T {
+    fn num_inputs(&self) -> usize {
+        T::NUM_INPUTS
+    }
+
+    fn inputs_version(&self) -> &'static str {
+        T::INPUTS_VERSION
+    }
+
+    fn inputs_for_single(&self, pos: &Position) -> Vec<f32> {
+        InputsGen::inputs_for_single(self, pos)
+    }
+
+    fn inputs_for_all(&self, positions: &[Position]) -> Vec<f32> {
+        InputsGen::inputs_for_all(self, positions)
+    }
+}
+
+/// Inputs generators by name, for example to pick one with a command line option.
+///
+/// The default registry contains the generators of the engine crate. Experiments with another
+/// encoding only need to register it:
+///
+/// ```
+/// use coach::inputs_registry::InputsRegistry;
+/// use engine::inputs::RaceInputsGen;
+///
+/// let mut registry = InputsRegistry::default();
+/// registry.register("my-race", RaceInputsGen {}).unwrap();
+/// assert_eq!(registry.get("my-race").unwrap().num_inputs(), 186);
+/// ```
+pub struct InputsRegistry {
+    generators: BTreeMap<String, Box<dyn DynInputsGen>>,
+}
+
+impl Default for InputsRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.insert("contact", ContactInputsGen {});
+        registry.insert("race", RaceInputsGen {});
+        registry.insert("gnubg", GnubgInputsGen {});
+        registry.insert("tesauro", TesauroInputsGen {});
+        registry
+    }
+}
+
+impl InputsRegistry {
+    /// A registry without any generators.
+    pub fn empty() -> Self {
+        Self {
+            generators: BTreeMap::new(),
+        }
+    }
+
+    /// Adds `inputs_gen` under `name`. Returns an error if the name is already taken.
+    pub fn register<T: InputsGen + Sync + 'static>(
+        &mut self,
+        name: &str,
+        inputs_gen: T,
+    ) -> Result<(), String> {
+        if self.generators.contains_key(name) {
+            return Err(format!("Inputs generator '{name}' is already registered."));
+        }
+        self.insert(name, inputs_gen);
+        Ok(())
+    }
+
+    fn insert<T: InputsGen + Sync + 'static>(&mut self, name: &str, inputs_gen: T) {
+        self.generators
+            .insert(name.to_string(), Box::new(inputs_gen));
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn DynInputsGen, String> {
+        self.generators
+            .get(name)
+            .map(|inputs_gen| inputs_gen.as_ref())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown inputs generator '{name}', use one of: {}.",
+                    self.names().join(", ")
+                )
+            })
+    }
+
+    /// All registered names in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.generators.keys().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs_registry::InputsRegistry;
+    use engine::inputs::{InputsGen, RaceInputsGen};
+    use engine::position::STARTING;
+
+    #[test]
+    fn default_generators() {
+        let registry = InputsRegistry::default();
+        assert_eq!(
+            registry.names(),
+            vec!["contact", "gnubg", "race", "tesauro"]
+        );
+        let race = registry.get("race").unwrap();
+        assert_eq!(race.num_inputs(), RaceInputsGen::NUM_INPUTS);
+        assert_eq!(
+            race.inputs_for_single(&STARTING),
+            RaceInputsGen {}.inputs_for_single(&STARTING)
+        );
+    }
+
+    #[test]
+    fn register_custom_generator() {
+        // Given
+        let mut registry = InputsRegistry::empty();
+        // When
+        let first = registry.register("custom", RaceInputsGen {});
+        let second = registry.register("custom", RaceInputsGen {});
+        // Then
+        assert_eq!(first, Ok(()));
+        assert_eq!(
+            second,
+            Err("Inputs generator 'custom' is already registered.".to_string())
+        );
+        assert_eq!(registry.names(), vec!["custom"]);
+    }
+
+    #[test]
+    fn unknown_generator() {
+        let registry = InputsRegistry::default();
+        let error = registry.get("foo").err().unwrap();
+        assert_eq!(
+            error,
+            "Unknown inputs generator 'foo', use one of: contact, gnubg, race, tesauro."
+        );
+    }
+}
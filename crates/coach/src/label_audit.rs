@@ -0,0 +1,173 @@
+use engine::probabilities::Probabilities;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Randomly picks about `fraction` of all `records`. The same `seed` always picks the same records.
+pub fn sample<T>(records: &[T], fraction: f32, seed: u64) -> Vec<&T> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    records.iter().filter(|_| rng.f32() < fraction).collect()
+}
+
+/// Averages several rollouts of the same position, for example done with different seeds.
+///
+/// Panics if `probabilities` is empty.
+pub fn average(probabilities: &[Probabilities]) -> Probabilities {
+    Probabilities::average(probabilities).expect("At least one rollout is needed.")
+}
+
+/// Absolute errors of single values of `f32`, sorted lazily when a statistic is requested.
+#[derive(Default)]
+pub struct ErrorDistribution {
+    errors: Vec<f32>,
+}
+
+impl ErrorDistribution {
+    pub fn add(&mut self, error: f32) {
+        self.errors.push(error.abs());
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.errors.iter().sum::<f32>() / self.errors.len() as f32
+    }
+
+    /// `quantile` must be between 0.0 and 1.0. The median is `percentile(0.5)`.
+    pub fn percentile(&self, quantile: f32) -> f32 {
+        let mut sorted = self.errors.clone();
+        sorted.sort_unstable_by(f32::total_cmp);
+        let index = ((sorted.len() - 1) as f32 * quantile).round() as usize;
+        sorted[index]
+    }
+
+    pub fn max(&self) -> f32 {
+        self.errors.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+impl fmt::Display for ErrorDistribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mean {:.4}; median {:.4}; p90 {:.4}; p99 {:.4}; max {:.4}",
+            self.mean(),
+            self.percentile(0.5),
+            self.percentile(0.9),
+            self.percentile(0.99),
+            self.max(),
+        )
+    }
+}
+
+/// Compares existing labels of a dataset with new rollouts of the same positions.
+///
+/// Values are compared in the "classic" 5 values format of [crate::data::PositionRecord]
+/// and additionally by cubeless equity.
+#[derive(Default)]
+pub struct LabelAudit {
+    pub win: ErrorDistribution,
+    pub win_g: ErrorDistribution,
+    pub lose_g: ErrorDistribution,
+    pub equity: ErrorDistribution,
+}
+
+impl LabelAudit {
+    pub fn add(&mut self, label: &Probabilities, rollout: &Probabilities) {
+        self.win.add(label.win() - rollout.win());
+        self.win_g.add(label.win_gammon - rollout.win_gammon);
+        self.lose_g.add(label.lose_gammon - rollout.lose_gammon);
+        self.equity.add(label.equity() - rollout.equity());
+    }
+}
+
+impl fmt::Display for LabelAudit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.equity.is_empty() {
+            return write!(f, "No positions audited.");
+        }
+        writeln!(f, "Audited {} positions.", self.equity.len())?;
+        writeln!(f, "win:    {}", self.win)?;
+        writeln!(f, "win_g:  {}", self.win_g)?;
+        writeln!(f, "lose_g: {}", self.lose_g)?;
+        write!(f, "equity: {}", self.equity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::label_audit::{average, sample, ErrorDistribution, LabelAudit};
+    use engine::probabilities::Probabilities;
+
+    #[test]
+    fn sample_is_deterministic_and_roughly_the_fraction() {
+        let records: Vec<usize> = (0..10_000).collect();
+        let first = sample(&records, 0.1, 42);
+        let second = sample(&records, 0.1, 42);
+        assert_eq!(first, second);
+        assert!(first.len() > 900 && first.len() < 1100);
+        assert!(sample(&records, 0.0, 42).is_empty());
+        assert_eq!(sample(&records, 1.0, 42).len(), 10_000);
+    }
+
+    #[test]
+    fn error_distribution_statistics() {
+        // Given
+        let mut distribution = ErrorDistribution::default();
+        // When
+        for error in [0.3, -0.1, 0.0, 0.2, -0.4] {
+            distribution.add(error);
+        }
+        // Then
+        assert_eq!(distribution.len(), 5);
+        assert!((distribution.mean() - 0.2).abs() < 0.0001);
+        assert_eq!(distribution.percentile(0.5), 0.2);
+        assert_eq!(distribution.percentile(0.0), 0.0);
+        assert_eq!(distribution.max(), 0.4);
+    }
+
+    #[test]
+    fn audit_compares_classic_values_and_equity() {
+        // Given
+        let label = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.25,
+            lose_normal: 0.25,
+            lose_gammon: 0.0,
+        };
+        let rollout = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.0,
+            lose_normal: 0.5,
+            lose_gammon: 0.0,
+        };
+        let mut audit = LabelAudit::default();
+        // When
+        audit.add(&label, &rollout);
+        // Then
+        assert_eq!(audit.win.max(), 0.25);
+        assert_eq!(audit.win_g.max(), 0.25);
+        assert_eq!(audit.lose_g.max(), 0.0);
+        assert_eq!(audit.equity.max(), 0.75);
+    }
+
+    #[test]
+    fn average_of_rollouts() {
+        let one = Probabilities {
+            win_normal: 1.0,
+            ..Default::default()
+        };
+        let two = Probabilities {
+            lose_gammon: 1.0,
+            ..Default::default()
+        };
+        let average = average(&[one, two]);
+        assert_eq!(average.win_normal, 0.5);
+        assert_eq!(average.lose_gammon, 0.5);
+    }
+}
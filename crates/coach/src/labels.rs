@@ -0,0 +1,88 @@
+use crate::rollout::RolloutEvaluator;
+use engine::evaluator::Evaluator;
+use engine::multiply::{discounted, one_ply_deeper};
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+
+/// Where the probabilities of the training data come from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LabelSource {
+    /// Rollouts with 1296 games per position. Most accurate, but by far the slowest step of the pipeline.
+    Rollout,
+    /// The current nets look one ply deeper, like [engine::multiply::MultiPlyEvaluator].
+    /// Much faster than a rollout, but the labels can't get much better than the nets themselves.
+    TwoPly,
+    /// Both of the above, mixed. A `two_ply_weight` of `0.0` equals [LabelSource::Rollout],
+    /// `1.0` equals [LabelSource::TwoPly].
+    Blend { two_ply_weight: f32 },
+}
+
+/// Evaluates positions for training data according to a [LabelSource].
+///
+/// Both rollouts and 2-ply evaluations use the evaluator of the [RolloutEvaluator].
+pub struct Labeler<T: Evaluator> {
+    rollout: RolloutEvaluator<T>,
+    source: LabelSource,
+}
+
+impl<T: Evaluator + Sync> Labeler<T> {
+    pub fn new(rollout: RolloutEvaluator<T>, source: LabelSource) -> Self {
+        if let LabelSource::Blend { two_ply_weight } = source {
+            assert!(
+                (0.0..=1.0).contains(&two_ply_weight),
+                "The weight of 2-ply must be between 0.0 and 1.0."
+            );
+        }
+        Self { rollout, source }
+    }
+
+    fn two_ply(&self, position: &Position) -> Probabilities {
+        one_ply_deeper(self.rollout.evaluator(), position, None)
+            .expect("Without deadline there is always a result.")
+    }
+}
+
+impl<T: Evaluator + Sync> Evaluator for Labeler<T> {
+    fn eval(&self, position: &Position) -> Probabilities {
+        match self.source {
+            LabelSource::Rollout => self.rollout.eval(position),
+            LabelSource::TwoPly => self.two_ply(position),
+            LabelSource::Blend { two_ply_weight } => discounted(
+                &self.two_ply(position),
+                &self.rollout.eval(position),
+                two_ply_weight,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::labels::{LabelSource, Labeler};
+    use crate::rollout::RolloutEvaluator;
+    use engine::evaluator::Evaluator;
+    use engine::pos;
+    use engine::test_utils::EvaluatorFake;
+
+    fn labeler(source: LabelSource) -> Labeler<EvaluatorFake> {
+        let rollout = RolloutEvaluator::with_evaluator_and_seed(EvaluatorFake {}, 1);
+        Labeler::new(rollout, source)
+    }
+
+    #[test]
+    fn blend_is_weighted() {
+        // Given
+        let position = pos!(x 6:1; o 19:1);
+        let rollout = labeler(LabelSource::Rollout).eval(&position);
+        let two_ply = labeler(LabelSource::TwoPly).eval(&position);
+        // When
+        let blend = labeler(LabelSource::Blend {
+            two_ply_weight: 0.25,
+        })
+        .eval(&position);
+        // Then
+        let expected = 0.25 * two_ply.win_normal + 0.75 * rollout.win_normal;
+        assert!((blend.win_normal - expected).abs() < 0.0001);
+        assert_ne!(rollout.win_normal, two_ply.win_normal);
+    }
+}
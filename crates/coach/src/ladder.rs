@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::{Read, Write};
+
+/// Neural nets of one training generation with their estimated Elo rating.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LadderEntry {
+    pub name: String,
+    pub contact: String,
+    pub race: String,
+    pub rating: f64,
+    /// Number of games played against other entries when this one was added.
+    pub games: u64,
+}
+
+impl fmt::Display for LadderEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<20} {:>7.0} {:>8}   {} {}",
+            self.name, self.rating, self.games, self.contact, self.race
+        )
+    }
+}
+
+/// Share of games which `player` won against `opponent`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LadderResult {
+    pub player: String,
+    pub opponent: String,
+    pub win_share: f64,
+    pub games: u64,
+}
+
+/// Ratings of all generations of neural nets, for tracking training progress.
+///
+/// The first entry is the anchor with a rating of 0. Each new entry plays against some of the
+/// existing ones. All results are kept, and after each new entry the ratings of all entries are
+/// fitted to them again with maximum likelihood.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ladder {
+    entries: Vec<LadderEntry>,
+    results: Vec<LadderResult>,
+}
+
+impl Ladder {
+    /// Reads the ladder from two CSV files; missing files are an empty ladder.
+    pub fn load(entries_path: &str, results_path: &str) -> std::io::Result<Self> {
+        let open = |path: &str| match std::fs::File::open(path) {
+            Ok(file) => Ok(Some(file)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        };
+        match (open(entries_path)?, open(results_path)?) {
+            (Some(entries), Some(results)) => Self::read(entries, results),
+            (Some(entries), None) => Self::read(entries, std::io::empty()),
+            (None, _) => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, entries_path: &str, results_path: &str) -> std::io::Result<()> {
+        self.write(
+            std::fs::File::create(entries_path)?,
+            std::fs::File::create(results_path)?,
+        )
+    }
+
+    pub fn read(entries: impl Read, results: impl Read) -> std::io::Result<Self> {
+        Ok(Self {
+            entries: read_csv(entries)?,
+            results: read_csv(results)?,
+        })
+    }
+
+    pub fn write(&self, entries: impl Write, results: impl Write) -> std::io::Result<()> {
+        write_csv(entries, &self.entries)?;
+        write_csv(results, &self.results)
+    }
+
+    /// Entries in the order in which they were added.
+    pub fn entries(&self) -> &[LadderEntry] {
+        &self.entries
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.name == name)
+    }
+
+    /// The `amount` entries with the highest ratings, which a new entry should play against.
+    pub fn opponents(&self, amount: usize) -> Vec<&LadderEntry> {
+        let mut opponents: Vec<&LadderEntry> = self.entries.iter().collect();
+        opponents.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        opponents.truncate(amount);
+        opponents
+    }
+
+    /// Adds a new entry and fits the ratings of all entries to all results again.
+    ///
+    /// `results` contains the names of the opponents with the share of games won against them
+    /// and the number of games played. An empty ladder needs no results, the first entry gets a rating of 0.
+    pub fn add(
+        &mut self,
+        name: &str,
+        contact: &str,
+        race: &str,
+        results: &[(String, f64, u64)],
+    ) -> Result<&LadderEntry, String> {
+        if self.contains(name) {
+            return Err(format!("'{name}' is already on the ladder."));
+        }
+        if results.is_empty() && !self.entries.is_empty() {
+            return Err("New entries need results against existing ones.".to_string());
+        }
+        for (opponent, win_share, games) in results {
+            if !self.contains(opponent) {
+                return Err(format!("'{opponent}' is not on the ladder."));
+            }
+            if *games == 0 {
+                return Err(format!("No games were played against '{opponent}'."));
+            }
+            if !(0.0..=1.0).contains(win_share) {
+                return Err(format!(
+                    "The share of games won against '{opponent}' must be between 0 and 1."
+                ));
+            }
+        }
+        self.entries.push(LadderEntry {
+            name: name.to_string(),
+            contact: contact.to_string(),
+            race: race.to_string(),
+            rating: 0.0,
+            games: results.iter().map(|(_, _, games)| games).sum(),
+        });
+        self.results.extend(
+            results
+                .iter()
+                .map(|(opponent, win_share, games)| LadderResult {
+                    player: name.to_string(),
+                    opponent: opponent.clone(),
+                    win_share: *win_share,
+                    games: *games,
+                }),
+        );
+        self.refit();
+        Ok(self.entries.last().unwrap())
+    }
+
+    /// Maximum likelihood ratings for all results, the first entry stays at 0.
+    ///
+    /// Each rating in turn gets a Newton step for the log-likelihood of its games, until no
+    /// rating changes anymore.
+    fn refit(&mut self) {
+        const SCALE: f64 = std::f64::consts::LN_10 / 400.0;
+        let index = |name: &str| self.entries.iter().position(|entry| entry.name == name);
+        // Player, opponent, clamped win share of the player and games.
+        let results: Vec<(usize, usize, f64, f64)> = self
+            .results
+            .iter()
+            .filter_map(|result| {
+                let share = clamped_share(result.win_share, result.games);
+                Some((
+                    index(&result.player)?,
+                    index(&result.opponent)?,
+                    share,
+                    result.games as f64,
+                ))
+            })
+            .collect();
+        let mut ratings: Vec<f64> = self.entries.iter().map(|entry| entry.rating).collect();
+        for _ in 0..1000 {
+            let mut largest_change: f64 = 0.0;
+            for i in 1..ratings.len() {
+                let (mut gradient, mut curvature) = (0.0, 0.0);
+                for &(player, opponent, share, games) in &results {
+                    let (share, other) = if player == i {
+                        (share, opponent)
+                    } else if opponent == i {
+                        (1.0 - share, player)
+                    } else {
+                        continue;
+                    };
+                    let expected = 1.0 / (1.0 + 10f64.powf((ratings[other] - ratings[i]) / 400.0));
+                    gradient += games * (share - expected);
+                    curvature += games * expected * (1.0 - expected);
+                }
+                if curvature > 0.0 {
+                    let change = gradient / (SCALE * curvature);
+                    ratings[i] += change;
+                    largest_change = largest_change.max(change.abs());
+                }
+            }
+            if largest_change < 1e-9 {
+                break;
+            }
+        }
+        for (entry, rating) in self.entries.iter_mut().zip(ratings) {
+            entry.rating = rating;
+        }
+    }
+}
+
+fn read_csv<T: serde::de::DeserializeOwned>(reader: impl Read) -> std::io::Result<Vec<T>> {
+    let values = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(reader)
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+    Ok(values)
+}
+
+fn write_csv<T: Serialize>(writer: impl Write, values: &[T]) -> std::io::Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    for value in values {
+        csv_writer.serialize(value)?;
+    }
+    csv_writer.flush()
+}
+
+/// Elo rating difference at which the stronger player wins `win_share` of all games.
+pub fn rating_difference(win_share: f64) -> f64 {
+    400.0 * (win_share / (1.0 - win_share)).log10()
+}
+
+/// Without winning or losing all games the rating difference would be infinite.
+/// So we assume that half a game of the other result would have happened.
+fn clamped_share(win_share: f64, games: u64) -> f64 {
+    let margin = 0.5 / games.max(1) as f64;
+    win_share.clamp(margin, 1.0 - margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ladder::{rating_difference, Ladder};
+
+    #[test]
+    fn rating_differences() {
+        assert_eq!(rating_difference(0.5), 0.0);
+        assert!((rating_difference(0.64) - 99.9).abs() < 0.1);
+        assert!((rating_difference(0.36) + 99.9).abs() < 0.1);
+    }
+
+    #[test]
+    fn ratings_are_weighted_by_games() {
+        // Given
+        let mut ladder = Ladder::default();
+        ladder.add("gen-1", "c1.onnx", "r1.onnx", &[]).unwrap();
+        ladder
+            .add(
+                "gen-2",
+                "c2.onnx",
+                "r2.onnx",
+                &[("gen-1".to_string(), 0.64, 1000)],
+            )
+            .unwrap();
+        // When
+        let results = [
+            ("gen-1".to_string(), 0.64, 1000),
+            ("gen-2".to_string(), 0.5, 3000),
+        ];
+        let entry = ladder.add("gen-3", "c3.onnx", "r3.onnx", &results).unwrap();
+        // Then
+        assert_eq!(entry.games, 4000);
+        assert!((entry.rating - 99.9).abs() < 0.1);
+        let names: Vec<&str> = ladder
+            .opponents(2)
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(!names.contains(&"gen-1"));
+    }
+
+    #[test]
+    fn all_games_won() {
+        let mut ladder = Ladder::default();
+        ladder.add("gen-1", "c1.onnx", "r1.onnx", &[]).unwrap();
+
+        let results = [("gen-1".to_string(), 1.0, 100)];
+        let entry = ladder.add("gen-2", "c2.onnx", "r2.onnx", &results).unwrap();
+
+        assert!((entry.rating - rating_difference(0.995)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn errors() {
+        let mut ladder = Ladder::default();
+        ladder.add("gen-1", "c1.onnx", "r1.onnx", &[]).unwrap();
+
+        assert_eq!(
+            ladder.add("gen-1", "c.onnx", "r.onnx", &[]),
+            Err("'gen-1' is already on the ladder.".to_string())
+        );
+        assert_eq!(
+            ladder.add("gen-2", "c.onnx", "r.onnx", &[]),
+            Err("New entries need results against existing ones.".to_string())
+        );
+        assert_eq!(
+            ladder.add(
+                "gen-2",
+                "c.onnx",
+                "r.onnx",
+                &[("gen-0".to_string(), 0.5, 10)]
+            ),
+            Err("'gen-0' is not on the ladder.".to_string())
+        );
+        assert_eq!(
+            ladder.add(
+                "gen-2",
+                "c.onnx",
+                "r.onnx",
+                &[("gen-1".to_string(), 0.5, 0)]
+            ),
+            Err("No games were played against 'gen-1'.".to_string())
+        );
+        assert_eq!(ladder.entries().len(), 1);
+    }
+
+    #[test]
+    fn ratings_are_refitted() {
+        // Given
+        let mut ladder = Ladder::default();
+        ladder.add("gen-1", "c1.onnx", "r1.onnx", &[]).unwrap();
+        let results = [("gen-1".to_string(), 0.64, 1000)];
+        ladder.add("gen-2", "c2.onnx", "r2.onnx", &results).unwrap();
+        assert!((ladder.entries()[1].rating - 99.9).abs() < 0.1);
+        // When
+        // gen-3 is as strong as gen-1 and gen-2, so gen-2 seems less strong than first estimated.
+        let results = [
+            ("gen-1".to_string(), 0.5, 1000),
+            ("gen-2".to_string(), 0.5, 1000),
+        ];
+        ladder.add("gen-3", "c3.onnx", "r3.onnx", &results).unwrap();
+        // Then
+        let ratings: Vec<f64> = ladder.entries().iter().map(|entry| entry.rating).collect();
+        assert_eq!(ratings[0], 0.0);
+        assert!(0.0 < ratings[1] && ratings[1] < 99.0);
+        assert!(0.0 < ratings[2] && ratings[2] < ratings[1]);
+    }
+
+    #[test]
+    fn write_and_read() {
+        // Given
+        let mut ladder = Ladder::default();
+        ladder.add("gen-1", "c1.onnx", "r1.onnx", &[]).unwrap();
+        ladder
+            .add(
+                "gen-2",
+                "c2.onnx",
+                "r2.onnx",
+                &[("gen-1".to_string(), 0.6, 500)],
+            )
+            .unwrap();
+        // When
+        let mut entries = Vec::new();
+        let mut results = Vec::new();
+        ladder.write(&mut entries, &mut results).unwrap();
+        // Then
+        let read = Ladder::read(entries.as_slice(), results.as_slice()).unwrap();
+        assert_eq!(read, ladder);
+    }
+}
@@ -1,6 +1,36 @@
+pub mod batch_queue;
+pub mod calibration;
 pub mod coach_helpers;
+pub mod config;
+pub mod cube_rollout;
 pub mod data;
+pub mod dedup;
 pub mod duel;
+pub mod export;
+pub mod gnubg_benchmark;
+pub mod hypergammon_solver;
+pub mod importance;
+pub mod inputs_registry;
+pub mod label_audit;
+pub mod labels;
+pub mod ladder;
+pub mod mat;
+pub mod match_analysis;
+pub mod npz;
+pub mod onnx_export;
+pub mod parquet;
+pub mod position_class;
 pub mod position_finder;
+pub mod position_index;
+pub mod prediction_metrics;
+pub mod progress;
+pub mod quarantine;
 pub mod rollout;
+pub mod self_play_stats;
+pub mod sgf;
+pub mod store;
+pub mod stored_rollout;
+pub mod td;
+pub mod training;
 pub mod unwrap;
+pub mod weakness;
@@ -0,0 +1,675 @@
+use engine::dice::Dice;
+use engine::dice_gen::DiceGen;
+use engine::evaluator::Evaluator;
+use engine::position::GameResult::{LoseGammon, LoseNormal, WinGammon, WinNormal};
+use engine::position::GameState::GameOver;
+use engine::position::{STARTING, X_BAR};
+use logic::analysis::MoveRecord;
+use logic::bg_move::{apply_checker_moves, BgMove};
+use logic::game_state::{GameState, Player};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A match as recorded in a Jellyfish/GNUBG `.mat` file.
+#[derive(Debug, PartialEq)]
+pub struct MatMatch {
+    /// `None` for money sessions.
+    pub length: Option<u32>,
+    /// Index 0 is the player in the left column, [Player::One].
+    pub players: [String; 2],
+    pub games: Vec<MatGame>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MatGame {
+    /// Score before the game started. Index 0 is for [Player::One].
+    pub score: [u32; 2],
+    pub actions: Vec<(Player, MatAction)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatAction {
+    /// Checker play as `(from, to)` pairs from the point of view of the player who moves.
+    /// The bar is `25`, bearing off is `0`. Empty if no checker could be moved.
+    Move {
+        dice: Dice,
+        checker_moves: Vec<(usize, usize)>,
+    },
+    Double,
+    Take,
+    Drop,
+    Wins(u32),
+}
+
+impl MatMatch {
+    /// Player by name as given in the score lines of the file.
+    pub fn player(&self, name: &str) -> Option<Player> {
+        match self.players.iter().position(|player| player == name) {
+            Some(0) => Some(Player::One),
+            Some(_) => Some(Player::Two),
+            None => None,
+        }
+    }
+
+    /// `true` if the game with the given index is the Crawford game.
+    ///
+    /// That's the first game in which one of the players needs only one more point.
+    pub fn is_crawford(&self, game_index: usize) -> bool {
+        let Some(length) = self.length else {
+            return false;
+        };
+        let one_away = |score: &[u32; 2]| score.iter().any(|points| points + 1 == length);
+        one_away(&self.games[game_index].score)
+            && (game_index == 0 || !one_away(&self.games[game_index - 1].score))
+    }
+
+    /// Appends `game` and sets its score according to the results of the previous games.
+    pub fn push(&mut self, mut game: MatGame) {
+        if let Some(previous) = self.games.last() {
+            game.score = previous.score;
+            if let Some((winner, points)) = previous.winner() {
+                game.score[winner_index(winner)] += points;
+            }
+        }
+        self.games.push(game);
+    }
+
+    /// The game states of all games, see [MatGame::replay].
+    pub fn replay(&self) -> Result<Vec<Vec<GameState>>, String> {
+        (0..self.games.len())
+            .map(|index| self.games[index].replay(self.is_crawford(index)))
+            .collect()
+    }
+}
+
+impl MatGame {
+    /// Winner and points as written in the file. `None` if the game has no result.
+    pub fn winner(&self) -> Option<(Player, u32)> {
+        self.actions
+            .iter()
+            .find_map(|(player, action)| match action {
+                MatAction::Wins(points) => Some((*player, *points)),
+                _ => None,
+            })
+    }
+
+    /// All checker plays of this game, each from the point of view of the player who moved.
+    ///
+    /// Returns an error if a checker play can't be applied to the position.
+    pub fn move_records(&self) -> Result<Vec<(Player, MoveRecord)>, String> {
+        let mut position = STARTING;
+        let mut records = Vec::new();
+        for (player, action) in &self.actions {
+            if let MatAction::Move {
+                dice,
+                checker_moves,
+            } = action
+            {
+                let played = apply_checker_moves(&position, checker_moves)?;
+                records.push((
+                    *player,
+                    MoveRecord {
+                        position,
+                        dice: *dice,
+                        played,
+                    },
+                ));
+                position = played.sides_switched();
+            }
+        }
+        Ok(records)
+    }
+
+    /// Replays the game with all cube actions and checker plays.
+    ///
+    /// Returns the initial state and the state after each action, so the last state has the outcome
+    /// of the game, unless the game ended with a resignation.
+    /// Returns an error if an action is not legal in the replayed game.
+    pub fn replay(&self, crawford: bool) -> Result<Vec<GameState>, String> {
+        let Some((first, _)) = self.actions.first() else {
+            return Ok(Vec::new());
+        };
+        let mut game = GameState::new(*first, crawford);
+        let mut states = vec![game.clone()];
+        for (player, action) in &self.actions {
+            let result = match action {
+                MatAction::Move {
+                    dice,
+                    checker_moves,
+                } => {
+                    if game.turn() != *player {
+                        return Err("The same player moved twice in a row.".to_string());
+                    }
+                    let played = apply_checker_moves(game.position(), checker_moves)?;
+                    game.roll(*dice).and_then(|_| game.play(&played))
+                }
+                MatAction::Double => game.double(),
+                MatAction::Take => game.take(),
+                MatAction::Drop => game.pass(),
+                MatAction::Wins(_) => continue,
+            };
+            result?;
+            states.push(game.clone());
+        }
+        Ok(states)
+    }
+}
+
+fn winner_index(player: Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
+
+/// Column in which the actions of [Player::Two] start when writing a `.mat` file.
+const RIGHT_COLUMN: usize = 36;
+
+impl fmt::Display for MatMatch {
+    /// Writes the match in the `.mat` format, so it can be imported by GNUBG or XG.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, " {} point match", self.length.unwrap_or(0))?;
+        for (index, game) in self.games.iter().enumerate() {
+            writeln!(f, "\n Game {}", index + 1)?;
+            let first = format!("{} : {}", self.players[0], game.score[0]);
+            writeln!(
+                f,
+                " {:<width$}{} : {}",
+                first,
+                self.players[1],
+                game.score[1],
+                width = RIGHT_COLUMN - 1
+            )?;
+            game.fmt_actions(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl MatGame {
+    /// Writes the actions in two columns, one line for each move of [Player::One].
+    fn fmt_actions(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut cube = 1;
+        let mut rows: Vec<[String; 2]> = Vec::new();
+        for (player, action) in &self.actions {
+            let text = match action {
+                MatAction::Move {
+                    dice,
+                    checker_moves,
+                } => format_move(dice, checker_moves),
+                MatAction::Double => {
+                    cube *= 2;
+                    format!(" Doubles => {}", cube)
+                }
+                MatAction::Take => " Takes".to_string(),
+                MatAction::Drop => " Drops".to_string(),
+                MatAction::Wins(_) => continue,
+            };
+            match (player, rows.last_mut()) {
+                (Player::Two, Some(row)) if row[1].is_empty() => row[1] = text,
+                (Player::One, _) => rows.push([text, String::new()]),
+                (Player::Two, _) => rows.push([String::new(), text]),
+            }
+        }
+        for (index, [left, right]) in rows.iter().enumerate() {
+            let line = format!(
+                "{:>3}) {:<width$}{}",
+                index + 1,
+                left,
+                right,
+                width = RIGHT_COLUMN - 5
+            );
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        if let Some((winner, points)) = self.winner() {
+            let indent = match winner {
+                Player::One => 6,
+                Player::Two => RIGHT_COLUMN,
+            };
+            let plural = if points == 1 { "" } else { "s" };
+            writeln!(f, "{:indent$}Wins {} point{}", "", points, plural)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats dice like `.mat` files do, the bigger die first. Example: `31`.
+pub(crate) fn format_dice(dice: &Dice) -> String {
+    match dice {
+        Dice::Mixed(mixed) => format!("{}{}", mixed.big(), mixed.small()),
+        Dice::Double(die) => format!("{}{}", die, die),
+    }
+}
+
+/// For example `52: 13/8 13/11` or `31: bar/22 6/off`.
+fn format_move(dice: &Dice, checker_moves: &[(usize, usize)]) -> String {
+    let point = |pip: usize| match pip {
+        X_BAR => "bar".to_string(),
+        0 => "off".to_string(),
+        pip => pip.to_string(),
+    };
+    let mut text = format!("{}:", format_dice(dice));
+    for (from, to) in checker_moves {
+        text.push_str(&format!(" {}/{}", point(*from), point(*to)));
+    }
+    text
+}
+
+/// Plays a cubeless game in which `evaluator` moves for both players. `first` has the opening roll.
+pub fn self_play_game<T: Evaluator, U: DiceGen>(
+    evaluator: &T,
+    dice_gen: &mut U,
+    first: Player,
+) -> MatGame {
+    let mut actions = Vec::new();
+    let mut player = first;
+    let mut dice = dice_gen.roll_mixed();
+    let mut position = STARTING;
+    loop {
+        let new = evaluator.best_position_by_equity(&position, &dice);
+        let bg_move = BgMove::new(&position, &new.sides_switched(), &dice);
+        let checker_moves = bg_move
+            .into_details()
+            .iter()
+            .map(|detail| (detail.from(), detail.to()))
+            .collect();
+        actions.push((
+            player,
+            MatAction::Move {
+                dice,
+                checker_moves,
+            },
+        ));
+        if let GameOver(result) = new.game_state() {
+            let points = match result {
+                LoseNormal => 1,
+                LoseGammon => 2,
+                WinNormal | WinGammon => unreachable!("The player who moved can't lose by moving."),
+            };
+            actions.push((player, MatAction::Wins(points)));
+            return MatGame {
+                score: [0, 0],
+                actions,
+            };
+        }
+        position = new;
+        player = player.opponent();
+        dice = dice_gen.roll();
+    }
+}
+
+/// Parses the content of a `.mat` file.
+///
+/// Comments, cube actions other than doubles, takes and drops, and unknown lines are ignored.
+pub fn parse(content: &str) -> Result<MatMatch, String> {
+    let mut length = None;
+    let mut players = [String::new(), String::new()];
+    let mut games: Vec<MatGame> = Vec::new();
+    // Column in which the actions of the second player start.
+    let mut right_column = usize::MAX;
+    for (index, line) in content.lines().enumerate() {
+        let error = |message: &str| format!("Line {}: {}", index + 1, message);
+        let trimmed = line.trim();
+        if trimmed.starts_with(';') || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.ends_with("point match") {
+            let number = trimmed.split_whitespace().next().unwrap_or_default();
+            let points: u32 = number.parse().map_err(|_| error("Invalid match length."))?;
+            // Money sessions are written as 0 point matches.
+            length = (points > 0).then_some(points);
+        } else if trimmed.starts_with("Game ") {
+            games.push(MatGame {
+                score: [0, 0],
+                actions: Vec::new(),
+            });
+        } else if trimmed.matches(" : ").count() == 2 {
+            let game = games
+                .last_mut()
+                .ok_or_else(|| error("Score before game."))?;
+            let (names, scores, column) =
+                parse_score_line(line).ok_or_else(|| error("Invalid score."))?;
+            players = names;
+            game.score = scores;
+            right_column = column;
+        } else if let Some(number_end) = move_number_end(trimmed) {
+            let game = games.last_mut().ok_or_else(|| error("Move before game."))?;
+            let offset = line.len() - line.trim_start().len() + number_end + 1;
+            let actions = parse_actions(&line[offset..]).map_err(|e| error(&e))?;
+            let lone_action_right = actions.len() == 1 && offset + actions[0].0 >= right_column;
+            for (i, (_, action)) in actions.into_iter().enumerate() {
+                let player = if i == 1 || lone_action_right {
+                    Player::Two
+                } else {
+                    Player::One
+                };
+                game.actions.push((player, action));
+            }
+        } else if trimmed.starts_with("Wins") {
+            let game = games
+                .last_mut()
+                .ok_or_else(|| error("Result before game."))?;
+            let column = line.len() - line.trim_start().len();
+            let player = if column >= right_column {
+                Player::Two
+            } else {
+                Player::One
+            };
+            let mut actions = parse_actions(trimmed).map_err(|e| error(&e))?;
+            if let Some((_, action)) = actions.pop() {
+                game.actions.push((player, action));
+            }
+        }
+    }
+    Ok(MatMatch {
+        length,
+        players,
+        games,
+    })
+}
+
+/// Parses a line like ` Alice : 3                 Bob : 0`.
+fn parse_score_line(line: &str) -> Option<([String; 2], [u32; 2], usize)> {
+    let first_colon = line.find(" : ")?;
+    let after_first = &line[first_colon + 3..];
+    let first_score_len = after_first.find(char::is_whitespace)?;
+    let first_score = after_first[..first_score_len].parse().ok()?;
+    let rest = &after_first[first_score_len..];
+    let second_name_start = line.len() - rest.trim_start().len();
+    let (second_name, second_score) = line[second_name_start..].split_once(" : ")?;
+    Some((
+        [
+            line[..first_colon].trim().to_string(),
+            second_name.trim().to_string(),
+        ],
+        [first_score, second_score.trim().parse().ok()?],
+        second_name_start,
+    ))
+}
+
+/// Index of the `)` in lines like `12) 52: 13/8 13/11`.
+fn move_number_end(trimmed: &str) -> Option<usize> {
+    let end = trimmed.find(')')?;
+    let is_number = end > 0 && trimmed[..end].chars().all(|c| c.is_ascii_digit());
+    is_number.then_some(end)
+}
+
+/// Actions in a line together with the column at which they start.
+fn parse_actions(text: &str) -> Result<Vec<(usize, MatAction)>, String> {
+    let mut actions: Vec<(usize, MatAction)> = Vec::new();
+    let mut tokens = tokens_with_columns(text).into_iter().peekable();
+    while let Some((column, token)) = tokens.next() {
+        let action = match token {
+            "Doubles" => MatAction::Double,
+            "Takes" => MatAction::Take,
+            "Drops" => MatAction::Drop,
+            "Wins" => {
+                let points = tokens.next().and_then(|(_, points)| points.parse().ok());
+                MatAction::Wins(points.ok_or("Invalid number of points.")?)
+            }
+            _ if token.len() == 3 && token.ends_with(':') => {
+                let mut checker_moves = Vec::new();
+                while let Some((_, checker_move)) = tokens.next_if(|(_, t)| t.contains('/')) {
+                    checker_moves.extend(parse_checker_move(checker_move)?);
+                }
+                MatAction::Move {
+                    dice: parse_dice(token)?,
+                    checker_moves,
+                }
+            }
+            _ => continue,
+        };
+        actions.push((column, action));
+    }
+    Ok(actions)
+}
+
+fn tokens_with_columns(text: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                tokens.push((s, &text[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, &text[s..]));
+    }
+    tokens
+}
+
+fn parse_dice(token: &str) -> Result<Dice, String> {
+    let mut digits = token.chars().filter_map(|c| c.to_digit(10));
+    match (digits.next(), digits.next()) {
+        (Some(die1), Some(die2)) => {
+            Dice::try_from((die1 as usize, die2 as usize)).map_err(|e| e.to_string())
+        }
+        _ => Err(format!("Invalid dice '{}'.", token)),
+    }
+}
+
+/// Parses a move in standard notation like `8/5 6/5` or `bar/22* 13/10(2)`, see [parse_checker_move].
+pub fn parse_checker_moves(text: &str) -> Result<Vec<(usize, usize)>, String> {
+    let mut checker_moves = Vec::new();
+    for token in text.split_whitespace() {
+        checker_moves.extend(parse_checker_move(token)?);
+    }
+    Ok(checker_moves)
+}
+
+/// Parses tokens like `13/11`, `bar/22*`, `6/off`, `24/18/13` or `8/5(2)`.
+fn parse_checker_move(token: &str) -> Result<Vec<(usize, usize)>, String> {
+    let (points, repetitions) = match token.split_once('(') {
+        Some((points, count)) => {
+            let count = count.trim_end_matches(')').parse::<usize>();
+            (
+                points,
+                count.map_err(|_| format!("Invalid move '{}'.", token))?,
+            )
+        }
+        None => (token, 1),
+    };
+    let points = points
+        .split('/')
+        .map(|point| match point.trim_end_matches('*') {
+            "bar" | "25" => Ok(X_BAR),
+            "off" => Ok(0),
+            number => match number.parse::<usize>() {
+                Ok(pip) if pip < X_BAR => Ok(pip),
+                _ => Err(format!("Invalid move '{}'.", token)),
+            },
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    let steps: Vec<(usize, usize)> = points.windows(2).map(|w| (w[0], w[1])).collect();
+    if steps.is_empty() {
+        return Err(format!("Invalid move '{}'.", token));
+    }
+    Ok(steps.repeat(repetitions))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat::{parse, parse_checker_moves, self_play_game, MatAction, MatMatch};
+    use engine::dice::Dice;
+    use engine::dice_gen::FastrandDice;
+    use engine::evaluator::RandomEvaluator;
+    use engine::pos;
+    use logic::bg_move::apply_checker_moves;
+    use logic::game_state::{CubeOwner, Player};
+
+    const MATCH: &str = " 5 point match
+
+ Game 1
+ Alice : 0                          Bob : 0
+  1) 31: 8/5 6/5                    64: 24/18 13/9
+  2) 62: 13/7* 13/11                 Doubles => 2
+  3)  Takes                         33: bar/22 13/10(2) 6/3
+      Wins 2 points
+
+ Game 2
+ Alice : 2                          Bob : 0
+  1)                                21: 13/11 6/5
+  2)  Doubles => 2                   Drops
+      Wins 1 point
+";
+
+    #[test]
+    fn parse_and_apply_standard_notation() {
+        // Given
+        let position = pos!(x 24:2, 13:5, 8:3, 6:5; o 19:5, 17:3, 12:5, 1:2);
+        // When
+        let checker_moves = parse_checker_moves(" 24/21 13/10(2) ").unwrap();
+        let played = apply_checker_moves(&position, &checker_moves).unwrap();
+        // Then
+        assert_eq!(checker_moves, vec![(24, 21), (13, 10), (13, 10)]);
+        assert_eq!(
+            played,
+            pos!(x 24:1, 21:1, 13:3, 10:2, 8:3, 6:5; o 19:5, 17:3, 12:5, 1:2)
+        );
+        assert!(parse_checker_moves("13-10").is_err());
+        assert!(apply_checker_moves(&position, &[(23, 20)]).is_err());
+    }
+
+    #[test]
+    fn parse_header_and_scores() {
+        // When
+        let mat_match = parse(MATCH).unwrap();
+        // Then
+        assert_eq!(mat_match.length, Some(5));
+        assert_eq!(mat_match.players, ["Alice".to_string(), "Bob".to_string()]);
+        assert_eq!(mat_match.player("Bob"), Some(Player::Two));
+        assert_eq!(mat_match.games.len(), 2);
+        assert_eq!(mat_match.games[1].score, [2, 0]);
+    }
+
+    #[test]
+    fn parse_actions_of_both_columns() {
+        // When
+        let mat_match = parse(MATCH).unwrap();
+        // Then
+        let game = &mat_match.games[0];
+        assert_eq!(game.actions.len(), 7);
+        assert_eq!(
+            game.actions[2],
+            (
+                Player::One,
+                MatAction::Move {
+                    dice: Dice::new(6, 2),
+                    checker_moves: vec![(13, 7), (13, 11)],
+                }
+            )
+        );
+        assert_eq!(game.actions[3], (Player::Two, MatAction::Double));
+        assert_eq!(game.actions[6], (Player::One, MatAction::Wins(2)));
+        let second_game = &mat_match.games[1];
+        assert_eq!(second_game.actions[0].0, Player::Two);
+        assert_eq!(second_game.actions[3], (Player::One, MatAction::Wins(1)));
+    }
+
+    #[test]
+    fn move_records_replay_the_game() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        // When
+        let records = mat_match.games[0].move_records().unwrap();
+        // Then
+        assert_eq!(records.len(), 4);
+        let (player, hit) = &records[2];
+        assert_eq!(player, &Player::One);
+        assert_eq!(hit.dice, Dice::new(6, 2));
+        assert_eq!(
+            hit.played,
+            pos!(x 24:2, 13:3, 11:1, 8:2, 7:1, 6:4, 5:2; o 0:1, 1:1, 12:4, 16:1, 17:3, 19:5)
+        );
+        assert_eq!(records[3].1.position, hit.played.sides_switched());
+    }
+
+    #[test]
+    fn replay_cube_actions() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        // When
+        let games = mat_match.replay().unwrap();
+        // Then
+        let first_game = &games[0];
+        assert_eq!(first_game.len(), 7);
+        let last = first_game.last().unwrap();
+        assert_eq!(last.cube().value(), 2);
+        assert_eq!(last.cube().owner(), CubeOwner::Owned(Player::One));
+        assert_eq!(last.turn(), Player::One);
+        assert_eq!(last.outcome(), None);
+        let second_outcome = games[1].last().unwrap().outcome().unwrap();
+        assert_eq!(second_outcome.winner, Player::One);
+        assert_eq!(second_outcome.points, 1);
+    }
+
+    #[test]
+    fn replay_fails_for_double_in_crawford_game() {
+        // Given
+        let text = " 3 point match\n Game 1\n A : 2   B : 0\n  1)  Doubles => 2\n";
+        let mat_match = parse(text).unwrap();
+        // When
+        let result = mat_match.replay();
+        // Then
+        assert!(mat_match.is_crawford(0));
+        assert_eq!(
+            result,
+            Err("Doubling is not allowed in the Crawford game.".to_string())
+        );
+    }
+
+    #[test]
+    fn written_match_can_be_parsed_again() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        // When
+        let written = mat_match.to_string();
+        // Then
+        assert!(written.contains("\n  2) 62: 13/7 13/11                  Doubles => 2\n"));
+        assert!(written.contains("\n  1)                                21: 13/11 6/5\n"));
+        assert_eq!(parse(&written).unwrap(), mat_match);
+    }
+
+    #[test]
+    fn self_play_games_can_be_replayed() {
+        // Given
+        let evaluator = RandomEvaluator {};
+        let mut dice_gen = FastrandDice::with_seed(42);
+        let mut session = MatMatch {
+            length: None,
+            players: ["wildbg".to_string(), "wildbg".to_string()],
+            games: Vec::new(),
+        };
+        // When
+        session.push(self_play_game(&evaluator, &mut dice_gen, Player::One));
+        session.push(self_play_game(&evaluator, &mut dice_gen, Player::Two));
+        // Then
+        let (winner, points) = session.games[0].winner().unwrap();
+        let mut expected_score = [0, 0];
+        expected_score[if winner == Player::One { 0 } else { 1 }] = points;
+        assert_eq!(session.games[1].score, expected_score);
+        let parsed = parse(&session.to_string()).unwrap();
+        assert_eq!(parsed.length, None);
+        let games = parsed.replay().unwrap();
+        let last = games[1].last().unwrap();
+        assert_eq!(
+            last.outcome().unwrap().winner,
+            session.games[1].winner().unwrap().0
+        );
+    }
+
+    #[test]
+    fn move_records_fail_for_impossible_moves() {
+        // Given
+        let mat_match = parse(" Game 1\n A : 0   B : 0\n  1) 31: 9/6 6/5\n").unwrap();
+        // When
+        let result = mat_match.games[0].move_records();
+        // Then
+        assert_eq!(result, Err("No checker to move from 9.".to_string()));
+    }
+}
@@ -0,0 +1,572 @@
+use crate::mat::{format_dice, MatAction, MatMatch};
+use engine::dice::{Dice, ALL_21};
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use logic::analysis::{analyze_move, MoveRecord};
+use logic::cube::{CubeAccess, Janowski};
+use logic::game_state::{CubeOwner, GameState, Player};
+use logic::match_equity::{MatchEquityTable, MatchScore};
+use serde::Serialize;
+#[cfg(feature = "web")]
+use utoipa::ToSchema;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(ToSchema))]
+pub enum DecisionKind {
+    CheckerPlay,
+    Double,
+    /// The player rolled although doubling was correct.
+    NoDouble,
+    Take,
+    Drop,
+}
+
+/// A single analyzed decision of a match.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(ToSchema))]
+pub struct Decision {
+    /// Index of the game in the match, starting with 0.
+    pub game: usize,
+    /// Name of the player, see [Decision::side] for telling players with the same name apart.
+    pub player: String,
+    #[serde(skip)]
+    pub side: Player,
+    pub kind: DecisionKind,
+    /// Position before the decision, from the point of view of the player on roll.
+    pub position_id: String,
+    /// Never negative. Zero if the decision was correct.
+    pub equity_loss: f32,
+    /// Loss of match winning chances, between 0.0 and 1.0. Only for checker plays in matches.
+    pub mwc_loss: Option<f32>,
+}
+
+/// Classification of a [Decision] by its equity loss, from least to most severe.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Severity {
+    Dubious,
+    Error,
+    Blunder,
+    DoubleBlunder,
+}
+
+/// Minimal equity loss for each [Severity].
+///
+/// The default values match the marks in GNUBG's SGF files: `DO` for errors, `BM[1]` for blunders
+/// and `BM[2]` for double blunders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thresholds {
+    pub dubious: f32,
+    pub error: f32,
+    pub blunder: f32,
+    pub double_blunder: f32,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            dubious: 0.02,
+            error: 0.04,
+            blunder: 0.08,
+            double_blunder: 0.16,
+        }
+    }
+}
+
+impl Thresholds {
+    /// Returns `None` if the equity loss is below all thresholds.
+    pub fn classify(&self, equity_loss: f32) -> Option<Severity> {
+        if equity_loss >= self.double_blunder {
+            Some(Severity::DoubleBlunder)
+        } else if equity_loss >= self.blunder {
+            Some(Severity::Blunder)
+        } else if equity_loss >= self.error {
+            Some(Severity::Error)
+        } else if equity_loss >= self.dubious {
+            Some(Severity::Dubious)
+        } else {
+            None
+        }
+    }
+}
+
+/// Luck of a single roll of the dice.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(ToSchema))]
+pub struct Roll {
+    /// Index of the game in the match, starting with 0.
+    pub game: usize,
+    /// Name of the player, see [Roll::side] for telling players with the same name apart.
+    pub player: String,
+    #[serde(skip)]
+    pub side: Player,
+    /// Example: `31`.
+    pub dice: String,
+    /// Position before rolling, from the point of view of the player on roll.
+    pub position_id: String,
+    /// Equity after the best move with the rolled dice minus the average over all rolls.
+    /// Positive values are lucky rolls, negative values are unlucky rolls.
+    pub luck: f32,
+}
+
+/// Error totals of one player. Equity losses are normalized to a cube value of 1.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(ToSchema))]
+pub struct PlayerSummary {
+    pub name: String,
+    pub checker_plays: usize,
+    pub checker_equity_loss: f32,
+    pub cube_decisions: usize,
+    pub cube_equity_loss: f32,
+    pub rolls: usize,
+    pub total_luck: f32,
+}
+
+impl PlayerSummary {
+    pub fn total_equity_loss(&self) -> f32 {
+        self.checker_equity_loss + self.cube_equity_loss
+    }
+
+    /// Average equity loss per decision, multiplied by 500 as done by other tools.
+    pub fn performance_rating(&self) -> f32 {
+        let decisions = self.checker_plays + self.cube_decisions;
+        if decisions == 0 {
+            0.0
+        } else {
+            self.total_equity_loss() / decisions as f32 * 500.0
+        }
+    }
+
+    pub fn average_luck(&self) -> f32 {
+        if self.rolls == 0 {
+            0.0
+        } else {
+            self.total_luck / self.rolls as f32
+        }
+    }
+
+    fn add(&mut self, decision: &Decision) {
+        match decision.kind {
+            DecisionKind::CheckerPlay => {
+                self.checker_plays += 1;
+                self.checker_equity_loss += decision.equity_loss;
+            }
+            _ => {
+                self.cube_decisions += 1;
+                self.cube_equity_loss += decision.equity_loss;
+            }
+        }
+    }
+}
+
+/// Result of [analyze_match], can be serialized to JSON.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "web", derive(ToSchema))]
+pub struct MatchAnalysis {
+    pub decisions: Vec<Decision>,
+    pub rolls: Vec<Roll>,
+    /// Index 0 is for [Player::One].
+    pub summaries: [PlayerSummary; 2],
+}
+
+impl MatchAnalysis {
+    /// All decisions with an equity loss of at least [Thresholds::dubious], in the order they were made.
+    pub fn classified(&self, thresholds: &Thresholds) -> Vec<(&Decision, Severity)> {
+        self.decisions
+            .iter()
+            .filter_map(|decision| {
+                thresholds
+                    .classify(decision.equity_loss)
+                    .map(|severity| (decision, severity))
+            })
+            .collect()
+    }
+
+    /// All blunders and double blunders, in the order they were made.
+    pub fn blunders(&self, thresholds: &Thresholds) -> Vec<&Decision> {
+        self.classified(thresholds)
+            .into_iter()
+            .filter(|(_, severity)| *severity >= Severity::Blunder)
+            .map(|(decision, _)| decision)
+            .collect()
+    }
+}
+
+/// Evaluates every non-forced checker play and every cube decision of `mat_match`.
+///
+/// Checker plays are compared by cubeless money equity, also for match play. In matches their loss
+/// of match winning chances is added, based on the default [MatchEquityTable].
+/// Cube decisions are judged by the cubeful money equities of [Janowski]'s model, in matches the
+/// loss of match winning chances with a dead cube is added. A missed double is only counted when
+/// doubling would have been correct.
+///
+/// Additionally the luck of every roll is measured, see [Roll::luck].
+pub fn analyze_match<T: Evaluator>(
+    evaluator: &T,
+    mat_match: &MatMatch,
+) -> Result<MatchAnalysis, String> {
+    let name = |player: Player| match player {
+        Player::One => mat_match.players[0].clone(),
+        Player::Two => mat_match.players[1].clone(),
+    };
+    let met = MatchEquityTable::default();
+    let mut decisions = Vec::new();
+    let mut rolls = Vec::new();
+    for (game_index, (game, states)) in mat_match.games.iter().zip(mat_match.replay()?).enumerate()
+    {
+        for (index, (player, action)) in game.actions.iter().enumerate() {
+            let before = &states[index.min(states.len() - 1)];
+            let position = *before.position();
+            let mut decide = |kind: DecisionKind, equity_loss: f32, mwc_loss: Option<f32>| {
+                decisions.push(Decision {
+                    game: game_index,
+                    player: name(*player),
+                    side: *player,
+                    kind,
+                    position_id: position.position_id(),
+                    equity_loss: equity_loss.max(0.0),
+                    mwc_loss: mwc_loss.map(|loss| loss.max(0.0)),
+                })
+            };
+            let doubler_score = |doubler: Player| match_score(mat_match, game_index, doubler);
+            match action {
+                MatAction::Move { dice, .. } => {
+                    rolls.push(Roll {
+                        game: game_index,
+                        player: name(*player),
+                        side: *player,
+                        dice: format_dice(dice),
+                        position_id: position.position_id(),
+                        luck: luck(evaluator, &position, dice, index == 0),
+                    });
+                    if before.may_double() {
+                        let cube =
+                            CubeEquities::new(evaluator, before, &met, doubler_score(*player));
+                        let (double, no_double) = (cube.double(), cube.no_double);
+                        if double.0 > no_double.0 {
+                            let mwc_loss = double.1.zip(no_double.1).map(|(d, n)| d - n);
+                            decide(DecisionKind::NoDouble, double.0 - no_double.0, mwc_loss);
+                        }
+                    }
+                    if position.all_positions_after_moving(dice).len() > 1 {
+                        let record = MoveRecord {
+                            position,
+                            dice: *dice,
+                            played: states[index + 1].position().sides_switched(),
+                        };
+                        let analysis = analyze_move(evaluator, &record, |p| p.equity())?;
+                        let mwc_loss = match match_score(mat_match, game_index, *player) {
+                            Some(score) => {
+                                let cube = before.cube().value();
+                                let mwc = |p: &Probabilities| met.mwc(p, &score, cube);
+                                Some(analyze_move(evaluator, &record, mwc)?.equity_loss)
+                            }
+                            None => None,
+                        };
+                        decide(DecisionKind::CheckerPlay, analysis.equity_loss, mwc_loss);
+                    }
+                }
+                MatAction::Double => {
+                    let cube = CubeEquities::new(evaluator, before, &met, doubler_score(*player));
+                    let (double, no_double) = (cube.double(), cube.no_double);
+                    let mwc_loss = no_double.1.zip(double.1).map(|(n, d)| n - d);
+                    decide(DecisionKind::Double, no_double.0 - double.0, mwc_loss);
+                }
+                // The position and the equities are still from the point of view of the doubler.
+                MatAction::Take => {
+                    let cube = CubeEquities::new(
+                        evaluator,
+                        before,
+                        &met,
+                        doubler_score(player.opponent()),
+                    );
+                    let (take, pass) = (cube.double_take, cube.double_pass);
+                    let mwc_loss = take.1.zip(pass.1).map(|(t, p)| t - p);
+                    decide(DecisionKind::Take, take.0 - pass.0, mwc_loss);
+                }
+                MatAction::Drop => {
+                    let cube = CubeEquities::new(
+                        evaluator,
+                        before,
+                        &met,
+                        doubler_score(player.opponent()),
+                    );
+                    let (take, pass) = (cube.double_take, cube.double_pass);
+                    let mwc_loss = pass.1.zip(take.1).map(|(p, t)| p - t);
+                    decide(DecisionKind::Drop, pass.0 - take.0, mwc_loss);
+                }
+                MatAction::Wins(_) => {}
+            }
+        }
+    }
+    let mut summaries = [PlayerSummary::default(), PlayerSummary::default()];
+    summaries[0].name = name(Player::One);
+    summaries[1].name = name(Player::Two);
+    // Names are not unique, both players can have the same one.
+    let index = |player: Player| match player {
+        Player::One => 0,
+        Player::Two => 1,
+    };
+    for decision in &decisions {
+        summaries[index(decision.side)].add(decision);
+    }
+    for roll in &rolls {
+        let summary = &mut summaries[index(roll.side)];
+        summary.rolls += 1;
+        summary.total_luck += roll.luck;
+    }
+    Ok(MatchAnalysis {
+        decisions,
+        rolls,
+        summaries,
+    })
+}
+
+/// Score of the game with `game_index` from the point of view of `player`. `None` for money sessions.
+fn match_score(mat_match: &MatMatch, game_index: usize, player: Player) -> Option<MatchScore> {
+    let length = mat_match.length?;
+    let score = mat_match.games[game_index].score;
+    let (own, other) = match player {
+        Player::One => (score[0], score[1]),
+        Player::Two => (score[1], score[0]),
+    };
+    Some(MatchScore {
+        x_away: length.saturating_sub(own),
+        o_away: length.saturating_sub(other),
+        crawford: mat_match.is_crawford(game_index),
+    })
+}
+
+/// Equities of the player who doubles or may double, in this order: the cubeful money equity of
+/// [Janowski]'s model, and in matches the MWC with a dead cube.
+struct CubeEquities {
+    no_double: (f32, Option<f32>),
+    double_take: (f32, Option<f32>),
+    double_pass: (f32, Option<f32>),
+}
+
+impl CubeEquities {
+    /// `state` is seen from the doubler, `score` is the score of the doubler.
+    fn new<T: Evaluator>(
+        evaluator: &T,
+        state: &GameState,
+        met: &MatchEquityTable,
+        score: Option<MatchScore>,
+    ) -> Self {
+        let probabilities = evaluator.eval(state.position());
+        let access = match state.cube().owner() {
+            CubeOwner::Centered => CubeAccess::Centered,
+            CubeOwner::Owned(_) => CubeAccess::Owned,
+        };
+        let [no_double, double_take, double_pass] =
+            Janowski::default().cube_equities(&probabilities, access);
+        let cube = state.cube().value();
+        let mwc = |cube: u32| score.map(|score| met.mwc(&probabilities, &score, cube));
+        Self {
+            no_double: (no_double, mwc(cube)),
+            double_take: (double_take, mwc(2 * cube)),
+            double_pass: (
+                double_pass,
+                score.map(|score| met.mwc_at(&score.after_game(cube, 0))),
+            ),
+        }
+    }
+
+    /// After a double the opponent chooses the smaller one of double/take and double/pass.
+    fn double(&self) -> (f32, Option<f32>) {
+        let (take, pass) = (self.double_take, self.double_pass);
+        let mwc = take.1.zip(pass.1).map(|(take, pass)| take.min(pass));
+        (take.0.min(pass.0), mwc)
+    }
+}
+
+/// Equity after the best move with `dice` minus the average equity after the best move of all rolls.
+///
+/// The opening roll can't be a double, so then only the 15 mixed rolls are considered.
+fn luck<T: Evaluator>(evaluator: &T, position: &Position, dice: &Dice, opening: bool) -> f32 {
+    let equity_after = |dice: &Dice| {
+        evaluator
+            .positions_and_probabilities_by_equity(position, dice)
+            .first()
+            .unwrap()
+            .1
+            .equity()
+    };
+    let (sum, count) = ALL_21
+        .iter()
+        .filter(|(dice, _)| !opening || matches!(dice, Dice::Mixed(_)))
+        .fold((0.0, 0), |(sum, count), (dice, number)| {
+            (sum + equity_after(dice) * *number as f32, count + number)
+        });
+    equity_after(dice) - sum / count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat::parse;
+    use crate::match_analysis::{analyze_match, DecisionKind, PlayerSummary, Severity, Thresholds};
+    use engine::probabilities::Probabilities;
+    use engine::test_utils::EvaluatorFake;
+    use logic::cube::{CubeAccess, Janowski};
+    use logic::match_equity::{MatchEquityTable, MatchScore};
+
+    const MATCH: &str = " 5 point match
+
+ Game 1
+ Alice : 0                          Bob : 0
+  1) 31: 24/21 24/23                64: 24/18 13/9
+  2)  Doubles => 2                   Takes
+  3) 31: 8/5 6/5
+";
+
+    #[test]
+    fn errors_of_checker_plays_and_cube_decisions() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        // When
+        let analysis = analyze_match(&EvaluatorFake {}, &mat_match).unwrap();
+        // Then
+        let kinds: Vec<DecisionKind> = analysis.decisions.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DecisionKind::CheckerPlay,
+                DecisionKind::CheckerPlay,
+                DecisionKind::Double,
+                DecisionKind::Take,
+                DecisionKind::CheckerPlay,
+            ]
+        );
+        let losses: Vec<f32> = analysis.decisions.iter().map(|d| d.equity_loss).collect();
+        // Alice doesn't make her 5 point with 3-1, that's an error of 2 * 2 * 0.15.
+        assert!((losses[0] - 0.6).abs() < 0.0001);
+        assert_eq!(losses[1], 0.0);
+        // Alice doubles with even chances, Bob takes correctly.
+        let even = Probabilities {
+            win_normal: 0.5,
+            lose_normal: 0.5,
+            ..Default::default()
+        };
+        let [no_double, double_take, double_pass] =
+            Janowski::default().cube_equities(&even, CubeAccess::Centered);
+        assert!(double_take < double_pass);
+        assert!(no_double > double_take);
+        assert!((losses[2] - (no_double - double_take)).abs() < 0.0001);
+        assert_eq!(losses[3], 0.0);
+        assert_eq!(losses[4], 0.0);
+
+        let alice = &analysis.summaries[0];
+        assert_eq!(alice.name, "Alice");
+        assert_eq!(alice.checker_plays, 2);
+        assert_eq!(alice.cube_decisions, 1);
+        assert!((alice.total_equity_loss() - 0.6 - losses[2]).abs() < 0.0001);
+        assert_eq!(analysis.summaries[1].cube_decisions, 1);
+    }
+
+    #[test]
+    fn players_with_the_same_name() {
+        // Given
+        let mat_match = parse(&MATCH.replace("Bob", "Alice")).unwrap();
+        // When
+        let analysis = analyze_match(&EvaluatorFake {}, &mat_match).unwrap();
+        // Then
+        let [one, two] = &analysis.summaries;
+        assert_eq!((one.checker_plays, one.cube_decisions), (2, 1));
+        assert_eq!((two.checker_plays, two.cube_decisions), (1, 1));
+        assert_eq!((one.rolls, two.rolls), (2, 1));
+    }
+
+    #[test]
+    fn match_winning_chances_of_checker_plays() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        let met = MatchEquityTable::default();
+        let score = |x_away: u32, o_away: u32| MatchScore {
+            x_away,
+            o_away,
+            crawford: false,
+        };
+        // When
+        let analysis = analyze_match(&EvaluatorFake {}, &mat_match).unwrap();
+        // Then
+        // Without gammons, 0.6 equity are 0.3 winning chances of a single game.
+        let single_game = met.mwc_at(&score(4, 5)) - met.mwc_at(&score(5, 4));
+        let mwc_loss = analysis.decisions[0].mwc_loss.unwrap();
+        assert!((mwc_loss - 0.3 * single_game).abs() < 0.0001);
+        assert_eq!(analysis.decisions[1].mwc_loss, Some(0.0));
+        // With even chances and without gammons the double doesn't change the MWC at 5-away
+        // 5-away, and Bob's take doesn't either.
+        assert_eq!(analysis.decisions[2].mwc_loss, Some(0.0));
+        assert_eq!(analysis.decisions[3].mwc_loss, Some(0.0));
+    }
+
+    #[test]
+    fn classify_by_thresholds() {
+        let thresholds = Thresholds::default();
+        assert_eq!(thresholds.classify(0.01), None);
+        assert_eq!(thresholds.classify(0.02), Some(Severity::Dubious));
+        assert_eq!(thresholds.classify(0.05), Some(Severity::Error));
+        assert_eq!(thresholds.classify(0.1), Some(Severity::Blunder));
+        assert_eq!(thresholds.classify(0.6), Some(Severity::DoubleBlunder));
+    }
+
+    #[test]
+    fn blunders_depend_on_thresholds() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        let analysis = analyze_match(&EvaluatorFake {}, &mat_match).unwrap();
+        // When
+        let default_blunders = analysis.blunders(&Thresholds::default());
+        let strict_thresholds = Thresholds {
+            blunder: 0.5,
+            double_blunder: 1.0,
+            ..Thresholds::default()
+        };
+        let strict_classified = analysis.classified(&strict_thresholds);
+        let strict_blunders = analysis.blunders(&strict_thresholds);
+        // Then
+        let kinds: Vec<DecisionKind> = default_blunders.iter().map(|d| d.kind).collect();
+        assert_eq!(kinds, vec![DecisionKind::CheckerPlay, DecisionKind::Double]);
+        assert_eq!(strict_classified[1].1, Severity::Error);
+        assert_eq!(strict_blunders.len(), 1);
+        assert_eq!(strict_blunders[0].kind, DecisionKind::CheckerPlay);
+    }
+
+    #[test]
+    fn luck_of_rolls() {
+        // Given
+        let mat_match = parse(MATCH).unwrap();
+        // When
+        let analysis = analyze_match(&EvaluatorFake {}, &mat_match).unwrap();
+        // Then
+        assert_eq!(analysis.rolls.len(), 3);
+        let opening = &analysis.rolls[0];
+        assert_eq!(opening.dice, "31");
+        // 31 puts 2 checkers on the 5 point. Of the 15 opening rolls, 9 others put 1 checker there.
+        // So the average equity is 0.3 * 11 / 15 = 0.22 and the equity after 31 is 0.6.
+        assert!((opening.luck - 0.38).abs() < 0.0001);
+
+        let alice = &analysis.summaries[0];
+        assert_eq!(alice.rolls, 2);
+        assert!((alice.average_luck() - alice.total_luck / 2.0).abs() < 0.0001);
+        assert_eq!(analysis.summaries[1].rolls, 1);
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let summary = PlayerSummary {
+            name: "Bob".to_string(),
+            checker_plays: 1,
+            checker_equity_loss: 0.5,
+            cube_decisions: 1,
+            cube_equity_loss: 0.0,
+            rolls: 2,
+            total_luck: -0.25,
+        };
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"Bob","checker_plays":1,"checker_equity_loss":0.5,"cube_decisions":1,"cube_equity_loss":0.0,"rolls":2,"total_luck":-0.25}"#
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::data::InputsRecord;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Writes [InputsRecord]s as NumPy `.npz` archive, like `numpy.savez` does.
+///
+/// The archive contains two `float32` arrays: `inputs` with one row of inputs per position and
+/// `targets` with the 4 probabilities per position. PyTorch can use them without parsing anything.
+///
+/// As the shapes of the arrays are part of their headers, the values are streamed into two
+/// temporary files and copied into the archive by [NpzWriter::finish]. So memory stays flat for
+/// any number of records.
+pub struct NpzWriter {
+    num_inputs: usize,
+    rows: usize,
+    inputs: BufWriter<File>,
+    targets: BufWriter<File>,
+}
+
+impl NpzWriter {
+    pub fn new(num_inputs: usize) -> std::io::Result<Self> {
+        Ok(Self {
+            num_inputs,
+            rows: 0,
+            inputs: BufWriter::new(tempfile::tempfile()?),
+            targets: BufWriter::new(tempfile::tempfile()?),
+        })
+    }
+
+    pub fn write(&mut self, record: &InputsRecord) -> std::io::Result<()> {
+        let values = record.values();
+        assert_eq!(
+            values.len(),
+            4 + self.num_inputs,
+            "All records need the same number of inputs."
+        );
+        for value in &values[..4] {
+            self.targets.write_all(&value.to_le_bytes())?;
+        }
+        for value in &values[4..] {
+            self.inputs.write_all(&value.to_le_bytes())?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn finish<W: Write + Seek>(self, writer: W) -> std::io::Result<W> {
+        let mut zip = ZipWriter::new(writer);
+        let inputs = self
+            .inputs
+            .into_inner()
+            .map_err(|error| error.into_error())?;
+        write_array(&mut zip, "inputs", inputs, self.rows, self.num_inputs)?;
+        let targets = self
+            .targets
+            .into_inner()
+            .map_err(|error| error.into_error())?;
+        write_array(&mut zip, "targets", targets, self.rows, 4)?;
+        Ok(zip.finish()?)
+    }
+}
+
+/// Copies the little endian `f32` values of `values` into the archive as `.npy` file.
+fn write_array<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    mut values: File,
+    rows: usize,
+    columns: usize,
+) -> std::io::Result<()> {
+    let header = npy_header(rows, columns);
+    let size = header.len() + 4 * rows * columns;
+    // Like `numpy.savez`, the arrays are not compressed.
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(size > u32::MAX as usize);
+    zip.start_file(format!("{name}.npy"), options)?;
+    zip.write_all(&header)?;
+    values.seek(SeekFrom::Start(0))?;
+    std::io::copy(&mut values, zip)?;
+    Ok(())
+}
+
+/// Header of version 1.0 of the `.npy` format for a C-ordered 2D array of little endian `f32`.
+fn npy_header(rows: usize, columns: usize) -> Vec<u8> {
+    let mut dict =
+        format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({rows}, {columns}), }}");
+    // Magic string, version and header length take 10 bytes. The data must be aligned to 64 bytes.
+    let total = (10 + dict.len() + 1).div_ceil(64) * 64;
+    dict.push_str(&" ".repeat(total - 10 - dict.len() - 1));
+    dict.push('\n');
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{InputsRecord, PositionRecord};
+    use crate::npz::{npy_header, NpzWriter};
+    use engine::inputs::{InputsGen, RaceInputsGen};
+    use engine::pos;
+    use engine::probabilities::Probabilities;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn npy_header_is_aligned() {
+        let header = npy_header(1000, 186);
+        assert_eq!(header.len(), 128);
+        assert_eq!(&header[..8], b"\x93NUMPY\x01\x00");
+        assert_eq!(u16::from_le_bytes([header[8], header[9]]), 118);
+        let dict = std::str::from_utf8(&header[10..]).unwrap();
+        assert!(
+            dict.starts_with("{'descr': '<f4', 'fortran_order': False, 'shape': (1000, 186), }")
+        );
+        assert!(dict.ends_with(" \n"));
+    }
+
+    #[test]
+    fn inputs_and_targets() {
+        // Given
+        let probabilities = Probabilities {
+            win_normal: 0.25,
+            win_gammon: 0.0,
+            lose_normal: 0.75,
+            lose_gammon: 0.0,
+        };
+        let record = PositionRecord::new(&pos!(x 1:1; o 24:1), &probabilities);
+        let record = InputsRecord::new(&record, &RaceInputsGen {});
+        let mut writer = NpzWriter::new(RaceInputsGen::NUM_INPUTS).unwrap();
+        // When
+        writer.write(&record).unwrap();
+        writer.write(&record).unwrap();
+        let bytes = writer.finish(Cursor::new(Vec::new())).unwrap().into_inner();
+        // Then
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut targets = Vec::new();
+        archive
+            .by_name("targets.npy")
+            .unwrap()
+            .read_to_end(&mut targets)
+            .unwrap();
+        assert_eq!(targets.len(), 128 + 2 * 4 * 4);
+        let first = f32::from_le_bytes(targets[128..132].try_into().unwrap());
+        assert_eq!(first, 0.25);
+        let second = f32::from_le_bytes(targets[144..148].try_into().unwrap());
+        assert_eq!(second, 0.25);
+        let mut inputs = Vec::new();
+        archive
+            .by_name("inputs.npy")
+            .unwrap()
+            .read_to_end(&mut inputs)
+            .unwrap();
+        assert_eq!(inputs.len(), 128 + 2 * 4 * RaceInputsGen::NUM_INPUTS);
+        let values = record.values();
+        let first_input = f32::from_le_bytes(inputs[128..132].try_into().unwrap());
+        assert_eq!(first_input, values[4]);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::training::Mlp;
+use engine::onnx::INPUTS_VERSION_KEY;
+use prost::Message;
+
+// Export of an [Mlp] in the ONNX format, so that `engine` can load it like the nets trained with PyTorch.
+//
+// Only the few parts of `onnx.proto` which are needed for a multilayer perceptron are written down here.
+// The field numbers are the ones of https://github.com/onnx/onnx/blob/main/onnx/onnx.proto
+
+const IR_VERSION: i64 = 8;
+const OPSET_VERSION: i64 = 13;
+/// `TensorProto.DataType.FLOAT`
+const FLOAT: i32 = 1;
+/// `AttributeProto.AttributeType.FLOAT`
+const ATTRIBUTE_FLOAT: i32 = 1;
+/// `AttributeProto.AttributeType.INT`
+const ATTRIBUTE_INT: i32 = 2;
+
+#[derive(Clone, PartialEq, Message)]
+struct ModelProto {
+    #[prost(int64, tag = "1")]
+    ir_version: i64,
+    #[prost(string, tag = "2")]
+    producer_name: String,
+    #[prost(message, optional, tag = "7")]
+    graph: Option<GraphProto>,
+    #[prost(message, repeated, tag = "8")]
+    opset_import: Vec<OperatorSetIdProto>,
+    #[prost(message, repeated, tag = "14")]
+    metadata_props: Vec<StringStringEntryProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct StringStringEntryProto {
+    #[prost(string, tag = "1")]
+    key: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct OperatorSetIdProto {
+    #[prost(string, tag = "1")]
+    domain: String,
+    #[prost(int64, tag = "2")]
+    version: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct GraphProto {
+    #[prost(message, repeated, tag = "1")]
+    node: Vec<NodeProto>,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(message, repeated, tag = "5")]
+    initializer: Vec<TensorProto>,
+    #[prost(message, repeated, tag = "11")]
+    input: Vec<ValueInfoProto>,
+    #[prost(message, repeated, tag = "12")]
+    output: Vec<ValueInfoProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct NodeProto {
+    #[prost(string, repeated, tag = "1")]
+    input: Vec<String>,
+    #[prost(string, repeated, tag = "2")]
+    output: Vec<String>,
+    #[prost(string, tag = "3")]
+    name: String,
+    #[prost(string, tag = "4")]
+    op_type: String,
+    #[prost(message, repeated, tag = "5")]
+    attribute: Vec<AttributeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct AttributeProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(float, optional, tag = "2")]
+    f: Option<f32>,
+    #[prost(int64, optional, tag = "3")]
+    i: Option<i64>,
+    #[prost(int32, tag = "20")]
+    r#type: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TensorProto {
+    #[prost(int64, repeated, tag = "1")]
+    dims: Vec<i64>,
+    #[prost(int32, tag = "2")]
+    data_type: i32,
+    #[prost(string, tag = "8")]
+    name: String,
+    /// Little endian values
+    #[prost(bytes = "vec", tag = "9")]
+    raw_data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ValueInfoProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, optional, tag = "2")]
+    r#type: Option<TypeProto>,
+}
+
+/// In `onnx.proto` this is a `oneof`; we only need the tensor variant.
+#[derive(Clone, PartialEq, Message)]
+struct TypeProto {
+    #[prost(message, optional, tag = "1")]
+    tensor_type: Option<TensorTypeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TensorTypeProto {
+    #[prost(int32, tag = "1")]
+    elem_type: i32,
+    #[prost(message, optional, tag = "2")]
+    shape: Option<TensorShapeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TensorShapeProto {
+    #[prost(message, repeated, tag = "1")]
+    dim: Vec<Dimension>,
+}
+
+/// In `onnx.proto` the two values are a `oneof`, exactly one of them is set.
+#[derive(Clone, PartialEq, Message)]
+struct Dimension {
+    #[prost(int64, optional, tag = "1")]
+    dim_value: Option<i64>,
+    #[prost(string, optional, tag = "2")]
+    dim_param: Option<String>,
+}
+
+/// Serializes the net as ONNX model with a dynamic batch size.
+///
+/// The graph is the same as the one PyTorch exports for `training/src/model.py`:
+/// `Gemm` and `HardSigmoid` for each hidden layer, `Gemm` and `Softmax` for the output layer.
+/// `inputs_version` is the [InputsGen::INPUTS_VERSION](engine::inputs::InputsGen::INPUTS_VERSION)
+/// the net was trained with, it's written to the metadata.
+pub fn to_onnx(mlp: &Mlp, inputs_version: &str) -> Vec<u8> {
+    let mut nodes = Vec::new();
+    let mut initializers = Vec::new();
+    let mut current = "input".to_string();
+    for (i, layer) in mlp.layers.iter().enumerate() {
+        let weights = format!("layer{i}.weight");
+        let bias = format!("layer{i}.bias");
+        initializers.push(tensor(
+            &weights,
+            &[layer.num_outputs, layer.num_inputs],
+            &layer.weights,
+        ));
+        initializers.push(tensor(&bias, &[layer.num_outputs], &layer.bias));
+        let linear = format!("layer{i}.linear");
+        nodes.push(NodeProto {
+            input: vec![current, weights, bias],
+            output: vec![linear.clone()],
+            name: format!("Gemm_{i}"),
+            op_type: "Gemm".to_string(),
+            attribute: vec![int_attribute("transB", 1)],
+        });
+        let is_output_layer = i + 1 == mlp.layers.len();
+        current = if is_output_layer {
+            "output".to_string()
+        } else {
+            format!("layer{i}.activation")
+        };
+        nodes.push(if is_output_layer {
+            NodeProto {
+                input: vec![linear],
+                output: vec![current.clone()],
+                name: "Softmax".to_string(),
+                op_type: "Softmax".to_string(),
+                attribute: vec![int_attribute("axis", 1)],
+            }
+        } else {
+            NodeProto {
+                input: vec![linear],
+                output: vec![current.clone()],
+                name: format!("HardSigmoid_{i}"),
+                op_type: "HardSigmoid".to_string(),
+                // Same as `nn.Hardsigmoid` in PyTorch; the ONNX default for `alpha` would be 0.2.
+                attribute: vec![
+                    float_attribute("alpha", 1.0 / 6.0),
+                    float_attribute("beta", 0.5),
+                ],
+            }
+        });
+    }
+
+    let model = ModelProto {
+        ir_version: IR_VERSION,
+        producer_name: "wildbg".to_string(),
+        graph: Some(GraphProto {
+            node: nodes,
+            name: "wildbg".to_string(),
+            initializer: initializers,
+            input: vec![value_info("input", mlp.num_inputs())],
+            output: vec![value_info("output", 4)],
+        }),
+        opset_import: vec![OperatorSetIdProto {
+            domain: String::new(),
+            version: OPSET_VERSION,
+        }],
+        metadata_props: vec![StringStringEntryProto {
+            key: INPUTS_VERSION_KEY.to_string(),
+            value: inputs_version.to_string(),
+        }],
+    };
+    model.encode_to_vec()
+}
+
+fn tensor(name: &str, dims: &[usize], values: &[f32]) -> TensorProto {
+    TensorProto {
+        dims: dims.iter().map(|&d| d as i64).collect(),
+        data_type: FLOAT,
+        name: name.to_string(),
+        raw_data: values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+    }
+}
+
+fn int_attribute(name: &str, value: i64) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        f: None,
+        i: Some(value),
+        r#type: ATTRIBUTE_INT,
+    }
+}
+
+fn float_attribute(name: &str, value: f32) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        f: Some(value),
+        i: None,
+        r#type: ATTRIBUTE_FLOAT,
+    }
+}
+
+/// Float tensor of shape `[batch_size, width]` where the batch size is dynamic.
+fn value_info(name: &str, width: usize) -> ValueInfoProto {
+    let dims = vec![
+        Dimension {
+            dim_value: None,
+            dim_param: Some("batch_size".to_string()),
+        },
+        Dimension {
+            dim_value: Some(width as i64),
+            dim_param: None,
+        },
+    ];
+    ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            tensor_type: Some(TensorTypeProto {
+                elem_type: FLOAT,
+                shape: Some(TensorShapeProto { dim: dims }),
+            }),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::onnx_export::{to_onnx, ModelProto};
+    use crate::training::Mlp;
+    use engine::evaluator::Evaluator;
+    use engine::inputs::{InputsGen, RaceInputsGen};
+    use engine::onnx::OnnxEvaluator;
+    use engine::pos;
+    use prost::Message;
+
+    #[test]
+    fn exported_net_evaluates_like_the_rust_one() {
+        // Given
+        let mlp = Mlp::new(RaceInputsGen::NUM_INPUTS, &[20, 10], 5);
+        let position = pos!(x 5:3, 3:2; o 20:4, 22:1);
+        // When
+        let bytes = to_onnx(&mlp, RaceInputsGen::INPUTS_VERSION);
+        let evaluator = OnnxEvaluator::from_bytes(&bytes, RaceInputsGen {}).unwrap();
+        // Then
+        let onnx = evaluator.eval(&position);
+        let rust = mlp.forward(&RaceInputsGen {}.inputs_for_single(&position));
+        assert!((onnx.win_normal - rust[0]).abs() < 0.0001);
+        assert!((onnx.win_gammon - rust[1]).abs() < 0.0001);
+        assert!((onnx.lose_normal - rust[2]).abs() < 0.0001);
+        assert!((onnx.lose_gammon - rust[3]).abs() < 0.0001);
+    }
+
+    #[test]
+    fn net_for_other_inputs_is_rejected() {
+        // Given
+        let mlp = Mlp::new(RaceInputsGen::NUM_INPUTS, &[20, 10], 5);
+        // When
+        let bytes = to_onnx(&mlp, "race-0");
+        let result = OnnxEvaluator::from_bytes(&bytes, RaceInputsGen {});
+        // Then
+        assert_eq!(
+            result.err().unwrap(),
+            "The neural net was trained for inputs version race-0, but race-1 is needed."
+        );
+    }
+
+    #[test]
+    fn net_without_inputs_version_is_rejected() {
+        // Given
+        let mlp = Mlp::new(RaceInputsGen::NUM_INPUTS, &[20, 10], 5);
+        let mut model =
+            ModelProto::decode(to_onnx(&mlp, RaceInputsGen::INPUTS_VERSION).as_slice()).unwrap();
+        model.metadata_props.clear();
+        // When
+        let result = OnnxEvaluator::from_bytes(&model.encode_to_vec(), RaceInputsGen {});
+        // Then
+        assert_eq!(
+            result.err().unwrap(),
+            "The neural net has no inputs version."
+        );
+    }
+}
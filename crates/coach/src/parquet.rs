@@ -0,0 +1,125 @@
+use crate::data::InputsRecord;
+use arrow_array::{ArrayRef, Float32Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use std::io::{Error, Write};
+use std::sync::Arc;
+
+/// Number of rows which are kept in memory before they are written as a row group.
+const ROWS_PER_GROUP: usize = 100_000;
+
+/// Writes [InputsRecord]s as Parquet, so that the Python scripts can load them much faster than CSV.
+///
+/// The columns are `win_normal`, `win_gammon`, `lose_normal`, `lose_gammon` followed by `input_0`,
+/// `input_1` and so on, just like the CSV files of inputs. All columns are non-nullable `f32`.
+pub struct ParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    /// Values of the current row group, one vector for each column.
+    columns: Vec<Vec<f32>>,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(writer: W, num_inputs: usize) -> std::io::Result<Self> {
+        let fields: Vec<Field> = ["win_normal", "win_gammon", "lose_normal", "lose_gammon"]
+            .into_iter()
+            .map(String::from)
+            .chain((0..num_inputs).map(|i| format!("input_{i}")))
+            .map(|name| Field::new(name, DataType::Float32, false))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let writer = ArrowWriter::try_new(writer, schema.clone(), None).map_err(Error::other)?;
+        Ok(Self {
+            writer,
+            columns: vec![Vec::new(); schema.fields().len()],
+            schema,
+        })
+    }
+
+    pub fn write(&mut self, record: &InputsRecord) -> std::io::Result<()> {
+        let values = record.values();
+        assert_eq!(
+            values.len(),
+            self.columns.len(),
+            "All records need the same number of inputs."
+        );
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            column.push(value);
+        }
+        if self.columns[0].len() == ROWS_PER_GROUP {
+            self.write_row_group()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining rows and the metadata and returns the inner writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if !self.columns[0].is_empty() {
+            self.write_row_group()?;
+        }
+        self.writer.into_inner().map_err(Error::other)
+    }
+
+    fn write_row_group(&mut self) -> std::io::Result<()> {
+        let arrays: Vec<ArrayRef> = self
+            .columns
+            .iter_mut()
+            .map(|column| Arc::new(Float32Array::from(std::mem::take(column))) as ArrayRef)
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(Error::other)?;
+        self.writer.write(&batch).map_err(Error::other)?;
+        self.writer.flush().map_err(Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{InputsRecord, PositionRecord};
+    use crate::parquet::ParquetWriter;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Float32Type;
+    use engine::inputs::{InputsGen, RaceInputsGen};
+    use engine::pos;
+    use engine::probabilities::Probabilities;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    #[test]
+    fn write_and_read_back() {
+        // Given
+        let path = std::env::temp_dir().join(format!("wildbg-{}.parquet", std::process::id()));
+        let probabilities = Probabilities {
+            win_normal: 0.25,
+            win_gammon: 0.0,
+            lose_normal: 0.75,
+            lose_gammon: 0.0,
+        };
+        let record = PositionRecord::new(&pos!(x 1:1; o 24:1), &probabilities);
+        let record = InputsRecord::new(&record, &RaceInputsGen {});
+        let file = File::create(&path).unwrap();
+        let mut writer = ParquetWriter::new(file, RaceInputsGen::NUM_INPUTS).unwrap();
+        // When
+        writer.write(&record).unwrap();
+        writer.write(&record).unwrap();
+        writer.finish().unwrap();
+        // Then
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 4 + RaceInputsGen::NUM_INPUTS);
+        let column = |name: &str| {
+            let column = batch.column_by_name(name).unwrap();
+            column.as_primitive::<Float32Type>().values().to_vec()
+        };
+        assert_eq!(column("win_normal"), vec![0.25, 0.25]);
+        assert_eq!(column("lose_normal"), vec![0.75, 0.75]);
+        let values = record.values();
+        assert_eq!(column("input_0"), vec![values[4], values[4]]);
+        _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,121 @@
+use engine::position::{GamePhase, OngoingPhase, Position};
+use std::fmt;
+use std::str::FromStr;
+
+/// Positions with at most this many checkers left on the board for one side are crashed.
+const CRASHED_CHECKERS: i32 = 6;
+
+/// Classes of positions for which separate neural nets can be trained.
+///
+/// Like [OngoingPhase], but contact positions where one side has almost nothing left to play with
+/// are classified as [PositionClass::Crashed]. The definition is the same as in GnuBG.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionClass {
+    Contact,
+    Crashed,
+    Race,
+}
+
+impl PositionClass {
+    pub const ALL: [PositionClass; 3] = [Self::Contact, Self::Crashed, Self::Race];
+
+    /// `None` if the game is already over.
+    pub fn of(position: &Position) -> Option<Self> {
+        match position.game_phase() {
+            GamePhase::GameOver(_) => None,
+            GamePhase::Ongoing(OngoingPhase::Race) => Some(Self::Race),
+            GamePhase::Ongoing(OngoingPhase::Contact) => {
+                if is_crashed(position) || is_crashed(&position.sides_switched()) {
+                    Some(Self::Crashed)
+                } else {
+                    Some(Self::Contact)
+                }
+            }
+        }
+    }
+}
+
+/// Whether `x` is crashed. Checkers stacked deep on the ace and deuce point hardly count.
+fn is_crashed(position: &Position) -> bool {
+    let checkers = |pip: usize| position.pip(pip).max(0) as i32;
+    let total: i32 = (1..=25).map(checkers).sum();
+    let ace = checkers(1);
+    let deuce = checkers(2);
+    if total <= CRASHED_CHECKERS {
+        true
+    } else if ace > 1 {
+        total <= CRASHED_CHECKERS + ace
+            || (deuce > 1 && 1 + total - (ace + deuce) <= CRASHED_CHECKERS)
+    } else {
+        total <= CRASHED_CHECKERS + (deuce - 1)
+    }
+}
+
+impl fmt::Display for PositionClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Contact => "contact",
+            Self::Crashed => "crashed",
+            Self::Race => "race",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for PositionClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|class| class.to_string() == s)
+            .ok_or_else(|| format!("'{s}' is none of contact, crashed and race."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::position_class::PositionClass;
+    use engine::pos;
+    use engine::position::STARTING;
+
+    #[test]
+    fn classes() {
+        assert_eq!(PositionClass::of(&STARTING), Some(PositionClass::Contact));
+        assert_eq!(
+            PositionClass::of(&pos!(x 1:1; o 24:1)),
+            Some(PositionClass::Race)
+        );
+        assert_eq!(PositionClass::of(&pos!(x 1:1; o)), None);
+    }
+
+    #[test]
+    fn crashed() {
+        // `x` has 6 checkers left, `o` is still in the game.
+        let few_checkers = pos!(x 24:2, 3:2, 2:2; o 12:5, 17:3, 19:5, 20:2);
+        assert_eq!(
+            PositionClass::of(&few_checkers),
+            Some(PositionClass::Crashed)
+        );
+        // `o` is crashed, so it's the same from the other side.
+        assert_eq!(
+            PositionClass::of(&few_checkers.sides_switched()),
+            Some(PositionClass::Crashed)
+        );
+        // 11 checkers, but 5 of them are dead on the ace point.
+        let deep_stack = pos!(x 24:2, 6:2, 3:2, 1:5; o 12:5, 17:3, 19:5, 20:2);
+        assert_eq!(PositionClass::of(&deep_stack), Some(PositionClass::Crashed));
+        // The same checkers spread more evenly.
+        let spread = pos!(x 24:2, 6:3, 5:2, 4:2, 3:2; o 12:5, 17:3, 19:5, 20:2);
+        assert_eq!(PositionClass::of(&spread), Some(PositionClass::Contact));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("crashed".parse(), Ok(PositionClass::Crashed));
+        assert_eq!(
+            "crash".parse::<PositionClass>(),
+            Err("'crash' is none of contact, crashed and race.".to_string())
+        );
+    }
+}
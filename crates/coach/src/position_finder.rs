@@ -1,15 +1,27 @@
+use crate::self_play_stats::SelfPlayStats;
 use engine::dice_gen::{DiceGen, FastrandDice};
 use engine::evaluator::Evaluator;
-use engine::position::GameState::Ongoing;
+use engine::position::GameState::{GameOver, Ongoing};
 use engine::position::OngoingPhase::Race;
-use engine::position::{GamePhase, OngoingPhase, Position, STARTING};
+use engine::position::{GamePhase, OngoingPhase, Position, STARTING, X_BAR};
 use engine::probabilities::Probabilities;
 use std::collections::HashSet;
 
+/// Default for [PositionFinder::with_temperature].
+pub const DEFAULT_TEMPERATURE: f32 = 0.02;
+
+/// Moves losing more than this multiple of the temperature are never chosen.
+const MAX_LOSS_PER_TEMPERATURE: f32 = 3.0;
+
 /// Finds random positions for later rollout.
+///
+/// The finder plays games against itself. In contact positions it sometimes chooses a move which
+/// is not the best one, so that the positions also cover what a weaker player would reach.
 pub struct PositionFinder<T: Evaluator, U: DiceGen> {
     evaluator: T,
     dice_gen: U,
+    stats: SelfPlayStats,
+    temperature: f32,
 }
 
 impl<T: Evaluator> PositionFinder<T, FastrandDice> {
@@ -18,11 +30,29 @@ impl<T: Evaluator> PositionFinder<T, FastrandDice> {
         PositionFinder {
             evaluator,
             dice_gen: FastrandDice::new(),
+            stats: SelfPlayStats::default(),
+            temperature: DEFAULT_TEMPERATURE,
         }
     }
 }
 
 impl<T: Evaluator, U: DiceGen> PositionFinder<T, U> {
+    /// How often sub-optimal moves are chosen in contact positions.
+    ///
+    /// A move losing `loss` equity compared to the best move is chosen with a weight of
+    /// `exp(-loss / temperature)`, the best move has a weight of 1. With a temperature of 0.0 the
+    /// finder always chooses the best move.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        assert!(temperature >= 0.0, "The temperature must not be negative.");
+        self.temperature = temperature;
+        self
+    }
+
+    /// Statistics of all games played so far by this finder.
+    pub fn stats(&self) -> &SelfPlayStats {
+        &self.stats
+    }
+
     pub fn find_positions(&mut self, amount: usize, phase: OngoingPhase) -> HashSet<Position> {
         let phase = GamePhase::Ongoing(phase);
         let mut found: HashSet<Position> = HashSet::new();
@@ -45,6 +75,7 @@ impl<T: Evaluator, U: DiceGen> PositionFinder<T, U> {
                 .evaluator
                 .positions_and_probabilities_by_equity(&pos, &dice);
             let next = self.next_position(&positions_and_probabilities);
+            self.stats.add_half_move(pos.pip(X_BAR) > 0 && next == pos);
             if next.game_state() == Ongoing {
                 pos = next.sides_switched();
                 dice = self.dice_gen.roll();
@@ -52,6 +83,9 @@ impl<T: Evaluator, U: DiceGen> PositionFinder<T, U> {
                     Self::positions_from_one_move(next, positions_and_probabilities);
                 positions.extend(rollout_positions);
             } else {
+                if let GameOver(result) = next.game_state() {
+                    self.stats.add_game(result);
+                }
                 return positions;
             }
         }
@@ -114,25 +148,17 @@ impl<T: Evaluator, U: DiceGen> PositionFinder<T, U> {
                 }
             }
         }
+        if self.temperature == 0.0 {
+            return best.0;
+        }
         // Ok, now we are in contact game; sometimes we want return another move than the best one.
         let best_equity = best.1.equity();
         let chances: Vec<f32> = positions_and_probabilities
             .iter()
-            .filter_map(|(_, probability)| {
-                let equity_loss = best_equity - probability.equity();
-                // Let's ignore really bad moves, but if the equity loss is small we sometimes
-                // want to use them.
-                if equity_loss < 0.05 {
-                    // So for the best move the chance is 1.0.
-                    // For a move with an equity loss of 0.03 the chance is 0.55
-                    Some(1.0 - 15.0 * equity_loss)
-                } else {
-                    // `positions_and_probabilities` is sorted by equity.
-                    // So we give a chance for the first entries in there and filter everything
-                    // after that away.
-                    None
-                }
-            })
+            .map(|(_, probability)| best_equity - probability.equity())
+            // `positions_and_probabilities` is sorted by equity, so really bad moves are at the end.
+            .take_while(|equity_loss| *equity_loss < MAX_LOSS_PER_TEMPERATURE * self.temperature)
+            .map(|equity_loss| (-equity_loss / self.temperature).exp())
             .collect();
         let choice = self.dice_gen.choose_index(&chances);
         positions_and_probabilities
@@ -144,7 +170,8 @@ impl<T: Evaluator, U: DiceGen> PositionFinder<T, U> {
 
 #[cfg(test)]
 mod private_tests {
-    use crate::position_finder::PositionFinder;
+    use crate::position_finder::{PositionFinder, DEFAULT_TEMPERATURE};
+    use crate::self_play_stats::SelfPlayStats;
     use engine::dice::Dice;
     use engine::dice_gen::DiceGen;
     use engine::evaluator::RandomEvaluator;
@@ -165,7 +192,7 @@ mod private_tests {
             // not be called as the best position should be returned.
             assert_eq!(chances.len(), 2);
             assert_eq!(chances[0], 1.0);
-            assert!((chances[1] - 0.85).abs() < 0.000001);
+            assert!((chances[1] - (-1.0_f32).exp()).abs() < 0.0001);
             1
         }
     }
@@ -187,6 +214,8 @@ mod private_tests {
         let mut finder = PositionFinder {
             evaluator: RandomEvaluator {},
             dice_gen: DiceGenChooseMock {},
+            stats: SelfPlayStats::default(),
+            temperature: 0.01,
         };
 
         // Given
@@ -217,6 +246,8 @@ mod private_tests {
         let mut finder = PositionFinder {
             evaluator: RandomEvaluator {},
             dice_gen: DiceGenChooseMock {},
+            stats: SelfPlayStats::default(),
+            temperature: 0.01,
         };
 
         // Given
@@ -226,4 +257,27 @@ mod private_tests {
         // Then
         assert_eq!(found, pos_1, "Best move should be returned");
     }
+
+    #[test]
+    fn contact_without_exploration() {
+        let pos_1 = pos!(x 20:1; o 1:1);
+        let pos_2 = pos!(x 20:1; o 2:1);
+        let prob_1 = Probabilities::from(&ResultCounter::new(81, 0, 0, 19, 0, 0));
+        let prob_2 = Probabilities::from(&ResultCounter::new(80, 0, 0, 20, 0, 0));
+
+        let finder = PositionFinder {
+            evaluator: RandomEvaluator {},
+            dice_gen: DiceGenChooseMock {},
+            stats: SelfPlayStats::default(),
+            temperature: DEFAULT_TEMPERATURE,
+        };
+        let mut finder = finder.with_temperature(0.0);
+
+        // Given
+        let input = vec![(pos_1, prob_1), (pos_2, prob_2)];
+        // When
+        let found = finder.next_position(&input);
+        // Then
+        assert_eq!(found, pos_1, "Best move should be returned");
+    }
 }
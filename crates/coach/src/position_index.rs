@@ -0,0 +1,164 @@
+use engine::inputs::InputsGen;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use std::collections::HashMap;
+
+/// Number of hash tables. More tables find more of the true nearest neighbors but need more memory.
+const NUM_TABLES: usize = 8;
+/// Number of random hyperplanes per table. More hyperplanes mean smaller buckets and faster queries.
+const PLANES_PER_TABLE: usize = 12;
+
+/// A known position found by [PositionIndex::nearest].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Neighbor {
+    pub position: Position,
+    pub probabilities: Probabilities,
+    /// Euclidean distance between the neural net inputs of this and the queried position.
+    pub distance: f32,
+}
+
+/// Approximate nearest neighbor search over labeled positions, based on their neural net inputs.
+///
+/// Uses locality sensitive hashing with random hyperplanes: Positions whose inputs are on the same side
+/// of all hyperplanes of a table land in the same bucket. Only positions sharing at least one bucket with
+/// the queried position are compared, so very close positions might be missed in rare cases.
+pub struct PositionIndex<T: InputsGen> {
+    inputs_gen: T,
+    entries: Vec<(Position, Probabilities, Vec<f32>)>,
+    /// For each table all hyperplanes, each with `T::NUM_INPUTS` coefficients.
+    planes: Vec<Vec<Vec<f32>>>,
+    tables: Vec<HashMap<u64, Vec<usize>>>,
+}
+
+impl<T: InputsGen> PositionIndex<T> {
+    /// The same `seed` always creates the same hyperplanes.
+    pub fn new(inputs_gen: T, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let planes = (0..NUM_TABLES)
+            .map(|_| {
+                (0..PLANES_PER_TABLE)
+                    .map(|_| (0..T::NUM_INPUTS).map(|_| 2.0 * rng.f32() - 1.0).collect())
+                    .collect()
+            })
+            .collect();
+        Self {
+            inputs_gen,
+            entries: Vec::new(),
+            planes,
+            tables: vec![HashMap::new(); NUM_TABLES],
+        }
+    }
+
+    pub fn insert(&mut self, position: Position, probabilities: Probabilities) {
+        let inputs = self.inputs_gen.inputs_for_single(&position);
+        let index = self.entries.len();
+        for (table, planes) in self.tables.iter_mut().zip(&self.planes) {
+            table
+                .entry(signature(planes, &inputs))
+                .or_default()
+                .push(index);
+        }
+        self.entries.push((position, probabilities, inputs));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Up to `count` known positions with the most similar inputs, the most similar first.
+    pub fn nearest(&self, position: &Position, count: usize) -> Vec<Neighbor> {
+        let inputs = self.inputs_gen.inputs_for_single(position);
+        let mut candidates: Vec<usize> = self
+            .tables
+            .iter()
+            .zip(&self.planes)
+            .filter_map(|(table, planes)| table.get(&signature(planes, &inputs)))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut neighbors: Vec<Neighbor> = candidates
+            .into_iter()
+            .map(|index| {
+                let (position, probabilities, other) = &self.entries[index];
+                Neighbor {
+                    position: *position,
+                    probabilities: probabilities.clone(),
+                    distance: distance(&inputs, other),
+                }
+            })
+            .collect();
+        neighbors.sort_unstable_by(|a, b| a.distance.total_cmp(&b.distance));
+        neighbors.truncate(count);
+        neighbors
+    }
+}
+
+/// One bit per hyperplane, set if the inputs are on the positive side.
+fn signature(planes: &[Vec<f32>], inputs: &[f32]) -> u64 {
+    planes.iter().enumerate().fold(0, |signature, (i, plane)| {
+        let dot: f32 = plane.iter().zip(inputs).map(|(a, b)| a * b).sum();
+        if dot > 0.0 {
+            signature | 1 << i
+        } else {
+            signature
+        }
+    })
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::position_index::PositionIndex;
+    use engine::inputs::RaceInputsGen;
+    use engine::pos;
+    use engine::probabilities::Probabilities;
+
+    fn probabilities(win_normal: f32) -> Probabilities {
+        Probabilities {
+            win_normal,
+            lose_normal: 1.0 - win_normal,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_identical_position_first() {
+        // Given
+        let mut index = PositionIndex::new(RaceInputsGen {}, 0);
+        for pip in 1..=6 {
+            index.insert(pos!(x pip:2; o 19:2), probabilities(pip as f32 / 10.0));
+        }
+        // When
+        let neighbors = index.nearest(&pos!(x 3:2; o 19:2), 3);
+        // Then
+        assert_eq!(index.len(), 6);
+        assert!(!neighbors.is_empty() && neighbors.len() <= 3);
+        assert_eq!(neighbors[0].position, pos!(x 3:2; o 19:2));
+        assert_eq!(neighbors[0].probabilities, probabilities(0.3));
+        assert_eq!(neighbors[0].distance, 0.0);
+        assert!(neighbors
+            .windows(2)
+            .all(|pair| pair[0].distance <= pair[1].distance));
+    }
+
+    #[test]
+    fn empty_index_returns_nothing() {
+        let index = PositionIndex::new(RaceInputsGen {}, 0);
+        assert!(index.is_empty());
+        assert!(index.nearest(&pos!(x 3:2; o 19:2), 3).is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use engine::probabilities::Probabilities;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Number of equally wide bins of predicted probabilities for the calibration curve.
+pub const CALIBRATION_BINS: usize = 10;
+
+/// Predictions below this value or above `1.0 - LOG_LOSS_EPSILON` are clamped for the log loss.
+const LOG_LOSS_EPSILON: f64 = 1e-7;
+
+/// The "classic" outcomes, as in [crate::data::PositionRecord].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Win,
+    WinGammon,
+    LoseGammon,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Win, Outcome::WinGammon, Outcome::LoseGammon];
+
+    pub fn of(&self, probabilities: &Probabilities) -> f32 {
+        match self {
+            Outcome::Win => probabilities.win(),
+            Outcome::WinGammon => probabilities.win_gammon,
+            Outcome::LoseGammon => probabilities.lose_gammon,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Outcome::Win => "win",
+            Outcome::WinGammon => "win gammon",
+            Outcome::LoseGammon => "lose gammon",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// All predictions of one bin, with the average rollout result for them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CalibrationBin {
+    pub count: usize,
+    predicted_sum: f64,
+    observed_sum: f64,
+}
+
+impl CalibrationBin {
+    pub fn predicted(&self) -> f64 {
+        self.predicted_sum / self.count as f64
+    }
+
+    pub fn observed(&self) -> f64 {
+        self.observed_sum / self.count as f64
+    }
+}
+
+/// Brier score, log loss and calibration curve of the predictions of a single outcome.
+///
+/// The targets are rollout probabilities, not single game results, so even a perfect net
+/// doesn't reach a Brier score or log loss of 0. But lower is better in any case.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OutcomeMetrics {
+    count: usize,
+    squared_error_sum: f64,
+    log_loss_sum: f64,
+    bins: [CalibrationBin; CALIBRATION_BINS],
+}
+
+impl OutcomeMetrics {
+    pub fn add(&mut self, predicted: f32, observed: f32) {
+        let (predicted, observed) = (predicted as f64, observed as f64);
+        self.count += 1;
+        self.squared_error_sum += (predicted - observed).powi(2);
+        let p = predicted.clamp(LOG_LOSS_EPSILON, 1.0 - LOG_LOSS_EPSILON);
+        self.log_loss_sum -= observed * p.ln() + (1.0 - observed) * (1.0 - p).ln();
+        let index = ((predicted * CALIBRATION_BINS as f64) as usize).min(CALIBRATION_BINS - 1);
+        let bin = &mut self.bins[index];
+        bin.count += 1;
+        bin.predicted_sum += predicted;
+        bin.observed_sum += observed;
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn brier_score(&self) -> f64 {
+        self.squared_error_sum / self.count as f64
+    }
+
+    pub fn log_loss(&self) -> f64 {
+        self.log_loss_sum / self.count as f64
+    }
+
+    /// Bins of predictions from `0.0` to `1.0`, empty bins included.
+    ///
+    /// For a well calibrated net `predicted` and `observed` are about the same in each bin.
+    pub fn calibration(&self) -> &[CalibrationBin] {
+        &self.bins
+    }
+}
+
+impl fmt::Display for OutcomeMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Brier score {:.5}, log loss {:.5}",
+            self.brier_score(),
+            self.log_loss()
+        )?;
+        for (i, bin) in self.bins.iter().enumerate() {
+            let from = i as f64 / CALIBRATION_BINS as f64;
+            let to = (i + 1) as f64 / CALIBRATION_BINS as f64;
+            if bin.count == 0 {
+                writeln!(f, "  {from:.1} - {to:.1}: no predictions")?;
+            } else {
+                writeln!(
+                    f,
+                    "  {from:.1} - {to:.1}: predicted {:.3}, rollouts {:.3} ({:+.3}), {} positions",
+                    bin.predicted(),
+                    bin.observed(),
+                    bin.predicted() - bin.observed(),
+                    bin.count
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares predictions of a net with rollout results for all [Outcome]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PredictionMetrics {
+    outcomes: [OutcomeMetrics; 3],
+}
+
+impl PredictionMetrics {
+    pub fn add(&mut self, predicted: &Probabilities, rollout: &Probabilities) {
+        for (outcome, metrics) in Outcome::ALL.iter().zip(self.outcomes.iter_mut()) {
+            metrics.add(outcome.of(predicted), outcome.of(rollout));
+        }
+    }
+
+    pub fn outcome(&self, outcome: Outcome) -> &OutcomeMetrics {
+        let index = Outcome::ALL.iter().position(|o| *o == outcome).unwrap();
+        &self.outcomes[index]
+    }
+}
+
+impl fmt::Display for PredictionMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for outcome in Outcome::ALL {
+            write!(f, "{outcome}: {}", self.outcome(outcome))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prediction_metrics::{Outcome, OutcomeMetrics, PredictionMetrics};
+    use engine::probabilities::Probabilities;
+
+    #[test]
+    fn brier_score_and_log_loss() {
+        // Given
+        let mut metrics = OutcomeMetrics::default();
+        // When
+        metrics.add(0.8, 1.0);
+        metrics.add(0.5, 0.5);
+        // Then
+        assert_eq!(metrics.count(), 2);
+        assert!((metrics.brier_score() - 0.02).abs() < 1e-6);
+        let expected_log_loss = (-(0.8f64.ln()) + -(0.5f64.ln())) / 2.0;
+        assert!((metrics.log_loss() - expected_log_loss).abs() < 1e-6);
+    }
+
+    #[test]
+    fn calibration_bins() {
+        // Given
+        let mut metrics = OutcomeMetrics::default();
+        // When
+        metrics.add(0.12, 0.2);
+        metrics.add(0.18, 0.3);
+        metrics.add(1.0, 1.0);
+        // Then
+        let bins = metrics.calibration();
+        assert_eq!(bins[0].count, 0);
+        assert_eq!(bins[1].count, 2);
+        assert!((bins[1].predicted() - 0.15).abs() < 1e-6);
+        assert!((bins[1].observed() - 0.25).abs() < 1e-6);
+        assert_eq!(bins[9].count, 1);
+    }
+
+    #[test]
+    fn metrics_per_outcome() {
+        // Given
+        let predicted = Probabilities {
+            win_normal: 0.4,
+            win_gammon: 0.2,
+            lose_normal: 0.3,
+            lose_gammon: 0.1,
+        };
+        let rollout = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.1,
+            lose_normal: 0.3,
+            lose_gammon: 0.1,
+        };
+        // When
+        let mut metrics = PredictionMetrics::default();
+        metrics.add(&predicted, &rollout);
+        // Then
+        assert!(metrics.outcome(Outcome::Win).brier_score() < 1e-9);
+        assert!((metrics.outcome(Outcome::WinGammon).brier_score() - 0.01).abs() < 1e-6);
+        assert!(metrics.outcome(Outcome::LoseGammon).brier_score() < 1e-9);
+    }
+}
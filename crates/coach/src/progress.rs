@@ -0,0 +1,196 @@
+use crate::coach_helpers::duration;
+use clap::ValueEnum;
+#[cfg(feature = "progress-bar")]
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::{stdout, IsTerminal};
+use std::time::{Duration, Instant};
+
+/// Environment variable to choose the [ProgressMode] of binaries without a `--progress` option.
+pub const PROGRESS_ENV: &str = "WILDBG_PROGRESS";
+
+/// How often a new line is printed in [ProgressMode::Lines].
+const LINE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How progress of long-running binaries is shown.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ProgressMode {
+    /// A bar of `indicatif` that is redrawn in place, for terminals.
+    ///
+    /// Needs the feature `progress-bar`, without it this is the same as [ProgressMode::Lines].
+    Bar,
+    /// A new line every 30 seconds, for log files and CI.
+    Lines,
+    /// Nothing.
+    Quiet,
+}
+
+impl ProgressMode {
+    /// Value of [PROGRESS_ENV] if it's set, otherwise [ProgressMode::Bar] in a terminal and
+    /// [ProgressMode::Lines] when the output is redirected.
+    pub fn from_env() -> Self {
+        std::env::var(PROGRESS_ENV)
+            .ok()
+            .and_then(|value| ProgressMode::from_str(&value, true).ok())
+            .unwrap_or_else(|| {
+                if stdout().is_terminal() {
+                    ProgressMode::Bar
+                } else {
+                    ProgressMode::Lines
+                }
+            })
+    }
+}
+
+/// Shows how many of `total` items are done, how many per second and when all will be done.
+pub struct Progress {
+    total: usize,
+    done: usize,
+    /// Plural name of the items, for example "positions" or "games".
+    unit: &'static str,
+    mode: ProgressMode,
+    start: Instant,
+    last_line: Instant,
+    #[cfg(feature = "progress-bar")]
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    pub fn new(total: usize, unit: &'static str, mode: ProgressMode) -> Self {
+        #[cfg(not(feature = "progress-bar"))]
+        let mode = match mode {
+            ProgressMode::Bar => ProgressMode::Lines,
+            mode => mode,
+        };
+        let now = Instant::now();
+        Self {
+            total,
+            done: 0,
+            unit,
+            mode,
+            start: now,
+            last_line: now,
+            #[cfg(feature = "progress-bar")]
+            bar: (mode == ProgressMode::Bar).then(|| progress_bar(total, unit)),
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    /// Marks one more item as done and shows the progress.
+    pub fn inc(&mut self) -> std::io::Result<()> {
+        self.done += 1;
+        match self.mode {
+            ProgressMode::Bar =>
+            {
+                #[cfg(feature = "progress-bar")]
+                if let Some(bar) = &self.bar {
+                    bar.inc(1);
+                }
+            }
+            ProgressMode::Lines => {
+                if self.last_line.elapsed() >= LINE_INTERVAL || self.done == self.total {
+                    self.last_line = Instant::now();
+                    println!("{}", self.summary(self.start.elapsed()));
+                }
+            }
+            ProgressMode::Quiet => {}
+        }
+        Ok(())
+    }
+
+    /// Ends the bar, so that following output starts on a new line.
+    pub fn finish(&self) {
+        #[cfg(feature = "progress-bar")]
+        if let Some(bar) = &self.bar {
+            bar.finish();
+        }
+    }
+
+    /// For example `1200/5000 positions (24.00 %), 3.20 positions/s, elapsed 00:06:15 h, ETA 00:19:47 h`.
+    pub fn summary(&self, elapsed: Duration) -> String {
+        let seconds = elapsed.as_secs_f64();
+        let rate = if seconds > 0.0 {
+            self.done as f64 / seconds
+        } else {
+            0.0
+        };
+        let eta = if rate > 0.0 {
+            duration(((self.total - self.done.min(self.total)) as f64 / rate) as u64)
+        } else {
+            "unknown".to_string()
+        };
+        format!(
+            "{}/{} {} ({:.2} %), {:.2} {}/s, elapsed {}, ETA {}",
+            self.done,
+            self.total,
+            self.unit,
+            self.fraction() * 100.0,
+            rate,
+            self.unit,
+            duration(elapsed.as_secs()),
+            eta,
+        )
+    }
+
+    fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+#[cfg(feature = "progress-bar")]
+fn progress_bar(total: usize, unit: &'static str) -> ProgressBar {
+    let template = format!(
+        "[{{bar:30}}] {{pos}}/{{len}} {unit} ({{percent}} %), {{per_sec}}, elapsed {{elapsed_precise}}, ETA {{eta_precise}}"
+    );
+    let style = ProgressStyle::with_template(&template)
+        .expect("The template is valid.")
+        .progress_chars("#-");
+    ProgressBar::new(total as u64).with_style(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress::{Progress, ProgressMode};
+    use std::time::Duration;
+
+    #[test]
+    fn summary_with_rate_and_eta() {
+        // Given
+        let mut progress = Progress::new(100, "positions", ProgressMode::Quiet);
+        // When
+        for _ in 0..25 {
+            progress.inc().unwrap();
+        }
+        // Then
+        assert_eq!(
+            progress.summary(Duration::from_secs(50)),
+            "25/100 positions (25.00 %), 0.50 positions/s, elapsed 00:00:50 h, ETA 00:02:30 h"
+        );
+    }
+
+    #[test]
+    fn nothing_done_yet() {
+        let progress = Progress::new(10, "games", ProgressMode::Quiet);
+
+        assert_eq!(
+            progress.summary(Duration::ZERO),
+            "0/10 games (0.00 %), 0.00 games/s, elapsed 00:00:00 h, ETA unknown"
+        );
+    }
+
+    #[test]
+    fn bar_needs_feature() {
+        let progress = Progress::new(3, "games", ProgressMode::Bar);
+
+        #[cfg(feature = "progress-bar")]
+        assert!(progress.bar.is_some());
+        #[cfg(not(feature = "progress-bar"))]
+        assert_eq!(progress.mode, ProgressMode::Lines);
+    }
+}
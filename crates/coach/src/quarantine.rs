@@ -0,0 +1,139 @@
+use engine::evaluator::Evaluator;
+use engine::inputs::{ContactInputsGen, InputsGen, RaceInputsGen};
+use engine::position::{GamePhase, OngoingPhase, Position};
+use engine::probabilities::Probabilities;
+use serde::Serialize;
+use std::fs::File;
+use std::sync::Mutex;
+
+/// Wraps another evaluator and records all positions for which it returns inconsistent probabilities.
+///
+/// The probabilities are passed on unchanged, so that the behavior of rollouts and self play doesn't change.
+/// The offending positions are appended to a CSV file for later inspection, see [QuarantineRecord].
+pub struct QuarantineEvaluator<T: Evaluator> {
+    evaluator: T,
+    writer: Mutex<csv::Writer<File>>,
+}
+
+/// One line in the quarantine file. The file has no header as the number of inputs varies.
+#[derive(Debug, Serialize)]
+pub struct QuarantineRecord {
+    position_id: String,
+    win_normal: f32,
+    win_gammon: f32,
+    lose_normal: f32,
+    lose_gammon: f32,
+    /// Inputs of the neural net for the game phase of the position.
+    inputs: Vec<f32>,
+}
+
+impl QuarantineRecord {
+    pub fn new(position: &Position, probabilities: &Probabilities) -> Self {
+        let inputs = match position.game_phase() {
+            GamePhase::Ongoing(OngoingPhase::Contact) => {
+                ContactInputsGen {}.inputs_for_single(position)
+            }
+            GamePhase::Ongoing(OngoingPhase::Race) => RaceInputsGen {}.inputs_for_single(position),
+            GamePhase::GameOver(_) => Vec::new(),
+        };
+        Self {
+            position_id: position.position_id(),
+            win_normal: probabilities.win_normal,
+            win_gammon: probabilities.win_gammon,
+            lose_normal: probabilities.lose_normal,
+            lose_gammon: probabilities.lose_gammon,
+            inputs,
+        }
+    }
+}
+
+impl<T: Evaluator> QuarantineEvaluator<T> {
+    /// New records are appended to the file at `path`, existing records are kept.
+    pub fn new(evaluator: T, path: &str) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_writer(file);
+        Ok(Self {
+            evaluator,
+            writer: Mutex::new(writer),
+        })
+    }
+
+    fn check(&self, position: &Position, probabilities: &Probabilities) {
+        if probabilities.is_consistent() {
+            return;
+        }
+        let record = QuarantineRecord::new(position, probabilities);
+        // Evaluation must go on, so we only report problems with the quarantine file.
+        let mut writer = self.writer.lock().unwrap();
+        let result = writer
+            .serialize(record)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writer.flush());
+        if let Err(error) = result {
+            eprintln!("\nCould not write to quarantine file: {error}");
+        }
+    }
+}
+
+impl<T: Evaluator> Evaluator for QuarantineEvaluator<T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        let probabilities = self.evaluator.eval(pos);
+        self.check(pos, &probabilities);
+        probabilities
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        let positions_and_probabilities = self.evaluator.eval_batch(positions);
+        for (position, probabilities) in &positions_and_probabilities {
+            self.check(position, probabilities);
+        }
+        positions_and_probabilities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::quarantine::QuarantineEvaluator;
+    use engine::evaluator::Evaluator;
+    use engine::pos;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+
+    /// Returns inconsistent probabilities for all positions where `x` has checkers on the 1 point.
+    struct EvaluatorFake {}
+    impl Evaluator for EvaluatorFake {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let win_normal = if pos.pip(1) > 0 { 1.2 } else { 1.0 };
+            Probabilities {
+                win_normal,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[test]
+    fn only_inconsistent_positions_are_recorded() {
+        // Given
+        let path = std::env::temp_dir().join("wildbg-quarantine-test.csv");
+        let path = path.to_str().unwrap();
+        _ = std::fs::remove_file(path);
+        let evaluator = QuarantineEvaluator::new(EvaluatorFake {}, path).unwrap();
+        let bad = pos!(x 1:1; o 24:1);
+        // When
+        let probabilities = evaluator.eval(&bad);
+        evaluator.eval_batch(vec![pos!(x 2:1; o 24:1), bad]);
+        // Then
+        assert_eq!(probabilities.win_normal, 1.2, "Values must not be changed");
+        let content = std::fs::read_to_string(path).unwrap();
+        _ = std::fs::remove_file(path);
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!("{},1.2,0.0,0.0,0.0,", bad.position_id())));
+    }
+}
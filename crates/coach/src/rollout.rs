@@ -1,18 +1,26 @@
 use engine::dice::{Dice, ALL_441};
 use engine::dice_gen::{DiceGen, FastrandDice};
 use engine::evaluator::{Evaluator, RandomEvaluator};
+use engine::position::GameResult::{LoseGammon, LoseNormal, WinGammon, WinNormal};
 use engine::position::GameState::{GameOver, Ongoing};
-use engine::position::{GameResult, Position};
-use engine::probabilities::{Probabilities, ResultCounter};
+use engine::position::{GamePhase, GameResult, Position, O_BAR, X_BAR};
+use engine::probabilities::{AtomicResultCounter, Probabilities, ProbabilitiesF64, ResultCounter};
 use rayon::prelude::*;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+mod report;
+
+use report::GameRecord;
+pub use report::RolloutReport;
+
 /// Two `RolloutEvaluator`s which are initialized with the same `seed` and the same evaluators,
 /// will always return the identical value when `eval` is called for the same position.
 pub struct RolloutEvaluator<T: Evaluator> {
     evaluator: T,
     seed: u64,
+    /// Number of groups of 1296 games per position in [Evaluator::eval].
+    repetitions: usize,
 }
 
 /// We will do 1296 single rollouts and we need different dice for them.
@@ -26,34 +34,105 @@ fn dice_seeds(dice_gen: &mut FastrandDice, amount: usize) -> Vec<u64> {
 }
 
 impl<T: Evaluator + Sync> Evaluator for RolloutEvaluator<T> {
-    /// Rolls out 1296 times, the dice for the first two half moves are given, rest is random
+    /// Rolls out 1296 times per repetition, the dice for the first two half moves are given, rest is random
     fn eval(&self, pos: &Position) -> Probabilities {
-        debug_assert!(pos.game_state() == Ongoing);
+        Probabilities::from(&self.results_with_seed(pos, self.seed, self.repetitions))
+    }
+}
 
-        // We don't want to have identical dice for rollouts of *all* positions.
-        // On the other hand, for a certain position, we always want the same dice, this helps in tests.
-        // So we initialize `FastrandDice` with a seed depending on the hash of the position combined
-        // with the seed of this RolloutEvaluator.
-        let mut hasher = DefaultHasher::new();
-        pos.hash(&mut hasher);
-        self.seed.hash(&mut hasher);
-        let seed = hasher.finish();
-        let mut dice_gen = FastrandDice::with_seed(seed);
+impl<T: Evaluator + Sync> RolloutEvaluator<T> {
+    /// Like [Evaluator::eval], but with the given `seed` instead of the one of this evaluator
+    /// and always with a single repetition.
+    ///
+    /// Useful for repeating a rollout with a known seed without loading the evaluator again.
+    pub fn eval_with_seed(&self, pos: &Position, seed: u64) -> Probabilities {
+        Probabilities::from(&self.results_with_seed(pos, seed, 1))
+    }
 
-        let dice_and_seeds =
-            ALL_441.map(|(dice, amount)| (dice, dice_seeds(&mut dice_gen, amount)));
-        let game_results: ResultCounter = dice_and_seeds
-            .par_iter()
-            .map(|(dice, seeds)| self.results_from_single_rollouts(pos, dice, seeds))
-            .reduce(ResultCounter::default, |a, b| a.combine(&b));
+    /// Rolls out `1296 * repetitions` games and returns the number of each game result.
+    ///
+    /// The first two half moves are the same for each group of 1296 games. With one repetition
+    /// the games are identical to those of [RolloutEvaluator::eval_with_seed].
+    pub fn results_with_seed(
+        &self,
+        pos: &Position,
+        seed: u64,
+        repetitions: usize,
+    ) -> ResultCounter {
+        debug_assert!(pos.game_state() == Ongoing);
+        let dice_and_seeds = dice_and_seeds(pos, seed, repetitions);
+        let game_results = AtomicResultCounter::default();
+        dice_and_seeds.par_iter().for_each(|(dice, seeds)| {
+            game_results.merge(&self.results_from_single_rollouts(pos, dice, seeds));
+        });
+        let game_results = game_results.into_inner();
 
         debug_assert_eq!(
-            game_results.sum(),
-            6 * 6 * 6 * 6,
-            "Rollout should look at 1296 games"
+            game_results.sum() as usize,
+            6 * 6 * 6 * 6 * repetitions,
+            "Rollout should look at 1296 games per repetition"
         );
-        Probabilities::from(&game_results)
+        game_results
     }
+
+    /// Plays the same games as [RolloutEvaluator::results_with_seed], one repetition of 1296 games
+    /// after the other, and passes the results of each repetition to `progress`.
+    ///
+    /// The sum of these results equals `results_with_seed`, so a rollout with progress reports
+    /// uses the same dice as one without. Stops early if `progress` returns `false`.
+    pub fn results_with_progress(
+        &self,
+        pos: &Position,
+        seed: u64,
+        repetitions: usize,
+        mut progress: impl FnMut(&ResultCounter) -> bool,
+    ) {
+        debug_assert!(pos.game_state() == Ongoing);
+        let dice_and_seeds = dice_and_seeds(pos, seed, repetitions);
+        for repetition in 0..repetitions {
+            let game_results = AtomicResultCounter::default();
+            dice_and_seeds.par_iter().for_each(|(dice, seeds)| {
+                let amount = seeds.len() / repetitions;
+                let seeds = &seeds[repetition * amount..(repetition + 1) * amount];
+                game_results.merge(&self.results_from_single_rollouts(pos, dice, seeds));
+            });
+            if !progress(&game_results.into_inner()) {
+                return;
+            }
+        }
+    }
+
+    /// Like [RolloutEvaluator::results_with_seed], but also records statistics of the games.
+    ///
+    /// The games are identical, so [RolloutReport::results] equals `results_with_seed`.
+    /// Slower, as games are also played one by one when the first two half moves end them.
+    pub fn report_with_seed(&self, pos: &Position, seed: u64, repetitions: usize) -> RolloutReport {
+        debug_assert!(pos.game_state() == Ongoing);
+        dice_and_seeds(pos, seed, repetitions)
+            .par_iter()
+            .flat_map_iter(|(dice, seeds)| seeds.iter().map(move |seed| (dice, *seed)))
+            .map(|(dice, seed)| self.recorded_rollout(pos, dice, seed))
+            .fold(RolloutReport::default, |mut report, game| {
+                report.add(&game);
+                report
+            })
+            .reduce(RolloutReport::default, RolloutReport::combine)
+    }
+}
+
+/// Dice for the first two half moves and seeds for the rest, for `1296 * repetitions` games.
+fn dice_and_seeds(pos: &Position, seed: u64, repetitions: usize) -> [([Dice; 2], Vec<u64>); 441] {
+    // We don't want to have identical dice for rollouts of *all* positions.
+    // On the other hand, for a certain position, we always want the same dice, this helps in tests.
+    // So we initialize `FastrandDice` with a seed depending on the hash of the position combined
+    // with the seed of this RolloutEvaluator.
+    let mut hasher = DefaultHasher::new();
+    pos.hash(&mut hasher);
+    seed.hash(&mut hasher);
+    let seed = hasher.finish();
+    let mut dice_gen = FastrandDice::with_seed(seed);
+
+    ALL_441.map(|(dice, amount)| (dice, dice_seeds(&mut dice_gen, amount * repetitions)))
 }
 
 impl RolloutEvaluator<RandomEvaluator> {
@@ -69,7 +148,23 @@ impl<T: Evaluator> RolloutEvaluator<T> {
     }
 
     pub fn with_evaluator_and_seed(evaluator: T, seed: u64) -> Self {
-        Self { evaluator, seed }
+        Self {
+            evaluator,
+            seed,
+            repetitions: 1,
+        }
+    }
+
+    /// Rolls out `1296 * repetitions` games per position instead of 1296.
+    pub fn with_repetitions(mut self, repetitions: usize) -> Self {
+        assert!(repetitions > 0, "At least one repetition is needed.");
+        self.repetitions = repetitions;
+        self
+    }
+
+    /// The evaluator choosing the moves during the rollouts.
+    pub fn evaluator(&self) -> &T {
+        &self.evaluator
     }
 
     /// Will do *n* rollouts from the given position, with *n* being the length of `seeds`.
@@ -84,13 +179,13 @@ impl<T: Evaluator> RolloutEvaluator<T> {
     ) -> ResultCounter {
         let mut counter = ResultCounter::default();
         match self.single_rollout_with_dice(from, first_dice) {
-            Ok(result) => {
-                counter.add_results(result, seeds.len() as u32);
+            Ok(outcome) => {
+                add_outcome(&mut counter, outcome, seeds.len() as u64);
             }
             Err(pos) => seeds.iter().for_each(|seed| {
                 let mut dice_gen = FastrandDice::with_seed(*seed);
-                let result = self.single_rollout_with_generator(&pos, &mut dice_gen);
-                counter.add(result);
+                let outcome = self.single_rollout_with_generator(&pos, &mut dice_gen);
+                add_outcome(&mut counter, outcome, 1);
             }),
         }
         counter
@@ -104,50 +199,148 @@ impl<T: Evaluator> RolloutEvaluator<T> {
         &self,
         from: &Position,
         first_dice: &[Dice; 2],
-    ) -> Result<GameResult, Position> {
+    ) -> Result<Outcome, Position> {
         let mut player_on_turn = true;
         let mut pos = *from;
         for dice in first_dice {
             pos = self.evaluator.best_position_by_equity(&pos, dice);
             if let GameOver(result) = pos.game_state() {
-                return if player_on_turn {
-                    Ok(result.reverse())
+                let result = if player_on_turn {
+                    result.reverse()
                 } else {
-                    Ok(result)
+                    result
                 };
+                return Ok((result, pos.is_backgammon()));
             }
             player_on_turn = !player_on_turn;
         }
         Err(pos)
     }
 
+    /// A single game like in [RolloutEvaluator::results_from_single_rollouts], with statistics.
+    fn recorded_rollout(&self, from: &Position, first_dice: &[Dice; 2], seed: u64) -> GameRecord {
+        let mut dice_gen = FastrandDice::with_seed(seed);
+        let mut player_on_turn = true;
+        let mut pos = *from;
+        let mut hits = [0, 0];
+        for half_moves in 1.. {
+            let dice = match first_dice.get(half_moves - 1) {
+                Some(dice) => *dice,
+                None => dice_gen.roll(),
+            };
+            let GamePhase::Ongoing(phase) = pos.game_phase() else {
+                unreachable!("Rollouts stop when the game is over.");
+            };
+            let next = self.evaluator.best_position_by_equity(&pos, &dice);
+            // After the move the opponent is `x`, so its checkers on the bar are on `X_BAR`.
+            let hit = next.pip(X_BAR) + pos.pip(O_BAR);
+            hits[if player_on_turn { 0 } else { 1 }] += hit as u32;
+            if let GameOver(result) = next.game_state() {
+                let result = if player_on_turn {
+                    result.reverse()
+                } else {
+                    result
+                };
+                return GameRecord {
+                    outcome: (result, next.is_backgammon()),
+                    half_moves,
+                    hits,
+                    final_phase: phase,
+                };
+            }
+            pos = next;
+            player_on_turn = !player_on_turn;
+        }
+        unreachable!("Games always end.")
+    }
+
     fn single_rollout_with_generator<U: DiceGen>(
         &self,
         from: &Position,
         dice_gen: &mut U,
-    ) -> GameResult {
+    ) -> Outcome {
         let mut player_on_turn = true;
         let mut pos = *from;
         loop {
             let dice = dice_gen.roll();
             pos = self.evaluator.best_position_by_equity(&pos, &dice);
             if let GameOver(result) = pos.game_state() {
-                return if player_on_turn {
+                let result = if player_on_turn {
                     result.reverse()
                 } else {
                     result
                 };
+                return (result, pos.is_backgammon());
             }
             player_on_turn = !player_on_turn;
         }
     }
 }
 
+/// Result of a single game and whether it's a backgammon.
+type Outcome = (GameResult, bool);
+
+fn add_outcome(counter: &mut ResultCounter, (result, backgammon): Outcome, amount: u64) {
+    if backgammon {
+        counter.add_backgammons(result, amount);
+    } else {
+        counter.add_results(result, amount);
+    }
+}
+
+/// Games per repetition of a rollout, see `RolloutEvaluator::results_with_seed`.
+pub const GAMES_PER_REPETITION: usize = 1296;
+
+/// Result of a rollout together with standard errors.
+///
+/// The standard errors are computed as if all games were independent. As the first two half moves
+/// are the same for each group of 1296 games, the real errors are somewhat smaller.
+pub struct RolloutSummary {
+    pub games: u64,
+    pub probabilities: Probabilities,
+    /// Standard errors of `win`, `win_gammon` and `lose_gammon`.
+    pub probability_errors: [f32; 3],
+    pub equity_error: f32,
+}
+
+impl From<&ResultCounter> for RolloutSummary {
+    /// Computes in double precision, so that rollouts with millions of games don't lose accuracy.
+    fn from(counter: &ResultCounter) -> Self {
+        let games = counter.sum();
+        let probabilities = ProbabilitiesF64::from(counter);
+        let n = games as f64;
+        let error = |p: f64| (p * (1.0 - p) / n).sqrt() as f32;
+        let mean_square = [
+            (WinNormal, 1.0),
+            (WinGammon, 4.0),
+            (LoseNormal, 1.0),
+            (LoseGammon, 4.0),
+        ]
+        .into_iter()
+        .map(|(result, square)| square * counter.num_of(result) as f64 / n)
+        .sum::<f64>();
+        let variance = mean_square - probabilities.equity().powi(2);
+        Self {
+            games,
+            probability_errors: [
+                error(probabilities.win()),
+                error(probabilities.win_gammon),
+                error(probabilities.lose_gammon),
+            ],
+            equity_error: (variance.max(0.0) / n).sqrt() as f32,
+            probabilities: Probabilities::from(&probabilities),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::rollout::RolloutEvaluator;
+    use crate::rollout::{RolloutEvaluator, RolloutSummary};
     use engine::evaluator::Evaluator;
     use engine::pos;
+    use engine::position::GameResult::{LoseNormal, WinNormal};
+    use engine::probabilities::ResultCounter;
+    use engine::test_utils::EvaluatorFake;
 
     #[test]
     fn correct_results_after_first_or_second_half_move() {
@@ -184,6 +377,65 @@ mod tests {
         let results = rollout_eval.eval(&pos);
         assert_eq!(results.lose_gammon, 1.0);
     }
+
+    #[test]
+    fn results_with_repetitions() {
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let pos = pos!(x 6:1; o 19:1);
+
+        // See `correct_results_after_first_or_second_half_move`, three times as many games.
+        let results = rollout_eval.results_with_seed(&pos, 42, 3);
+        assert_eq!(results.sum(), 3 * 1296);
+        assert_eq!(results.num_of(WinNormal), 3 * 1053);
+        assert_eq!(results.num_of(LoseNormal), 3 * 243);
+    }
+
+    #[test]
+    fn results_with_progress_plays_the_same_games() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_evaluator(EvaluatorFake {});
+        let pos = pos!(x 6:2, 4:1; o 19:2, 21:1);
+        // When
+        let mut repetitions = Vec::new();
+        rollout_eval.results_with_progress(&pos, 42, 3, |results| {
+            repetitions.push(results.clone());
+            true
+        });
+        // Then
+        assert_eq!(repetitions.len(), 3);
+        assert!(repetitions.iter().all(|results| results.sum() == 1296));
+        let sum = repetitions
+            .iter()
+            .fold(ResultCounter::default(), |sum, results| {
+                sum.combine(results)
+            });
+        assert_eq!(sum, rollout_eval.results_with_seed(&pos, 42, 3));
+    }
+
+    #[test]
+    fn results_with_progress_stops_early() {
+        let rollout_eval = RolloutEvaluator::with_evaluator(EvaluatorFake {});
+        let mut calls = 0;
+        rollout_eval.results_with_progress(&pos!(x 6:1; o 19:1), 42, 3, |_| {
+            calls += 1;
+            false
+        });
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn standard_errors() {
+        // Given
+        let counter = ResultCounter::new(30, 20, 0, 40, 10, 0);
+        // When
+        let summary = RolloutSummary::from(&counter);
+        // Then
+        assert_eq!(summary.games, 100);
+        // Win is 50%: sqrt(0.5 * 0.5 / 100)
+        assert_eq!(summary.probability_errors[0], 0.05);
+        // Equity is 0.3 - 0.4 + 2 * (0.2 - 0.1) = 0.1, mean square is 0.3 + 0.8 + 0.4 + 0.4 = 1.9
+        assert!((summary.equity_error - (1.89_f32 / 100.0).sqrt()).abs() < 1e-6);
+    }
 }
 
 #[cfg(test)]
@@ -192,9 +444,7 @@ mod private_tests {
     use engine::dice::Dice;
     use engine::dice_gen::DiceGenMock;
     use engine::pos;
-    use engine::position::GameResult::{
-         LoseGammon, LoseNormal, WinGammon, WinNormal,
-    };
+    use engine::position::GameResult::{LoseGammon, LoseNormal, WinGammon, WinNormal};
 
     #[test]
     fn single_rollout_with_generator_win_normal() {
@@ -206,7 +456,7 @@ mod private_tests {
         let result = rollout_eval.single_rollout_with_generator(&pos, &mut dice_gen);
         //Then
         dice_gen.assert_all_dice_were_used();
-        assert_eq!(result, WinNormal);
+        assert_eq!(result, (WinNormal, false));
     }
 
     #[test]
@@ -224,7 +474,7 @@ mod private_tests {
         let result = rollout_eval.single_rollout_with_generator(&pos, &mut dice_gen);
         // Then
         dice_gen.assert_all_dice_were_used();
-        assert_eq!(result, LoseNormal);
+        assert_eq!(result, (LoseNormal, false));
     }
 
     #[test]
@@ -236,7 +486,7 @@ mod private_tests {
         let result =
             rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(2, 2), Dice::new(6, 6)]);
         //Then
-        assert_eq!(result, Ok(WinGammon));
+        assert_eq!(result, Ok((WinGammon, false)));
     }
 
     #[test]
@@ -248,7 +498,19 @@ mod private_tests {
         let result =
             rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(2, 1), Dice::new(3, 3)]);
         //Then
-        assert_eq!(result, Ok(LoseGammon));
+        assert_eq!(result, Ok((LoseGammon, false)));
+    }
+
+    #[test]
+    fn single_rollout_with_dice_win_backgammon() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let pos = pos!(x 1:4; o 3:1, 18:14);
+        // When
+        let result =
+            rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(2, 2), Dice::new(6, 6)]);
+        //Then
+        assert_eq!(result, Ok((WinGammon, true)));
     }
 
     #[test]
@@ -0,0 +1,205 @@
+use crate::rollout::{add_outcome, Outcome};
+use engine::position::GameResult::{LoseGammon, WinGammon};
+use engine::position::OngoingPhase;
+use engine::probabilities::ResultCounter;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A single rollout game, as recorded by [crate::rollout::RolloutEvaluator::report_with_seed].
+pub(super) struct GameRecord {
+    pub(super) outcome: Outcome,
+    pub(super) half_moves: usize,
+    /// Checkers hit by `x` and by `o`.
+    pub(super) hits: [u32; 2],
+    /// Phase of the position from which the last half move ended the game.
+    pub(super) final_phase: OngoingPhase,
+}
+
+/// Statistics of rollout games beyond the probabilities.
+///
+/// Useful for research and for finding out why a net behaves oddly, for example if it plays for
+/// gammons in races or hits far more often than the other net. `x` is the player on roll in the
+/// rolled out position.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RolloutReport {
+    /// Results of the games which ended while the players were still in contact.
+    contact_results: ResultCounter,
+    /// Results of the games which ended in the race.
+    race_results: ResultCounter,
+    /// `game_lengths[n]` is the number of games which ended after `n` half moves.
+    game_lengths: Vec<u64>,
+    /// Checkers hit by `x` and by `o`, summed over all games.
+    hits: [u64; 2],
+}
+
+impl RolloutReport {
+    pub(super) fn add(&mut self, game: &GameRecord) {
+        let results = match game.final_phase {
+            OngoingPhase::Contact => &mut self.contact_results,
+            OngoingPhase::Race => &mut self.race_results,
+        };
+        add_outcome(results, game.outcome.clone(), 1);
+        if self.game_lengths.len() <= game.half_moves {
+            self.game_lengths.resize(game.half_moves + 1, 0);
+        }
+        self.game_lengths[game.half_moves] += 1;
+        self.hits[0] += game.hits[0] as u64;
+        self.hits[1] += game.hits[1] as u64;
+    }
+
+    pub fn combine(mut self, other: RolloutReport) -> Self {
+        self.contact_results = self.contact_results.combine(&other.contact_results);
+        self.race_results = self.race_results.combine(&other.race_results);
+        if self.game_lengths.len() < other.game_lengths.len() {
+            self.game_lengths.resize(other.game_lengths.len(), 0);
+        }
+        for (length, games) in other.game_lengths.iter().enumerate() {
+            self.game_lengths[length] += games;
+        }
+        self.hits[0] += other.hits[0];
+        self.hits[1] += other.hits[1];
+        self
+    }
+
+    /// Results of all games, the same as [crate::rollout::RolloutEvaluator::results_with_seed].
+    pub fn results(&self) -> ResultCounter {
+        self.contact_results.clone().combine(&self.race_results)
+    }
+
+    /// Results of the games which ended in `phase`.
+    pub fn results_by_phase(&self, phase: OngoingPhase) -> &ResultCounter {
+        match phase {
+            OngoingPhase::Contact => &self.contact_results,
+            OngoingPhase::Race => &self.race_results,
+        }
+    }
+
+    pub fn games(&self) -> u64 {
+        self.contact_results.sum() + self.race_results.sum()
+    }
+
+    /// Share of gammons and backgammons, won or lost, among the games which ended in `phase`.
+    ///
+    /// `None` if no game ended in `phase`.
+    pub fn gammon_rate(&self, phase: OngoingPhase) -> Option<f64> {
+        let results = self.results_by_phase(phase);
+        let gammons = results.num_of(WinGammon) + results.num_of(LoseGammon);
+        (results.sum() > 0).then(|| gammons as f64 / results.sum() as f64)
+    }
+
+    /// `game_lengths()[n]` is the number of games which ended after `n` half moves.
+    pub fn game_lengths(&self) -> &[u64] {
+        &self.game_lengths
+    }
+
+    /// Average number of half moves per game.
+    pub fn average_length(&self) -> f64 {
+        let half_moves: u64 = self
+            .game_lengths
+            .iter()
+            .enumerate()
+            .map(|(length, games)| length as u64 * games)
+            .sum();
+        half_moves as f64 / self.games() as f64
+    }
+
+    /// Checkers hit per game by `x` and by `o`.
+    pub fn hits_per_game(&self) -> [f64; 2] {
+        self.hits.map(|hits| hits as f64 / self.games() as f64)
+    }
+}
+
+impl fmt::Display for RolloutReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rate = |phase| match self.gammon_rate(phase) {
+            Some(rate) => format!("{:.2}%", 100.0 * rate),
+            None => "-".to_string(),
+        };
+        let [x_hits, o_hits] = self.hits_per_game();
+        write!(
+            f,
+            "Games: {}, average length: {:.1} half moves, hits per game: x {:.2}, o {:.2}, \
+            gammons in contact: {}, gammons in race: {}",
+            self.games(),
+            self.average_length(),
+            x_hits,
+            o_hits,
+            rate(OngoingPhase::Contact),
+            rate(OngoingPhase::Race),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rollout::report::{GameRecord, RolloutReport};
+    use crate::rollout::RolloutEvaluator;
+    use engine::pos;
+    use engine::position::GameResult::{LoseGammon, WinNormal};
+    use engine::position::OngoingPhase::{Contact, Race};
+
+    fn game(half_moves: usize, hits: [u32; 2], gammon: bool) -> GameRecord {
+        GameRecord {
+            outcome: (if gammon { LoseGammon } else { WinNormal }, false),
+            half_moves,
+            hits,
+            final_phase: if hits == [0, 0] { Race } else { Contact },
+        }
+    }
+
+    #[test]
+    fn statistics_of_games() {
+        // Given
+        let mut report = RolloutReport::default();
+        report.add(&game(10, [1, 0], false));
+        report.add(&game(20, [2, 1], true));
+        let mut other = RolloutReport::default();
+        other.add(&game(30, [0, 0], false));
+        // When
+        let report = report.combine(other);
+        // Then
+        assert_eq!(report.games(), 3);
+        assert_eq!(report.average_length(), 20.0);
+        assert_eq!(report.game_lengths().len(), 31);
+        assert_eq!(report.game_lengths()[20], 1);
+        assert_eq!(report.hits_per_game(), [1.0, 1.0 / 3.0]);
+        assert_eq!(report.gammon_rate(Contact), Some(0.5));
+        assert_eq!(report.gammon_rate(Race), Some(0.0));
+        assert_eq!(report.results().num_of(LoseGammon), 1);
+    }
+
+    #[test]
+    fn report_has_same_results_as_rollout() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let pos = pos!(x 6:1; o 19:1);
+        // When
+        let report = rollout_eval.report_with_seed(&pos, 42, 1);
+        // Then
+        // See `correct_results_after_first_or_second_half_move` in the rollout tests.
+        assert_eq!(
+            report.results(),
+            rollout_eval.results_with_seed(&pos, 42, 1)
+        );
+        assert_eq!(report.game_lengths(), &[0, 972, 243, 81]);
+        assert_eq!(report.hits_per_game(), [0.0, 0.0]);
+        assert_eq!(report.gammon_rate(Race), Some(0.0));
+        assert_eq!(report.gammon_rate(Contact), None);
+    }
+
+    #[test]
+    fn hits_are_counted() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        // With 11 the checker of `x` has to hit on its way off.
+        let pos = pos!(x 2:1; o 1:1);
+        // When
+        let report = rollout_eval.report_with_seed(&pos, 42, 1);
+        // Then
+        assert_eq!(report.games(), 1296);
+        assert_eq!(report.game_lengths(), &[0, 1296]);
+        assert!(report.hits_per_game()[0] >= 36.0 / 1296.0);
+        assert_eq!(report.hits_per_game()[1], 0.0);
+        assert_eq!(report.results_by_phase(Contact).num_of(WinNormal), 1296);
+    }
+}
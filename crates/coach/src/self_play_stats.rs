@@ -0,0 +1,155 @@
+use engine::position::GameResult;
+use engine::position::GameResult::{LoseGammon, WinGammon};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Aggregated outcomes of all games played during one self play run.
+///
+/// Shifts in these numbers between net generations are early indicators of training problems,
+/// for example a net which suddenly plays for gammons far too often.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SelfPlayStats {
+    games: u32,
+    gammons: u32,
+    half_moves: u32,
+    /// Half moves in which a checker on the bar could not enter.
+    dances: u32,
+}
+
+impl SelfPlayStats {
+    pub fn add_half_move(&mut self, dance: bool) {
+        self.half_moves += 1;
+        if dance {
+            self.dances += 1;
+        }
+    }
+
+    pub fn add_game(&mut self, result: GameResult) {
+        self.games += 1;
+        if matches!(result, WinGammon | LoseGammon) {
+            self.gammons += 1;
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    pub fn gammon_rate(&self) -> f32 {
+        self.gammons as f32 / self.games as f32
+    }
+
+    /// Average number of half moves per game.
+    pub fn average_length(&self) -> f32 {
+        self.half_moves as f32 / self.games as f32
+    }
+
+    /// Fraction of half moves in which the player on turn danced.
+    pub fn dance_rate(&self) -> f32 {
+        self.dances as f32 / self.half_moves as f32
+    }
+
+    pub fn record(&self, run: &str) -> SelfPlayRecord {
+        SelfPlayRecord {
+            run: run.to_owned(),
+            games: self.games,
+            gammon_rate: self.gammon_rate(),
+            average_length: self.average_length(),
+            dance_rate: self.dance_rate(),
+        }
+    }
+}
+
+/// One line in the CSV file in which the statistics of all self play runs are persisted.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SelfPlayRecord {
+    /// Name of the run, typically containing the name of the neural nets.
+    pub run: String,
+    pub games: u32,
+    pub gammon_rate: f32,
+    pub average_length: f32,
+    pub dance_rate: f32,
+}
+
+impl SelfPlayRecord {
+    /// Appends this record to the CSV file at `path`. The header is written if the file is new.
+    pub fn append_to(&self, path: &str) -> std::io::Result<()> {
+        let is_new = !std::path::Path::new(path).exists();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let mut csv_writer = csv::WriterBuilder::new()
+            .has_headers(is_new)
+            .from_writer(file);
+        csv_writer.serialize(self)?;
+        csv_writer.flush()
+    }
+
+    pub fn read_all(path: &str) -> std::io::Result<Vec<SelfPlayRecord>> {
+        let records = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_path(path)?
+            .deserialize()
+            .collect::<Result<_, _>>()?;
+        Ok(records)
+    }
+}
+
+impl fmt::Display for SelfPlayRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<30} {:>8} {:>7.2}% {:>7.1} {:>7.2}%",
+            self.run,
+            self.games,
+            100.0 * self.gammon_rate,
+            self.average_length,
+            100.0 * self.dance_rate,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::self_play_stats::{SelfPlayRecord, SelfPlayStats};
+    use engine::position::GameResult::{LoseGammon, WinNormal};
+
+    #[test]
+    fn rates() {
+        // Given
+        let mut stats = SelfPlayStats::default();
+        // When
+        for dance in [false, true, false, false, false, true, false, false] {
+            stats.add_half_move(dance);
+        }
+        stats.add_game(LoseGammon);
+        stats.add_game(WinNormal);
+        // Then
+        let record = stats.record("contact-42");
+        assert_eq!(record.run, "contact-42");
+        assert_eq!(record.games, 2);
+        assert_eq!(record.gammon_rate, 0.5);
+        assert_eq!(record.average_length, 4.0);
+        assert_eq!(record.dance_rate, 0.25);
+    }
+
+    #[test]
+    fn append_and_read() {
+        // Given
+        let path = std::env::temp_dir().join("wildbg-self-play-stats-test.csv");
+        let path = path.to_str().unwrap();
+        _ = std::fs::remove_file(path);
+        let mut stats = SelfPlayStats::default();
+        stats.add_half_move(false);
+        stats.add_game(WinNormal);
+        // When
+        stats.record("first").append_to(path).unwrap();
+        stats.record("second").append_to(path).unwrap();
+        // Then
+        let records = SelfPlayRecord::read_all(path).unwrap();
+        _ = std::fs::remove_file(path);
+        assert_eq!(records, vec![stats.record("first"), stats.record("second")]);
+    }
+}
@@ -0,0 +1,496 @@
+use crate::mat::{MatAction, MatGame, MatMatch};
+use crate::match_analysis::{Severity, Thresholds};
+use engine::dice::Dice;
+use engine::evaluator::Evaluator;
+use engine::position::X_BAR;
+use logic::analysis::analyze_move;
+use logic::game_state::Player;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Properties written by wildbg for skill marks. They are replaced when annotating a game again.
+const SKILL_PROPERTIES: [&str; 2] = ["BM", "DO"];
+
+/// A property like `PW[Alice]` or `MI[length:7][game:0]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// A single action together with all other properties of the node, for example GNUBG's analysis.
+#[derive(Debug, PartialEq)]
+pub struct SgfNode {
+    pub player: Player,
+    pub action: MatAction,
+    pub properties: Vec<Property>,
+}
+
+/// One game of a GNUBG SGF file. A file contains one game tree for each game of a match.
+///
+/// Properties unknown to wildbg are kept, so that a game can be read and written again
+/// without losing GNUBG's analysis.
+#[derive(Debug, PartialEq)]
+pub struct SgfGame {
+    pub root: Vec<Property>,
+    pub nodes: Vec<SgfNode>,
+}
+
+impl SgfGame {
+    fn root_values(&self, name: &str) -> Option<&[String]> {
+        self.root
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| property.values.as_slice())
+    }
+
+    /// Value of a `MI` entry like `length:7`.
+    fn match_info(&self, key: &str) -> Option<u32> {
+        self.root_values("MI")?.iter().find_map(|value| {
+            let (k, v) = value.split_once(':')?;
+            (k == key).then(|| v.parse().ok())?
+        })
+    }
+
+    /// Winner and points from the `RE` property, for example `W+2` or `B+1R`.
+    fn result(&self) -> Option<(Player, u32)> {
+        let result = self.root_values("RE")?.first()?;
+        let (color, points) = result.split_once('+')?;
+        let points = points.trim_end_matches('R').parse().ok()?;
+        Some((player_from_color(color)?, points))
+    }
+
+    /// Marks checker plays of both players with GNUBG's skill properties, based on the equity loss
+    /// and the default [Thresholds].
+    ///
+    /// Moves are compared by cubeless money equity. Existing skill marks are replaced.
+    pub fn annotate<T: Evaluator>(&mut self, evaluator: &T) -> Result<(), String> {
+        let game = MatGame {
+            score: [0, 0],
+            actions: self
+                .nodes
+                .iter()
+                .map(|node| (node.player, node.action.clone()))
+                .collect(),
+        };
+        let mut records = game.move_records()?.into_iter();
+        for node in &mut self.nodes {
+            if !matches!(node.action, MatAction::Move { .. }) {
+                continue;
+            }
+            let (_, record) = records.next().expect("One record for each move.");
+            let analysis = analyze_move(evaluator, &record, |p| p.equity())?;
+            node.properties
+                .retain(|property| !SKILL_PROPERTIES.contains(&property.name.as_str()));
+            let skill = match Thresholds::default().classify(analysis.equity_loss) {
+                Some(Severity::DoubleBlunder) => Some(("BM", "2")),
+                Some(Severity::Blunder) => Some(("BM", "1")),
+                Some(Severity::Error) => Some(("DO", "")),
+                Some(Severity::Dubious) | None => None,
+            };
+            if let Some((name, value)) = skill {
+                node.properties.push(Property {
+                    name: name.to_string(),
+                    values: vec![value.to_string()],
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn player_from_color(color: &str) -> Option<Player> {
+    match color {
+        "W" => Some(Player::One),
+        "B" => Some(Player::Two),
+        _ => None,
+    }
+}
+
+fn color(player: Player) -> char {
+    match player {
+        Player::One => 'W',
+        Player::Two => 'B',
+    }
+}
+
+/// Converts all games of an SGF file into a match, so it can be replayed or analyzed.
+pub fn to_mat_match(games: &[SgfGame]) -> Result<MatMatch, String> {
+    let first = games.first().ok_or("The file contains no game.")?;
+    let name = |property: &str| {
+        first
+            .root_values(property)
+            .and_then(|values| values.first().cloned())
+            .unwrap_or_default()
+    };
+    let length = first.match_info("length").filter(|length| *length > 0);
+    let mut mat_match = MatMatch {
+        length,
+        players: [name("PW"), name("PB")],
+        games: Vec::new(),
+    };
+    for game in games {
+        let mut actions: Vec<(Player, MatAction)> = game
+            .nodes
+            .iter()
+            .map(|node| (node.player, node.action.clone()))
+            .collect();
+        if let Some((winner, points)) = game.result() {
+            actions.push((winner, MatAction::Wins(points)));
+        }
+        mat_match.games.push(MatGame {
+            score: [
+                game.match_info("ws").unwrap_or_default(),
+                game.match_info("bs").unwrap_or_default(),
+            ],
+            actions,
+        });
+    }
+    Ok(mat_match)
+}
+
+/// Converts a match into the game trees of an SGF file.
+pub fn from_mat_match(mat_match: &MatMatch) -> Vec<SgfGame> {
+    let property = |name: &str, values: Vec<String>| Property {
+        name: name.to_string(),
+        values,
+    };
+    mat_match
+        .games
+        .iter()
+        .enumerate()
+        .map(|(index, game)| {
+            let mut root = vec![
+                property("FF", vec!["4".to_string()]),
+                property("GM", vec!["6".to_string()]),
+                property("CA", vec!["UTF-8".to_string()]),
+                property("AP", vec!["wildbg".to_string()]),
+                property(
+                    "MI",
+                    vec![
+                        format!("length:{}", mat_match.length.unwrap_or(0)),
+                        format!("game:{}", index),
+                        format!("ws:{}", game.score[0]),
+                        format!("bs:{}", game.score[1]),
+                    ],
+                ),
+                property("PW", vec![mat_match.players[0].clone()]),
+                property("PB", vec![mat_match.players[1].clone()]),
+            ];
+            if let Some((winner, points)) = game.winner() {
+                root.push(property(
+                    "RE",
+                    vec![format!("{}+{}", color(winner), points)],
+                ));
+            }
+            let nodes = game
+                .actions
+                .iter()
+                .filter(|(_, action)| !matches!(action, MatAction::Wins(_)))
+                .map(|(player, action)| SgfNode {
+                    player: *player,
+                    action: action.clone(),
+                    properties: Vec::new(),
+                })
+                .collect();
+            SgfGame { root, nodes }
+        })
+        .collect()
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for value in &self.values {
+            write!(f, "[{}]", value.replace('\\', "\\\\").replace(']', "\\]"))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SgfGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "(;")?;
+        for property in &self.root {
+            write!(f, "{}", property)?;
+        }
+        writeln!(f)?;
+        for node in &self.nodes {
+            write!(
+                f,
+                ";{}[{}]",
+                color(node.player),
+                format_action(node.player, &node.action)
+            )?;
+            for property in &node.properties {
+                write!(f, "{}", property)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, ")")
+    }
+}
+
+/// Writes all games of a match, as GNUBG does.
+pub fn write(games: &[SgfGame]) -> String {
+    games.iter().map(|game| game.to_string()).collect()
+}
+
+/// For example `52lqgq` for 5-2 with `13/8 13/11` by [Player::Two].
+fn format_action(player: Player, action: &MatAction) -> String {
+    match action {
+        MatAction::Move {
+            dice,
+            checker_moves,
+        } => {
+            let (die1, die2) = match dice {
+                Dice::Mixed(mixed) => (mixed.big(), mixed.small()),
+                Dice::Double(die) => (*die, *die),
+            };
+            let mut text = format!("{}{}", die1, die2);
+            for (from, to) in checker_moves {
+                text.push(point_to_char(player, *from));
+                text.push(point_to_char(player, *to));
+            }
+            text
+        }
+        MatAction::Double => "double".to_string(),
+        MatAction::Take => "take".to_string(),
+        MatAction::Drop => "drop".to_string(),
+        MatAction::Wins(_) => unreachable!("Results are stored in the root node."),
+    }
+}
+
+/// GNUBG uses `a` to `x` for the points, in opposite directions for both players.
+/// `y` is the bar and `z` is off.
+fn point_to_char(player: Player, pip: usize) -> char {
+    match (pip, player) {
+        (X_BAR, _) => 'y',
+        (0, _) => 'z',
+        (pip, Player::One) => (b'x' - (pip as u8 - 1)) as char,
+        (pip, Player::Two) => (b'a' + (pip as u8 - 1)) as char,
+    }
+}
+
+fn char_to_point(player: Player, c: char) -> Result<usize, String> {
+    match (c, player) {
+        ('y', _) => Ok(X_BAR),
+        ('z', _) => Ok(0),
+        ('a'..='x', Player::One) => Ok((b'x' - c as u8) as usize + 1),
+        ('a'..='x', Player::Two) => Ok((c as u8 - b'a') as usize + 1),
+        _ => Err(format!("Invalid point '{}'.", c)),
+    }
+}
+
+fn parse_action(player: Player, value: &str) -> Result<Option<MatAction>, String> {
+    let action = match value {
+        "double" => MatAction::Double,
+        "take" => MatAction::Take,
+        "drop" => MatAction::Drop,
+        _ => {
+            let mut chars = value.chars();
+            let dice = match (chars.next(), chars.next()) {
+                (Some(die1), Some(die2)) => match (die1.to_digit(10), die2.to_digit(10)) {
+                    (Some(die1), Some(die2)) => {
+                        Dice::try_from((die1 as usize, die2 as usize)).map_err(|e| e.to_string())?
+                    }
+                    // Other actions like `beaver` or resignations are ignored.
+                    _ => return Ok(None),
+                },
+                _ => return Ok(None),
+            };
+            let points: Vec<char> = chars.collect();
+            if !points.len().is_multiple_of(2) {
+                return Err(format!("Invalid move '{}'.", value));
+            }
+            let checker_moves = points
+                .chunks_exact(2)
+                .map(|pair| {
+                    Ok((
+                        char_to_point(player, pair[0])?,
+                        char_to_point(player, pair[1])?,
+                    ))
+                })
+                .collect::<Result<_, String>>()?;
+            MatAction::Move {
+                dice,
+                checker_moves,
+            }
+        }
+    };
+    Ok(Some(action))
+}
+
+/// Parses the content of a GNUBG SGF file, one [SgfGame] for each game tree.
+///
+/// Variations are not supported, only the main line of each game is read.
+pub fn parse(content: &str) -> Result<Vec<SgfGame>, String> {
+    let mut games = Vec::new();
+    let mut nodes: Vec<Vec<Property>> = Vec::new();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => nodes.clear(),
+            ';' => nodes.push(Vec::new()),
+            ')' if !nodes.is_empty() => {
+                games.push(game_from_nodes(std::mem::take(&mut nodes))?);
+            }
+            c if c.is_ascii_uppercase() => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_uppercase()) {
+                    name.push(c);
+                }
+                let mut values = Vec::new();
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                while chars.next_if_eq(&'[').is_some() {
+                    let mut value = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('\\') => value.extend(chars.next()),
+                            Some(']') => break,
+                            Some(c) => value.push(c),
+                            None => return Err("Unterminated property value.".to_string()),
+                        }
+                    }
+                    values.push(value);
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                }
+                nodes
+                    .last_mut()
+                    .ok_or("Property outside of a node.")?
+                    .push(Property { name, values });
+            }
+            _ => {}
+        }
+    }
+    Ok(games)
+}
+
+fn game_from_nodes(nodes: Vec<Vec<Property>>) -> Result<SgfGame, String> {
+    let mut nodes = nodes.into_iter();
+    let root = nodes.next().unwrap_or_default();
+    let mut sgf_nodes = Vec::new();
+    for mut properties in nodes {
+        let index = properties
+            .iter()
+            .position(|property| property.name == "W" || property.name == "B");
+        let Some(index) = index else {
+            continue;
+        };
+        let property = properties.remove(index);
+        let player = player_from_color(&property.name).expect("Checked above.");
+        let value = property.values.first().map_or("", String::as_str);
+        if let Some(action) = parse_action(player, value)? {
+            sgf_nodes.push(SgfNode {
+                player,
+                action,
+                properties,
+            });
+        }
+    }
+    Ok(SgfGame {
+        root,
+        nodes: sgf_nodes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat;
+    use crate::mat::MatAction;
+    use crate::sgf::{from_mat_match, parse, to_mat_match, write};
+    use engine::dice::Dice;
+    use engine::evaluator::Evaluator;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+    use logic::game_state::Player;
+
+    const SGF: &str = "(;FF[4]GM[6]CA[UTF-8]AP[GNU Backgammon:1.06.002]MI[length:5][game:0][ws:0][bs:0]PW[Alice]PB[Bob]RE[W+1]
+;W[31adab]A[some analysis \\] by GNUBG]
+;B[64xrmi]
+;W[double]
+;B[drop])
+";
+
+    #[test]
+    fn parse_gnubg_game() {
+        // When
+        let games = parse(SGF).unwrap();
+        // Then
+        assert_eq!(games.len(), 1);
+        let game = &games[0];
+        assert_eq!(game.nodes.len(), 4);
+        assert_eq!(game.nodes[0].player, Player::One);
+        assert_eq!(
+            game.nodes[0].action,
+            MatAction::Move {
+                dice: Dice::new(3, 1),
+                checker_moves: vec![(24, 21), (24, 23)],
+            }
+        );
+        assert_eq!(
+            game.nodes[1].action,
+            MatAction::Move {
+                dice: Dice::new(6, 4),
+                checker_moves: vec![(24, 18), (13, 9)],
+            }
+        );
+        assert_eq!(
+            game.nodes[0].properties[0].values[0],
+            "some analysis ] by GNUBG"
+        );
+        assert_eq!(game.nodes[3].action, MatAction::Drop);
+    }
+
+    #[test]
+    fn round_trip_keeps_analysis() {
+        let games = parse(SGF).unwrap();
+        let written = write(&games);
+        assert_eq!(parse(&written).unwrap(), games);
+        assert!(written.contains(";W[31adab]A[some analysis \\] by GNUBG]\n"));
+    }
+
+    #[test]
+    fn convert_from_and_to_mat() {
+        // Given
+        let mat_text = " 5 point match\n\n Game 1\n Alice : 0      Bob : 0\n  1) 31: 8/5 6/5      64: 24/18 13/9\n  2)  Doubles => 2     Drops\n      Wins 1 point\n";
+        let mat_match = mat::parse(mat_text).unwrap();
+        // When
+        let games = from_mat_match(&mat_match);
+        // Then
+        assert_eq!(to_mat_match(&games).unwrap(), mat_match);
+        let written = write(&games);
+        assert!(written.contains("MI[length:5][game:0][ws:0][bs:0]PW[Alice]PB[Bob]RE[W+1]"));
+        assert!(written.contains(";W[31qtst]\n;B[64xrmi]\n"));
+        assert_eq!(to_mat_match(&parse(&written).unwrap()).unwrap(), mat_match);
+    }
+
+    /// Test double. The more checkers the player who moved has on the 5 point, the better.
+    /// Positions are evaluated from the point of view of the opponent, so that's pip 20 of `o`.
+    struct EvaluatorFake {}
+    impl Evaluator for EvaluatorFake {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let o_on_20 = -pos.pip(20) as f32;
+            Probabilities {
+                win_normal: 0.5 - o_on_20 * 0.05,
+                win_gammon: 0.0,
+                lose_normal: 0.5 + o_on_20 * 0.05,
+                lose_gammon: 0.0,
+            }
+        }
+    }
+
+    #[test]
+    fn annotate_bad_moves() {
+        // Given
+        let mut games = parse(SGF).unwrap();
+        // When
+        games[0].annotate(&EvaluatorFake {}).unwrap();
+        // Then
+        let properties = &games[0].nodes[0].properties;
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties[1].name, "BM");
+        assert_eq!(properties[1].values, vec!["2".to_string()]);
+        assert!(games[0].nodes[1].properties.is_empty());
+    }
+}
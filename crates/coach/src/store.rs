@@ -0,0 +1,206 @@
+use crate::data::PositionRecord;
+use engine::position::{GamePhase, OngoingPhase, Position};
+use engine::probabilities::Probabilities;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// Store of labeled positions, meant to replace huge CSV files of training data.
+///
+/// The store is an SQLite database, `training-data/store.sqlite` by default. Each position is
+/// stored once with its game phase, so inserts are incremental and never create duplicates:
+/// inserting a known position replaces its probabilities.
+pub struct PositionStore {
+    connection: Connection,
+}
+
+impl PositionStore {
+    /// Opens the store at `path` and creates it if it doesn't exist yet.
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let connection = Connection::open(path).map_err(sqlite_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS positions (
+                    position_id TEXT PRIMARY KEY NOT NULL,
+                    phase TEXT NOT NULL,
+                    win_normal REAL NOT NULL,
+                    win_gammon REAL NOT NULL,
+                    lose_normal REAL NOT NULL,
+                    lose_gammon REAL NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS positions_by_phase ON positions (phase);",
+            )
+            .map_err(sqlite_error)?;
+        Ok(Self { connection })
+    }
+
+    /// Returns `true` if the position was new and `false` if existing probabilities were replaced.
+    ///
+    /// Positions where the game is already over can't be stored.
+    pub fn insert(
+        &mut self,
+        position: &Position,
+        probabilities: &Probabilities,
+    ) -> std::io::Result<bool> {
+        let phase = match position.game_phase() {
+            GamePhase::Ongoing(phase) => phase,
+            GamePhase::GameOver(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Positions where the game is over can't be stored.",
+                ))
+            }
+        };
+        let is_new = !self.contains(position)?;
+        self.connection
+            .execute(
+                "INSERT INTO positions VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT (position_id) DO UPDATE SET
+                    win_normal = excluded.win_normal,
+                    win_gammon = excluded.win_gammon,
+                    lose_normal = excluded.lose_normal,
+                    lose_gammon = excluded.lose_gammon",
+                params![
+                    position.position_id(),
+                    phase_name(&phase),
+                    probabilities.win_normal,
+                    probabilities.win_gammon,
+                    probabilities.lose_normal,
+                    probabilities.lose_gammon,
+                ],
+            )
+            .map_err(sqlite_error)?;
+        Ok(is_new)
+    }
+
+    pub fn get(&self, position: &Position) -> std::io::Result<Option<Probabilities>> {
+        self.connection
+            .query_row(
+                "SELECT win_normal, win_gammon, lose_normal, lose_gammon FROM positions
+                WHERE position_id = ?1",
+                [position.position_id()],
+                |row| {
+                    Ok(Probabilities {
+                        win_normal: row.get(0)?,
+                        win_gammon: row.get(1)?,
+                        lose_normal: row.get(2)?,
+                        lose_gammon: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(sqlite_error)
+    }
+
+    pub fn contains(&self, position: &Position) -> std::io::Result<bool> {
+        self.connection
+            .query_row(
+                "SELECT 1 FROM positions WHERE position_id = ?1",
+                [position.position_id()],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|row| row.is_some())
+            .map_err(sqlite_error)
+    }
+
+    /// Number of positions of the given phase.
+    pub fn len(&self, phase: &OngoingPhase) -> std::io::Result<usize> {
+        self.connection
+            .query_row(
+                "SELECT COUNT(*) FROM positions WHERE phase = ?1",
+                [phase_name(phase)],
+                |row| row.get(0),
+            )
+            .map_err(sqlite_error)
+    }
+
+    /// All positions of the given phase in the order they were first inserted.
+    pub fn records(&self, phase: &OngoingPhase) -> std::io::Result<Vec<PositionRecord>> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT position_id, win_normal, win_gammon, lose_normal, lose_gammon
+                FROM positions WHERE phase = ?1 ORDER BY rowid",
+            )
+            .map_err(sqlite_error)?;
+        let rows = statement
+            .query_map([phase_name(phase)], |row| {
+                let position_id: String = row.get(0)?;
+                let probabilities = Probabilities {
+                    win_normal: row.get(1)?,
+                    win_gammon: row.get(2)?,
+                    lose_normal: row.get(3)?,
+                    lose_gammon: row.get(4)?,
+                };
+                Ok((position_id, probabilities))
+            })
+            .map_err(sqlite_error)?;
+        rows.map(|row| {
+            let (position_id, probabilities) = row.map_err(sqlite_error)?;
+            let position = Position::try_from_id(&position_id)
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+            Ok(PositionRecord::new(&position, &probabilities))
+        })
+        .collect()
+    }
+}
+
+fn phase_name(phase: &OngoingPhase) -> &'static str {
+    match phase {
+        OngoingPhase::Contact => "contact",
+        OngoingPhase::Race => "race",
+    }
+}
+
+fn sqlite_error(error: rusqlite::Error) -> Error {
+    Error::other(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::store::PositionStore;
+    use engine::pos;
+    use engine::position::OngoingPhase;
+    use engine::probabilities::Probabilities;
+
+    fn probabilities(win_normal: f32) -> Probabilities {
+        Probabilities {
+            win_normal,
+            win_gammon: 0.0,
+            lose_normal: 1.0 - win_normal,
+            lose_gammon: 0.0,
+        }
+    }
+
+    #[test]
+    fn insert_dedup_and_reopen() {
+        // Given
+        let directory = std::env::temp_dir().join(format!("wildbg-store-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&directory);
+        let path = directory.join("store.sqlite");
+        let race = pos!(x 1:1; o 24:1);
+        let contact = pos!(x 20:1; o 1:1);
+        let mut store = PositionStore::open(&path).unwrap();
+        // When
+        let new_race = store.insert(&race, &probabilities(0.5)).unwrap();
+        let new_contact = store.insert(&contact, &probabilities(0.6)).unwrap();
+        let replaced = store.insert(&race, &probabilities(0.7)).unwrap();
+        drop(store);
+        let store = PositionStore::open(&path).unwrap();
+        // Then
+        assert!(new_race && new_contact && !replaced);
+        assert_eq!(store.len(&OngoingPhase::Race).unwrap(), 1);
+        assert_eq!(store.len(&OngoingPhase::Contact).unwrap(), 1);
+        assert!(store.contains(&contact).unwrap());
+        assert_eq!(store.get(&race).unwrap(), Some(probabilities(0.7)));
+        let records = store.records(&OngoingPhase::Contact).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].position(), contact);
+        assert_eq!(records[0].probabilities(), probabilities(0.6));
+        _ = std::fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::rollout::RolloutEvaluator;
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use serde::{Deserialize, Serialize};
+
+/// A rollout together with everything needed to repeat it: the seed and a hash of the neural nets.
+///
+/// The number of games and the way dice are generated are fixed in [RolloutEvaluator], so there are no further settings.
+/// The probabilities are stored with full `f32` precision, so a replay can be compared bit for bit.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StoredRollout {
+    #[serde(rename = "position_id")]
+    position: Position,
+    seed: u64,
+    model_hash: String,
+    win_normal: f32,
+    win_gammon: f32,
+    lose_normal: f32,
+    lose_gammon: f32,
+}
+
+/// Result of replaying a [StoredRollout].
+#[derive(Debug, PartialEq)]
+pub enum Verification {
+    /// The replay returned exactly the same probabilities.
+    Identical,
+    /// The rollout was done with other neural nets, so it can't be replayed.
+    DifferentModel,
+    Mismatch {
+        stored: Probabilities,
+        replayed: Probabilities,
+    },
+}
+
+impl StoredRollout {
+    /// Rolls out `position` with the given `seed` and keeps the result.
+    pub fn roll_out<T: Evaluator + Sync>(
+        evaluator: T,
+        position: &Position,
+        seed: u64,
+        model_hash: &str,
+    ) -> Self {
+        let probabilities =
+            RolloutEvaluator::with_evaluator_and_seed(evaluator, seed).eval(position);
+        Self {
+            position: *position,
+            seed,
+            model_hash: model_hash.to_string(),
+            win_normal: probabilities.win_normal,
+            win_gammon: probabilities.win_gammon,
+            lose_normal: probabilities.lose_normal,
+            lose_gammon: probabilities.lose_gammon,
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn probabilities(&self) -> Probabilities {
+        Probabilities {
+            win_normal: self.win_normal,
+            win_gammon: self.win_gammon,
+            lose_normal: self.lose_normal,
+            lose_gammon: self.lose_gammon,
+        }
+    }
+
+    /// Repeats the rollout with the stored seed and compares the result bit for bit.
+    ///
+    /// The seed of `evaluator` is ignored.
+    /// `model_hash` must be the hash of the nets used by `evaluator`, see [model_hash].
+    pub fn verify<T: Evaluator + Sync>(
+        &self,
+        evaluator: &RolloutEvaluator<T>,
+        model_hash: &str,
+    ) -> Verification {
+        if self.model_hash != model_hash {
+            return Verification::DifferentModel;
+        }
+        let replayed = evaluator.eval_with_seed(&self.position, self.seed);
+        let stored = self.probabilities();
+        let identical = [
+            (stored.win_normal, replayed.win_normal),
+            (stored.win_gammon, replayed.win_gammon),
+            (stored.lose_normal, replayed.lose_normal),
+            (stored.lose_gammon, replayed.lose_gammon),
+        ]
+        .iter()
+        .all(|(a, b)| a.to_bits() == b.to_bits());
+        if identical {
+            Verification::Identical
+        } else {
+            Verification::Mismatch { stored, replayed }
+        }
+    }
+}
+
+/// Hash over the content of all given model files, in this order.
+///
+/// This is meant to detect changed nets, it's not a cryptographic hash. We use 64 bit FNV-1a,
+/// because the hash is stored next to the rollouts and must not change with the Rust toolchain.
+pub fn model_hash(paths: &[&str]) -> std::io::Result<String> {
+    let mut hash = FNV_OFFSET_BASIS;
+    for path in paths {
+        hash = fnv_1a(hash, &std::fs::read(path)?);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv_1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rollout::RolloutEvaluator;
+    use crate::stored_rollout::{fnv_1a, StoredRollout, Verification, FNV_OFFSET_BASIS};
+    use engine::evaluator::Evaluator;
+    use engine::pos;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+
+    /// Deterministic, prefers positions where the opponent has few checkers on the 1 point.
+    struct EvaluatorFake {}
+    impl Evaluator for EvaluatorFake {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let win_normal = 0.1 * pos.pip(1).unsigned_abs() as f32;
+            Probabilities {
+                win_normal,
+                lose_normal: 1.0 - win_normal,
+                ..Default::default()
+            }
+        }
+    }
+
+    fn rollout_evaluator() -> RolloutEvaluator<EvaluatorFake> {
+        RolloutEvaluator::with_evaluator_and_seed(EvaluatorFake {}, 0)
+    }
+
+    #[test]
+    fn fnv_1a_test_vectors() {
+        assert_eq!(fnv_1a(FNV_OFFSET_BASIS, b""), 0xcbf29ce484222325);
+        assert_eq!(fnv_1a(FNV_OFFSET_BASIS, b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv_1a(FNV_OFFSET_BASIS, b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn replay_is_identical() {
+        let position = pos!(x 6:2, 3:1; o 19:2, 22:1);
+        let stored = StoredRollout::roll_out(EvaluatorFake {}, &position, 7, "abc");
+        assert_eq!(stored.position(), position);
+        assert_eq!(
+            stored.verify(&rollout_evaluator(), "abc"),
+            Verification::Identical
+        );
+        assert_eq!(
+            stored.verify(&rollout_evaluator(), "def"),
+            Verification::DifferentModel
+        );
+    }
+
+    #[test]
+    fn changed_result_is_detected() {
+        let position = pos!(x 6:2, 3:1; o 19:2, 22:1);
+        let mut stored = StoredRollout::roll_out(EvaluatorFake {}, &position, 7, "abc");
+        let original = stored.probabilities();
+        stored.win_normal = f32::from_bits(stored.win_normal.to_bits() + 1);
+        assert_eq!(
+            stored.verify(&rollout_evaluator(), "abc"),
+            Verification::Mismatch {
+                stored: stored.probabilities(),
+                replayed: original,
+            }
+        );
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_all_bits() {
+        let position = pos!(x 6:2, 3:1; o 19:2, 22:1);
+        let stored = StoredRollout::roll_out(EvaluatorFake {}, &position, 7, "abc");
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.serialize(&stored).unwrap();
+        let bytes = writer.into_inner().unwrap();
+        let read: StoredRollout = csv::Reader::from_reader(bytes.as_slice())
+            .deserialize()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            read.verify(&rollout_evaluator(), "abc"),
+            Verification::Identical
+        );
+    }
+}
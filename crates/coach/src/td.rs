@@ -0,0 +1,243 @@
+use crate::self_play_stats::SelfPlayStats;
+use crate::training::{Loss, Mlp, Sample, Trainer};
+use engine::dice_gen::DiceGen;
+use engine::evaluator::Evaluator;
+use engine::inputs::InputsGen;
+use engine::position::GameState::{GameOver, Ongoing};
+use engine::position::{GameResult, Position, STARTING, X_BAR};
+use engine::probabilities::Probabilities;
+
+// Temporal difference learning during self play, like Tesauro's TD-Gammon.
+//
+// Unlike the supervised training on rollout data, this needs no existing net: the net learns
+// from the games it plays against itself, starting with random weights.
+
+/// Evaluates positions with the net which is being trained; finished games get their exact result.
+pub struct MlpEvaluator<'a, T: InputsGen> {
+    mlp: &'a Mlp,
+    inputs_gen: &'a T,
+}
+
+impl<'a, T: InputsGen> MlpEvaluator<'a, T> {
+    pub fn new(mlp: &'a Mlp, inputs_gen: &'a T) -> Self {
+        Self { mlp, inputs_gen }
+    }
+}
+
+impl<T: InputsGen> Evaluator for MlpEvaluator<'_, T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        match pos.game_state() {
+            Ongoing => probabilities(self.mlp.forward(&self.inputs_gen.inputs_for_single(pos))),
+            GameOver(result) => Probabilities::from(&result),
+        }
+    }
+}
+
+/// Plays games with the net against itself and trains it after each game with TD(λ).
+///
+/// One net is trained for all game phases, so `inputs_gen` should cover both contact and race.
+pub struct TdTrainer<T: InputsGen, U: DiceGen> {
+    trainer: Trainer,
+    inputs_gen: T,
+    dice_gen: U,
+    /// `0.0` trains each position towards the evaluation of the next one, `1.0` towards the final result.
+    lambda: f32,
+    stats: SelfPlayStats,
+}
+
+impl<T: InputsGen, U: DiceGen> TdTrainer<T, U> {
+    pub fn new(mlp: Mlp, learning_rate: f32, lambda: f32, inputs_gen: T, dice_gen: U) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&lambda),
+            "lambda must be between 0 and 1"
+        );
+        Self {
+            trainer: Trainer::new(mlp, learning_rate).with_loss(Loss::MeanSquared),
+            inputs_gen,
+            dice_gen,
+            lambda,
+            stats: SelfPlayStats::default(),
+        }
+    }
+
+    pub fn mlp(&self) -> &Mlp {
+        self.trainer.mlp()
+    }
+
+    pub fn stats(&self) -> &SelfPlayStats {
+        &self.stats
+    }
+
+    /// Plays one game, each side choosing the best move according to the current net, and then
+    /// updates the net once with all positions of the game. Returns the loss of this update.
+    pub fn train_one_game(&mut self) -> f32 {
+        let (positions, result) = self.play_one_game();
+        let values: Vec<Probabilities> = positions
+            .iter()
+            .map(|position| MlpEvaluator::new(self.mlp(), &self.inputs_gen).eval(position))
+            .collect();
+        let samples: Vec<Sample> = positions
+            .iter()
+            .zip(lambda_returns(&values, &result, self.lambda))
+            .map(|(position, target)| Sample {
+                inputs: self.inputs_gen.inputs_for_single(position),
+                labels: [
+                    target.win_normal,
+                    target.win_gammon,
+                    target.lose_normal,
+                    target.lose_gammon,
+                ],
+            })
+            .collect();
+        self.trainer.train_batch(&samples)
+    }
+
+    /// All positions of a game, each from the perspective of the player on roll, and the result
+    /// from the perspective of the player who made the last move.
+    fn play_one_game(&mut self) -> (Vec<Position>, GameResult) {
+        let mut positions = Vec::new();
+        let mut pos = STARTING;
+        let mut dice = self.dice_gen.roll_mixed();
+        loop {
+            positions.push(pos);
+            let evaluator = MlpEvaluator::new(self.trainer.mlp(), &self.inputs_gen);
+            let next = evaluator.best_position_by_equity(&pos, &dice);
+            self.stats
+                .add_half_move(pos.pip(X_BAR) > 0 && next == pos.sides_switched());
+            match next.game_state() {
+                Ongoing => {
+                    pos = next;
+                    dice = self.dice_gen.roll();
+                }
+                GameOver(result) => {
+                    // `next` is seen from the opponent, who has just lost.
+                    let result = result.reverse();
+                    self.stats.add_game(result.clone());
+                    return (positions, result);
+                }
+            }
+        }
+    }
+}
+
+/// Targets for training on the positions of one game, given their `values` by the current net.
+///
+/// The target of each position is `(1 - λ) * value + λ * target` of the following position,
+/// switched to the other player. The target of the last position is the `result` of the game.
+fn lambda_returns(
+    values: &[Probabilities],
+    result: &GameResult,
+    lambda: f32,
+) -> Vec<Probabilities> {
+    let mut targets = vec![Probabilities::default(); values.len()];
+    let mut target = Probabilities::from(result);
+    for i in (0..values.len()).rev() {
+        targets[i] = target.clone();
+        let mix = |value: f32, target: f32| (1.0 - lambda) * value + lambda * target;
+        let value = &values[i];
+        target = Probabilities {
+            win_normal: mix(value.win_normal, target.win_normal),
+            win_gammon: mix(value.win_gammon, target.win_gammon),
+            lose_normal: mix(value.lose_normal, target.lose_normal),
+            lose_gammon: mix(value.lose_gammon, target.lose_gammon),
+        }
+        .switch_sides();
+    }
+    targets
+}
+
+fn probabilities(output: [f32; 4]) -> Probabilities {
+    Probabilities {
+        win_normal: output[0],
+        win_gammon: output[1],
+        lose_normal: output[2],
+        lose_gammon: output[3],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::td::{lambda_returns, TdTrainer};
+    use crate::training::Mlp;
+    use engine::dice_gen::FastrandDice;
+    use engine::inputs::{ContactInputsGen, InputsGen};
+    use engine::position::GameResult::WinGammon;
+    use engine::probabilities::Probabilities;
+
+    fn values() -> Vec<Probabilities> {
+        vec![
+            Probabilities {
+                win_normal: 0.5,
+                win_gammon: 0.1,
+                lose_normal: 0.3,
+                lose_gammon: 0.1,
+            },
+            Probabilities {
+                win_normal: 0.4,
+                win_gammon: 0.2,
+                lose_normal: 0.3,
+                lose_gammon: 0.1,
+            },
+            Probabilities {
+                win_normal: 0.7,
+                win_gammon: 0.1,
+                lose_normal: 0.2,
+                lose_gammon: 0.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn lambda_zero_targets_next_value() {
+        let targets = lambda_returns(&values(), &WinGammon, 0.0);
+
+        assert_eq!(targets[0], values()[1].switch_sides());
+        assert_eq!(targets[1], values()[2].switch_sides());
+        assert_eq!(targets[2], Probabilities::from(&WinGammon));
+    }
+
+    #[test]
+    fn lambda_one_targets_result() {
+        let targets = lambda_returns(&values(), &WinGammon, 1.0);
+
+        // The last move was made by the player on roll in position 2, and so also in position 0.
+        assert_eq!(targets[0], Probabilities::from(&WinGammon));
+        assert_eq!(targets[1], Probabilities::from(&WinGammon).switch_sides());
+        assert_eq!(targets[2], Probabilities::from(&WinGammon));
+    }
+
+    #[test]
+    fn lambda_mixes_value_and_later_target() {
+        let targets = lambda_returns(&values(), &WinGammon, 0.25);
+
+        let next = values()[2].switch_sides();
+        let result = Probabilities::from(&WinGammon).switch_sides();
+        assert_eq!(
+            targets[1].win_normal,
+            0.75 * next.win_normal + 0.25 * result.win_normal
+        );
+        assert_eq!(
+            targets[1].lose_gammon,
+            0.75 * next.lose_gammon + 0.25 * result.lose_gammon
+        );
+    }
+
+    #[test]
+    fn train_one_game() {
+        // Given
+        let mlp = Mlp::new(ContactInputsGen::NUM_INPUTS, &[10], 1);
+        let mut trainer = TdTrainer::new(
+            mlp.clone(),
+            0.001,
+            0.7,
+            ContactInputsGen {},
+            FastrandDice::with_seed(2),
+        );
+        // When
+        trainer.train_one_game();
+        // Then
+        assert_eq!(trainer.stats().games(), 1);
+        assert!(trainer.stats().average_length() > 2.0);
+        assert_ne!(trainer.mlp(), &mlp);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::data::InputsRecord;
+use candle_core::{Device, Tensor, Var};
+use candle_nn::{AdamW, Optimizer, ParamsAdamW};
+
+// Training of neural nets in Rust with candle, without PyTorch. The architecture is the same as in
+// `training/src/model.py`: dense layers with `Hardsigmoid` activations, followed by a dense layer
+// with 4 outputs and `Softmax`. The loss is `L1Loss` and the optimizer `AdamW`.
+
+/// One training example: the inputs of a position and the 4 probabilities which should come out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub inputs: Vec<f32>,
+    pub labels: [f32; 4],
+}
+
+impl From<&InputsRecord> for Sample {
+    fn from(record: &InputsRecord) -> Self {
+        let values = record.values();
+        Self {
+            inputs: values[4..].to_vec(),
+            labels: [values[0], values[1], values[2], values[3]],
+        }
+    }
+}
+
+/// Fully connected layer, computing `weights * inputs + bias`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub num_inputs: usize,
+    pub num_outputs: usize,
+    /// `num_outputs` rows with `num_inputs` columns each, like `nn.Linear` in PyTorch.
+    pub weights: Vec<f32>,
+    pub bias: Vec<f32>,
+}
+
+impl Layer {
+    /// Random weights and biases in the same range as in PyTorch.
+    fn new(num_inputs: usize, num_outputs: usize, rng: &mut fastrand::Rng) -> Self {
+        let bound = 1.0 / (num_inputs as f32).sqrt();
+        let mut random = || (2.0 * rng.f32() - 1.0) * bound;
+        Self {
+            num_inputs,
+            num_outputs,
+            weights: (0..num_inputs * num_outputs).map(|_| random()).collect(),
+            bias: (0..num_outputs).map(|_| random()).collect(),
+        }
+    }
+
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks_exact(self.num_inputs)
+            .zip(&self.bias)
+            .map(|(row, bias)| bias + row.iter().zip(inputs).map(|(w, x)| w * x).sum::<f32>())
+            .collect()
+    }
+}
+
+/// Multilayer perceptron with `Hardsigmoid` activations and `Softmax` output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mlp {
+    pub layers: Vec<Layer>,
+}
+
+impl Mlp {
+    /// The sizes of the hidden layers are for example `[300, 250, 200]`. There are always 4 outputs.
+    pub fn new(num_inputs: usize, hidden: &[usize], seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let mut sizes = vec![num_inputs];
+        sizes.extend_from_slice(hidden);
+        sizes.push(4);
+        let layers = sizes
+            .windows(2)
+            .map(|pair| Layer::new(pair[0], pair[1], &mut rng))
+            .collect();
+        Self { layers }
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.layers[0].num_inputs
+    }
+
+    /// The 4 probabilities for the given inputs.
+    pub fn forward(&self, inputs: &[f32]) -> [f32; 4] {
+        let mut activations = inputs.to_vec();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let z = layer.forward(&activations);
+            activations = if i + 1 == self.layers.len() {
+                softmax(&z)
+            } else {
+                z.iter().map(|&x| hardsigmoid(x)).collect()
+            };
+        }
+        [
+            activations[0],
+            activations[1],
+            activations[2],
+            activations[3],
+        ]
+    }
+}
+
+fn hardsigmoid(x: f32) -> f32 {
+    (x / 6.0 + 0.5).clamp(0.0, 1.0)
+}
+
+fn softmax(z: &[f32]) -> Vec<f32> {
+    let max = z.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exp: Vec<f32> = z.iter().map(|x| (x - max).exp()).collect();
+    let sum: f32 = exp.iter().sum();
+    exp.iter().map(|x| x / sum).collect()
+}
+
+/// Loss function during training, averaged over all outputs of a batch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Loss {
+    /// Like `nn.L1Loss`, which has worked better for supervised training on rollout data.
+    L1,
+    /// Like `nn.MSELoss`, the classic choice for temporal difference learning.
+    MeanSquared,
+}
+
+impl Loss {
+    fn tensor(self, outputs: &Tensor, labels: &Tensor) -> candle_core::Result<Tensor> {
+        let errors = (outputs - labels)?;
+        match self {
+            Loss::L1 => errors.abs()?.mean_all(),
+            Loss::MeanSquared => errors.sqr()?.mean_all(),
+        }
+    }
+}
+
+/// Trains an [Mlp] with the `AdamW` of candle, using the defaults of PyTorch except for the
+/// learning rate.
+///
+/// The parameters live in candle variables during training, [Trainer::mlp] is a copy which is
+/// updated after each batch.
+pub struct Trainer {
+    mlp: Mlp,
+    /// Weights and bias of each layer.
+    variables: Vec<(Var, Var)>,
+    optimizer: AdamW,
+    loss: Loss,
+}
+
+/// The shapes of the tensors are always consistent, so candle can't fail on the CPU.
+const CONSISTENT: &str = "Tensors of the net have consistent shapes.";
+
+impl Trainer {
+    pub fn new(mlp: Mlp, learning_rate: f32) -> Self {
+        let variables: Vec<(Var, Var)> = mlp
+            .layers
+            .iter()
+            .map(|layer| {
+                let shape = (layer.num_outputs, layer.num_inputs);
+                let weights = Var::from_vec(layer.weights.clone(), shape, &Device::Cpu);
+                let bias = Var::from_vec(layer.bias.clone(), layer.num_outputs, &Device::Cpu);
+                (weights.expect(CONSISTENT), bias.expect(CONSISTENT))
+            })
+            .collect();
+        let parameters = ParamsAdamW {
+            lr: learning_rate as f64,
+            ..ParamsAdamW::default()
+        };
+        let all_variables = variables
+            .iter()
+            .flat_map(|(weights, bias)| [weights.clone(), bias.clone()])
+            .collect();
+        Self {
+            mlp,
+            variables,
+            optimizer: AdamW::new(all_variables, parameters).expect(CONSISTENT),
+            loss: Loss::L1,
+        }
+    }
+
+    pub fn with_loss(self, loss: Loss) -> Self {
+        Self { loss, ..self }
+    }
+
+    pub fn mlp(&self) -> &Mlp {
+        &self.mlp
+    }
+
+    /// Shuffles `samples`, trains once on all of them in batches of `batch_size` and returns the
+    /// average loss.
+    pub fn train_epoch(
+        &mut self,
+        samples: &mut [Sample],
+        batch_size: usize,
+        rng: &mut fastrand::Rng,
+    ) -> f32 {
+        rng.shuffle(samples);
+        let mut loss_sum = 0.0;
+        let mut batches = 0;
+        for batch in samples.chunks(batch_size) {
+            loss_sum += self.train_batch(batch);
+            batches += 1;
+        }
+        loss_sum / batches.max(1) as f32
+    }
+
+    /// Updates the net once with the gradients of all `samples` and returns their loss.
+    pub fn train_batch(&mut self, samples: &[Sample]) -> f32 {
+        self.try_train_batch(samples).expect(CONSISTENT)
+    }
+
+    fn try_train_batch(&mut self, samples: &[Sample]) -> candle_core::Result<f32> {
+        let num_inputs = self.mlp.num_inputs();
+        let inputs: Vec<f32> = samples
+            .iter()
+            .flat_map(|sample| sample.inputs.iter().copied())
+            .collect();
+        let labels: Vec<f32> = samples.iter().flat_map(|sample| sample.labels).collect();
+        let inputs = Tensor::from_vec(inputs, (samples.len(), num_inputs), &Device::Cpu)?;
+        let labels = Tensor::from_vec(labels, (samples.len(), 4), &Device::Cpu)?;
+
+        let loss = self.loss.tensor(&self.forward(&inputs)?, &labels)?;
+        self.optimizer.backward_step(&loss)?;
+        for (layer, (weights, bias)) in self.mlp.layers.iter_mut().zip(&self.variables) {
+            layer.weights = weights.flatten_all()?.to_vec1()?;
+            layer.bias = bias.to_vec1()?;
+        }
+        loss.to_scalar()
+    }
+
+    /// Outputs for a batch of inputs with one row per sample, like [Mlp::forward].
+    fn forward(&self, inputs: &Tensor) -> candle_core::Result<Tensor> {
+        let mut activations = inputs.clone();
+        for (i, (weights, bias)) in self.variables.iter().enumerate() {
+            let z = activations.matmul(&weights.t()?)?.broadcast_add(bias)?;
+            activations = if i + 1 == self.variables.len() {
+                candle_nn::ops::softmax(&z, 1)?
+            } else {
+                // Hardsigmoid
+                z.affine(1.0 / 6.0, 0.5)?.clamp(0.0, 1.0)?
+            };
+        }
+        Ok(activations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::training::{Loss, Mlp, Sample, Trainer};
+    use candle_core::{Device, Tensor};
+
+    #[test]
+    fn outputs_are_probabilities() {
+        let mlp = Mlp::new(3, &[5, 4], 1);
+        let output = mlp.forward(&[0.5, -1.0, 2.0]);
+        assert!((output.iter().sum::<f32>() - 1.0).abs() < 0.0001);
+        assert!(output.iter().all(|&p| p > 0.0));
+    }
+
+    #[test]
+    fn candle_forward_matches_mlp() {
+        // Given
+        let mlp = Mlp::new(3, &[5, 4], 2);
+        let trainer = Trainer::new(mlp.clone(), 0.01);
+        let inputs = [0.3, -0.7, 1.1];
+        // When
+        let tensor = Tensor::from_vec(inputs.to_vec(), (1, 3), &Device::Cpu).unwrap();
+        let candle = trainer.forward(&tensor).unwrap().flatten_all().unwrap();
+        // Then
+        let candle: Vec<f32> = candle.to_vec1().unwrap();
+        for (candle, rust) in candle.iter().zip(mlp.forward(&inputs)) {
+            assert!((candle - rust).abs() < 1e-6, "{candle} vs. {rust}");
+        }
+    }
+
+    #[test]
+    fn mean_squared_loss_decreases() {
+        // Given
+        let samples = vec![Sample {
+            inputs: vec![0.3, -0.7, 1.1],
+            labels: [0.1, 0.2, 0.3, 0.4],
+        }];
+        let mut trainer = Trainer::new(Mlp::new(3, &[4], 2), 0.01).with_loss(Loss::MeanSquared);
+        // When
+        let first = trainer.train_batch(&samples);
+        let mut last = first;
+        for _ in 0..20 {
+            last = trainer.train_batch(&samples);
+        }
+        // Then
+        assert!(last < first, "loss went from {first} to {last}");
+    }
+
+    #[test]
+    fn loss_decreases() {
+        // Given: The first input decides whether `win_normal` or `lose_normal` is likely.
+        let mut samples: Vec<Sample> = (0..200)
+            .map(|i| {
+                let x = (i % 2) as f32;
+                Sample {
+                    inputs: vec![x, 0.5],
+                    labels: [0.8 * x + 0.1, 0.0, 0.9 - 0.8 * x, 0.0],
+                }
+            })
+            .collect();
+        let mut trainer = Trainer::new(Mlp::new(2, &[8], 3), 0.01);
+        let mut rng = fastrand::Rng::with_seed(4);
+        // When
+        let first = trainer.train_epoch(&mut samples, 16, &mut rng);
+        let mut last = first;
+        for _ in 0..30 {
+            last = trainer.train_epoch(&mut samples, 16, &mut rng);
+        }
+        // Then
+        assert!(last < first / 2.0, "loss went from {first} to {last}");
+        assert!(trainer.mlp().forward(&[1.0, 0.5])[0] > 0.6);
+        assert!(trainer.mlp().forward(&[0.0, 0.5])[2] > 0.6);
+    }
+}
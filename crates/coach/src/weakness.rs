@@ -0,0 +1,108 @@
+use crate::data::PositionRecord;
+use engine::dice::ALL_21;
+use engine::evaluator::Evaluator;
+use engine::position::GameState::Ongoing;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// A position for which the net and the rollout disagree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Discrepancy {
+    pub position: Position,
+    pub net: Probabilities,
+    pub rollout: Probabilities,
+}
+
+impl Discrepancy {
+    /// Absolute difference of the cubeless equities.
+    pub fn error(&self) -> f32 {
+        (self.net.equity() - self.rollout.equity()).abs()
+    }
+}
+
+/// The `amount` records for which the 0-ply evaluation of `evaluator` is furthest away from the
+/// rollout, the biggest error first.
+pub fn worst_positions<T: Evaluator + Sync>(
+    evaluator: &T,
+    records: &[PositionRecord],
+    amount: usize,
+) -> Vec<Discrepancy> {
+    let mut discrepancies: Vec<Discrepancy> = records
+        .par_iter()
+        .map(|record| {
+            let position = record.position();
+            Discrepancy {
+                position,
+                net: evaluator.eval(&position),
+                rollout: record.probabilities(),
+            }
+        })
+        .collect();
+    discrepancies.par_sort_by(|a, b| b.error().total_cmp(&a.error()));
+    discrepancies.truncate(amount);
+    discrepancies
+}
+
+/// Positions after the best move for each of the 21 rolls, seen from the opponent who is then on roll.
+///
+/// These are close to `position` and so are likely also evaluated badly if `position` is.
+/// Finished games are left out.
+pub fn successors<T: Evaluator>(evaluator: &T, position: &Position) -> Vec<Position> {
+    let mut unique = HashSet::new();
+    ALL_21
+        .iter()
+        .map(|(dice, _)| evaluator.best_position_by_equity(position, dice))
+        .filter(|next| next.game_state() == Ongoing && unique.insert(*next))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::PositionRecord;
+    use crate::weakness::{successors, worst_positions};
+    use engine::pos;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+    use engine::test_utils::EvaluatorFake;
+
+    fn record(position: Position, win: f32) -> PositionRecord {
+        let probabilities = Probabilities {
+            win_normal: win,
+            lose_normal: 1.0 - win,
+            ..Default::default()
+        };
+        PositionRecord::new(&position, &probabilities)
+    }
+
+    #[test]
+    fn worst_positions_first() {
+        // Given
+        // The fake evaluator returns 0.5 for the first two and 0.8 for the last position.
+        let records = [
+            record(pos!(x 6:2; o 19:2), 0.6),
+            record(pos!(x 7:2; o 19:2), 0.5),
+            record(pos!(x 5:2; o 19:2), 0.4),
+        ];
+        // When
+        let worst = worst_positions(&EvaluatorFake {}, &records, 2);
+        // Then
+        assert_eq!(worst.len(), 2);
+        assert_eq!(worst[0].position, pos!(x 5:2; o 19:2));
+        assert!((worst[0].error() - 0.8).abs() < 0.0001);
+        assert_eq!(worst[1].position, pos!(x 6:2; o 19:2));
+        assert!((worst[1].error() - 0.2).abs() < 0.0001);
+    }
+
+    #[test]
+    fn successors_are_unique_and_ongoing() {
+        // Given
+        let position = pos!(x 1:3; o 24:1);
+        // When
+        let successors = successors(&EvaluatorFake {}, &position);
+        // Then
+        // Doubles bear off all checkers, all other rolls leave one checker on the 1 point.
+        assert_eq!(successors, vec![pos!(x 1:1; o 24:1).sides_switched()]);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::position::{Position, NUM_OF_CHECKERS, X_BAR};
+
+/// All one-sided bearoff positions of a single player with up to 15 checkers in the home board.
+pub const HOME_BOARD: OneSidedBearoff<6> = OneSidedBearoff::new(NUM_OF_CHECKERS);
+
+/// One-sided bearoff positions: up to `max_checkers` checkers of a single player on the lowest
+/// `POINTS` points, the other checkers are already borne off.
+///
+/// A board is an array with the number of checkers on the 1 point at index 0, on the 2 point at
+/// index 1 and so on. [OneSidedBearoff::index] is a perfect hash: it maps all boards to the numbers
+/// from 0 to [OneSidedBearoff::len], so that databases can be plain arrays.
+///
+/// Guarantees for databases and other users:
+/// - Index 0 is the board without checkers.
+/// - [OneSidedBearoff::iter] returns the boards in the order of their indices.
+/// - Moving a checker, including bearing it off, always leads to a smaller index. So a database
+///   can be built in a single pass in index order.
+/// - The index doesn't depend on `max_checkers`, only the number of boards does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OneSidedBearoff<const POINTS: usize> {
+    max_checkers: u8,
+}
+
+impl<const POINTS: usize> OneSidedBearoff<POINTS> {
+    /// Panics if `POINTS` is not between 1 and 24 or if `max_checkers` is bigger than 15.
+    pub const fn new(max_checkers: u8) -> Self {
+        assert!(
+            POINTS >= 1 && POINTS < X_BAR,
+            "POINTS must be between 1 and 24."
+        );
+        assert!(
+            max_checkers <= NUM_OF_CHECKERS,
+            "There are at most 15 checkers."
+        );
+        Self { max_checkers }
+    }
+
+    /// Number of different boards.
+    pub fn len(&self) -> usize {
+        binomial(POINTS + self.max_checkers as usize, POINTS)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // There is always the board without any checkers.
+        false
+    }
+
+    /// The perfect hash of `board`, smaller than [OneSidedBearoff::len].
+    ///
+    /// Panics if there are more than `max_checkers` on the board.
+    pub fn index(&self, board: &[u8; POINTS]) -> usize {
+        let total: usize = board.iter().map(|&c| c as usize).sum();
+        assert!(
+            total <= self.max_checkers as usize,
+            "Board has more than {} checkers.",
+            self.max_checkers
+        );
+        // Stars and bars: each board is a set of `POINTS` numbers, which is ranked with the
+        // combinatorial number system. Starting with the highest point lets moves decrease it.
+        let mut checkers = 0;
+        (0..POINTS)
+            .map(|i| {
+                checkers += board[POINTS - 1 - i] as usize;
+                binomial(checkers + i, i + 1)
+            })
+            .sum()
+    }
+
+    /// The board with the given `index`, the inverse of [OneSidedBearoff::index].
+    ///
+    /// Panics if `index` is not smaller than [OneSidedBearoff::len].
+    pub fn board(&self, index: usize) -> [u8; POINTS] {
+        assert!(index < self.len(), "Index {index} is out of range.");
+        let mut remaining = index;
+        let mut elements = [0; POINTS];
+        for i in (0..POINTS).rev() {
+            let mut element = i;
+            while binomial(element + 1, i + 1) <= remaining {
+                element += 1;
+            }
+            remaining -= binomial(element, i + 1);
+            elements[i] = element;
+        }
+        let mut board = [0; POINTS];
+        let mut previous = 0;
+        for (i, element) in elements.into_iter().enumerate() {
+            let checkers = element - i;
+            board[POINTS - 1 - i] = (checkers - previous) as u8;
+            previous = checkers;
+        }
+        board
+    }
+
+    /// All boards, ordered by their index.
+    pub fn iter(&self) -> BearoffBoards<POINTS> {
+        BearoffBoards {
+            bearoff: *self,
+            next: 0,
+            len: self.len(),
+        }
+    }
+
+    /// The board of player `x` in `position`.
+    ///
+    /// `None` if `x` has checkers above the lowest `POINTS` points or more than `max_checkers`.
+    /// The checkers of `o` are ignored.
+    pub fn board_of(&self, position: &Position) -> Option<[u8; POINTS]> {
+        if (POINTS + 1..=X_BAR).any(|pip| position.pip(pip) > 0) {
+            return None;
+        }
+        let mut board = [0; POINTS];
+        for (i, checkers) in board.iter_mut().enumerate() {
+            *checkers = position.pip(i + 1).max(0) as u8;
+        }
+        let total: u8 = board.iter().sum();
+        (total <= self.max_checkers).then_some(board)
+    }
+}
+
+/// Iterator over all boards of a [OneSidedBearoff], see [OneSidedBearoff::iter].
+pub struct BearoffBoards<const POINTS: usize> {
+    bearoff: OneSidedBearoff<POINTS>,
+    next: usize,
+    len: usize,
+}
+
+impl<const POINTS: usize> Iterator for BearoffBoards<POINTS> {
+    type Item = [u8; POINTS];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.len {
+            return None;
+        }
+        let board = self.bearoff.board(self.next);
+        self.next += 1;
+        Some(board)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<const POINTS: usize> ExactSizeIterator for BearoffBoards<POINTS> {}
+
+/// Binomial coefficient, `0` if `k > n`.
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bearoff::{binomial, OneSidedBearoff, HOME_BOARD};
+    use crate::pos;
+    use crate::position::Position;
+
+    #[test]
+    fn binomials() {
+        assert_eq!(binomial(5, 2), 10);
+        assert_eq!(binomial(21, 6), 54264);
+        assert_eq!(binomial(3, 4), 0);
+        assert_eq!(binomial(0, 0), 1);
+    }
+
+    #[test]
+    fn home_board_is_perfect_hash() {
+        assert_eq!(HOME_BOARD.len(), 54264);
+        assert_eq!(HOME_BOARD.iter().len(), 54264);
+        for (index, board) in HOME_BOARD.iter().enumerate() {
+            assert_eq!(HOME_BOARD.index(&board), index, "{board:?}");
+        }
+        assert_eq!(HOME_BOARD.board(0), [0; 6]);
+        assert_eq!(HOME_BOARD.board(HOME_BOARD.len() - 1), [0, 0, 0, 0, 0, 15]);
+    }
+
+    #[test]
+    fn index_does_not_depend_on_max_checkers() {
+        let small = OneSidedBearoff::<6>::new(3);
+        assert_eq!(small.len(), 84);
+        for board in small.iter() {
+            assert_eq!(small.index(&board), HOME_BOARD.index(&board));
+        }
+    }
+
+    #[test]
+    fn moves_decrease_the_index() {
+        let bearoff = OneSidedBearoff::<6>::new(4);
+        for board in bearoff.iter() {
+            let mut pips = [0; 26];
+            for (i, checkers) in board.iter().enumerate() {
+                pips[i + 1] = *checkers as i8;
+            }
+            // A single checker of `o` far away, so that the position is valid.
+            pips[24] = -1;
+            let position = Position::try_from(pips).unwrap();
+            let index = bearoff.index(&board);
+            for from in 1..=6 {
+                for die in 1..=6 {
+                    if let Some(next) = position.try_move_single_checker(from, die) {
+                        let next_board = bearoff.board_of(&next).unwrap();
+                        assert!(bearoff.index(&next_board) < index, "{board:?} {from} {die}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn board_of_position() {
+        let position = pos!(x 6:5, 5:2, 1:3; o 24:2);
+        assert_eq!(HOME_BOARD.board_of(&position), Some([3, 0, 0, 0, 2, 5]));
+        assert_eq!(OneSidedBearoff::<6>::new(9).board_of(&position), None);
+        assert_eq!(HOME_BOARD.board_of(&pos!(x 7:1; o 1:1)), None);
+        assert_eq!(
+            OneSidedBearoff::<7>::new(1).board_of(&pos!(x 7:1; o 1:1)),
+            Some([0, 0, 0, 0, 0, 0, 1])
+        );
+    }
+}
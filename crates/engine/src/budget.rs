@@ -0,0 +1,175 @@
+use crate::dice::Dice;
+use crate::evaluator::Evaluator;
+use crate::multiply::{discounted, one_ply_deeper};
+use crate::position::Position;
+use crate::probabilities::Probabilities;
+use crate::time::Instant;
+use std::time::Duration;
+
+/// Limits how much work a single request may do.
+///
+/// Evaluations start with the evaluator itself, which is 1-ply. As long as `max_plies` is not reached
+/// and `latency` not used up, they go one ply deeper. If the time runs out in the middle of a deeper
+/// evaluation, the result of the shallower evaluation is returned instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Budget {
+    /// Currently only 1 and 2 are supported, bigger values are treated like 2.
+    pub max_plies: u8,
+    pub latency: Duration,
+    /// Weight of the shallower evaluation in deeper evaluations, see [discounted]. Zero means no discount.
+    pub discount: f32,
+}
+
+impl Default for Budget {
+    /// Only 1-ply, which is fast enough to not need a time limit.
+    fn default() -> Self {
+        Self {
+            max_plies: 1,
+            latency: Duration::from_secs(1),
+            discount: 0.0,
+        }
+    }
+}
+
+impl Budget {
+    fn deadline(&self, start: Instant) -> Option<Instant> {
+        if self.max_plies > 1 {
+            Some(start + self.latency)
+        } else {
+            None
+        }
+    }
+
+    /// Probabilities and the number of plies actually used.
+    pub fn eval<T: Evaluator>(&self, evaluator: &T, position: &Position) -> (Probabilities, u8) {
+        let start = Instant::now();
+        let shallow = evaluator.eval(position);
+        match self.deadline(start) {
+            None => (shallow, 1),
+            Some(deadline) => match one_ply_deeper(evaluator, position, Some(deadline)) {
+                Some(deep) => (discounted(&shallow, &deep, self.discount), 2),
+                None => (shallow, 1),
+            },
+        }
+    }
+
+    /// All legal positions after moving, like [Evaluator::positions_and_probabilities_by_equity].
+    ///
+    /// If deeper evaluations are allowed, the candidates are looked at again in the order of their
+    /// 1-ply equity until the time runs out. Candidates evaluated deeper are ranked before all others,
+    /// so the best move is always one of the deeper evaluated moves if there is at least one.
+    ///
+    /// The second value is `true` if the time ran out before all candidates were evaluated deeper.
+    pub fn positions_and_probabilities_by_equity<T: Evaluator>(
+        &self,
+        evaluator: &T,
+        position: &Position,
+        dice: &Dice,
+    ) -> (Vec<(Position, Probabilities)>, bool) {
+        let start = Instant::now();
+        let shallow = evaluator.positions_and_probabilities_by_equity(position, dice);
+        let deadline = match self.deadline(start) {
+            None => return (shallow, false),
+            Some(deadline) => deadline,
+        };
+        if shallow.len() < 2 {
+            return (shallow, false);
+        }
+
+        let mut deep: Vec<(Position, Probabilities)> = Vec::new();
+        let mut rest = shallow.into_iter();
+        for (new_position, probabilities) in rest.by_ref() {
+            // `new_position` is from the point of view of `x`, the deeper evaluation from the point of view of `o`.
+            match one_ply_deeper(evaluator, &new_position.sides_switched(), Some(deadline)) {
+                Some(deeper) => {
+                    let deeper = discounted(&probabilities, &deeper.switch_sides(), self.discount);
+                    deep.push((new_position, deeper));
+                }
+                None => {
+                    deep.sort_unstable_by(|a, b| b.1.equity().total_cmp(&a.1.equity()));
+                    deep.push((new_position, probabilities));
+                    deep.extend(rest);
+                    return (deep, true);
+                }
+            }
+        }
+        deep.sort_unstable_by(|a, b| b.1.equity().total_cmp(&a.1.equity()));
+        (deep, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::budget::Budget;
+    use crate::composite::CompositeEvaluator;
+    use crate::dice::Dice;
+    use crate::evaluator::Evaluator;
+    use crate::multiply::MultiPlyEvaluator;
+    use crate::pos;
+    use std::time::Duration;
+
+    #[test]
+    fn one_ply_by_default() {
+        let evaluator = CompositeEvaluator::default_tests();
+        let position = pos!(x 5:1; o 24:1);
+        let (probabilities, plies) = Budget::default().eval(&evaluator, &position);
+        assert_eq!(plies, 1);
+        assert_eq!(probabilities, evaluator.eval(&position));
+    }
+
+    #[test]
+    fn two_plies_with_enough_time() {
+        let evaluator = CompositeEvaluator::default_tests();
+        let position = pos!(x 5:1; o 24:1);
+        let budget = Budget {
+            max_plies: 2,
+            latency: Duration::from_secs(60),
+            discount: 0.0,
+        };
+        let (probabilities, plies) = budget.eval(&evaluator, &position);
+        assert_eq!(plies, 2);
+        let multi = MultiPlyEvaluator::new(evaluator);
+        assert_eq!(probabilities, multi.eval(&position));
+    }
+
+    #[test]
+    fn degrade_to_one_ply_without_time() {
+        let evaluator = CompositeEvaluator::default_tests();
+        let position = pos!(x 5:1, 3:1; o 24:1, 20:1);
+        let dice = Dice::new(2, 1);
+        let budget = Budget {
+            max_plies: 2,
+            latency: Duration::ZERO,
+            discount: 0.0,
+        };
+        assert_eq!(budget.eval(&evaluator, &position).1, 1);
+        let (moves, degraded) =
+            budget.positions_and_probabilities_by_equity(&evaluator, &position, &dice);
+        assert!(degraded);
+        assert_eq!(
+            moves,
+            evaluator.positions_and_probabilities_by_equity(&position, &dice)
+        );
+    }
+
+    #[test]
+    fn all_moves_two_plies_with_enough_time() {
+        let evaluator = CompositeEvaluator::default_tests();
+        let position = pos!(x 5:1, 3:1; o 24:1, 20:1);
+        let dice = Dice::new(2, 1);
+        let budget = Budget {
+            max_plies: 2,
+            latency: Duration::from_secs(60),
+            discount: 0.0,
+        };
+        let (moves, degraded) =
+            budget.positions_and_probabilities_by_equity(&evaluator, &position, &dice);
+        assert!(!degraded);
+        let multi = MultiPlyEvaluator::new(evaluator);
+        assert_eq!(moves.len(), 3);
+        for (new_position, probabilities) in moves {
+            let expected = multi.eval(&new_position.sides_switched()).switch_sides();
+            assert_eq!(probabilities, expected);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::evaluator::{BatchEvaluator, Evaluator, PartialEvaluator};
 use crate::inputs::{ContactInputsGen, RaceInputsGen};
 use crate::onnx::OnnxEvaluator;
-use crate::position::{GamePhase, GameResult, GameState, OngoingPhase, Position};
+use crate::position::{GamePhase, GameState, OngoingPhase, Position};
 use crate::probabilities::Probabilities;
 
 type Error = String;
@@ -92,6 +92,38 @@ impl CompositeEvaluator {
         }
     }
 
+    /// Uses the given contact net, for example one that has been uploaded, and the default race net.
+    pub fn from_contact_bytes(contact: &[u8]) -> Result<Self, Error> {
+        let contact_evaluator = OnnxEvaluator::from_bytes(contact, ContactInputsGen {})?;
+        let race_evaluator = OnnxEvaluator::race_default()?;
+        Ok(Self {
+            contact_evaluator,
+            race_evaluator,
+            game_over_evaluator: GameOverEvaluator {},
+        })
+    }
+
+    /// Uses nets which are already in memory, for example in the browser where there is no file system.
+    pub fn from_bytes(contact: &[u8], race: &[u8]) -> Result<Self, Error> {
+        let contact_evaluator = OnnxEvaluator::from_bytes(contact, ContactInputsGen {})?;
+        let race_evaluator = OnnxEvaluator::from_bytes(race, RaceInputsGen {})?;
+        Ok(Self {
+            contact_evaluator,
+            race_evaluator,
+            game_over_evaluator: GameOverEvaluator {},
+        })
+    }
+
+    pub fn from_file_paths(contact_path: &str, race_path: &str) -> Result<Self, Error> {
+        let contact_evaluator = OnnxEvaluator::from_file_path(contact_path, ContactInputsGen {})?;
+        let race_evaluator = OnnxEvaluator::from_file_path(race_path, RaceInputsGen {})?;
+        Ok(Self {
+            contact_evaluator,
+            race_evaluator,
+            game_over_evaluator: GameOverEvaluator {},
+        })
+    }
+
     pub fn from_file_paths_optimized(contact_path: &str, race_path: &str) -> Result<Self, Error> {
         let contact_evaluator =
             OnnxEvaluator::from_file_path_optimized(contact_path, ContactInputsGen {})?;
@@ -110,25 +142,7 @@ impl PartialEvaluator for GameOverEvaluator {
     fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
         match pos.game_state() {
             GameState::Ongoing => None,
-            GameState::GameOver(result) => match result {
-                GameResult::WinNormal => Some(Probabilities {
-                    win_normal: 1.,
-                    ..Default::default()
-                }),
-                GameResult::WinGammon => Some(Probabilities {
-                    win_gammon: 1.,
-                    ..Default::default()
-                }),
-                GameResult::LoseNormal => Some(Probabilities {
-                    lose_normal: 1.,
-                    ..Default::default()
-                }),
-                GameResult::LoseGammon => Some(Probabilities {
-                    lose_gammon: 1.,
-                    ..Default::default()
-                }),
-
-            },
+            GameState::GameOver(result) => Some(Probabilities::from(&result)),
         }
     }
 }
@@ -192,11 +206,28 @@ mod composite_tests {
             OnnxEvaluator::contact_default().unwrap().eval(&contact)
         );
     }
+
+    #[test]
+    fn from_bytes_equals_default() {
+        // Given
+        let contact = include_bytes!("../../../neural-nets/contact.onnx");
+        let race = include_bytes!("../../../neural-nets/race.onnx");
+        // When
+        let evaluator = super::CompositeEvaluator::from_bytes(contact, race).unwrap();
+        // Then
+        let position = pos![x 1:1, 24:1; o 10:1];
+        assert_eq!(
+            evaluator.eval(&position),
+            super::CompositeEvaluator::default_tests().eval(&position)
+        );
+        assert!(super::CompositeEvaluator::from_bytes(race, race).is_err());
+    }
 }
 
 #[cfg(test)]
 mod game_over_tests {
     use crate::evaluator::Evaluator;
+    use crate::onnx::OnnxEvaluator;
     use crate::pos;
 
     #[test]
@@ -237,14 +268,13 @@ mod game_over_tests {
         assert_eq!(probabilities.equity(), 2.);
     }
 
-
     #[test]
     fn game_over_ongoing() {
         let evaluator = super::CompositeEvaluator::default_tests();
         let position = pos!(x 1:1; o 2:2).sides_switched();
         let probabilities = evaluator.eval(&position);
         // The probabilities now come from the onnx evaluator
-        assert!(probabilities.equity() < 0.);
-        assert!(probabilities.equity() > -1.);
+        let race_evaluator = OnnxEvaluator::race_default().unwrap();
+        assert_eq!(probabilities, race_evaluator.eval(&position));
     }
 }
@@ -1,5 +1,12 @@
 /// Contains a legal pair of dice (values between 1 and 6).
+///
+/// With the feature `serde` dice are serialized as pair, for example `[5, 3]`.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "(usize, usize)", into = "(usize, usize)")
+)]
 pub enum Dice {
     Mixed(MixedDice),
     Double(usize),
@@ -118,6 +125,15 @@ impl TryFrom<(usize, usize)> for Dice {
     }
 }
 
+impl From<Dice> for (usize, usize) {
+    fn from(dice: Dice) -> Self {
+        match dice {
+            Dice::Double(die) => (die, die),
+            Dice::Mixed(mixed) => (mixed.big, mixed.small),
+        }
+    }
+}
+
 impl MixedDice {
     #[inline]
     pub fn small(&self) -> usize {
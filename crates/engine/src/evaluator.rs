@@ -136,13 +136,13 @@ impl Evaluator for RandomEvaluator {
         let lose_gammon = fastrand::f32();
 
         // Now we like to make sure that the different probabilities add up to 1
-        let sum = win_normal + win_gammon + lose_normal + lose_gammon;
         Probabilities {
-            win_normal: win_normal / sum,
-            win_gammon: win_gammon / sum,
-            lose_normal: lose_normal / sum,
-            lose_gammon: lose_gammon / sum,
+            win_normal,
+            win_gammon,
+            lose_normal,
+            lose_gammon,
         }
+        .normalized()
     }
 }
 
@@ -257,8 +257,7 @@ mod random_evaluator_tests {
     fn sum_is_1() {
         let evaluator = RandomEvaluator {};
         let p = evaluator.eval(&position::STARTING);
-        let sum =
-            p.win_normal + p.win_gammon + p.lose_normal + p.lose_gammon;
+        let sum = p.win_normal + p.win_gammon + p.lose_normal + p.lose_gammon;
         assert!((sum - 1.0).abs() < 0.0001);
     }
 }
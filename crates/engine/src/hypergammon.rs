@@ -0,0 +1,172 @@
+use crate::evaluator::Evaluator;
+use crate::position::GameResult::{LoseGammon, LoseNormal, WinGammon, WinNormal};
+use crate::position::GameState::GameOver;
+use crate::position::{GameState, Position, NUM_OF_CHECKERS};
+use crate::probabilities::Probabilities;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+type Error = String;
+
+/// Number of checkers per player in hypergammon.
+pub const HYPERGAMMON_CHECKERS: u8 = 3;
+
+/// Number of bytes of a single entry in the database file: position key and four `f32`.
+const RECORD_SIZE: usize = 10 + 4 * 4;
+
+/// Like [Position::game_state], but with gammons as defined in hypergammon.
+///
+/// The missing twelve checkers are treated as borne off. So a gammon is won if the opponent
+/// still has all three checkers on the board.
+pub fn game_state(position: &Position) -> GameState {
+    let none_off = NUM_OF_CHECKERS - HYPERGAMMON_CHECKERS;
+    match position.game_state() {
+        GameOver(WinNormal) if position.o_off() == none_off => GameOver(WinGammon),
+        GameOver(LoseNormal) if position.x_off() == none_off => GameOver(LoseGammon),
+        game_state => game_state,
+    }
+}
+
+/// Exact cubeless probabilities for all hypergammon positions.
+///
+/// The state space of hypergammon is small enough to be solved completely. The database is created
+/// by the coach and then loaded from a file, so the engine plays hypergammon perfectly.
+pub struct HypergammonEvaluator {
+    probabilities: HashMap<Position, Probabilities>,
+}
+
+impl Evaluator for HypergammonEvaluator {
+    /// Panics if `pos` is ongoing and not part of the database.
+    fn eval(&self, pos: &Position) -> Probabilities {
+        match game_state(pos) {
+            GameOver(result) => Probabilities::from(&result),
+            GameState::Ongoing => self
+                .probabilities
+                .get(pos)
+                .expect("Position is not part of the hypergammon database.")
+                .clone(),
+        }
+    }
+}
+
+impl HypergammonEvaluator {
+    /// Probabilities for all ongoing positions, from the point of view of the player on roll.
+    pub fn new(probabilities: HashMap<Position, Probabilities>) -> Self {
+        Self { probabilities }
+    }
+
+    pub fn from_file_path(file_path: &str) -> Result<Self, Error> {
+        let file = File::open(file_path)
+            .map_err(|_| format!("Could not find hypergammon database {file_path}"))?;
+        Self::from_reader(&mut BufReader::new(file))
+            .map_err(|_| format!("Could not process hypergammon database {file_path}"))
+    }
+
+    /// Reads the format written by [HypergammonEvaluator::write].
+    pub fn from_reader(reader: &mut dyn Read) -> std::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        if bytes.len() % RECORD_SIZE != 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Hypergammon database has an invalid length.",
+            ));
+        }
+        let probabilities = bytes
+            .chunks_exact(RECORD_SIZE)
+            .map(|record| {
+                let key: [u8; 10] = record[..10].try_into().unwrap();
+                let value = |i: usize| {
+                    let start = 10 + 4 * i;
+                    f32::from_le_bytes(record[start..start + 4].try_into().unwrap())
+                };
+                let probabilities = Probabilities {
+                    win_normal: value(0),
+                    win_gammon: value(1),
+                    lose_normal: value(2),
+                    lose_gammon: value(3),
+                };
+                let position = Position::from_position_key(key).map_err(|error| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, error.message())
+                })?;
+                Ok((position, probabilities))
+            })
+            .collect::<std::io::Result<_>>()?;
+        Ok(Self { probabilities })
+    }
+
+    /// Each entry is written as the 80 bit position key followed by the four probabilities as
+    /// little endian `f32`.
+    pub fn write(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        for (position, probabilities) in &self.probabilities {
+            writer.write_all(&position.position_key())?;
+            for value in [
+                probabilities.win_normal,
+                probabilities.win_gammon,
+                probabilities.lose_normal,
+                probabilities.lose_gammon,
+            ] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Number of ongoing positions in the database.
+    pub fn len(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+    use crate::hypergammon::{game_state, HypergammonEvaluator};
+    use crate::pos;
+    use crate::position::GameResult::{LoseNormal, WinGammon, WinNormal};
+    use crate::position::GameState::{GameOver, Ongoing};
+    use crate::position::HYPERGAMMON_STARTING;
+    use crate::probabilities::Probabilities;
+    use std::collections::HashMap;
+
+    #[test]
+    fn game_state_with_hypergammon_gammons() {
+        assert_eq!(game_state(&HYPERGAMMON_STARTING), Ongoing);
+        // `o` has still all three checkers on the board
+        let gammon = pos!(x 24:3; o).sides_switched();
+        assert_eq!(game_state(&gammon), GameOver(WinGammon));
+        // `o` has borne off one checker
+        let normal = pos!(x 24:2; o).sides_switched();
+        assert_eq!(game_state(&normal), GameOver(WinNormal));
+        assert_eq!(game_state(&normal.sides_switched()), GameOver(LoseNormal));
+    }
+
+    #[test]
+    fn write_and_read() {
+        // Given
+        let probabilities = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.25,
+            lose_normal: 0.125,
+            lose_gammon: 0.125,
+        };
+        let database = HypergammonEvaluator::new(HashMap::from([(
+            HYPERGAMMON_STARTING,
+            probabilities.clone(),
+        )]));
+        let mut bytes: Vec<u8> = Vec::new();
+        // When
+        database.write(&mut bytes).unwrap();
+        let read = HypergammonEvaluator::from_reader(&mut bytes.as_slice()).unwrap();
+        // Then
+        assert_eq!(read.len(), 1);
+        assert_eq!(read.eval(&HYPERGAMMON_STARTING), probabilities);
+        assert!(HypergammonEvaluator::from_reader(&mut [0u8; 5].as_slice()).is_err());
+    }
+}
@@ -1,9 +1,20 @@
+mod gnubg;
+mod tesauro;
+
 use crate::position::{Position, X_BAR};
+pub use gnubg::GnubgInputsGen;
+pub use tesauro::TesauroInputsGen;
 
 pub trait InputsGen {
     /// The number of inputs for the neural network.
     const NUM_INPUTS: usize;
 
+    /// Identifies the encoding of the inputs. Change it whenever the meaning of an input changes.
+    ///
+    /// Neural nets store the version they were trained for in their metadata, see
+    /// [INPUTS_VERSION_KEY](crate::onnx::INPUTS_VERSION_KEY).
+    const INPUTS_VERSION: &'static str;
+
     /// Fill the given slice with the neural net inputs for a single position.
     /// The slice is expected to have a length of `NUM_INPUTS`.
     ///
@@ -125,6 +136,7 @@ mod contact {
 
 impl InputsGen for ContactInputsGen {
     const NUM_INPUTS: usize = 202;
+    const INPUTS_VERSION: &'static str = "contact-1";
 
     fn fill_inputs(&self, pos: &Position, inputs: &mut [f32]) {
         use contact::*;
@@ -158,6 +170,7 @@ mod race {
 
 impl InputsGen for RaceInputsGen {
     const NUM_INPUTS: usize = 186;
+    const INPUTS_VERSION: &'static str = "race-1";
 
     fn fill_inputs(&self, pos: &Position, inputs: &mut [f32]) {
         use race::*;
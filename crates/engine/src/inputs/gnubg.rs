@@ -0,0 +1,771 @@
+use crate::inputs::InputsGen;
+use crate::position::{Position, O_BAR, X_BAR};
+
+// The 250 inputs of the contact and crashed nets of GNU Backgammon, see `CalculateContactInputs`
+// in its `eval.c`. Each player is described by a board of 25 points from their own point of view:
+// index 0 is their 1 point, index 24 their bar.
+//
+// The first 200 inputs encode the checkers on each point, 100 per player and starting with the
+// opponent of the player on roll. They are followed by 25 hand-crafted inputs per player, like
+// the number of shots or how well the opponent's back checkers are contained. As in GnuBG these
+// are mixed up: the first group contains the men off of the opponent, but everything else of the
+// player on roll, and vice versa.
+//
+// The tests derive their expected values by hand from the formulas in `eval.c`. They have not
+// been compared with inputs printed by GnuBG itself yet; until then, nets of GnuBG may not be
+// evaluated with these inputs.
+
+/// Number of checkers on each point of one player, index 24 is the bar.
+type Board = [usize; 25];
+
+/// The slice indices of the 25 inputs following the 200 base inputs, as in GnuBG.
+mod more {
+    pub const OFF1: usize = 0;
+    pub const OFF2: usize = 1;
+    pub const OFF3: usize = 2;
+    pub const BREAK_CONTACT: usize = 3;
+    pub const BACK_CHECKER: usize = 4;
+    pub const BACK_ANCHOR: usize = 5;
+    pub const FORWARD_ANCHOR: usize = 6;
+    pub const PIP_LOSS: usize = 7;
+    pub const P1: usize = 8;
+    pub const P2: usize = 9;
+    pub const BACK_ESCAPES: usize = 10;
+    pub const A_CONTAIN: usize = 11;
+    pub const A_CONTAIN2: usize = 12;
+    pub const CONTAIN: usize = 13;
+    pub const CONTAIN2: usize = 14;
+    pub const MOBILITY: usize = 15;
+    pub const MOMENT2: usize = 16;
+    pub const ENTER: usize = 17;
+    pub const ENTER2: usize = 18;
+    pub const TIMING: usize = 19;
+    pub const BACKBONE: usize = 20;
+    pub const BACK_GAME: usize = 21;
+    pub const BACK_GAME1: usize = 22;
+    pub const FREE_PIP: usize = 23;
+    pub const BACK_R_ESCAPES: usize = 24;
+    pub const NUM: usize = 25;
+}
+
+/// Inputs compatible with the contact and crashed nets of GNU Backgammon.
+///
+/// Useful for comparing our nets with the ones of GnuBG on exactly the same information.
+pub struct GnubgInputsGen {}
+
+impl InputsGen for GnubgInputsGen {
+    const NUM_INPUTS: usize = 250;
+    const INPUTS_VERSION: &'static str = "gnubg-1";
+
+    fn fill_inputs(&self, pos: &Position, inputs: &mut [f32]) {
+        // Help the compiler to check less bounds by giving exact size
+        let inputs = <&mut [f32; Self::NUM_INPUTS]>::try_from(inputs).unwrap();
+
+        let (on_roll, opponent) = boards(pos);
+        base_inputs(&opponent, &mut inputs[0..100]);
+        base_inputs(&on_roll, &mut inputs[100..200]);
+
+        let (first, second) = inputs[200..].split_at_mut(more::NUM);
+        men_off(&opponent, first);
+        half_inputs(&on_roll, &opponent, first);
+        men_off(&on_roll, second);
+        half_inputs(&opponent, &on_roll, second);
+    }
+}
+
+/// Boards of the player on roll and of the opponent.
+fn boards(pos: &Position) -> (Board, Board) {
+    let mut on_roll = [0; 25];
+    let mut opponent = [0; 25];
+    for i in 0..24 {
+        on_roll[i] = pos.pips[i + 1].max(0) as usize;
+        opponent[i] = (-pos.pips[24 - i]).max(0) as usize;
+    }
+    on_roll[24] = pos.pips[X_BAR] as usize;
+    opponent[24] = (-pos.pips[O_BAR]) as usize;
+    (on_roll, opponent)
+}
+
+/// 4 inputs for each point. The bar is encoded slightly differently.
+fn base_inputs(board: &Board, inputs: &mut [f32]) {
+    let many = |checkers: usize| {
+        if checkers > 3 {
+            (checkers - 3) as f32 / 2.0
+        } else {
+            0.0
+        }
+    };
+    for (i, &checkers) in board.iter().enumerate().take(24) {
+        inputs[4 * i] = (checkers == 1) as u8 as f32;
+        inputs[4 * i + 1] = (checkers == 2) as u8 as f32;
+        inputs[4 * i + 2] = (checkers >= 3) as u8 as f32;
+        inputs[4 * i + 3] = many(checkers);
+    }
+    let bar = board[24];
+    inputs[96] = (bar >= 1) as u8 as f32;
+    inputs[97] = (bar >= 2) as u8 as f32;
+    inputs[98] = (bar >= 3) as u8 as f32;
+    inputs[99] = many(bar);
+}
+
+/// Checkers borne off, in three steps of 5.
+fn men_off(board: &Board, inputs: &mut [f32]) {
+    let off = 15 - board.iter().sum::<usize>();
+    let step = |from: usize| (off.saturating_sub(from).min(5)) as f32 / 5.0;
+    inputs[more::OFF1] = step(0);
+    inputs[more::OFF2] = step(5);
+    inputs[more::OFF3] = step(10);
+}
+
+/// One way to hit a blot with some or all dice of a roll.
+#[derive(Clone, Copy)]
+struct Shot {
+    /// `true` if all intermediate points must be open, `false` if one of two is enough.
+    all: bool,
+    /// Distances of the intermediate points from the hitter, `0` for unused entries.
+    intermediate: [usize; 3],
+    /// Number of dice used.
+    faces: usize,
+    pips: usize,
+}
+
+/// All 39 shots: 6 direct ones, 15 with two different dice and 18 with 2, 3 or 4 dice of a double.
+const SHOTS: [Shot; 39] = all_shots();
+
+/// Indices into [SHOTS] for each of the 21 rolls.
+const ROLLS: [[Option<usize>; 4]; 21] = rolls();
+
+/// Indices into [SHOTS] for each distance from 1 to 24 pips.
+const SHOTS_BY_DISTANCE: [[Option<usize>; 5]; 24] = shots_by_distance();
+
+const fn direct_shot(die: usize) -> usize {
+    die - 1
+}
+
+const fn combined_shot(low: usize, high: usize) -> usize {
+    // Shots with a low die of 1 come first, there are `6 - low` of them per low die.
+    let mut index = 6;
+    let mut die = 1;
+    while die < low {
+        index += 6 - die;
+        die += 1;
+    }
+    index + high - low - 1
+}
+
+const fn double_shot(die: usize, faces: usize) -> usize {
+    21 + 3 * (die - 1) + faces - 2
+}
+
+const fn all_shots() -> [Shot; 39] {
+    let mut shots = [Shot {
+        all: true,
+        intermediate: [0; 3],
+        faces: 1,
+        pips: 0,
+    }; 39];
+    let mut low = 1;
+    while low <= 6 {
+        shots[direct_shot(low)].pips = low;
+        let mut high = low + 1;
+        while high <= 6 {
+            shots[combined_shot(low, high)] = Shot {
+                all: false,
+                intermediate: [low, high, 0],
+                faces: 2,
+                pips: low + high,
+            };
+            high += 1;
+        }
+        shots[double_shot(low, 2)] = Shot {
+            all: true,
+            intermediate: [low, 0, 0],
+            faces: 2,
+            pips: 2 * low,
+        };
+        shots[double_shot(low, 3)] = Shot {
+            all: true,
+            intermediate: [low, 2 * low, 0],
+            faces: 3,
+            pips: 3 * low,
+        };
+        shots[double_shot(low, 4)] = Shot {
+            all: true,
+            intermediate: [low, 2 * low, 3 * low],
+            faces: 4,
+            pips: 4 * low,
+        };
+        low += 1;
+    }
+    shots
+}
+
+const fn rolls() -> [[Option<usize>; 4]; 21] {
+    let mut rolls = [[None; 4]; 21];
+    let mut index = 0;
+    let mut low = 1;
+    while low <= 6 {
+        let mut high = low;
+        while high <= 6 {
+            rolls[index] = if low == high {
+                [
+                    Some(direct_shot(low)),
+                    Some(double_shot(low, 2)),
+                    Some(double_shot(low, 3)),
+                    Some(double_shot(low, 4)),
+                ]
+            } else {
+                [
+                    Some(direct_shot(low)),
+                    Some(direct_shot(high)),
+                    Some(combined_shot(low, high)),
+                    None,
+                ]
+            };
+            index += 1;
+            high += 1;
+        }
+        low += 1;
+    }
+    rolls
+}
+
+const fn shots_by_distance() -> [[Option<usize>; 5]; 24] {
+    let shots = all_shots();
+    let mut by_distance = [[None; 5]; 24];
+    let mut index = 0;
+    while index < shots.len() {
+        let distance = shots[index].pips - 1;
+        let mut slot = 0;
+        while by_distance[distance][slot].is_some() {
+            slot += 1;
+        }
+        by_distance[distance][slot] = Some(index);
+        index += 1;
+    }
+    by_distance
+}
+
+/// For each combination of blocked points in front of a checker (bit `i` for the point `i + 1`
+/// pips away), the number of the 36 rolls with which the checker can be moved by both dice.
+const ESCAPES: [u8; 4096] = escapes_table(false);
+
+/// Like [ESCAPES], but only rolls which move the checker beyond the nearest blocked point count.
+const ESCAPES1: [u8; 4096] = escapes_table(true);
+
+const fn escapes_table(beyond_nearest: bool) -> [u8; 4096] {
+    let mut table = [0; 4096];
+    let mut mask = 0;
+    while mask < 4096 {
+        let mut nearest = 0;
+        while nearest < 12 && mask & (1 << nearest) == 0 {
+            nearest += 1;
+        }
+        let mut count = 0;
+        let mut n0 = 0;
+        while n0 <= 5 {
+            let mut n1 = 0;
+            while n1 <= n0 {
+                let lands = mask & (1 << (n0 + n1 + 1)) == 0;
+                let passes = mask & (1 << n0) == 0 || mask & (1 << n1) == 0;
+                let far_enough = !beyond_nearest || n0 + n1 + 1 > nearest;
+                if lands && passes && far_enough {
+                    count += if n0 == n1 { 1 } else { 2 };
+                }
+                n1 += 1;
+            }
+            n0 += 1;
+        }
+        table[mask] = count;
+        mask += 1;
+    }
+    table
+}
+
+/// Escapes of an opponent's checker on its point `n` (0-based) past the points made on `board`.
+fn escapes(table: &[u8; 4096], board: &Board, n: isize) -> usize {
+    let mut mask = 0;
+    for i in 0..n.min(12) {
+        if board[(24 + i - n) as usize] >= 2 {
+            mask |= 1 << i;
+        }
+    }
+    table[mask] as usize
+}
+
+/// The 22 hand-crafted inputs of `board`, which is also responsible for the men off of the other player.
+fn half_inputs(board: &Board, opp: &Board, inputs: &mut [f32]) {
+    use more::*;
+
+    let opp_back = (0..25)
+        .rev()
+        .find(|&i| opp[i] > 0)
+        .map_or(-1, |i| i as isize);
+    // Number of points between the opponent's back checker and our bar.
+    let opp_back_distance = 23 - opp_back;
+
+    let break_contact: isize = ((opp_back_distance + 1).max(0)..25)
+        .map(|i| (i + 1 - opp_back_distance) * board[i as usize] as isize)
+        .sum();
+    inputs[BREAK_CONTACT] = break_contact as f32 / (15.0 + 152.0);
+
+    let free_pip: usize = (0..opp_back_distance.max(0) as usize)
+        .map(|i| (i + 1) * board[i])
+        .sum();
+    inputs[FREE_PIP] = free_pip as f32 / 100.0;
+
+    inputs[TIMING] = timing(board, opp_back_distance) as f32 / 100.0;
+
+    let back = (0..25)
+        .rev()
+        .find(|&i| board[i] > 0)
+        .map_or(-1, |i| i as isize);
+    inputs[BACK_CHECKER] = back as f32 / 24.0;
+    let anchor_from = if back == 24 { 23 } else { back };
+    let back_anchor = (0..=anchor_from)
+        .rev()
+        .find(|&i| board[i as usize] >= 2)
+        .unwrap_or(-1);
+    inputs[BACK_ANCHOR] = back_anchor as f32 / 24.0;
+    let forward_anchor = (18..=back_anchor)
+        .find(|&i| board[i as usize] >= 2)
+        .or_else(|| (12..=17).rev().find(|&i| board[i as usize] >= 2))
+        .map_or(0, |i| 24 - i);
+    inputs[FORWARD_ANCHOR] = if forward_anchor == 0 {
+        2.0
+    } else {
+        forward_anchor as f32 / 6.0
+    };
+
+    let (pip_loss, p1, p2) = shots(board, opp);
+    inputs[PIP_LOSS] = pip_loss as f32 / (12.0 * 36.0);
+    inputs[P1] = p1 as f32 / 36.0;
+    inputs[P2] = p2 as f32 / 36.0;
+
+    inputs[BACK_ESCAPES] = escapes(&ESCAPES, board, opp_back) as f32 / 36.0;
+    inputs[BACK_R_ESCAPES] = escapes(&ESCAPES1, board, opp_back) as f32 / 36.0;
+
+    let min_escapes = |to: isize| {
+        (15..to)
+            .map(|i| escapes(&ESCAPES, board, i))
+            .fold(36, usize::min)
+    };
+    inputs[A_CONTAIN] = (36 - min_escapes(24 - opp_back_distance)) as f32 / 36.0;
+    inputs[A_CONTAIN2] = inputs[A_CONTAIN] * inputs[A_CONTAIN];
+    inputs[CONTAIN] = (36 - min_escapes(24)) as f32 / 36.0;
+    inputs[CONTAIN2] = inputs[CONTAIN] * inputs[CONTAIN];
+
+    let mobility: usize = (6..25)
+        .map(|i| (i - 5) * board[i] * escapes(&ESCAPES, opp, i as isize))
+        .sum();
+    inputs[MOBILITY] = mobility as f32 / 3600.0;
+
+    inputs[MOMENT2] = moment2(board) as f32 / 400.0;
+
+    if board[24] > 0 {
+        inputs[ENTER] = dance_loss(opp) as f32 / (36.0 * (49.0 / 6.0));
+        let closed = (0..6).filter(|&i| opp[i] >= 2).count() as f32;
+        inputs[ENTER2] = (36.0 - (closed - 6.0) * (closed - 6.0)) / 36.0;
+    } else {
+        inputs[ENTER] = 0.0;
+        inputs[ENTER2] = 0.0;
+    }
+
+    inputs[BACKBONE] = backbone(board);
+
+    let anchors = (18..24).filter(|&i| board[i] >= 2).count();
+    let in_opponents_home: usize = board[18..25].iter().sum();
+    inputs[BACK_GAME] = if anchors > 1 {
+        (in_opponents_home as f32 - 3.0) / 4.0
+    } else {
+        0.0
+    };
+    inputs[BACK_GAME1] = if anchors == 1 {
+        in_opponents_home as f32 / 8.0
+    } else {
+        0.0
+    };
+}
+
+/// Pips that can be played without breaking the home board, before the points in front of the
+/// opponent's back checker must be given up.
+fn timing(board: &Board, opp_back_distance: isize) -> isize {
+    let mut pips = 24 * board[24] as isize;
+    let mut checkers = board[24] as isize;
+    let mut i: isize = 23;
+    // Outside the opponent's reach, spare checkers and blots can be played.
+    while i >= 12 && i > opp_back_distance {
+        let n = board[i as usize] as isize;
+        if n > 0 && n != 2 {
+            let spare = if n > 2 { n - 2 } else { 1 };
+            checkers += spare;
+            pips += i * spare;
+        }
+        i -= 1;
+    }
+    while i >= 6 {
+        let n = board[i as usize] as isize;
+        checkers += n;
+        pips += i * n;
+        i -= 1;
+    }
+    // In the home board, checkers are needed to fill the points.
+    for i in (0..6).rev() {
+        let n = board[i] as isize;
+        if n > 2 {
+            pips += i as isize * (n - 2);
+            checkers += n - 2;
+        } else if n < 2 && checkers >= 2 - n {
+            pips -= i as isize * (2 - n);
+            checkers -= 2 - n;
+        }
+    }
+    pips.max(0)
+}
+
+/// Second moment of the checkers behind their average point, rounded up.
+fn moment2(board: &Board) -> usize {
+    let checkers: usize = board.iter().sum();
+    let pips: usize = board.iter().enumerate().map(|(i, n)| i * n).sum();
+    let average = if checkers > 0 {
+        pips.div_ceil(checkers)
+    } else {
+        0
+    };
+    let behind: usize = board[(average + 1).min(25)..].iter().sum();
+    let squares: usize = (average + 1..25)
+        .map(|i| board[i] * (i - average) * (i - average))
+        .sum();
+    if behind > 0 {
+        squares.div_ceil(behind)
+    } else {
+        0
+    }
+}
+
+/// Pips of all rolls with which a checker on the bar can't enter.
+fn dance_loss(opp: &Board) -> usize {
+    let mut loss = 0;
+    for low in 0..6 {
+        for high in low..6 {
+            if opp[low] >= 2 && opp[high] >= 2 {
+                loss += if low == high {
+                    4 * (low + 1)
+                } else {
+                    2 * (low + high + 2)
+                };
+            }
+        }
+    }
+    loss
+}
+
+/// How well the points are connected, `0` if there are no two points.
+fn backbone(board: &Board) -> f32 {
+    let mut previous: Option<usize> = None;
+    let mut weighted = 0;
+    let mut total = 0;
+    for point in (1..24).rev() {
+        if board[point] >= 2 {
+            if let Some(previous) = previous {
+                let distance = previous - point;
+                let connection = if distance <= 6 {
+                    11
+                } else if distance <= 11 {
+                    13 - distance
+                } else {
+                    0
+                };
+                weighted += connection * board[previous];
+                total += board[previous];
+            }
+            previous = Some(point);
+        }
+    }
+    if total > 0 {
+        1.0 - weighted as f32 / (total as f32 * 11.0)
+    } else {
+        0.0
+    }
+}
+
+/// Shots of `board` at blots of `opp`: pips lost by the opponent, rolls hitting at least one
+/// checker and rolls hitting at least two checkers. Rolls other than doubles count twice.
+fn shots(board: &Board, opp: &Board) -> (usize, usize, usize) {
+    // For each of the 39 shots, bit `j` is set if a checker on point `j` can hit with that shot.
+    let mut hits = [0u32; 39];
+    let home_points = (0..6).filter(|&i| board[i] > 0).count();
+    let furthest_blot = if home_points > 2 { 23 } else { 21 };
+    for blot in (0..=furthest_blot).rev() {
+        if opp[blot] != 1 {
+            continue;
+        }
+        // From our point of view, the blot is on point `23 - blot`.
+        for hitter in 24 - blot..25 {
+            // We don't break a home board point to hit.
+            if board[hitter] == 0 || (hitter < 6 && board[hitter] == 2) {
+                continue;
+            }
+            for shot_index in SHOTS_BY_DISTANCE[hitter + blot - 24].iter().flatten() {
+                let shot = &SHOTS[*shot_index];
+                let blocked = |distance: &usize| opp[blot - distance] > 1;
+                let possible = if shot.all {
+                    shot.intermediate
+                        .iter()
+                        .take_while(|&&distance| distance > 0)
+                        .all(|distance| !blocked(distance))
+                } else {
+                    !shot.intermediate[..2].iter().all(blocked)
+                };
+                if possible {
+                    hits[*shot_index] |= 1 << hitter;
+                }
+            }
+        }
+    }
+
+    let mut checkers = [0; 21];
+    let mut pips = [0; 21];
+    let highest = |bits: u32| 31 - bits.leading_zeros() as usize;
+    for (roll, shot_indices) in ROLLS.iter().enumerate() {
+        let mut add_pips = |from: usize, shot: &Shot| {
+            pips[roll] = pips[roll].max(from + 1 - shot.pips);
+        };
+        match board[24] {
+            0 => {
+                let mut last_hitter = None;
+                for &shot_index in shot_indices.iter().flatten() {
+                    if hits[shot_index] == 0 {
+                        continue;
+                    }
+                    let shot = &SHOTS[shot_index];
+                    let hitter = highest(hits[shot_index]);
+                    add_pips(hitter, shot);
+                    if shot.faces == 1 {
+                        if last_hitter != Some(hitter) || board[hitter] > 1 {
+                            checkers[roll] += 1;
+                        }
+                        last_hitter = Some(hitter);
+                        // Doubles can hit with several checkers directly.
+                        if shot_indices[3].is_some() && hits[shot_index] & !(1 << hitter) != 0 {
+                            checkers[roll] += 1;
+                        }
+                    } else {
+                        checkers[roll] = checkers[roll].max(1);
+                        // Another blot might be hit on the way.
+                        if shot
+                            .intermediate
+                            .iter()
+                            .take_while(|&&distance| distance > 0)
+                            .any(|distance| opp[23 - hitter + distance] == 1)
+                        {
+                            checkers[roll] += 1;
+                        }
+                    }
+                }
+            }
+            1 => {
+                // The other die might be needed to enter.
+                let mut entered = false;
+                for (j, &shot_index) in shot_indices.iter().enumerate() {
+                    let Some(shot_index) = shot_index else {
+                        break;
+                    };
+                    if hits[shot_index] == 0 {
+                        continue;
+                    }
+                    let shot = &SHOTS[shot_index];
+                    if shot.faces == 1 {
+                        for hitter in (1..25).rev() {
+                            if hits[shot_index] & (1 << hitter) == 0 {
+                                continue;
+                            }
+                            if entered && hitter != 24 {
+                                break;
+                            }
+                            if hitter != 24 {
+                                let other = shot_indices[1 - j.min(1)].map_or(0, |i| SHOTS[i].pips);
+                                if other == 0 || opp[other - 1] > 1 {
+                                    break;
+                                }
+                                entered = true;
+                            }
+                            checkers[roll] += 1;
+                            add_pips(hitter, shot);
+                        }
+                    } else if hits[shot_index] & (1 << 24) != 0 {
+                        checkers[roll] = checkers[roll].max(1);
+                        add_pips(24, shot);
+                        if shot
+                            .intermediate
+                            .iter()
+                            .take_while(|&&distance| distance > 0)
+                            .any(|distance| opp[distance - 1] == 1)
+                        {
+                            checkers[roll] += 1;
+                        }
+                    }
+                }
+            }
+            _ => {
+                // Only direct shots from the bar with the first two shots of the roll.
+                for shot_index in shot_indices[..2].iter().flatten() {
+                    let shot = &SHOTS[*shot_index];
+                    if hits[*shot_index] & (1 << 24) != 0 && shot.faces == 1 {
+                        checkers[roll] += 1;
+                        add_pips(24, shot);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut pip_loss = 0;
+    let mut p1 = 0;
+    let mut p2 = 0;
+    for (roll, shot_indices) in ROLLS.iter().enumerate() {
+        let weight = if shot_indices[3].is_some() { 1 } else { 2 };
+        pip_loss += pips[roll] * weight;
+        if checkers[roll] > 0 {
+            p1 += weight;
+        }
+        if checkers[roll] > 1 {
+            p2 += weight;
+        }
+    }
+    (pip_loss, p1, p2)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs::gnubg::{escapes, more, Board, ESCAPES, ESCAPES1, SHOTS_BY_DISTANCE};
+    use crate::inputs::{GnubgInputsGen, InputsGen};
+    use crate::pos;
+    use crate::position::STARTING;
+
+    #[test]
+    fn starting_position() {
+        // When
+        let inputs = GnubgInputsGen {}.inputs_for_single(&STARTING);
+        // Then
+        assert_eq!(inputs.len(), 250);
+        // Both sides are the same.
+        assert_eq!(inputs[0..100], inputs[100..200]);
+        assert_eq!(inputs[200..225], inputs[225..250]);
+        // 2 checkers on the 24 point
+        assert_eq!(inputs[100 + 4 * 23..100 + 4 * 23 + 4], [0.0, 1.0, 0.0, 0.0]);
+        // 5 checkers on the 13 point
+        assert_eq!(inputs[100 + 4 * 12..100 + 4 * 12 + 4], [0.0, 0.0, 1.0, 1.0]);
+        let more = &inputs[200..225];
+        assert_eq!(more[more::OFF1], 0.0);
+        assert_eq!(more[more::BREAK_CONTACT], 1.0);
+        assert_eq!(more[more::BACK_CHECKER], 23.0 / 24.0);
+        assert_eq!(more[more::BACK_ANCHOR], 23.0 / 24.0);
+        assert_eq!(more[more::FORWARD_ANCHOR], 1.0 / 6.0);
+        assert_eq!(more[more::FREE_PIP], 0.0);
+        assert_eq!(more[more::P1], 0.0);
+        assert_eq!(more[more::ENTER], 0.0);
+    }
+
+    #[test]
+    fn starting_position_hand_derived_vector() {
+        // The expected values are derived by hand from the formulas in GnuBG's `eval.c`, not
+        // printed by GnuBG itself. Replace them with a vector dumped by GnuBG when it's at hand.
+        // Given
+        let mut points = [[0.0; 4]; 25];
+        points[5] = [0.0, 0.0, 1.0, 1.0];
+        points[7] = [0.0, 0.0, 1.0, 0.0];
+        points[12] = [0.0, 0.0, 1.0, 1.0];
+        points[23] = [0.0, 1.0, 0.0, 0.0];
+        let more: [f32; 25] = [
+            0.0,                // OFF1
+            0.0,                // OFF2
+            0.0,                // OFF3
+            1.0,                // BREAK_CONTACT, 167 / (15 + 152)
+            23.0 / 24.0,        // BACK_CHECKER
+            23.0 / 24.0,        // BACK_ANCHOR
+            1.0 / 6.0,          // FORWARD_ANCHOR
+            0.0,                // PIP_LOSS
+            0.0,                // P1
+            0.0,                // P2
+            24.0 / 36.0,        // BACK_ESCAPES
+            14.0 / 36.0,        // A_CONTAIN
+            0.0,                // A_CONTAIN2, checked below
+            14.0 / 36.0,        // CONTAIN
+            0.0,                // CONTAIN2, checked below
+            2234.0 / 3600.0,    // MOBILITY
+            42.0 / 400.0,       // MOMENT2
+            0.0,                // ENTER
+            0.0,                // ENTER2
+            52.0 / 100.0,       // TIMING
+            1.0 - 92.0 / 110.0, // BACKBONE
+            0.0,                // BACK_GAME
+            2.0 / 8.0,          // BACK_GAME1
+            0.0,                // FREE_PIP
+            18.0 / 36.0,        // BACK_R_ESCAPES
+        ];
+        let mut expected: Vec<f32> = points.iter().flatten().copied().collect();
+        expected.extend(expected.clone());
+        expected.extend(more);
+        expected.extend(more);
+        for i in [200, 225] {
+            expected[i + more::A_CONTAIN2] = expected[i + more::A_CONTAIN].powi(2);
+            expected[i + more::CONTAIN2] = expected[i + more::CONTAIN].powi(2);
+        }
+        // When
+        let inputs = GnubgInputsGen {}.inputs_for_single(&STARTING);
+        // Then
+        for (i, (input, expected)) in inputs.iter().zip(expected).enumerate() {
+            assert!(
+                (input - expected).abs() < 1e-6,
+                "input {i}: {input} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn direct_and_indirect_shots() {
+        // Given
+        // A single hitter 6 pips away from the blot, nothing in between.
+        let position = pos!(x 13:1, 1:14; o 7:1, 24:14);
+        // When
+        let inputs = GnubgInputsGen {}.inputs_for_single(&position);
+        // Then
+        let more = &inputs[200..225];
+        // 11 rolls with a 6, 5-1, 4-2, 3-3 and 2-2.
+        assert_eq!(more[more::P1], 17.0 / 36.0);
+        assert_eq!(more[more::P2], 0.0);
+        assert_eq!(more[more::PIP_LOSS], 7.0 * 17.0 / (12.0 * 36.0));
+    }
+
+    #[test]
+    fn men_off() {
+        let position = pos!(x 1:3; o 24:8);
+
+        let inputs = GnubgInputsGen {}.inputs_for_single(&position);
+
+        // The men off of the opponent come first.
+        assert_eq!(inputs[200..203], [1.0, 0.4, 0.0]);
+        assert_eq!(inputs[225..228], [1.0, 1.0, 0.4]);
+    }
+
+    #[test]
+    fn escapes_from_a_prime() {
+        let mut board: Board = [0; 25];
+        board[6..12].fill(2);
+        // The opponent's checker on its 19 point (index 18) is behind the prime.
+        assert_eq!(escapes(&ESCAPES, &board, 18), 0);
+        // Without any points, all rolls escape.
+        assert_eq!(escapes(&ESCAPES, &[0; 25], 18), 36);
+        assert_eq!(escapes(&ESCAPES1, &[0; 25], 18), 0);
+    }
+
+    #[test]
+    fn shots_by_distance() {
+        let count = |distance: usize| SHOTS_BY_DISTANCE[distance - 1].iter().flatten().count();
+        assert_eq!(count(1), 1);
+        // 6, 5-1, 4-2, 3-3, 2-2
+        assert_eq!(count(6), 5);
+        assert_eq!(count(24), 1);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::inputs::InputsGen;
+use crate::position::{Position, O_BAR, X_BAR};
+
+/// The 198 inputs of TD-Gammon as described by Tesauro.
+///
+/// Unlike [super::ContactInputsGen] there are no separate race inputs and the inputs for more than
+/// three checkers on a point are halved. The last two inputs encode whose turn it is; as positions
+/// are always seen from the player on roll, they never change. Mainly useful as a baseline to
+/// compare other encodings against.
+pub struct TesauroInputsGen {}
+
+/// The slice indices for the inputs
+mod index {
+    pub const X_PIPS: usize = 0;
+    pub const X_BAR: usize = X_PIPS + 24 * 4;
+    pub const X_OFF: usize = X_BAR + 1;
+    pub const O_PIPS: usize = X_OFF + 1;
+    pub const O_BAR: usize = O_PIPS + 24 * 4;
+    pub const O_OFF: usize = O_BAR + 1;
+    pub const X_TURN: usize = O_OFF + 1;
+    pub const O_TURN: usize = X_TURN + 1;
+}
+
+/// 4 inputs for a single point with `checkers` of one player.
+#[inline]
+fn point_inputs(checkers: i8) -> [f32; 4] {
+    match checkers {
+        ..=0 => [0., 0., 0., 0.],
+        1 => [1., 0., 0., 0.],
+        2 => [1., 1., 0., 0.],
+        _ => [1., 1., 1., (checkers - 3) as f32 / 2.],
+    }
+}
+
+impl InputsGen for TesauroInputsGen {
+    const NUM_INPUTS: usize = 198;
+    const INPUTS_VERSION: &'static str = "tesauro-1";
+
+    fn fill_inputs(&self, pos: &Position, inputs: &mut [f32]) {
+        // Help the compiler to check less bounds by giving exact size
+        let inputs = <&mut [f32; Self::NUM_INPUTS]>::try_from(inputs).unwrap();
+
+        // Both players are encoded on the same board, seen from the player on roll.
+        for (point, &pips) in pos.pips[1..X_BAR].iter().enumerate() {
+            let x_start = index::X_PIPS + 4 * point;
+            inputs[x_start..x_start + 4].copy_from_slice(&point_inputs(pips));
+            let o_start = index::O_PIPS + 4 * point;
+            inputs[o_start..o_start + 4].copy_from_slice(&point_inputs(-pips));
+        }
+        inputs[index::X_BAR] = pos.pips[X_BAR] as f32 / 2.;
+        inputs[index::X_OFF] = pos.x_off() as f32 / 15.;
+        inputs[index::O_BAR] = -pos.pips[O_BAR] as f32 / 2.;
+        inputs[index::O_OFF] = pos.o_off() as f32 / 15.;
+        inputs[index::X_TURN] = 1.;
+        inputs[index::O_TURN] = 0.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs::{InputsGen, TesauroInputsGen};
+    use crate::pos;
+    use crate::position::STARTING;
+
+    #[test]
+    fn starting_position() {
+        let inputs = TesauroInputsGen {}.inputs_for_single(&STARTING);
+        assert_eq!(inputs.len(), 198);
+        // Own 6 point with 5 checkers
+        assert_eq!(inputs[20..24], [1., 1., 1., 1.]);
+        // Opponent's 6 point is our 19 point
+        assert_eq!(inputs[98 + 72..98 + 76], [1., 1., 1., 1.]);
+        // Nothing on the bar or borne off, own turn
+        assert_eq!(inputs[96..98], [0., 0.]);
+        assert_eq!(inputs[194..198], [0., 0., 1., 0.]);
+    }
+
+    #[test]
+    fn bar_and_off() {
+        let position = pos!(x 25:2, 3:4; o 0:1, 22:3);
+        let inputs = TesauroInputsGen {}.inputs_for_single(&position);
+        assert_eq!(inputs[8..12], [1., 1., 1., 0.5]);
+        assert_eq!(inputs[96..98], [1., 9. / 15.]);
+        assert_eq!(inputs[98 + 84..98 + 88], [1., 1., 1., 0.]);
+        assert_eq!(inputs[194..196], [0.5, 11. / 15.]);
+    }
+}
@@ -1,9 +1,19 @@
+pub mod bearoff;
+pub mod budget;
 pub mod composite;
 pub mod dice;
 pub mod dice_gen;
 pub mod evaluator;
+pub mod hypergammon;
 pub mod inputs;
+pub mod manifest;
 pub mod multiply;
+pub mod noise;
 pub mod onnx;
+pub mod opening_book;
 pub mod position;
 pub mod probabilities;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+mod time;
+pub mod variant;
@@ -0,0 +1,202 @@
+use crate::composite::CompositeEvaluator;
+use crate::evaluator::Evaluator;
+use crate::hypergammon::HypergammonEvaluator;
+use crate::position::Position;
+use crate::probabilities::Probabilities;
+use crate::variant::{EvalContext, Variant};
+use std::collections::HashMap;
+
+type Error = String;
+
+/// Files needed to evaluate positions of one variant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModelFiles {
+    /// Onnx neural nets for contact and race positions.
+    Nets { contact: String, race: String },
+    /// Exact database, as created by the coach for small variants.
+    Database(String),
+}
+
+/// Maps each variant to the nets or database used for evaluation.
+///
+/// Variants without an entry can't be evaluated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelManifest {
+    models: HashMap<Variant, ModelFiles>,
+}
+
+impl Default for ModelManifest {
+    /// Nackgammon doesn't have its own nets yet, so it uses the ones of standard backgammon.
+    fn default() -> Self {
+        let standard_nets = ModelFiles::Nets {
+            contact: "neural-nets/contact.onnx".to_string(),
+            race: "neural-nets/race.onnx".to_string(),
+        };
+        Self {
+            models: HashMap::from([
+                (Variant::Standard, standard_nets.clone()),
+                (Variant::Nackgammon, standard_nets),
+                (
+                    Variant::Hypergammon,
+                    ModelFiles::Database("neural-nets/hypergammon.db".to_string()),
+                ),
+            ]),
+        }
+    }
+}
+
+impl ModelManifest {
+    /// A manifest without any variants. Add them with [ModelManifest::insert].
+    pub fn empty() -> Self {
+        Self {
+            models: HashMap::new(),
+        }
+    }
+
+    /// Replaces the files of `variant` if it was already part of the manifest.
+    pub fn insert(&mut self, variant: Variant, files: ModelFiles) {
+        self.models.insert(variant, files);
+    }
+
+    pub fn get(&self, variant: Variant) -> Option<&ModelFiles> {
+        self.models.get(&variant)
+    }
+}
+
+/// Evaluator for a single variant, loaded from [ModelFiles].
+pub enum VariantEvaluator {
+    Nets(CompositeEvaluator),
+    Database(HypergammonEvaluator),
+}
+
+impl Evaluator for VariantEvaluator {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        match self {
+            VariantEvaluator::Nets(evaluator) => evaluator.eval(pos),
+            VariantEvaluator::Database(evaluator) => evaluator.eval(pos),
+        }
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        match self {
+            VariantEvaluator::Nets(evaluator) => evaluator.eval_batch(positions),
+            VariantEvaluator::Database(evaluator) => evaluator.eval_batch(positions),
+        }
+    }
+}
+
+impl VariantEvaluator {
+    pub fn from_files(files: &ModelFiles) -> Result<Self, Error> {
+        match files {
+            ModelFiles::Nets { contact, race } => {
+                CompositeEvaluator::from_file_paths(contact, race).map(VariantEvaluator::Nets)
+            }
+            ModelFiles::Database(path) => {
+                HypergammonEvaluator::from_file_path(path).map(VariantEvaluator::Database)
+            }
+        }
+    }
+}
+
+/// Holds one evaluator per variant, so a single process can serve several variants.
+///
+/// Variants sharing the same files also share the loaded evaluator.
+pub struct MultiVariantEvaluator {
+    evaluators: Vec<VariantEvaluator>,
+    indices: HashMap<Variant, usize>,
+}
+
+impl MultiVariantEvaluator {
+    pub fn try_from_manifest(manifest: &ModelManifest) -> Result<Self, Error> {
+        let mut loaded: Vec<&ModelFiles> = Vec::new();
+        let mut evaluators = Vec::new();
+        let mut indices = HashMap::new();
+        for (variant, files) in &manifest.models {
+            let index = match loaded.iter().position(|other| *other == files) {
+                Some(index) => index,
+                None => {
+                    evaluators.push(VariantEvaluator::from_files(files)?);
+                    loaded.push(files);
+                    loaded.len() - 1
+                }
+            };
+            indices.insert(*variant, index);
+        }
+        Ok(Self {
+            evaluators,
+            indices,
+        })
+    }
+
+    /// `None` if the manifest didn't contain the variant.
+    pub fn evaluator(&self, variant: Variant) -> Option<&VariantEvaluator> {
+        self.indices
+            .get(&variant)
+            .map(|index| &self.evaluators[*index])
+    }
+
+    pub fn eval(&self, context: &EvalContext, pos: &Position) -> Result<Probabilities, Error> {
+        self.evaluator(context.variant)
+            .map(|evaluator| evaluator.eval(pos))
+            .ok_or_else(|| format!("No model configured for variant {:?}", context.variant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manifest::{ModelFiles, ModelManifest, MultiVariantEvaluator};
+    use crate::position::STARTING;
+    use crate::variant::{EvalContext, Variant};
+
+    fn standard_only() -> ModelManifest {
+        let mut manifest = ModelManifest::empty();
+        manifest.insert(
+            Variant::Standard,
+            ModelFiles::Nets {
+                contact: "../../neural-nets/contact.onnx".to_string(),
+                race: "../../neural-nets/race.onnx".to_string(),
+            },
+        );
+        manifest
+    }
+
+    #[test]
+    fn default_manifest_contains_all_variants() {
+        let manifest = ModelManifest::default();
+        assert_eq!(
+            manifest.get(Variant::Standard),
+            manifest.get(Variant::Nackgammon)
+        );
+        assert!(matches!(
+            manifest.get(Variant::Hypergammon),
+            Some(ModelFiles::Database(_))
+        ));
+    }
+
+    #[test]
+    fn eval_selects_by_variant() {
+        // Given
+        let evaluator = MultiVariantEvaluator::try_from_manifest(&standard_only()).unwrap();
+        // When
+        let standard = evaluator.eval(&EvalContext::default(), &STARTING);
+        let hypergammon = evaluator.eval(
+            &EvalContext {
+                variant: Variant::Hypergammon,
+            },
+            &STARTING,
+        );
+        // Then
+        assert!(standard.unwrap().is_consistent());
+        assert!(hypergammon.is_err());
+    }
+
+    #[test]
+    fn missing_database_is_an_error() {
+        let mut manifest = standard_only();
+        manifest.insert(
+            Variant::Hypergammon,
+            ModelFiles::Database("does-not-exist.db".to_string()),
+        );
+        assert!(MultiVariantEvaluator::try_from_manifest(&manifest).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::evaluator::Evaluator;
+use crate::position::Position;
+use crate::probabilities::Probabilities;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Playing strength of a bot, from beginner to the full strength of the engine.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Difficulty {
+    Beginner,
+    Casual,
+    Intermediate,
+    Advanced,
+    #[default]
+    Expert,
+}
+
+impl Difficulty {
+    /// Standard deviation of the noise added to the cubeless equity of each evaluated position.
+    ///
+    /// The values are chosen so that the average equity loss per move roughly matches that of
+    /// human players of the respective level.
+    pub fn noise(&self) -> f32 {
+        match self {
+            Difficulty::Beginner => 0.3,
+            Difficulty::Casual => 0.15,
+            Difficulty::Intermediate => 0.07,
+            Difficulty::Advanced => 0.03,
+            Difficulty::Expert => 0.0,
+        }
+    }
+}
+
+/// Weakens another evaluator by adding Gaussian noise to its evaluations.
+///
+/// The noise only depends on the position and the seed, so evaluating the same position twice gives
+/// the same result. Use a different seed for each game to get different mistakes.
+///
+/// The noise is added to the equity by moving probability from normal losses to normal wins or vice versa.
+/// So the returned probabilities are only meant for choosing moves, they might not be consistent.
+pub struct NoisyEvaluator<T: Evaluator> {
+    evaluator: T,
+    noise: f32,
+    seed: u64,
+}
+
+impl<T: Evaluator> Evaluator for NoisyEvaluator<T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        self.add_noise(pos, self.evaluator.eval(pos))
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        self.evaluator
+            .eval_batch(positions)
+            .into_iter()
+            .map(|(pos, probabilities)| (pos, self.add_noise(&pos, probabilities)))
+            .collect()
+    }
+}
+
+impl<T: Evaluator> NoisyEvaluator<T> {
+    /// `noise` is the standard deviation of the noise added to the cubeless equity.
+    pub fn new(evaluator: T, noise: f32, seed: u64) -> Self {
+        Self {
+            evaluator,
+            noise,
+            seed,
+        }
+    }
+
+    pub fn with_difficulty(evaluator: T, difficulty: Difficulty, seed: u64) -> Self {
+        Self::new(evaluator, difficulty.noise(), seed)
+    }
+
+    fn add_noise(&self, pos: &Position, probabilities: Probabilities) -> Probabilities {
+        if self.noise == 0.0 {
+            return probabilities;
+        }
+        let mut hasher = DefaultHasher::new();
+        pos.hash(&mut hasher);
+        self.seed.hash(&mut hasher);
+        let mut rng = fastrand::Rng::with_seed(hasher.finish());
+
+        // Box-Muller transform, `1.0 - f32()` is never zero.
+        let u1 = 1.0 - rng.f32();
+        let u2 = rng.f32();
+        let gaussian = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+
+        // Equity changes twice as much as `win_normal` and `lose_normal`.
+        let delta = 0.5 * self.noise * gaussian;
+        Probabilities {
+            win_normal: probabilities.win_normal + delta,
+            lose_normal: probabilities.lose_normal - delta,
+            ..probabilities
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+    use crate::noise::{Difficulty, NoisyEvaluator};
+    use crate::pos;
+    use crate::position::STARTING;
+    use crate::test_utils::EvaluatorFake;
+
+    #[test]
+    fn expert_has_no_noise() {
+        let noisy = NoisyEvaluator::with_difficulty(EvaluatorFake {}, Difficulty::Expert, 1);
+        assert_eq!(noisy.eval(&STARTING), EvaluatorFake {}.eval(&STARTING));
+    }
+
+    #[test]
+    fn same_noise_for_same_position_and_seed() {
+        let noisy = NoisyEvaluator::new(EvaluatorFake {}, 0.2, 1);
+        let other_seed = NoisyEvaluator::new(EvaluatorFake {}, 0.2, 2);
+        assert_eq!(noisy.eval(&STARTING), noisy.eval(&STARTING));
+        assert_ne!(noisy.eval(&STARTING), other_seed.eval(&STARTING));
+        let batch = noisy.eval_batch(vec![STARTING]);
+        assert_eq!(batch[0].1, noisy.eval(&STARTING));
+    }
+
+    #[test]
+    fn noise_has_given_standard_deviation() {
+        // Given
+        let noisy = NoisyEvaluator::new(EvaluatorFake {}, 0.2, 1);
+        // When
+        // Without checkers on pip 5 and 20 the fake evaluates all positions the same.
+        let equities: Vec<f32> = (1..=12)
+            .filter(|&x| x != 5)
+            .flat_map(|x| {
+                (13..=24)
+                    .filter(|&o| o != 20)
+                    .map(move |o| pos!(x x:1; o o:1))
+            })
+            .map(|pos| noisy.eval(&pos).equity())
+            .collect();
+        // Then
+        let n = equities.len() as f32;
+        let mean = equities.iter().sum::<f32>() / n;
+        let deviation = (equities.iter().map(|e| (e - mean).powi(2)).sum::<f32>() / n).sqrt();
+        assert!(mean.abs() < 0.03);
+        assert!((deviation - 0.2).abs() < 0.03);
+    }
+}
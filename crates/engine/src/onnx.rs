@@ -11,6 +11,12 @@ use crate::probabilities::Probabilities;
 type TractModel = RunnableModel<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
 type Error = String;
 
+/// Key in the metadata of an onnx model for the [InputsGen::INPUTS_VERSION] the net was trained for.
+///
+/// [OnnxEvaluator::from_bytes] rejects nets without it, so that a net isn't used with other inputs
+/// of the same length.
+pub const INPUTS_VERSION_KEY: &str = "wildbg_inputs_version";
+
 mod number_of_models {
     pub(super) const SINGLE: usize = 1;
     pub(super) const OPTIMIZED: usize = 50;
@@ -42,30 +48,9 @@ impl<T: InputsGen> BatchEvaluator for OnnxEvaluator<T> {
         }
 
         let inputs = self.inputs_gen.inputs_for_all(&positions);
-        let tract_inputs = tract_ndarray::Array1::from_vec(inputs)
-            .into_shape((positions.len(), T::NUM_INPUTS))
-            .unwrap();
-        let tensor = tract_inputs.into_tensor();
-
-        // run the model on the input
-        let index = if positions.len() < self.models.len() {
-            positions.len()
-        } else {
-            0
-        };
-        let result = self.models[index].run(tvec!(tensor.into())).unwrap();
-
-        // Extract all the probabilities from the result:
-        let array_view = result[0].to_array_view::<f32>().unwrap();
-        let probabilities_in_shape = array_view.into_shape((positions.len(), 4)).unwrap();
-        let probabilities_iter = probabilities_in_shape.outer_iter().map(|x| Probabilities {
-            win_normal: x[0],
-            win_gammon: x[1],
-            lose_normal: x[2],
-            lose_gammon: x[3],
-        });
+        let probabilities = self.eval_inputs(inputs);
         let positions_and_probabilities: Vec<(Position, Probabilities)> =
-            positions.into_iter().zip(probabilities_iter).collect();
+            positions.into_iter().zip(probabilities).collect();
         positions_and_probabilities
     }
 }
@@ -113,6 +98,50 @@ impl OnnxEvaluator<ContactInputsGen> {
 }
 
 impl<T: InputsGen> OnnxEvaluator<T> {
+    /// Evaluates inputs which don't necessarily belong to a position, for example to measure how
+    /// much the neural net depends on a single input.
+    ///
+    /// `inputs` contains `T::NUM_INPUTS` values for each evaluation, like the result of
+    /// [InputsGen::inputs_for_all]. Panics if the length is not a multiple of `T::NUM_INPUTS`.
+    pub fn eval_inputs(&self, inputs: Vec<f32>) -> Vec<Probabilities> {
+        assert_eq!(
+            inputs.len() % T::NUM_INPUTS,
+            0,
+            "The number of inputs must be a multiple of {}.",
+            T::NUM_INPUTS
+        );
+        let batch_size = inputs.len() / T::NUM_INPUTS;
+        if batch_size == 0 {
+            return Vec::new();
+        }
+
+        let tract_inputs = tract_ndarray::Array1::from_vec(inputs)
+            .into_shape((batch_size, T::NUM_INPUTS))
+            .unwrap();
+        let tensor = tract_inputs.into_tensor();
+
+        // run the model on the input
+        let index = if batch_size < self.models.len() {
+            batch_size
+        } else {
+            0
+        };
+        let result = self.models[index].run(tvec!(tensor.into())).unwrap();
+
+        // Extract all the probabilities from the result:
+        let array_view = result[0].to_array_view::<f32>().unwrap();
+        let probabilities_in_shape = array_view.into_shape((batch_size, 4)).unwrap();
+        probabilities_in_shape
+            .outer_iter()
+            .map(|x| Probabilities {
+                win_normal: x[0],
+                win_gammon: x[1],
+                lose_normal: x[2],
+                lose_gammon: x[3],
+            })
+            .collect()
+    }
+
     /// Load the onnx model from the file path and optimize it for any batch size.
     ///
     /// Use it when you are low on memory or if this initializer is called very often.
@@ -139,6 +168,61 @@ impl<T: InputsGen> OnnxEvaluator<T> {
         )
     }
 
+    /// Load an onnx model, for example one uploaded by a user, and check that it fits `inputs_gen`.
+    ///
+    /// Returns an error if the model wasn't trained for [InputsGen::INPUTS_VERSION] of `inputs_gen`,
+    /// expects a different number of inputs than this version of wildbg generates or doesn't
+    /// return the four probabilities.
+    pub fn from_bytes(bytes: &[u8], inputs_gen: T) -> Result<OnnxEvaluator<T>, Error> {
+        let mut reader = bytes;
+        let proto = onnx()
+            .proto_model_for_read(&mut reader)
+            .map_err(|_| "The neural net is no valid onnx model.".to_string())?;
+        Self::check_inputs_version(&proto)?;
+        let models = onnx()
+            .model_for_proto_model(&proto)
+            .and_then(|model| Self::optimized_models(model, number_of_models::SINGLE))
+            .map_err(|_| format!("The neural net doesn't accept {} inputs.", T::NUM_INPUTS))?;
+        let evaluator = OnnxEvaluator { models, inputs_gen };
+        evaluator.validate()?;
+        Ok(evaluator)
+    }
+
+    /// Checks the inputs version in the metadata of the model, see [INPUTS_VERSION_KEY].
+    fn check_inputs_version(proto: &tract_onnx::pb::ModelProto) -> Result<(), Error> {
+        let version = proto
+            .metadata_props
+            .iter()
+            .find(|entry| entry.key == INPUTS_VERSION_KEY)
+            .map(|entry| entry.value.as_str());
+        match version {
+            None => Err("The neural net has no inputs version.".to_string()),
+            Some(version) if version == T::INPUTS_VERSION => Ok(()),
+            Some(version) => Err(format!(
+                "The neural net was trained for inputs version {}, but {} is needed.",
+                version,
+                T::INPUTS_VERSION
+            )),
+        }
+    }
+
+    /// Runs the model once to make sure it returns four probabilities for a single position.
+    fn validate(&self) -> Result<(), Error> {
+        let inputs = tract_ndarray::Array2::<f32>::zeros((1, T::NUM_INPUTS)).into_tensor();
+        let output_len = self.models[0]
+            .run(tvec!(inputs.into()))
+            .map(|result| result[0].len())
+            .map_err(|_| "The neural net can't be evaluated.".to_string())?;
+        if output_len == 4 {
+            Ok(())
+        } else {
+            Err(format!(
+                "The neural net returns {} instead of 4 values.",
+                output_len
+            ))
+        }
+    }
+
     fn from_file_path_with_variable_number_of_models(
         file_path: &str,
         inputs_gen: T,
@@ -181,6 +265,14 @@ impl<T: InputsGen> OnnxEvaluator<T> {
         number_of_optimized_models: usize,
     ) -> TractResult<Vec<TractModel>> {
         let model = onnx().model_for_read(reader)?;
+        Self::optimized_models(model, number_of_optimized_models)
+    }
+
+    /// Optimizes `model` like [OnnxEvaluator::models] does.
+    fn optimized_models(
+        model: InferenceModel,
+        number_of_optimized_models: usize,
+    ) -> TractResult<Vec<TractModel>> {
         let mut models: Vec<TractModel> = Vec::new();
         for i in 0..number_of_optimized_models {
             let fact: InferenceFact = if i == 0 {
@@ -206,17 +298,52 @@ impl<T: InputsGen> OnnxEvaluator<T> {
 #[cfg(test)]
 mod tests {
     use crate::evaluator::Evaluator;
+    use crate::inputs::{ContactInputsGen, RaceInputsGen};
     use crate::onnx::OnnxEvaluator;
     use crate::pos;
 
     #[test]
-    fn eval_certain_win_normal() {
+    fn from_bytes_accepts_matching_net() {
+        let bytes = include_bytes!("../../../neural-nets/contact.onnx");
+        let onnx = OnnxEvaluator::from_bytes(bytes, ContactInputsGen {}).unwrap();
+        let position = pos![x 1:1; o 24:1];
+        assert_eq!(
+            onnx.eval(&position),
+            OnnxEvaluator::contact_default().unwrap().eval(&position)
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_net_with_other_inputs() {
+        let bytes = include_bytes!("../../../neural-nets/contact.onnx");
+        let result = OnnxEvaluator::from_bytes(bytes, RaceInputsGen {});
+        assert_eq!(
+            result.err().unwrap(),
+            "The neural net was trained for inputs version contact-1, but race-1 is needed."
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The number of inputs must be a multiple of 202.")]
+    fn eval_inputs_rejects_incomplete_inputs() {
         let onnx = OnnxEvaluator::contact_default().unwrap();
+        onnx.eval_inputs(vec![0.0; 203]);
+    }
+
+    #[test]
+    fn from_bytes_rejects_garbage() {
+        let result = OnnxEvaluator::from_bytes(b"no onnx", ContactInputsGen {});
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn eval_certain_win_normal() {
+        // A race, so it's evaluated with the race net like in the `CompositeEvaluator`.
+        let onnx = OnnxEvaluator::race_default().unwrap();
         let position = pos![x 1:1; o 24:1];
 
         let probabilities = onnx.eval(&position);
-        assert!(probabilities.win_normal > 0.85);
-        assert!(probabilities.win_normal < 0.9); // This should be wrong, let's improve the nets.
+        assert!(probabilities.win_normal > 0.99);
     }
 
     #[test]
@@ -225,8 +352,8 @@ mod tests {
         let position = pos![x 1:1; o 18:15];
 
         let probabilities = onnx.eval(&position);
-        assert!(probabilities.win_gammon > 0.85);
-        assert!(probabilities.win_gammon < 0.9); // This should be wrong, let's improve the nets.
+        assert!(probabilities.win_gammon > 0.95);
+        assert!(probabilities.win_gammon < 0.96); // This should be wrong, let's improve the nets.
     }
 
     #[test]
@@ -235,8 +362,8 @@ mod tests {
         let position = pos![x 1:6; o 24:1];
 
         let probabilities = onnx.eval(&position);
-        assert!(probabilities.lose_normal > 0.77);
-        assert!(probabilities.lose_normal < 0.82); // This should be wrong, let's improve the nets.
+        assert!(probabilities.lose_normal > 0.98);
+        assert!(probabilities.lose_normal < 0.99); // This should be wrong, let's improve the nets.
     }
 
     #[test]
@@ -245,9 +372,7 @@ mod tests {
         let position = pos![x 7:15; o 24:1];
 
         let probabilities = onnx.eval(&position);
-        assert!(probabilities.lose_gammon > 0.92);
-        assert!(probabilities.lose_gammon < 0.98); // This should be wrong, let's improve the nets.
+        assert!(probabilities.lose_gammon > 0.99);
+        assert!(probabilities.lose_gammon < 0.995); // This should be wrong, let's improve the nets.
     }
-
-
 }
@@ -0,0 +1,137 @@
+use crate::dice::Dice;
+use crate::evaluator::Evaluator;
+use crate::position::{Position, STARTING};
+use crate::probabilities::Probabilities;
+
+/// The bigger die, the smaller die and the two checker moves as `(from, die)`.
+type OpeningMove = (usize, usize, [(usize, usize); 2]);
+
+/// Best plays for all 15 opening rolls, as found by rollouts of strong bots for money game.
+const OPENING_MOVES: [OpeningMove; 15] = [
+    (2, 1, [(13, 2), (6, 1)]),
+    (3, 1, [(8, 3), (6, 1)]),
+    (3, 2, [(24, 3), (13, 2)]),
+    (4, 1, [(24, 1), (13, 4)]),
+    (4, 2, [(8, 4), (6, 2)]),
+    (4, 3, [(24, 4), (13, 3)]),
+    (5, 1, [(24, 1), (13, 5)]),
+    (5, 2, [(13, 5), (13, 2)]),
+    (5, 3, [(8, 5), (6, 3)]),
+    (5, 4, [(24, 4), (13, 5)]),
+    (6, 1, [(13, 6), (8, 1)]),
+    (6, 2, [(24, 6), (13, 2)]),
+    (6, 3, [(24, 6), (13, 3)]),
+    (6, 4, [(8, 6), (6, 4)]),
+    (6, 5, [(24, 6), (18, 5)]),
+];
+
+/// The book move for `dice` if `position` is the starting position, otherwise `None`.
+///
+/// Like [Position::all_positions_after_moving], the returned position has already switched sides.
+/// Doubles can't be rolled on the first move, so there are no book moves for them.
+pub fn opening_move(position: &Position, dice: &Dice) -> Option<Position> {
+    let dice = match dice {
+        Dice::Mixed(dice) if position == &STARTING => dice,
+        _ => return None,
+    };
+    let (_, _, moves) = OPENING_MOVES
+        .iter()
+        .find(|(big, small, _)| *big == dice.big() && *small == dice.small())?;
+    let after = moves.iter().try_fold(*position, |position, (from, die)| {
+        position.try_move_single_checker(*from, *die)
+    })?;
+    Some(after.sides_switched())
+}
+
+/// Wraps another evaluator and plays the first move of the game from the opening book.
+///
+/// This is faster and stronger than evaluating the first move. All other positions are evaluated
+/// by the wrapped evaluator.
+pub struct OpeningBookEvaluator<T: Evaluator> {
+    evaluator: T,
+}
+
+impl<T: Evaluator> OpeningBookEvaluator<T> {
+    pub fn new(evaluator: T) -> Self {
+        Self { evaluator }
+    }
+}
+
+impl<T: Evaluator> Evaluator for OpeningBookEvaluator<T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        self.evaluator.eval(pos)
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        self.evaluator.eval_batch(positions)
+    }
+
+    fn best_position<F>(&self, pos: &Position, dice: &Dice, value: F) -> Position
+    where
+        F: Fn(&Probabilities) -> f32,
+    {
+        opening_move(pos, dice).unwrap_or_else(|| self.evaluator.best_position(pos, dice, value))
+    }
+
+    /// The book move is always first, the other moves are sorted by equity.
+    fn positions_and_probabilities_by_equity(
+        &self,
+        position: &Position,
+        dice: &Dice,
+    ) -> Vec<(Position, Probabilities)> {
+        let mut pos_and_probs = self
+            .evaluator
+            .positions_and_probabilities_by_equity(position, dice);
+        if let Some(book) = opening_move(position, dice) {
+            let book = book.sides_switched();
+            if let Some(index) = pos_and_probs.iter().position(|(pos, _)| pos == &book) {
+                let book_move = pos_and_probs.remove(index);
+                pos_and_probs.insert(0, book_move);
+            }
+        }
+        pos_and_probs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::Dice;
+    use crate::evaluator::{Evaluator, RandomEvaluator};
+    use crate::opening_book::{opening_move, OpeningBookEvaluator};
+    use crate::pos;
+    use crate::position::STARTING;
+
+    #[test]
+    fn book_moves_are_legal() {
+        for die1 in 1..=6 {
+            for die2 in 1..die1 {
+                let dice = Dice::new(die1, die2);
+                let book = opening_move(&STARTING, &dice).unwrap();
+                assert!(STARTING.all_positions_after_moving(&dice).contains(&book));
+            }
+        }
+    }
+
+    #[test]
+    fn no_book_moves_for_doubles_or_other_positions() {
+        assert_eq!(opening_move(&STARTING, &Dice::new(3, 3)), None);
+        assert_eq!(opening_move(&pos!(x 24:2; o 1:2), &Dice::new(3, 1)), None);
+    }
+
+    #[test]
+    fn evaluator_plays_book_move() {
+        let evaluator = OpeningBookEvaluator::new(RandomEvaluator {});
+        let dice = Dice::new(3, 1);
+        let expected = pos!(x 24:2, 13:5, 8:2, 6:4, 5:2; o 19:5, 17:3, 12:5, 1:2);
+        assert_eq!(
+            evaluator.best_position_by_equity(&STARTING, &dice),
+            expected.sides_switched()
+        );
+        let ranked = evaluator.positions_and_probabilities_by_equity(&STARTING, &dice);
+        assert_eq!(ranked[0].0, expected);
+        assert_eq!(
+            ranked.len(),
+            STARTING.all_positions_after_moving(&dice).len()
+        );
+    }
+}
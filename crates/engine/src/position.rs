@@ -1,17 +1,26 @@
+mod board;
 mod conversion;
 mod double_moves;
+mod error;
 mod mixed_moves;
+mod shots;
+mod structure;
+mod symmetry;
+mod zobrist;
 
 use crate::dice::Dice;
 use crate::position::GameResult::*;
 use crate::position::GameState::*;
 use crate::position::OngoingPhase::{Contact, Race};
+pub use error::PositionError;
+pub use shots::BlotShots;
 use std::cmp::min;
 use std::fmt;
 use std::fmt::Formatter;
 use std::fmt::Write;
+pub use zobrist::{ZobristBuildHasher, ZobristHasher, ZobristKey};
 
-const NUM_OF_CHECKERS: u8 = 15;
+pub(crate) const NUM_OF_CHECKERS: u8 = 15;
 pub const X_BAR: usize = 25;
 pub const O_BAR: usize = 0;
 
@@ -23,13 +32,36 @@ const MOVES_CAPACITY: usize = 128;
 
 pub const STARTING: Position = Position {
     pips: [
-        0, 0, 0, 0, 0, 0, 11, 0, 0, 0, 0, 0, -4, 0, 0, 0, 0, 0, -11, 0, 0, 0, 0, 0, 4, 0,
+        0, -2, 0, 0, 0, 0, 5, 0, 3, 0, 0, 0, -5, 5, 0, 0, 0, -3, 0, -5, 0, 0, 0, 0, 2, 0,
     ],
     x_off: 0,
     o_off: 0,
 };
 
+/// Starting position of Nackgammon: like backgammon, but with two checkers on the 23 point.
+///
+/// Those checkers are taken from the 6 point and the mid point.
+pub const NACKGAMMON_STARTING: Position = Position {
+    pips: [
+        0, -2, -2, 0, 0, 0, 4, 0, 3, 0, 0, 0, -4, 4, 0, 0, 0, -3, 0, -4, 0, 0, 0, 2, 2, 0,
+    ],
+    x_off: 0,
+    o_off: 0,
+};
+
+/// Starting position of hypergammon, a variant with only three checkers per player.
+///
+/// The twelve missing checkers are treated as already borne off.
+pub const HYPERGAMMON_STARTING: Position = Position {
+    pips: [
+        0, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0,
+    ],
+    x_off: 12,
+    o_off: 12,
+};
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameResult {
     WinNormal,
     WinGammon,
@@ -49,18 +81,21 @@ impl GameResult {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameState {
     Ongoing,
     GameOver(GameResult),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OngoingPhase {
     Contact,
     Race,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GamePhase {
     Ongoing(OngoingPhase),
     GameOver(GameResult),
@@ -124,6 +159,16 @@ impl Position {
         }
     }
 
+    /// `true` if the game is over with a gammon and the loser still has a checker on the bar or in
+    /// the home board of the winner.
+    pub fn is_backgammon(&self) -> bool {
+        match self.game_state() {
+            GameOver(WinGammon) => self.pips[O_BAR..=6].iter().any(|&p| p < 0),
+            GameOver(LoseGammon) => self.pips[19..=X_BAR].iter().any(|&p| p > 0),
+            _ => false,
+        }
+    }
+
     /// Returns more info than `game_state` - not only whether the game is still ongoing, but also
     /// whether we are already in the race phase.
     ///
@@ -168,8 +213,7 @@ impl Position {
     }
 
     #[inline]
-    pub fn sides_switched(&self) -> Position{
-        
+    pub fn sides_switched(&self) -> Position {
         let mut pips = [0; 26];
         for (i, pip) in self.pips.iter().enumerate() {
             pips[25 - i] = -pip;
@@ -189,26 +233,41 @@ impl From<Position> for [i8; 26] {
 }
 
 impl TryFrom<[i8; 26]> for Position {
-    type Error = &'static str;
+    type Error = PositionError;
 
     /// Use positive numbers for checkers of `x`. Use negative number for checkers of `o`.
     /// Index `25` is the bar for `x`, index `0` is the the bar for `o`.
     /// Checkers already off the board are calculated based on the input array.
     /// Will return an error if the sum of checkers for `x` or `o` is bigger than 15.
     fn try_from(pips: [i8; 26]) -> Result<Self, Self::Error> {
-        let x_off: i8 =
-            (NUM_OF_CHECKERS as i8) - pips.iter().filter(|p| p.is_positive()).sum::<i8>();
-        let o_off: i8 =
-            (NUM_OF_CHECKERS as i8) + pips.iter().filter(|p| p.is_negative()).sum::<i8>();
+        // Summing up as `i8` could overflow for illegal input.
+        let x_checkers: i32 = pips
+            .iter()
+            .filter(|p| p.is_positive())
+            .map(|&p| p as i32)
+            .sum();
+        let o_checkers: i32 = -pips
+            .iter()
+            .filter(|p| p.is_negative())
+            .map(|&p| p as i32)
+            .sum::<i32>();
+        let x_off = NUM_OF_CHECKERS as i32 - x_checkers;
+        let o_off = NUM_OF_CHECKERS as i32 - o_checkers;
 
         if x_off < 0 {
-            Err("Player x has more than 15 checkers on the board.")
+            Err(PositionError::TooManyXCheckers(
+                x_checkers.min(u8::MAX as i32) as u8,
+            ))
         } else if o_off < 0 {
-            Err("Player o has more than 15 checkers on the board.")
+            Err(PositionError::TooManyOCheckers(
+                o_checkers.min(u8::MAX as i32) as u8,
+            ))
         } else if pips[X_BAR].is_negative() {
-            Err("Index 25 is the bar for player x, number of checkers needs to be positive.")
+            Err(PositionError::OCheckersOnXBar)
         } else if pips[O_BAR].is_positive() {
-            Err("Index 0 is the bar for player o, number of checkers needs to be negative.")
+            Err(PositionError::XCheckersOnOBar)
+        } else if x_checkers == 0 && o_checkers == 0 {
+            Err(PositionError::NoCheckers)
         } else {
             Ok(Position {
                 pips,
@@ -221,7 +280,7 @@ impl TryFrom<[i8; 26]> for Position {
 
 impl fmt::Debug for Position {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Position:").unwrap();
+        writeln!(f, "Position:")?;
 
         // Write x:
         let mut s = String::from("x: {");
@@ -239,7 +298,7 @@ impl fmt::Debug for Position {
         s.pop(); // remove last ", "
         s.pop();
         writeln!(s, "}}").unwrap();
-        write!(f, "{}", s).unwrap();
+        write!(f, "{}", s)?;
 
         // Write o:
         let mut s = String::from("o: {");
@@ -305,7 +364,7 @@ impl Position {
         } else if from > die {
             // mixed move, no bear off
             let number_of_opposing_checkers = self.pips[from - die];
-            number_of_opposing_checkers > -1
+            number_of_opposing_checkers > -2
         } else if from == die {
             // bear off
             let checker_out_of_homeboard = self.pips[7..X_BAR].iter().any(|x| x > &0);
@@ -336,7 +395,7 @@ impl Position {
         } else if from > die {
             // mixed move, no bear off
             let number_of_opposing_checkers = self.pips[from - die];
-            number_of_opposing_checkers > -1
+            number_of_opposing_checkers > -2
         } else {
             true
         }
@@ -392,7 +451,6 @@ mod tests {
         assert_eq!(given.o_off(), 5);
     }
 
-
     #[test]
     fn game_state_gammon() {
         let given = pos!(x 18:15; o);
@@ -415,6 +473,16 @@ mod tests {
         assert!(!given.sides_switched().has_lost());
     }
 
+    #[test]
+    fn backgammons() {
+        assert!(!pos!(x 18:15; o).is_backgammon());
+        assert!(pos!(x 19:15; o).is_backgammon());
+        assert!(pos!(x 25:1, 10:14; o).is_backgammon());
+        assert!(pos!(x 19:15; o).sides_switched().is_backgammon());
+        // Only gammons can be backgammons.
+        assert!(!pos!(x 19:14; o).is_backgammon());
+    }
+
     #[test]
     fn game_state_ongoing() {
         let given = pos!(x 19:14; o 1:4);
@@ -499,6 +567,27 @@ mod tests {
         assert_eq!(positions, [expected1, expected2]);
     }
 
+    #[test]
+    fn all_positions_after_moving_hit_in_board() {
+        // Given
+        let pos = pos!(x 6:1; o 4:1, 20:1);
+        // When
+        let positions = pos.all_positions_after_moving(&Dice::new(2, 1));
+        // Then
+        // Hitting the blot on the 4 point on the way to the 3 point is legal.
+        assert!(positions.iter().any(|p| p.pip(X_BAR) == 1));
+    }
+
+    #[test]
+    fn all_positions_after_moving_double_hit_in_board() {
+        // Given
+        let pos = pos!(x 6:1; o 5:1, 20:1);
+        // When
+        let positions = pos.all_positions_after_moving(&Dice::new(1, 1));
+        // Then
+        assert!(positions.iter().all(|p| p.pip(X_BAR) == 1));
+    }
+
     #[test]
     fn switch_sides() {
         // Given
@@ -566,10 +655,7 @@ mod tests {
         // When
         let position = Position::try_from(pips);
         // Then
-        assert_eq!(
-            position,
-            Err("Player x has more than 15 checkers on the board.")
-        );
+        assert_eq!(position, Err(PositionError::TooManyXCheckers(20)));
     }
 
     #[test]
@@ -582,10 +668,7 @@ mod tests {
         // When
         let position = Position::try_from(pips);
         // Then
-        assert_eq!(
-            position,
-            Err("Player o has more than 15 checkers on the board.")
-        );
+        assert_eq!(position, Err(PositionError::TooManyOCheckers(20)));
     }
 
     #[test]
@@ -598,10 +681,7 @@ mod tests {
         // When
         let position = Position::try_from(pips);
         // Then
-        assert_eq!(
-            position,
-            Err("Index 25 is the bar for player x, number of checkers needs to be positive.")
-        );
+        assert_eq!(position, Err(PositionError::OCheckersOnXBar));
     }
 
     #[test]
@@ -614,9 +694,16 @@ mod tests {
         // When
         let position = Position::try_from(pips);
         // Then
+        assert_eq!(position, Err(PositionError::XCheckersOnOBar));
+    }
+
+    #[test]
+    fn try_from_fails_without_checkers() {
+        let position = Position::try_from([0_i8; 26]);
+        assert_eq!(position, Err(PositionError::NoCheckers));
         assert_eq!(
-            position,
-            Err("Index 0 is the bar for player o, number of checkers needs to be negative.")
+            position.unwrap_err().to_string(),
+            "At least one player must have checkers on the board."
         );
     }
 
@@ -691,7 +778,7 @@ mod tests {
             }
         }
         for (id, dice, number) in positions {
-            let position = Position::from_id(id.to_string());
+            let position = Position::try_from_id(id).unwrap();
             let dice = Dice::new(dice.0, dice.1);
             assert_eq!(
                 number_of_moves(&position, &dice),
@@ -706,7 +793,7 @@ mod tests {
 #[cfg(test)]
 mod private_tests {
     use crate::pos;
-    use crate::position::{Position, O_BAR, STARTING};
+    use crate::position::{Position, O_BAR, STARTING, X_BAR};
     use std::collections::HashMap;
 
     #[test]
@@ -716,6 +803,15 @@ mod private_tests {
         assert_eq!(STARTING, STARTING.sides_switched());
     }
 
+    #[test]
+    fn starting_position_has_15_checkers_on_the_board_for_each_player() {
+        let x: i8 = STARTING.pips.iter().filter(|&&p| p > 0).sum();
+        let o: i8 = STARTING.pips.iter().filter(|&&p| p < 0).sum();
+        assert_eq!((x, o), (15, -15));
+        assert_eq!((STARTING.x_off, STARTING.o_off), (0, 0));
+        assert_eq!((STARTING.pips[X_BAR], STARTING.pips[O_BAR]), (0, 0));
+    }
+
     #[test]
     fn move_single_checker_mixed_move() {
         let before = pos!(x 4:10; o);
@@ -756,7 +852,7 @@ mod private_tests {
 
     #[test]
     fn cannot_move_opposing_checker() {
-        let given = Position::from_hash_maps(&HashMap::new(), &HashMap::from([(4, 10)]));
+        let given = Position::from_hash_maps(&HashMap::new(), &HashMap::from([(4, 10)])).unwrap();
         assert!(!given.can_move_in_board(4, 2));
     }
 
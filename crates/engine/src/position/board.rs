@@ -0,0 +1,108 @@
+use crate::position::{Position, O_BAR, X_BAR};
+
+/// Number of checkers drawn on a point; if there are more, the last row shows their number.
+const ROWS: usize = 5;
+
+/// ASCII art similar to the board of GNU Backgammon.
+///
+/// Player `x` plays `X` from the 24 point in the top right to the 1 point in the bottom right.
+/// The bar of `o` is in the upper half, the bar of `x` in the lower half.
+/// An example is the starting position:
+/// ```text
+///  +13-14-15-16-17-18------19-20-21-22-23-24-+
+///  | X           O    |   | O              X |
+///  | X           O    |   | O              X |
+///  | X           O    |   | O                |
+///  | X                |   | O                |
+///  | X                |   | O                |
+///  |                  |BAR|                  |
+///  | O                |   | X                |
+///  | O                |   | X                |
+///  | O           X    |   | X                |
+///  | O           X    |   | X              O |
+///  | O           X    |   | X              O |
+///  +12-11-10--9--8--7-------6--5--4--3--2--1-+
+///  Off: X 0, O 0
+/// ```
+impl Position {
+    pub fn ascii_board(&self) -> String {
+        let top: Vec<usize> = (13..=24).collect();
+        let bottom: Vec<usize> = (1..=12).rev().collect();
+        let mut lines = vec![border(&top)];
+        for row in 0..ROWS {
+            lines.push(self.board_row(&top, O_BAR, row));
+        }
+        lines.push(format!(" |{0}|BAR|{0}|", " ".repeat(18)));
+        for row in (0..ROWS).rev() {
+            lines.push(self.board_row(&bottom, X_BAR, row));
+        }
+        lines.push(border(&bottom));
+        lines.push(format!(" Off: X {}, O {}", self.x_off, self.o_off));
+        lines.join("\n")
+    }
+
+    /// One row of a board half. `row` 0 is at the edge of the board, where the points start.
+    fn board_row(&self, pips: &[usize], bar: usize, row: usize) -> String {
+        let cell = |pip: usize| -> String {
+            let checkers = self.pips[pip];
+            let count = checkers.unsigned_abs() as usize;
+            let symbol = if checkers > 0 { 'X' } else { 'O' };
+            if count > ROWS && row == ROWS - 1 {
+                format!("{count:>2} ")
+            } else if count > row {
+                format!(" {symbol} ")
+            } else {
+                "   ".to_string()
+            }
+        };
+        let half = |pips: &[usize]| -> String { pips.iter().map(|&pip| cell(pip)).collect() };
+        format!(" |{}|{}|{}|", half(&pips[..6]), cell(bar), half(&pips[6..]))
+    }
+}
+
+/// Top or bottom edge of the board with the numbers of the points.
+fn border(pips: &[usize]) -> String {
+    let labels = |pips: &[usize]| -> String {
+        pips.iter()
+            .map(|pip| format!("{:->2}-", pip.to_string()))
+            .collect()
+    };
+    format!(" +{}-----{}+", labels(&pips[..6]), labels(&pips[6..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pos;
+    use crate::position::{O_BAR, STARTING, X_BAR};
+
+    #[test]
+    fn starting_position() {
+        let expected = " +13-14-15-16-17-18------19-20-21-22-23-24-+
+ | X           O    |   | O              X |
+ | X           O    |   | O              X |
+ | X           O    |   | O                |
+ | X                |   | O                |
+ | X                |   | O                |
+ |                  |BAR|                  |
+ | O                |   | X                |
+ | O                |   | X                |
+ | O           X    |   | X                |
+ | O           X    |   | X              O |
+ | O           X    |   | X              O |
+ +12-11-10--9--8--7-------6--5--4--3--2--1-+
+ Off: X 0, O 0";
+        assert_eq!(STARTING.ascii_board(), expected);
+    }
+
+    #[test]
+    fn bar_off_and_stacks() {
+        let position = pos!(x X_BAR:1, 6:7, 1:1; o 24:2, 20:11, O_BAR:2);
+        let board = position.ascii_board();
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(lines[1], " |                  | O |    O           O |");
+        assert_eq!(lines[5], " |                  |   |   11             |");
+        assert_eq!(lines[7], " |                  |   | 7                |");
+        assert_eq!(lines[11], " |                  | X | X              X |");
+        assert_eq!(lines[13], " Off: X 6, O 0");
+    }
+}
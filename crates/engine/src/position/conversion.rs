@@ -1,8 +1,8 @@
-use crate::position::{Position, NUM_OF_CHECKERS, O_BAR, X_BAR};
+use crate::position::{Position, PositionError, NUM_OF_CHECKERS, O_BAR, X_BAR};
 use base64::engine::general_purpose;
 use base64::Engine;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::ops::Add;
 
 /// Simple way to create positions for testing
 /// The starting position would be:
@@ -25,7 +25,7 @@ macro_rules! pos {
                 o.insert($o_pip as usize, $o_checkers as u8);
             )*
 
-            $crate::position::Position::from_hash_maps(&x, &o)
+            $crate::position::Position::from_hash_maps(&x, &o).expect("pos! needs a legal position")
         }
     };
 }
@@ -38,10 +38,36 @@ impl Position {
         b64[..14].to_string()
     }
 
-    pub fn from_id(id: String) -> Position {
-        let key = general_purpose::STANDARD.decode(id.add("==")).unwrap();
-        Position::decode(key.try_into().unwrap())
+    /// Returns an error for IDs which don't encode a legal position, so user input can be passed in.
+    pub fn try_from_id(id: &str) -> Result<Position, PositionError> {
+        let is_base64 = |c: char| c.is_ascii_alphanumeric() || c == '+' || c == '/';
+        if id.len() != 14 || !id.chars().all(is_base64) {
+            return Err(PositionError::MalformedId);
+        }
+        let key: [u8; 10] = general_purpose::STANDARD
+            .decode(format!("{id}=="))
+            .ok()
+            .and_then(|key| key.try_into().ok())
+            .ok_or(PositionError::UndecodableId)?;
+        Position::from_position_key(key)
+    }
+
+    /// The 80 bit key from which the GnuBG position ID is derived via Base64.
+    pub fn position_key(&self) -> [u8; 10] {
+        self.encode()
+    }
+
+    /// Inverse of [Position::position_key]. Returns an error for keys which don't encode a legal
+    /// position, so arbitrary 80 bits can be passed in.
+    pub fn from_position_key(key: [u8; 10]) -> Result<Position, PositionError> {
+        // Each checker is a 1 bit. With more than 30 of them `decode` could read beyond the key.
+        if key.iter().map(|byte| byte.count_ones()).sum::<u32>() > 2 * NUM_OF_CHECKERS as u32 {
+            return Err(PositionError::TooManyCheckersInId);
+        }
+        // `decode` doesn't check the number of checkers per player, `try_from` does.
+        Position::try_from(<[i8; 26]>::from(Position::decode(key)))
     }
+
     fn encode(&self) -> [u8; 10] {
         let mut key = [0u8; 10];
         let mut bit_index = 0;
@@ -125,22 +151,187 @@ impl Position {
         }
     }
 
-    pub fn from_hash_maps(x: &HashMap<usize, u8>, o: &HashMap<usize, u8>) -> Position {
-        let mut pips = [0; 26];
-        for (i, v) in x {
-            pips[*i] = *v as i8;
+    /// Keys are pips from the point of view of `x`, values are numbers of checkers.
+    pub fn from_hash_maps(
+        x: &HashMap<usize, u8>,
+        o: &HashMap<usize, u8>,
+    ) -> Result<Position, PositionError> {
+        let mut pips = [0i8; 26];
+        // Numbers of checkers which don't fit into an `i8` are rejected by `try_from` anyway.
+        let checkers = |v: u8| i8::try_from(v).unwrap_or(i8::MAX);
+        for (&i, &v) in x {
+            *pips.get_mut(i).ok_or(PositionError::IllegalPip(i))? = checkers(v);
+        }
+        for (&i, &v) in o {
+            let pip = pips.get_mut(i).ok_or(PositionError::IllegalPip(i))?;
+            if *pip != 0 && v != 0 {
+                return Err(PositionError::CheckersOfBothPlayers(i));
+            }
+            *pip = -checkers(v);
+        }
+        Position::try_from(pips)
+    }
+}
+
+/// XGID, the position format of eXtreme Gammon.
+///
+/// An example is `XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:0:0:10` for the starting position.
+/// Only the board and the player on roll are taken into account; cube, dice and score are ignored.
+impl Position {
+    /// Player `x` is the bottom player and on roll. Cube, dice and score are set to default values.
+    pub fn xgid(&self) -> String {
+        let board: String = self
+            .pips
+            .iter()
+            .map(|&pip| match pip.cmp(&0) {
+                Ordering::Equal => '-',
+                Ordering::Greater => (b'A' + pip as u8 - 1) as char,
+                Ordering::Less => (b'a' + (-pip) as u8 - 1) as char,
+            })
+            .collect();
+        format!("XGID={board}:0:0:1:00:0:0:0:0:10")
+    }
+
+    /// The `XGID=` prefix is optional. If the top player is on roll, sides are switched,
+    /// so that the returned position is always from the point of view of the player on roll.
+    pub fn from_xgid(xgid: &str) -> Result<Position, PositionError> {
+        let xgid = xgid.strip_prefix("XGID=").unwrap_or(xgid);
+        let mut fields = xgid.split(':');
+        let board = fields.next().unwrap_or_default();
+        if board.chars().count() != 26 {
+            return Err(PositionError::XgidLength);
+        }
+        let mut pips = [0_i8; 26];
+        for (pip, c) in pips.iter_mut().zip(board.chars()) {
+            *pip = match c {
+                '-' => 0,
+                'A'..='O' => (c as u8 - b'A') as i8 + 1,
+                'a'..='o' => -((c as u8 - b'a') as i8 + 1),
+                _ => return Err(PositionError::XgidCharacter(c)),
+            };
         }
-        for (i, v) in o {
-            debug_assert!(pips[*i] == 0);
-            pips[*i] = -(*v as i8);
+        let position = Position::try_from(pips)?;
+        // Fields after the board are: cube value, cube owner, turn.
+        // Turn `1` is the bottom player, turn `-1` the top player.
+        match fields.nth(2) {
+            Some("-1") => Ok(position.sides_switched()),
+            _ => Ok(position),
         }
-        Position::try_from(pips).expect("Need legal position")
+    }
+}
+
+/// Board state of FIBS in "boardstyle 3", also used by other tools and backgammon servers.
+///
+/// An example is
+/// `board:You:Opponent:9999:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0`
+/// for the starting position. The board is always seen by the player named in the second field.
+/// Only the board is taken into account; names, score, dice and cube are ignored.
+impl Position {
+    /// Player `x` is `You`, plays `O` from 24 to 1 and is on roll. All other fields have default values.
+    pub fn fibs_board(&self) -> String {
+        let board: Vec<String> = self.pips.iter().map(|pip| pip.to_string()).collect();
+        format!(
+            "board:You:Opponent:9999:0:0:{}:1:0:0:0:0:1:1:1:0:1:-1:0:25:{}:{}:{}:{}:0:0:0:0",
+            board.join(":"),
+            self.x_off,
+            self.o_off,
+            self.pips[X_BAR],
+            -self.pips[O_BAR],
+        )
+    }
+
+    /// The returned position is from the point of view of `You`, no matter who is on roll.
+    pub fn from_fibs_board(board: &str) -> Result<Position, PositionError> {
+        let fields: Vec<&str> = board.trim().split(':').collect();
+        if fields.len() != 53 || fields[0] != "board" {
+            return Err(PositionError::FibsFormat);
+        }
+        let number = |index: usize| {
+            fields[index]
+                .parse::<i8>()
+                .map_err(|_| PositionError::FibsNumber)
+        };
+        let sign = |index: usize| match number(index)? {
+            1 => Ok(1),
+            -1 => Ok(-1),
+            _ => Err(PositionError::FibsColourOrDirection),
+        };
+        // Field 41 is the colour of `You`: positive numbers on the board are checkers of `O`.
+        // Field 42 is the direction: `-1` if `You` moves from point 24 to point 1.
+        let colour = sign(41)?;
+        let direction = sign(42)?;
+        let mut pips = [0_i8; 26];
+        for index in 0..26 {
+            let pip = if direction == -1 { index } else { 25 - index };
+            let checkers = number(index + 6)?;
+            if checkers.unsigned_abs() > NUM_OF_CHECKERS {
+                return Err(PositionError::FibsCheckers);
+            }
+            pips[pip] = checkers * colour;
+        }
+        Position::try_from(pips)
+    }
+}
+
+/// Positions are serialized as their GnuBG position ID, for example `"4HPwATDgc/ABMA"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Position {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.position_id())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Position {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        Position::try_from_id(&id).map_err(serde::de::Error::custom)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::position::STARTING;
+    use crate::position::{Position, PositionError, STARTING};
+
+    #[test]
+    fn try_from_id() {
+        assert_eq!(Position::try_from_id("4HPwATDgc/ABMA"), Ok(STARTING));
+        assert_eq!(
+            Position::try_from_id("4HPwATDgc/AB"),
+            Err(PositionError::MalformedId)
+        );
+        assert_eq!(
+            Position::try_from_id("//////////////"),
+            Err(PositionError::UndecodableId)
+        );
+        assert_eq!(
+            Position::try_from_id("/////////////w"),
+            Err(PositionError::TooManyCheckersInId)
+        );
+        assert_eq!(
+            Position::try_from_id("4Dn4ABjwOfgAOA"),
+            Err(PositionError::TooManyXCheckers(16))
+        );
+    }
+
+    #[test]
+    fn from_hash_maps_illegal() {
+        use std::collections::HashMap;
+        let x = HashMap::from([(6, 5)]);
+        assert_eq!(
+            Position::from_hash_maps(&HashMap::from([(26, 1)]), &HashMap::new()),
+            Err(PositionError::IllegalPip(26))
+        );
+        assert_eq!(
+            Position::from_hash_maps(&x, &HashMap::from([(6, 1)])),
+            Err(PositionError::CheckersOfBothPlayers(6))
+        );
+        assert_eq!(
+            Position::from_hash_maps(&x, &HashMap::from([(1, 200)])),
+            Err(PositionError::TooManyOCheckers(127))
+        );
+        assert!(Position::from_hash_maps(&x, &HashMap::from([(6, 0), (19, 5)])).is_ok());
+    }
 
     #[test]
     fn start_id() {
@@ -158,8 +349,160 @@ mod tests {
             "zGbiIYCYD3gALA", // O off
         ];
         for pid in pids {
-            let game = super::Position::from_id(pid.to_string());
+            let game = super::Position::try_from_id(pid).unwrap();
             assert_eq!(pid, game.position_id());
         }
     }
+
+    #[test]
+    fn starting_xgid() {
+        let position = super::Position::try_from_id("4HPwATDgc/ABMA").unwrap();
+        assert_eq!(
+            position.xgid(),
+            "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10"
+        );
+    }
+
+    #[test]
+    fn from_xgid_round_trip() {
+        let xgid = "XGID=-a-BBBB-A---dB---b-bc-C-A-:0:0:1:00:0:0:0:0:10";
+        let position = super::Position::from_xgid(xgid).unwrap();
+        assert_eq!(position.xgid(), xgid);
+    }
+
+    #[test]
+    fn from_xgid_top_player_on_roll() {
+        let bottom = super::Position::from_xgid("-a-BBBB-A---dB---b-bc-C-A-:0:0:1:52:0:0:0:0:10");
+        let top = super::Position::from_xgid("-a-BBBB-A---dB---b-bc-C-A-:0:0:-1:52:0:0:0:0:10");
+        assert_eq!(top.unwrap(), bottom.unwrap().sides_switched());
+    }
+
+    #[test]
+    fn starting_fibs_board() {
+        assert_eq!(
+            STARTING.fibs_board(),
+            "board:You:Opponent:9999:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0"
+        );
+    }
+
+    #[test]
+    fn from_fibs_board_round_trip() {
+        let position = pos!(x 25:1, 6:3, 2:2; o 1:2, 0:1);
+        let board = position.fibs_board();
+        assert_eq!(super::Position::from_fibs_board(&board), Ok(position));
+        assert!(board.ends_with(":9:12:1:1:0:0:0:0"));
+    }
+
+    #[test]
+    fn from_fibs_board_playing_x() {
+        // `You` plays `X` (colour -1) from point 1 to point 24 (direction 1).
+        let board = "board:You:alice:5:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:3:1:1:1:1:0:-1:1:25:0:0:0:0:0:0:0:0:0";
+        assert_eq!(super::Position::from_fibs_board(board), Ok(STARTING));
+        let board = "board:You:alice:5:0:0:-1:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:0:2:1:0:0:3:1:1:1:1:0:-1:1:25:0:0:0:0:0:0:0:0:0";
+        assert_eq!(
+            super::Position::from_fibs_board(board),
+            Ok(pos!(x 25:1; o 0:2))
+        );
+    }
+
+    #[test]
+    fn from_fibs_board_illegal() {
+        assert_eq!(
+            super::Position::from_fibs_board("board:You:Opponent:9999:0:0"),
+            Err(PositionError::FibsFormat)
+        );
+        let board = STARTING.fibs_board().replace(":-5:", ":x:");
+        assert_eq!(
+            super::Position::from_fibs_board(&board),
+            Err(PositionError::FibsNumber)
+        );
+        // -128 * -1 would overflow.
+        let board = STARTING.fibs_board().replacen(":-5:", ":-128:", 1);
+        let board = board.replace(":1:-1:0:25:", ":-1:-1:0:25:");
+        assert_eq!(
+            super::Position::from_fibs_board(&board),
+            Err(PositionError::FibsCheckers)
+        );
+        for (colour, direction) in [(2, -1), (1, 0), (-128, -1)] {
+            let board = STARTING
+                .fibs_board()
+                .replace(":1:-1:0:25:", &format!(":{colour}:{direction}:0:25:"));
+            assert_eq!(
+                super::Position::from_fibs_board(&board),
+                Err(PositionError::FibsColourOrDirection)
+            );
+        }
+    }
+
+    #[test]
+    fn from_xgid_illegal() {
+        assert_eq!(
+            super::Position::from_xgid("XGID=-b----E-C---eE---c-e----B"),
+            Err(PositionError::XgidLength)
+        );
+        assert_eq!(
+            super::Position::from_xgid("XGID=-b----E-C---eE---c-e----B?"),
+            Err(PositionError::XgidCharacter('?'))
+        );
+        assert_eq!(
+            super::Position::from_xgid("XGID=-b----E-C---eE---c-e----P-"),
+            Err(PositionError::XgidCharacter('P'))
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use crate::position::{GamePhase, GameResult, OngoingPhase, Position, STARTING};
+    use crate::probabilities::Probabilities;
+
+    #[test]
+    fn position_as_id() {
+        let json = serde_json::to_string(&STARTING).unwrap();
+        assert_eq!(json, "\"4HPwATDgc/ABMA\"");
+        let position: Position = serde_json::from_str(&json).unwrap();
+        assert_eq!(position, STARTING);
+    }
+
+    #[test]
+    fn illegal_position_id() {
+        let error = serde_json::from_str::<Position>("\"4HPwATDgc/AB\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("A GnuBG position ID consists of 14 Base64 characters."));
+    }
+
+    #[test]
+    fn probabilities_and_phases() {
+        let probabilities = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.25,
+            lose_normal: 0.25,
+            lose_gammon: 0.0,
+        };
+        let json = serde_json::to_string(&probabilities).unwrap();
+        assert_eq!(
+            json,
+            r#"{"win_normal":0.5,"win_gammon":0.25,"lose_normal":0.25,"lose_gammon":0.0}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Probabilities>(&json).unwrap(),
+            probabilities
+        );
+
+        let invalid = r#"{"win_normal":0.5,"win_gammon":0.5,"lose_normal":0.5,"lose_gammon":0.0}"#;
+        let error = serde_json::from_str::<Probabilities>(invalid).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Probabilities must sum up to 1.0."));
+
+        let phase = pos!(x 1:1; o 24:1).game_phase();
+        assert_eq!(phase, GamePhase::Ongoing(OngoingPhase::Race));
+        assert_eq!(
+            serde_json::to_string(&phase).unwrap(),
+            r#"{"Ongoing":"Race"}"#
+        );
+        let over: GamePhase = serde_json::from_str(r#"{"GameOver":"WinGammon"}"#).unwrap();
+        assert_eq!(over, GamePhase::GameOver(GameResult::WinGammon));
+    }
 }
@@ -0,0 +1,92 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Why a [crate::position::Position] couldn't be created, for example from user input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionError {
+    /// Player `x` has the contained number of checkers on the board, more than 15.
+    TooManyXCheckers(u8),
+    /// Player `o` has the contained number of checkers on the board, more than 15.
+    TooManyOCheckers(u8),
+    /// Checkers of `o` on index 25, which is the bar of `x`.
+    OCheckersOnXBar,
+    /// Checkers of `x` on index 0, which is the bar of `o`.
+    XCheckersOnOBar,
+    /// Neither player has checkers on the board, so both would have won.
+    NoCheckers,
+    /// The contained pip is not between 0 and 25.
+    IllegalPip(usize),
+    /// Both players have checkers on the contained pip.
+    CheckersOfBothPlayers(usize),
+    /// A GnuBG position ID which doesn't consist of 14 Base64 characters.
+    MalformedId,
+    /// A GnuBG position ID which can't be decoded into 80 bits.
+    UndecodableId,
+    /// A GnuBG position ID or position key with more than 30 checkers.
+    TooManyCheckersInId,
+    /// The board of a XGID doesn't have 26 characters.
+    XgidLength,
+    /// The board of a XGID contains the contained character, which is neither `-` nor a letter.
+    XgidCharacter(char),
+    /// A FIBS board which doesn't start with "board" or doesn't have 53 fields.
+    FibsFormat,
+    /// A FIBS board with a field which should be a number but isn't.
+    FibsNumber,
+    /// A FIBS board whose colour or direction is neither `1` nor `-1`.
+    FibsColourOrDirection,
+    /// A FIBS board with more than 15 checkers on a single point.
+    FibsCheckers,
+}
+
+impl PositionError {
+    /// Human readable description, without the details contained in some variants.
+    pub fn message(&self) -> &'static str {
+        match self {
+            PositionError::TooManyXCheckers(_) => {
+                "Player x has more than 15 checkers on the board."
+            }
+            PositionError::TooManyOCheckers(_) => {
+                "Player o has more than 15 checkers on the board."
+            }
+            PositionError::OCheckersOnXBar => {
+                "Index 25 is the bar for player x, number of checkers needs to be positive."
+            }
+            PositionError::XCheckersOnOBar => {
+                "Index 0 is the bar for player o, number of checkers needs to be negative."
+            }
+            PositionError::NoCheckers => "At least one player must have checkers on the board.",
+            PositionError::IllegalPip(_) => "Pips must be between 0 and 25.",
+            PositionError::CheckersOfBothPlayers(_) => {
+                "A point can't have checkers of both players."
+            }
+            PositionError::MalformedId => "A GnuBG position ID consists of 14 Base64 characters.",
+            PositionError::UndecodableId => "The GnuBG position ID can't be decoded.",
+            PositionError::TooManyCheckersInId => {
+                "The GnuBG position ID or key contains more than 30 checkers."
+            }
+            PositionError::XgidLength => "The board of a XGID must have 26 characters.",
+            PositionError::XgidCharacter(_) => "The board of a XGID contains an illegal character.",
+            PositionError::FibsFormat => "A FIBS board must start with 'board' and have 53 fields.",
+            PositionError::FibsNumber => "The FIBS board contains a field which is not a number.",
+            PositionError::FibsColourOrDirection => {
+                "The colour and direction of a FIBS board must be 1 or -1."
+            }
+            PositionError::FibsCheckers => "A point of the FIBS board has more than 15 checkers.",
+        }
+    }
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+/// Lets functions which return `Result<_, &'static str>` use `?` on position errors.
+impl From<PositionError> for &'static str {
+    fn from(error: PositionError) -> Self {
+        error.message()
+    }
+}
@@ -0,0 +1,185 @@
+use crate::dice::{Dice, ALL_21};
+use crate::position::{Position, X_BAR};
+
+/// Shots of the opponent `o` against a single blot of player `x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlotShots {
+    /// The pip of the blot from the point of view of `x`, between 1 and 24.
+    pub pip: usize,
+    /// Number of the 36 rolls which hit the blot with a single die.
+    pub direct: u8,
+    /// Number of the 36 rolls which only hit the blot by combining dice.
+    pub indirect: u8,
+}
+
+impl BlotShots {
+    /// Number of the 36 rolls which hit the blot.
+    pub fn total(&self) -> u8 {
+        self.direct + self.indirect
+    }
+}
+
+/// How a certain roll hits a blot. Ordered, so that a direct hit replaces an indirect one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Hit {
+    None,
+    Indirect,
+    Direct,
+}
+
+impl Position {
+    /// Pips with exactly one checker of `x`, from the 24 point down to the 1 point.
+    pub fn blots(&self) -> Vec<usize> {
+        (1..X_BAR)
+            .rev()
+            .filter(|&pip| self.pips[pip] == 1)
+            .collect()
+    }
+
+    /// Shots of `o` against each blot of `x`, in the same order as [Position::blots].
+    ///
+    /// All legal checker moves are taken into account, including points blocked by `x` and
+    /// checkers of `o` which have to enter from the bar first. A roll is a direct shot if a checker
+    /// of `o` hits with a single die, otherwise an indirect one if it hits by combining dice.
+    pub fn blot_shots(&self) -> Vec<BlotShots> {
+        let mut shots: Vec<BlotShots> = self
+            .blots()
+            .into_iter()
+            .map(|pip| BlotShots {
+                pip,
+                direct: 0,
+                indirect: 0,
+            })
+            .collect();
+        for (dice, weight) in ALL_21 {
+            let hits = self.hits(&dice);
+            for shot in shots.iter_mut() {
+                match hits[shot.pip] {
+                    Hit::Direct => shot.direct += weight as u8,
+                    Hit::Indirect => shot.indirect += weight as u8,
+                    Hit::None => {}
+                }
+            }
+        }
+        shots
+    }
+
+    /// Number of the 36 rolls with which `o` can hit at least one blot of `x`.
+    pub fn shots(&self) -> u8 {
+        if self.blots().is_empty() {
+            return 0;
+        }
+        ALL_21
+            .iter()
+            .filter(|(dice, _)| self.hits(dice).iter().any(|&hit| hit != Hit::None))
+            .map(|(_, weight)| *weight as u8)
+            .sum()
+    }
+
+    /// For each pip of `x` how `o` can hit a blot there with `dice`.
+    fn hits(&self, dice: &Dice) -> [Hit; 26] {
+        let opponent = self.sides_switched();
+        // Checkers of `o` which were already there before the roll, only they can hit directly.
+        let mut originals = opponent.pips.map(|pip| pip.max(0));
+        let mut hits = [Hit::None; 26];
+        match dice {
+            Dice::Double(die) => {
+                opponent.explore_hits(&[*die; 4], &mut originals, &mut hits);
+            }
+            Dice::Mixed(mixed) => {
+                let (big, small) = (mixed.big(), mixed.small());
+                opponent.explore_hits(&[big, small], &mut originals, &mut hits);
+                opponent.explore_hits(&[small, big], &mut originals, &mut hits);
+            }
+        }
+        hits
+    }
+
+    /// Plays `dice` in the given order in all possible ways and records hits in `hits`.
+    ///
+    /// `self` is seen from the point of view of the hitting player, `hits` from the other player.
+    fn explore_hits(&self, dice: &[usize], originals: &mut [i8; 26], hits: &mut [Hit; 26]) {
+        let Some((&die, remaining)) = dice.split_first() else {
+            return;
+        };
+        for from in (1..=X_BAR).rev() {
+            if let Some(next) = self.try_move_single_checker(from, die) {
+                let original = originals[from] > 0;
+                if from > die && self.pips[from - die] == -1 {
+                    let hit = if original { Hit::Direct } else { Hit::Indirect };
+                    let pip = X_BAR - (from - die);
+                    hits[pip] = hits[pip].max(hit);
+                }
+                // If possible an original checker left `from`, it arrives as a new one.
+                if original {
+                    originals[from] -= 1;
+                }
+                next.explore_hits(remaining, originals, hits);
+                if original {
+                    originals[from] += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pos;
+    use crate::position::{BlotShots, STARTING};
+
+    #[test]
+    fn no_blots() {
+        assert!(STARTING.blots().is_empty());
+        assert_eq!(STARTING.shots(), 0);
+    }
+
+    #[test]
+    fn single_direct_distance() {
+        // Given
+        // `o` has a checker 3 pips away. It hits with all 11 rolls containing a 3, with 1-2 and 1-1.
+        let position = pos!(x 10:1, 6:14; o 7:1, 24:14);
+        // When
+        let shots = position.blot_shots();
+        // Then
+        assert_eq!(position.blots(), vec![10]);
+        assert_eq!(
+            shots,
+            vec![BlotShots {
+                pip: 10,
+                direct: 11,
+                indirect: 3,
+            }]
+        );
+        assert_eq!(shots[0].total(), 14);
+        assert_eq!(position.shots(), 14);
+    }
+
+    #[test]
+    fn indirect_shot_is_blocked() {
+        // Given
+        // The blot is 8 pips away. 6-2 is blocked by our 11 and 15 points, 4-4 and 2-2 by our 13
+        // point. So only 5-3 hits.
+        let position = pos!(x 17:1, 15:2, 13:2, 11:2, 6:8; o 9:1, 24:14);
+        // When
+        let shots = position.blot_shots();
+        // Then
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].direct, 0);
+        assert_eq!(shots[0].indirect, 2);
+    }
+
+    #[test]
+    fn bar_checkers_have_to_enter_first() {
+        // Given
+        // Two checkers of `o` on the bar need both dice of a mixed roll to enter.
+        // The blot on our 4 point is hit directly by 4-x when entering, 1-3 can't hit.
+        let position = pos!(x 4:1, 6:14; o 0:2, 24:13);
+        // When
+        let shots = position.blot_shots();
+        // Then
+        // 11 rolls contain a 4, 2-2 enters two checkers on the 2 point and hits with the others.
+        assert_eq!(shots[0].direct, 11);
+        assert_eq!(shots[0].indirect, 1);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::position::{Position, X_BAR};
+
+/// Structural features of the board, all from the point of view of player `x`.
+///
+/// A point is made with at least two checkers. For the opponent use [Position::sides_switched].
+impl Position {
+    /// Length of the longest sequence of consecutive made points between the 24 and the 1 point.
+    pub fn longest_prime(&self) -> usize {
+        let mut longest = 0;
+        let mut current = 0;
+        for pip in 1..X_BAR {
+            if self.pips[pip] >= 2 {
+                current += 1;
+                longest = longest.max(current);
+            } else {
+                current = 0;
+            }
+        }
+        longest
+    }
+
+    /// Made points in the home board of the opponent, from the 24 point down to the 19 point.
+    pub fn anchors(&self) -> Vec<usize> {
+        (19..X_BAR)
+            .rev()
+            .filter(|&pip| self.pips[pip] >= 2)
+            .collect()
+    }
+
+    /// Number of checkers on the points 7 to 11, which can make points in the home board with a
+    /// single die.
+    pub fn builders(&self) -> usize {
+        self.pips[7..12].iter().map(|&p| p.max(0) as usize).sum()
+    }
+
+    /// Number of made points in the own home board, between 0 and 6.
+    pub fn home_board_points(&self) -> usize {
+        self.pips[1..7].iter().filter(|&&p| p >= 2).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pos;
+    use crate::position::STARTING;
+
+    #[test]
+    fn starting_position() {
+        assert_eq!(STARTING.longest_prime(), 1);
+        assert_eq!(STARTING.anchors(), vec![24]);
+        assert_eq!(STARTING.builders(), 3);
+        assert_eq!(STARTING.home_board_points(), 1);
+    }
+
+    #[test]
+    fn prime_with_gap() {
+        // Given
+        let position = pos!(x 11:2, 10:2, 9:1, 8:2, 7:2, 6:2, 5:2, 20:2; o 24:2);
+        // Then
+        assert_eq!(position.longest_prime(), 4);
+        assert_eq!(position.anchors(), vec![20]);
+        assert_eq!(position.builders(), 9);
+        assert_eq!(position.home_board_points(), 2);
+    }
+
+    #[test]
+    fn opponent_features() {
+        // The opponent's home board and prime are ours after switching sides.
+        let position = pos!(x 1:2; o 24:3, 23:3, 22:3, 21:3, 20:3).sides_switched();
+        assert_eq!(position.longest_prime(), 5);
+        assert_eq!(position.home_board_points(), 5);
+        assert!(position.anchors().is_empty());
+    }
+}
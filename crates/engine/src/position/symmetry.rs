@@ -0,0 +1,105 @@
+use crate::position::GameState::{GameOver, Ongoing};
+use crate::position::{GameState, Position, X_BAR};
+
+/// Mirroring a position swaps the two players, so that the opponent `o` becomes player `x`.
+///
+/// Guarantees, which can be relied upon for data augmentation and in test assertions:
+/// - Mirroring twice gives back the original: `position.mirror().mirror() == position`.
+/// - The pip `p` of `x` becomes the pip [Position::mirror_pip]`(p)` of `o` and vice versa.
+/// - The game state is reversed, see [GameState::reverse].
+/// - For finished games the probabilities are exactly switched with
+///   [crate::probabilities::Probabilities::switch_sides].
+///
+/// Ongoing positions are different: a [Position] always has `x` on roll, so the mirrored position
+/// has the other player on roll. Probabilities of a position and its mirror are therefore not
+/// switched versions of each other, the difference is the advantage of having the roll. What holds
+/// is that the probabilities for `x` of a position after `x` has moved are the switched
+/// probabilities of its mirror, that's how the evaluators rate moves.
+impl Position {
+    /// The same board from the point of view of the opponent, same as [Position::sides_switched].
+    #[inline]
+    pub fn mirror(&self) -> Position {
+        self.sides_switched()
+    }
+
+    /// The index of `pip` from the point of view of the opponent, bars included.
+    ///
+    /// The 1 point becomes the 24 point and `X_BAR` becomes `O_BAR`.
+    #[inline]
+    pub const fn mirror_pip(pip: usize) -> usize {
+        X_BAR - pip
+    }
+
+    /// Whether both players have their checkers on the same points, so that mirroring doesn't
+    /// change the position. The starting position is symmetric.
+    pub fn is_symmetric(&self) -> bool {
+        *self == self.mirror()
+    }
+}
+
+impl GameState {
+    /// The game state from the point of view of the opponent.
+    pub fn reverse(&self) -> Self {
+        match self {
+            Ongoing => Ongoing,
+            GameOver(result) => GameOver(result.reverse()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::ALL_21;
+    use crate::pos;
+    use crate::position::{Position, O_BAR, STARTING, X_BAR};
+
+    #[test]
+    fn mirroring_twice_is_identity() {
+        for (dice, _) in ALL_21 {
+            for position in STARTING.all_positions_after_moving(&dice) {
+                assert_eq!(position.mirror().mirror(), position);
+                assert_eq!(
+                    position.mirror().game_state(),
+                    position.game_state().reverse()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pips_are_mirrored() {
+        // Given
+        let position = pos!(x 25:1, 6:3, 2:2; o 1:1, 5:1, 0:1);
+        // When
+        let mirror = position.mirror();
+        // Then
+        for pip in O_BAR..=X_BAR {
+            assert_eq!(
+                mirror.pip(Position::mirror_pip(pip)),
+                -position.pip(pip),
+                "pip {pip}"
+            );
+        }
+        assert_eq!(Position::mirror_pip(X_BAR), O_BAR);
+        assert_eq!(Position::mirror_pip(1), 24);
+        assert_eq!(
+            (mirror.x_off, mirror.o_off),
+            (position.o_off, position.x_off)
+        );
+    }
+
+    #[test]
+    fn finished_games_are_reversed() {
+        let gammon = pos!(x 18:15; o);
+        assert_eq!(gammon.mirror().game_state(), gammon.game_state().reverse());
+        assert!(!gammon.mirror().has_lost());
+    }
+
+    #[test]
+    fn symmetric_positions() {
+        assert!(STARTING.is_symmetric());
+        assert!(pos!(x 6:2; o 19:2).is_symmetric());
+        assert!(!pos!(x 6:2; o 18:2).is_symmetric());
+        assert!(!pos!(x 6:2; o 19:1).is_symmetric());
+    }
+}
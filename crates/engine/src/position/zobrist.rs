@@ -0,0 +1,193 @@
+use crate::position::{Position, O_BAR};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+/// One random key for each pip and each number of checkers on it, from -15 to 15.
+///
+/// The checkers off the board are not needed, they follow from the checkers on the board.
+const KEYS: [[u64; 31]; 26] = keys();
+
+/// Fixed pseudo random numbers, so that hashes are the same on all machines and in all runs.
+const fn keys() -> [[u64; 31]; 26] {
+    let mut keys = [[0; 31]; 26];
+    // SplitMix64, see https://prng.di.unimi.it/splitmix64.c
+    let mut state: u64 = 0x5EED_BAC4_6A77_0000;
+    let mut pip = 0;
+    while pip < 26 {
+        let mut checkers = 0;
+        while checkers < 31 {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            keys[pip][checkers] = z ^ (z >> 31);
+            checkers += 1;
+        }
+        pip += 1;
+    }
+    keys
+}
+
+#[inline(always)]
+fn key(pip: usize, checkers: i8) -> u64 {
+    KEYS[pip][(checkers + 15) as usize]
+}
+
+impl Position {
+    /// 64 bit Zobrist hash, for example for transposition tables.
+    ///
+    /// When a move is applied, [Position::zobrist_after_move] derives the new hash much faster.
+    pub fn zobrist(&self) -> u64 {
+        self.pips
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (pip, &checkers)| hash ^ key(pip, checkers))
+    }
+
+    /// The Zobrist hash after moving a checker of `x` from `from` by `die` pips.
+    ///
+    /// `hash` is the Zobrist hash of `self`, only the changed pips are taken into account.
+    /// The move must be legal, as for [Position::try_move_single_checker].
+    pub fn zobrist_after_move(&self, hash: u64, from: usize, die: usize) -> u64 {
+        let mut hash = hash ^ key(from, self.pips[from]) ^ key(from, self.pips[from] - 1);
+        if from > die {
+            let to = from - die;
+            let checkers = self.pips[to];
+            if checkers == -1 {
+                // hit opponent
+                let bar = self.pips[O_BAR];
+                hash ^= key(to, -1) ^ key(to, 1) ^ key(O_BAR, bar) ^ key(O_BAR, bar - 1);
+            } else {
+                hash ^= key(to, checkers) ^ key(to, checkers + 1);
+            }
+        }
+        hash
+    }
+}
+
+/// A [Position] which is hashed by its Zobrist hash.
+///
+/// Use it with [ZobristBuildHasher] as key of `HashSet`s and `HashMap`s, as the Zobrist hash is
+/// already well distributed and doesn't need to be hashed again. [Position] itself keeps hashing
+/// all of its fields, so this is opt-in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ZobristKey(pub Position);
+
+impl From<Position> for ZobristKey {
+    fn from(position: Position) -> Self {
+        Self(position)
+    }
+}
+
+impl Hash for ZobristKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.zobrist());
+    }
+}
+
+/// Hasher which passes through the Zobrist hash of a [ZobristKey].
+#[derive(Default)]
+pub struct ZobristHasher {
+    hash: u64,
+}
+
+impl Hasher for ZobristHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // Only used for keys other than Zobrist keys; FNV-1a.
+        for byte in bytes {
+            self.hash = (self.hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        // A single value is passed through. Further values are mixed in, so that equal values
+        // don't cancel each other out and the order matters.
+        self.hash = self.hash.wrapping_mul(0x517C_C1B7_2722_0A95) ^ value;
+    }
+}
+
+/// For example `HashSet<ZobristKey, ZobristBuildHasher>`.
+pub type ZobristBuildHasher = BuildHasherDefault<ZobristHasher>;
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::ALL_21;
+    use crate::pos;
+    use crate::position::{
+        Position, ZobristBuildHasher, ZobristHasher, ZobristKey, STARTING, X_BAR,
+    };
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    #[test]
+    fn equal_positions_have_equal_hashes() {
+        let position = pos!(x 6:5, 8:3; o 19:5, 12:3);
+        assert_eq!(position.zobrist(), pos!(x 8:3, 6:5; o 12:3, 19:5).zobrist());
+        assert_ne!(position.zobrist(), position.sides_switched().zobrist());
+        assert_ne!(STARTING.zobrist(), 0);
+    }
+
+    #[test]
+    fn incremental_hash_matches_full_hash() {
+        let positions = [
+            STARTING,
+            pos!(x 25:1, 6:3, 2:2; o 1:1, 5:1, 0:1),
+            pos!(x 6:2, 4:2, 1:1; o 19:2, 24:1),
+        ];
+        for position in positions {
+            let hash = position.zobrist();
+            for from in 1..=X_BAR {
+                for die in 1..=6 {
+                    if let Some(after) = position.try_move_single_checker(from, die) {
+                        assert_eq!(
+                            position.zobrist_after_move(hash, from, die),
+                            after.zobrist(),
+                            "from {from} with die {die}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dedup_set() {
+        let mut positions: HashSet<ZobristKey, ZobristBuildHasher> = HashSet::default();
+        for (dice, _) in ALL_21 {
+            positions.extend(
+                STARTING
+                    .all_positions_after_moving(&dice)
+                    .into_iter()
+                    .map(ZobristKey::from),
+            );
+        }
+        let all: HashSet<Position> = ALL_21
+            .iter()
+            .flat_map(|(dice, _)| STARTING.all_positions_after_moving(dice))
+            .collect();
+        assert_eq!(positions.len(), all.len());
+        assert!(positions.iter().all(|key| all.contains(&key.0)));
+    }
+
+    #[test]
+    fn hasher_passes_through_a_single_value() {
+        let mut hasher = ZobristHasher::default();
+        STARTING.zobrist().hash(&mut hasher);
+        assert_eq!(hasher.finish(), STARTING.zobrist());
+    }
+
+    #[test]
+    fn hasher_mixes_several_values() {
+        let hash = |values: &[u64]| {
+            let mut hasher = ZobristHasher::default();
+            values.iter().for_each(|value| hasher.write_u64(*value));
+            hasher.finish()
+        };
+        assert_ne!(hash(&[7, 7]), 0);
+        assert_ne!(hash(&[7, 7]), hash(&[3, 3]));
+        assert_ne!(hash(&[3, 7]), hash(&[7, 3]));
+    }
+}
@@ -1,10 +1,20 @@
-use crate::position::GameResult;
 use crate::position::GameResult::*;
+use crate::position::GameState::GameOver;
+use crate::position::{GameResult, Position};
 use std::fmt;
 use std::fmt::Formatter;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-/// Sum of all six fields will always be 1.0
+/// Sum of all four fields will always be 1.0
+///
+/// Use [Probabilities::try_new] for values from untrusted sources, deserialization checks them the
+/// same way.
 #[derive(Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "UncheckedProbabilities")
+)]
 pub struct Probabilities {
     pub win_normal: f32,
     pub win_gammon: f32,
@@ -31,15 +41,76 @@ impl fmt::Display for Probabilities {
         write!(
             f,
             "{};{};{};{}",
-            self.win_normal,
-            self.win_gammon,
-            self.lose_normal,
-            self.lose_gammon,
+            self.win_normal, self.win_gammon, self.lose_normal, self.lose_gammon,
         )
     }
 }
 
+/// Maximum difference between 1.0 and the sum of the values accepted by [Probabilities::try_new].
+const SUM_TOLERANCE: f32 = 0.01;
+
+/// Why [Probabilities::try_new] rejected its values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProbabilitiesError {
+    /// A value is not a number or infinite.
+    NotFinite,
+    /// A value is negative or bigger than 1.0, the contained one.
+    OutOfRange(f32),
+    /// The values sum up to the contained number, which is too far from 1.0 to be normalized.
+    Sum(f32),
+}
+
+impl ProbabilitiesError {
+    /// Human readable description, without the details contained in some variants.
+    pub fn message(&self) -> &'static str {
+        match self {
+            ProbabilitiesError::NotFinite => "Probabilities must be finite numbers.",
+            ProbabilitiesError::OutOfRange(_) => "Probabilities must be between 0.0 and 1.0.",
+            ProbabilitiesError::Sum(_) => "Probabilities must sum up to 1.0.",
+        }
+    }
+}
+
+impl fmt::Display for ProbabilitiesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ProbabilitiesError {}
+
 impl Probabilities {
+    /// Checks the values and normalizes them, so that the sum is exactly 1.0.
+    ///
+    /// All values must be between 0.0 and 1.0. A sum which is off by rounding errors is fine, for
+    /// example from values written with few decimals. A sum which is off by more than 0.01 is an
+    /// error. Gammons can't exceed wins: `win_gammon` is part of the wins, the classic value `win`
+    /// is `win_normal + win_gammon`.
+    pub fn try_new(
+        win_normal: f32,
+        win_gammon: f32,
+        lose_normal: f32,
+        lose_gammon: f32,
+    ) -> Result<Self, ProbabilitiesError> {
+        let values = [win_normal, win_gammon, lose_normal, lose_gammon];
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(ProbabilitiesError::NotFinite);
+        }
+        if let Some(&value) = values.iter().find(|&&v| !(0.0..=1.0).contains(&v)) {
+            return Err(ProbabilitiesError::OutOfRange(value));
+        }
+        let sum: f32 = values.iter().sum();
+        if (sum - 1.0).abs() > SUM_TOLERANCE {
+            return Err(ProbabilitiesError::Sum(sum));
+        }
+        Ok(Probabilities {
+            win_normal: win_normal / sum,
+            win_gammon: win_gammon / sum,
+            lose_normal: lose_normal / sum,
+            lose_gammon: lose_gammon / sum,
+        })
+    }
+
     pub fn csv_header() -> String {
         "win_normal;win_gammon;lose_normal;lose_gammon".to_string()
     }
@@ -48,7 +119,11 @@ impl Probabilities {
         self.win_normal + self.win_gammon
     }
 
-    pub(crate) fn switch_sides(&self) -> Self {
+    /// Probabilities from the point of view of the opponent.
+    ///
+    /// Switching twice gives back the original. See [crate::position::Position::mirror] for how
+    /// this relates to mirrored positions.
+    pub fn switch_sides(&self) -> Self {
         Self {
             win_normal: self.lose_normal,
             win_gammon: self.lose_gammon,
@@ -57,62 +132,317 @@ impl Probabilities {
         }
     }
 
+    /// `false` if a value is not a number, negative, bigger than 1.0 or if the sum is not 1.0.
+    ///
+    /// Rollouts always return consistent probabilities, neural nets might not.
+    pub fn is_consistent(&self) -> bool {
+        let values = [
+            self.win_normal,
+            self.win_gammon,
+            self.lose_normal,
+            self.lose_gammon,
+        ];
+        let tolerance = 0.001;
+        values
+            .iter()
+            .all(|v| v.is_finite() && *v >= -tolerance && *v <= 1.0 + tolerance)
+            && (values.iter().sum::<f32>() - 1.0).abs() < tolerance
+    }
+
     /// Cubeless equity
     pub fn equity(&self) -> f32 {
-        self.win_normal - self.lose_normal
-            + 2.0 * (self.win_gammon - self.lose_gammon)
+        self.win_normal - self.lose_normal + 2.0 * (self.win_gammon - self.lose_gammon)
+    }
+
+    /// Linear interpolation: `0.0` returns `self`, `1.0` returns `other`.
+    ///
+    /// If both are consistent, the result is consistent as well.
+    pub fn blend(&self, other: &Probabilities, weight: f32) -> Probabilities {
+        let mix = |this: f32, other: f32| (1.0 - weight) * this + weight * other;
+        Probabilities {
+            win_normal: mix(self.win_normal, other.win_normal),
+            win_gammon: mix(self.win_gammon, other.win_gammon),
+            lose_normal: mix(self.lose_normal, other.lose_normal),
+            lose_gammon: mix(self.lose_gammon, other.lose_gammon),
+        }
+    }
+
+    /// Weighted average of probabilities, for example of all 21 rolls with weights 1 and 2.
+    ///
+    /// Sums up in double precision. `None` if the sum of the weights is not positive.
+    pub fn weighted_average<'a>(
+        weighted: impl IntoIterator<Item = (&'a Probabilities, f32)>,
+    ) -> Option<Probabilities> {
+        let mut sum = ProbabilitiesF64::default();
+        let mut total_weight = 0.0;
+        for (probabilities, weight) in weighted {
+            sum.add(probabilities, weight as f64);
+            total_weight += weight as f64;
+        }
+        (total_weight > 0.0).then(|| Probabilities::from(&sum.scaled(1.0 / total_weight)))
+    }
+
+    /// Unweighted average, `None` for an empty slice.
+    pub fn average(probabilities: &[Probabilities]) -> Option<Probabilities> {
+        Self::weighted_average(probabilities.iter().map(|p| (p, 1.0)))
+    }
+
+    /// Negative values are set to zero and then all values are divided by their sum, so that the
+    /// result is consistent. For example for raw outputs of neural nets.
+    ///
+    /// Values which are not a number and probabilities which sum up to zero give an even split.
+    pub fn normalized(&self) -> Probabilities {
+        let values = [
+            self.win_normal,
+            self.win_gammon,
+            self.lose_normal,
+            self.lose_gammon,
+        ]
+        .map(|v| if v.is_nan() { 0.0 } else { v.max(0.0) });
+        let sum: f32 = values.iter().sum();
+        if !sum.is_normal() {
+            return Probabilities {
+                win_normal: 0.25,
+                win_gammon: 0.25,
+                lose_normal: 0.25,
+                lose_gammon: 0.25,
+            };
+        }
+        Probabilities {
+            win_normal: values[0] / sum,
+            win_gammon: values[1] / sum,
+            lose_normal: values[2] / sum,
+            lose_gammon: values[3] / sum,
+        }
+    }
+}
+
+impl From<&GameResult> for Probabilities {
+    /// Certain probabilities for a game which is already over.
+    fn from(value: &GameResult) -> Self {
+        match value {
+            WinNormal => Probabilities {
+                win_normal: 1.,
+                ..Default::default()
+            },
+            WinGammon => Probabilities {
+                win_gammon: 1.,
+                ..Default::default()
+            },
+            LoseNormal => Probabilities {
+                lose_normal: 1.,
+                ..Default::default()
+            },
+            LoseGammon => Probabilities {
+                lose_gammon: 1.,
+                ..Default::default()
+            },
+        }
     }
 }
 
 impl From<&ResultCounter> for Probabilities {
-    /// Typically used from rollouts.
+    /// Typically used from rollouts. Divides in double precision, see [ProbabilitiesF64].
     fn from(value: &ResultCounter) -> Self {
-        let sum = value.sum() as f32;
+        Probabilities::from(&ProbabilitiesF64::from(value))
+    }
+}
+
+/// Deserialized fields before they are checked by [Probabilities::try_new].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedProbabilities {
+    win_normal: f32,
+    win_gammon: f32,
+    lose_normal: f32,
+    lose_gammon: f32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedProbabilities> for Probabilities {
+    type Error = ProbabilitiesError;
+
+    fn try_from(value: UncheckedProbabilities) -> Result<Self, Self::Error> {
+        Probabilities::try_new(
+            value.win_normal,
+            value.win_gammon,
+            value.lose_normal,
+            value.lose_gammon,
+        )
+    }
+}
+
+/// Probabilities with double precision, for accumulating many values.
+///
+/// With millions of rollout trials `f32` sums lose precision: above 2^24 adding a single game
+/// doesn't change the sum anymore. Accumulate with this type and convert to [Probabilities] at the end.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProbabilitiesF64 {
+    pub win_normal: f64,
+    pub win_gammon: f64,
+    pub lose_normal: f64,
+    pub lose_gammon: f64,
+}
+
+impl ProbabilitiesF64 {
+    /// Adds `probabilities` multiplied by `weight`.
+    pub fn add(&mut self, probabilities: &Probabilities, weight: f64) {
+        self.win_normal += weight * probabilities.win_normal as f64;
+        self.win_gammon += weight * probabilities.win_gammon as f64;
+        self.lose_normal += weight * probabilities.lose_normal as f64;
+        self.lose_gammon += weight * probabilities.lose_gammon as f64;
+    }
+
+    /// All values multiplied by `factor`, for example to turn a sum into an average.
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            win_normal: self.win_normal * factor,
+            win_gammon: self.win_gammon * factor,
+            lose_normal: self.lose_normal * factor,
+            lose_gammon: self.lose_gammon * factor,
+        }
+    }
+
+    pub fn win(&self) -> f64 {
+        self.win_normal + self.win_gammon
+    }
+
+    /// Cubeless equity
+    pub fn equity(&self) -> f64 {
+        self.win_normal - self.lose_normal + 2.0 * (self.win_gammon - self.lose_gammon)
+    }
+}
+
+impl From<&Probabilities> for ProbabilitiesF64 {
+    fn from(value: &Probabilities) -> Self {
+        let mut probabilities = Self::default();
+        probabilities.add(value, 1.0);
+        probabilities
+    }
+}
+
+impl From<&ProbabilitiesF64> for Probabilities {
+    /// Rounds to single precision.
+    fn from(value: &ProbabilitiesF64) -> Self {
         Probabilities {
-            win_normal: value.num_of(WinNormal) as f32 / sum,
-            win_gammon: value.num_of(WinGammon) as f32 / sum,
-            lose_normal: value.num_of(LoseNormal) as f32 / sum,
-            lose_gammon: value.num_of(LoseGammon) as f32 / sum,
+            win_normal: value.win_normal as f32,
+            win_gammon: value.win_gammon as f32,
+            lose_normal: value.lose_normal as f32,
+            lose_gammon: value.lose_gammon as f32,
+        }
+    }
+}
+
+impl From<&ResultCounter> for ProbabilitiesF64 {
+    fn from(value: &ResultCounter) -> Self {
+        let sum = value.sum() as f64;
+        ProbabilitiesF64 {
+            win_normal: value.num_of(WinNormal) as f64 / sum,
+            win_gammon: value.num_of(WinGammon) as f64 / sum,
+            lose_normal: value.num_of(LoseNormal) as f64 / sum,
+            lose_gammon: value.num_of(LoseGammon) as f64 / sum,
         }
     }
 }
 
-#[derive(Default)]
+/// Number of games for each game result, typically from rollouts.
+///
+/// Counts are `u64`, so that even billions of trials don't overflow. Backgammons are counted in
+/// their own buckets, but [ResultCounter::num_of] and the conversion to [Probabilities] include
+/// them in the gammons, as [Probabilities] don't distinguish them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ResultCounter {
-    results: [u32; 4],
+    /// Win normal, win gammon, win backgammon, lose normal, lose gammon, lose backgammon.
+    results: [u64; 6],
+}
+
+/// Index of the bucket for `result` in [ResultCounter] and [AtomicResultCounter].
+///
+/// Gammons with `backgammon` go into the bucket right after the gammons. Panics for backgammons of
+/// normal results, otherwise they would be counted as gammons.
+fn bucket(result: &GameResult, backgammon: bool) -> usize {
+    let index = match result {
+        WinNormal => 0,
+        WinGammon => 1,
+        LoseNormal => 3,
+        LoseGammon => 4,
+    };
+    assert!(
+        !backgammon || matches!(result, WinGammon | LoseGammon),
+        "Only gammons can be backgammons."
+    );
+    if backgammon {
+        index + 1
+    } else {
+        index
+    }
 }
 
 impl ResultCounter {
     /// Convenience method, mainly for tests
     pub fn new(
-        win_normal: u32,
-        win_gammon: u32,
-        lose_normal: u32,
-        lose_gammon: u32,
+        win_normal: u64,
+        win_gammon: u64,
+        win_backgammon: u64,
+        lose_normal: u64,
+        lose_gammon: u64,
+        lose_backgammon: u64,
     ) -> Self {
         let results = [
             win_normal,
             win_gammon,
+            win_backgammon,
             lose_normal,
             lose_gammon,
+            lose_backgammon,
         ];
         Self { results }
     }
+
     pub fn add(&mut self, result: GameResult) {
-        self.results[result as usize] += 1;
+        self.add_results(result, 1);
+    }
+
+    pub fn add_results(&mut self, result: GameResult, amount: u64) {
+        self.results[bucket(&result, false)] += amount;
+    }
+
+    /// Adds `amount` backgammons, `result` must be [WinGammon] or [LoseGammon], otherwise it panics.
+    pub fn add_backgammons(&mut self, result: GameResult, amount: u64) {
+        self.results[bucket(&result, true)] += amount;
     }
 
-    pub fn add_results(&mut self, result: GameResult, amount: u32) {
-        self.results[result as usize] += amount;
+    /// Adds `amount` games which ended in `position`, including whether it's a backgammon.
+    ///
+    /// Panics if the game in `position` is still ongoing. The result is from the point of view of
+    /// `x`, switch the sides of `position` before if needed.
+    pub fn add_final_position(&mut self, position: &Position, amount: u64) {
+        let GameOver(result) = position.game_state() else {
+            panic!("The game in {position:?} is not over yet.");
+        };
+        self.results[bucket(&result, position.is_backgammon())] += amount;
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.results.iter().sum::<u64>()
     }
 
-    pub fn sum(&self) -> u32 {
-        self.results.iter().sum::<u32>()
+    /// Number of games with `result`, gammons include backgammons.
+    pub fn num_of(&self, result: GameResult) -> u64 {
+        match result {
+            WinNormal | LoseNormal => self.results[bucket(&result, false)],
+            WinGammon | LoseGammon => {
+                self.results[bucket(&result, false)] + self.results[bucket(&result, true)]
+            }
+        }
     }
 
-    pub fn num_of(&self, result: GameResult) -> u32 {
-        // This works because the enum has associated integer values (discriminant), starting with zero.
-        self.results[result as usize]
+    /// Number of backgammons, `result` is [WinGammon] or [LoseGammon]. Zero for normal results.
+    pub fn num_of_backgammons(&self, result: GameResult) -> u64 {
+        match result {
+            WinNormal | LoseNormal => 0,
+            WinGammon | LoseGammon => self.results[bucket(&result, true)],
+        }
     }
 
     pub fn combine(self, counter: &ResultCounter) -> Self {
@@ -124,21 +454,264 @@ impl ResultCounter {
     }
 }
 
+/// [ResultCounter] which can be shared between threads, for example by parallel rollout workers.
+///
+/// All methods take `&self` and only use atomic additions, so workers can count their games or
+/// merge their own [ResultCounter] at any time without locking. Counts are only consistent with
+/// each other once all workers are done, [AtomicResultCounter::snapshot] may be taken before that
+/// for progress reports.
+#[derive(Debug, Default)]
+pub struct AtomicResultCounter {
+    results: [AtomicU64; 6],
+}
+
+impl AtomicResultCounter {
+    pub fn add(&self, result: GameResult) {
+        self.add_results(result, 1);
+    }
+
+    pub fn add_results(&self, result: GameResult, amount: u64) {
+        self.results[bucket(&result, false)].fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Adds `amount` backgammons, `result` must be [WinGammon] or [LoseGammon], otherwise it panics.
+    pub fn add_backgammons(&self, result: GameResult, amount: u64) {
+        self.results[bucket(&result, true)].fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Adds all counts of `counter`.
+    pub fn merge(&self, counter: &ResultCounter) {
+        for (atomic, value) in self.results.iter().zip(counter.results) {
+            if value > 0 {
+                atomic.fetch_add(value, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Current counts.
+    pub fn snapshot(&self) -> ResultCounter {
+        ResultCounter {
+            results: self
+                .results
+                .each_ref()
+                .map(|atomic| atomic.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Final counts, once no other thread uses this counter anymore.
+    pub fn into_inner(self) -> ResultCounter {
+        ResultCounter {
+            results: self.results.map(AtomicU64::into_inner),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::position::GameResult::{LoseGammon, LoseNormal, WinGammon};
-    use crate::probabilities::{Probabilities, ResultCounter};
+    use crate::pos;
+    use crate::position::GameResult::{LoseGammon, LoseNormal, WinGammon, WinNormal};
+    use crate::probabilities::{
+        AtomicResultCounter, Probabilities, ProbabilitiesError, ProbabilitiesF64, ResultCounter,
+    };
+
+    #[test]
+    fn try_new_normalizes() {
+        // Given
+        // Written with two decimals, so the sum is slightly off.
+        let sum = 0.33 + 0.33 + 0.33 + 0.0;
+        // When
+        let normalized = Probabilities::try_new(0.33, 0.33, 0.33, 0.0).unwrap();
+        // Then
+        assert_eq!(normalized.win_normal, 0.33 / sum);
+        assert!((normalized.win() + normalized.lose_normal - 1.0).abs() < 1e-6);
+        assert_eq!(
+            Probabilities::try_new(0.5, 0.25, 0.25, 0.0),
+            Ok(probabilities(0.5, 0.25, 0.25))
+        );
+    }
+
+    #[test]
+    fn try_new_rejects_invalid_values() {
+        assert_eq!(
+            Probabilities::try_new(f32::NAN, 0.0, 1.0, 0.0),
+            Err(ProbabilitiesError::NotFinite)
+        );
+        assert_eq!(
+            Probabilities::try_new(1.1, -0.1, 0.0, 0.0),
+            Err(ProbabilitiesError::OutOfRange(1.1))
+        );
+        assert_eq!(
+            Probabilities::try_new(0.5, 0.25, 0.0, 0.0),
+            Err(ProbabilitiesError::Sum(0.75))
+        );
+        assert_eq!(
+            ProbabilitiesError::Sum(0.75).to_string(),
+            "Probabilities must sum up to 1.0."
+        );
+    }
+
+    #[test]
+    fn f64_result_counter_with_many_games() {
+        // Given
+        // 2^24 + 1 can't be represented as `f32`.
+        let mut counter = ResultCounter::default();
+        counter.add_results(WinNormal, 16_777_217);
+        counter.add_results(LoseNormal, 16_777_215);
+        // When
+        let probabilities = ProbabilitiesF64::from(&counter);
+        // Then
+        assert_eq!(probabilities.equity(), 2.0 / 33_554_432.0);
+        assert!(Probabilities::from(&counter).equity() < 1e-6);
+    }
+
+    #[test]
+    fn f64_accumulation() {
+        // Given
+        let probabilities = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.25,
+            lose_normal: 0.125,
+            lose_gammon: 0.125,
+        };
+        let mut sum = ProbabilitiesF64::default();
+        // When
+        for _ in 0..10_000_000 {
+            sum.add(&probabilities, 1.0);
+        }
+        let average = Probabilities::from(&sum.scaled(1.0 / 10_000_000.0));
+        // Then
+        assert_eq!(average, probabilities);
+    }
+
+    fn probabilities(win_normal: f32, win_gammon: f32, lose_normal: f32) -> Probabilities {
+        Probabilities {
+            win_normal,
+            win_gammon,
+            lose_normal,
+            lose_gammon: 1.0 - win_normal - win_gammon - lose_normal,
+        }
+    }
+
+    #[test]
+    fn blend() {
+        // Given
+        let one = probabilities(0.5, 0.25, 0.25);
+        let two = probabilities(0.0, 0.25, 0.5);
+        // When
+        let blended = one.blend(&two, 0.25);
+        // Then
+        assert_eq!(blended, probabilities(0.375, 0.25, 0.3125));
+        assert_eq!(one.blend(&two, 0.0), one);
+        assert_eq!(one.blend(&two, 1.0), two);
+    }
+
+    #[test]
+    fn weighted_average() {
+        // Given
+        let one = probabilities(0.5, 0.25, 0.25);
+        let two = probabilities(0.0, 0.25, 0.5);
+        // When
+        let average = Probabilities::weighted_average([(&one, 1.0), (&two, 3.0)]);
+        // Then
+        assert_eq!(average, Some(probabilities(0.125, 0.25, 0.4375)));
+        assert_eq!(
+            Probabilities::average(&[one.clone(), two.clone()]),
+            Some(one.blend(&two, 0.5))
+        );
+        assert_eq!(Probabilities::average(&[]), None);
+        assert_eq!(Probabilities::weighted_average([(&one, 0.0)]), None);
+    }
+
+    #[test]
+    fn normalized() {
+        // Given
+        let raw = Probabilities {
+            win_normal: 0.5,
+            win_gammon: -0.1,
+            lose_normal: 1.0,
+            lose_gammon: 0.5,
+        };
+        // When
+        let normalized = raw.normalized();
+        // Then
+        assert_eq!(normalized, probabilities(0.25, 0.0, 0.5));
+        assert!(normalized.is_consistent());
+        assert_eq!(Probabilities::default().normalized().win_normal, 0.25);
+    }
+
+    #[test]
+    fn result_counter_with_backgammons() {
+        // Given
+        let mut counter = ResultCounter::default();
+        counter.add(WinGammon);
+        counter.add_backgammons(WinGammon, 3);
+        counter.add_final_position(&pos!(x 19:15; o), 1);
+        counter.add_final_position(&pos!(x 18:15; o), 1);
+        // Then
+        assert_eq!(counter, ResultCounter::new(0, 1, 3, 0, 1, 1));
+        assert_eq!(counter.sum(), 6);
+        assert_eq!(counter.num_of(WinGammon), 4);
+        assert_eq!(counter.num_of_backgammons(WinGammon), 3);
+        assert_eq!(counter.num_of(LoseGammon), 2);
+        assert_eq!(counter.num_of_backgammons(LoseNormal), 0);
+        let probabilities = Probabilities::from(&counter);
+        assert_eq!(probabilities.win_gammon, 4.0 / 6.0);
+        assert_eq!(probabilities.lose_gammon, 2.0 / 6.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only gammons can be backgammons.")]
+    fn backgammon_of_normal_win() {
+        ResultCounter::default().add_backgammons(WinNormal, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only gammons can be backgammons.")]
+    fn atomic_backgammon_of_normal_loss() {
+        AtomicResultCounter::default().add_backgammons(LoseNormal, 1);
+    }
+
+    #[test]
+    fn result_counter_beyond_u32() {
+        let mut counter = ResultCounter::default();
+        counter.add_results(WinNormal, u32::MAX as u64);
+        counter.add_results(WinNormal, 1);
+        counter.add(LoseNormal);
+        assert_eq!(counter.num_of(WinNormal), 1 << 32);
+        assert_eq!(counter.sum(), (1 << 32) + 1);
+    }
+
+    #[test]
+    fn atomic_result_counter_merges_threads() {
+        // Given
+        let atomic = AtomicResultCounter::default();
+        let local = ResultCounter::new(1, 2, 3, 4, 5, 6);
+        // When
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    atomic.merge(&local);
+                    atomic.add(LoseNormal);
+                    atomic.add_backgammons(LoseGammon, 1);
+                });
+            }
+        });
+        // Then
+        assert_eq!(atomic.snapshot(), ResultCounter::new(4, 8, 12, 20, 20, 28));
+        assert_eq!(atomic.into_inner().sum(), 92);
+    }
 
     #[test]
     fn from_result_counter() {
         // sum of `results is 32, a power of 2. Makes fractions easier to handle.
         let mut counter = ResultCounter::default();
+        counter.add_results(WinNormal, 19);
         counter.add(WinGammon);
         counter.add_results(LoseNormal, 4);
         counter.add_results(LoseGammon, 8);
 
         let probabilities = Probabilities::from(&counter);
-        assert_eq!(probabilities.win_normal, 0.0);
+        assert_eq!(probabilities.win_normal, 0.59375);
         assert_eq!(probabilities.win_gammon, 0.03125);
         assert_eq!(probabilities.lose_normal, 0.125);
         assert_eq!(probabilities.lose_gammon, 0.25);
@@ -154,7 +727,7 @@ mod tests {
         };
         assert_eq!(
             probabilities.to_string(),
-            "0.04761905;0.0952381;0.14285715;0.1904762;0.23809524;0.2857143"
+            "0.04761905;0.0952381;0.1904762;0.23809524"
         );
     }
 
@@ -216,11 +789,41 @@ mod tests {
     #[test]
     fn win() {
         let probabilities = Probabilities {
+            win_normal: 0.5,
+            win_gammon: 0.25,
+            lose_normal: 0.125,
+            lose_gammon: 0.125,
+        };
+        assert_eq!(probabilities.win(), 0.75);
+    }
+
+    #[test]
+    fn is_consistent() {
+        let consistent = Probabilities {
             win_normal: 0.5,
             win_gammon: 0.2,
-            lose_normal: 0.1,
-            lose_gammon: 0.07,
+            lose_normal: 0.2,
+            lose_gammon: 0.1,
+        };
+        assert!(consistent.is_consistent());
+
+        let negative = Probabilities {
+            win_normal: 0.7,
+            lose_gammon: -0.1,
+            ..consistent.clone()
+        };
+        assert!(!negative.is_consistent());
+
+        let not_a_number = Probabilities {
+            win_normal: f32::NAN,
+            ..consistent.clone()
+        };
+        assert!(!not_a_number.is_consistent());
+
+        let sum_too_big = Probabilities {
+            win_normal: 0.6,
+            ..consistent
         };
-        assert_eq!(probabilities.win(), 0.82);
+        assert!(!sum_too_big.is_consistent());
     }
 }
@@ -0,0 +1,39 @@
+use crate::hypergammon;
+use crate::position::{GameState, Position, HYPERGAMMON_STARTING, NACKGAMMON_STARTING, STARTING};
+
+/// Variants of backgammon which only differ in the starting position and the number of checkers.
+///
+/// Move generation is the same for all variants, so the same [Position] can be used.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Like standard backgammon, but with two back checkers on the 24 and 23 point each.
+    Nackgammon,
+    /// Three checkers per player, starting on the 24, 23 and 22 point.
+    Hypergammon,
+}
+
+impl Variant {
+    pub fn starting_position(&self) -> Position {
+        match self {
+            Variant::Standard => STARTING,
+            Variant::Nackgammon => NACKGAMMON_STARTING,
+            Variant::Hypergammon => HYPERGAMMON_STARTING,
+        }
+    }
+
+    /// Like [Position::game_state], but takes into account what counts as a gammon in this variant.
+    pub fn game_state(&self, position: &Position) -> GameState {
+        match self {
+            Variant::Standard | Variant::Nackgammon => position.game_state(),
+            Variant::Hypergammon => hypergammon::game_state(position),
+        }
+    }
+}
+
+/// Everything besides the position itself that decides how a position is evaluated.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct EvalContext {
+    pub variant: Variant,
+}
@@ -0,0 +1,82 @@
+use clap::Parser;
+use engine::evaluator::Evaluator;
+use fibs::external::respond;
+use logic::wildbg_api::WildbgApi;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+
+/// Lets GNUBG or other tools use wildbg via GNUBG's "external" interface.
+///
+/// Each line sent by the client is a FIBS board, each answer is a single line, see
+/// `fibs::external::respond`. Send `exit` to close the connection. Clients are served one after
+/// the other.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// TCP port on localhost to listen on.
+    #[arg(long, default_value_t = 9008)]
+    port: u16,
+    /// Path of a Unix domain socket to listen on instead of a TCP port.
+    #[cfg(unix)]
+    #[arg(long)]
+    socket: Option<std::path::PathBuf>,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(message) = run(&args) {
+        eprintln!("{message}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let api = WildbgApi::try_default()?;
+    #[cfg(unix)]
+    if let Some(path) = &args.socket {
+        let listener = std::os::unix::net::UnixListener::bind(path)
+            .map_err(|error| format!("Could not bind to {}: {}", path.display(), error))?;
+        println!("Listening on {}", path.display());
+        for stream in listener.incoming() {
+            let stream = stream.map_err(|error| error.to_string())?;
+            serve(
+                &api,
+                stream.try_clone().map_err(|error| error.to_string())?,
+                stream,
+            );
+        }
+        return Ok(());
+    }
+    let listener = TcpListener::bind(("127.0.0.1", args.port))
+        .map_err(|error| format!("Could not bind to port {}: {}", args.port, error))?;
+    println!("Listening on port {}", args.port);
+    for stream in listener.incoming() {
+        let stream = stream.map_err(|error| error.to_string())?;
+        serve(
+            &api,
+            stream.try_clone().map_err(|error| error.to_string())?,
+            stream,
+        );
+    }
+    Ok(())
+}
+
+/// Answers requests until the client sends `exit` or closes the connection.
+fn serve<T: Evaluator, R: Read, W: Write>(api: &WildbgApi<T>, reader: R, mut writer: W) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "exit" {
+            return;
+        }
+        let response = respond(api, line).unwrap_or_else(|error| format!("Error: {error}"));
+        if writeln!(writer, "{response}").is_err() {
+            return;
+        }
+    }
+}
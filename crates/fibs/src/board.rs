@@ -0,0 +1,196 @@
+use engine::dice::Dice;
+use engine::position::Position;
+use logic::bg_move::BgMove;
+use std::str::FromStr;
+
+/// FIBS uses this match length for unlimited matches, in other words money games.
+const UNLIMITED: u32 = 9999;
+
+/// The state of a game as sent by FIBS with `set boardstyle 3`.
+///
+/// Example: `board:You:someplayer:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0`
+///
+/// Only the fields needed by the bot are stored. Index 0 of all arrays is for the logged in player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Board {
+    pub names: [String; 2],
+    pub match_length: u32,
+    pub score: [u32; 2],
+    /// From the point of view of the player, so the player is `x`.
+    pub position: Position,
+    /// `1` if it's the turn of `O`, `-1` if it's the turn of `X`, `0` if the game is over.
+    pub turn: i8,
+    /// Zeros if the player hasn't rolled yet.
+    pub dice: [usize; 2],
+    pub cube: u32,
+    pub may_double: [bool; 2],
+    /// `true` if the opponent doubled and the player has to accept or reject.
+    pub was_doubled: bool,
+    /// `1` if the player is `O`, `-1` if the player is `X`.
+    pub colour: i8,
+    /// `-1` if the player moves from FIBS point 24 to 1, `1` if the player moves from 1 to 24.
+    pub direction: i8,
+    /// Number of checkers the player has to move with the current dice.
+    pub can_move: usize,
+}
+
+impl FromStr for Board {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let position = Position::from_fibs_board(s).map_err(|error| error.to_string())?;
+        let fields: Vec<&str> = s.trim().split(':').collect();
+        let number = |index: usize| {
+            fields[index]
+                .parse::<i32>()
+                .map_err(|_| format!("Field {} of the board is not a number.", index))
+        };
+        let count = |index: usize| {
+            fields[index]
+                .parse::<u32>()
+                .map_err(|_| format!("Field {} of the board is not a count.", index))
+        };
+        Ok(Self {
+            names: [fields[1].to_string(), fields[2].to_string()],
+            match_length: count(3)?,
+            score: [count(4)?, count(5)?],
+            position,
+            turn: number(32)? as i8,
+            dice: [count(33)? as usize, count(34)? as usize],
+            cube: count(37)?,
+            may_double: [number(38)? == 1, number(39)? == 1],
+            was_doubled: number(40)? != 0,
+            colour: number(41)? as i8,
+            direction: number(42)? as i8,
+            can_move: count(49)? as usize,
+        })
+    }
+}
+
+impl Board {
+    pub fn is_my_turn(&self) -> bool {
+        self.turn == self.colour
+    }
+
+    /// `None` if the player hasn't rolled yet.
+    pub fn dice(&self) -> Option<Dice> {
+        match self.dice {
+            [0, 0] => None,
+            [die1, die2] => Some(Dice::new(die1, die2)),
+        }
+    }
+
+    /// `None` for unlimited matches and for matches which are over. Index 0 is for the player.
+    pub fn away(&self) -> Option<(u32, u32)> {
+        if self.match_length == UNLIMITED {
+            return None;
+        }
+        let away = |score: u32| {
+            self.match_length
+                .checked_sub(score)
+                .filter(|&away| away > 0)
+        };
+        Some((away(self.score[0])?, away(self.score[1])?))
+    }
+
+    /// The FIBS command for moving the checkers of the player, for example `move 24-18 13-9`.
+    pub fn move_command(&self, bg_move: &BgMove) -> String {
+        let point = |pip: usize| match pip {
+            25 => "bar".to_string(),
+            0 => "off".to_string(),
+            pip => self.point(pip).to_string(),
+        };
+        let mut command = "move".to_string();
+        for detail in bg_move.details() {
+            command.push_str(&format!(" {}-{}", point(detail.from()), point(detail.to())));
+        }
+        command
+    }
+
+    /// Converts a pip of the player into a FIBS point.
+    fn point(&self, pip: usize) -> usize {
+        if self.direction == -1 {
+            pip
+        } else {
+            25 - pip
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use engine::dice::Dice;
+    use engine::pos;
+    use engine::position::STARTING;
+    use logic::bg_move::BgMove;
+
+    const STARTING_O: &str = "board:You:someplayer:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+    const STARTING_X: &str = "board:You:someplayer:9999:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:3:1:1:1:1:0:-1:1:25:0:0:0:0:0:0:0:0:0";
+
+    #[test]
+    fn parse_fields() {
+        let board: Board = STARTING_O.parse().unwrap();
+        assert_eq!(board.names, ["You".to_string(), "someplayer".to_string()]);
+        assert_eq!(board.away(), Some((3, 3)));
+        assert!(board.is_my_turn());
+        assert_eq!(board.dice(), Some(Dice::new(6, 2)));
+        assert_eq!(board.cube, 1);
+        assert_eq!(board.may_double, [true, true]);
+        assert!(!board.was_doubled);
+        assert_eq!(board.can_move, 2);
+    }
+
+    #[test]
+    fn away_is_none_when_match_is_over() {
+        // Given
+        let final_score = STARTING_O.replacen(":3:0:0:", ":3:3:1:", 1);
+        // When
+        let board: Board = final_score.parse().unwrap();
+        // Then
+        assert_eq!(board.score, [3, 1]);
+        assert_eq!(board.away(), None);
+    }
+
+    #[test]
+    fn parse_fails_for_negative_counts() {
+        let negative_score = STARTING_O.replacen(":3:0:0:", ":3:-1:0:", 1);
+        assert_eq!(
+            negative_score.parse::<Board>(),
+            Err("Field 4 of the board is not a count.".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_fails_for_other_lines() {
+        assert!("board:You:someplayer:3:0:0".parse::<Board>().is_err());
+        assert!(STARTING_O
+            .replace(":6:2:", ":six:2:")
+            .parse::<Board>()
+            .is_err());
+    }
+
+    #[test]
+    fn position_for_both_directions() {
+        let o: Board = STARTING_O.parse().unwrap();
+        assert_eq!(o.position, STARTING);
+
+        let x: Board = STARTING_X.parse().unwrap();
+        assert!(!x.is_my_turn());
+        assert_eq!(x.dice(), None);
+        assert_eq!(x.away(), None);
+        assert_eq!(x.position, STARTING);
+    }
+
+    #[test]
+    fn move_command_for_both_directions() {
+        let old = pos!(x 25:1, 13:1; o 1:1);
+        let new = pos!(x 19:1, 11:1; o 1:1);
+        let bg_move = BgMove::new(&old, &new, &Dice::new(6, 2));
+
+        let o: Board = STARTING_O.parse().unwrap();
+        assert_eq!(o.move_command(&bg_move), "move bar-19 13-11");
+        let x: Board = STARTING_X.parse().unwrap();
+        assert_eq!(x.move_command(&bg_move), "move bar-6 12-14");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::board::Board;
+use crate::clip::Message;
+use engine::evaluator::Evaluator;
+use logic::resignation::Resignation;
+use logic::wildbg_api::{WildbgApi, WildbgConfig};
+
+/// Cubeless equity of the doubler up to which a double is accepted.
+const TAKE_POINT: f32 = 0.5;
+
+/// Decides how to react to messages from FIBS. Doesn't know anything about the network.
+///
+/// The bot accepts all invitations, never doubles and plays checkers with [WildbgApi::best_move].
+pub struct Bot<T: Evaluator> {
+    api: WildbgApi<T>,
+    /// The last board sent by FIBS.
+    board: Option<Board>,
+}
+
+impl<T: Evaluator> Bot<T> {
+    pub fn new(api: WildbgApi<T>) -> Self {
+        Self { api, board: None }
+    }
+
+    /// The commands to send to FIBS, often none.
+    pub fn respond(&mut self, message: Message) -> Result<Vec<String>, String> {
+        let commands = match message {
+            Message::Welcome => vec!["set boardstyle 3".to_string()],
+            Message::OwnInfo { autoboard, ready } => {
+                let mut commands = Vec::new();
+                if !autoboard {
+                    commands.push("toggle autoboard".to_string());
+                }
+                if !ready {
+                    commands.push("toggle ready".to_string());
+                }
+                commands
+            }
+            Message::Invitation { name } => vec![format!("join {}", name)],
+            Message::Board(board) => {
+                let commands = self.play(&board)?;
+                self.board = Some(board);
+                commands
+            }
+            Message::Doubled => match &self.board {
+                None => Vec::new(),
+                Some(board) => {
+                    vec![if should_take(&self.api, board) {
+                        "accept"
+                    } else {
+                        "reject"
+                    }
+                    .to_string()]
+                }
+            },
+            Message::ResignationOffered { points } => match &self.board {
+                None => Vec::new(),
+                Some(board) => {
+                    let resignation = match points / board.cube.max(1) {
+                        1 => Resignation::Normal,
+                        2 => Resignation::Gammon,
+                        _ => Resignation::Backgammon,
+                    };
+                    let accept =
+                        self.api
+                            .accept_resignation(&board.position, resignation, &config(board));
+                    vec![if accept { "accept" } else { "reject" }.to_string()]
+                }
+            },
+            Message::Other => Vec::new(),
+        };
+        Ok(commands)
+    }
+
+    fn play(&self, board: &Board) -> Result<Vec<String>, String> {
+        if !board.is_my_turn() || board.was_doubled {
+            return Ok(Vec::new());
+        }
+        let commands = match board.dice() {
+            None => vec!["roll".to_string()],
+            Some(_) if board.can_move == 0 => Vec::new(),
+            Some(dice) => {
+                let bg_move = self.api.best_move(&board.position, &dice, &config(board));
+                vec![board.move_command(&bg_move)]
+            }
+        };
+        Ok(commands)
+    }
+}
+
+/// `true` if the player should accept the double of the opponent, who is on turn.
+pub(crate) fn should_take<T: Evaluator>(api: &WildbgApi<T>, board: &Board) -> bool {
+    let position = board.position.sides_switched();
+    api.probabilities(&position).equity() <= TAKE_POINT
+}
+
+pub(crate) fn config(board: &Board) -> WildbgConfig {
+    WildbgConfig {
+        away: board.away(),
+        gammons_count: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bot::Bot;
+    use crate::clip::Message;
+    use engine::test_utils::EvaluatorFake;
+    use logic::wildbg_api::WildbgApi;
+
+    fn bot() -> Bot<EvaluatorFake> {
+        Bot::new(WildbgApi::new(EvaluatorFake {}))
+    }
+
+    fn board(line: &str) -> Message {
+        Message::parse(line).unwrap()
+    }
+
+    const BEFORE_ROLL: &str = "board:You:alice:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0";
+    const AFTER_ROLL: &str = "board:You:alice:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+    const OPPONENTS_TURN: &str = "board:You:alice:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:-1:0:0:3:1:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0";
+
+    #[test]
+    fn setup_after_login() {
+        let mut bot = bot();
+        assert_eq!(
+            bot.respond(Message::Welcome),
+            Ok(vec!["set boardstyle 3".to_string()])
+        );
+        let own_info = Message::OwnInfo {
+            autoboard: false,
+            ready: true,
+        };
+        assert_eq!(
+            bot.respond(own_info),
+            Ok(vec!["toggle autoboard".to_string()])
+        );
+        let invitation = Message::Invitation {
+            name: "alice".to_string(),
+        };
+        assert_eq!(bot.respond(invitation), Ok(vec!["join alice".to_string()]));
+    }
+
+    #[test]
+    fn roll_and_move_on_own_turn() {
+        let mut bot = bot();
+        assert_eq!(
+            bot.respond(board(BEFORE_ROLL)),
+            Ok(vec!["roll".to_string()])
+        );
+        // 13/5 is the only way to put a checker on the 5 point with 6-2.
+        let commands = bot.respond(board(AFTER_ROLL)).unwrap();
+        assert_eq!(commands, vec!["move 13-11 11-5".to_string()]);
+        assert_eq!(bot.respond(board(OPPONENTS_TURN)), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn take_double_in_even_position() {
+        let mut bot = bot();
+        // Without a board the bot doesn't know the position.
+        assert_eq!(bot.respond(Message::Doubled), Ok(Vec::new()));
+
+        bot.respond(board(OPPONENTS_TURN)).unwrap();
+        assert_eq!(
+            bot.respond(Message::Doubled),
+            Ok(vec!["accept".to_string()])
+        );
+    }
+
+    #[test]
+    fn accept_resignation_in_even_position() {
+        let mut bot = bot();
+        bot.respond(board(OPPONENTS_TURN)).unwrap();
+        let resignation = Message::ResignationOffered { points: 1 };
+        assert_eq!(bot.respond(resignation), Ok(vec!["accept".to_string()]));
+    }
+}
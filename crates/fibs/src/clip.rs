@@ -0,0 +1,134 @@
+use crate::board::Board;
+
+/// Name and version of the client, sent to FIBS when logging in.
+const CLIENT_NAME: &str = "wildbg";
+/// Version of the Client Interface Protocol (CLIP) this client understands.
+const CLIP_VERSION: u32 = 1008;
+
+/// The command to log in with CLIP enabled. Must be sent after the `login:` prompt.
+pub fn login_command(user: &str, password: &str) -> String {
+    format!("login {CLIENT_NAME} {CLIP_VERSION} {user} {password}")
+}
+
+/// A line sent by FIBS which is relevant for the bot.
+///
+/// CLIP messages start with a number, game related messages are plain text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// `1 name lastlogin lasthost`, sent after a successful login.
+    Welcome,
+    /// `2 name allowpip autoboard ...`, the settings of the logged in player.
+    OwnInfo {
+        autoboard: bool,
+        ready: bool,
+    },
+    Board(Board),
+    /// `alice wants to play a 5 point match with you.`
+    Invitation {
+        name: String,
+    },
+    /// `alice doubles. Type 'accept' or 'reject'.`
+    Doubled,
+    /// `alice wants to resign. You will win 2 points. Type 'accept' or 'reject'.`
+    ResignationOffered {
+        points: u32,
+    },
+    /// Anything else, like chat messages or the list of logged in players.
+    Other,
+}
+
+impl Message {
+    /// Returns an error if the line looks relevant but can't be parsed.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line.starts_with("board:") {
+            return line.parse().map(Message::Board);
+        }
+        let message = match tokens.as_slice() {
+            ["1", ..] => Message::Welcome,
+            ["2", ..] => {
+                if tokens.len() < 18 {
+                    return Err(format!("Own info is too short: {}", line));
+                }
+                Message::OwnInfo {
+                    autoboard: tokens[3] == "1",
+                    ready: tokens[17] == "1",
+                }
+            }
+            [name, "wants", "to", "play", ..] if line.ends_with("match with you.") => {
+                Message::Invitation {
+                    name: name.to_string(),
+                }
+            }
+            [_, "doubles.", ..] => Message::Doubled,
+            [_, "wants", "to", "resign.", "You", "will", "win", points, ..] => {
+                Message::ResignationOffered {
+                    points: points
+                        .parse()
+                        .map_err(|_| format!("Can't parse resignation: {}", line))?,
+                }
+            }
+            _ => Message::Other,
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clip::{login_command, Message};
+
+    #[test]
+    fn login() {
+        assert_eq!(
+            login_command("bot", "secret"),
+            "login wildbg 1008 bot secret"
+        );
+    }
+
+    #[test]
+    fn parse_clip_messages() {
+        assert_eq!(
+            Message::parse("1 wildbg 1041253132 192.168.1.308"),
+            Ok(Message::Welcome)
+        );
+        let own_info =
+            "2 wildbg 1 1 0 0 0 0 1 1 2396 0 1 0 1 3457.85 0 0 0 0 0 Australia/Melbourne";
+        assert_eq!(
+            Message::parse(own_info),
+            Ok(Message::OwnInfo {
+                autoboard: true,
+                ready: false,
+            })
+        );
+        assert!(Message::parse("2 wildbg 1 1").is_err());
+        assert_eq!(Message::parse("12 alice Hello!"), Ok(Message::Other));
+    }
+
+    #[test]
+    fn parse_game_messages() {
+        assert_eq!(
+            Message::parse("alice wants to play a 5 point match with you."),
+            Ok(Message::Invitation {
+                name: "alice".to_string()
+            })
+        );
+        assert_eq!(
+            Message::parse("alice wants to play an unlimited match with you."),
+            Ok(Message::Invitation {
+                name: "alice".to_string()
+            })
+        );
+        assert_eq!(
+            Message::parse("alice doubles. Type 'accept' or 'reject'."),
+            Ok(Message::Doubled)
+        );
+        assert_eq!(
+            Message::parse(
+                "alice wants to resign. You will win 2 points. Type 'accept' or 'reject'."
+            ),
+            Ok(Message::ResignationOffered { points: 2 })
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use crate::board::Board;
+use crate::bot::{config, should_take};
+use engine::evaluator::Evaluator;
+use engine::position::X_BAR;
+use logic::wildbg_api::WildbgApi;
+
+/// Answers a single request of GNUBG's "external" interface.
+///
+/// GNUBG's external interface is a line based protocol: the client sends a FIBS board in
+/// "boardstyle 3" and gets back a single line with the decision of the player named `You`:
+/// - `roll` if no dice have been rolled yet. `wildbg` never doubles.
+/// - `take` or `drop` if the opponent has doubled.
+/// - The move, for example `24/18 13/11` or `bar/20 6/off`, from the point of view of the player.
+///   The line is empty if no checker can be moved.
+pub fn respond<T: Evaluator>(api: &WildbgApi<T>, request: &str) -> Result<String, String> {
+    let board: Board = request.parse()?;
+    if board.was_doubled {
+        let take = should_take(api, &board);
+        return Ok(if take { "take" } else { "drop" }.to_string());
+    }
+    if !board.is_my_turn() {
+        return Err("It's not the turn of the player.".to_string());
+    }
+    let response = match board.dice() {
+        None => "roll".to_string(),
+        Some(dice) => {
+            let bg_move = api.best_move(&board.position, &dice, &config(&board));
+            let point = |pip: usize| match pip {
+                X_BAR => "bar".to_string(),
+                0 => "off".to_string(),
+                pip => pip.to_string(),
+            };
+            bg_move
+                .details()
+                .iter()
+                .map(|detail| format!("{}/{}", point(detail.from()), point(detail.to())))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    };
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::external::respond;
+    use engine::test_utils::EvaluatorFake;
+    use logic::wildbg_api::WildbgApi;
+
+    const BEFORE_ROLL: &str = "board:You:alice:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:0:0:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0";
+    const AFTER_ROLL: &str = "board:You:alice:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+    const OPPONENTS_TURN: &str = "board:You:alice:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:-1:0:0:3:1:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0";
+
+    #[test]
+    fn roll_and_move() {
+        let api = WildbgApi::new(EvaluatorFake {});
+        assert_eq!(respond(&api, BEFORE_ROLL), Ok("roll".to_string()));
+        assert_eq!(respond(&api, AFTER_ROLL), Ok("13/11 11/5".to_string()));
+    }
+
+    #[test]
+    fn take_double() {
+        let api = WildbgApi::new(EvaluatorFake {});
+        let doubled = OPPONENTS_TURN.replace(":1:1:0:1:-1:", ":1:1:1:1:-1:");
+        assert_eq!(respond(&api, &doubled), Ok("take".to_string()));
+    }
+
+    #[test]
+    fn errors() {
+        let api = WildbgApi::new(EvaluatorFake {});
+        assert!(respond(&api, OPPONENTS_TURN).is_err());
+        assert!(respond(&api, "version").is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod board;
+pub mod bot;
+pub mod clip;
+pub mod external;
@@ -0,0 +1,91 @@
+use clap::Parser;
+use fibs::bot::Bot;
+use fibs::clip::{login_command, Message};
+use logic::wildbg_api::WildbgApi;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+/// Runs wildbg as a bot on the First Internet Backgammon Server (FIBS).
+///
+/// The bot accepts all invitations. Everything sent and received is printed to stdout, only the
+/// password is masked.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Name of the FIBS account of the bot.
+    #[arg(long)]
+    user: String,
+    #[arg(long)]
+    password: String,
+    #[arg(long, default_value = "fibs.com")]
+    host: String,
+    #[arg(long, default_value_t = 4321)]
+    port: u16,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(message) = run(&args) {
+        eprintln!("{message}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let api = WildbgApi::try_default()?;
+    let mut bot = Bot::new(api);
+
+    let stream = TcpStream::connect((args.host.as_str(), args.port))
+        .map_err(|error| format!("Could not connect to {}: {}", args.host, error))?;
+    let mut writer = stream.try_clone().map_err(|error| error.to_string())?;
+    let mut reader = BufReader::new(stream);
+
+    wait_for_login_prompt(&mut reader)?;
+    // Everything sent is echoed to stdout, except the password.
+    println!("> {}", login_command(&args.user, "********"));
+    write_line(&mut writer, &login_command(&args.user, &args.password))?;
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let bytes = reader
+            .read_line(&mut line)
+            .map_err(|error| error.to_string())?;
+        if bytes == 0 {
+            return Err("Connection closed by FIBS.".to_string());
+        }
+        let line = line.trim();
+        println!("{line}");
+        let commands = Message::parse(line).and_then(|message| bot.respond(message));
+        match commands {
+            Ok(commands) => {
+                for command in commands {
+                    send(&mut writer, &command)?;
+                }
+            }
+            // A single strange line shouldn't end the session.
+            Err(message) => eprintln!("{message}"),
+        }
+    }
+}
+
+/// The prompt is not terminated by a newline, so we can't read it line by line.
+fn wait_for_login_prompt<R: Read>(reader: &mut BufReader<R>) -> Result<(), String> {
+    let mut received = Vec::new();
+    for byte in reader.bytes() {
+        received.push(byte.map_err(|error| error.to_string())?);
+        if received.ends_with(b"login: ") {
+            return Ok(());
+        }
+    }
+    Err("Connection closed before login prompt.".to_string())
+}
+
+fn send<W: Write>(writer: &mut W, command: &str) -> Result<(), String> {
+    println!("> {command}");
+    write_line(writer, command)
+}
+
+fn write_line<W: Write>(writer: &mut W, command: &str) -> Result<(), String> {
+    write!(writer, "{command}\r\n").map_err(|error| error.to_string())
+}
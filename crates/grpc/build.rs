@@ -0,0 +1,6 @@
+fn main() {
+    // A vendored `protoc`, so building wildbg doesn't need an installed one.
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("protoc is vendored");
+    std::env::set_var("PROTOC", protoc);
+    tonic_build::compile_protos("proto/wildbg.proto").expect("proto/wildbg.proto compiles");
+}
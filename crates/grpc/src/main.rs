@@ -0,0 +1,42 @@
+use clap::Parser;
+use engine::composite::CompositeEvaluator;
+use engine::opening_book::OpeningBookEvaluator;
+use grpc::proto::engine_server::EngineServer;
+use grpc::service::{EngineService, DEFAULT_MAX_ROLLOUTS};
+use tonic::transport::Server;
+
+/// gRPC server for evaluations, moves and rollouts, see `proto/wildbg.proto`.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// The address to host the server at.
+    #[arg(short, long, default_value_t = String::from("127.0.0.1"))]
+    address: String,
+
+    /// The port to host the server at.
+    #[arg(short, long, default_value_t = 50051)]
+    port: u16,
+
+    /// Maximum number of rollouts running at the same time.
+    #[arg(long, default_value_t = DEFAULT_MAX_ROLLOUTS)]
+    max_rollouts: usize,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let evaluator = CompositeEvaluator::try_default()
+        .unwrap_or_else(|error| panic!("Neural net could not be constructed: {error}"));
+    let service = EngineService::new(OpeningBookEvaluator::new(evaluator))
+        .with_max_rollouts(args.max_rollouts);
+    let address = format!("{}:{}", args.address, args.port)
+        .parse()
+        .unwrap_or_else(|_| panic!("Invalid address: '{}:{}'", args.address, args.port));
+
+    println!("The gRPC server is running at '{address}'.");
+    Server::builder()
+        .add_service(EngineServer::new(service))
+        .serve(address)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,40 @@
+// Messages, client and server of `proto/wildbg.proto`, generated by `tonic-build` in `build.rs`.
+
+tonic::include_proto!("wildbg");
+
+#[cfg(test)]
+mod tests {
+    use crate::proto::engine_client::EngineClient;
+    use crate::proto::engine_server::EngineServer;
+    use crate::proto::EvaluateRequest;
+    use crate::service::EngineService;
+    use engine::evaluator::RandomEvaluator;
+    use engine::position::STARTING;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    #[tokio::test]
+    async fn evaluate_over_the_network() {
+        // Given
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(EngineServer::new(EngineService::new(RandomEvaluator {})))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let mut client = EngineClient::connect(format!("http://{address}"))
+            .await
+            .unwrap();
+        let request = EvaluateRequest {
+            position_ids: vec![STARTING.position_id()],
+        };
+        // When
+        let response = client.evaluate(request).await;
+        // Then
+        let evaluations = response.unwrap().into_inner().evaluations;
+        assert_eq!(evaluations.len(), 1);
+        assert_eq!(evaluations[0].position_id, STARTING.position_id());
+    }
+}
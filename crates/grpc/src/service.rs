@@ -0,0 +1,312 @@
+use crate::proto::engine_server::Engine;
+use crate::proto::{
+    CheckerMove, EvaluateRequest, EvaluateResponse, Evaluation, Move, MovesRequest, MovesResponse,
+    RolloutProgress, RolloutRequest,
+};
+use coach::rollout::{RolloutEvaluator, RolloutSummary, GAMES_PER_REPETITION};
+use engine::dice::Dice;
+use engine::dice_gen::FastrandDice;
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use engine::probabilities::{Probabilities, ResultCounter};
+use logic::bg_move::BgMove;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+/// Maximum number of positions for one `Evaluate` call.
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Maximum number of games for one `Rollout` call.
+pub const MAX_ROLLOUT_GAMES: u32 = 10 * GAMES_PER_REPETITION as u32;
+
+/// Default for [EngineService::with_max_rollouts].
+pub const DEFAULT_MAX_ROLLOUTS: usize = 4;
+
+/// Implementation of the `Engine` service of `proto/wildbg.proto`.
+///
+/// Evaluations run on the blocking thread pool of tokio, so that they don't stall other requests.
+pub struct EngineService<T: Evaluator> {
+    evaluator: Arc<T>,
+    rollouts: Arc<Semaphore>,
+}
+
+impl<T: Evaluator> EngineService<T> {
+    pub fn new(evaluator: T) -> Self {
+        Self {
+            evaluator: Arc::new(evaluator),
+            rollouts: Arc::new(Semaphore::new(DEFAULT_MAX_ROLLOUTS)),
+        }
+    }
+
+    /// At most `max_rollouts` rollouts run at the same time, further calls fail with
+    /// `RESOURCE_EXHAUSTED`. Each rollout already uses all CPU cores.
+    pub fn with_max_rollouts(self, max_rollouts: usize) -> Self {
+        Self {
+            rollouts: Arc::new(Semaphore::new(max_rollouts)),
+            ..self
+        }
+    }
+}
+
+impl From<Probabilities> for crate::proto::Probabilities {
+    fn from(value: Probabilities) -> Self {
+        Self {
+            win: value.win(),
+            win_gammon: value.win_gammon,
+            lose_gammon: value.lose_gammon,
+        }
+    }
+}
+
+fn position(position_id: &str) -> Result<Position, String> {
+    Position::try_from_id(position_id).map_err(|error| format!("'{position_id}': {error}"))
+}
+
+fn internal_error<E>(_: E) -> Status {
+    Status::internal("Evaluation failed.")
+}
+
+#[tonic::async_trait]
+impl<T: Evaluator + Send + Sync + 'static> Engine for EngineService<T> {
+    async fn evaluate(
+        &self,
+        request: Request<EvaluateRequest>,
+    ) -> Result<Response<EvaluateResponse>, Status> {
+        let position_ids = request.into_inner().position_ids;
+        if position_ids.len() > MAX_BATCH_SIZE {
+            return Err(Status::invalid_argument(format!(
+                "At most {MAX_BATCH_SIZE} positions can be evaluated in one request."
+            )));
+        }
+        let positions = position_ids
+            .iter()
+            .map(|id| position(id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Status::invalid_argument)?;
+        let evaluator = self.evaluator.clone();
+        let evaluations = tokio::task::spawn_blocking(move || evaluator.eval_batch(positions))
+            .await
+            .map_err(internal_error)?
+            .into_iter()
+            .zip(position_ids)
+            .map(|((_, probabilities), position_id)| Evaluation {
+                position_id,
+                equity: probabilities.equity(),
+                probabilities: Some(probabilities.into()),
+            })
+            .collect();
+        Ok(Response::new(EvaluateResponse { evaluations }))
+    }
+
+    async fn moves(
+        &self,
+        request: Request<MovesRequest>,
+    ) -> Result<Response<MovesResponse>, Status> {
+        let request = request.into_inner();
+        let position = position(&request.position_id).map_err(Status::invalid_argument)?;
+        let dice = Dice::try_from((request.die1 as usize, request.die2 as usize))
+            .map_err(Status::invalid_argument)?;
+        let evaluator = self.evaluator.clone();
+        let moves = tokio::task::spawn_blocking(move || {
+            evaluator
+                .positions_and_probabilities_by_equity(&position, &dice)
+                .into_iter()
+                .map(|(new_position, probabilities)| Move {
+                    play: BgMove::new(&position, &new_position, &dice)
+                        .details()
+                        .iter()
+                        .map(|detail| CheckerMove {
+                            from: detail.from() as u32,
+                            to: detail.to() as u32,
+                        })
+                        .collect(),
+                    position_id: new_position.position_id(),
+                    equity: probabilities.equity(),
+                    probabilities: Some(probabilities.into()),
+                })
+                .collect()
+        })
+        .await
+        .map_err(internal_error)?;
+        Ok(Response::new(MovesResponse { moves }))
+    }
+
+    type RolloutStream = ReceiverStream<Result<RolloutProgress, Status>>;
+
+    async fn rollout(
+        &self,
+        request: Request<RolloutRequest>,
+    ) -> Result<Response<Self::RolloutStream>, Status> {
+        let request = request.into_inner();
+        let position = position(&request.position_id).map_err(Status::invalid_argument)?;
+        if position.has_lost() || position.sides_switched().has_lost() {
+            return Err(Status::invalid_argument("The game is already over."));
+        }
+        let games = request.games.unwrap_or(GAMES_PER_REPETITION as u32);
+        if games == 0 || games > MAX_ROLLOUT_GAMES {
+            return Err(Status::invalid_argument(
+                "The number of games must be between 1 and 12960.",
+            ));
+        }
+        let repetitions = (games as usize).div_ceil(GAMES_PER_REPETITION);
+        let seed = request.seed.unwrap_or_else(FastrandDice::random_seed);
+        let permit = self.rollouts.clone().try_acquire_owned().map_err(|_| {
+            Status::resource_exhausted("Too many rollouts are running, please try again later.")
+        })?;
+
+        let (sender, receiver) = mpsc::channel(1);
+        let evaluator = SharedEvaluator(self.evaluator.clone());
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let evaluator = RolloutEvaluator::with_evaluator_and_seed(evaluator, seed);
+            let mut results = ResultCounter::default();
+            evaluator.results_with_progress(&position, seed, repetitions, |repetition| {
+                results = std::mem::take(&mut results).combine(repetition);
+                let summary = RolloutSummary::from(&results);
+                let progress = RolloutProgress {
+                    games: summary.games,
+                    total_games: (repetitions * GAMES_PER_REPETITION) as u64,
+                    equity: summary.probabilities.equity(),
+                    equity_error: summary.equity_error,
+                    probabilities: Some(summary.probabilities.into()),
+                };
+                // If the client has gone, sending fails and the rollout stops.
+                sender.blocking_send(Ok(progress)).is_ok()
+            });
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+/// [RolloutEvaluator] owns its evaluator, but the one of [EngineService] is shared by all calls.
+struct SharedEvaluator<T: Evaluator>(Arc<T>);
+
+impl<T: Evaluator> Evaluator for SharedEvaluator<T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        self.0.eval(pos)
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        self.0.eval_batch(positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::proto::engine_server::Engine;
+    use crate::proto::{CheckerMove, EvaluateRequest, MovesRequest, RolloutRequest};
+    use crate::service::EngineService;
+    use engine::pos;
+    use engine::test_utils::EvaluatorFake;
+    use tokio_stream::StreamExt;
+    use tonic::{Code, Request};
+
+    #[tokio::test]
+    async fn evaluate_keeps_order() {
+        // Given
+        let service = EngineService::new(EvaluatorFake {});
+        let good = pos!(x 5:2; o 24:2).position_id();
+        let neutral = pos!(x 6:2; o 24:2).position_id();
+        let request = EvaluateRequest {
+            position_ids: vec![good.clone(), neutral.clone()],
+        };
+        // When
+        let response = service.evaluate(Request::new(request)).await.unwrap();
+        // Then
+        let evaluations = response.into_inner().evaluations;
+        assert_eq!(evaluations[0].position_id, good);
+        assert!((evaluations[0].equity - 0.6).abs() < 0.0001);
+        assert_eq!(evaluations[1].position_id, neutral);
+        assert_eq!(evaluations[1].equity, 0.0);
+    }
+
+    #[tokio::test]
+    async fn evaluate_illegal_position_id() {
+        let service = EngineService::new(EvaluatorFake {});
+        let request = EvaluateRequest {
+            position_ids: vec!["abc".to_string()],
+        };
+        let status = service.evaluate(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().starts_with("'abc': "));
+    }
+
+    #[tokio::test]
+    async fn moves_best_first() {
+        // Given
+        let service = EngineService::new(EvaluatorFake {});
+        let request = MovesRequest {
+            position_id: pos!(x 8:1, 6:1; o 24:2).position_id(),
+            die1: 3,
+            die2: 1,
+        };
+        // When
+        let response = service.moves(Request::new(request)).await.unwrap();
+        // Then
+        let best = &response.into_inner().moves[0];
+        assert_eq!(
+            best.play,
+            vec![
+                CheckerMove { from: 8, to: 5 },
+                CheckerMove { from: 6, to: 5 }
+            ]
+        );
+        assert_eq!(best.position_id, pos!(x 5:2; o 24:2).position_id());
+    }
+
+    #[tokio::test]
+    async fn rollout_streams_progress() {
+        // Given
+        let service = EngineService::new(EvaluatorFake {});
+        let request = RolloutRequest {
+            position_id: pos!(x 1:1; o 24:1).position_id(),
+            games: Some(2000),
+            seed: Some(1),
+        };
+        // When
+        let stream = service.rollout(Request::new(request)).await.unwrap();
+        // Then
+        let progress: Vec<_> = stream.into_inner().collect().await;
+        assert_eq!(progress.len(), 2);
+        let last = progress[1].as_ref().unwrap();
+        assert_eq!(last.games, 2592);
+        assert_eq!(last.total_games, 2592);
+    }
+
+    #[tokio::test]
+    async fn rollout_too_many_at_once() {
+        // Given
+        let service = EngineService::new(EvaluatorFake {}).with_max_rollouts(1);
+        let request = || RolloutRequest {
+            position_id: pos!(x 1:1; o 24:1).position_id(),
+            games: Some(1296),
+            seed: Some(1),
+        };
+        // When
+        let first = service.rollout(Request::new(request())).await.unwrap();
+        let second = service.rollout(Request::new(request())).await;
+        // Then
+        assert_eq!(second.unwrap_err().code(), Code::ResourceExhausted);
+        let progress: Vec<_> = first.into_inner().collect().await;
+        assert_eq!(progress.len(), 1);
+        assert!(service.rollout(Request::new(request())).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rollout_too_many_games() {
+        let service = EngineService::new(EvaluatorFake {});
+        let request = RolloutRequest {
+            position_id: pos!(x 1:1; o 24:1).position_id(),
+            games: Some(20_000),
+            seed: None,
+        };
+        let status = service.rollout(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "The number of games must be between 1 and 12960."
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use engine::dice::{Dice, ALL_21};
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+
+type Error = &'static str;
+
+/// A single checker play as it happened in a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveRecord {
+    /// Position before the move, from the point of view of the player on turn.
+    pub position: Position,
+    pub dice: Dice,
+    /// Position after the move, still from the point of view of the player who moved.
+    pub played: Position,
+}
+
+/// Result of analyzing a single [MoveRecord].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveAnalysis {
+    /// Best position after moving, from the point of view of the player who moved.
+    pub best: Position,
+    /// Difference between the value of the best move and the played move. Never negative.
+    pub equity_loss: f32,
+}
+
+/// Aggregated equity losses of several analyzed moves, typically of one player in a match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnalysisSummary {
+    pub move_count: usize,
+    pub total_equity_loss: f32,
+    pub max_equity_loss: f32,
+}
+
+impl AnalysisSummary {
+    pub fn add(&mut self, analysis: &MoveAnalysis) {
+        self.move_count += 1;
+        self.total_equity_loss += analysis.equity_loss;
+        self.max_equity_loss = self.max_equity_loss.max(analysis.equity_loss);
+    }
+
+    /// Zero if no moves have been added.
+    pub fn average_equity_loss(&self) -> f32 {
+        if self.move_count == 0 {
+            0.0
+        } else {
+            self.total_equity_loss / self.move_count as f32
+        }
+    }
+}
+
+/// Compares the played move with the best move according to `value`.
+///
+/// `value` is applied to probabilities from the point of view of the player who moved, so bigger is better.
+/// Returns an error if the played move is not legal.
+pub fn analyze_move<T, F>(
+    evaluator: &T,
+    record: &MoveRecord,
+    value: F,
+) -> Result<MoveAnalysis, Error>
+where
+    T: Evaluator,
+    F: Fn(&Probabilities) -> f32,
+{
+    let played = record.played.sides_switched();
+    let values = values_after_moving(evaluator, &record.position, &record.dice, &value);
+    let played_value = values
+        .iter()
+        .find(|(position, _)| position == &played)
+        .ok_or("The played move is not legal.")?
+        .1;
+    let (best, best_value) = values
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("There is always at least one legal move.");
+    Ok(MoveAnalysis {
+        best: best.sides_switched(),
+        equity_loss: (best_value - played_value).max(0.0),
+    })
+}
+
+/// Value of the best move for each of the 36 rolls, from the point of view of the player on roll.
+///
+/// Rolls with a big difference to the average are good or bad for the player, a big spread
+/// means the position is volatile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemperatureMap {
+    values: [[f32; 6]; 6],
+}
+
+impl TemperatureMap {
+    /// Both dice must be between 1 and 6. The order of the dice doesn't matter.
+    pub fn value(&self, die1: usize, die2: usize) -> f32 {
+        self.values[die1 - 1][die2 - 1]
+    }
+
+    /// The 6x6 grid, index 0 is for a die showing 1.
+    pub fn values(&self) -> &[[f32; 6]; 6] {
+        &self.values
+    }
+
+    /// Expected value before rolling the dice, assuming the best move is played.
+    pub fn average(&self) -> f32 {
+        self.values.iter().flatten().sum::<f32>() / 36.0
+    }
+
+    pub fn standard_deviation(&self) -> f32 {
+        let average = self.average();
+        let variance = self
+            .values
+            .iter()
+            .flatten()
+            .map(|value| (value - average).powi(2))
+            .sum::<f32>()
+            / 36.0;
+        variance.sqrt()
+    }
+}
+
+/// Evaluates the best move for all 21 different rolls.
+///
+/// `value` is applied to probabilities from the point of view of the player on roll, so bigger is better.
+pub fn temperature_map<T, F>(evaluator: &T, position: &Position, value: F) -> TemperatureMap
+where
+    T: Evaluator,
+    F: Fn(&Probabilities) -> f32,
+{
+    let mut values = [[0.0; 6]; 6];
+    for (dice, _) in ALL_21 {
+        let best = values_after_moving(evaluator, position, &dice, &value)
+            .into_iter()
+            .map(|(_, value)| value)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let (die1, die2) = match dice {
+            Dice::Mixed(mixed) => (mixed.big(), mixed.small()),
+            Dice::Double(die) => (die, die),
+        };
+        values[die1 - 1][die2 - 1] = best;
+        values[die2 - 1][die1 - 1] = best;
+    }
+    TemperatureMap { values }
+}
+
+/// All legal positions after moving, from the point of view of the opponent, together with
+/// `value` from the point of view of the player who moved.
+fn values_after_moving<T, F>(
+    evaluator: &T,
+    position: &Position,
+    dice: &Dice,
+    value: &F,
+) -> Vec<(Position, f32)>
+where
+    T: Evaluator,
+    F: Fn(&Probabilities) -> f32,
+{
+    evaluator
+        .eval_batch(position.all_positions_after_moving(dice))
+        .into_iter()
+        .map(|(position, probabilities)| (position, value(&probabilities.switch_sides())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{
+        analyze_move, temperature_map, AnalysisSummary, MoveAnalysis, MoveRecord,
+    };
+    use engine::dice::Dice;
+    use engine::evaluator::Evaluator;
+    use engine::pos;
+    use engine::position::Position;
+    use engine::probabilities::Probabilities;
+
+    /// Test double. The player who moved wants to have checkers on their 5 point.
+    ///
+    /// Positions are evaluated from the point of view of the opponent, so that's pip 20.
+    struct EvaluatorFake {}
+    impl Evaluator for EvaluatorFake {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let lose = 0.1 * pos.pip(20).unsigned_abs() as f32;
+            Probabilities {
+                win_normal: 1.0 - lose,
+                lose_normal: lose,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[test]
+    fn equity_loss_of_played_move() {
+        // Given
+        let record = MoveRecord {
+            position: pos!(x 20:2, 7:2; o 1:2),
+            dice: Dice::new(2, 1),
+            played: pos!(x 20:2, 7:1, 4:1; o 1:2),
+        };
+        // When
+        let analysis = analyze_move(&EvaluatorFake {}, &record, |p| p.equity()).unwrap();
+        // Then
+        assert_eq!(analysis.best.pip(5), 1);
+        assert!((analysis.equity_loss - 0.2).abs() < 0.0001);
+    }
+
+    #[test]
+    fn illegal_move_is_an_error() {
+        let record = MoveRecord {
+            position: pos!(x 20:2; o 1:2),
+            dice: Dice::new(2, 1),
+            played: pos!(x 20:1, 10:1; o 1:2),
+        };
+        assert!(analyze_move(&EvaluatorFake {}, &record, |p| p.equity()).is_err());
+    }
+
+    #[test]
+    fn summary() {
+        let mut summary = AnalysisSummary::default();
+        assert_eq!(summary.average_equity_loss(), 0.0);
+        for equity_loss in [0.0, 0.25, 0.05] {
+            summary.add(&MoveAnalysis {
+                best: pos!(x 1:1; o 24:1),
+                equity_loss,
+            });
+        }
+        assert_eq!(summary.move_count, 3);
+        assert_eq!(summary.total_equity_loss, 0.3);
+        assert_eq!(summary.max_equity_loss, 0.25);
+        assert_eq!(summary.average_equity_loss(), 0.1);
+    }
+
+    #[test]
+    fn temperature_map_by_roll() {
+        // Given
+        let position = pos!(x 7:2; o 1:2);
+        // When
+        let map = temperature_map(&EvaluatorFake {}, &position, |p| p.equity());
+        // Then
+        assert_eq!(map.value(2, 1), map.value(1, 2));
+        // One checker to the 5 point
+        assert!((map.value(2, 1) - -0.8).abs() < 0.0001);
+        // Both checkers to the 5 point
+        assert!((map.value(1, 1) - -0.6).abs() < 0.0001);
+        // Blocked
+        assert!((map.value(6, 6) - -1.0).abs() < 0.0001);
+        assert!(map.average() > -1.0 && map.average() < -0.8);
+        assert!(map.standard_deviation() > 0.0);
+    }
+}
@@ -1,5 +1,5 @@
 use engine::dice::Dice;
-use engine::position::Position;
+use engine::position::{Position, O_BAR};
 #[cfg(feature = "web")]
 use serde::Serialize;
 use std::cmp::max;
@@ -16,7 +16,7 @@ pub struct BgMove {
     pub(crate) details: Vec<MoveDetail>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "web", derive(Serialize, ToSchema))]
 /// Single movement of one checker. We always move from bigger pips to smaller pips.
 /// If the same checker is moved more than once, multiple `MoveDetail`s are given.
@@ -28,6 +28,8 @@ pub struct MoveDetail {
     /// bear off is represented by `0`.
     #[cfg_attr(feature = "web", schema(minimum = 0, maximum = 24))]
     pub(crate) to: usize,
+    /// `true` if an opponent's blot on `to` is hit and put on the bar.
+    pub(crate) hit: bool,
 }
 
 impl MoveDetail {
@@ -38,6 +40,20 @@ impl MoveDetail {
     pub fn to(&self) -> usize {
         self.to
     }
+
+    pub fn hit(&self) -> bool {
+        self.hit
+    }
+}
+
+/// A legal move together with the position it leads to.
+///
+/// In contrast to [Position::all_positions_after_moving], `position` has *not* switched sides.
+/// It is still from the point of view of the player who made the move.
+#[derive(Debug, PartialEq)]
+pub struct LegalMove {
+    pub bg_move: BgMove,
+    pub position: Position,
 }
 
 impl BgMove {
@@ -46,10 +62,49 @@ impl BgMove {
         self.details
     }
 
+    #[inline]
+    pub fn details(&self) -> &[MoveDetail] {
+        &self.details
+    }
+
     pub fn new(old: &Position, new: &Position, dice: &Dice) -> BgMove {
-        match dice {
+        let mut bg_move = match dice {
             Dice::Mixed(dice) => Self::new_mixed(old, new, dice),
             Dice::Double(die) => Self::new_double(old, new, *die),
+        };
+        bg_move.mark_hits(old);
+        bg_move
+    }
+
+    /// All legal moves for the given position and dice, each with the resulting position.
+    ///
+    /// The order is the same as in [Position::all_positions_after_moving].
+    /// If no checker can be moved, a single `LegalMove` with empty details is returned.
+    pub fn all_legal_moves(position: &Position, dice: &Dice) -> Vec<LegalMove> {
+        position
+            .all_positions_after_moving(dice)
+            .into_iter()
+            .map(|new| {
+                let new = new.sides_switched();
+                LegalMove {
+                    bg_move: BgMove::new(position, &new, dice),
+                    position: new,
+                }
+            })
+            .collect()
+    }
+
+    /// Sets the `hit` flag of all details by replaying them on the `old` position.
+    ///
+    /// Only the opponent's checkers matter here, so we don't need to know which die was used.
+    fn mark_hits(&mut self, old: &Position) {
+        let mut pips: [i8; 26] = (*old).into();
+        for detail in self.details.iter_mut() {
+            pips[detail.from] -= 1;
+            if detail.to > 0 {
+                detail.hit = pips[detail.to] == -1;
+                pips[detail.to] = max(0, pips[detail.to]) + 1;
+            }
         }
     }
 
@@ -73,11 +128,38 @@ impl BgMove {
     }
 }
 
+/// Moves the checkers of `x` without checking the dice. Blots of `o` are hit.
+///
+/// Use [crate::game_state::GameState::play] to check whether the resulting position is legal for the dice.
+pub fn apply_checker_moves(
+    position: &Position,
+    checker_moves: &[(usize, usize)],
+) -> Result<Position, String> {
+    let mut pips: [i8; 26] = (*position).into();
+    for &(from, to) in checker_moves {
+        if pips[from] < 1 {
+            return Err(format!("No checker to move from {}.", from));
+        }
+        pips[from] -= 1;
+        if to > 0 {
+            if pips[to] == -1 {
+                pips[to] = 0;
+                pips[O_BAR] -= 1;
+            } else if pips[to] < -1 {
+                return Err(format!("Point {} is blocked.", to));
+            }
+            pips[to] += 1;
+        }
+    }
+    Position::try_from(pips).map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::bg_move::{BgMove, MoveDetail};
-    use engine::dice::MixedDice;
+    use crate::bg_move::{apply_checker_moves, BgMove, MoveDetail};
+    use engine::dice::{Dice, MixedDice};
     use engine::pos;
+    use engine::position::{O_BAR, X_BAR};
 
     #[test]
     fn double_could_move_only_one_pieces() {
@@ -87,7 +169,14 @@ mod tests {
         // When
         let bg_move = BgMove::new_double(&old, &new, 4);
         // Then
-        assert_eq!(bg_move.details, vec![MoveDetail { from: 20, to: 16 },]);
+        assert_eq!(
+            bg_move.details,
+            vec![MoveDetail {
+                from: 20,
+                to: 16,
+                hit: false
+            },]
+        );
     }
 
     #[test]
@@ -101,8 +190,16 @@ mod tests {
         assert_eq!(
             bg_move.details,
             vec![
-                MoveDetail { from: 20, to: 15 },
-                MoveDetail { from: 20, to: 18 },
+                MoveDetail {
+                    from: 20,
+                    to: 15,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 20,
+                    to: 18,
+                    hit: false
+                },
             ]
         );
     }
@@ -117,7 +214,14 @@ mod tests {
         let bg_move = BgMove::new_mixed(&old, &new, &MixedDice::new(5, 2));
 
         // Then
-        assert_eq!(bg_move.details, vec![MoveDetail { from: 1, to: 0 },]);
+        assert_eq!(
+            bg_move.details,
+            vec![MoveDetail {
+                from: 1,
+                to: 0,
+                hit: false
+            },]
+        );
     }
 
     #[test]
@@ -132,7 +236,109 @@ mod tests {
         // Then
         assert_eq!(
             bg_move.details,
-            vec![MoveDetail { from: 4, to: 2 }, MoveDetail { from: 2, to: 0 },]
+            vec![
+                MoveDetail {
+                    from: 4,
+                    to: 2,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 2,
+                    to: 0,
+                    hit: false
+                },
+            ]
         );
     }
+
+    #[test]
+    fn new_marks_hit_on_second_detail_only() {
+        // Given
+        let old = pos!(x 18:1, 14:1; o 12:1, 2:2);
+        let new = pos!(x 12:2; o O_BAR:1, 2:2);
+        // When
+        let bg_move = BgMove::new(&old, &new, &Dice::new(6, 2));
+        // Then
+        let hits: Vec<bool> = bg_move.details().iter().map(|d| d.hit()).collect();
+        assert_eq!(bg_move.details().len(), 2);
+        assert_eq!(hits.iter().filter(|&&hit| hit).count(), 1);
+        assert!(bg_move.details().iter().all(|d| d.to() == 12));
+    }
+
+    #[test]
+    fn new_marks_hit_with_double() {
+        // Given
+        let old = pos!(x 9:1; o 7:1, 5:1, 2:2);
+        let new = pos!(x 1:1; o O_BAR:2, 2:2);
+        // When
+        let bg_move = BgMove::new(&old, &new, &Dice::new(2, 2));
+        // Then
+        let hits: Vec<bool> = bg_move.details().iter().map(|d| d.hit()).collect();
+        assert_eq!(hits, vec![true, true, false, false]);
+    }
+
+    #[test]
+    fn all_legal_moves_hit_flags_match_checkers_sent_to_the_bar() {
+        // Given
+        let position = pos!(x 13:2, 8:1; o 10:1, 5:1, 3:2);
+        let dice = Dice::new(5, 3);
+        // When
+        let legal_moves = BgMove::all_legal_moves(&position, &dice);
+        // Then
+        assert_eq!(
+            legal_moves.len(),
+            position.all_positions_after_moving(&dice).len()
+        );
+        for legal_move in legal_moves.iter() {
+            let checker_moves: Vec<(usize, usize)> = legal_move
+                .bg_move
+                .details()
+                .iter()
+                .map(|d| (d.from(), d.to()))
+                .collect();
+            let replayed = apply_checker_moves(&position, &checker_moves).unwrap();
+            assert_eq!(replayed, legal_move.position);
+
+            let hits = legal_move
+                .bg_move
+                .details()
+                .iter()
+                .filter(|d| d.hit())
+                .count();
+            let hit_checkers = (position.pip(O_BAR) - legal_move.position.pip(O_BAR)) as usize;
+            assert_eq!(hits, hit_checkers);
+        }
+        // 13/10*/5* hits both blots with the same checker.
+        let double_hit = legal_moves
+            .iter()
+            .find(|m| m.position.pip(O_BAR) == -2)
+            .unwrap();
+        assert_eq!(
+            double_hit.bg_move.details,
+            vec![
+                MoveDetail {
+                    from: 13,
+                    to: 10,
+                    hit: true
+                },
+                MoveDetail {
+                    from: 10,
+                    to: 5,
+                    hit: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn all_legal_moves_no_move_possible() {
+        // Given
+        let position = pos!(x X_BAR:1; o 24:2, 23:2, 22:2, 21:2, 20:2, 19:2);
+        // When
+        let legal_moves = BgMove::all_legal_moves(&position, &Dice::new(4, 2));
+        // Then
+        assert_eq!(legal_moves.len(), 1);
+        assert!(legal_moves[0].bg_move.details().is_empty());
+        assert_eq!(legal_moves[0].position, position);
+    }
 }
@@ -11,7 +11,11 @@ impl BgMove {
                 // We could speed this up by replacing the next line with the private method `move_single_checker`
                 position = position.try_move_single_checker(index, die).unwrap();
                 let to = index.saturating_sub(die);
-                details.push(MoveDetail { from: index, to })
+                details.push(MoveDetail {
+                    from: index,
+                    to,
+                    hit: false,
+                })
             } else {
                 index -= 1
             }
@@ -47,8 +51,16 @@ mod tests {
         assert_eq!(
             bg_move.details,
             vec![
-                MoveDetail { from: 20, to: 16 },
-                MoveDetail { from: 20, to: 16 },
+                MoveDetail {
+                    from: 20,
+                    to: 16,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 20,
+                    to: 16,
+                    hit: false
+                },
             ]
         );
     }
@@ -64,10 +76,26 @@ mod tests {
         assert_eq!(
             bg_move.details,
             vec![
-                MoveDetail { from: 5, to: 1 },
-                MoveDetail { from: 4, to: 0 },
-                MoveDetail { from: 3, to: 0 },
-                MoveDetail { from: 3, to: 0 },
+                MoveDetail {
+                    from: 5,
+                    to: 1,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 4,
+                    to: 0,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 3,
+                    to: 0,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 3,
+                    to: 0,
+                    hit: false
+                },
             ]
         );
     }
@@ -22,10 +22,12 @@ impl BgMove {
                                     MoveDetail {
                                         from: from_pip,
                                         to: from_pip - die1,
+                                        hit: false,
                                     },
                                     MoveDetail {
                                         from: from_pip - die1,
                                         to,
+                                        hit: false,
                                     },
                                 ],
                             };
@@ -38,7 +40,11 @@ impl BgMove {
                         if position == *new {
                             let to = from_pip.saturating_sub(die);
                             return BgMove {
-                                details: vec![MoveDetail { from: from_pip, to }],
+                                details: vec![MoveDetail {
+                                    from: from_pip,
+                                    to,
+                                    hit: false,
+                                }],
                             };
                         }
                     }
@@ -66,10 +72,12 @@ impl BgMove {
                     MoveDetail {
                         from: from1,
                         to: to1,
+                        hit: false,
                     },
                     MoveDetail {
                         from: from2,
                         to: to2,
+                        hit: false,
                     },
                 ]
             }
@@ -127,7 +135,8 @@ mod tests {
             bg_move.details,
             vec![MoveDetail {
                 from: X_BAR,
-                to: 19
+                to: 19,
+                hit: false
             },]
         );
     }
@@ -143,8 +152,16 @@ mod tests {
         assert_eq!(
             bg_move.details,
             vec![
-                MoveDetail { from: 20, to: 14 },
-                MoveDetail { from: 14, to: 10 }
+                MoveDetail {
+                    from: 20,
+                    to: 14,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 14,
+                    to: 10,
+                    hit: false
+                }
             ]
         );
     }
@@ -160,8 +177,16 @@ mod tests {
         assert_eq!(
             bg_move.details,
             vec![
-                MoveDetail { from: 20, to: 16 },
-                MoveDetail { from: 16, to: 10 }
+                MoveDetail {
+                    from: 20,
+                    to: 16,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 16,
+                    to: 10,
+                    hit: false
+                }
             ]
         );
     }
@@ -176,7 +201,18 @@ mod tests {
         // then
         assert_eq!(
             bg_move.details,
-            vec![MoveDetail { from: 8, to: 4 }, MoveDetail { from: 4, to: 0 }]
+            vec![
+                MoveDetail {
+                    from: 8,
+                    to: 4,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 4,
+                    to: 0,
+                    hit: false
+                }
+            ]
         );
     }
 
@@ -190,7 +226,18 @@ mod tests {
         // then
         assert_eq!(
             bg_move.details,
-            vec![MoveDetail { from: 4, to: 0 }, MoveDetail { from: 3, to: 0 }]
+            vec![
+                MoveDetail {
+                    from: 4,
+                    to: 0,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 3,
+                    to: 0,
+                    hit: false
+                }
+            ]
         );
     }
 
@@ -204,7 +251,18 @@ mod tests {
         // then
         assert_eq!(
             bg_move.details,
-            vec![MoveDetail { from: 4, to: 2 }, MoveDetail { from: 2, to: 0 }]
+            vec![
+                MoveDetail {
+                    from: 4,
+                    to: 2,
+                    hit: false
+                },
+                MoveDetail {
+                    from: 2,
+                    to: 0,
+                    hit: false
+                }
+            ]
         );
     }
 }
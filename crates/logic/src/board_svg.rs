@@ -0,0 +1,344 @@
+use engine::dice::Dice;
+use engine::position::{Position, O_BAR, X_BAR};
+use std::fmt::Write;
+
+/// Where the cube is drawn. `X` is the player on roll, like in [Position].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CubeSide {
+    Centered,
+    X,
+    O,
+}
+
+/// SVG image of a position with optional dice and cube, for example to share analyzed positions.
+///
+/// The layout is the same as in [Position::ascii_board]: `x` plays the light checkers from the top
+/// right to the bottom right. Both pip counts are written below the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoardSvg {
+    position: Position,
+    dice: Option<Dice>,
+    cube: Option<(u32, CubeSide)>,
+}
+
+const POINT_WIDTH: u32 = 40;
+const CHECKER_RADIUS: u32 = 18;
+const FRAME: u32 = 20;
+const BAR_WIDTH: u32 = 40;
+const TRAY_WIDTH: u32 = 60;
+const HALF_HEIGHT: u32 = 200;
+const MIDDLE_HEIGHT: u32 = 40;
+const TEXT_HEIGHT: u32 = 30;
+/// Number of checkers drawn on a point; if there are more, the top checker shows their number.
+const MAX_DRAWN: u32 = 5;
+
+const BOARD_WIDTH: u32 = 2 * FRAME + 12 * POINT_WIDTH + BAR_WIDTH;
+const WIDTH: u32 = BOARD_WIDTH + TRAY_WIDTH;
+const BOARD_HEIGHT: u32 = 2 * FRAME + 2 * HALF_HEIGHT + MIDDLE_HEIGHT;
+const HEIGHT: u32 = BOARD_HEIGHT + TEXT_HEIGHT;
+
+const X_COLOUR: &str = "#f2ead3";
+const O_COLOUR: &str = "#3b2f2f";
+
+impl BoardSvg {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            dice: None,
+            cube: None,
+        }
+    }
+
+    /// The dice are drawn on the right side of the board, as they were rolled by `x`.
+    pub fn with_dice(self, dice: Dice) -> Self {
+        Self {
+            dice: Some(dice),
+            ..self
+        }
+    }
+
+    /// A centered cube with value 1 is drawn as 64, like on a real board.
+    pub fn with_cube(self, value: u32, side: CubeSide) -> Self {
+        Self {
+            cube: Some((value, side)),
+            ..self
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif">"#
+        );
+        svg.push_str(&format!(
+            r##"<rect width="{WIDTH}" height="{HEIGHT}" fill="#ffffff"/><rect width="{BOARD_WIDTH}" height="{BOARD_HEIGHT}" fill="#6b4226"/>"##
+        ));
+        for (left, width) in [
+            (FRAME, 6 * POINT_WIDTH),
+            (FRAME + 6 * POINT_WIDTH + BAR_WIDTH, 6 * POINT_WIDTH),
+        ] {
+            svg.push_str(&format!(
+                r##"<rect x="{left}" y="{FRAME}" width="{width}" height="{}" fill="#d9b38c"/>"##,
+                2 * HALF_HEIGHT + MIDDLE_HEIGHT
+            ));
+        }
+        for pip in 1..=24 {
+            self.draw_point(&mut svg, pip);
+        }
+        self.draw_bar(&mut svg);
+        self.draw_off(&mut svg);
+        if let Some((value, side)) = self.cube {
+            draw_cube(&mut svg, value, side);
+        }
+        if let Some(dice) = self.dice {
+            draw_dice(&mut svg, dice);
+        }
+        let (x_pips, o_pips) = pip_counts(&self.position);
+        let y = BOARD_HEIGHT + 20;
+        write!(
+            svg,
+            r#"<text x="{FRAME}" y="{y}" font-size="16">X: {x_pips} pips</text><text x="{}" y="{y}" font-size="16" text-anchor="end">O: {o_pips} pips</text>"#,
+            BOARD_WIDTH - FRAME
+        )
+        .unwrap();
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Triangle and checkers of a point between 1 and 24.
+    fn draw_point(&self, svg: &mut String, pip: usize) {
+        let (left, is_top) = point_location(pip);
+        let base = if is_top {
+            FRAME
+        } else {
+            FRAME + 2 * HALF_HEIGHT + MIDDLE_HEIGHT
+        };
+        let tip = if is_top {
+            base + HALF_HEIGHT - 20
+        } else {
+            base - HALF_HEIGHT + 20
+        };
+        let colour = if pip.is_multiple_of(2) {
+            "#8b1a1a"
+        } else {
+            "#f5deb3"
+        };
+        write!(
+            svg,
+            r#"<polygon points="{left},{base} {},{base} {},{tip}" fill="{colour}"/>"#,
+            left + POINT_WIDTH,
+            left + POINT_WIDTH / 2,
+        )
+        .unwrap();
+        let centre = left + POINT_WIDTH / 2;
+        let first = if is_top {
+            FRAME + CHECKER_RADIUS
+        } else {
+            FRAME + 2 * HALF_HEIGHT + MIDDLE_HEIGHT - CHECKER_RADIUS
+        };
+        let step = |index: u32| {
+            if is_top {
+                first + index * 2 * CHECKER_RADIUS
+            } else {
+                first - index * 2 * CHECKER_RADIUS
+            }
+        };
+        draw_stack(svg, self.position.pip(pip), centre, step);
+    }
+
+    /// Checkers of `o` on the bar are in the upper half, checkers of `x` in the lower half.
+    fn draw_bar(&self, svg: &mut String) {
+        let centre = FRAME + 6 * POINT_WIDTH + BAR_WIDTH / 2;
+        let middle = FRAME + HALF_HEIGHT + MIDDLE_HEIGHT / 2;
+        let o_first = middle - MIDDLE_HEIGHT / 2 - CHECKER_RADIUS;
+        draw_stack(svg, self.position.pip(O_BAR), centre, |index| {
+            o_first - index * 2 * CHECKER_RADIUS
+        });
+        let x_first = middle + MIDDLE_HEIGHT / 2 + CHECKER_RADIUS;
+        draw_stack(svg, self.position.pip(X_BAR), centre, |index| {
+            x_first + index * 2 * CHECKER_RADIUS
+        });
+    }
+
+    /// Number of checkers born off, `o` at the top of the tray and `x` at the bottom.
+    fn draw_off(&self, svg: &mut String) {
+        let (x_on_board, o_on_board) = checkers_on_board(&self.position);
+        let centre = BOARD_WIDTH + TRAY_WIDTH / 2;
+        for (off, y) in [
+            (15 - o_on_board, FRAME + 16),
+            (15 - x_on_board, BOARD_HEIGHT - FRAME - 4),
+        ] {
+            write!(
+                svg,
+                r#"<text x="{centre}" y="{y}" font-size="14" text-anchor="middle">off {off}</text>"#
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// Left edge and half of the board for a point between 1 and 24.
+fn point_location(pip: usize) -> (u32, bool) {
+    let (column, is_top) = if pip <= 12 {
+        (12 - pip as u32, false)
+    } else {
+        (pip as u32 - 13, true)
+    };
+    let bar = if column >= 6 { BAR_WIDTH } else { 0 };
+    (FRAME + column * POINT_WIDTH + bar, is_top)
+}
+
+/// Draws up to [MAX_DRAWN] checkers; `checkers` is positive for `x` and negative for `o`.
+fn draw_stack(svg: &mut String, checkers: i8, centre: u32, y: impl Fn(u32) -> u32) {
+    let count = checkers.unsigned_abs() as u32;
+    let (fill, text) = if checkers > 0 {
+        (X_COLOUR, O_COLOUR)
+    } else {
+        (O_COLOUR, X_COLOUR)
+    };
+    for index in 0..count.min(MAX_DRAWN) {
+        write!(
+            svg,
+            r##"<circle cx="{centre}" cy="{}" r="{}" fill="{fill}" stroke="#000000"/>"##,
+            y(index),
+            CHECKER_RADIUS - 1
+        )
+        .unwrap();
+    }
+    if count > MAX_DRAWN {
+        write!(
+            svg,
+            r#"<text x="{centre}" y="{}" font-size="16" text-anchor="middle" fill="{text}">{count}</text>"#,
+            y(MAX_DRAWN - 1) + 6
+        )
+        .unwrap();
+    }
+}
+
+fn draw_cube(svg: &mut String, value: u32, side: CubeSide) {
+    let size = 36;
+    let left = BOARD_WIDTH + (TRAY_WIDTH - size) / 2;
+    let top = match side {
+        CubeSide::O => FRAME + 30,
+        CubeSide::Centered => BOARD_HEIGHT / 2 - size / 2,
+        CubeSide::X => BOARD_HEIGHT - FRAME - 30 - size,
+    };
+    let shown = if value == 1 && side == CubeSide::Centered {
+        64
+    } else {
+        value
+    };
+    write!(
+        svg,
+        r##"<rect x="{left}" y="{top}" width="{size}" height="{size}" rx="4" fill="#ffffff" stroke="#000000"/><text x="{}" y="{}" font-size="18" text-anchor="middle">{shown}</text>"##,
+        left + size / 2,
+        top + size / 2 + 6
+    )
+    .unwrap();
+}
+
+/// Both dice in the middle of the right half of the board.
+fn draw_dice(svg: &mut String, dice: Dice) {
+    let (die1, die2) = match dice {
+        Dice::Double(die) => (die, die),
+        Dice::Mixed(mixed) => (mixed.big(), mixed.small()),
+    };
+    let size = 32;
+    let centre = FRAME + 9 * POINT_WIDTH + BAR_WIDTH;
+    let top = FRAME + HALF_HEIGHT + (MIDDLE_HEIGHT - size) / 2;
+    for (die, left) in [(die1, centre - size - 4), (die2, centre + 4)] {
+        write!(
+            svg,
+            r##"<rect x="{left}" y="{top}" width="{size}" height="{size}" rx="5" fill="{X_COLOUR}" stroke="#000000"/>"##
+        )
+        .unwrap();
+        for (dx, dy) in die_spots(die) {
+            write!(
+                svg,
+                r##"<circle cx="{}" cy="{}" r="3" fill="#000000"/>"##,
+                left + dx * size / 4,
+                top + dy * size / 4
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// Spots of a die face on a 4x4 grid, where (2, 2) is the centre.
+fn die_spots(die: usize) -> Vec<(u32, u32)> {
+    let spots: &[(u32, u32)] = match die {
+        1 => &[(2, 2)],
+        2 => &[(1, 1), (3, 3)],
+        3 => &[(1, 1), (2, 2), (3, 3)],
+        4 => &[(1, 1), (3, 1), (1, 3), (3, 3)],
+        5 => &[(1, 1), (3, 1), (2, 2), (1, 3), (3, 3)],
+        _ => &[(1, 1), (3, 1), (1, 2), (3, 2), (1, 3), (3, 3)],
+    };
+    spots.to_vec()
+}
+
+fn checkers_on_board(position: &Position) -> (u32, u32) {
+    let x = (O_BAR..=X_BAR)
+        .map(|pip| position.pip(pip).max(0) as u32)
+        .sum();
+    let o = (O_BAR..=X_BAR)
+        .map(|pip| -position.pip(pip).min(0) as u32)
+        .sum();
+    (x, o)
+}
+
+/// Pip counts of `x` and `o`; checkers on the bar count 25.
+fn pip_counts(position: &Position) -> (u32, u32) {
+    let x = (O_BAR..=X_BAR)
+        .map(|pip| position.pip(pip).max(0) as u32 * pip as u32)
+        .sum();
+    let o = (O_BAR..=X_BAR)
+        .map(|pip| -position.pip(pip).min(0) as u32 * (25 - pip) as u32)
+        .sum();
+    (x, o)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board_svg::{pip_counts, BoardSvg, CubeSide};
+    use engine::dice::Dice;
+    use engine::pos;
+    use engine::position::{O_BAR, STARTING, X_BAR};
+
+    #[test]
+    fn pip_counts_with_checkers_on_bar() {
+        assert_eq!(pip_counts(&STARTING), (167, 167));
+        let position = pos!(x X_BAR:1, 3:2; o 22:1, O_BAR:1);
+        assert_eq!(pip_counts(&position), (31, 28));
+    }
+
+    #[test]
+    fn starting_position_with_dice_and_cube() {
+        // When
+        let svg = BoardSvg::new(STARTING)
+            .with_dice(Dice::new(3, 1))
+            .with_cube(1, CubeSide::Centered)
+            .render();
+        // Then
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="620""#));
+        assert!(svg.ends_with("</svg>"));
+        // 24 points, 30 checkers and 4 spots on the dice
+        assert_eq!(svg.matches("<polygon").count(), 24);
+        assert_eq!(svg.matches("<circle").count(), 30 + 4);
+        assert!(svg.contains(">64</text>"));
+        assert!(svg.contains(">X: 167 pips</text>"));
+        assert!(svg.contains(">O: 167 pips</text>"));
+    }
+
+    #[test]
+    fn high_stacks_show_their_number() {
+        // When
+        let svg = BoardSvg::new(pos!(x 1:8; o 24:1)).render();
+        // Then
+        assert_eq!(svg.matches("<circle").count(), 5 + 1);
+        assert!(svg.contains(">8</text>"));
+        assert!(svg.contains(">off 7</text>"));
+        assert!(svg.contains(">off 14</text>"));
+        // No cube
+        assert!(!svg.contains(r#"rx="4""#));
+    }
+}
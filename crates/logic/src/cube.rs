@@ -1,9 +1,11 @@
 use engine::probabilities::Probabilities;
+#[cfg(feature = "web")]
 use serde::Serialize;
+#[cfg(feature = "web")]
 use utoipa::ToSchema;
 
-#[derive(Serialize, ToSchema)]
-/// Information about proper cube decisions. Currently quick and dirty calculations.
+#[cfg_attr(feature = "web", derive(Serialize, ToSchema))]
+/// Information about proper cube decisions for money play, see [Janowski].
 pub struct CubeInfo {
     /// `true` if the player `x` should double, `false` if no double yet or too good.
     double: bool,
@@ -12,13 +14,399 @@ pub struct CubeInfo {
 }
 
 impl From<&Probabilities> for CubeInfo {
+    /// Decisions with a centered cube and the default cube efficiency.
     fn from(value: &Probabilities) -> Self {
-        // This is just a very simple calculation so that we can implement the cube API.
-        // Later we want better cube decisions, helpful could be the article:
-        // https://bkgm.com/articles/Janowski/cubeformulae.pdf
-        let equity = value.equity();
-        let double = equity > 0.4 && equity < 0.6;
-        let accept = equity < 0.5;
-        Self { double, accept }
+        let janowski = Janowski::default();
+        Self {
+            double: janowski.should_double(value, CubeAccess::Centered),
+            accept: janowski.should_take(value),
+        }
+    }
+}
+
+impl CubeInfo {
+    #[inline]
+    pub fn double(&self) -> bool {
+        self.double
+    }
+
+    #[inline]
+    pub fn accept(&self) -> bool {
+        self.accept
+    }
+}
+
+/// Cube efficiency for money play, as usually suggested for [Janowski]'s model.
+pub const DEFAULT_CUBE_EFFICIENCY: f32 = 0.68;
+
+/// Who may double next, from the point of view of player `x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeAccess {
+    Centered,
+    /// `x` owns the cube.
+    Owned,
+    /// `o` owns the cube.
+    Opponent,
+}
+
+/// Cubeful money equities derived from cubeless probabilities with Janowski's formulas.
+///
+/// The cubeful equity is interpolated between two extremes: a dead cube, for which it's the
+/// cubeless equity, and a perfectly live cube, for which the winning chances change continuously
+/// and a double can always be given exactly at the opponent's take point. `cube_efficiency` is the
+/// weight of the live cube. See <https://bkgm.com/articles/Janowski/cubeformulae.pdf>.
+///
+/// All equities are from the point of view of `x` and normalized to a cube value of 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Janowski {
+    /// Between 0.0 for a dead cube and 1.0 for a perfectly live cube.
+    pub cube_efficiency: f32,
+}
+
+impl Default for Janowski {
+    fn default() -> Self {
+        Self::new(DEFAULT_CUBE_EFFICIENCY)
+    }
+}
+
+impl Janowski {
+    pub fn new(cube_efficiency: f32) -> Self {
+        Self { cube_efficiency }
+    }
+
+    /// Cubeful equity of `x` with the given cube access.
+    pub fn equity(&self, probabilities: &Probabilities, access: CubeAccess) -> f32 {
+        let dead = probabilities.equity();
+        let live = live_equity(probabilities, access);
+        self.cube_efficiency * live + (1.0 - self.cube_efficiency) * dead
+    }
+
+    /// Minimum winning chances `x` needs to take a double.
+    pub fn take_point(&self, probabilities: &Probabilities) -> f32 {
+        let (win_value, lose_value) = values(probabilities);
+        (lose_value - 0.5) / (win_value + lose_value + 0.5 * self.cube_efficiency)
+    }
+
+    /// Winning chances of `x` from which the opponent has to pass a double.
+    pub fn cash_point(&self, probabilities: &Probabilities) -> f32 {
+        let (win_value, lose_value) = values(probabilities);
+        let x = self.cube_efficiency;
+        (lose_value + 0.5 + 0.5 * x) / (win_value + lose_value + 0.5 * x)
+    }
+
+    /// Winning chances of `x` from which doubling is better than not doubling.
+    ///
+    /// `access` is the cube access of `x` before doubling, so either centered or owned. With an
+    /// owned cube `x` loses more by doubling and has to wait longer.
+    pub fn doubling_point(&self, probabilities: &Probabilities, access: CubeAccess) -> f32 {
+        let (win_value, lose_value) = values(probabilities);
+        let x = self.cube_efficiency;
+        let denominator = win_value + lose_value + 0.5 * x;
+        if access == CubeAccess::Owned {
+            return (lose_value + x) / denominator;
+        }
+        // No double and double/take are both linear in the winning chances, see [live_equity].
+        let slope = win_value + lose_value + 0.5;
+        let take_point = (lose_value - 0.5) / slope;
+        let window = (lose_value + 1.0) / slope - take_point;
+        let no_double_slope = 2.0 * x / window + (1.0 - x) * (win_value + lose_value);
+        let no_double_offset = -x * (1.0 + 2.0 * take_point / window) - (1.0 - x) * lose_value;
+        let double_take_slope = 2.0 * denominator;
+        let double_take_offset = -2.0 * (lose_value + 0.5 * x);
+        (no_double_offset - double_take_offset) / (double_take_slope - no_double_slope)
+    }
+
+    /// Winning chances of `x` from which playing on for a gammon is better than cashing.
+    ///
+    /// `1.0` or more if `x` can't be too good, for example without any gammons.
+    pub fn too_good_point(&self, probabilities: &Probabilities) -> f32 {
+        let (win_value, lose_value) = values(probabilities);
+        (lose_value + 1.0) / (win_value + lose_value)
+    }
+
+    /// Doubling window of `x` for the gammon rates of `probabilities`.
+    pub fn window(&self, probabilities: &Probabilities, access: CubeAccess) -> DoublingWindow {
+        DoublingWindow {
+            doubling_point: self.doubling_point(probabilities, access),
+            cash_point: self.cash_point(probabilities),
+            too_good_point: self.too_good_point(probabilities),
+            win: probabilities.win(),
+        }
+    }
+
+    /// Equities of `x` for no double, double/take and double/pass, in this order.
+    ///
+    /// `access` is the cube access of `x` before doubling, so either centered or owned.
+    pub fn cube_equities(&self, probabilities: &Probabilities, access: CubeAccess) -> [f32; 3] {
+        [
+            self.equity(probabilities, access),
+            2.0 * self.equity(probabilities, CubeAccess::Opponent),
+            1.0,
+        ]
+    }
+
+    /// `true` if `x` should double. `false` if it's too early or `x` is too good to double.
+    pub fn should_double(&self, probabilities: &Probabilities, access: CubeAccess) -> bool {
+        let [no_double, double_take, double_pass] = self.cube_equities(probabilities, access);
+        access != CubeAccess::Opponent && double_take.min(double_pass) > no_double
+    }
+
+    /// `true` if `o` should take a double of `x`.
+    pub fn should_take(&self, probabilities: &Probabilities) -> bool {
+        let [_, double_take, double_pass] = self.cube_equities(probabilities, CubeAccess::Centered);
+        double_take <= double_pass
+    }
+}
+
+/// Where the winning chances of `x` are in the [DoublingWindow].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "web", derive(Serialize, ToSchema))]
+pub enum WindowPosition {
+    /// Below the doubling point.
+    NoDouble,
+    /// Between the doubling point and the cash point.
+    DoubleTake,
+    /// Between the cash point and the too good point.
+    DoublePass,
+    /// Above the too good point, `x` should play on for a gammon.
+    TooGood,
+}
+
+/// Thresholds of the winning chances of `x` for cube decisions.
+///
+/// All values are winning chances of `x`, including gammons. They depend on the gammon rates of the
+/// position, so a window is only valid for positions with similar gammon rates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "web", derive(Serialize, ToSchema))]
+#[cfg_attr(feature = "web", serde(rename_all = "camelCase"))]
+pub struct DoublingWindow {
+    /// From here on doubling is correct.
+    pub doubling_point: f32,
+    /// From here on the opponent has to pass, this is the take point of the opponent.
+    pub cash_point: f32,
+    /// From here on playing on is better than cashing. `1.0` or more if `x` can't be too good.
+    pub too_good_point: f32,
+    /// Current winning chances of `x`.
+    pub win: f32,
+}
+
+impl DoublingWindow {
+    /// Where the current winning chances are.
+    pub fn position(&self) -> WindowPosition {
+        if self.win >= self.too_good_point {
+            WindowPosition::TooGood
+        } else if self.win >= self.cash_point {
+            WindowPosition::DoublePass
+        } else if self.win >= self.doubling_point {
+            WindowPosition::DoubleTake
+        } else {
+            WindowPosition::NoDouble
+        }
+    }
+
+    /// How much the winning chances of `x` may still change before reaching the next threshold.
+    /// Zero if `x` is too good.
+    pub fn distance_to_next(&self) -> f32 {
+        let next = match self.position() {
+            WindowPosition::NoDouble => self.doubling_point,
+            WindowPosition::DoubleTake => self.cash_point,
+            WindowPosition::DoublePass => self.too_good_point,
+            WindowPosition::TooGood => self.win,
+        };
+        next - self.win
+    }
+}
+
+/// Take point adjusted for the recube vig, the value of owning the cube after taking.
+///
+/// `dead_take_point` is the take point if the cube were dead. `recube_take_point` is the take point
+/// of the doubler when the taker redoubles later, with the roles switched. With a perfectly live
+/// cube the taker redoubles exactly at that point, so taking is worth as much as reaching
+/// `1.0 - recube_take_point` and cashing. `cube_efficiency` interpolates between this live take
+/// point and the dead one.
+///
+/// For money games the live take point is a fixed point, as both sides have the same take points:
+/// `t = recube_take_point(0.25, t, x)`, which is the [Janowski::take_point] without gammons.
+pub fn recube_take_point(
+    dead_take_point: f32,
+    recube_take_point: f32,
+    cube_efficiency: f32,
+) -> f32 {
+    let live = dead_take_point * (1.0 - recube_take_point);
+    cube_efficiency * live + (1.0 - cube_efficiency) * dead_take_point
+}
+
+/// Average value of a win and of a loss of `x`, at least 1.0.
+fn values(probabilities: &Probabilities) -> (f32, f32) {
+    let average = |normal: f32, gammon: f32| {
+        let all = normal + gammon;
+        if all > 0.0 {
+            (normal + 2.0 * gammon) / all
+        } else {
+            1.0
+        }
+    };
+    (
+        average(probabilities.win_normal, probabilities.win_gammon),
+        average(probabilities.lose_normal, probabilities.lose_gammon),
+    )
+}
+
+/// Equity with a perfectly live cube, linear in the winning chances between take and cash points.
+///
+/// Beyond these points the game is cashed, unless playing on for a gammon is better.
+fn live_equity(probabilities: &Probabilities, access: CubeAccess) -> f32 {
+    let (win_value, lose_value) = values(probabilities);
+    let dead = probabilities.equity();
+    let win = probabilities.win();
+    let slope = win_value + lose_value + 0.5;
+    let take_point = (lose_value - 0.5) / slope;
+    let cash_point = (lose_value + 1.0) / slope;
+    if win >= cash_point && access != CubeAccess::Opponent {
+        return dead.max(1.0);
+    }
+    if win <= take_point && access != CubeAccess::Owned {
+        return dead.min(-1.0);
+    }
+    match access {
+        CubeAccess::Owned => win * slope - lose_value,
+        CubeAccess::Opponent => win * slope - lose_value - 0.5,
+        CubeAccess::Centered => -1.0 + 2.0 * (win - take_point) / (cash_point - take_point),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::{recube_take_point, CubeAccess, Janowski, WindowPosition};
+    use engine::probabilities::Probabilities;
+
+    fn no_gammons(win: f32) -> Probabilities {
+        Probabilities {
+            win_normal: win,
+            win_gammon: 0.0,
+            lose_normal: 1.0 - win,
+            lose_gammon: 0.0,
+        }
+    }
+
+    #[test]
+    fn take_and_cash_points_without_gammons() {
+        let even = no_gammons(0.5);
+        let live = Janowski::new(1.0);
+        assert_eq!(live.take_point(&even), 0.2);
+        assert_eq!(live.cash_point(&even), 0.8);
+        let dead = Janowski::new(0.0);
+        assert_eq!(dead.take_point(&even), 0.25);
+        assert_eq!(dead.cash_point(&even), 0.75);
+    }
+
+    #[test]
+    fn equities_with_live_cube() {
+        // Given
+        let janowski = Janowski::new(1.0);
+        // Then
+        assert_eq!(janowski.equity(&no_gammons(0.5), CubeAccess::Centered), 0.0);
+        // Owning the cube is worth something.
+        assert!((janowski.equity(&no_gammons(0.5), CubeAccess::Owned) - 0.25).abs() < 1e-6);
+        assert!((janowski.equity(&no_gammons(0.5), CubeAccess::Opponent) + 0.25).abs() < 1e-6);
+        // Beyond the cash point the game is cashed.
+        assert_eq!(janowski.equity(&no_gammons(0.9), CubeAccess::Centered), 1.0);
+        assert_eq!(
+            janowski.equity(&no_gammons(0.1), CubeAccess::Centered),
+            -1.0
+        );
+    }
+
+    #[test]
+    fn cube_decisions() {
+        let janowski = Janowski::default();
+        // Too early
+        assert!(!janowski.should_double(&no_gammons(0.6), CubeAccess::Centered));
+        // Double, take
+        assert!(janowski.should_double(&no_gammons(0.7), CubeAccess::Centered));
+        assert!(janowski.should_take(&no_gammons(0.7)));
+        // Double, pass
+        assert!(janowski.should_double(&no_gammons(0.8), CubeAccess::Owned));
+        assert!(!janowski.should_take(&no_gammons(0.8)));
+        // Nobody can double a cube owned by the opponent.
+        assert!(!janowski.should_double(&no_gammons(0.8), CubeAccess::Opponent));
+    }
+
+    #[test]
+    fn recube_vig_matches_janowski() {
+        for cube_efficiency in [0.0, 0.68, 1.0] {
+            // Given
+            let janowski = Janowski::new(cube_efficiency);
+            // When
+            let mut take_point = 0.25;
+            for _ in 0..50 {
+                take_point = recube_take_point(0.25, take_point, cube_efficiency);
+            }
+            // Then
+            let expected = janowski.take_point(&no_gammons(0.5));
+            assert!((take_point - expected).abs() < 1e-6, "{cube_efficiency}");
+        }
+        // A redouble which the doubler always takes gains nothing.
+        assert_eq!(recube_take_point(0.3, 0.0, 1.0), 0.3);
+    }
+
+    #[test]
+    fn doubling_points() {
+        let even = no_gammons(0.5);
+        // A perfectly live cube is doubled at the last moment, a dead cube as soon as `x` is the
+        // favorite.
+        assert!(
+            (Janowski::new(1.0).doubling_point(&even, CubeAccess::Centered) - 0.8).abs() < 1e-6
+        );
+        assert!(
+            (Janowski::new(0.0).doubling_point(&even, CubeAccess::Centered) - 0.5).abs() < 1e-6
+        );
+        assert!((Janowski::new(1.0).doubling_point(&even, CubeAccess::Owned) - 0.8).abs() < 1e-6);
+        assert!((Janowski::new(0.0).doubling_point(&even, CubeAccess::Owned) - 0.5).abs() < 1e-6);
+        // Giving away cube ownership costs more than turning a centered cube.
+        let janowski = Janowski::default();
+        let centered = janowski.doubling_point(&even, CubeAccess::Centered);
+        assert!(centered > 0.6 && centered < 0.7);
+        assert!(janowski.doubling_point(&even, CubeAccess::Owned) > centered);
+    }
+
+    #[test]
+    fn window_agrees_with_decisions() {
+        let janowski = Janowski::default();
+        for win in [0.55, 0.65, 0.68, 0.7, 0.75, 0.78, 0.8, 0.9] {
+            let probabilities = no_gammons(win);
+            let window = janowski.window(&probabilities, CubeAccess::Centered);
+            let double = janowski.should_double(&probabilities, CubeAccess::Centered);
+            let take = janowski.should_take(&probabilities);
+            let expected = match (double, take) {
+                (false, _) => WindowPosition::NoDouble,
+                (true, true) => WindowPosition::DoubleTake,
+                (true, false) => WindowPosition::DoublePass,
+            };
+            assert_eq!(window.position(), expected, "{win}");
+            assert!(window.distance_to_next() >= 0.0);
+        }
+    }
+
+    #[test]
+    fn too_good_to_double() {
+        // Given
+        let probabilities = Probabilities {
+            win_normal: 0.2,
+            win_gammon: 0.75,
+            lose_normal: 0.05,
+            lose_gammon: 0.0,
+        };
+        let janowski = Janowski::default();
+        // When
+        let [no_double, _, double_pass] =
+            janowski.cube_equities(&probabilities, CubeAccess::Centered);
+        // Then
+        assert!(no_double > double_pass);
+        assert!(!janowski.should_double(&probabilities, CubeAccess::Centered));
+        assert!(!janowski.should_take(&probabilities));
+        let window = janowski.window(&probabilities, CubeAccess::Centered);
+        assert_eq!(window.position(), WindowPosition::TooGood);
+        assert_eq!(window.distance_to_next(), 0.0);
     }
 }
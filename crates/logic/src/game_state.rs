@@ -0,0 +1,599 @@
+use crate::money_rules::MoneyRules;
+use engine::dice::Dice;
+use engine::position::GameResult::{WinGammon, WinNormal};
+use engine::position::{GameState as PositionState, Position, STARTING};
+
+mod fibs;
+#[cfg(feature = "serde")]
+mod serialization;
+
+type Error = &'static str;
+
+/// The two players of a game or match.
+///
+/// In contrast to `x` and `o` in [Position], which are always the player on roll and the opponent,
+/// `Player` doesn't change during a game.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn opponent(&self) -> Self {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    /// `0` for `One` and `1` for `Two`. Useful for arrays like the score.
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CubeOwner {
+    Centered,
+    Owned(Player),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serialization::CubeData")
+)]
+pub struct Cube {
+    value: u32,
+    owner: CubeOwner,
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self {
+            value: 1,
+            owner: CubeOwner::Centered,
+        }
+    }
+}
+
+impl Cube {
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    #[inline]
+    pub fn owner(&self) -> CubeOwner {
+        self.owner
+    }
+
+    /// A centered cube can be turned by both players, otherwise only by the owner.
+    pub fn may_double(&self, player: Player) -> bool {
+        match self.owner {
+            CubeOwner::Centered => true,
+            CubeOwner::Owned(owner) => owner == player,
+        }
+    }
+}
+
+/// Winner and points of a finished game. The points already include the cube value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameOutcome {
+    pub winner: Player,
+    pub points: u32,
+}
+
+/// A single game including dice, cube and the player on turn.
+///
+/// All changes happen through methods like [GameState::roll] or [GameState::play]; they return an
+/// error if the transition is not legal in the current state. This way a `GameState` always
+/// represents a legal situation. With the feature `serde`, deserialized game states are checked
+/// in the same way.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serialization::GameStateData")
+)]
+pub struct GameState {
+    /// From the point of view of the player on turn, so `x` in `position` is `turn`.
+    position: Position,
+    turn: Player,
+    dice: Option<Dice>,
+    cube: Cube,
+    /// `true` if `turn` has offered a double and the opponent has not yet decided.
+    doubled: bool,
+    /// `true` if the opponent of `turn` has just beavered; `turn` may then raccoon before rolling.
+    beavered: bool,
+    /// In the Crawford game nobody may double.
+    crawford: bool,
+    /// `None` for match play.
+    money_rules: Option<MoneyRules>,
+    outcome: Option<GameOutcome>,
+}
+
+impl GameState {
+    /// A new game in the starting position. `turn` will be the first to roll.
+    pub fn new(turn: Player, crawford: bool) -> Self {
+        Self {
+            position: STARTING,
+            turn,
+            dice: None,
+            cube: Cube::default(),
+            doubled: false,
+            beavered: false,
+            crawford,
+            money_rules: None,
+            outcome: None,
+        }
+    }
+
+    /// Money game with optional rules like Jacoby or beavers.
+    pub fn with_money_rules(self, money_rules: MoneyRules) -> Self {
+        Self {
+            money_rules: Some(money_rules),
+            ..self
+        }
+    }
+
+    /// A new game started with the opening roll: each player rolls one die and the higher one
+    /// starts the game, playing both dice.
+    ///
+    /// Returns an error if both dice are identical, in this case they have to be rolled again.
+    pub fn from_opening_roll(
+        die_one: usize,
+        die_two: usize,
+        crawford: bool,
+    ) -> Result<Self, Error> {
+        let dice = Dice::try_from((die_one, die_two))?;
+        if die_one == die_two {
+            return Err("The opening roll must not be a double.");
+        }
+        let turn = if die_one > die_two {
+            Player::One
+        } else {
+            Player::Two
+        };
+        let mut game = Self::new(turn, crawford);
+        game.dice = Some(dice);
+        Ok(game)
+    }
+
+    /// The position from the point of view of the player on turn.
+    #[inline]
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    #[inline]
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    #[inline]
+    pub fn dice(&self) -> Option<Dice> {
+        self.dice
+    }
+
+    #[inline]
+    pub fn cube(&self) -> Cube {
+        self.cube
+    }
+
+    #[inline]
+    pub fn is_doubled(&self) -> bool {
+        self.doubled
+    }
+
+    #[inline]
+    pub fn is_crawford(&self) -> bool {
+        self.crawford
+    }
+
+    #[inline]
+    pub fn money_rules(&self) -> Option<MoneyRules> {
+        self.money_rules
+    }
+
+    /// `None` while the game is still ongoing.
+    #[inline]
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        self.outcome
+    }
+
+    /// Whether the player on turn may offer a double right now.
+    pub fn may_double(&self) -> bool {
+        self.outcome.is_none()
+            && self.dice.is_none()
+            && !self.doubled
+            && !self.crawford
+            && self.cube.may_double(self.turn)
+    }
+
+    pub fn roll(&mut self, dice: Dice) -> Result<(), Error> {
+        self.check_ongoing()?;
+        if self.doubled {
+            return Err("The opponent first has to take or pass the double.");
+        }
+        if self.dice.is_some() {
+            return Err("The dice have already been rolled.");
+        }
+        self.dice = Some(dice);
+        self.beavered = false;
+        Ok(())
+    }
+
+    pub fn double(&mut self) -> Result<(), Error> {
+        self.check_ongoing()?;
+        if self.crawford {
+            return Err("Doubling is not allowed in the Crawford game.");
+        }
+        if self.dice.is_some() {
+            return Err("Doubling is only allowed before rolling the dice.");
+        }
+        if self.doubled {
+            return Err("The double has already been offered.");
+        }
+        if !self.cube.may_double(self.turn) {
+            return Err("The cube is owned by the opponent.");
+        }
+        self.doubled = true;
+        Ok(())
+    }
+
+    /// The opponent of `turn` accepts the double and owns the cube afterwards.
+    pub fn take(&mut self) -> Result<(), Error> {
+        self.check_doubled()?;
+        self.doubled = false;
+        self.cube = Cube {
+            value: self.cube.value * 2,
+            owner: CubeOwner::Owned(self.turn.opponent()),
+        };
+        Ok(())
+    }
+
+    /// The opponent of `turn` accepts the double, immediately redoubles and keeps the cube.
+    pub fn beaver(&mut self) -> Result<(), Error> {
+        self.check_doubled()?;
+        if !self.money_rules.is_some_and(|rules| rules.beavers) {
+            return Err("Beavers are not allowed.");
+        }
+        self.doubled = false;
+        self.beavered = true;
+        self.cube = Cube {
+            value: self.cube.value * 4,
+            owner: CubeOwner::Owned(self.turn.opponent()),
+        };
+        Ok(())
+    }
+
+    /// After a beaver, `turn` accepts the redouble, immediately redoubles again and keeps the cube.
+    pub fn raccoon(&mut self) -> Result<(), Error> {
+        self.check_ongoing()?;
+        if !self.money_rules.is_some_and(|rules| rules.may_raccoon()) {
+            return Err("Raccoons are not allowed.");
+        }
+        if !self.beavered {
+            return Err("A raccoon is only allowed directly after a beaver.");
+        }
+        if self.cube.owner != CubeOwner::Owned(self.turn.opponent()) {
+            return Err("A raccoon is only allowed when the opponent has beavered the cube.");
+        }
+        self.beavered = false;
+        self.cube = Cube {
+            value: self.cube.value * 2,
+            owner: CubeOwner::Owned(self.turn),
+        };
+        Ok(())
+    }
+
+    /// Doubles the centered cube because both players rolled the same number for the opening roll.
+    ///
+    /// Must be called before any checker has been moved.
+    pub fn automatic_double(&mut self) -> Result<(), Error> {
+        let maximum = self.money_rules.map_or(0, |rules| rules.automatic_doubles);
+        if self.position != STARTING || self.cube.owner != CubeOwner::Centered {
+            return Err("Automatic doubles are only allowed before the game starts.");
+        }
+        if self.cube.value.trailing_zeros() >= maximum {
+            return Err("No more automatic doubles are allowed.");
+        }
+        self.cube.value *= 2;
+        Ok(())
+    }
+
+    /// The opponent of `turn` rejects the double and loses the current cube value.
+    pub fn pass(&mut self) -> Result<(), Error> {
+        self.check_doubled()?;
+        self.doubled = false;
+        self.outcome = Some(GameOutcome {
+            winner: self.turn,
+            points: self.cube.value,
+        });
+        Ok(())
+    }
+
+    /// Moves the checkers of the player on turn. Afterwards it's the opponent's turn.
+    ///
+    /// `new` is the position after moving, from the point of view of the player who moved.
+    /// So it has *not* switched sides, contrary to [Position::all_positions_after_moving].
+    pub fn play(&mut self, new: &Position) -> Result<(), Error> {
+        self.check_ongoing()?;
+        let dice = self.dice.ok_or("The dice have not been rolled yet.")?;
+        let switched = new.sides_switched();
+        if !self
+            .position
+            .all_positions_after_moving(&dice)
+            .contains(&switched)
+        {
+            return Err("This is not a legal move for the given position and dice.");
+        }
+        if let PositionState::GameOver(result) = new.game_state() {
+            let gammons_count = self
+                .money_rules
+                .is_none_or(|rules| rules.gammons_count(&self.cube));
+            let points = match result {
+                WinGammon if gammons_count => 2,
+                WinGammon | WinNormal => 1,
+                _ => unreachable!("The player who moved can't lose by moving."),
+            };
+            self.outcome = Some(GameOutcome {
+                winner: self.turn,
+                points: points * self.cube.value,
+            });
+        }
+        self.position = switched;
+        self.turn = self.turn.opponent();
+        self.dice = None;
+        Ok(())
+    }
+
+    fn check_ongoing(&self) -> Result<(), Error> {
+        match self.outcome {
+            Some(_) => Err("The game is already over."),
+            None => Ok(()),
+        }
+    }
+
+    fn check_doubled(&self) -> Result<(), Error> {
+        self.check_ongoing()?;
+        if self.doubled {
+            Ok(())
+        } else {
+            Err("No double has been offered.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{Cube, CubeOwner, GameOutcome, GameState, Player};
+    use crate::money_rules::MoneyRules;
+    use engine::dice::Dice;
+    use engine::pos;
+    use engine::position::STARTING;
+
+    #[test]
+    fn from_opening_roll() {
+        let game = GameState::from_opening_roll(3, 5, false).unwrap();
+        assert_eq!(game.turn(), Player::Two);
+        assert_eq!(game.dice(), Some(Dice::new(3, 5)));
+        assert_eq!(game.position(), &STARTING);
+        assert!(!game.may_double());
+
+        assert_eq!(
+            GameState::from_opening_roll(4, 4, false),
+            Err("The opening roll must not be a double.")
+        );
+    }
+
+    #[test]
+    fn play_switches_turn() {
+        // Given
+        let mut game = GameState::from_opening_roll(3, 1, false).unwrap();
+        let after_move = pos!(x 24:2, 13:5, 8:2, 6:4, 5:2; o 19:5, 17:3, 12:5, 1:2);
+        // When
+        game.play(&after_move).unwrap();
+        // Then
+        assert_eq!(game.turn(), Player::Two);
+        assert_eq!(game.position(), &after_move.sides_switched());
+        assert_eq!(game.dice(), None);
+        assert!(game.may_double());
+    }
+
+    #[test]
+    fn play_illegal_move() {
+        let mut game = GameState::from_opening_roll(3, 1, false).unwrap();
+        let after_move = pos!(x 24:2, 13:5, 8:2, 6:4, 4:1, 3:1; o 19:5, 17:3, 12:5, 1:2);
+        assert_eq!(
+            game.play(&after_move),
+            Err("This is not a legal move for the given position and dice.")
+        );
+        assert_eq!(game.turn(), Player::One);
+    }
+
+    #[test]
+    fn play_without_dice() {
+        let mut game = GameState::new(Player::One, false);
+        assert_eq!(
+            game.play(&STARTING),
+            Err("The dice have not been rolled yet.")
+        );
+        game.roll(Dice::new(6, 6)).unwrap();
+        assert_eq!(
+            game.roll(Dice::new(6, 6)),
+            Err("The dice have already been rolled.")
+        );
+    }
+
+    #[test]
+    fn double_and_take() {
+        // Given
+        let mut game = GameState::new(Player::One, false);
+        // When
+        game.double().unwrap();
+        assert_eq!(
+            game.roll(Dice::new(2, 1)),
+            Err("The opponent first has to take or pass the double.")
+        );
+        game.take().unwrap();
+        // Then
+        assert_eq!(game.cube().value(), 2);
+        assert_eq!(game.cube().owner(), CubeOwner::Owned(Player::Two));
+        assert_eq!(game.double(), Err("The cube is owned by the opponent."));
+        assert!(game.roll(Dice::new(2, 1)).is_ok());
+    }
+
+    #[test]
+    fn double_and_pass() {
+        // Given
+        let mut game = GameState::new(Player::Two, false);
+        // When
+        game.double().unwrap();
+        game.pass().unwrap();
+        // Then
+        let expected = GameOutcome {
+            winner: Player::Two,
+            points: 1,
+        };
+        assert_eq!(game.outcome(), Some(expected));
+        assert_eq!(game.roll(Dice::new(2, 1)), Err("The game is already over."));
+    }
+
+    #[test]
+    fn no_double_in_crawford_game() {
+        let mut game = GameState::new(Player::One, true);
+        assert!(!game.may_double());
+        assert_eq!(
+            game.double(),
+            Err("Doubling is not allowed in the Crawford game.")
+        );
+    }
+
+    #[test]
+    fn game_over_with_gammon_and_cube() {
+        // Given
+        let mut game = GameState::new(Player::One, false);
+        game.position = pos!(x 1:1; o 24:15);
+        game.double().unwrap();
+        game.take().unwrap();
+        game.roll(Dice::new(2, 1)).unwrap();
+        // When
+        let all_checkers_off = pos!(x 1:15; o).sides_switched(); // The macro only works when `x` has checkers
+        game.play(&all_checkers_off).unwrap();
+        // Then
+        let expected = GameOutcome {
+            winner: Player::One,
+            points: 4,
+        };
+        assert_eq!(game.outcome(), Some(expected));
+    }
+
+    #[test]
+    fn jacoby_rule_without_cube_turn() {
+        // Given
+        let rules = MoneyRules {
+            jacoby: true,
+            ..Default::default()
+        };
+        let mut game = GameState::new(Player::One, false).with_money_rules(rules);
+        game.position = pos!(x 1:1; o 24:15);
+        game.roll(Dice::new(2, 1)).unwrap();
+        // When
+        let all_checkers_off = pos!(x 1:15; o).sides_switched();
+        game.play(&all_checkers_off).unwrap();
+        // Then
+        let expected = GameOutcome {
+            winner: Player::One,
+            points: 1,
+        };
+        assert_eq!(game.outcome(), Some(expected));
+    }
+
+    #[test]
+    fn beaver_and_raccoon() {
+        // Given
+        let rules = MoneyRules {
+            beavers: true,
+            raccoons: true,
+            ..Default::default()
+        };
+        let mut game = GameState::new(Player::One, false).with_money_rules(rules);
+        assert_eq!(
+            game.raccoon(),
+            Err("A raccoon is only allowed directly after a beaver.")
+        );
+        // When
+        game.double().unwrap();
+        game.beaver().unwrap();
+        game.raccoon().unwrap();
+        // Then
+        assert_eq!(game.cube().value(), 8);
+        assert_eq!(game.cube().owner(), CubeOwner::Owned(Player::One));
+        assert_eq!(game.turn(), Player::One);
+        assert!(game.roll(Dice::new(2, 1)).is_ok());
+    }
+
+    #[test]
+    fn no_raccoon_when_the_beaver_player_does_not_own_the_cube() {
+        // Given
+        let rules = MoneyRules {
+            beavers: true,
+            raccoons: true,
+            ..Default::default()
+        };
+        let mut game = GameState::new(Player::One, false).with_money_rules(rules);
+        game.double().unwrap();
+        game.beaver().unwrap();
+        game.cube = Cube {
+            value: 4,
+            owner: CubeOwner::Centered,
+        };
+        // When
+        let raccoon = game.raccoon();
+        // Then
+        assert_eq!(
+            raccoon,
+            Err("A raccoon is only allowed when the opponent has beavered the cube.")
+        );
+        assert_eq!(game.cube().value(), 4);
+    }
+
+    #[test]
+    fn no_beaver_without_rule() {
+        let mut game = GameState::new(Player::One, false);
+        game.double().unwrap();
+        assert_eq!(game.beaver(), Err("Beavers are not allowed."));
+    }
+
+    #[test]
+    fn automatic_doubles_are_limited() {
+        // Given
+        let rules = MoneyRules {
+            automatic_doubles: 2,
+            ..Default::default()
+        };
+        let mut game = GameState::from_opening_roll(5, 2, false)
+            .unwrap()
+            .with_money_rules(rules);
+        // When
+        game.automatic_double().unwrap();
+        game.automatic_double().unwrap();
+        // Then
+        assert_eq!(
+            game.automatic_double(),
+            Err("No more automatic doubles are allowed.")
+        );
+        assert_eq!(game.cube().value(), 4);
+        assert_eq!(game.cube().owner(), CubeOwner::Centered);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::game_state::{Cube, CubeOwner, Error, GameState, Player};
+use engine::dice::Dice;
+use engine::position::Position;
+
+// Indices of the fields of a FIBS board, see [Position::fibs_board].
+const TURN: usize = 32;
+const DICE: [[usize; 2]; 2] = [[33, 34], [35, 36]];
+const CUBE: usize = 37;
+const MAY_DOUBLE: [usize; 2] = [38, 39];
+const WAS_DOUBLED: usize = 40;
+const COLOUR: usize = 41;
+
+/// Conversion from and to FIBS boards in "boardstyle 3".
+///
+/// `You` is [Player::One], the opponent is [Player::Two]. Names, match length and score are not part
+/// of a `GameState`; they are ignored when parsing and get default values when writing.
+impl GameState {
+    /// If `You` has been doubled, the opponent is on turn. If nobody may double, the game is
+    /// considered to be the Crawford game.
+    pub fn from_fibs_board(board: &str) -> Result<Self, Error> {
+        let position = Position::from_fibs_board(board)?;
+        // `from_fibs_board` has already checked the number of fields.
+        let fields: Vec<&str> = board.trim().split(':').collect();
+        let number = |index: usize| {
+            fields[index]
+                .parse::<i32>()
+                .map_err(|_| "The FIBS board contains a field which is not a number.")
+        };
+        let doubled = number(WAS_DOUBLED)? != 0;
+        let turn = match number(TURN)? {
+            0 => return Err("The game of this FIBS board is already over."),
+            _ if doubled => Player::Two,
+            turn if turn == number(COLOUR)? => Player::One,
+            _ => Player::Two,
+        };
+        let cube_value = u32::try_from(number(CUBE)?)
+            .ok()
+            .filter(|value| value.is_power_of_two())
+            .ok_or("The cube of the FIBS board must be a power of 2.")?;
+        let [die1, die2] = DICE[turn.index()].map(number);
+        let dice = match (die1?, die2?) {
+            (0, 0) => None,
+            (die1, die2) => Some(Dice::try_from((die1 as usize, die2 as usize))?),
+        };
+        let (owner, crawford) = match MAY_DOUBLE.map(|index| number(index) == Ok(1)) {
+            [true, true] => (CubeOwner::Centered, false),
+            [true, false] => (CubeOwner::Owned(Player::One), false),
+            [false, true] => (CubeOwner::Owned(Player::Two), false),
+            [false, false] => (CubeOwner::Centered, true),
+        };
+        Ok(Self {
+            position: match turn {
+                Player::One => position,
+                Player::Two => position.sides_switched(),
+            },
+            turn,
+            dice,
+            cube: Cube {
+                value: cube_value,
+                owner,
+            },
+            doubled,
+            beavered: false,
+            crawford,
+            money_rules: None,
+            outcome: None,
+        })
+    }
+
+    /// A double offered by `You` can't be represented, as FIBS only shows doubles offered to `You`.
+    pub fn fibs_board(&self) -> String {
+        let position = match self.turn {
+            Player::One => self.position,
+            Player::Two => self.position.sides_switched(),
+        };
+        let mut fields: Vec<String> = position.fibs_board().split(':').map(String::from).collect();
+        fields[TURN] = match (self.outcome, self.turn) {
+            (Some(_), _) => "0",
+            // `Position::fibs_board` lets `You` play with colour 1.
+            (None, Player::One) => "1",
+            (None, Player::Two) => "-1",
+        }
+        .to_string();
+        if let Some(dice) = self.dice {
+            let (die1, die2) = match dice {
+                Dice::Mixed(mixed) => (mixed.big(), mixed.small()),
+                Dice::Double(die) => (die, die),
+            };
+            let [index1, index2] = DICE[self.turn.index()];
+            fields[index1] = die1.to_string();
+            fields[index2] = die2.to_string();
+        }
+        fields[CUBE] = self.cube.value.to_string();
+        for player in [Player::One, Player::Two] {
+            let may_double = !self.crawford && self.cube.may_double(player);
+            fields[MAY_DOUBLE[player.index()]] = (may_double as u8).to_string();
+        }
+        let was_doubled = self.doubled && self.turn == Player::Two;
+        fields[WAS_DOUBLED] = (was_doubled as u8).to_string();
+        fields.join(":")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::fibs::CUBE;
+    use crate::game_state::{CubeOwner, GameState, Player};
+    use engine::dice::Dice;
+    use engine::pos;
+    use engine::position::STARTING;
+
+    #[test]
+    fn new_game_round_trip() {
+        // Given
+        let game = GameState::from_opening_roll(3, 1, false).unwrap();
+        // When
+        let board = game.fibs_board();
+        // Then
+        assert_eq!(board, "board:You:Opponent:9999:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:3:1:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:0:0:0:0");
+        assert_eq!(GameState::from_fibs_board(&board), Ok(game));
+    }
+
+    #[test]
+    fn opponent_on_turn_with_owned_cube() {
+        // Given
+        let mut game = GameState::new(Player::One, false);
+        game.double().unwrap();
+        game.take().unwrap();
+        game.roll(Dice::new(6, 2)).unwrap();
+        game.play(&pos!(x 24:2, 13:4, 8:3, 6:5, 5:1; o 19:5, 17:3, 12:5, 1:2))
+            .unwrap();
+        game.roll(Dice::new(4, 4)).unwrap();
+        // When
+        let board = game.fibs_board();
+        let parsed = GameState::from_fibs_board(&board).unwrap();
+        // Then
+        assert_eq!(parsed, game);
+        assert_eq!(parsed.turn(), Player::Two);
+        assert_eq!(parsed.cube().value(), 2);
+        assert_eq!(parsed.cube().owner(), CubeOwner::Owned(Player::Two));
+        assert!(board.contains(":-1:0:0:4:4:2:0:1:0:1:-1:"));
+    }
+
+    #[test]
+    fn doubled_by_opponent() {
+        // Given
+        let mut game = GameState::new(Player::Two, false);
+        game.double().unwrap();
+        // When
+        let parsed = GameState::from_fibs_board(&game.fibs_board()).unwrap();
+        // Then
+        assert_eq!(parsed, game);
+        assert!(parsed.is_doubled());
+    }
+
+    #[test]
+    fn crawford_game() {
+        let game = GameState::new(Player::One, true);
+        let parsed = GameState::from_fibs_board(&game.fibs_board()).unwrap();
+        assert_eq!(parsed, game);
+        assert!(parsed.is_crawford());
+    }
+
+    #[test]
+    fn illegal_boards() {
+        let game_over = STARTING
+            .fibs_board()
+            .replace(":0:1:0:0:0:0:1:", ":0:0:0:0:0:0:1:");
+        assert_eq!(
+            GameState::from_fibs_board(&game_over),
+            Err("The game of this FIBS board is already over.")
+        );
+        for cube in ["0", "3", "-2"] {
+            let mut fields: Vec<String> =
+                STARTING.fibs_board().split(':').map(String::from).collect();
+            fields[CUBE] = cube.to_string();
+            assert_eq!(
+                GameState::from_fibs_board(&fields.join(":")),
+                Err("The cube of the FIBS board must be a power of 2.")
+            );
+        }
+        assert!(GameState::from_fibs_board("board:You:Opponent").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::game_state::{Cube, CubeOwner, Error, GameOutcome, GameState, Player};
+use crate::money_rules::MoneyRules;
+use engine::dice::Dice;
+use engine::position::{GameState as PositionState, Position};
+use serde::Deserialize;
+
+// Deserialization goes through the structs below, so that a deserialized `GameState` fulfills the
+// same invariants as one which has been built with the methods of `GameState`.
+
+#[derive(Deserialize)]
+pub(super) struct CubeData {
+    value: u32,
+    owner: CubeOwner,
+}
+
+impl TryFrom<CubeData> for Cube {
+    type Error = Error;
+
+    fn try_from(data: CubeData) -> Result<Self, Self::Error> {
+        if !data.value.is_power_of_two() {
+            return Err("The cube value must be a power of two.");
+        }
+        Ok(Self {
+            value: data.value,
+            owner: data.owner,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct GameStateData {
+    position: Position,
+    turn: Player,
+    dice: Option<Dice>,
+    cube: Cube,
+    doubled: bool,
+    beavered: bool,
+    crawford: bool,
+    money_rules: Option<MoneyRules>,
+    outcome: Option<GameOutcome>,
+}
+
+impl TryFrom<GameStateData> for GameState {
+    type Error = Error;
+
+    fn try_from(data: GameStateData) -> Result<Self, Self::Error> {
+        if data.outcome.is_none() && data.position.game_state() != PositionState::Ongoing {
+            return Err("A game with a finished position must have an outcome.");
+        }
+        if data.crawford && (data.money_rules.is_some() || data.cube != Cube::default()) {
+            return Err("The Crawford game is played without cube and only in matches.");
+        }
+        if data.crawford && data.doubled {
+            return Err("No double can be offered in the Crawford game.");
+        }
+        if data.doubled
+            && (data.dice.is_some()
+                || data.outcome.is_some()
+                || data.beavered
+                || !data.cube.may_double(data.turn))
+        {
+            return Err("A double can only be offered before rolling and with access to the cube.");
+        }
+        if data.beavered
+            && (data.dice.is_some()
+                || !data.money_rules.is_some_and(|rules| rules.beavers)
+                || data.cube.owner != CubeOwner::Owned(data.turn.opponent()))
+        {
+            return Err("A beaver is only possible before rolling and with beavers allowed.");
+        }
+        Ok(Self {
+            position: data.position,
+            turn: data.turn,
+            dice: data.dice,
+            cube: data.cube,
+            doubled: data.doubled,
+            beavered: data.beavered,
+            crawford: data.crawford,
+            money_rules: data.money_rules,
+            outcome: data.outcome,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{GameState, Player};
+    use crate::money_rules::MoneyRules;
+    use engine::dice::Dice;
+
+    #[test]
+    fn round_trip() {
+        // Given
+        let mut game = GameState::from_opening_roll(3, 1, false)
+            .unwrap()
+            .with_money_rules(MoneyRules {
+                beavers: true,
+                ..MoneyRules::default()
+            });
+        game.play(
+            &game.position().all_positions_after_moving(&Dice::new(3, 1))[0].sides_switched(),
+        )
+        .unwrap();
+        game.double().unwrap();
+        // When
+        let json = serde_json::to_string(&game).unwrap();
+        let deserialized: GameState = serde_json::from_str(&json).unwrap();
+        // Then
+        assert_eq!(deserialized, game);
+        assert_eq!(deserialized.turn(), Player::Two);
+    }
+
+    #[test]
+    fn dice_as_pair() {
+        let game = GameState::from_opening_roll(3, 5, false).unwrap();
+        let json = serde_json::to_value(&game).unwrap();
+        assert_eq!(json["dice"], serde_json::json!([5, 3]));
+        assert_eq!(
+            json["cube"],
+            serde_json::json!({"value": 1, "owner": "Centered"})
+        );
+    }
+
+    #[test]
+    fn illegal_game_states() {
+        let game = GameState::new(Player::One, false);
+        let mut json = serde_json::to_value(&game).unwrap();
+        json["cube"]["value"] = 3.into();
+        let error = serde_json::from_value::<GameState>(json.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "The cube value must be a power of two.");
+
+        json["cube"]["value"] = 1.into();
+        json["dice"] = serde_json::json!([2, 7]);
+        let error = serde_json::from_value::<GameState>(json.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "Dice values must be between 1 and 6.");
+
+        json["dice"] = serde_json::json!([2, 6]);
+        json["doubled"] = true.into();
+        let error = serde_json::from_value::<GameState>(json.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A double can only be offered before rolling and with access to the cube."
+        );
+
+        json["dice"] = serde_json::Value::Null;
+        json["crawford"] = true.into();
+        let error = serde_json::from_value::<GameState>(json.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No double can be offered in the Crawford game."
+        );
+
+        json["crawford"] = false.into();
+        json["doubled"] = false.into();
+        json["beavered"] = true.into();
+        let error = serde_json::from_value::<GameState>(json).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A beaver is only possible before rolling and with beavers allowed."
+        );
+    }
+}
@@ -1,4 +1,11 @@
+pub mod analysis;
 pub mod bg_move;
-#[cfg(feature = "web")]
+pub mod board_svg;
 pub mod cube;
+pub mod game_state;
+pub mod match_equity;
+pub mod match_state;
+pub mod money_rules;
+pub mod resignation;
+pub mod tutor;
 pub mod wildbg_api;
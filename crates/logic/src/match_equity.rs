@@ -0,0 +1,630 @@
+use crate::cube::{recube_take_point, DoublingWindow};
+use engine::probabilities::Probabilities;
+
+type Error = &'static str;
+
+/// Largest number of points away covered by [MatchEquityTable::default].
+pub const MAX_AWAY: u32 = 25;
+
+/// Share of games which end with a gammon, used to generate [MatchEquityTable::default].
+pub const DEFAULT_GAMMON_RATE: f32 = 0.2;
+
+/// Score of a match from the point of view of player `x`, who is on roll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchScore {
+    /// Points `x` still needs to win the match, zero if `x` has already won.
+    pub x_away: u32,
+    /// Points `o` still needs to win the match, zero if `o` has already won.
+    pub o_away: u32,
+    /// `true` if the current game is the Crawford game.
+    pub crawford: bool,
+}
+
+impl MatchScore {
+    /// Score before the next game, after `x` won `x_points` or `o` won `o_points` in this one.
+    pub fn after_game(&self, x_points: u32, o_points: u32) -> Self {
+        let x_away = self.x_away.saturating_sub(x_points);
+        let o_away = self.o_away.saturating_sub(o_points);
+        let was_one_away = self.x_away == 1 || self.o_away == 1;
+        let is_one_away = x_away == 1 || o_away == 1;
+        Self {
+            x_away,
+            o_away,
+            crawford: !was_one_away && is_one_away,
+        }
+    }
+
+    /// The same score from the point of view of `o`.
+    pub fn sides_switched(&self) -> Self {
+        Self {
+            x_away: self.o_away,
+            o_away: self.x_away,
+            crawford: self.crawford,
+        }
+    }
+}
+
+/// Match winning chances (MWC) for all scores, used to convert cubeless probabilities into MWC.
+///
+/// Like published tables it consists of two parts: the MWC before the Crawford game, where the row
+/// for 1-away is the Crawford game itself, and the MWC of the trailer after the Crawford game.
+/// Published tables like Kazaross-XG2 can be loaded from the XML files of GnuBG with
+/// [MatchEquityTable::from_gnubg_xml] or from plain values with [MatchEquityTable::try_new].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchEquityTable {
+    /// `pre_crawford[i][j]` is the MWC of a player who is `i + 1` away against `j + 1` away.
+    pre_crawford: Vec<Vec<f32>>,
+    /// `post_crawford[i]` is the MWC of the trailer who is `i + 1` away against 1-away.
+    post_crawford: Vec<f32>,
+}
+
+impl Default for MatchEquityTable {
+    fn default() -> Self {
+        Self::generated(MAX_AWAY, DEFAULT_GAMMON_RATE)
+    }
+}
+
+impl MatchEquityTable {
+    /// Takes tables as described in [MatchEquityTable], both with the same length.
+    pub fn try_new(pre_crawford: Vec<Vec<f32>>, post_crawford: Vec<f32>) -> Result<Self, Error> {
+        let len = post_crawford.len();
+        if len == 0 || pre_crawford.len() != len || pre_crawford.iter().any(|row| row.len() != len)
+        {
+            return Err(
+                "The match equity table must be square and as long as the post Crawford table.",
+            );
+        }
+        let values = pre_crawford.iter().flatten().chain(post_crawford.iter());
+        if values.clone().any(|mwc| !(0.0..=1.0).contains(mwc)) {
+            return Err("Match winning chances must be between 0.0 and 1.0.");
+        }
+        Ok(Self {
+            pre_crawford,
+            post_crawford,
+        })
+    }
+
+    /// Reads a table in the XML format of GnuBG, for example `met/Kazaross-XG2.xml` of a GnuBG
+    /// installation.
+    ///
+    /// Only explicit tables are supported, not the parametrized ones. If the file contains post
+    /// Crawford tables for both players separately, the first one is used. If both parts have a
+    /// different length, the table is cut to the shorter one.
+    pub fn from_gnubg_xml(xml: &str) -> Result<Self, Error> {
+        let pre_crawford = xml_table(xml, "pre-crawford-table")?;
+        let post_crawford = xml_table(xml, "post-crawford-table")?
+            .into_iter()
+            .next()
+            .ok_or("The post Crawford table has no row.")?;
+        let len = post_crawford.len().min(pre_crawford.len());
+        let pre_crawford = pre_crawford
+            .into_iter()
+            .take(len)
+            .map(|row| row.into_iter().take(len).collect())
+            .collect();
+        Self::try_new(pre_crawford, post_crawford.into_iter().take(len).collect())
+    }
+
+    /// Generates a table with a simple model of the game.
+    ///
+    /// Each game is won by both players with 50%, `gammon_rate` of the games end with a gammon.
+    /// Games before the Crawford game are assumed to be doubled once and taken. After the Crawford
+    /// game the trailer doubles immediately, the free drop of the leader is ignored.
+    ///
+    /// Up to 5-away the values are within four percentage points of published tables, for long
+    /// matches the leader is overrated, as cubes bigger than 2 are not modeled.
+    pub fn generated(max_away: u32, gammon_rate: f32) -> Self {
+        let n = max_away as usize;
+        let (g, normal) = (gammon_rate, 1.0 - gammon_rate);
+        // Index is the number of points away, zero or less means the trailer has won.
+        let mut post = vec![1.0; n + 1];
+        for away in 1..=n {
+            let after = |won: usize| if won >= away { 1.0 } else { post[away - won] };
+            let mwc = 0.5 * normal * after(2) + 0.5 * g * after(4);
+            post[away] = mwc;
+        }
+        let post_at = |away: usize| if away == 0 { 1.0 } else { post[away] };
+        // MWC of the trailer in the Crawford game.
+        let crawford_trailer = |away: usize| {
+            0.5 * normal * post_at(away - 1) + 0.5 * g * post_at(away.saturating_sub(2))
+        };
+        let mut pre = vec![vec![0.5; n]; n];
+        for x in 1..=n {
+            for o in 1..=n {
+                let mwc = match (x, o) {
+                    (1, 1) => 0.5,
+                    (1, o) => 1.0 - crawford_trailer(o),
+                    (x, 1) => crawford_trailer(x),
+                    (x, o) => {
+                        let value = |x: usize, o: usize| match (x, o) {
+                            (0, _) => 1.0,
+                            (_, 0) => 0.0,
+                            (x, o) => pre[x - 1][o - 1],
+                        };
+                        0.5 * normal * value(x.saturating_sub(2), o)
+                            + 0.5 * g * value(x.saturating_sub(4), o)
+                            + 0.5 * normal * value(x, o.saturating_sub(2))
+                            + 0.5 * g * value(x, o.saturating_sub(4))
+                    }
+                };
+                pre[x - 1][o - 1] = mwc;
+            }
+        }
+        Self {
+            pre_crawford: pre,
+            post_crawford: post[1..].to_vec(),
+        }
+    }
+
+    /// Largest number of points away in the table. Bigger values are treated as this one.
+    pub fn max_away(&self) -> u32 {
+        self.post_crawford.len() as u32
+    }
+
+    /// MWC of `x` at `score` before the game is started.
+    pub fn mwc_at(&self, score: &MatchScore) -> f32 {
+        let index = |away: u32| (away.min(self.max_away()) - 1) as usize;
+        match (score.x_away, score.o_away) {
+            (0, _) => 1.0,
+            (_, 0) => 0.0,
+            (1, o_away) if !score.crawford && o_away > 1 => 1.0 - self.post_crawford[index(o_away)],
+            (x_away, 1) if !score.crawford && x_away > 1 => self.post_crawford[index(x_away)],
+            (x_away, o_away) => self.pre_crawford[index(x_away)][index(o_away)],
+        }
+    }
+
+    /// Cubeless MWC of `x` at `score` with a cube of value `cube`.
+    ///
+    /// Backgammons are counted as gammons, the probabilities don't contain them.
+    pub fn mwc(&self, probabilities: &Probabilities, score: &MatchScore, cube: u32) -> f32 {
+        let after =
+            |x_points: u32, o_points: u32| self.mwc_at(&score.after_game(x_points, o_points));
+        probabilities.win_normal * after(cube, 0)
+            + probabilities.win_gammon * after(2 * cube, 0)
+            + probabilities.lose_normal * after(0, cube)
+            + probabilities.lose_gammon * after(0, 2 * cube)
+    }
+
+    /// Cubeless MWC of `o` at `score`, for probabilities from the point of view of `x`.
+    pub fn mwc_of_opponent(
+        &self,
+        probabilities: &Probabilities,
+        score: &MatchScore,
+        cube: u32,
+    ) -> f32 {
+        self.mwc(&probabilities.switch_sides(), &score.sides_switched(), cube)
+    }
+
+    /// Converts `mwc` of `x` into normalized match equity, as reported for example by XG.
+    ///
+    /// Winning a single game with the current cube is +1.0, losing it is -1.0, so that errors are
+    /// comparable with money game equities.
+    pub fn normalized_equity(&self, mwc: f32, score: &MatchScore, cube: u32) -> f32 {
+        let win = self.mwc_at(&score.after_game(cube, 0));
+        let lose = self.mwc_at(&score.after_game(0, cube));
+        2.0 * (mwc - lose) / (win - lose) - 1.0
+    }
+
+    /// Minimum winning chances of `o` to take a double of `x` from `cube` to `2 * cube`.
+    ///
+    /// Unlike [MatchEquityTable::window] this includes the recube vig: after taking, `o` owns the
+    /// cube and may redouble as long as that can gain something. The take point of `x` for that
+    /// redouble is computed in the same way, so all recubes until the end of the match count.
+    /// `cube_efficiency` interpolates between a dead cube at `0.0` and a perfectly live cube at
+    /// `1.0`, see [crate::cube::Janowski].
+    ///
+    /// Returns an error if `cube` is not a power of 2 or `cube_efficiency` is not between `0.0` and
+    /// `1.0`.
+    pub fn take_point(
+        &self,
+        probabilities: &Probabilities,
+        score: &MatchScore,
+        cube: u32,
+        cube_efficiency: f32,
+    ) -> Result<f32, Error> {
+        if !cube.is_power_of_two() {
+            return Err("The cube value must be a power of 2.");
+        }
+        if !(0.0..=1.0).contains(&cube_efficiency) {
+            return Err("The cube efficiency must be between 0.0 and 1.0.");
+        }
+        Ok(self.take_point_with_recubes(probabilities, score, cube, cube_efficiency))
+    }
+
+    /// [MatchEquityTable::take_point] for a valid cube value and cube efficiency.
+    fn take_point_with_recubes(
+        &self,
+        probabilities: &Probabilities,
+        score: &MatchScore,
+        cube: u32,
+        cube_efficiency: f32,
+    ) -> f32 {
+        let dead_take_point = 1.0 - self.window(probabilities, score, cube).cash_point;
+        // A redouble is useless if `o` already wins the match with the doubled cube.
+        let doubled = match cube.checked_mul(2) {
+            Some(doubled) if score.o_away > doubled && score.x_away > 0 => doubled,
+            _ => return dead_take_point,
+        };
+        let recube = self.take_point_with_recubes(
+            &probabilities.switch_sides(),
+            &score.sides_switched(),
+            doubled,
+            cube_efficiency,
+        );
+        recube_take_point(dead_take_point, recube, cube_efficiency)
+    }
+
+    /// Doubling window of `x` at `score`, when `x` considers doubling a cube of value `cube`.
+    ///
+    /// The cube is treated as dead, so after a take the game is played to the end. Wins and losses
+    /// are split into normal ones and gammons like in `probabilities`. Thresholds which can't be
+    /// reached are clamped to `0.0` or `1.0`, for example the opponent has to take a double which
+    /// already loses the match.
+    pub fn window(
+        &self,
+        probabilities: &Probabilities,
+        score: &MatchScore,
+        cube: u32,
+    ) -> DoublingWindow {
+        let win = probabilities.win();
+        let after =
+            |x_points: u32, o_points: u32| self.mwc_at(&score.after_game(x_points, o_points));
+        // MWC of `x` after a win or loss with the current cube and with the doubled cube.
+        let average = |normal: f32, gammon: f32, value: &dyn Fn(u32) -> f32, cube: u32| {
+            if normal + gammon > 0.0 {
+                (normal * value(cube) + gammon * value(cube.saturating_mul(2))) / (normal + gammon)
+            } else {
+                value(cube)
+            }
+        };
+        let won = |cube: u32| {
+            let value = |points: u32| after(points, 0);
+            average(
+                probabilities.win_normal,
+                probabilities.win_gammon,
+                &value,
+                cube,
+            )
+        };
+        let lost = |cube: u32| {
+            let value = |points: u32| after(0, points);
+            average(
+                probabilities.lose_normal,
+                probabilities.lose_gammon,
+                &value,
+                cube,
+            )
+        };
+        let (won_now, lost_now) = (won(cube), lost(cube));
+        let doubled = cube.saturating_mul(2);
+        let (won_doubled, lost_doubled) = (won(doubled), lost(doubled));
+        let double_pass = after(cube, 0);
+        let ratio = |numerator: f32, denominator: f32| {
+            if denominator > 0.0 {
+                (numerator / denominator).clamp(0.0, 1.0)
+            } else {
+                1.0
+            }
+        };
+        DoublingWindow {
+            doubling_point: ratio(
+                lost_now - lost_doubled,
+                (won_doubled - lost_doubled) - (won_now - lost_now),
+            ),
+            cash_point: ratio(double_pass - lost_doubled, won_doubled - lost_doubled),
+            too_good_point: ratio(double_pass - lost_now, won_now - lost_now),
+            win,
+        }
+    }
+}
+
+/// Rows of the first element `<name ...>` in `xml`, each row is `<row>` with `<me>` values.
+fn xml_table(xml: &str, name: &str) -> Result<Vec<Vec<f32>>, Error> {
+    let start = xml
+        .find(&format!("<{name}"))
+        .ok_or("The match equity table is incomplete.")?;
+    let xml = &xml[start..];
+    let (tag, body) = xml
+        .split_once('>')
+        .ok_or("The match equity table is not valid XML.")?;
+    if !tag.contains("type=\"explicit\"") {
+        return Err("Only explicit match equity tables are supported.");
+    }
+    let body = &body[..body
+        .find(&format!("</{name}>"))
+        .ok_or("The match equity table is not valid XML.")?];
+    body.split("<row>")
+        .skip(1)
+        .map(|row| {
+            let row = &row[..row
+                .find("</row>")
+                .ok_or("The match equity table is not valid XML.")?];
+            row.split("<me>")
+                .skip(1)
+                .map(|value| {
+                    value
+                        .split_once("</me>")
+                        .and_then(|(value, _)| value.trim().parse::<f32>().ok())
+                        .ok_or("Match winning chances must be numbers.")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::WindowPosition;
+    use crate::match_equity::{MatchEquityTable, MatchScore};
+    use engine::probabilities::Probabilities;
+
+    fn score(x_away: u32, o_away: u32) -> MatchScore {
+        MatchScore {
+            x_away,
+            o_away,
+            crawford: false,
+        }
+    }
+
+    #[test]
+    fn generated_table_is_symmetric() {
+        let met = MatchEquityTable::default();
+        assert_eq!(met.max_away(), 25);
+        for x_away in 1..=25 {
+            assert!((met.mwc_at(&score(x_away, x_away)) - 0.5).abs() < 1e-5);
+            for o_away in 1..=25 {
+                let mwc = met.mwc_at(&score(x_away, o_away));
+                let opponent = met.mwc_at(&score(o_away, x_away));
+                assert!((mwc + opponent - 1.0).abs() < 1e-5, "{x_away} {o_away}");
+            }
+        }
+        // The leader is favorite.
+        assert!(met.mwc_at(&score(2, 3)) > 0.55);
+        assert!(met.mwc_at(&score(3, 2)) < 0.45);
+    }
+
+    #[test]
+    fn crawford_and_post_crawford() {
+        // Given
+        let met = MatchEquityTable::generated(3, 0.2);
+        let crawford = MatchScore {
+            crawford: true,
+            ..score(1, 2)
+        };
+        // Then
+        // The trailer wins the Crawford game with 50%, then it's 1-away against 1-away. Or with a
+        // gammon, then the match is over.
+        assert!((met.mwc_at(&crawford) - 0.7).abs() < 1e-6);
+        // After the Crawford game the trailer doubles immediately, any win wins the match.
+        assert_eq!(met.mwc_at(&score(1, 2)), 0.5);
+        assert_eq!(met.mwc_at(&score(0, 2)), 1.0);
+        assert_eq!(met.mwc_at(&score(2, 0)), 0.0);
+    }
+
+    #[test]
+    fn mwc_and_normalized_equity() {
+        // Given
+        let met = MatchEquityTable::default();
+        let at = score(2, 2);
+        let certain_win = Probabilities {
+            win_normal: 1.0,
+            ..Default::default()
+        };
+        let even = Probabilities {
+            win_normal: 0.5,
+            lose_normal: 0.5,
+            ..Default::default()
+        };
+        // When
+        let mwc_win = met.mwc(&certain_win, &at, 1);
+        // Then
+        let crawford = MatchScore {
+            crawford: true,
+            ..score(1, 2)
+        };
+        assert_eq!(mwc_win, met.mwc_at(&crawford));
+        assert_eq!(met.normalized_equity(mwc_win, &at, 1), 1.0);
+        assert!(met.normalized_equity(met.mwc(&even, &at, 1), &at, 1).abs() < 1e-6);
+        assert_eq!(met.mwc(&certain_win, &at, 2), 1.0);
+        assert_eq!(met.mwc_of_opponent(&certain_win, &at, 2), 0.0);
+    }
+
+    #[test]
+    fn window_at_double_match_point() {
+        // Given
+        let met = MatchEquityTable::default();
+        let at = score(2, 2);
+        let probabilities = Probabilities {
+            win_normal: 0.6,
+            lose_normal: 0.4,
+            ..Default::default()
+        };
+        // When
+        let window = met.window(&probabilities, &at, 1);
+        // Then
+        // Without gammons the first game decides nothing after a double, so each double is right.
+        assert!(window.cash_point > 0.5 && window.cash_point < 1.0);
+        assert!(window.doubling_point <= window.cash_point);
+        assert_eq!(window.position(), WindowPosition::DoubleTake);
+    }
+
+    #[test]
+    fn window_of_trailer_after_crawford() {
+        // Given
+        let met = MatchEquityTable::default();
+        let at = score(4, 1);
+        let underdog = Probabilities {
+            win_normal: 0.3,
+            win_gammon: 0.1,
+            lose_normal: 0.5,
+            lose_gammon: 0.1,
+        };
+        // When
+        let window = met.window(&underdog, &at, 1);
+        // Then
+        // Any loss loses the match, so the trailer has nothing to lose by doubling.
+        assert_eq!(window.doubling_point, 0.0);
+        assert!(window.cash_point > window.win);
+        assert_eq!(window.position(), WindowPosition::DoubleTake);
+    }
+
+    #[test]
+    fn recube_vig_lowers_take_point() {
+        // Given
+        let met = MatchEquityTable::default();
+        let even = Probabilities {
+            win_normal: 0.4,
+            win_gammon: 0.1,
+            lose_normal: 0.4,
+            lose_gammon: 0.1,
+        };
+        let at = score(7, 7);
+        // When
+        let dead = met.take_point(&even, &at, 1, 0.0).unwrap();
+        let live = met.take_point(&even, &at, 1, 0.68).unwrap();
+        // Then
+        assert_eq!(dead, 1.0 - met.window(&even, &at, 1).cash_point);
+        assert!(live < dead);
+        assert!(met.take_point(&even, &at, 1, 1.0).unwrap() < live);
+    }
+
+    #[test]
+    fn no_recube_vig_when_cube_is_dead() {
+        // Given
+        let met = MatchEquityTable::default();
+        let even = Probabilities {
+            win_normal: 0.4,
+            win_gammon: 0.1,
+            lose_normal: 0.4,
+            lose_gammon: 0.1,
+        };
+        // When
+        let at = score(3, 2);
+        // Then
+        // After taking, `o` wins the match with any win, so there is no reason to redouble.
+        let dead = met.take_point(&even, &at, 1, 0.0).unwrap();
+        assert_eq!(met.take_point(&even, &at, 1, 1.0), Ok(dead));
+    }
+
+    #[test]
+    fn take_point_rejects_invalid_cube() {
+        let met = MatchEquityTable::default();
+        let even = Probabilities {
+            win_normal: 0.5,
+            lose_normal: 0.5,
+            ..Default::default()
+        };
+        let at = score(7, 7);
+        let error = Err("The cube value must be a power of 2.");
+        assert_eq!(met.take_point(&even, &at, 0, 0.68), error);
+        assert_eq!(met.take_point(&even, &at, 3, 0.68), error);
+        assert_eq!(
+            met.take_point(&even, &at, 2, 1.5),
+            Err("The cube efficiency must be between 0.0 and 1.0.")
+        );
+        // The doubled cube doesn't fit into `u32`, so there is no redouble.
+        let huge = score(u32::MAX, u32::MAX);
+        assert!(met.take_point(&even, &huge, 1 << 31, 0.68).is_ok());
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert!(MatchEquityTable::try_new(vec![vec![0.5]], vec![0.5]).is_ok());
+        assert!(MatchEquityTable::try_new(vec![vec![0.5]], vec![0.5, 0.4]).is_err());
+        assert!(MatchEquityTable::try_new(vec![vec![1.5]], vec![0.5]).is_err());
+        assert!(MatchEquityTable::try_new(vec![], vec![]).is_err());
+    }
+
+    #[test]
+    fn generated_table_is_close_to_kazaross_xg2() {
+        // Kazaross-XG2 rounded to whole percent, as it's usually quoted. Rows are the leader,
+        // 1-away is the Crawford game.
+        let published = [
+            (1, 2, 68),
+            (1, 3, 75),
+            (1, 4, 82),
+            (1, 5, 84),
+            (2, 3, 60),
+            (2, 4, 67),
+            (2, 5, 74),
+            (3, 4, 58),
+            (3, 5, 65),
+            (4, 5, 58),
+        ];
+        let met = MatchEquityTable::default();
+        for (x_away, o_away, percent) in published {
+            let at = MatchScore {
+                crawford: x_away == 1,
+                ..score(x_away, o_away)
+            };
+            let mwc = met.mwc_at(&at);
+            assert!(
+                (100.0 * mwc - percent as f32).abs() < 4.0,
+                "{x_away}-away {o_away}-away: {mwc}"
+            );
+        }
+    }
+
+    #[test]
+    fn gnubg_xml() {
+        // Given
+        let generated = MatchEquityTable::generated(3, 0.2);
+        let row = |values: &[f32]| {
+            let values: String = values.iter().map(|v| format!("<me>{v}</me> ")).collect();
+            format!("<row> {values}</row>\n")
+        };
+        let pre: String = generated.pre_crawford.iter().map(|r| row(r)).collect();
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="ISO-8859-1" ?>
+<met>
+  <info><name>Generated</name></info>
+  <pre-crawford-table type="explicit">
+{pre}  </pre-crawford-table>
+  <post-crawford-table player="both" type="explicit">
+{}  </post-crawford-table>
+</met>"#,
+            row(&generated.post_crawford)
+        );
+        // When
+        let met = MatchEquityTable::from_gnubg_xml(&xml);
+        // Then
+        assert_eq!(met, Ok(generated));
+    }
+
+    #[test]
+    fn gnubg_xml_cut_to_shorter_part() {
+        let xml = r#"<pre-crawford-table type="explicit">
+              <row><me>0.5</me><me>0.7</me></row>
+              <row><me>0.3</me><me>0.5</me></row>
+            </pre-crawford-table>
+            <post-crawford-table player="0" type="explicit"><row><me>0.5</me></row></post-crawford-table>
+            <post-crawford-table player="1" type="explicit"><row><me>0.4</me></row></post-crawford-table>"#;
+        let met = MatchEquityTable::from_gnubg_xml(xml).unwrap();
+        assert_eq!(met.max_away(), 1);
+        assert_eq!(met.pre_crawford, vec![vec![0.5]]);
+    }
+
+    #[test]
+    fn invalid_gnubg_xml() {
+        let explicit =
+            r#"<post-crawford-table type="explicit"><row><me>0.5</me></row></post-crawford-table>"#;
+        for (xml, error) in [
+            ("<met></met>", "The match equity table is incomplete."),
+            (
+                r#"<pre-crawford-table type="zadeh"></pre-crawford-table>"#,
+                "Only explicit match equity tables are supported.",
+            ),
+            (
+                r#"<pre-crawford-table type="explicit"><row><me>0.5</me></row>"#,
+                "The match equity table is not valid XML.",
+            ),
+            (
+                r#"<pre-crawford-table type="explicit"><row><me>half</me></row></pre-crawford-table>"#,
+                "Match winning chances must be numbers.",
+            ),
+        ] {
+            let xml = format!("{xml}{explicit}");
+            assert_eq!(MatchEquityTable::from_gnubg_xml(&xml), Err(error));
+        }
+    }
+}
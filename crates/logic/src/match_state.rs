@@ -0,0 +1,231 @@
+use crate::game_state::{GameOutcome, GameState, Player};
+use crate::match_equity::MatchScore;
+use crate::money_rules::MoneyRules;
+use crate::wildbg_api::WildbgConfig;
+
+type Error = &'static str;
+
+/// Score and Crawford status of a match or a money session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchState {
+    /// Number of points needed to win the match. `None` for money game.
+    length: Option<u32>,
+    /// Index 0 is for [Player::One], index 1 for [Player::Two].
+    score: [u32; 2],
+    /// `true` if the next game is the Crawford game.
+    crawford: bool,
+    /// `true` if the Crawford game has already been played.
+    post_crawford: bool,
+    /// `None` for match play.
+    money_rules: Option<MoneyRules>,
+}
+
+impl MatchState {
+    pub fn money(money_rules: MoneyRules) -> Self {
+        Self {
+            length: None,
+            score: [0, 0],
+            crawford: false,
+            post_crawford: false,
+            money_rules: Some(money_rules),
+        }
+    }
+
+    pub fn new(length: u32) -> Result<Self, Error> {
+        if length == 0 {
+            return Err("The match length must be at least 1.");
+        }
+        Ok(Self {
+            length: Some(length),
+            score: [0, 0],
+            crawford: false,
+            post_crawford: false,
+            money_rules: None,
+        })
+    }
+
+    #[inline]
+    pub fn length(&self) -> Option<u32> {
+        self.length
+    }
+
+    #[inline]
+    pub fn score(&self, player: Player) -> u32 {
+        self.score[player.index()]
+    }
+
+    #[inline]
+    pub fn money_rules(&self) -> Option<MoneyRules> {
+        self.money_rules
+    }
+
+    /// `true` if the next game is the Crawford game.
+    #[inline]
+    pub fn is_crawford(&self) -> bool {
+        self.crawford
+    }
+
+    /// Number of points `player` still needs to win the match. `None` for money game.
+    pub fn away(&self, player: Player) -> Option<u32> {
+        self.length
+            .map(|length| length.saturating_sub(self.score(player)))
+    }
+
+    /// Score from the point of view of `player`, for example to convert probabilities into match
+    /// winning chances. `None` for money game.
+    pub fn match_score(&self, player: Player) -> Option<MatchScore> {
+        Some(MatchScore {
+            x_away: self.away(player)?,
+            o_away: self.away(player.opponent())?,
+            crawford: self.crawford,
+        })
+    }
+
+    /// `None` for money game or if the match is still ongoing.
+    pub fn winner(&self) -> Option<Player> {
+        [Player::One, Player::Two]
+            .into_iter()
+            .find(|&player| self.away(player) == Some(0))
+    }
+
+    /// A new game with proper Crawford status, started with the opening roll.
+    pub fn new_game(&self, die_one: usize, die_two: usize) -> Result<GameState, Error> {
+        if self.winner().is_some() {
+            return Err("The match is already over.");
+        }
+        let game = GameState::from_opening_roll(die_one, die_two, self.crawford)?;
+        Ok(match self.money_rules {
+            Some(money_rules) => game.with_money_rules(money_rules),
+            None => game,
+        })
+    }
+
+    /// Adds the points of a finished game to the score and updates the Crawford status.
+    pub fn add_game(&mut self, game: &GameState) -> Result<(), Error> {
+        let GameOutcome { winner, points } = game.outcome().ok_or("The game is not over yet.")?;
+        if self.winner().is_some() {
+            return Err("The match is already over.");
+        }
+        self.score[winner.index()] += points;
+        if self.crawford {
+            self.crawford = false;
+            self.post_crawford = true;
+        } else if !self.post_crawford && self.winner().is_none() {
+            self.crawford = self.away(winner) == Some(1);
+        }
+        Ok(())
+    }
+
+    /// Configuration for the evaluation of the current position of `game`.
+    pub fn config(&self, game: &GameState) -> WildbgConfig {
+        let turn = game.turn();
+        let away = match (self.away(turn), self.away(turn.opponent())) {
+            (Some(x_away), Some(o_away)) => Some((x_away, o_away)),
+            _ => None,
+        };
+        let gammons_count = self
+            .money_rules
+            .is_none_or(|rules| rules.gammons_count(&game.cube()));
+        WildbgConfig {
+            away,
+            gammons_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{GameState, Player};
+    use crate::match_equity::MatchScore;
+    use crate::match_state::MatchState;
+    use crate::money_rules::MoneyRules;
+    use engine::pos;
+
+    /// A game that `winner` won by a single point after the opponent passed a double.
+    fn passed_game(winner: Player) -> GameState {
+        let mut game = GameState::new(winner, false);
+        game.double().unwrap();
+        game.pass().unwrap();
+        game
+    }
+
+    #[test]
+    fn money_has_no_away_and_no_winner() {
+        let mut money = MatchState::money(MoneyRules::default());
+        money.add_game(&passed_game(Player::One)).unwrap();
+        assert_eq!(money.score(Player::One), 1);
+        assert_eq!(money.away(Player::One), None);
+        assert_eq!(money.winner(), None);
+        assert_eq!(money.config(&passed_game(Player::One)).away, None);
+        assert_eq!(money.match_score(Player::One), None);
+    }
+
+    #[test]
+    fn crawford_game_follows_reaching_one_away() {
+        // Given
+        let mut state = MatchState::new(3).unwrap();
+        // When
+        state.add_game(&passed_game(Player::Two)).unwrap();
+        state.add_game(&passed_game(Player::Two)).unwrap();
+        // Then
+        assert_eq!(state.away(Player::Two), Some(1));
+        assert!(state.is_crawford());
+        let crawford_game = state.new_game(3, 1).unwrap();
+        assert!(crawford_game.is_crawford());
+        assert_eq!(state.config(&crawford_game).away, Some((3, 1)));
+        assert_eq!(
+            state.match_score(Player::One),
+            Some(MatchScore {
+                x_away: 3,
+                o_away: 1,
+                crawford: true
+            })
+        );
+
+        // Only one Crawford game
+        state.add_game(&passed_game(Player::One)).unwrap();
+        assert!(!state.is_crawford());
+        state.add_game(&passed_game(Player::One)).unwrap();
+        assert!(!state.is_crawford());
+        assert_eq!(state.away(Player::One), Some(1));
+    }
+
+    #[test]
+    fn match_over() {
+        // Given
+        let mut state = MatchState::new(1).unwrap();
+        // When
+        state.add_game(&passed_game(Player::One)).unwrap();
+        // Then
+        assert_eq!(state.winner(), Some(Player::One));
+        assert!(!state.is_crawford());
+        assert_eq!(state.new_game(3, 1), Err("The match is already over."));
+    }
+
+    #[test]
+    fn unfinished_game_cannot_be_added() {
+        let mut state = MatchState::new(5).unwrap();
+        let game = GameState::new(Player::One, false);
+        assert_eq!(state.add_game(&game), Err("The game is not over yet."));
+    }
+
+    #[test]
+    fn jacoby_rule_in_config() {
+        // Given
+        let rules = MoneyRules {
+            jacoby: true,
+            ..Default::default()
+        };
+        let money = MatchState::money(rules);
+        let mut game = money.new_game(3, 1).unwrap();
+        assert_eq!(game.money_rules(), Some(rules));
+        assert!(!money.config(&game).gammons_count);
+        // When
+        game.play(&pos!(x 24:2, 13:5, 8:2, 6:4, 5:2; o 19:5, 17:3, 12:5, 1:2))
+            .unwrap();
+        game.double().unwrap();
+        game.take().unwrap();
+        // Then
+        assert!(money.config(&game).gammons_count);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::game_state::{Cube, CubeOwner};
+use engine::probabilities::Probabilities;
+
+/// Optional rules for money sessions. They don't apply to match play.
+///
+/// The default is to play without any of them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoneyRules {
+    /// Gammons and backgammons only count if the cube has been turned.
+    pub jacoby: bool,
+    /// After a double the opponent may immediately redouble and keep the cube.
+    pub beavers: bool,
+    /// After a beaver the original doubler may immediately redouble again. Requires `beavers`.
+    pub raccoons: bool,
+    /// Maximum number of automatic doubles when both players roll the same opening number.
+    /// Zero means no automatic doubles.
+    pub automatic_doubles: u32,
+}
+
+impl MoneyRules {
+    /// `false` if the Jacoby rule is active and the cube is still centered.
+    ///
+    /// Automatic doubles don't count as turning the cube, as it stays centered.
+    pub fn gammons_count(&self, cube: &Cube) -> bool {
+        !self.jacoby || cube.owner() != CubeOwner::Centered
+    }
+
+    pub fn may_raccoon(&self) -> bool {
+        self.beavers && self.raccoons
+    }
+
+    /// Cubeless equity from the point of view of the player on turn, taking the Jacoby rule into account.
+    pub fn equity(&self, probabilities: &Probabilities, cube: &Cube) -> f32 {
+        if self.gammons_count(cube) {
+            probabilities.equity()
+        } else {
+            2.0 * probabilities.win() - 1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::Cube;
+    use crate::money_rules::MoneyRules;
+    use engine::probabilities::Probabilities;
+
+    fn probabilities() -> Probabilities {
+        Probabilities {
+            win_normal: 0.25,
+            win_gammon: 0.5,
+            lose_normal: 0.25,
+            lose_gammon: 0.0,
+        }
+    }
+
+    #[test]
+    fn equity_without_jacoby() {
+        let rules = MoneyRules::default();
+        assert_eq!(rules.equity(&probabilities(), &Cube::default()), 1.0);
+    }
+
+    #[test]
+    fn equity_with_jacoby_and_centered_cube() {
+        let rules = MoneyRules {
+            jacoby: true,
+            ..Default::default()
+        };
+        assert!(!rules.gammons_count(&Cube::default()));
+        assert_eq!(rules.equity(&probabilities(), &Cube::default()), 0.5);
+    }
+
+    #[test]
+    fn raccoons_need_beavers() {
+        let rules = MoneyRules {
+            raccoons: true,
+            ..Default::default()
+        };
+        assert!(!rules.may_raccoon());
+    }
+}
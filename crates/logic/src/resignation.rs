@@ -0,0 +1,111 @@
+use engine::probabilities::Probabilities;
+
+/// How much a player gives up when resigning, multiplied by the cube value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Resignation {
+    Normal,
+    Gammon,
+    Backgammon,
+}
+
+/// Difference in cubeless equity below which continuing to play and resigning are considered equal.
+const TOLERANCE: f32 = 0.001;
+
+impl Resignation {
+    const ALL: [Resignation; 3] = [
+        Resignation::Normal,
+        Resignation::Gammon,
+        Resignation::Backgammon,
+    ];
+
+    pub fn points(&self) -> f32 {
+        match self {
+            Resignation::Normal => 1.0,
+            Resignation::Gammon => 2.0,
+            Resignation::Backgammon => 3.0,
+        }
+    }
+
+    /// The resignation the player with these `probabilities` should offer, if any.
+    ///
+    /// A resignation is only offered if it costs nothing compared to playing on and if the opponent
+    /// should accept it. In practice that means the outcome of the game is already decided.
+    /// Backgammons are not part of [Probabilities], so `Backgammon` is never offered.
+    pub fn to_offer(probabilities: &Probabilities, gammons_count: bool) -> Option<Self> {
+        let expected_loss = -equity(probabilities, gammons_count);
+        Self::ALL.into_iter().find(|resignation| {
+            resignation.points() <= expected_loss + TOLERANCE
+                && resignation.points() >= expected_loss - TOLERANCE
+        })
+    }
+
+    /// `true` if the player with these `probabilities` should accept this resignation of the opponent.
+    ///
+    /// The resignation is accepted if it gives at least as many points as playing on is expected to win.
+    pub fn should_accept(&self, probabilities: &Probabilities, gammons_count: bool) -> bool {
+        self.points() >= equity(probabilities, gammons_count) - TOLERANCE
+    }
+}
+
+fn equity(probabilities: &Probabilities, gammons_count: bool) -> f32 {
+    if gammons_count {
+        probabilities.equity()
+    } else {
+        2.0 * probabilities.win() - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resignation::Resignation;
+    use engine::probabilities::Probabilities;
+
+    #[test]
+    fn offer_only_when_decided() {
+        let certain_loss = Probabilities {
+            lose_normal: 1.0,
+            ..Default::default()
+        };
+        let certain_gammon_loss = Probabilities {
+            lose_gammon: 1.0,
+            ..Default::default()
+        };
+        let likely_loss = Probabilities {
+            win_normal: 0.1,
+            lose_normal: 0.9,
+            ..Default::default()
+        };
+        let gammon_or_not = Probabilities {
+            lose_normal: 0.5,
+            lose_gammon: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            Resignation::to_offer(&certain_loss, true),
+            Some(Resignation::Normal)
+        );
+        assert_eq!(
+            Resignation::to_offer(&certain_gammon_loss, true),
+            Some(Resignation::Gammon)
+        );
+        assert_eq!(Resignation::to_offer(&likely_loss, true), None);
+        assert_eq!(Resignation::to_offer(&gammon_or_not, true), None);
+        assert_eq!(
+            Resignation::to_offer(&gammon_or_not, false),
+            Some(Resignation::Normal)
+        );
+    }
+
+    #[test]
+    fn accept_if_at_least_equity() {
+        let gammon_chances = Probabilities {
+            win_normal: 0.6,
+            win_gammon: 0.4,
+            ..Default::default()
+        };
+        assert!(!Resignation::Normal.should_accept(&gammon_chances, true));
+        assert!(Resignation::Normal.should_accept(&gammon_chances, false));
+        assert!(Resignation::Gammon.should_accept(&gammon_chances, true));
+        assert!(Resignation::Backgammon.should_accept(&gammon_chances, true));
+    }
+}
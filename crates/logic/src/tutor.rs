@@ -0,0 +1,124 @@
+use crate::bg_move::BgMove;
+use engine::dice::Dice;
+use engine::position::{Position, X_BAR};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A property of a move which a teaching frontend can use to explain why the move was chosen.
+///
+/// All pips are from the point of view of the player who moved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MoveFeature {
+    /// Number of the opponent's blots which are hit.
+    Hits(usize),
+    /// A point with at least two checkers which wasn't there before the move.
+    MakesPoint(usize),
+    /// The longest sequence of consecutive points gets longer, the value is the new length.
+    ExtendsPrime(usize),
+    /// Number of the opponent's 36 rolls which hit at least one blot. Only given if there are blots.
+    LeavesShots(usize),
+}
+
+impl fmt::Display for MoveFeature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveFeature::Hits(1) => write!(f, "hits a blot"),
+            MoveFeature::Hits(blots) => write!(f, "hits {} blots", blots),
+            MoveFeature::MakesPoint(pip) => write!(f, "makes the {} point", pip),
+            MoveFeature::ExtendsPrime(length) => {
+                write!(f, "extends the prime to {} points", length)
+            }
+            MoveFeature::LeavesShots(1) => write!(f, "leaves 1 shot"),
+            MoveFeature::LeavesShots(shots) => write!(f, "leaves {} shots", shots),
+        }
+    }
+}
+
+/// Derives all [MoveFeature]s of a move from `old` to `new`.
+///
+/// `new` has not switched sides, it's still from the point of view of the player who moved.
+pub fn explain(old: &Position, new: &Position, dice: &Dice) -> Vec<MoveFeature> {
+    let mut features = Vec::new();
+
+    let hits = BgMove::new(old, new, dice)
+        .details()
+        .iter()
+        .filter(|detail| detail.hit())
+        .count();
+    if hits > 0 {
+        features.push(MoveFeature::Hits(hits));
+    }
+
+    for pip in (1..X_BAR).rev() {
+        if new.pip(pip) >= 2 && old.pip(pip) < 2 {
+            features.push(MoveFeature::MakesPoint(pip));
+        }
+    }
+
+    let prime = new.longest_prime();
+    if prime > old.longest_prime() && prime >= 2 {
+        features.push(MoveFeature::ExtendsPrime(prime));
+    }
+
+    if (1..X_BAR).any(|pip| new.pip(pip) == 1) {
+        features.push(MoveFeature::LeavesShots(new.shots() as usize));
+    }
+
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tutor::{explain, MoveFeature};
+    use engine::dice::Dice;
+    use engine::pos;
+    use engine::position::O_BAR;
+
+    #[test]
+    fn makes_5_point_and_extends_prime() {
+        let old = pos!(x 8:3, 6:3, 4:2; o 24:2);
+        let new = pos!(x 8:2, 6:2, 5:2, 4:2; o 24:2);
+        let features = explain(&old, &new, &Dice::new(3, 1));
+        assert_eq!(
+            features,
+            vec![MoveFeature::MakesPoint(5), MoveFeature::ExtendsPrime(3)]
+        );
+    }
+
+    #[test]
+    fn leaves_shots() {
+        let old = pos!(x 13:3; o 1:1);
+        let new = pos!(x 13:2, 7:1; o 1:1);
+        let features = explain(&old, &new, &Dice::new(4, 2));
+        // 11 rolls with a 6, plus 5-1, 4-2, 3-3 and 2-2
+        assert_eq!(features, vec![MoveFeature::LeavesShots(17)]);
+    }
+
+    #[test]
+    fn hits_and_leaves_direct_shot() {
+        // 10/7*/4/1 10/7
+        let old = pos!(x 10:2; o 7:1, 24:2);
+        let new = pos!(x 7:1, 1:1; o 24:2, O_BAR:1);
+        let dice = Dice::new(3, 3);
+        assert!(old
+            .all_positions_after_moving(&dice)
+            .contains(&new.sides_switched()));
+        let features = explain(&old, &new, &dice);
+        // 11 rolls with a 1 hit directly, 5-2 and 4-3 hit the blot on the 7 point.
+        assert_eq!(
+            features,
+            vec![MoveFeature::Hits(1), MoveFeature::LeavesShots(15)]
+        );
+        assert_eq!(features[0].to_string(), "hits a blot");
+    }
+
+    #[test]
+    fn no_shots_without_blots() {
+        let old = pos!(x 8:2, 6:2; o 24:2);
+        let new = pos!(x 6:4; o 24:2);
+        let features = explain(&old, &new, &Dice::new(2, 2));
+        assert_eq!(features, vec![]);
+        assert_eq!(MoveFeature::LeavesShots(11).to_string(), "leaves 11 shots");
+        assert_eq!(MoveFeature::MakesPoint(5).to_string(), "makes the 5 point");
+    }
+}
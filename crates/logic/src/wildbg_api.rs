@@ -1,7 +1,11 @@
+use crate::analysis::{analyze_move, temperature_map, MoveAnalysis, MoveRecord, TemperatureMap};
 use crate::bg_move::BgMove;
+use crate::resignation::Resignation;
+use crate::tutor::{explain, MoveFeature};
 use engine::composite::CompositeEvaluator;
 use engine::dice::Dice;
 use engine::evaluator::Evaluator;
+use engine::opening_book::OpeningBookEvaluator;
 use engine::position::Position;
 use engine::probabilities::Probabilities;
 
@@ -10,33 +14,141 @@ pub struct WildbgConfig {
     /// Index 0 is for the player on turn, index 1 for the opponent.
     /// Zero indicates money game.
     pub away: Option<(u32, u32)>,
+    /// `false` if gammons and backgammons don't count, for example because of the Jacoby rule.
+    pub gammons_count: bool,
+}
+
+impl WildbgConfig {
+    /// Function used to compare positions, bigger values are better.
+    pub fn value(&self) -> fn(&Probabilities) -> f32 {
+        if self.away == Some((1, 1)) || !self.gammons_count {
+            |p| p.win()
+        } else {
+            // For now assume money game if not 1 pointer
+            |p| p.equity()
+        }
+    }
+}
+
+/// One of several candidate moves, as returned by [WildbgApi::ranked_moves].
+#[derive(Debug, PartialEq)]
+pub struct RankedMove {
+    pub bg_move: BgMove,
+    /// From the point of view of the player who moves.
+    pub probabilities: Probabilities,
+    /// Value according to the [WildbgConfig], for money game this is the cubeless equity.
+    pub equity: f32,
+    /// Difference to the equity of the best move. Zero for the best move, negative for all others.
+    pub equity_difference: f32,
 }
 
 pub struct WildbgApi<T: Evaluator> {
     evaluator: T,
 }
 
-impl WildbgApi<CompositeEvaluator> {
+impl WildbgApi<OpeningBookEvaluator<CompositeEvaluator>> {
+    /// Plays the first move of a game from the opening book, everything else with the neural nets.
     pub fn try_default() -> Result<Self, String> {
-        CompositeEvaluator::try_default().map(|evaluator| Self { evaluator })
+        CompositeEvaluator::try_default().map(|evaluator| Self {
+            evaluator: OpeningBookEvaluator::new(evaluator),
+        })
     }
 }
 
 impl<T: Evaluator> WildbgApi<T> {
+    pub fn new(evaluator: T) -> Self {
+        Self { evaluator }
+    }
+
     pub fn probabilities(&self, position: &Position) -> Probabilities {
         self.evaluator.eval(position)
     }
 
+    /// The resignation the player on turn should offer in `position`, if any.
+    pub fn resignation(&self, position: &Position, config: &WildbgConfig) -> Option<Resignation> {
+        let probabilities = self.evaluator.eval(position);
+        Resignation::to_offer(&probabilities, config.gammons_count)
+    }
+
+    /// `true` if the player on turn should accept a `resignation` offered by the opponent.
+    pub fn accept_resignation(
+        &self,
+        position: &Position,
+        resignation: Resignation,
+        config: &WildbgConfig,
+    ) -> bool {
+        let probabilities = self.evaluator.eval(position);
+        resignation.should_accept(&probabilities, config.gammons_count)
+    }
+
     pub fn best_move(&self, position: &Position, dice: &Dice, config: &WildbgConfig) -> BgMove {
-        let value: fn(&Probabilities) -> f32 = if config.away == Some((1, 1)) {
-            |p| p.win()
-        } else {
-            // For now assume money game if not 1 pointer
-            |p| p.equity()
-        };
-        let new_position = self.evaluator.best_position(position, dice, value);
+        let new_position = self.evaluator.best_position(position, dice, config.value());
         BgMove::new(position, &new_position.sides_switched(), dice)
     }
+
+    /// Best move together with the features explaining why it's good, see [crate::tutor].
+    pub fn explained_best_move(
+        &self,
+        position: &Position,
+        dice: &Dice,
+        config: &WildbgConfig,
+    ) -> (BgMove, Vec<MoveFeature>) {
+        let new_position = self
+            .evaluator
+            .best_position(position, dice, config.value())
+            .sides_switched();
+        let features = explain(position, &new_position, dice);
+        (BgMove::new(position, &new_position, dice), features)
+    }
+
+    /// Up to `max_moves` legal moves, sorted by equity. The best move is first.
+    ///
+    /// If no checker can be moved, a single move without details is returned.
+    pub fn ranked_moves(
+        &self,
+        position: &Position,
+        dice: &Dice,
+        config: &WildbgConfig,
+        max_moves: usize,
+    ) -> Vec<RankedMove> {
+        let value = config.value();
+        let mut candidates: Vec<(Position, Probabilities, f32)> = self
+            .evaluator
+            .eval_batch(position.all_positions_after_moving(dice))
+            .into_iter()
+            .map(|(new_position, probabilities)| {
+                let probabilities = probabilities.switch_sides();
+                let equity = value(&probabilities);
+                (new_position.sides_switched(), probabilities, equity)
+            })
+            .collect();
+        candidates.sort_unstable_by(|a, b| b.2.total_cmp(&a.2));
+        candidates.truncate(max_moves);
+        let best_equity = candidates.first().map_or(0.0, |candidate| candidate.2);
+        candidates
+            .into_iter()
+            .map(|(new_position, probabilities, equity)| RankedMove {
+                bg_move: BgMove::new(position, &new_position, dice),
+                probabilities,
+                equity,
+                equity_difference: equity - best_equity,
+            })
+            .collect()
+    }
+
+    /// Value of the best move for each roll of the player on turn.
+    pub fn temperature_map(&self, position: &Position, config: &WildbgConfig) -> TemperatureMap {
+        temperature_map(&self.evaluator, position, config.value())
+    }
+
+    /// Equity loss of a move that has been played, compared to the best move.
+    pub fn analyze_move(
+        &self,
+        record: &MoveRecord,
+        config: &WildbgConfig,
+    ) -> Result<MoveAnalysis, &'static str> {
+        analyze_move(&self.evaluator, record, config.value())
+    }
 }
 
 #[cfg(test)]
@@ -85,15 +197,57 @@ mod tests {
         let evaluator = EvaluatorFake {};
         let api = WildbgApi { evaluator };
         // When
-        let config = WildbgConfig { away: Some((1, 1)) };
+        let config = WildbgConfig {
+            away: Some((1, 1)),
+            gammons_count: true,
+        };
         let bg_move = api.best_move(&given_pos, &Dice::new(4, 2), &config);
         // Then
         let expected_move = BgMove {
-            details: vec![MoveDetail { from: 7, to: 5 }, MoveDetail { from: 5, to: 1 }],
+            details: vec![
+                MoveDetail {
+                    from: 7,
+                    to: 5,
+                    hit: false,
+                },
+                MoveDetail {
+                    from: 5,
+                    to: 1,
+                    hit: false,
+                },
+            ],
         };
         assert_eq!(bg_move, expected_move);
     }
 
+    #[test]
+    fn ranked_moves_money_game() {
+        // Given
+        let given_pos = pos!(x 7:2; o 20:2);
+        let api = WildbgApi {
+            evaluator: EvaluatorFake {},
+        };
+        let config = WildbgConfig {
+            away: None,
+            gammons_count: true,
+        };
+        // When
+        let ranked = api.ranked_moves(&given_pos, &Dice::new(4, 2), &config, 2);
+        // Then
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(
+            ranked[0].bg_move,
+            api.best_move(&given_pos, &Dice::new(4, 2), &config)
+        );
+        assert_eq!(ranked[0].equity_difference, 0.0);
+        assert!(ranked[1].equity_difference < 0.0);
+        assert_eq!(
+            ranked[1].equity - ranked[0].equity,
+            ranked[1].equity_difference
+        );
+        assert_eq!(ranked[0].probabilities.lose_normal, 0.5);
+    }
+
     #[test]
     fn best_move_money_game() {
         // Given
@@ -101,11 +255,25 @@ mod tests {
         let evaluator = EvaluatorFake {};
         let api = WildbgApi { evaluator };
         // When
-        let config = WildbgConfig { away: None };
+        let config = WildbgConfig {
+            away: None,
+            gammons_count: true,
+        };
         let bg_move = api.best_move(&given_pos, &Dice::new(4, 2), &config);
         // Then
         let expected_move = BgMove {
-            details: vec![MoveDetail { from: 7, to: 3 }, MoveDetail { from: 7, to: 5 }],
+            details: vec![
+                MoveDetail {
+                    from: 7,
+                    to: 3,
+                    hit: false,
+                },
+                MoveDetail {
+                    from: 7,
+                    to: 5,
+                    hit: false,
+                },
+            ],
         };
         assert_eq!(bg_move, expected_move);
     }
@@ -1,10 +1,36 @@
-use crate::web_api::{DiceParams, EvalResponse, MoveResponse, PipParams, WebApi};
-use axum::extract::{Query, State};
-use axum::http::StatusCode;
+use crate::cache::CacheStats;
+use crate::metrics::Metrics;
+use crate::rate_limit::RateLimiter;
+use crate::session::{ClientMessage, ServerMessage, Session};
+use crate::web_api::{
+    get_board, BatchRequest, BatchResponse, BenchmarkResponse, BoardParams, CubeParams,
+    CubeResponse, DiceParams, EvalResponse, FromContactNet, ModelParams, MoveResponse, PipParams,
+    RolloutParams, ValidRollout, WebApi, DEFAULT_MAX_PENDING,
+};
+use axum::body::Bytes;
+use axum::extract::multipart::{Multipart, MultipartRejection};
+use axum::extract::ws::rejection::WebSocketUpgradeRejection;
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, DefaultBodyLimit, MatchedPath, Query, Request, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
 use axum::{routing::get, Json, Router};
+use coach::match_analysis::MatchAnalysis;
+use engine::dice_gen::FastrandDice;
 use engine::evaluator::Evaluator;
 use serde::Serialize;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subtle::{Choice, ConstantTimeEq};
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
 use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
@@ -12,18 +38,168 @@ use utoipa_swagger_ui::SwaggerUi;
 
 type DynWebApi<T> = Arc<Option<WebApi<T>>>;
 
-pub fn router<T: Evaluator + Send + Sync + 'static>(web_api: DynWebApi<T>) -> Router {
+/// Evaluations are CPU bound and would block the async runtime, so they run on tokio's blocking
+/// thread pool. `pending` limits how many of them may run or wait at the same time.
+struct ApiState<T: Evaluator> {
+    web_api: DynWebApi<T>,
+    pending: Arc<Semaphore>,
+    max_pending: usize,
+    metrics: Arc<Metrics>,
+}
+
+// Derived `Clone` would require `T: Clone`.
+impl<T: Evaluator> Clone for ApiState<T> {
+    fn clone(&self) -> Self {
+        Self {
+            web_api: self.web_api.clone(),
+            pending: self.pending.clone(),
+            max_pending: self.max_pending,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+impl<T: Evaluator + Send + Sync + 'static> ApiState<T> {
+    fn new(web_api: DynWebApi<T>) -> Self {
+        let max_pending = web_api
+            .as_ref()
+            .as_ref()
+            .map_or(DEFAULT_MAX_PENDING, WebApi::max_pending);
+        Self {
+            web_api,
+            pending: Arc::new(Semaphore::new(max_pending)),
+            max_pending,
+            metrics: Arc::new(Metrics::default()),
+        }
+    }
+
+    /// Runs `evaluation` without blocking the async runtime.
+    ///
+    /// If too many evaluations are already pending, the request is rejected right away instead of
+    /// piling up, so that the server stays responsive under load.
+    async fn evaluate<R: Send + 'static>(
+        &self,
+        model: ModelParams,
+        evaluation: impl FnOnce(&WebApi<T>) -> R + Send + 'static,
+    ) -> Result<R, (StatusCode, Json<ErrorMessage>)> {
+        self.check_model(&model)?;
+        let permit = self.permit()?;
+        let web_api = self.web_api.clone();
+        let metrics = self.metrics.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let result = web_api
+                .as_ref()
+                .as_ref()
+                .map(|web_api| evaluation(selected_model(web_api, &model)));
+            metrics.record_evaluation();
+            result
+        })
+        .await;
+        match result {
+            Ok(Some(result)) => Ok(result),
+            Ok(None) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::json("Neural net could not be constructed."),
+            )),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::json("Evaluation failed."),
+            )),
+        }
+    }
+
+    /// Like [ApiState::evaluate], but returns right away. `evaluation` reports its results itself,
+    /// for example through a channel.
+    fn spawn_evaluation(
+        &self,
+        model: ModelParams,
+        evaluation: impl FnOnce(&WebApi<T>) + Send + 'static,
+    ) -> Result<(), (StatusCode, Json<ErrorMessage>)> {
+        if self.web_api.is_none() {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::json("Neural net could not be constructed."),
+            ));
+        }
+        self.check_model(&model)?;
+        let permit = self.permit()?;
+        let web_api = self.web_api.clone();
+        let metrics = self.metrics.clone();
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            if let Some(web_api) = web_api.as_ref() {
+                evaluation(selected_model(web_api, &model));
+            }
+            metrics.record_evaluation();
+        });
+        Ok(())
+    }
+
+    /// Rejects unknown models before an evaluation is started.
+    fn check_model(&self, model: &ModelParams) -> Result<(), (StatusCode, Json<ErrorMessage>)> {
+        match self.web_api.as_ref() {
+            Some(web_api) => web_api
+                .model(model.model.as_deref())
+                .map(|_| ())
+                .map_err(|message| (StatusCode::BAD_REQUEST, ErrorMessage::json(&message))),
+            None => Ok(()),
+        }
+    }
+
+    fn permit(&self) -> Result<OwnedSemaphorePermit, (StatusCode, Json<ErrorMessage>)> {
+        self.pending.clone().try_acquire_owned().map_err(|_| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorMessage::json("Too many pending requests, please try again later."),
+            )
+        })
+    }
+}
+
+/// Models are fixed when the server starts, so a model that has been checked can't disappear.
+fn selected_model<'a, T: Evaluator>(web_api: &'a WebApi<T>, model: &ModelParams) -> &'a WebApi<T> {
+    web_api
+        .model(model.model.as_deref())
+        .expect("The model has been checked before.")
+}
+
+/// OpenAPI specification of all public endpoints, served at `/openapi.json`.
+///
+/// Client developers can use it to generate typed bindings, see also `server --openapi`.
+pub fn openapi() -> utoipa::openapi::OpenApi {
     #[derive(OpenApi)]
     #[openapi(
-        paths(crate::axum::get_eval, crate::axum::get_move,),
+        paths(
+            crate::axum::get_eval,
+            crate::axum::post_eval_batch,
+            crate::axum::get_move,
+            crate::axum::get_cube,
+            crate::axum::get_board_svg,
+            crate::axum::get_rollout,
+            crate::axum::post_analysis,
+        ),
         components(schemas(
+            coach::match_analysis::Decision,
+            coach::match_analysis::DecisionKind,
+            coach::match_analysis::MatchAnalysis,
+            coach::match_analysis::PlayerSummary,
+            coach::match_analysis::Roll,
             logic::bg_move::MoveDetail,
             crate::axum::ErrorMessage,
             logic::cube::CubeInfo,
+            crate::web_api::BatchEvaluation,
+            crate::web_api::BatchRequest,
+            crate::web_api::BatchResponse,
+            crate::web_api::CubeEquities,
+            crate::web_api::CubeResponse,
+            logic::cube::DoublingWindow,
+            logic::cube::WindowPosition,
             crate::web_api::EvalResponse,
             crate::web_api::MoveInfo,
             crate::web_api::MoveResponse,
             crate::web_api::ProbabilitiesView,
+            crate::web_api::RolloutProgress,
         )),
         info(
             title = "wildbg",
@@ -31,11 +207,247 @@ pub fn router<T: Evaluator + Send + Sync + 'static>(web_api: DynWebApi<T>) -> Ro
         )
     )]
     struct ApiDoc;
+    ApiDoc::openapi()
+}
+
+pub fn router<T: Evaluator + Send + Sync + 'static>(web_api: DynWebApi<T>) -> Router {
+    let state = ApiState::new(web_api);
     Router::new()
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", openapi()))
+        // The former path of the specification.
+        .route("/api-docs/openapi.json", get(|| async { Json(openapi()) }))
         .route("/eval", get(get_eval))
+        .route("/eval/batch", post(post_eval_batch))
         .route("/move", get(get_move))
-        .with_state(web_api)
+        .route("/cube", get(get_cube))
+        .route("/board.svg", get(get_board_svg))
+        .route("/rollout", get(get_rollout))
+        .route("/analysis", post(post_analysis))
+        .route("/play", get(get_play))
+        .route("/metrics", get(get_metrics))
+        .route("/healthz", get(get_healthz))
+        .route("/readyz", get(get_readyz))
+        .layer(middleware::map_response(add_retry_after))
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            record_metrics,
+        ))
+        .with_state(state)
+}
+
+/// Counts responses and their latency per route. Requests to unknown paths are counted as `other`.
+async fn record_metrics(
+    State(metrics): State<Arc<Metrics>>,
+    path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let path = path.as_ref().map_or("other", MatchedPath::as_str);
+    metrics.record_request(path, response.status().as_u16(), start.elapsed());
+    response
+}
+
+/// Liveness probe, the server answers requests.
+async fn get_healthz() -> &'static str {
+    "ok"
+}
+
+/// Readiness probe, the neural nets are loaded and warmed up, see [WebApi::warm_up].
+async fn get_readyz<T: Evaluator + Send + Sync + 'static>(
+    State(state): State<ApiState<T>>,
+) -> Result<&'static str, (StatusCode, Json<ErrorMessage>)> {
+    match state.web_api.as_ref() {
+        None => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorMessage::json("Neural net could not be constructed."),
+        )),
+        Some(web_api) if !web_api.is_ready() => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorMessage::json("The neural nets are still warming up."),
+        )),
+        Some(_) => Ok("ready"),
+    }
+}
+
+/// Metrics for Prometheus, not part of the public API documentation.
+async fn get_metrics<T: Evaluator + Send + Sync + 'static>(
+    State(state): State<ApiState<T>>,
+) -> impl IntoResponse {
+    let pending = state.max_pending - state.pending.available_permits();
+    let cache = state
+        .web_api
+        .as_ref()
+        .as_ref()
+        .map_or(CacheStats::default(), WebApi::cache_stats);
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(pending, cache),
+    )
+}
+
+/// Overloaded evaluation workers usually recover within a second.
+async fn add_retry_after(mut response: Response) -> Response {
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+        response
+            .headers_mut()
+            .entry(RETRY_AFTER)
+            .or_insert(HeaderValue::from_static("1"));
+    }
+    response
+}
+
+/// Uploaded neural nets may be bigger than the default body limit of axum.
+const MAX_NEURAL_NET_SIZE: usize = 64 * 1024 * 1024;
+
+struct AdminState<T: Evaluator> {
+    web_api: DynWebApi<T>,
+    admin_token: Arc<String>,
+}
+
+// Derived `Clone` would require `T: Clone`.
+impl<T: Evaluator> Clone for AdminState<T> {
+    fn clone(&self) -> Self {
+        Self {
+            web_api: self.web_api.clone(),
+            admin_token: self.admin_token.clone(),
+        }
+    }
+}
+
+/// Like [router], but additionally with admin endpoints.
+///
+/// Requests to admin endpoints need the header `Authorization: Bearer <admin_token>`.
+/// They are not part of the public API documentation.
+pub fn router_with_admin<T: FromContactNet + Send + Sync + 'static>(
+    web_api: DynWebApi<T>,
+    admin_token: String,
+) -> Router {
+    let admin_state = AdminState {
+        web_api: web_api.clone(),
+        admin_token: Arc::new(admin_token),
+    };
+    let admin = Router::new()
+        .route("/admin/benchmark", post(post_benchmark))
+        .route("/admin/reload", post(post_reload))
+        .layer(DefaultBodyLimit::max(MAX_NEURAL_NET_SIZE))
+        .with_state(admin_state);
+    router(web_api).merge(admin)
+}
+
+/// Probes of orchestrators like Kubernetes, they neither send API keys nor count for rate limits.
+const PROBE_PATHS: [&str; 2] = ["/healthz", "/readyz"];
+
+/// Paths which don't need an API key: the documentation and the admin endpoints, which have their
+/// own token.
+const PATHS_WITHOUT_API_KEY: [&str; 4] = ["/swagger-ui", "/openapi.json", "/api-docs/", "/admin/"];
+
+/// Returns 401 for all requests without one of `api_keys`, except for the documentation.
+///
+/// The key is given in the header `X-API-Key`. Browsers can't set headers for WebSocket
+/// connections, so the query parameter `api_key` is accepted as well.
+///
+/// Apply this after [limit_rate], so that the rate limit only sees validated keys.
+pub fn require_api_keys(router: Router, api_keys: Vec<String>) -> Router {
+    let api_keys: Arc<HashSet<String>> = Arc::new(api_keys.into_iter().collect());
+    router.layer(middleware::from_fn_with_state(api_keys, check_api_key))
+}
+
+/// An API key which was checked by [require_api_keys].
+#[derive(Clone)]
+struct ValidApiKey(String);
+
+async fn check_api_key(
+    State(api_keys): State<Arc<HashSet<String>>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if PATHS_WITHOUT_API_KEY
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+        || PROBE_PATHS.contains(&path)
+    {
+        next.run(request).await
+    } else if let Some(key) = api_key(&request).filter(|key| is_valid_api_key(&api_keys, key)) {
+        let key = ValidApiKey(key.to_string());
+        request.extensions_mut().insert(key);
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            ErrorMessage::json("Missing or wrong API key."),
+        )
+            .into_response()
+    }
+}
+
+/// Compares `key` with all `api_keys` in constant time, like [check_admin_token] does with the
+/// admin token. So the response time doesn't reveal how much of a key is right.
+fn is_valid_api_key(api_keys: &HashSet<String>, key: &str) -> bool {
+    let found = api_keys.iter().fold(Choice::from(0), |found, valid| {
+        found | key.as_bytes().ct_eq(valid.as_bytes())
+    });
+    bool::from(found)
+}
+
+/// The API key from the header `X-API-Key` or the query parameter `api_key`.
+fn api_key(request: &Request) -> Option<&str> {
+    let from_query = || {
+        request
+            .uri()
+            .query()?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("api_key="))
+    };
+    request
+        .headers()
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(from_query)
+}
+
+/// Returns 429 with a `Retry-After` header if a client sends more than `requests_per_minute`.
+///
+/// Clients are identified by their API key if it was validated by [require_api_keys], otherwise by
+/// their IP address. The latter is only known if the router is served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`.
+pub fn limit_rate(router: Router, requests_per_minute: u32) -> Router {
+    let limiter = Arc::new(RateLimiter::new(
+        requests_per_minute,
+        Duration::from_secs(60),
+    ));
+    router.layer(middleware::from_fn_with_state(limiter, check_rate))
+}
+
+async fn check_rate(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if PROBE_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+    let client = match request.extensions().get::<ValidApiKey>() {
+        Some(ValidApiKey(key)) => format!("key {key}"),
+        None => request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map_or("unknown".to_string(), |info| format!("ip {}", info.0.ip())),
+    };
+    match limiter.check(&client, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, seconds.to_string())],
+                ErrorMessage::json("Too many requests, please try again later."),
+            )
+                .into_response()
+        }
+    }
 }
 
 /// Returned as body along a 4xx or 5xx HTTP status code.
@@ -69,6 +481,7 @@ impl ErrorMessage {
     tag = "endpoints",
     params(
         PipParams,
+        ModelParams,
     ),
     responses(
         (status = 200, description = "Successful request. Response includes game outcome probabilities and cube decisions.", body = EvalResponse,
@@ -89,22 +502,75 @@ impl ErrorMessage {
         ),
         (status = 500, description = "Server error", body = ErrorMessage,
             example = json!({"message": "Neural net could not be constructed."})
+        ),
+        (status = 503, description = "Server is busy, too many requests are pending", body = ErrorMessage,
+            example = json!({"message": "Too many pending requests, please try again later."})
         )
     )
 )]
-async fn get_eval<T: Evaluator>(
+async fn get_eval<T: Evaluator + Send + Sync + 'static>(
     Query(pips): Query<PipParams>,
-    State(web_api): State<DynWebApi<T>>,
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
 ) -> Result<Json<EvalResponse>, (StatusCode, Json<ErrorMessage>)> {
-    match web_api.as_ref() {
-        None => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            ErrorMessage::json("Neural net could not be constructed."),
-        )),
-        Some(web_api) => match web_api.get_eval(pips) {
-            Err((status_code, message)) => Err((status_code, ErrorMessage::json(message.as_str()))),
-            Ok(eval_response) => Ok(Json(eval_response)),
-        },
+    match state
+        .evaluate(model, |web_api| web_api.get_eval(pips))
+        .await?
+    {
+        Err((status_code, message)) => Err((status_code, ErrorMessage::json(message.as_str()))),
+        Ok(eval_response) => Ok(Json(eval_response)),
+    }
+}
+
+/// Evaluation of many positions.
+/// Returns probabilities and cubeless equities for a list of GnuBG position IDs.
+///
+/// All positions are evaluated in one batch on the server, which is much faster than calling `/eval`
+/// for each of them. At most 1000 positions can be sent in one request.
+#[utoipa::path(
+    post,
+    path = "/eval/batch",
+    tag = "endpoints",
+    params(
+        ModelParams,
+    ),
+    request_body = BatchRequest,
+    responses(
+        (status = 200, description = "Successful request. Response includes one evaluation per position ID.", body = BatchResponse,
+            example = json!({
+                "evaluations": [{
+                    "positionId": "4HPwATDgc/ABMA",
+                    "probabilities": {
+                        "win": 0.5254,
+                        "winG": 0.1487,
+                        "loseG": 0.1324,
+                    },
+                    "equity": 0.0671
+                }]
+            })
+        ),
+        (status = 400, description = "Client error, a position ID is illegal or there are too many", body = ErrorMessage,
+            example = json!({"message": "'4HPwATDgc/AB': A GnuBG position ID consists of 14 Base64 characters."})
+        ),
+        (status = 500, description = "Server error", body = ErrorMessage,
+            example = json!({"message": "Neural net could not be constructed."})
+        ),
+        (status = 503, description = "Server is busy, too many requests are pending", body = ErrorMessage,
+            example = json!({"message": "Too many pending requests, please try again later."})
+        )
+    )
+)]
+async fn post_eval_batch<T: Evaluator + Send + Sync + 'static>(
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Json<BatchResponse>, (StatusCode, Json<ErrorMessage>)> {
+    match state
+        .evaluate(model, |web_api| web_api.post_eval_batch(request))
+        .await?
+    {
+        Err(message) => Err((StatusCode::BAD_REQUEST, ErrorMessage::json(&message))),
+        Ok(batch_response) => Ok(Json(batch_response)),
     }
 }
 
@@ -126,81 +592,430 @@ async fn get_eval<T: Evaluator>(
     params(
         DiceParams,
         PipParams,
+        ModelParams,
     ),
     responses(
         (status = 200, description = "Successful request. Response includes the best move and other data.", body = MoveResponse,
-            example = json!({"moves": [{"play": [{"from": 5, "to": 2}, {"from": 2, "to": 0}], "probabilities": {"win": 0.14432532, "winG": 0.0000012345678, "loseG": 0.26282439}},{"play": [{"from": 5, "to": 2}, {"from": 5, "to": 3}], "probabilities": {"win": 0.74432532, "winG": 0.223456782, "loseG": 0.012345678}}]})
+            example = json!({"moves": [{"play": [{"from": 5, "to": 2, "hit": false}, {"from": 2, "to": 0, "hit": false}], "probabilities": {"win": 0.14432532, "winG": 0.0000012345678, "loseG": 0.26282439}},{"play": [{"from": 5, "to": 2, "hit": false}, {"from": 5, "to": 3, "hit": false}], "probabilities": {"win": 0.74432532, "winG": 0.223456782, "loseG": 0.012345678}}]})
         ),
         (status = 400, description = "Client error, parameters don't represent legal position/dice", body = ErrorMessage,
             example = json!({"message": "Player x has more than 15 checkers on the board."})
         ),
         (status = 500, description = "Server error", body = ErrorMessage,
             example = json!({"message": "Neural net could not be constructed."})
+        ),
+        (status = 503, description = "Server is busy, too many requests are pending", body = ErrorMessage,
+            example = json!({"message": "Too many pending requests, please try again later."})
         )
     )
 )]
-async fn get_move<T: Evaluator>(
+async fn get_move<T: Evaluator + Send + Sync + 'static>(
     Query(dice): Query<DiceParams>,
     Query(pips): Query<PipParams>,
-    State(web_api): State<DynWebApi<T>>,
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
 ) -> Result<Json<MoveResponse>, (StatusCode, Json<ErrorMessage>)> {
-    match web_api.as_ref() {
+    match state
+        .evaluate(model, |web_api| web_api.get_move(pips, dice))
+        .await?
+    {
+        Err(message) => Err((StatusCode::BAD_REQUEST, ErrorMessage::json(message))),
+        Ok(move_response) => Ok(Json(move_response)),
+    }
+}
+
+/// Cube decisions for a position.
+/// Returns whether `x` should double before rolling and whether `o` should take, together with the
+/// doubling window: the winning chances from which `x` should double, `o` should pass and `x` is
+/// too good to double.
+///
+/// The position is given in the same way as for `/eval`. Without score parameters a money game is
+/// assumed and the cubeful equities are derived from the cubeless evaluation with Janowski's model.
+/// At a match score the doubling window of the match equity table decides, and the equities are
+/// normalized match equities. `x` never doubles a cube owned by `o`.
+#[utoipa::path(
+    get,
+    path = "/cube",
+    tag = "endpoints",
+    params(
+        CubeParams,
+        PipParams,
+        ModelParams,
+    ),
+    responses(
+        (status = 200, description = "Successful request. Response includes the cube decisions and equities.", body = CubeResponse,
+            example = json!({
+                "double": true,
+                "take": true,
+                "equities": {
+                    "noDouble": 0.45,
+                    "doubleTake": 0.9,
+                    "doublePass": 1.0
+                },
+                "window": {
+                    "doublingPoint": 0.68,
+                    "cashPoint": 0.78,
+                    "tooGoodPoint": 0.92,
+                    "win": 0.72
+                },
+                "windowPosition": "DoubleTake"
+            })
+        ),
+        (status = 400, description = "Client error, parameters don't represent legal position/cube/score", body = ErrorMessage,
+            example = json!({"message": "The cube owner must be 'x', 'o' or 'centered'."})
+        ),
+        (status = 500, description = "Server error", body = ErrorMessage,
+            example = json!({"message": "Neural net could not be constructed."})
+        ),
+        (status = 503, description = "Server is busy, too many requests are pending", body = ErrorMessage,
+            example = json!({"message": "Too many pending requests, please try again later."})
+        )
+    )
+)]
+async fn get_cube<T: Evaluator + Send + Sync + 'static>(
+    Query(cube): Query<CubeParams>,
+    Query(pips): Query<PipParams>,
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
+) -> Result<Json<CubeResponse>, (StatusCode, Json<ErrorMessage>)> {
+    match state
+        .evaluate(model, |web_api| web_api.get_cube(pips, cube))
+        .await?
+    {
+        Err((status_code, message)) => Err((status_code, ErrorMessage::json(message.as_str()))),
+        Ok(cube_response) => Ok(Json(cube_response)),
+    }
+}
+
+/// Image of a position.
+/// Returns an SVG image of the board with pip counts and optionally dice and cube.
+///
+/// The position is given in the same way as for `/eval`. `x` (you) moves from the top right to the
+/// bottom right, the dice are drawn as rolled by `x`. The image can be embedded or shared as is.
+///
+/// As example in the API documentation the starting position with dice 3 and 1 is given.
+#[utoipa::path(
+    get,
+    path = "/board.svg",
+    tag = "endpoints",
+    params(
+        BoardParams,
+        PipParams,
+    ),
+    responses(
+        (status = 200, description = "Successful request. The body is an SVG image.", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "Client error, parameters don't represent legal position/dice/cube", body = ErrorMessage,
+            example = json!({"message": "The cube value must be a power of 2."})
+        )
+    )
+)]
+async fn get_board_svg(
+    Query(board): Query<BoardParams>,
+    Query(pips): Query<PipParams>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorMessage>)> {
+    match get_board(pips, board) {
+        Err(message) => Err((StatusCode::BAD_REQUEST, ErrorMessage::json(message))),
+        Ok(svg) => Ok(([(CONTENT_TYPE, "image/svg+xml")], svg)),
+    }
+}
+
+/// Rollout of a position with progress.
+/// Streams the results so far as Server-Sent Events after each group of 1296 games.
+///
+/// The position is given in the same way as for `/eval`. Each event has the type `progress` and
+/// the data is JSON like in the example below. The stream ends once `games` equals `totalGames`.
+#[utoipa::path(
+    get,
+    path = "/rollout",
+    tag = "endpoints",
+    params(
+        RolloutParams,
+        PipParams,
+        ModelParams,
+    ),
+    responses(
+        (status = 200, description = "Successful request. The body is a stream of Server-Sent Events.", content_type = "text/event-stream", body = RolloutProgress,
+            example = json!({
+                "games": 1296,
+                "totalGames": 2592,
+                "probabilities": {
+                    "win": 0.52,
+                    "winG": 0.14,
+                    "loseG": 0.12
+                },
+                "equity": 0.06,
+                "equityError": 0.03
+            })
+        ),
+        (status = 400, description = "Client error, parameters don't represent a legal position or number of games", body = ErrorMessage,
+            example = json!({"message": "The game is already over."})
+        ),
+        (status = 503, description = "Too many pending requests", body = ErrorMessage,
+            example = json!({"message": "Too many pending requests, please try again later."})
+        ),
+    )
+)]
+async fn get_rollout<T: Evaluator + Send + Sync + 'static>(
+    Query(rollout): Query<RolloutParams>,
+    Query(pips): Query<PipParams>,
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, (StatusCode, Json<ErrorMessage>)> {
+    let rollout = ValidRollout::try_new(pips, rollout)
+        .map_err(|message| (StatusCode::BAD_REQUEST, ErrorMessage::json(&message)))?;
+    let (sender, receiver) = mpsc::channel(1);
+    let metrics = state.metrics.clone();
+    // If the client disconnects, sending fails and the rollout stops.
+    state.spawn_evaluation(model, move |web_api| {
+        metrics.rollout_started();
+        web_api.rollout(&rollout, |progress| sender.blocking_send(progress).is_ok());
+        metrics.rollout_finished();
+    })?;
+    let events = ReceiverStream::new(receiver)
+        .map(|progress| Event::default().event("progress").json_data(progress));
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Analysis of a whole match.
+/// Upload a `.mat` or GNUBG `.sgf` file as field `file` of a `multipart/form-data` body.
+///
+/// Every checker play and cube decision is analyzed, the response lists the equity loss of each
+/// decision, the luck of each roll and totals for both players. This takes some seconds.
+#[utoipa::path(
+    post,
+    path = "/analysis",
+    tag = "endpoints",
+    params(
+        ModelParams,
+    ),
+    request_body(content = String, content_type = "multipart/form-data", description = "The match file in the field `file`."),
+    responses(
+        (status = 200, description = "Successful request. Response includes the analysis of all decisions.", body = MatchAnalysis),
+        (status = 400, description = "Client error, the body or the match file can't be read", body = ErrorMessage,
+            example = json!({"message": "The multipart body contains no field with this name."})
+        ),
+    )
+)]
+async fn post_analysis<T: Evaluator + Send + Sync + 'static>(
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<MatchAnalysis>, (StatusCode, Json<ErrorMessage>)> {
+    let bad_request = |message: &str| (StatusCode::BAD_REQUEST, ErrorMessage::json(message));
+    let mut multipart = multipart.map_err(|rejection| bad_request(&rejection.body_text()))?;
+    let (file_name, content) = loop {
+        let field = multipart
+            .next_field()
+            .await
+            .map_err(|error| bad_request(&error.body_text()))?
+            .ok_or_else(|| bad_request("The multipart body contains no field with this name."))?;
+        if field.name() == Some("file") {
+            let file_name = field.file_name().map(str::to_string);
+            let content = field
+                .bytes()
+                .await
+                .map_err(|error| bad_request(&error.body_text()))?;
+            break (file_name, content.to_vec());
+        }
+    };
+    match state
+        .evaluate(model, move |web_api| {
+            web_api.analyze_match_file(file_name.as_deref(), &content)
+        })
+        .await?
+    {
+        Err(message) => Err((StatusCode::BAD_REQUEST, ErrorMessage::json(&message))),
+        Ok(analysis) => Ok(Json(analysis)),
+    }
+}
+
+/// Bigger messages from the client are rejected. Play messages are only a few hundred bytes.
+const MAX_PLAY_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Play against wildbg over a WebSocket connection.
+///
+/// The client sends JSON messages like `{"type":"move","play":[{"from":8,"to":5}]}`, the server
+/// answers with the moves and cube actions of wildbg and the current state, see [crate::session].
+/// Dice are rolled by the server unless the client sends its own.
+async fn get_play<T: Evaluator + Send + Sync + 'static>(
+    Query(model): Query<ModelParams>,
+    State(state): State<ApiState<T>>,
+    upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Result<Response, (StatusCode, Json<ErrorMessage>)> {
+    let upgrade = upgrade.map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorMessage::json("This endpoint only accepts WebSocket connections, version 13."),
+        )
+    })?;
+    state.check_model(&model)?;
+    Ok(upgrade
+        .max_message_size(MAX_PLAY_MESSAGE_SIZE)
+        .on_upgrade(move |socket| async move {
+            // Errors only mean that the connection is gone, nobody is left to report them to.
+            let _ = play_session(state, model, socket).await;
+        }))
+}
+
+/// One session per connection, it ends when the client closes the connection.
+async fn play_session<T: Evaluator + Send + Sync + 'static>(
+    state: ApiState<T>,
+    model: ModelParams,
+    mut socket: WebSocket,
+) -> Result<(), axum::Error> {
+    let session = Arc::new(Mutex::new(Session::new(FastrandDice::new())));
+    while let Some(message) = socket.recv().await {
+        let text = match message? {
+            Message::Text(text) => text,
+            // Pings are answered by axum.
+            Message::Ping(_) | Message::Pong(_) => continue,
+            Message::Close(_) => break,
+            Message::Binary(_) => {
+                let close = CloseFrame {
+                    code: close_code::UNSUPPORTED,
+                    reason: "Only text messages are supported.".into(),
+                };
+                return socket.send(Message::Close(Some(close))).await;
+            }
+        };
+        let messages = match serde_json::from_str::<ClientMessage>(&text) {
+            Err(error) => vec![ServerMessage::Error {
+                message: error.to_string(),
+            }],
+            Ok(message) => {
+                let session = session.clone();
+                state
+                    .evaluate(model.clone(), move |web_api| {
+                        let mut session = session.lock().expect("A session is never poisoned.");
+                        session.handle(web_api.evaluator().as_ref(), message)
+                    })
+                    .await
+                    .unwrap_or_else(|(_, Json(error))| {
+                        vec![ServerMessage::Error {
+                            message: error.message,
+                        }]
+                    })
+            }
+        };
+        for message in messages {
+            let json = serde_json::to_string(&message).expect("Messages can be serialized.");
+            socket.send(Message::Text(json)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Compares an uploaded contact net with the nets of the server.
+///
+/// The body is the onnx file. The comparison takes a couple of seconds.
+async fn post_benchmark<T: Evaluator + Send + Sync + 'static>(
+    State(admin): State<AdminState<T>>,
+    headers: HeaderMap,
+    contact_net: Bytes,
+) -> Result<Json<BenchmarkResponse>, (StatusCode, Json<ErrorMessage>)> {
+    check_admin_token(&admin, &headers)?;
+    let web_api = admin.web_api;
+    let result = tokio::task::spawn_blocking(move || match web_api.as_ref() {
         None => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            ErrorMessage::json("Neural net could not be constructed."),
+            "Neural net could not be constructed.".to_string(),
+        )),
+        Some(web_api) => web_api
+            .benchmark(&contact_net)
+            .map_err(|message| (StatusCode::BAD_REQUEST, message)),
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Benchmark failed.".to_string(),
+        ))
+    });
+    match result {
+        Ok(response) => Ok(Json(response)),
+        Err((status_code, message)) => Err((status_code, ErrorMessage::json(&message))),
+    }
+}
+
+/// Replaces the contact net of the server by the uploaded one, without restarting the server.
+///
+/// The body is the onnx file. Requests which have already started finish with the old net.
+/// With the query parameter `model` an alternative model is replaced instead of the default one.
+async fn post_reload<T: FromContactNet + Send + Sync + 'static>(
+    Query(model): Query<ModelParams>,
+    State(admin): State<AdminState<T>>,
+    headers: HeaderMap,
+    contact_net: Bytes,
+) -> Result<StatusCode, (StatusCode, Json<ErrorMessage>)> {
+    check_admin_token(&admin, &headers)?;
+    let web_api = admin.web_api;
+    let result = tokio::task::spawn_blocking(move || match web_api.as_ref() {
+        None => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Neural net could not be constructed.".to_string(),
         )),
-        Some(web_api) => match web_api.get_move(pips, dice) {
-            Err(message) => Err((StatusCode::BAD_REQUEST, ErrorMessage::json(message))),
-            Ok(move_response) => Ok(Json(move_response)),
-        },
+        Some(web_api) => web_api
+            .model(model.model.as_deref())
+            .and_then(|web_api| web_api.reload(&contact_net))
+            .map_err(|message| (StatusCode::BAD_REQUEST, message)),
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Reload failed.".to_string(),
+        ))
+    });
+    match result {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err((status_code, message)) => Err((status_code, ErrorMessage::json(&message))),
+    }
+}
+
+fn check_admin_token<T: Evaluator>(
+    admin: &AdminState<T>,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, Json<ErrorMessage>)> {
+    let expected = format!("Bearer {}", admin.admin_token);
+    let authorization = headers
+        .get(AUTHORIZATION)
+        .map(|value| value.as_bytes())
+        .unwrap_or_default();
+    // Compared in constant time, so that the response time doesn't reveal how much of the token is right.
+    if bool::from(authorization.ct_eq(expected.as_bytes())) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::UNAUTHORIZED,
+            ErrorMessage::json("Missing or wrong admin token."),
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     // use crate::{router, DynWebApi};
-    use crate::axum::router;
     use crate::axum::DynWebApi;
-    use crate::web_api::WebApi;
+    use crate::axum::{limit_rate, openapi, require_api_keys, router, router_with_admin};
+    use crate::web_api::{FromContactNet, WebApi};
     use axum::body::Body;
     use axum::http::header::CONTENT_TYPE;
-    use engine::evaluator::Evaluator;
     use engine::inputs::ContactInputsGen;
     use engine::onnx::OnnxEvaluator;
     use engine::pos;
-    use engine::position::Position;
-    use engine::probabilities::{Probabilities, ResultCounter};
+    use engine::test_utils::EvaluatorFake;
+    use futures_util::{SinkExt, StreamExt};
     use http_body_util::BodyExt;
     use hyper::{Request, StatusCode};
     use std::sync::Arc;
+    use tokio_tungstenite::tungstenite::Message;
     use tower::ServiceExt; // for `oneshot
 
-    struct EvaluatorFake {}
-    /// Because of different floating point implementations on different CPUs we don't want to
-    /// use a real neural net evaluator in unit tests. Instead we we use a fake evaluator.
-    /// This fake evaluator _knows_ evaluations for all given positions.
-    /// Based on that we later verify the rendered json.
-    /// Note: The probabilities used here are a different type compared to those in the json.
-    /// For internal probabilities all six values later add up to 1, for the json `win` and `lose`
-    /// will add up to 1.
-    /// Also sides are switched, so winning and losing values are switched.
-    impl Evaluator for EvaluatorFake {
-        fn eval(&self, position: &Position) -> Probabilities {
-            let forced_move = pos!(x 1:1; o 24:1).sides_switched();
-            let double_roll_1 = pos!(x 4:1, 2:1; o 24:1).sides_switched();
-            let double_roll_2 = pos!(x 5:1, 1:1; o 24:1).sides_switched();
-            let double_roll_3 = pos!(x 3:2; o 24:1).sides_switched();
-
-            if position == &forced_move {
-                Probabilities::from(&ResultCounter::new(874, 1, 1, 130, 1, 1))
-            } else if position == &double_roll_1 {
-                Probabilities::from(&ResultCounter::new(865, 1, 0, 137, 1, 1))
-            } else if position == &double_roll_2 {
-                Probabilities::from(&ResultCounter::new(12, 1, 1, 16, 3, 1))
-            } else if position == &double_roll_3 {
-                Probabilities::from(&ResultCounter::new(925, 1, 0, 75, 1, 1))
-            } else {
-                unreachable!("All evaluated positions should be listed here");
+    impl FromContactNet for EvaluatorFake {
+        fn from_contact_net(contact_net: &[u8]) -> Result<Self, String> {
+            match contact_net {
+                b"fake net" => Ok(EvaluatorFake {}),
+                _ => Err("The neural net can't be read.".to_string()),
             }
         }
     }
@@ -240,7 +1055,7 @@ mod tests {
         let response = router(web_api)
             .oneshot(
                 Request::builder()
-                    .uri("/eval?p1=1&p20=-1&p24=-1") // this position was specified in EvaluatorFake
+                    .uri("/eval?p1=1&p20=-1&p24=-1") // o on pip 20 is an advantage for o in EvaluatorFake
                     .body(Body::empty())
                     .unwrap(),
             )
@@ -253,7 +1068,7 @@ mod tests {
         let body = body_string(response).await;
         assert_eq!(
             body,
-            r#"{"cube":{"double":false,"accept":true},"probabilities":{"win":0.4117647,"winG":0.05882353,"loseG":0.11764706}}"#
+            r#"{"cube":{"double":false,"accept":true},"probabilities":{"win":0.35,"winG":0.0,"loseG":0.0}}"#
         );
     }
 
@@ -367,7 +1182,7 @@ mod tests {
         let body = body_string(response).await;
         assert_eq!(
             body,
-            r#"{"moves":[{"play":[{"from":5,"to":4},{"from":4,"to":1}],"probabilities":{"win":0.13095237,"winG":0.001984127,"loseG":0.001984127}}]}"#
+            r#"{"moves":[{"play":[{"from":5,"to":4,"hit":false},{"from":4,"to":1,"hit":false}],"probabilities":{"win":0.5,"winG":0.0,"loseG":0.0}}]}"#
         );
     }
 
@@ -390,7 +1205,593 @@ mod tests {
         let body = body_string(response).await;
         assert_eq!(
             body,
-            r#"{"moves":[{"play":[{"from":5,"to":4},{"from":4,"to":3},{"from":3,"to":2},{"from":2,"to":1}],"probabilities":{"win":0.5882353,"winG":0.11764706,"loseG":0.05882353}},{"play":[{"from":5,"to":4},{"from":5,"to":4},{"from":4,"to":3},{"from":3,"to":2}],"probabilities":{"win":0.13830847,"winG":0.0019900498,"loseG":0.0009950249}},{"play":[{"from":5,"to":4},{"from":5,"to":4},{"from":4,"to":3},{"from":4,"to":3}],"probabilities":{"win":0.07676969,"winG":0.001994018,"loseG":0.000997009}}]}"#
+            r#"{"moves":[{"play":[{"from":5,"to":4,"hit":false},{"from":4,"to":3,"hit":false},{"from":3,"to":2,"hit":false},{"from":2,"to":1,"hit":false}],"probabilities":{"win":0.65,"winG":0.0,"loseG":0.0}},{"play":[{"from":5,"to":4,"hit":false},{"from":5,"to":4,"hit":false},{"from":4,"to":3,"hit":false},{"from":3,"to":2,"hit":false}],"probabilities":{"win":0.5,"winG":0.0,"loseG":0.0}},{"play":[{"from":5,"to":4,"hit":false},{"from":5,"to":4,"hit":false},{"from":4,"to":3,"hit":false},{"from":4,"to":3,"hit":false}],"probabilities":{"win":0.5,"winG":0.0,"loseG":0.0}}]}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn post_eval_batch_success() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let id = pos!(x 5:3; o 24:1).position_id();
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/eval/batch")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(format!(r#"{{"positionIds":["{id}","{id}"]}}"#)))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+        let evaluations = body["evaluations"].as_array().unwrap();
+        assert_eq!(evaluations.len(), 2);
+        assert_eq!(evaluations[1]["positionId"], id);
+        assert!(evaluations[1]["equity"].as_f64().unwrap() > 0.7);
+    }
+
+    #[tokio::test]
+    async fn post_eval_batch_illegal_id() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/eval/batch")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"positionIds":["4HPwATDgc/AB"]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"'4HPwATDgc/AB': A GnuBG position ID consists of 14 Base64 characters."}"#
         );
     }
+
+    #[tokio::test]
+    async fn get_cube_double_pass() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/cube?p5=3&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        let body = body_string(response).await;
+        assert!(body.starts_with(r#"{"double":true,"take":false,"equities":{"noDouble":0.9"#));
+        assert!(body.contains(r#","doublePass":1.0},"window":{"doublingPoint":"#));
+        assert!(body.ends_with(r#","windowPosition":"DoublePass"}"#));
+    }
+
+    #[tokio::test]
+    async fn get_cube_at_match_score() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let router = router(web_api);
+        let get = |uri: &str| {
+            router
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let centered = body_string(get("/cube?x_away=3&o_away=5&p5=3&p24=-1").await.unwrap()).await;
+        assert!(centered.starts_with(r#"{"double":true,"take":false,"equities":{"noDouble":"#));
+        assert!(centered.contains(r#","doublePass":1.0},"window":{"doublingPoint":"#));
+        assert!(centered.ends_with(r#","windowPosition":"DoublePass"}"#));
+
+        // Same window, but `x` may not double.
+        let uri = "/cube?cube=2&owner=o&x_away=3&o_away=5&p5=3&p24=-1";
+        let owned_by_o = body_string(get(uri).await.unwrap()).await;
+        assert!(owned_by_o.starts_with(r#"{"double":false,"take":false,"#));
+
+        let uri = "/cube?x_away=1&o_away=3&crawford=true&p5=3&p24=-1";
+        let crawford = body_string(get(uri).await.unwrap()).await;
+        assert!(crawford.starts_with(r#"{"double":false,"#));
+    }
+
+    #[tokio::test]
+    async fn get_cube_illegal_owner() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/cube?cube=2&owner=y&p1=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"The cube owner must be 'x', 'o' or 'centered'."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_eval_by_xgid() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let router = router(web_api);
+        let get = |uri: &str| {
+            router
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let by_xgid = get("/eval?xgid=XGID=-A----------------------a-:0:0:1:00:0:0:0:0:10")
+            .await
+            .unwrap();
+        let by_pips = get("/eval?p1=1&p24=-1").await.unwrap();
+
+        assert_eq!(by_xgid.status(), StatusCode::OK);
+        assert_eq!(body_string(by_xgid).await, body_string(by_pips).await);
+    }
+
+    #[tokio::test]
+    async fn get_eval_unknown_model() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/eval?model=new&p1=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"Unknown model 'new'. Available models: none."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_eval_xgid_and_pips() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/eval?xgid=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:0:0:10&p5=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"Give the position either as XGID or as single pips, not both."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_rollout_streams_progress() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/rollout?games=2000&seed=1&p1=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+
+        let body = body_string(response).await;
+        let events: Vec<&str> = body.split_terminator("\n\n").collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[1],
+            r#"event: progress
+data: {"games":2592,"totalGames":2592,"probabilities":{"win":1.0,"winG":0.0,"loseG":0.0},"equity":1.0,"equityError":0.0}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_rollout_too_many_games() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/rollout?games=20000&p1=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"The number of games must be between 1 and 12960."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn post_analysis_without_file() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/analysis")
+                    .header("content-type", "multipart/form-data; boundary=XyZ")
+                    .body(Body::from(
+                        "--XyZ\r\nContent-Disposition: form-data; name=\"notes\"\r\n\r\nhi\r\n--XyZ--\r\n",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"The multipart body contains no field with this name."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn post_analysis_of_empty_match() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/analysis")
+                    .header("content-type", "multipart/form-data; boundary=XyZ")
+                    .body(Body::from(
+                        "--XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.mat\"\r\n\r\n 3 point match\n\r\n--XyZ--\r\n",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response).await;
+        assert!(body.starts_with(r#"{"decisions":[],"rolls":[],"summaries":[{"name":""#));
+    }
+
+    #[tokio::test]
+    async fn get_openapi_json() {
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response).await;
+        assert_eq!(body, openapi().to_json().unwrap());
+        assert!(body.contains(r#""/analysis":{"post":"#));
+    }
+
+    #[tokio::test]
+    async fn get_play_over_websocket() {
+        // Given
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(web_api)).await });
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{address}/play"))
+            .await
+            .unwrap();
+        // When
+        let new_match = r#"{"type":"newMatch","length":3,"dice":[3,1]}"#;
+        socket.send(Message::text(new_match)).await.unwrap();
+        let answer = socket.next().await.unwrap().unwrap().into_text().unwrap();
+        // Then
+        assert!(answer.starts_with(r#"{"type":"state","#), "{answer}");
+        assert!(answer.contains(r#""dice":[3,1]"#), "{answer}");
+    }
+
+    #[tokio::test]
+    async fn get_play_without_websocket() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router(web_api)
+            .oneshot(Request::builder().uri("/play").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"This endpoint only accepts WebSocket connections, version 13."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_board_svg_success() {
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/board.svg?die1=3&die2=1&cube=2&owner=o&p5=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/svg+xml");
+
+        let body = body_string(response).await;
+        assert!(body.starts_with("<svg "));
+        assert!(body.contains(">X: 5 pips</text>"));
+    }
+
+    #[tokio::test]
+    async fn get_board_svg_illegal_cube() {
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/board.svg?cube=3&p5=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"The cube value must be a power of 2."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn get_move_too_many_pending() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {}).with_max_pending(0)));
+        let response = router(web_api)
+            .oneshot(
+                Request::builder()
+                    .uri("/move?die1=3&die2=1&p5=1&p24=-1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()["retry-after"], "1");
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"Too many pending requests, please try again later."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn post_benchmark_without_token() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router_with_admin(web_api, "secret".to_string())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/admin/benchmark")
+                    .body(Body::from("no onnx"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body = body_string(response).await;
+        assert_eq!(body, r#"{"message":"Missing or wrong admin token."}"#);
+    }
+
+    #[tokio::test]
+    async fn post_benchmark_invalid_net() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let response = router_with_admin(web_api, "secret".to_string())
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/admin/benchmark")
+                    .header("Authorization", "Bearer secret")
+                    .body(Body::from("no onnx"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_string(response).await;
+        assert_eq!(
+            body,
+            r#"{"message":"The neural net is no valid onnx model."}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn post_reload_replaces_net() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let app = router_with_admin(web_api, "secret".to_string());
+        let reload = |token: &str, net: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/admin/reload")
+                    .header("Authorization", format!("Bearer {token}"))
+                    .body(Body::from(net))
+                    .unwrap(),
+            )
+        };
+
+        let wrong_token = reload("guess", "fake net").await.unwrap();
+        assert_eq!(wrong_token.status(), StatusCode::UNAUTHORIZED);
+        let invalid_net = reload("secret", "no onnx").await.unwrap();
+        assert_eq!(invalid_net.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_string(invalid_net).await,
+            r#"{"message":"The neural net can't be read."}"#
+        );
+        let valid_net = reload("secret", "fake net").await.unwrap();
+        assert_eq!(valid_net.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn health_and_readiness() {
+        let web_api = Arc::new(Some(WebApi::new(EvaluatorFake {})));
+        let app = require_api_keys(router(web_api.clone()), vec!["key".to_string()]);
+        let get = |uri: &str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let health = get("/healthz").await.unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+        assert_eq!(body_string(health).await, "ok");
+        let cold = get("/readyz").await.unwrap();
+        assert_eq!(cold.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            body_string(cold).await,
+            r#"{"message":"The neural nets are still warming up."}"#
+        );
+
+        web_api.as_ref().as_ref().unwrap().warm_up();
+        let ready = get("/readyz").await.unwrap();
+        assert_eq!(ready.status(), StatusCode::OK);
+        assert_eq!(body_string(ready).await, "ready");
+    }
+
+    #[tokio::test]
+    async fn api_key_required() {
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let app = require_api_keys(router(web_api), vec!["key1".to_string()]);
+        let get = |uri: &str, key: Option<&str>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some(key) = key {
+                request = request.header("X-API-Key", key);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        let board = "/board.svg?p5=1&p24=-1";
+
+        let without_key = get(board, None).await.unwrap();
+        assert_eq!(without_key.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            body_string(without_key).await,
+            r#"{"message":"Missing or wrong API key."}"#
+        );
+        let wrong_key = get(board, Some("key2")).await.unwrap();
+        assert_eq!(wrong_key.status(), StatusCode::UNAUTHORIZED);
+
+        assert_eq!(
+            get(board, Some("key1")).await.unwrap().status(),
+            StatusCode::OK
+        );
+        let in_query = get("/board.svg?api_key=key1&p5=1&p24=-1", None);
+        assert_eq!(in_query.await.unwrap().status(), StatusCode::OK);
+        let documentation = get("/openapi.json", None).await.unwrap();
+        assert_eq!(documentation.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_per_api_key() {
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let keys = vec!["key1".to_string(), "key2".to_string()];
+        let app = require_api_keys(limit_rate(router(web_api), 2), keys);
+        let get = |key: &str| {
+            let request = Request::builder()
+                .uri("/board.svg?p5=1&p24=-1")
+                .header("X-API-Key", key);
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(get("key1").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(get("key1").await.unwrap().status(), StatusCode::OK);
+        let limited = get("key1").await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()["retry-after"], "30");
+        assert_eq!(
+            body_string(limited).await,
+            r#"{"message":"Too many requests, please try again later."}"#
+        );
+        assert_eq!(get("key2").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            get("key3").await.unwrap().status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limit_ignores_unchecked_api_keys() {
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let app = limit_rate(router(web_api), 2);
+        let get = |key: &str| {
+            let request = Request::builder()
+                .uri("/board.svg?p5=1&p24=-1")
+                .header("X-API-Key", key);
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(get("key1").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(get("key2").await.unwrap().status(), StatusCode::OK);
+        let other_key = get("key3").await.unwrap();
+        assert_eq!(other_key.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn get_metrics_after_requests() {
+        // Given
+        let web_api = Arc::new(None) as DynWebApi<OnnxEvaluator<ContactInputsGen>>;
+        let app = router(web_api);
+        let get = |uri: &str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        get("/board.svg?p5=1&p24=-1").await.unwrap();
+        get("/board.svg?p5=16").await.unwrap();
+        get("/unknown").await.unwrap();
+        // When
+        let response = get("/metrics").await.unwrap();
+        // Then
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_string(response).await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert!(lines.contains(&r#"wildbg_requests_total{path="/board.svg",status="200"} 1"#));
+        assert!(lines.contains(&r#"wildbg_requests_total{path="/board.svg",status="400"} 1"#));
+        assert!(lines.contains(&r#"wildbg_requests_total{path="other",status="404"} 1"#));
+        assert!(lines.contains(&r#"wildbg_request_duration_seconds_count{path="/board.svg"} 2"#));
+        assert!(lines.contains(&"wildbg_evaluations_pending 0"));
+    }
 }
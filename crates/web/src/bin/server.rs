@@ -1,13 +1,23 @@
 use clap::Parser;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use web::axum::router;
+use web::axum::{limit_rate, openapi, require_api_keys, router, router_with_admin};
 use web::startup::{self, Args};
-use web::web_api::WebApi;
+use web::web_api::{FromContactNet, WebApi};
 
 #[tokio::main]
 async fn main() {
-    let web_address = startup::get_web_address(&Args::parse());
+    let args = Args::parse();
+    if args.openapi {
+        let json = openapi()
+            .to_pretty_json()
+            .expect("The specification can be serialized.");
+        println!("{json}");
+        return;
+    }
+    let web_address = startup::get_web_address(&args);
 
     log_server_links(&web_address);
 
@@ -15,9 +25,47 @@ async fn main() {
         .await
         .unwrap_or_else(|_| panic!("Could not bind to the web address: '{web_address}'"));
 
-    let web_api = Arc::new(WebApi::try_default());
-    let app = router(web_api);
-    axum::serve(listener, app).await.unwrap();
+    let web_api = Arc::new(WebApi::try_default().map(|api| {
+        let api = api
+            .with_budgets(args.budgets())
+            .with_max_pending(args.max_pending)
+            .with_cache(args.cache_size, Duration::from_secs(args.cache_ttl_secs));
+        args.models.iter().fold(api, |api, (name, path)| {
+            let evaluator = std::fs::read(path)
+                .map_err(|error| error.to_string())
+                .and_then(|bytes| FromContactNet::from_contact_net(&bytes))
+                .unwrap_or_else(|error| {
+                    panic!("Could not load model '{name}' from '{path}': {error}")
+                });
+            api.with_model(name.clone(), evaluator)
+        })
+    }));
+    let warm_up = web_api.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Some(web_api) = warm_up.as_ref() {
+            web_api.warm_up();
+        }
+    });
+    let app = match args.admin_token {
+        Some(admin_token) => router_with_admin(web_api, admin_token),
+        None => router(web_api),
+    };
+    // The rate limit runs after the API key check and only trusts validated keys.
+    let app = match args.rate_limit {
+        Some(requests_per_minute) => limit_rate(app, requests_per_minute),
+        None => app,
+    };
+    let app = if args.api_keys.is_empty() {
+        app
+    } else {
+        require_api_keys(app, args.api_keys)
+    };
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 /// Log the web address and helpful links to the command line.
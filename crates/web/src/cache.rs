@@ -0,0 +1,197 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default for the maximum number of entries of each [ResponseCache].
+pub const DEFAULT_CACHE_SIZE: usize = 10_000;
+
+/// Default for the time after which a cached response is evaluated again.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Cache for responses of positions which are requested again and again, like the openings.
+///
+/// When the cache is full, the oldest entry is dropped. As all entries live equally long, this is
+/// also the one which expires first.
+pub struct ResponseCache<K, V> {
+    max_size: usize,
+    ttl: Duration,
+    entries: Mutex<Entries<K, V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct Entries<K, V> {
+    values: HashMap<K, (V, Instant)>,
+    /// Keys in the order they were inserted. A key can appear more than once if it expired and was
+    /// inserted again; only the occurrence with the same time as in `values` counts.
+    order: VecDeque<(K, Instant)>,
+}
+
+/// Hits and misses of all caches of a [crate::web_api::WebApi], shown at `/metrics`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Add for CacheStats {
+    type Output = CacheStats;
+
+    fn add(self, other: CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> ResponseCache<K, V> {
+    /// A `max_size` of 0 disables the cache.
+    pub fn new(max_size: usize, ttl: Duration) -> Self {
+        Self {
+            max_size,
+            ttl,
+            entries: Mutex::new(Entries {
+                values: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached value for `key` or computes and caches it.
+    pub fn get_or_insert(&self, key: K, now: Instant, compute: impl FnOnce() -> V) -> V {
+        self.get_or_insert_if(key, now, || (compute(), true))
+    }
+
+    /// Like [ResponseCache::get_or_insert], but the value is only cached if `compute` also returns
+    /// `true`, for example not when it was computed less thoroughly than usual.
+    pub fn get_or_insert_if(&self, key: K, now: Instant, compute: impl FnOnce() -> (V, bool)) -> V {
+        if self.max_size == 0 {
+            return compute().0;
+        }
+        if let Some(value) = self.get(&key, now) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return value;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // The lock is not held while computing, so two requests may compute the same value.
+        let (value, cacheable) = compute();
+        if cacheable {
+            self.insert(key, value.clone(), now);
+        }
+        value
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.values.clear();
+        entries.order.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn get(&self, key: &K, now: Instant) -> Option<V> {
+        let entries = self.lock();
+        let (value, inserted) = entries.values.get(key)?;
+        (now.saturating_duration_since(*inserted) < self.ttl).then(|| value.clone())
+    }
+
+    fn insert(&self, key: K, value: V, now: Instant) {
+        let mut entries = self.lock();
+        entries.values.insert(key.clone(), (value, now));
+        entries.order.push_back((key, now));
+        while entries.values.len() > self.max_size || entries.order.len() > 2 * self.max_size {
+            let Some((oldest, inserted)) = entries.order.pop_front() else {
+                break;
+            };
+            if entries
+                .values
+                .get(&oldest)
+                .is_some_and(|(_, current)| *current == inserted)
+            {
+                entries.values.remove(&oldest);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries<K, V>> {
+        self.entries.lock().expect("The cache is never poisoned.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{CacheStats, ResponseCache};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn hit_until_expired() {
+        // Given
+        let cache = ResponseCache::new(10, Duration::from_secs(60));
+        let start = Instant::now();
+        cache.get_or_insert("a", start, || 1);
+        // When
+        let cached = cache.get_or_insert("a", start + Duration::from_secs(59), || 2);
+        let expired = cache.get_or_insert("a", start + Duration::from_secs(60), || 3);
+        // Then
+        assert_eq!(cached, 1);
+        assert_eq!(expired, 3);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[test]
+    fn drop_oldest_when_full() {
+        // Given
+        let cache = ResponseCache::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.get_or_insert("a", now, || 1);
+        cache.get_or_insert("b", now, || 2);
+        // When
+        cache.get_or_insert("c", now, || 3);
+        // Then
+        assert_eq!(cache.get_or_insert("b", now, || 0), 2);
+        assert_eq!(cache.get_or_insert("c", now, || 0), 3);
+        assert_eq!(cache.get_or_insert("a", now, || 0), 0);
+    }
+
+    #[test]
+    fn size_0_disables_cache() {
+        let cache = ResponseCache::new(0, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.get_or_insert("a", now, || 1);
+        assert_eq!(cache.get_or_insert("a", now, || 2), 2);
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn values_which_may_not_be_cached() {
+        // Given
+        let cache = ResponseCache::new(10, Duration::from_secs(60));
+        let now = Instant::now();
+        // When
+        let degraded = cache.get_or_insert_if("a", now, || (1, false));
+        // Then
+        assert_eq!(degraded, 1);
+        assert_eq!(cache.get_or_insert_if("a", now, || (2, true)), 2);
+        assert_eq!(cache.get_or_insert("a", now, || 3), 2);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+}
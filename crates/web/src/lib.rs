@@ -1,3 +1,7 @@
 pub mod axum;
+pub mod cache;
+pub mod metrics;
+pub mod rate_limit;
+pub mod session;
 pub mod startup;
 pub mod web_api;
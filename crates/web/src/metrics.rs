@@ -0,0 +1,183 @@
+use crate::cache::CacheStats;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Metrics of the web server in the text format of Prometheus, served at `/metrics`.
+
+/// Upper bounds of the latency buckets in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0];
+
+#[derive(Default)]
+pub struct Metrics {
+    /// Number of responses per path and status code.
+    requests: Mutex<BTreeMap<(String, u16), u64>>,
+    latencies: Mutex<BTreeMap<String, Histogram>>,
+    evaluations: AtomicU64,
+    rollouts: AtomicUsize,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Cumulative like in the output: each bucket counts all requests up to its bound.
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Metrics {
+    /// `path` should be the route like `/eval`, not the requested URI, to keep the number of
+    /// label values small.
+    pub fn record_request(&self, path: &str, status: u16, latency: Duration) {
+        let mut requests = self.requests.lock().expect("Metrics are never poisoned.");
+        *requests.entry((path.to_string(), status)).or_default() += 1;
+        drop(requests);
+
+        let seconds = latency.as_secs_f64();
+        let mut latencies = self.latencies.lock().expect("Metrics are never poisoned.");
+        let histogram = latencies.entry(path.to_string()).or_default();
+        for (count, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+        histogram.count += 1;
+        histogram.sum += seconds;
+    }
+
+    /// Called once a request to the neural net, a rollout or an analysis has finished.
+    pub fn record_evaluation(&self) {
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rollout_started(&self) {
+        self.rollouts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rollout_finished(&self) {
+        self.rollouts.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// All metrics in the text exposition format of Prometheus.
+    ///
+    /// `pending` is the number of evaluations running or waiting for a worker thread.
+    pub fn render(&self, pending: usize, cache: CacheStats) -> String {
+        let mut text = String::new();
+        let mut line = |line: String| {
+            text.push_str(&line);
+            text.push('\n');
+        };
+
+        line("# HELP wildbg_requests_total Number of HTTP responses.".to_string());
+        line("# TYPE wildbg_requests_total counter".to_string());
+        let requests = self.requests.lock().expect("Metrics are never poisoned.");
+        for ((path, status), count) in requests.iter() {
+            line(format!(
+                "wildbg_requests_total{{path=\"{path}\",status=\"{status}\"}} {count}"
+            ));
+        }
+        drop(requests);
+
+        line(
+            "# HELP wildbg_request_duration_seconds Time until the response headers are sent."
+                .to_string(),
+        );
+        line("# TYPE wildbg_request_duration_seconds histogram".to_string());
+        let latencies = self.latencies.lock().expect("Metrics are never poisoned.");
+        for (path, histogram) in latencies.iter() {
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                line(format!(
+                    "wildbg_request_duration_seconds_bucket{{path=\"{path}\",le=\"{bound}\"}} {count}"
+                ));
+            }
+            line(format!(
+                "wildbg_request_duration_seconds_bucket{{path=\"{path}\",le=\"+Inf\"}} {}",
+                histogram.count
+            ));
+            line(format!(
+                "wildbg_request_duration_seconds_sum{{path=\"{path}\"}} {}",
+                histogram.sum
+            ));
+            line(format!(
+                "wildbg_request_duration_seconds_count{{path=\"{path}\"}} {}",
+                histogram.count
+            ));
+        }
+        drop(latencies);
+
+        line("# HELP wildbg_evaluations_total Number of finished evaluations, rollouts and analyses.".to_string());
+        line("# TYPE wildbg_evaluations_total counter".to_string());
+        line(format!(
+            "wildbg_evaluations_total {}",
+            self.evaluations.load(Ordering::Relaxed)
+        ));
+        line(
+            "# HELP wildbg_evaluations_pending Evaluations running or waiting for a worker thread."
+                .to_string(),
+        );
+        line("# TYPE wildbg_evaluations_pending gauge".to_string());
+        line(format!("wildbg_evaluations_pending {pending}"));
+        line(
+            "# HELP wildbg_rollouts_running Rollouts currently streaming their progress."
+                .to_string(),
+        );
+        line("# TYPE wildbg_rollouts_running gauge".to_string());
+        line(format!(
+            "wildbg_rollouts_running {}",
+            self.rollouts.load(Ordering::Relaxed)
+        ));
+        line(
+            "# HELP wildbg_cache_hits_total Evaluations and moves served from the cache."
+                .to_string(),
+        );
+        line("# TYPE wildbg_cache_hits_total counter".to_string());
+        line(format!("wildbg_cache_hits_total {}", cache.hits));
+        line(
+            "# HELP wildbg_cache_misses_total Evaluations and moves not found in the cache."
+                .to_string(),
+        );
+        line("# TYPE wildbg_cache_misses_total counter".to_string());
+        line(format!("wildbg_cache_misses_total {}", cache.misses));
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::CacheStats;
+    use crate::metrics::Metrics;
+    use std::time::Duration;
+
+    #[test]
+    fn render_requests_and_latencies() {
+        // Given
+        let metrics = Metrics::default();
+        metrics.record_request("/eval", 200, Duration::from_millis(20));
+        metrics.record_request("/eval", 200, Duration::from_millis(300));
+        metrics.record_request("/eval", 400, Duration::from_millis(1));
+        metrics.record_evaluation();
+        metrics.rollout_started();
+        // When
+        let text = metrics.render(3, CacheStats { hits: 4, misses: 5 });
+        // Then
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines.contains(&r#"wildbg_requests_total{path="/eval",status="200"} 2"#));
+        assert!(lines.contains(&r#"wildbg_requests_total{path="/eval",status="400"} 1"#));
+        assert!(
+            lines.contains(&r#"wildbg_request_duration_seconds_bucket{path="/eval",le="0.005"} 1"#)
+        );
+        assert!(
+            lines.contains(&r#"wildbg_request_duration_seconds_bucket{path="/eval",le="0.025"} 2"#)
+        );
+        assert!(
+            lines.contains(&r#"wildbg_request_duration_seconds_bucket{path="/eval",le="+Inf"} 3"#)
+        );
+        assert!(lines.contains(&r#"wildbg_request_duration_seconds_count{path="/eval"} 3"#));
+        assert!(lines.contains(&"wildbg_evaluations_total 1"));
+        assert!(lines.contains(&"wildbg_evaluations_pending 3"));
+        assert!(lines.contains(&"wildbg_rollouts_running 1"));
+        assert!(lines.contains(&"wildbg_cache_hits_total 4"));
+        assert!(lines.contains(&"wildbg_cache_misses_total 5"));
+    }
+}
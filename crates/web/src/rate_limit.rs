@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Above this number of clients, those with a full bucket are forgotten. If that's not enough,
+/// the client which was seen least recently is forgotten as well.
+const MAX_CLIENTS: usize = 10_000;
+
+/// Token bucket per client: each client may send `requests` requests at once, afterwards one more
+/// each `per / requests`.
+pub struct RateLimiter {
+    capacity: f64,
+    refill: Duration,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        let requests = requests.max(1);
+        Self {
+            capacity: requests as f64,
+            refill: per / requests,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes one token from the bucket of `client`.
+    ///
+    /// Returns the time after which the next request would be allowed if the bucket is empty.
+    pub fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .expect("Rate limiter is never poisoned.");
+        if buckets.len() >= MAX_CLIENTS {
+            buckets.retain(|_, bucket| self.tokens(bucket, now) < self.capacity);
+        }
+        if buckets.len() >= MAX_CLIENTS && !buckets.contains_key(client) {
+            let least_recent = buckets
+                .iter()
+                .min_by_key(|(_, bucket)| bucket.updated)
+                .map(|(client, _)| client.clone());
+            if let Some(least_recent) = least_recent {
+                buckets.remove(&least_recent);
+            }
+        }
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        bucket.tokens = self.tokens(bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.refill.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+
+    fn tokens(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        let refilled = elapsed.as_secs_f64() / self.refill.as_secs_f64();
+        (bucket.tokens + refilled).min(self.capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rate_limit::{RateLimiter, MAX_CLIENTS};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn burst_then_refill() {
+        // Given
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        // When
+        let first = limiter.check("a", start);
+        let second = limiter.check("a", start);
+        let third = limiter.check("a", start + Duration::from_secs(10));
+        let other_client = limiter.check("b", start);
+        let later = limiter.check("a", start + Duration::from_secs(30));
+        // Then
+        assert_eq!(first, Ok(()));
+        assert_eq!(second, Ok(()));
+        assert_eq!(third, Err(Duration::from_secs(20)));
+        assert_eq!(other_client, Ok(()));
+        assert_eq!(later, Ok(()));
+    }
+
+    #[test]
+    fn number_of_clients_is_bounded() {
+        // Given
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        for client in 0..MAX_CLIENTS {
+            let now = start + Duration::from_millis(client as u64);
+            limiter.check(&client.to_string(), now).unwrap();
+        }
+        // When
+        let later = start + Duration::from_secs(1);
+        let new_client = limiter.check("new", later);
+        // Then
+        assert_eq!(new_client, Ok(()));
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_CLIENTS);
+        assert!(!buckets.contains_key("0"));
+        assert!(buckets.contains_key("1"));
+    }
+}
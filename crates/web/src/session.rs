@@ -0,0 +1,486 @@
+use engine::dice::Dice;
+use engine::dice_gen::DiceGen;
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use logic::bg_move::{apply_checker_moves, BgMove, MoveDetail};
+use logic::cube::{CubeInfo, WindowPosition};
+use logic::game_state::{CubeOwner, GameState, Player};
+use logic::match_equity::MatchEquityTable;
+use logic::match_state::MatchState;
+use logic::money_rules::MoneyRules;
+use serde::{Deserialize, Serialize};
+
+// A game between a human client and the bot, independent of the transport. The WebSocket endpoint
+// in `axum.rs` only converts the messages from and to JSON.
+
+/// The human is always [Player::One].
+const HUMAN: Player = Player::One;
+const BOT: Player = Player::Two;
+
+/// Messages sent by the client, tagged by `type`.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ClientMessage {
+    /// Starts a match of `length` points, or money games if `length` is missing.
+    ///
+    /// `dice` is the opening roll with the die of the human first; without it the server rolls.
+    NewMatch {
+        length: Option<u32>,
+        dice: Option<[usize; 2]>,
+    },
+    /// Starts the next game of the match after the previous one is over.
+    NewGame {
+        dice: Option<[usize; 2]>,
+    },
+    /// Rolls for the human; without `dice` the server rolls.
+    Roll {
+        dice: Option<[usize; 2]>,
+    },
+    Double,
+    Take,
+    Pass,
+    /// Checker moves of the human in the same format as the moves of `/move`.
+    Move {
+        play: Vec<CheckerMove>,
+    },
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CheckerMove {
+    /// The bar is `25`.
+    pub from: usize,
+    /// Bearing off is `0`.
+    pub to: usize,
+}
+
+/// Messages sent by the server, tagged by `type`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[allow(non_snake_case)]
+pub enum ServerMessage {
+    BotRolls {
+        dice: [usize; 2],
+        play: Vec<MoveDetail>,
+    },
+    BotDoubles {
+        cube: u32,
+    },
+    BotTakes,
+    BotPasses,
+    GameOver {
+        winner: Side,
+        points: u32,
+    },
+    MatchOver {
+        winner: Side,
+    },
+    /// Sent after every client message, so the client never has to keep track of the game itself.
+    State(StateView),
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Side {
+    Human,
+    Bot,
+}
+
+impl From<Player> for Side {
+    fn from(player: Player) -> Self {
+        if player == HUMAN {
+            Side::Human
+        } else {
+            Side::Bot
+        }
+    }
+}
+
+/// The current game and score. Positive pips are checkers of the human, who moves from 24 to 1.
+#[derive(Debug, PartialEq, Serialize)]
+#[allow(non_snake_case)]
+pub struct StateView {
+    positionId: String,
+    pips: [i8; 26],
+    turn: Side,
+    dice: Option<[usize; 2]>,
+    cube: u32,
+    /// `None` if the cube is centered.
+    cubeOwner: Option<Side>,
+    /// `true` if the player on turn has doubled and the opponent has to decide.
+    doubled: bool,
+    gameOver: bool,
+    /// Index 0 is the human, index 1 the bot.
+    score: [u32; 2],
+    /// `None` for money games.
+    length: Option<u32>,
+    crawford: bool,
+}
+
+/// One human playing a match or money games against the bot.
+pub struct Session<D: DiceGen> {
+    match_state: MatchState,
+    game: Option<GameState>,
+    dice_gen: D,
+}
+
+impl<D: DiceGen> Session<D> {
+    /// No game is running until the client sends [ClientMessage::NewMatch].
+    pub fn new(dice_gen: D) -> Self {
+        Self {
+            match_state: MatchState::money(MoneyRules::default()),
+            game: None,
+            dice_gen,
+        }
+    }
+
+    /// Applies the message of the client and lets the bot act until it's the human's turn again.
+    ///
+    /// The last message is always the current state, if a game has been started.
+    pub fn handle<T: Evaluator>(
+        &mut self,
+        evaluator: &T,
+        message: ClientMessage,
+    ) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        if let Err(message) = self.apply(evaluator, message, &mut messages) {
+            messages.push(ServerMessage::Error { message });
+        }
+        if let Some(game) = &self.game {
+            messages.push(ServerMessage::State(self.state(game)));
+        }
+        messages
+    }
+
+    fn apply<T: Evaluator>(
+        &mut self,
+        evaluator: &T,
+        message: ClientMessage,
+        messages: &mut Vec<ServerMessage>,
+    ) -> Result<(), String> {
+        match message {
+            ClientMessage::NewMatch { length, dice } => {
+                self.match_state = match length {
+                    Some(length) => MatchState::new(length)?,
+                    None => MatchState::money(MoneyRules::default()),
+                };
+                self.game = None;
+                self.start_game(dice)?;
+            }
+            ClientMessage::NewGame { dice } => {
+                if self
+                    .game
+                    .as_ref()
+                    .is_some_and(|game| game.outcome().is_none())
+                {
+                    return Err("The game is not over yet.".to_string());
+                }
+                self.start_game(dice)?;
+            }
+            ClientMessage::Roll { dice } => {
+                let dice = match dice {
+                    Some(dice) => Dice::try_from((dice[0], dice[1]))?,
+                    None => self.dice_gen.roll(),
+                };
+                self.human_game()?.roll(dice)?;
+            }
+            ClientMessage::Double => self.human_game()?.double()?,
+            ClientMessage::Take | ClientMessage::Pass => {
+                let game = self.game.as_mut().ok_or("No game has been started.")?;
+                if game.turn() != BOT || !game.is_doubled() {
+                    return Err("The bot hasn't doubled.".to_string());
+                }
+                match message {
+                    ClientMessage::Take => game.take()?,
+                    _ => game.pass()?,
+                }
+            }
+            ClientMessage::Move { play } => {
+                let game = self.human_game()?;
+                let checker_moves: Vec<(usize, usize)> =
+                    play.iter().map(|m| (m.from, m.to)).collect();
+                let new = apply_checker_moves(game.position(), &checker_moves)?;
+                game.play(&new)?;
+            }
+        }
+        self.bot_acts(evaluator, messages)
+    }
+
+    fn start_game(&mut self, dice: Option<[usize; 2]>) -> Result<(), String> {
+        let [human_die, bot_die] = match dice {
+            Some(dice) => dice,
+            None => {
+                let Dice::Mixed(dice) = self.dice_gen.roll_mixed() else {
+                    unreachable!("The opening roll is never a double.");
+                };
+                match self.dice_gen.choose_index(&[1.0, 1.0]) {
+                    0 => [dice.big(), dice.small()],
+                    _ => [dice.small(), dice.big()],
+                }
+            }
+        };
+        self.game = Some(self.match_state.new_game(human_die, bot_die)?);
+        Ok(())
+    }
+
+    /// The running game, if it's the human's turn.
+    fn human_game(&mut self) -> Result<&mut GameState, String> {
+        let game = self.game.as_mut().ok_or("No game has been started.")?;
+        if game.turn() != HUMAN || game.outcome().is_some() {
+            return Err("It's not your turn.".to_string());
+        }
+        Ok(game)
+    }
+
+    /// Cube decisions follow Janowski's money model of [CubeInfo] in money games and the doubling
+    /// window of the default [MatchEquityTable] in matches, checker plays depend on the score.
+    fn bot_acts<T: Evaluator>(
+        &mut self,
+        evaluator: &T,
+        messages: &mut Vec<ServerMessage>,
+    ) -> Result<(), String> {
+        let Some(game) = self.game.as_mut() else {
+            return Ok(());
+        };
+        // If the bot has doubled, it waits for the human to take or pass.
+        while game.outcome().is_none() && game.turn() == BOT && !game.is_doubled() {
+            if game.may_double() && bot_doubles(evaluator, game, &self.match_state) {
+                game.double()?;
+                messages.push(ServerMessage::BotDoubles {
+                    cube: 2 * game.cube().value(),
+                });
+                break;
+            }
+            let dice = self.dice_gen.roll();
+            game.roll(dice)?;
+            let config = self.match_state.config(game);
+            let position = *game.position();
+            let new = evaluator
+                .best_position(&position, &dice, config.value())
+                .sides_switched();
+            messages.push(ServerMessage::BotRolls {
+                dice: dice_view(&dice),
+                play: BgMove::new(&position, &new, &dice).into_details(),
+            });
+            game.play(&new)?;
+        }
+        // The human has doubled, the bot is the opponent of `turn`.
+        if game.outcome().is_none() && game.turn() == HUMAN && game.is_doubled() {
+            if bot_takes(evaluator, game, &self.match_state) {
+                game.take()?;
+                messages.push(ServerMessage::BotTakes);
+            } else {
+                game.pass()?;
+                messages.push(ServerMessage::BotPasses);
+            }
+        }
+        if let Some(outcome) = game.outcome() {
+            messages.push(ServerMessage::GameOver {
+                winner: outcome.winner.into(),
+                points: outcome.points,
+            });
+            self.match_state.add_game(game)?;
+            if let Some(winner) = self.match_state.winner() {
+                messages.push(ServerMessage::MatchOver {
+                    winner: winner.into(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn state(&self, game: &GameState) -> StateView {
+        let position: Position = if game.turn() == HUMAN {
+            *game.position()
+        } else {
+            game.position().sides_switched()
+        };
+        let cube = game.cube();
+        StateView {
+            positionId: position.position_id(),
+            pips: position.into(),
+            turn: game.turn().into(),
+            dice: game.dice().as_ref().map(dice_view),
+            cube: cube.value(),
+            cubeOwner: match cube.owner() {
+                CubeOwner::Centered => None,
+                CubeOwner::Owned(player) => Some(player.into()),
+            },
+            doubled: game.is_doubled(),
+            gameOver: game.outcome().is_some(),
+            score: [self.match_state.score(HUMAN), self.match_state.score(BOT)],
+            length: self.match_state.length(),
+            crawford: game.is_crawford(),
+        }
+    }
+}
+
+/// `true` if the bot, who is on turn, should double.
+fn bot_doubles<T: Evaluator>(evaluator: &T, game: &GameState, match_state: &MatchState) -> bool {
+    let probabilities = evaluator.eval(game.position());
+    match match_state.match_score(game.turn()) {
+        None => CubeInfo::from(&probabilities).double(),
+        Some(score) => {
+            let window =
+                MatchEquityTable::default().window(&probabilities, &score, game.cube().value());
+            matches!(
+                window.position(),
+                WindowPosition::DoubleTake | WindowPosition::DoublePass
+            )
+        }
+    }
+}
+
+/// `true` if the bot should take the double of the human, who is on turn.
+fn bot_takes<T: Evaluator>(evaluator: &T, game: &GameState, match_state: &MatchState) -> bool {
+    let probabilities = evaluator.eval(game.position());
+    match match_state.match_score(game.turn()) {
+        None => CubeInfo::from(&probabilities).accept(),
+        Some(score) => {
+            let window =
+                MatchEquityTable::default().window(&probabilities, &score, game.cube().value());
+            probabilities.win() < window.cash_point
+        }
+    }
+}
+
+fn dice_view(dice: &Dice) -> [usize; 2] {
+    match dice {
+        Dice::Double(die) => [*die, *die],
+        Dice::Mixed(dice) => [dice.big(), dice.small()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::session::{ClientMessage, ServerMessage, Session, Side};
+    use engine::dice::Dice;
+    use engine::dice_gen::DiceGenMock;
+    use engine::test_utils::EvaluatorFake;
+
+    fn message(json: &str) -> ClientMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn human_moves_and_bot_answers() {
+        // Given
+        let mut session = Session::new(DiceGenMock::new(&[Dice::new(6, 4)]));
+        let started = session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"newMatch","length":3,"dice":[3,1]}"#),
+        );
+        assert_eq!(started.len(), 1);
+        // When
+        let messages = session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"move","play":[{"from":8,"to":5},{"from":6,"to":5}]}"#),
+        );
+        // Then
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(
+            messages[0],
+            ServerMessage::BotRolls { dice: [6, 4], .. }
+        ));
+        let ServerMessage::State(state) = &messages[1] else {
+            panic!("The last message is the state.");
+        };
+        assert_eq!(state.turn, Side::Human);
+        assert_eq!(state.dice, None);
+        assert_eq!(state.pips[5], 2);
+        assert_eq!(state.length, Some(3));
+    }
+
+    #[test]
+    fn illegal_move_is_rejected() {
+        // Given
+        let mut session = Session::new(DiceGenMock::new(&[]));
+        session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"newMatch","dice":[3,1]}"#),
+        );
+        // When
+        let messages = session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"move","play":[{"from":8,"to":6}]}"#),
+        );
+        // Then
+        assert_eq!(
+            messages[0],
+            ServerMessage::Error {
+                message: "This is not a legal move for the given position and dice.".to_string()
+            }
+        );
+        let ServerMessage::State(state) = &messages[1] else {
+            panic!("The last message is the state.");
+        };
+        assert_eq!(state.dice, Some([3, 1]));
+    }
+
+    #[test]
+    fn bot_passes_in_money_game() {
+        // Given
+        let mut session = Session::new(DiceGenMock::new(&[Dice::new(6, 4)]));
+        session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"newMatch","dice":[3,1]}"#),
+        );
+        session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"move","play":[{"from":8,"to":5},{"from":6,"to":5}]}"#),
+        );
+        // When
+        let messages = session.handle(&EvaluatorFake {}, message(r#"{"type":"double"}"#));
+        // Then
+        // With two checkers on the 5 point the human wins 80% of the games.
+        assert_eq!(messages[0], ServerMessage::BotPasses);
+        assert_eq!(
+            messages[1],
+            ServerMessage::GameOver {
+                winner: Side::Human,
+                points: 1
+            }
+        );
+        assert!(matches!(messages[2], ServerMessage::State(_)));
+    }
+
+    #[test]
+    fn bot_takes_when_passing_loses_the_match() {
+        // Given
+        let mut session = Session::new(DiceGenMock::new(&[Dice::new(6, 4)]));
+        session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"newMatch","length":1,"dice":[3,1]}"#),
+        );
+        session.handle(
+            &EvaluatorFake {},
+            message(r#"{"type":"move","play":[{"from":8,"to":5},{"from":6,"to":5}]}"#),
+        );
+        // When
+        let messages = session.handle(&EvaluatorFake {}, message(r#"{"type":"double"}"#));
+        // Then
+        // Same position as in the money game, but at 1-away 1-away a pass loses the match.
+        assert_eq!(messages[0], ServerMessage::BotTakes);
+        let ServerMessage::State(state) = messages.last().unwrap() else {
+            panic!("The last message is the state.");
+        };
+        assert_eq!(state.cube, 2);
+        assert_eq!(state.cubeOwner, Some(Side::Bot));
+        assert!(!state.gameOver);
+    }
+
+    #[test]
+    fn serialized_messages() {
+        let message = ServerMessage::BotDoubles { cube: 2 };
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"type":"botDoubles","cube":2}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ServerMessage::BotTakes).unwrap(),
+            r#"{"type":"botTakes"}"#
+        );
+    }
+}
@@ -1,4 +1,8 @@
+use crate::cache::{DEFAULT_CACHE_SIZE, DEFAULT_CACHE_TTL};
+use crate::web_api::{EndpointBudgets, DEFAULT_MAX_PENDING};
 use clap::Parser;
+use engine::budget::Budget;
+use std::time::Duration;
 
 /// Command line arguments for starting the web application.
 #[derive(Parser, Debug)]
@@ -11,6 +15,92 @@ pub struct Args {
     /// The port to host the server at with a default value of "8080" when no input is provided.
     #[arg(short, long, default_value_t = String::from("8080"))]
     pub port: String,
+
+    /// Maximum search depth for the `/eval` endpoint. 1 means only the neural net is used.
+    #[arg(long, default_value_t = 1)]
+    pub eval_plies: u8,
+
+    /// Time in milliseconds after which `/eval` falls back to a shallower evaluation.
+    #[arg(long, default_value_t = 1000)]
+    pub eval_latency_ms: u64,
+
+    /// Maximum search depth for the `/move` endpoint. 1 means only the neural net is used.
+    #[arg(long, default_value_t = 1)]
+    pub move_plies: u8,
+
+    /// Time in milliseconds after which `/move` falls back to shallower evaluations.
+    #[arg(long, default_value_t = 200)]
+    pub move_latency_ms: u64,
+
+    /// Weight of the 1-ply evaluation in deeper evaluations, between 0.0 and 1.0. 0.0 disables the discount.
+    #[arg(long, default_value_t = 0.0, value_parser = parse_discount)]
+    pub search_discount: f32,
+
+    /// Maximum number of evaluations running or waiting at the same time. Further requests get status 503 with a `Retry-After` header.
+    #[arg(long, default_value_t = DEFAULT_MAX_PENDING)]
+    pub max_pending: usize,
+
+    /// Enables admin endpoints like `/admin/benchmark`. Requests need this token as bearer token.
+    #[arg(long)]
+    pub admin_token: Option<String>,
+
+    /// Maximum number of cached evaluations and, separately, of cached moves per model. 0 disables the cache.
+    #[arg(long, default_value_t = DEFAULT_CACHE_SIZE)]
+    pub cache_size: usize,
+
+    /// Time in seconds after which cached evaluations and moves are computed again.
+    #[arg(long, default_value_t = DEFAULT_CACHE_TTL.as_secs())]
+    pub cache_ttl_secs: u64,
+
+    /// Comma separated API keys. If given, requests without one of them get status 401.
+    #[arg(long, env = "WILDBG_API_KEYS", value_delimiter = ',')]
+    pub api_keys: Vec<String>,
+
+    /// Maximum number of requests per minute and client, identified by API key or IP address. Further requests get status 429.
+    #[arg(long)]
+    pub rate_limit: Option<u32>,
+
+    /// Additional contact net as `name=path/to/contact.onnx`, selected per request with `model=name`. Can be repeated.
+    #[arg(long = "model", value_parser = parse_model)]
+    pub models: Vec<(String, String)>,
+
+    /// Prints the OpenAPI specification as JSON and exits without starting the server.
+    #[arg(long)]
+    pub openapi: bool,
+}
+
+impl Args {
+    /// Latency budgets and maximum search depths of all endpoints.
+    pub fn budgets(&self) -> EndpointBudgets {
+        EndpointBudgets {
+            eval: Budget {
+                max_plies: self.eval_plies,
+                latency: Duration::from_millis(self.eval_latency_ms),
+                discount: self.search_discount,
+            },
+            moves: Budget {
+                max_plies: self.move_plies,
+                latency: Duration::from_millis(self.move_latency_ms),
+                discount: self.search_discount,
+            },
+        }
+    }
+}
+
+fn parse_discount(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(discount) if (0.0..=1.0).contains(&discount) => Ok(discount),
+        _ => Err("Expected a number between 0.0 and 1.0.".to_string()),
+    }
+}
+
+fn parse_model(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), path.to_string()))
+        }
+        _ => Err("Expected a model like 'name=path/to/contact.onnx'.".to_string()),
+    }
 }
 
 /// Parse the command line arguments and generate a web address used for starting the application
@@ -27,7 +117,20 @@ pub struct Args {
 ///
 /// let args = Args {
 ///     address: String::from("127.0.0.1"),
-///     port: String::from("8080")
+///     port: String::from("8080"),
+///     eval_plies: 1,
+///     eval_latency_ms: 1000,
+///     move_plies: 1,
+///     move_latency_ms: 200,
+///     search_discount: 0.0,
+///     max_pending: 64,
+///     cache_size: 10_000,
+///     cache_ttl_secs: 600,
+///     admin_token: None,
+///     api_keys: Vec::new(),
+///     rate_limit: None,
+///     models: Vec::new(),
+///     openapi: false,
 /// };
 ///
 /// let web_address = startup::get_web_address(&args);
@@ -1,29 +1,216 @@
+use crate::cache::{CacheStats, ResponseCache, DEFAULT_CACHE_SIZE, DEFAULT_CACHE_TTL};
+use coach::mat;
+use coach::match_analysis::{analyze_match, MatchAnalysis};
+use coach::rollout::{RolloutEvaluator, RolloutSummary, GAMES_PER_REPETITION};
+use coach::sgf;
+use engine::budget::Budget;
 use engine::composite::CompositeEvaluator;
 use engine::dice::Dice;
+use engine::dice::ALL_21;
+use engine::dice_gen::FastrandDice;
 use engine::evaluator::Evaluator;
-use engine::position::Position;
+use engine::opening_book::{opening_move, OpeningBookEvaluator};
+use engine::position::{Position, STARTING};
+use engine::probabilities::{Probabilities, ResultCounter};
 use hyper::StatusCode;
 use logic::bg_move::{BgMove, MoveDetail};
-use logic::cube::CubeInfo;
+use logic::board_svg::{BoardSvg, CubeSide};
+use logic::cube::{CubeAccess, CubeInfo, DoublingWindow, Janowski, WindowPosition};
+use logic::match_equity::{MatchEquityTable, MatchScore};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use utoipa::{IntoParams, ToSchema};
 
 pub struct WebApi<T: Evaluator> {
-    evaluator: T,
+    /// Requests in progress keep the old evaluator when it's replaced, see [WebApi::reload].
+    evaluator: RwLock<Arc<T>>,
+    budgets: EndpointBudgets,
+    max_pending: usize,
+    /// Alternative evaluators by name, selected with [ModelParams].
+    models: BTreeMap<String, WebApi<T>>,
+    caches: ResponseCaches,
+    /// Set by [WebApi::warm_up], reported at `/readyz`.
+    warmed_up: AtomicBool,
 }
 
-impl WebApi<CompositeEvaluator> {
+/// Evaluations are cached by position ID, moves additionally by dice. The budgets are the same for
+/// all requests to a [WebApi], so they are not part of the keys. Results for which the deadline of
+/// the budget ran out are not cached, they would be served instead of deeper ones for the whole TTL.
+struct ResponseCaches {
+    evaluations: ResponseCache<String, Probabilities>,
+    moves: ResponseCache<(String, Dice), MoveResponse>,
+}
+
+impl ResponseCaches {
+    fn new(max_size: usize, ttl: Duration) -> Self {
+        Self {
+            evaluations: ResponseCache::new(max_size, ttl),
+            moves: ResponseCache::new(max_size, ttl),
+        }
+    }
+}
+
+/// Default for [WebApi::max_pending].
+pub const DEFAULT_MAX_PENDING: usize = 64;
+
+/// Maximum number of positions for [WebApi::post_eval_batch].
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Maximum number of games for [WebApi::rollout], the default is [GAMES_PER_REPETITION].
+pub const MAX_ROLLOUT_GAMES: usize = 10 * GAMES_PER_REPETITION;
+
+/// How deep and how long each endpoint may evaluate.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EndpointBudgets {
+    pub eval: Budget,
+    pub moves: Budget,
+}
+
+impl WebApi<OpeningBookEvaluator<CompositeEvaluator>> {
+    /// Plays the first move of a game from the opening book, everything else with the neural nets.
     pub fn try_default() -> Option<Self> {
         match CompositeEvaluator::try_default() {
-            Ok(evaluator) => Some(Self { evaluator }),
+            Ok(evaluator) => Some(Self::new(OpeningBookEvaluator::new(evaluator))),
             Err(_) => None,
         }
     }
 }
 
 impl<T: Evaluator> WebApi<T> {
+    /// Uses the default budgets, so all endpoints evaluate only 1-ply.
     pub fn new(evaluator: T) -> Self {
-        Self { evaluator }
+        Self {
+            evaluator: RwLock::new(Arc::new(evaluator)),
+            budgets: EndpointBudgets::default(),
+            max_pending: DEFAULT_MAX_PENDING,
+            models: BTreeMap::new(),
+            caches: ResponseCaches::new(DEFAULT_CACHE_SIZE, DEFAULT_CACHE_TTL),
+            warmed_up: AtomicBool::new(false),
+        }
+    }
+
+    /// Evaluates the starting position with all models, so that the first requests of clients
+    /// don't pay for the lazy initialization of the neural nets.
+    pub fn warm_up(&self) {
+        for web_api in std::iter::once(self).chain(self.models.values()) {
+            web_api.evaluator().eval(&STARTING);
+            web_api.warmed_up.store(true, Ordering::Release);
+        }
+    }
+
+    /// `true` once [WebApi::warm_up] has finished for all models.
+    pub fn is_ready(&self) -> bool {
+        self.warmed_up.load(Ordering::Acquire) && self.models.values().all(|model| model.is_ready())
+    }
+
+    /// Each cache holds up to `max_size` responses for `ttl`; 0 disables caching. Applies to all
+    /// models, including those added later.
+    pub fn with_cache(self, max_size: usize, ttl: Duration) -> Self {
+        let models = self
+            .models
+            .into_iter()
+            .map(|(name, model)| (name, model.with_cache(max_size, ttl)))
+            .collect();
+        Self {
+            models,
+            caches: ResponseCaches::new(max_size, ttl),
+            ..self
+        }
+    }
+
+    /// Hits and misses of the caches of all models.
+    pub fn cache_stats(&self) -> CacheStats {
+        let own = self.caches.evaluations.stats() + self.caches.moves.stats();
+        self.models
+            .values()
+            .fold(own, |stats, model| stats + model.cache_stats())
+    }
+
+    /// The budgets apply to all models, including those added later.
+    pub fn with_budgets(self, budgets: EndpointBudgets) -> Self {
+        let models = self
+            .models
+            .into_iter()
+            .map(|(name, model)| (name, model.with_budgets(budgets)))
+            .collect();
+        Self {
+            budgets,
+            models,
+            ..self
+        }
+    }
+
+    /// Adds an evaluator which clients select with the query parameter `model`, for example to
+    /// compare a new net with the current one. Requests without `model` use the default evaluator.
+    pub fn with_model(mut self, name: String, evaluator: T) -> Self {
+        let caches = &self.caches.evaluations;
+        let model = WebApi::new(evaluator)
+            .with_budgets(self.budgets)
+            .with_cache(caches.max_size(), caches.ttl());
+        self.models.insert(name, model);
+        self
+    }
+
+    /// The default model for `None`, otherwise the one added with [WebApi::with_model].
+    pub fn model(&self, name: Option<&str>) -> Result<&Self, String> {
+        match name {
+            None => Ok(self),
+            Some(name) => self.models.get(name).ok_or_else(|| {
+                let names: Vec<&str> = self.models.keys().map(String::as_str).collect();
+                format!(
+                    "Unknown model '{name}'. Available models: {}.",
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                )
+            }),
+        }
+    }
+
+    pub fn with_max_pending(self, max_pending: usize) -> Self {
+        Self {
+            max_pending,
+            ..self
+        }
+    }
+
+    /// How many evaluations may run or wait at the same time. Further requests are rejected.
+    pub fn max_pending(&self) -> usize {
+        self.max_pending
+    }
+
+    /// Used by play sessions, which keep their own game state, see [crate::session].
+    pub(crate) fn evaluator(&self) -> Arc<T> {
+        self.evaluator
+            .read()
+            .expect("The evaluator is never poisoned.")
+            .clone()
+    }
+
+    /// Replaces the evaluator for all following requests without waiting for running ones.
+    pub fn replace_evaluator(&self, evaluator: T) {
+        *self
+            .evaluator
+            .write()
+            .expect("The evaluator is never poisoned.") = Arc::new(evaluator);
+        self.caches.evaluations.clear();
+        self.caches.moves.clear();
+    }
+
+    /// Evaluation with the budget of `/eval`, also used by `/cube`.
+    fn evaluate(&self, position: &Position) -> Probabilities {
+        self.caches
+            .evaluations
+            .get_or_insert_if(position.position_id(), Instant::now(), || {
+                let budget = &self.budgets.eval;
+                let (evaluation, plies) = budget.eval(self.evaluator().as_ref(), position);
+                (evaluation, plies >= budget.max_plies.min(2))
+            })
     }
 
     pub fn get_eval(&self, pip_params: PipParams) -> Result<EvalResponse, (StatusCode, String)> {
@@ -31,7 +218,7 @@ impl<T: Evaluator> WebApi<T> {
         match position {
             Err(error) => Err((StatusCode::BAD_REQUEST, error.to_string())),
             Ok(position) => {
-                let evaluation = self.evaluator.eval(&position);
+                let evaluation = self.evaluate(&position);
                 let cube = CubeInfo::from(&evaluation);
                 let probabilities = ProbabilitiesView::from(evaluation);
                 Ok(EvalResponse {
@@ -42,6 +229,127 @@ impl<T: Evaluator> WebApi<T> {
         }
     }
 
+    /// Evaluates all positions of the request with a single batched call of the evaluator.
+    ///
+    /// The evaluations are always 1-ply, independent of the budget for `/eval`.
+    pub fn post_eval_batch(&self, request: BatchRequest) -> Result<BatchResponse, String> {
+        if request.positionIds.len() > MAX_BATCH_SIZE {
+            return Err(format!(
+                "At most {MAX_BATCH_SIZE} positions can be evaluated in one request."
+            ));
+        }
+        let positions = request
+            .positionIds
+            .iter()
+            .map(|id| Position::try_from_id(id).map_err(|error| format!("'{id}': {error}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let evaluations = self
+            .evaluator()
+            .eval_batch(positions)
+            .into_iter()
+            .zip(request.positionIds)
+            .map(|((_, probabilities), position_id)| BatchEvaluation {
+                positionId: position_id,
+                equity: probabilities.equity(),
+                probabilities: probabilities.into(),
+            })
+            .collect();
+        Ok(BatchResponse { evaluations })
+    }
+
+    /// Double and take decisions for `x`, who is on roll and hasn't rolled yet.
+    ///
+    /// For money games the cubeful equities are derived from the cubeless evaluation with
+    /// [Janowski]'s model. At a match score the doubling window of the default [MatchEquityTable]
+    /// is used and the equities are normalized match equities. `x` may only double with a centered
+    /// or own cube, never in the Crawford game and not when the cube is already dead for `x`.
+    pub fn get_cube(
+        &self,
+        pip_params: PipParams,
+        cube_params: CubeParams,
+    ) -> Result<CubeResponse, (StatusCode, String)> {
+        let bad_request = |message: &str| (StatusCode::BAD_REQUEST, message.to_string());
+        let position = Position::try_from(pip_params).map_err(bad_request)?;
+        let value = cube_params.cube.unwrap_or(1);
+        if !value.is_power_of_two() {
+            return Err(bad_request("The cube value must be a power of 2."));
+        }
+        let owner = cube_params.owner.as_deref().unwrap_or("centered");
+        let access = match owner {
+            "centered" => CubeAccess::Centered,
+            "x" => CubeAccess::Owned,
+            "o" => CubeAccess::Opponent,
+            _ => {
+                return Err(bad_request(
+                    "The cube owner must be 'x', 'o' or 'centered'.",
+                ))
+            }
+        };
+        if owner == "centered" && value != 1 {
+            return Err(bad_request("A centered cube must have the value 1."));
+        }
+        let score = match (cube_params.x_away, cube_params.o_away) {
+            (None, None) => None,
+            (Some(x_away), Some(o_away)) if x_away > 0 && o_away > 0 => Some(MatchScore {
+                x_away,
+                o_away,
+                crawford: cube_params.crawford.unwrap_or(false),
+            }),
+            _ => {
+                return Err(bad_request(
+                    "Give both scores as points away, each at least 1.",
+                ))
+            }
+        };
+        let may_double = access != CubeAccess::Opponent
+            && !score.is_some_and(|score| score.crawford || score.x_away <= value);
+        let evaluation = self.evaluate(&position);
+        let (double, take, equities, window) = match score {
+            None => {
+                let janowski = Janowski::default();
+                let [no_double, double_take, double_pass] =
+                    janowski.cube_equities(&evaluation, access);
+                (
+                    janowski.should_double(&evaluation, access),
+                    janowski.should_take(&evaluation),
+                    [no_double, double_take, double_pass],
+                    janowski.window(&evaluation, access),
+                )
+            }
+            Some(score) => {
+                let met = MatchEquityTable::default();
+                let window = met.window(&evaluation, &score, value);
+                let normalized = |mwc: f32| met.normalized_equity(mwc, &score, value);
+                let double_pass = met.mwc_at(&score.after_game(value, 0));
+                (
+                    matches!(
+                        window.position(),
+                        WindowPosition::DoubleTake | WindowPosition::DoublePass
+                    ),
+                    window.win < window.cash_point,
+                    [
+                        normalized(met.mwc(&evaluation, &score, value)),
+                        normalized(met.mwc(&evaluation, &score, 2 * value)),
+                        normalized(double_pass),
+                    ],
+                    window,
+                )
+            }
+        };
+        let [no_double, double_take, double_pass] = equities;
+        Ok(CubeResponse {
+            double: may_double && double,
+            take,
+            equities: CubeEquities {
+                noDouble: no_double,
+                doubleTake: double_take,
+                doublePass: double_pass,
+            },
+            windowPosition: window.position(),
+            window,
+        })
+    }
+
     pub fn get_move(
         &self,
         pip_params: PipParams,
@@ -49,23 +357,189 @@ impl<T: Evaluator> WebApi<T> {
     ) -> Result<MoveResponse, &'static str> {
         let position = Position::try_from(pip_params)?;
         let dice = Dice::try_from((dice_params.die1, dice_params.die2))?;
-        let pos_and_probs = self
-            .evaluator
-            .positions_and_probabilities_by_equity(&position, &dice);
-        let moves: Vec<MoveInfo> = pos_and_probs
-            .into_iter()
-            .map(|(new_pos, probabilities)| {
-                let bg_move = BgMove::new(&position, &new_pos, &dice);
-                let play = bg_move.into_details();
-                let probabilities = probabilities.into(); // convert model into view model
-                MoveInfo {
-                    play,
-                    probabilities,
-                }
-            })
-            .collect();
-        Ok(MoveResponse { moves })
+        let key = (position.position_id(), dice);
+        Ok(self.caches.moves.get_or_insert_if(key, Instant::now(), || {
+            let (pos_and_probs, degraded) = self
+                .budgets
+                .moves
+                .positions_and_probabilities_by_equity(self.evaluator().as_ref(), &position, &dice);
+            let moves: Vec<MoveInfo> = pos_and_probs
+                .into_iter()
+                .map(|(new_pos, probabilities)| {
+                    let bg_move = BgMove::new(&position, &new_pos, &dice);
+                    let play = bg_move.into_details();
+                    let probabilities = probabilities.into(); // convert model into view model
+                    MoveInfo {
+                        play,
+                        probabilities,
+                    }
+                })
+                .collect();
+            (MoveResponse { moves }, !degraded)
+        }))
+    }
+
+    /// Compares an uploaded contact net with the nets of this server, using the default race net.
+    ///
+    /// Returns an error if the uploaded net can't be used, for example because it expects other inputs.
+    pub fn benchmark(&self, contact_net: &[u8]) -> Result<BenchmarkResponse, String> {
+        let candidate = CompositeEvaluator::from_contact_bytes(contact_net)?;
+        Ok(BenchmarkResponse::compare(
+            self.evaluator().as_ref(),
+            &candidate,
+        ))
+    }
+
+    /// Analyzes all checker plays and cube decisions of a match file, see [analyze_match].
+    ///
+    /// Files ending with `.sgf` or starting like an SGF file are read as GNUBG SGF, all others as
+    /// `.mat` file.
+    pub fn analyze_match_file(
+        &self,
+        file_name: Option<&str>,
+        content: &[u8],
+    ) -> Result<MatchAnalysis, String> {
+        let content = std::str::from_utf8(content)
+            .map_err(|_| "The match file must be encoded as UTF-8.".to_string())?;
+        let is_sgf = file_name.is_some_and(|name| name.to_lowercase().ends_with(".sgf"))
+            || content.trim_start().starts_with("(;");
+        let mat_match = if is_sgf {
+            sgf::to_mat_match(&sgf::parse(content)?)?
+        } else {
+            mat::parse(content)?
+        };
+        analyze_match(self.evaluator().as_ref(), &mat_match)
+    }
+}
+
+impl<T: FromContactNet> WebApi<T> {
+    /// Replaces the contact net, for example with a newly trained one, see [WebApi::replace_evaluator].
+    pub fn reload(&self, contact_net: &[u8]) -> Result<(), String> {
+        self.replace_evaluator(T::from_contact_net(contact_net)?);
+        Ok(())
+    }
+}
+
+/// Evaluators which can be built from an uploaded contact net, so that [WebApi::reload] works.
+pub trait FromContactNet: Evaluator + Sized {
+    fn from_contact_net(contact_net: &[u8]) -> Result<Self, String>;
+}
+
+impl FromContactNet for OpeningBookEvaluator<CompositeEvaluator> {
+    /// Uses the default race net.
+    fn from_contact_net(contact_net: &[u8]) -> Result<Self, String> {
+        CompositeEvaluator::from_contact_bytes(contact_net).map(OpeningBookEvaluator::new)
+    }
+}
+
+impl<T: Evaluator + Sync> WebApi<T> {
+    /// Rolls out `position` in groups of [GAMES_PER_REPETITION] games and reports the results
+    /// so far after each group.
+    ///
+    /// Stops early if `progress` returns `false`, for example because the client has disconnected.
+    pub fn rollout(
+        &self,
+        rollout: &ValidRollout,
+        mut progress: impl FnMut(RolloutProgress) -> bool,
+    ) {
+        let net = self.evaluator();
+        let evaluator = RolloutEvaluator::with_evaluator_and_seed(
+            BorrowedEvaluator(net.as_ref()),
+            rollout.seed,
+        );
+        let mut results = ResultCounter::default();
+        evaluator.results_with_progress(
+            &rollout.position,
+            rollout.seed,
+            rollout.repetitions,
+            |repetition| {
+                results = std::mem::take(&mut results).combine(repetition);
+                let summary = RolloutSummary::from(&results);
+                progress(RolloutProgress {
+                    games: summary.games,
+                    totalGames: (rollout.repetitions * GAMES_PER_REPETITION) as u64,
+                    equity: summary.probabilities.equity(),
+                    equityError: summary.equity_error,
+                    probabilities: summary.probabilities.into(),
+                })
+            },
+        );
+    }
+}
+
+/// [RolloutEvaluator] owns its evaluator, but the one of [WebApi] is shared by all requests.
+struct BorrowedEvaluator<'a, T: Evaluator>(&'a T);
+
+impl<T: Evaluator> Evaluator for BorrowedEvaluator<'_, T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        self.0.eval(pos)
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        self.0.eval_batch(positions)
+    }
+}
+
+/// Parameters of [WebApi::rollout], checked before the rollout starts.
+pub struct ValidRollout {
+    position: Position,
+    repetitions: usize,
+    seed: u64,
+}
+
+impl ValidRollout {
+    pub fn try_new(pip_params: PipParams, params: RolloutParams) -> Result<Self, String> {
+        let position = Position::try_from(pip_params)?;
+        if position.has_lost() || position.sides_switched().has_lost() {
+            return Err("The game is already over.".to_string());
+        }
+        let games = params.games.unwrap_or(GAMES_PER_REPETITION);
+        if games == 0 || games > MAX_ROLLOUT_GAMES {
+            return Err(format!(
+                "The number of games must be between 1 and {MAX_ROLLOUT_GAMES}."
+            ));
+        }
+        Ok(Self {
+            position,
+            repetitions: games.div_ceil(GAMES_PER_REPETITION),
+            seed: params.seed.unwrap_or_else(FastrandDice::random_seed),
+        })
+    }
+}
+
+/// SVG image of the position with optional dice and cube. No neural net is needed for this.
+pub fn get_board(pip_params: PipParams, board_params: BoardParams) -> Result<String, &'static str> {
+    let position = Position::try_from(pip_params)?;
+    let mut board = BoardSvg::new(position);
+    match (board_params.die1, board_params.die2) {
+        (Some(die1), Some(die2)) => board = board.with_dice(Dice::try_from((die1, die2))?),
+        (None, None) => {}
+        _ => return Err("Either give both dice or none."),
     }
+    let side = match board_params.owner.as_deref() {
+        None | Some("centered") => CubeSide::Centered,
+        Some("x") => CubeSide::X,
+        Some("o") => CubeSide::O,
+        Some(_) => return Err("The cube owner must be 'x', 'o' or 'centered'."),
+    };
+    match board_params.cube {
+        Some(value) if value.is_power_of_two() => board = board.with_cube(value, side),
+        Some(_) => return Err("The cube value must be a power of 2."),
+        None if side != CubeSide::Centered => return Err("A cube owner needs a cube value."),
+        None => {}
+    }
+    Ok(board.render())
+}
+
+/// Decisions used for comparing neural nets: the positions after each opening move,
+/// combined with all 21 rolls of the opponent.
+fn benchmark_suite() -> Vec<(Position, Dice)> {
+    let openings = ALL_21
+        .iter()
+        .filter_map(|(dice, _)| opening_move(&STARTING, dice));
+    openings
+        .flat_map(|position| ALL_21.iter().map(move |(dice, _)| (position, *dice)))
+        .collect()
 }
 
 #[derive(Serialize, ToSchema)]
@@ -76,7 +550,112 @@ pub struct EvalResponse {
     probabilities: ProbabilitiesView,
 }
 
+#[derive(Deserialize, ToSchema)]
+#[allow(non_snake_case)]
+/// The body of a batch request.
+pub struct BatchRequest {
+    /// GnuBG position IDs, each from the point of view of the player on roll.
+    #[schema(example = json!(["4HPwATDgc/ABMA", "4HPhASLgc/ABMA"]))]
+    positionIds: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+/// The whole body of the HTTP response for a batch request.
+pub struct BatchResponse {
+    /// In the same order as the position IDs of the request.
+    evaluations: Vec<BatchEvaluation>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(non_snake_case)]
+/// Evaluation of a single position of a batch request.
+pub struct BatchEvaluation {
+    positionId: String,
+    probabilities: ProbabilitiesView,
+    /// Cubeless money game equity.
+    equity: f32,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(non_snake_case)]
+/// Intermediate result of a rollout, sent after each group of 1296 games.
+pub struct RolloutProgress {
+    /// Games rolled out so far.
+    games: u64,
+    /// Games after which the rollout is finished, a multiple of 1296.
+    totalGames: u64,
+    probabilities: ProbabilitiesView,
+    /// Cubeless money game equity.
+    equity: f32,
+    /// Standard error of `equity`, as if all games were independent.
+    equityError: f32,
+}
+
 #[derive(Serialize, ToSchema)]
+#[allow(non_snake_case)]
+/// Cube decisions of both players for the position before `x` rolls.
+pub struct CubeResponse {
+    /// `true` if `x` should double. Always `false` if `x` may not double.
+    double: bool,
+    /// `true` if `o` should take a double of `x`.
+    take: bool,
+    equities: CubeEquities,
+    /// Thresholds of the winning chances of `x` for the gammon rates of this position.
+    window: DoublingWindow,
+    /// Where the winning chances of `x` are in `window`.
+    windowPosition: WindowPosition,
+}
+
+/// Equities of `x` for the three possible outcomes of the cube decision.
+///
+/// They are normalized to the current cube value and derived from the cubeless evaluation. At a
+/// match score they are normalized match equities.
+#[derive(Serialize, ToSchema)]
+#[allow(non_snake_case)]
+pub struct CubeEquities {
+    noDouble: f32,
+    doubleTake: f32,
+    doublePass: f32,
+}
+
+#[derive(Debug, Serialize)]
+/// Comparison of an uploaded neural net with the neural nets of the running server.
+pub struct BenchmarkResponse {
+    /// Number of checker plays in the benchmark suite.
+    decisions: usize,
+    /// Share of decisions in which both nets choose the same move.
+    agreement: f32,
+    /// Average absolute difference of the cubeless equity, evaluated after the moves chosen by the server.
+    mean_equity_difference: f32,
+    max_equity_difference: f32,
+}
+
+impl BenchmarkResponse {
+    fn compare<T: Evaluator, U: Evaluator>(current: &T, candidate: &U) -> Self {
+        let suite = benchmark_suite();
+        let mut same_moves = 0;
+        let mut total_difference = 0.0;
+        let mut max_equity_difference: f32 = 0.0;
+        for (position, dice) in &suite {
+            let best = current.best_position_by_equity(position, dice);
+            if candidate.best_position_by_equity(position, dice) == best {
+                same_moves += 1;
+            }
+            let difference = (current.eval(&best).equity() - candidate.eval(&best).equity()).abs();
+            total_difference += difference;
+            max_equity_difference = max_equity_difference.max(difference);
+        }
+        let decisions = suite.len();
+        Self {
+            decisions,
+            agreement: same_moves as f32 / decisions as f32,
+            mean_equity_difference: total_difference / decisions as f32,
+            max_equity_difference,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
 /// The whole body of the HTTP response. Contains the list of all legal moves.
 pub struct MoveResponse {
     /// The array is ordered by match equity. First move is the best one.
@@ -86,7 +665,7 @@ pub struct MoveResponse {
     moves: Vec<MoveInfo>,
 }
 
-#[derive(Serialize, ToSchema)]
+#[derive(Clone, Serialize, ToSchema)]
 /// This represents one complete move.
 pub struct MoveInfo {
     /// Contains 0 to 4 elements for moving a single checker.
@@ -105,7 +684,7 @@ pub struct MoveInfo {
 /// `winG` includes the chances to win BG and `loseG` includes the chance to lose BG.
 /// This way we use the same format as earlier engines like GnuBG have done.
 /// `lose` is not given, you can calculate it through `1 - win`.
-#[derive(Serialize, ToSchema)]
+#[derive(Clone, Serialize, ToSchema)]
 #[allow(non_snake_case)]
 #[schema(title = "Probabilities")]
 pub struct ProbabilitiesView {
@@ -126,7 +705,7 @@ impl From<engine::probabilities::Probabilities> for ProbabilitiesView {
             win: value.win_normal + value.win_gammon,
             winG: value.win_gammon,
             loseG: value.lose_gammon,
-=        }
+        }
     }
 }
 
@@ -138,8 +717,60 @@ pub struct DiceParams {
     die2: usize,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CubeParams {
+    /// Value of the cube, 1 if not given.
+    #[param(minimum = 1, example = 1)]
+    cube: Option<u32>,
+    /// Owner of the cube: `x`, `o` or `centered`, which is the default.
+    #[param(example = "centered")]
+    owner: Option<String>,
+    /// Points `x` still needs to win the match. Money game if no score is given.
+    #[param(minimum = 1)]
+    x_away: Option<u32>,
+    /// Points `o` still needs to win the match.
+    #[param(minimum = 1)]
+    o_away: Option<u32>,
+    /// `true` in the Crawford game, when nobody may double.
+    crawford: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+pub struct ModelParams {
+    /// Name of an alternative neural net of the server. The default net is used if not given.
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RolloutParams {
+    /// Number of games, rounded up to a multiple of 1296. The default is 1296.
+    #[param(minimum = 1, maximum = 12960, example = 2592)]
+    games: Option<usize>,
+    /// Seed for the dice. The same seed always gives the same results. Random if not given.
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct BoardParams {
+    /// Dice of `x`, only drawn if both dice are given.
+    #[param(minimum = 1, maximum = 6, example = 3)]
+    die1: Option<usize>,
+    #[param(minimum = 1, maximum = 6, example = 1)]
+    die2: Option<usize>,
+    /// Value of the cube. Without it, no cube is drawn.
+    #[param(minimum = 1, example = 2)]
+    cube: Option<u32>,
+    /// Owner of the cube: `x`, `o` or `centered`, which is the default.
+    #[param(example = "x")]
+    owner: Option<String>,
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct PipParams {
+    /// Position as XGID, for example copied from eXtreme Gammon or a forum post.
+    /// Use it instead of the single pips; cube, dice and score of the XGID are ignored.
+    #[param(example = "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:0:0:10")]
+    xgid: Option<String>,
     /// Bar for the opponent `o`.
     #[param(minimum = -15, maximum = 0)]
     p0: Option<i8>,
@@ -200,36 +831,19 @@ impl TryFrom<PipParams> for Position {
     type Error = &'static str;
 
     fn try_from(params: PipParams) -> Result<Self, Self::Error> {
-        // let params = params.pips;
-        let pips: [i8; 26] = [
-            params.p0.unwrap_or_default(),
-            params.p1.unwrap_or_default(),
-            params.p2.unwrap_or_default(),
-            params.p3.unwrap_or_default(),
-            params.p4.unwrap_or_default(),
-            params.p5.unwrap_or_default(),
-            params.p6.unwrap_or_default(),
-            params.p7.unwrap_or_default(),
-            params.p8.unwrap_or_default(),
-            params.p9.unwrap_or_default(),
-            params.p10.unwrap_or_default(),
-            params.p11.unwrap_or_default(),
-            params.p12.unwrap_or_default(),
-            params.p13.unwrap_or_default(),
-            params.p14.unwrap_or_default(),
-            params.p15.unwrap_or_default(),
-            params.p16.unwrap_or_default(),
-            params.p17.unwrap_or_default(),
-            params.p18.unwrap_or_default(),
-            params.p19.unwrap_or_default(),
-            params.p20.unwrap_or_default(),
-            params.p21.unwrap_or_default(),
-            params.p22.unwrap_or_default(),
-            params.p23.unwrap_or_default(),
-            params.p24.unwrap_or_default(),
-            params.p25.unwrap_or_default(),
+        let pips: [Option<i8>; 26] = [
+            params.p0, params.p1, params.p2, params.p3, params.p4, params.p5, params.p6, params.p7,
+            params.p8, params.p9, params.p10, params.p11, params.p12, params.p13, params.p14,
+            params.p15, params.p16, params.p17, params.p18, params.p19, params.p20, params.p21,
+            params.p22, params.p23, params.p24, params.p25,
         ];
-        Position::try_from(pips)
+        match params.xgid {
+            Some(_) if pips.iter().any(Option::is_some) => {
+                Err("Give the position either as XGID or as single pips, not both.")
+            }
+            Some(xgid) => Ok(Position::from_xgid(&xgid)?),
+            None => Ok(Position::try_from(pips.map(Option::unwrap_or_default))?),
+        }
     }
 }
 
@@ -239,9 +853,9 @@ mod probabilities_tests {
     fn from() {
         let model_probs = engine::probabilities::Probabilities {
             win_normal: 0.32,
-            win_gammon: 0.26,
+            win_gammon: 0.38,
             lose_normal: 0.15,
-            lose_gammon: 0.1,
+            lose_gammon: 0.15,
         };
 
         let view_probs: crate::web_api::ProbabilitiesView = model_probs.into();
@@ -250,3 +864,197 @@ mod probabilities_tests {
         assert_eq!(view_probs.loseG, 0.15);
     }
 }
+
+#[cfg(test)]
+mod benchmark_tests {
+    use crate::web_api::WebApi;
+    use engine::composite::CompositeEvaluator;
+
+    #[test]
+    fn same_net_agrees_on_all_decisions() {
+        // Given
+        let web_api = WebApi::new(CompositeEvaluator::default_tests());
+        let contact_net = include_bytes!("../../../neural-nets/contact.onnx");
+        // When
+        let response = web_api.benchmark(contact_net).unwrap();
+        // Then
+        assert_eq!(response.decisions, 15 * 21);
+        assert_eq!(response.agreement, 1.0);
+        assert_eq!(response.max_equity_difference, 0.0);
+    }
+
+    #[test]
+    fn race_net_is_rejected() {
+        let web_api = WebApi::new(CompositeEvaluator::default_tests());
+        let race_net = include_bytes!("../../../neural-nets/race.onnx");
+        let result = web_api.benchmark(race_net);
+        assert_eq!(
+            result.unwrap_err(),
+            "The neural net was trained for inputs version race-1, but contact-1 is needed."
+        );
+    }
+
+    #[test]
+    fn invalid_net_is_rejected() {
+        let web_api = WebApi::new(CompositeEvaluator::default_tests());
+        let result = web_api.benchmark(b"no onnx");
+        assert_eq!(
+            result.unwrap_err(),
+            "The neural net is no valid onnx model."
+        );
+    }
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use crate::web_api::WebApi;
+    use coach::match_analysis::DecisionKind;
+    use engine::test_utils::EvaluatorFake;
+
+    const MATCH: &str = " 5 point match
+
+ Game 1
+ Alice : 0                          Bob : 0
+  1) 31: 24/21 24/23                64: 24/18 13/9
+";
+
+    #[test]
+    fn mat_and_sgf_give_same_analysis() {
+        // Given
+        let web_api = WebApi::new(EvaluatorFake {});
+        let mat_match = coach::mat::parse(MATCH).unwrap();
+        let sgf = coach::sgf::write(&coach::sgf::from_mat_match(&mat_match));
+        // When
+        let from_mat = web_api.analyze_match_file(Some("match.mat"), MATCH.as_bytes());
+        let from_sgf = web_api.analyze_match_file(None, sgf.as_bytes());
+        // Then
+        let from_mat = from_mat.unwrap();
+        assert_eq!(from_mat.decisions[0].kind, DecisionKind::CheckerPlay);
+        assert!(from_mat.decisions[0].equity_loss > 0.0);
+        assert_eq!(from_sgf.unwrap(), from_mat);
+    }
+
+    #[test]
+    fn unreadable_file() {
+        let web_api = WebApi::new(EvaluatorFake {});
+        let result = web_api.analyze_match_file(Some("match.mat"), &[0xFF, 0xFE]);
+        assert_eq!(
+            result.unwrap_err(),
+            "The match file must be encoded as UTF-8."
+        );
+    }
+}
+
+#[cfg(test)]
+mod model_tests {
+    use crate::cache::CacheStats;
+    use crate::web_api::{EndpointBudgets, PipParams, WebApi};
+    use engine::budget::Budget;
+    use engine::evaluator::Evaluator;
+    use engine::noise::{Difficulty, NoisyEvaluator};
+    use engine::position::Position;
+    use engine::test_utils::EvaluatorFake;
+    use std::time::Duration;
+
+    /// Two models which evaluate the position of [pips] differently.
+    fn evaluator(difficulty: Difficulty) -> NoisyEvaluator<EvaluatorFake> {
+        NoisyEvaluator::with_difficulty(EvaluatorFake {}, difficulty, 1)
+    }
+
+    fn win(difficulty: Difficulty) -> f32 {
+        let position = Position::try_from(pips()).unwrap();
+        evaluator(difficulty).eval(&position).win()
+    }
+
+    fn pips() -> PipParams {
+        serde_json::from_str(r#"{"p1": 1, "p24": -1}"#).unwrap()
+    }
+
+    #[test]
+    fn select_model_by_name() {
+        // Given
+        let web_api = WebApi::new(evaluator(Difficulty::Expert))
+            .with_model("new".to_string(), evaluator(Difficulty::Beginner));
+        // When
+        let default = web_api.model(None).unwrap().get_eval(pips()).unwrap();
+        let new = web_api
+            .model(Some("new"))
+            .unwrap()
+            .get_eval(pips())
+            .unwrap();
+        // Then
+        assert_eq!(default.probabilities.win, 0.5);
+        assert_eq!(new.probabilities.win, win(Difficulty::Beginner));
+        assert_ne!(new.probabilities.win, 0.5);
+    }
+
+    #[test]
+    fn replace_evaluator_clears_cache() {
+        // Given
+        let web_api = WebApi::new(evaluator(Difficulty::Expert));
+        web_api.get_eval(pips()).unwrap();
+        let cached = web_api.get_eval(pips()).unwrap();
+        // When
+        web_api.replace_evaluator(evaluator(Difficulty::Beginner));
+        let replaced = web_api.get_eval(pips()).unwrap();
+        // Then
+        assert_eq!(cached.probabilities.win, 0.5);
+        assert_eq!(replaced.probabilities.win, win(Difficulty::Beginner));
+        assert_eq!(web_api.cache_stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[test]
+    fn results_cut_short_by_the_deadline_are_not_cached() {
+        // Given
+        let budget = Budget {
+            max_plies: 2,
+            latency: Duration::ZERO,
+            discount: 0.0,
+        };
+        let web_api = WebApi::new(EvaluatorFake {}).with_budgets(EndpointBudgets {
+            eval: budget,
+            moves: budget,
+        });
+        // Several moves, so that there is something to evaluate deeper.
+        let move_pips = || serde_json::from_str(r#"{"p8": 1, "p6": 1, "p24": -1}"#).unwrap();
+        let dice = || serde_json::from_str(r#"{"die1": 2, "die2": 1}"#).unwrap();
+        // When
+        for _ in 0..2 {
+            web_api.get_eval(pips()).unwrap();
+            web_api.get_move(move_pips(), dice()).unwrap();
+        }
+        // Then
+        assert_eq!(web_api.cache_stats(), CacheStats { hits: 0, misses: 4 });
+    }
+
+    #[test]
+    fn moves_state_hits() {
+        // Given
+        let web_api = WebApi::new(EvaluatorFake {});
+        let pips = serde_json::from_str(r#"{"p5": 1, "p4": -1}"#).unwrap();
+        let dice = serde_json::from_str(r#"{"die1": 3, "die2": 1}"#).unwrap();
+        // When
+        let response = web_api.get_move(pips, dice).unwrap();
+        // Then
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains(r#"{"from":5,"to":4,"hit":true}"#));
+        assert!(json.contains(r#"{"from":5,"to":2,"hit":false}"#));
+    }
+
+    #[test]
+    fn ready_after_warm_up_of_all_models() {
+        let web_api = WebApi::new(EvaluatorFake {}).with_model("new".to_string(), EvaluatorFake {});
+        assert!(!web_api.is_ready());
+        web_api.warm_up();
+        assert!(web_api.is_ready());
+    }
+
+    #[test]
+    fn unknown_model() {
+        let web_api = WebApi::new(EvaluatorFake {})
+            .with_model("a".to_string(), EvaluatorFake {})
+            .with_model("b".to_string(), EvaluatorFake {});
+        let error = web_api.model(Some("c")).err().unwrap();
+        assert_eq!(error, "Unknown model 'c'. Available models: a, b.");
+    }
+}
@@ -1,15 +1,15 @@
 use core::ffi::*;
 use engine::composite::CompositeEvaluator;
 use engine::dice::Dice;
+use engine::opening_book::OpeningBookEvaluator;
 use engine::position::Position;
 use engine::probabilities::Probabilities;
+use logic::analysis::{AnalysisSummary, MoveRecord};
 use logic::bg_move::{BgMove, MoveDetail};
 use logic::wildbg_api::{WildbgApi, WildbgConfig};
 
 // When this file is changed, recreate the header file by executing this from the project's root:
-// touch cbindgen.toml
-// cbindgen --config cbindgen.toml --crate wildbg-c --output crates/wildbg-c/wildbg.h --lang c
-// rm cbindgen.toml
+// cbindgen --config crates/wildbg-c/cbindgen.toml --crate wildbg-c --output crates/wildbg-c/wildbg.h
 
 // For more infos about Rust -> C see
 // https://docs.rust-embedded.org/book/interoperability/rust-with-c.html
@@ -17,7 +17,7 @@ use logic::wildbg_api::{WildbgApi, WildbgConfig};
 
 // Wrap the WildbgApi into a new struct, so that we don't have to expose the CompositeEvaluator
 pub struct Wildbg {
-    api: WildbgApi<CompositeEvaluator>,
+    api: WildbgApi<OpeningBookEvaluator<CompositeEvaluator>>,
 }
 
 /// Configuration needed for the evaluation of positions.
@@ -35,15 +35,28 @@ pub struct BgConfig {
 impl From<&BgConfig> for WildbgConfig {
     fn from(value: &BgConfig) -> Self {
         if value.x_away == 0 && value.o_away == 0 {
-            Self { away: None }
+            Self {
+                away: None,
+                gammons_count: true,
+            }
         } else {
             Self {
                 away: Some((value.x_away, value.o_away)),
+                gammons_count: true,
             }
         }
     }
 }
 
+/// Version of this library, for example `0.2.1`, as null terminated string.
+///
+/// Functions and structs only change with the minor version, see the changelog.
+/// Call this to check whether the library matches the header your code was compiled with.
+#[no_mangle]
+pub extern "C" fn wildbg_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
 #[no_mangle]
 /// Loads the neural nets into memory and returns a pointer to the API.
 /// Returns `NULL` if the neural nets cannot be found.
@@ -77,10 +90,8 @@ pub struct CProbabilities {
     win: c_float,
     /// Probability to win gammon or backgammon.
     win_g: c_float,
-    /// Probability to win backgammon.
     /// Probability to lose gammon or backgammon.
     lose_g: c_float,
-    /// Probability to lose backgammon.
 }
 
 impl From<&Probabilities> for CProbabilities {
@@ -195,20 +206,119 @@ pub extern "C" fn probabilities(wildbg: &Wildbg, pips: &[c_int; 26]) -> CProbabi
     }
 }
 
+/// A single checker play of a match, used as input for `analyze_moves`.
+///
+/// Both `pips` and `played_pips` are from the point of view of the player who moved,
+/// encoded the same way as for `best_move`.
+#[repr(C)]
+pub struct CMoveRecord {
+    /// Position before the move.
+    pips: [c_int; 26],
+    die1: c_uint,
+    die2: c_uint,
+    /// Position after the move.
+    played_pips: [c_int; 26],
+}
+
+impl TryFrom<&CMoveRecord> for MoveRecord {
+    type Error = Error;
+
+    fn try_from(value: &CMoveRecord) -> Result<Self, Self::Error> {
+        Ok(MoveRecord {
+            position: Position::try_from(value.pips.map(|pip| pip as i8))?,
+            dice: Dice::try_from((value.die1 as usize, value.die2 as usize))?,
+            played: Position::try_from(value.played_pips.map(|pip| pip as i8))?,
+        })
+    }
+}
+
+/// Summary of all records passed to `analyze_moves`.
+#[repr(C)]
+#[derive(Default)]
+pub struct CAnalysisSummary {
+    /// Number of records which could be analyzed.
+    move_count: c_uint,
+    /// Number of records with an illegal position, dice or move.
+    invalid_count: c_uint,
+    total_equity_loss: c_float,
+    average_equity_loss: c_float,
+    max_equity_loss: c_float,
+}
+
+impl CAnalysisSummary {
+    fn new(summary: &AnalysisSummary, invalid_count: usize) -> Self {
+        Self {
+            move_count: summary.move_count as c_uint,
+            invalid_count: invalid_count as c_uint,
+            total_equity_loss: summary.total_equity_loss,
+            average_equity_loss: summary.average_equity_loss(),
+            max_equity_loss: summary.max_equity_loss,
+        }
+    }
+}
+
+/// Analyzes `count` checker plays, for example all moves of one player in a match.
+///
+/// For each record the equity loss compared to the best move is written to `equity_losses`.
+/// Invalid records get an equity loss of `-1` and are not part of the returned summary.
+///
+/// # Safety
+///
+/// `records` and `equity_losses` must both point to arrays with at least `count` elements.
+#[no_mangle]
+pub unsafe extern "C" fn analyze_moves(
+    wildbg: &Wildbg,
+    records: *const CMoveRecord,
+    count: usize,
+    equity_losses: *mut c_float,
+    config: &BgConfig,
+) -> CAnalysisSummary {
+    if count == 0 {
+        return CAnalysisSummary::default();
+    }
+    let records = unsafe { std::slice::from_raw_parts(records, count) };
+    let equity_losses = unsafe { std::slice::from_raw_parts_mut(equity_losses, count) };
+    let config = WildbgConfig::from(config);
+    let mut summary = AnalysisSummary::default();
+    let mut invalid_count = 0;
+    for (record, equity_loss) in records.iter().zip(equity_losses.iter_mut()) {
+        let analysis = MoveRecord::try_from(record)
+            .and_then(|record| wildbg.api.analyze_move(&record, &config));
+        match analysis {
+            Ok(analysis) => {
+                *equity_loss = analysis.equity_loss;
+                summary.add(&analysis);
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                *equity_loss = -1.0;
+                invalid_count += 1;
+            }
+        }
+    }
+    CAnalysisSummary::new(&summary, invalid_count)
+}
+
 #[cfg(test)]
 mod tests {
 
-    use crate::CProbabilities;
+    use crate::{wildbg_version, CProbabilities};
     use engine::position::X_BAR;
     use engine::{dice::Dice, pos};
 
+    #[test]
+    fn version() {
+        let version = unsafe { std::ffi::CStr::from_ptr(wildbg_version()) };
+        assert_eq!(version.to_str(), Ok(env!("CARGO_PKG_VERSION")));
+    }
+
     #[test]
     fn from_probabilities() {
         let model_probs = engine::probabilities::Probabilities {
             win_normal: 0.32,
-            win_gammon: 0.26,
+            win_gammon: 0.38,
             lose_normal: 0.15,
-            lose_gammon: 0.1,
+            lose_gammon: 0.15,
         };
 
         let c_probs: CProbabilities = (&model_probs).into();
@@ -249,6 +359,20 @@ mod tests {
         assert_eq!(c_move.details[3].to, 7);
     }
 
+    #[test]
+    fn move_record_with_illegal_dice() {
+        let mut pips = [0; 26];
+        pips[24] = 1;
+        pips[1] = -1;
+        let record = crate::CMoveRecord {
+            pips,
+            die1: 7,
+            die2: 1,
+            played_pips: pips,
+        };
+        assert!(logic::analysis::MoveRecord::try_from(&record).is_err());
+    }
+
     #[test]
     fn from_bgmove_nomoves() {
         let pos = pos!(x X_BAR:15; o 24:3, 23:3, 22:3, 21:2, 20: 2, 19: 2);
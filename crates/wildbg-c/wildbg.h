@@ -1,3 +1,6 @@
+#ifndef WILDBG_H
+#define WILDBG_H
+
 #include <stdarg.h>
 #include <stdbool.h>
 #include <stdint.h>
@@ -56,17 +59,59 @@ typedef struct CProbabilities {
    * Probability to win gammon or backgammon.
    */
   float win_g;
-  /**
-   * Probability to win backgammon.
-   */
   /**
    * Probability to lose gammon or backgammon.
    */
   float lose_g;
+} CProbabilities;
+
+/**
+ * A single checker play of a match, used as input for `analyze_moves`.
+ *
+ * Both `pips` and `played_pips` are from the point of view of the player who moved,
+ * encoded the same way as for `best_move`.
+ */
+typedef struct CMoveRecord {
   /**
-   * Probability to lose backgammon.
+   * Position before the move.
    */
-} CProbabilities;
+  int pips[26];
+  unsigned int die1;
+  unsigned int die2;
+  /**
+   * Position after the move.
+   */
+  int played_pips[26];
+} CMoveRecord;
+
+/**
+ * Summary of all records passed to `analyze_moves`.
+ */
+typedef struct CAnalysisSummary {
+  /**
+   * Number of records which could be analyzed.
+   */
+  unsigned int move_count;
+  /**
+   * Number of records with an illegal position, dice or move.
+   */
+  unsigned int invalid_count;
+  float total_equity_loss;
+  float average_equity_loss;
+  float max_equity_loss;
+} CAnalysisSummary;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Version of this library, for example `0.2.1`, as null terminated string.
+ *
+ * Functions and structs only change with the minor version, see the changelog.
+ * Call this to check whether the library matches the header your code was compiled with.
+ */
+const char *wildbg_version(void);
 
 /**
  * Loads the neural nets into memory and returns a pointer to the API.
@@ -107,3 +152,25 @@ struct CMove best_move(const struct Wildbg *wildbg,
  */
 struct CProbabilities probabilities(const struct Wildbg *wildbg,
                                     const int (*pips)[26]);
+
+/**
+ * Analyzes `count` checker plays, for example all moves of one player in a match.
+ *
+ * For each record the equity loss compared to the best move is written to `equity_losses`.
+ * Invalid records get an equity loss of `-1` and are not part of the returned summary.
+ *
+ * # Safety
+ *
+ * `records` and `equity_losses` must both point to arrays with at least `count` elements.
+ */
+struct CAnalysisSummary analyze_moves(const struct Wildbg *wildbg,
+                                      const struct CMoveRecord *records,
+                                      uintptr_t count,
+                                      float *equity_losses,
+                                      const struct BgConfig *config);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WILDBG_H */
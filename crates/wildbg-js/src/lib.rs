@@ -0,0 +1,116 @@
+use engine::composite::CompositeEvaluator;
+use engine::dice::Dice;
+use engine::opening_book::OpeningBookEvaluator;
+use engine::position::Position;
+use logic::bg_move::BgMove;
+use logic::wildbg_api::{WildbgApi, WildbgConfig};
+use wasm_bindgen::prelude::*;
+
+// Build the JavaScript package by executing this from the project's root:
+// wasm-pack build crates/wildbg-js --target web
+//
+// For more infos about Rust -> JavaScript see https://rustwasm.github.io/docs/wasm-bindgen/
+
+/// Wrap the WildbgApi into a new struct, so that we don't have to expose the CompositeEvaluator.
+///
+/// The neural nets are part of the WebAssembly binary, so everything runs in the browser.
+#[wasm_bindgen]
+pub struct Wildbg {
+    api: WildbgApi<OpeningBookEvaluator<CompositeEvaluator>>,
+}
+
+/// Cubeless money game probabilities from the point of view of the player on turn.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JsProbabilities {
+    /// Cubeless probability to win the game. This includes gammons and backgammons.
+    pub win: f32,
+    /// Probability to win gammon or backgammon.
+    pub win_g: f32,
+    /// Probability to lose gammon or backgammon.
+    pub lose_g: f32,
+    /// Cubeless money game equity.
+    pub equity: f32,
+}
+
+/// A single checker being moved.
+///
+/// `from` is an integer between 25 (bar) and 1, `to` is an integer between 24 and 0 (off).
+/// The player on turn always moves from pip 24 to pip 1.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JsMoveDetail {
+    pub from: u8,
+    pub to: u8,
+}
+
+#[wasm_bindgen]
+impl Wildbg {
+    /// Loads the neural nets which are embedded in the binary.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Result<Wildbg, JsError> {
+        let api = WildbgApi::try_default().map_err(|message| JsError::new(&message))?;
+        Ok(Self { api })
+    }
+
+    /// Probabilities for the position with the given GnuBG position ID.
+    pub fn probabilities(&self, position_id: &str) -> Result<JsProbabilities, JsError> {
+        let position = parse_position_id(position_id)?;
+        let probabilities = self.api.probabilities(&position);
+        Ok(JsProbabilities {
+            win: probabilities.win(),
+            win_g: probabilities.win_gammon,
+            lose_g: probabilities.lose_gammon,
+            equity: probabilities.equity(),
+        })
+    }
+
+    /// Best move for the position with the given GnuBG position ID.
+    ///
+    /// `x_away` and `o_away` are the points the player on turn and the opponent need to finish
+    /// the match; pass zero for both in money game. Currently only 1 pointers and money game are
+    /// supported.
+    ///
+    /// When no move is possible, the returned array is empty.
+    /// If the same checker is moved twice, this is encoded in two details.
+    pub fn best_move(
+        &self,
+        position_id: &str,
+        die1: u8,
+        die2: u8,
+        x_away: u32,
+        o_away: u32,
+    ) -> Result<Vec<JsMoveDetail>, JsError> {
+        let position = parse_position_id(position_id)?;
+        let dice = Dice::try_from((die1 as usize, die2 as usize)).map_err(JsError::new)?;
+        let config = WildbgConfig {
+            away: if x_away == 0 && o_away == 0 {
+                None
+            } else {
+                Some((x_away, o_away))
+            },
+            gammons_count: true,
+        };
+        let bg_move = self.api.best_move(&position, &dice, &config);
+        Ok(details(bg_move))
+    }
+}
+
+fn details(bg_move: BgMove) -> Vec<JsMoveDetail> {
+    bg_move
+        .into_details()
+        .iter()
+        .map(|detail| JsMoveDetail {
+            from: detail.from() as u8,
+            to: detail.to() as u8,
+        })
+        .collect()
+}
+
+fn parse_position_id(position_id: &str) -> Result<Position, JsError> {
+    Position::try_from_id(position_id).map_err(|error| {
+        JsError::new(&format!(
+            "'{position_id}' is not a legal GnuBG position ID. {error}"
+        ))
+    })
+}
@@ -0,0 +1,230 @@
+use coach::rollout::RolloutEvaluator;
+use engine::composite::CompositeEvaluator;
+use engine::dice::Dice;
+use engine::evaluator::Evaluator;
+use engine::position::GameState::Ongoing;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+// Build and install the Python module into the current virtual environment by executing this from
+// the project's root:
+// maturin develop --release -m crates/wildbg-py/Cargo.toml
+//
+// For more infos about Rust -> Python see https://pyo3.rs
+
+/// A backgammon position without match information.
+///
+/// The player on turn always moves from pip 24 to pip 1. Pip 25 is the bar of the player on turn,
+/// pip 0 the bar of the opponent. Checkers of the player on turn are positive, the opponent's
+/// checkers negative.
+#[pyclass(frozen, eq, hash, module = "wildbg_engine")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Position {
+    position: engine::position::Position,
+}
+
+#[pymethods]
+impl Position {
+    /// Creates a position from a list of 26 pips.
+    #[new]
+    fn new(pips: Vec<i8>) -> PyResult<Self> {
+        let pips: [i8; 26] = pips
+            .try_into()
+            .map_err(|_| PyValueError::new_err("A position needs exactly 26 pips."))?;
+        let position = engine::position::Position::try_from(pips)
+            .map_err(|error| PyValueError::new_err(error.message()))?;
+        Ok(Self { position })
+    }
+
+    #[staticmethod]
+    fn starting() -> Self {
+        Self {
+            position: engine::position::STARTING,
+        }
+    }
+
+    /// Creates a position from a GnuBG position ID like `4HPwATDgc/ABMA`.
+    #[staticmethod]
+    fn from_id(position_id: &str) -> PyResult<Self> {
+        let position = engine::position::Position::try_from_id(position_id).map_err(|error| {
+            PyValueError::new_err(format!(
+                "'{position_id}' is not a legal GnuBG position ID. {error}"
+            ))
+        })?;
+        Ok(Self { position })
+    }
+
+    fn position_id(&self) -> String {
+        self.position.position_id()
+    }
+
+    /// The 26 pips, see the class documentation.
+    fn pips(&self) -> Vec<i8> {
+        <[i8; 26]>::from(self.position).to_vec()
+    }
+
+    fn sides_switched(&self) -> Self {
+        Self {
+            position: self.position.sides_switched(),
+        }
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.position.game_state() != Ongoing
+    }
+
+    /// All legal positions after moving with the given dice.
+    ///
+    /// The returned positions have switched sides, so the opponent is on turn.
+    fn positions_after_moving(&self, die1: usize, die2: usize) -> PyResult<Vec<Self>> {
+        let dice = Dice::try_from((die1, die2)).map_err(PyValueError::new_err)?;
+        Ok(self
+            .position
+            .all_positions_after_moving(&dice)
+            .into_iter()
+            .map(|position| Self { position })
+            .collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Position.from_id('{}')", self.position.position_id())
+    }
+}
+
+/// Cubeless money game probabilities from the point of view of the player on turn.
+#[pyclass(frozen, get_all, module = "wildbg_engine")]
+#[derive(Clone)]
+struct Probabilities {
+    win_normal: f32,
+    win_gammon: f32,
+    lose_normal: f32,
+    lose_gammon: f32,
+}
+
+#[pymethods]
+impl Probabilities {
+    /// Probability to win the game. This includes gammons and backgammons.
+    fn win(&self) -> f32 {
+        engine::probabilities::Probabilities::from(self).win()
+    }
+
+    /// Cubeless money game equity.
+    fn equity(&self) -> f32 {
+        engine::probabilities::Probabilities::from(self).equity()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Probabilities(win_normal={}, win_gammon={}, lose_normal={}, lose_gammon={})",
+            self.win_normal, self.win_gammon, self.lose_normal, self.lose_gammon
+        )
+    }
+}
+
+impl From<engine::probabilities::Probabilities> for Probabilities {
+    fn from(value: engine::probabilities::Probabilities) -> Self {
+        Self {
+            win_normal: value.win_normal,
+            win_gammon: value.win_gammon,
+            lose_normal: value.lose_normal,
+            lose_gammon: value.lose_gammon,
+        }
+    }
+}
+
+impl From<&Probabilities> for engine::probabilities::Probabilities {
+    fn from(value: &Probabilities) -> Self {
+        Self {
+            win_normal: value.win_normal,
+            win_gammon: value.win_gammon,
+            lose_normal: value.lose_normal,
+            lose_gammon: value.lose_gammon,
+        }
+    }
+}
+
+/// Evaluates positions with the neural nets.
+#[pyclass(frozen, module = "wildbg_engine")]
+struct NeuralNets {
+    evaluator: CompositeEvaluator,
+}
+
+#[pymethods]
+impl NeuralNets {
+    /// Without arguments, the default nets are used which are part of the module.
+    #[new]
+    #[pyo3(signature = (contact_path=None, race_path=None))]
+    fn new(contact_path: Option<&str>, race_path: Option<&str>) -> PyResult<Self> {
+        let evaluator = match (contact_path, race_path) {
+            (None, None) => CompositeEvaluator::try_default(),
+            (Some(contact), Some(race)) => CompositeEvaluator::from_file_paths(contact, race),
+            _ => Err("Either pass both net paths or none.".to_string()),
+        }
+        .map_err(PyValueError::new_err)?;
+        Ok(Self { evaluator })
+    }
+
+    fn eval(&self, position: &Position) -> Probabilities {
+        self.evaluator.eval(&position.position).into()
+    }
+
+    /// Evaluates many positions at once, which is much faster than calling `eval` repeatedly.
+    fn eval_batch(&self, py: Python<'_>, positions: Vec<Position>) -> Vec<Probabilities> {
+        let positions = positions.iter().map(|p| p.position).collect();
+        py.allow_threads(|| self.evaluator.eval_batch(positions))
+            .into_iter()
+            .map(|(_, probabilities)| probabilities.into())
+            .collect()
+    }
+
+    /// The position after the best move by cubeless equity, with switched sides.
+    fn best_position(&self, position: &Position, die1: usize, die2: usize) -> PyResult<Position> {
+        let dice = Dice::try_from((die1, die2)).map_err(PyValueError::new_err)?;
+        Ok(Position {
+            position: self
+                .evaluator
+                .best_position_by_equity(&position.position, &dice),
+        })
+    }
+}
+
+/// Rolls out positions with 1296 games each, using the neural nets for the moves.
+///
+/// Two rollouts with the same `seed` return identical probabilities for the same position.
+#[pyclass(frozen, module = "wildbg_engine")]
+struct Rollout {
+    evaluator: RolloutEvaluator<CompositeEvaluator>,
+}
+
+#[pymethods]
+impl Rollout {
+    #[new]
+    #[pyo3(signature = (seed=None))]
+    fn new(seed: Option<u64>) -> PyResult<Self> {
+        let nets = CompositeEvaluator::try_default().map_err(PyValueError::new_err)?;
+        let evaluator = match seed {
+            Some(seed) => RolloutEvaluator::with_evaluator_and_seed(nets, seed),
+            None => RolloutEvaluator::with_evaluator(nets),
+        };
+        Ok(Self { evaluator })
+    }
+
+    /// Takes several seconds; the games are played in parallel on all cores.
+    fn eval(&self, py: Python<'_>, position: &Position) -> PyResult<Probabilities> {
+        if position.is_game_over() {
+            return Err(PyValueError::new_err("The game is already over."));
+        }
+        let probabilities = py.allow_threads(|| self.evaluator.eval(&position.position));
+        Ok(probabilities.into())
+    }
+}
+
+/// Access to the backgammon engine `wildbg` from Python.
+#[pymodule]
+fn wildbg_engine(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Position>()?;
+    module.add_class::<Probabilities>()?;
+    module.add_class::<NeuralNets>()?;
+    module.add_class::<Rollout>()?;
+    Ok(())
+}
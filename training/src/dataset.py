@@ -8,6 +8,22 @@ class WildBgDataSet(Dataset):
         labels = []
         inputs = []
         for path in csv_files:
+            if path.endswith('.npz'):
+                # Written by `convert-to-npz.rs`, no parsing needed at all.
+                import numpy as np
+                with np.load(path) as arrays:
+                    labels.extend(arrays['targets'].tolist())
+                    inputs.extend(arrays['inputs'].tolist())
+                continue
+            if path.endswith('.parquet'):
+                # Written by `convert-to-parquet.rs`, much faster to load than CSV.
+                import numpy as np
+                import pyarrow.parquet as pq
+                table = pq.read_table(path)
+                rows = np.column_stack([column.to_numpy() for column in table.columns])
+                labels.extend(rows[:, :4].tolist())
+                inputs.extend(rows[:, 4:].tolist())
+                continue
             with open(path, 'r') as f:
                 lines = f.readlines()
                 for line in lines[1:]:
@@ -1,4 +1,5 @@
 from pathlib import Path
+import onnx
 import torch
 from torch import nn
 from torch.utils.data import DataLoader
@@ -6,9 +7,14 @@ from model import Model
 from tiny_model import TinyModel
 from dataset import WildBgDataSet
 
-def save_model(model: nn.Module, path: str, num_inputs: int) -> None:
+# `inputs_version` must match `InputsGen::INPUTS_VERSION` in `crates/engine/src/inputs.rs`, otherwise
+# wildbg refuses to load an uploaded net.
+def save_model(model: nn.Module, path: str, num_inputs: int, inputs_version: str) -> None:
     dummy_input = torch.randn(1, num_inputs, requires_grad=True, device=device)
     torch.onnx.export(model, dummy_input, path)
+    onnx_model = onnx.load(path)
+    onnx.helper.set_model_props(onnx_model, {"wildbg_inputs_version": inputs_version})
+    onnx.save(onnx_model, path)
 
 
 # `path_prefix` should be something like `../training-data/race-` or `../training-data/contact-`
@@ -48,7 +54,7 @@ def train(model: nn.Module, trainloader: DataLoader, path_prefix: str, epochs: i
 
         if epoch_plus_one > epochs * 0.33:
             # Save epochs for each iteration after half the epochs have passed
-            save_model(model, path_prefix + f"{epoch_plus_one:03}" + ".onnx", num_inputs)
+            save_model(model, path_prefix + f"{epoch_plus_one:03}" + ".onnx", num_inputs, inputs_version)
 
 
 def main(model: nn.Module, data_path: str, path_prefix: str, num_inputs: int):
@@ -77,16 +83,19 @@ if __name__ == "__main__":
     match mode:
         case "contact":
             num_inputs = 202
+            inputs_version = "contact-1"
             model = Model(num_inputs).to(device)
             main(model, path + "contact-inputs.csv", path + mode, num_inputs)
         case "race":
             # `Race` has fewer inputs than `Contact`
             num_inputs = 186
+            inputs_version = "race-1"
             model = Model(num_inputs).to(device)
             main(model, path + "race-inputs.csv", path + mode, num_inputs)
         case "tiny_race":
             # This is used to be committed to the repository and not taking up much space.
             num_inputs = 186
+            inputs_version = "race-1"
             model = TinyModel(num_inputs).to(device)
             main(model, path + "race-inputs.csv", path + mode, num_inputs)
         case _: